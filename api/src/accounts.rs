@@ -117,6 +117,7 @@ impl Account {
                 latest_ledger_info.version(),
             ));
         }
+        context.check_state_not_pruned(ledger_version, latest_ledger_info.version())?;
 
         Ok(Self {
             ledger_version,
@@ -2,8 +2,10 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use anyhow::{anyhow, ensure, format_err, Context as AnyhowContext, Result};
-use aptos_api_types::{AsConverter, BlockInfo, Error, LedgerInfo, TransactionOnChainData, U64};
-use aptos_config::config::{NodeConfig, RoleType};
+use aptos_api_types::{
+    AsConverter, BlockInfo, Error, GasEstimation, LedgerInfo, TransactionOnChainData, U64,
+};
+use aptos_config::config::{ApiCorsConfig, ApiRateLimitConfig, NodeConfig, RoleType};
 use aptos_crypto::HashValue;
 use aptos_mempool::{MempoolClientRequest, MempoolClientSender, SubmissionStatus};
 use aptos_state_view::StateView;
@@ -24,14 +26,19 @@ use aptos_vm::data_cache::{IntoMoveResolver, RemoteStorageOwned};
 use futures::{channel::oneshot, SinkExt};
 use move_deps::move_core_types::ident_str;
 use serde::{Deserialize, Serialize};
-use std::{collections::HashMap, convert::Infallible, sync::Arc};
+use std::{
+    collections::HashMap,
+    convert::Infallible,
+    sync::{Arc, Mutex},
+};
 use storage_interface::{
     state_view::{DbStateView, DbStateViewAtVersion, LatestDbStateCheckpointView},
     DbReader, Order,
 };
 use warp::{filters::BoxedFilter, Filter, Reply};
 
-use crate::poem_backend::{AptosErrorCode, InternalError};
+use crate::gas_estimation::GasPriceWindow;
+use crate::poem_backend::{build_version_pruned, AptosErrorCode, InternalError, NotFoundError};
 
 // Context holds application scope context
 #[derive(Clone)]
@@ -40,6 +47,9 @@ pub struct Context {
     pub db: Arc<dyn DbReader>,
     mp_sender: MempoolClientSender,
     node_config: NodeConfig,
+    // Key used to sign pagination cursors, cursors do not survive a restart.
+    cursor_key: HashValue,
+    gas_price_window: Arc<Mutex<GasPriceWindow>>,
 }
 
 impl Context {
@@ -54,6 +64,8 @@ impl Context {
             db,
             mp_sender,
             node_config,
+            cursor_key: HashValue::random(),
+            gas_price_window: Arc::new(Mutex::new(GasPriceWindow::default())),
         }
     }
 
@@ -83,10 +95,48 @@ impl Context {
         self.node_config.base.role
     }
 
+    pub fn cursor_key(&self) -> &HashValue {
+        &self.cursor_key
+    }
+
     pub fn content_length_limit(&self) -> u64 {
         self.node_config.api.content_length_limit()
     }
 
+    pub fn max_transactions_page_size(&self) -> u16 {
+        self.node_config.api.max_transactions_page_size()
+    }
+
+    pub fn max_events_page_size(&self) -> u16 {
+        self.node_config.api.max_events_page_size()
+    }
+
+    pub fn rate_limit_config(&self) -> &ApiRateLimitConfig {
+        &self.node_config.api.rate_limit
+    }
+
+    pub fn cors_config(&self) -> &ApiCorsConfig {
+        &self.node_config.api.cors
+    }
+
+    pub fn legacy_api_sunset(&self) -> Option<&str> {
+        self.node_config.api.legacy_api_sunset.as_deref()
+    }
+
+    /// Estimates gas unit prices from the user transactions in the most recent versions.
+    pub fn estimate_gas_price(
+        &self,
+        ledger_info: &LedgerInfo,
+        min_gas_unit_price: u64,
+    ) -> Result<GasEstimation> {
+        self.gas_price_window.lock().unwrap().estimate(
+            self.db.as_ref(),
+            ledger_info.oldest_ledger_version.0,
+            ledger_info.version(),
+            min_gas_unit_price,
+        )
+    }
+
     pub fn filter(self) -> impl Filter<Extract = (Context,), Error = Infallible> + Clone {
         warp::any().map(move || self.clone())
     }
@@ -134,6 +184,75 @@ impl Context {
         }
     }
 
+    /// The v0 API counterpart of `check_state_not_pruned_poem`.
+    pub fn check_state_not_pruned(&self, version: u64, ledger_version: u64) -> Result<(), Error> {
+        match self.db.get_state_min_readable_version()? {
+            Some(min_readable_version) if version < min_readable_version => Err(
+                Error::version_pruned("State", version, min_readable_version, ledger_version),
+            ),
+            _ => Ok(()),
+        }
+    }
+
+    /// Fails with a 404 and the `VersionPruned` error code if the state at `version` has been
+    /// pruned, which tells clients to ask for a later version, or another node.
+    pub fn check_state_not_pruned_poem<E: NotFoundError + InternalError>(
+        &self,
+        version: u64,
+        ledger_version: u64,
+    ) -> Result<(), E> {
+        let min_readable_version = self
+            .db
+            .get_state_min_readable_version()
+            .map_err(|e| E::internal(e).error_code(AptosErrorCode::ReadFromStorageError))?;
+        match min_readable_version {
+            Some(min_readable_version) if version < min_readable_version => Err(
+                build_version_pruned("State", version, min_readable_version, ledger_version),
+            ),
+            _ => Ok(()),
+        }
+    }
+
+    /// Like `check_state_not_pruned_poem`, for the events emitted at `version`.
+    pub fn check_events_not_pruned_poem<E: NotFoundError + InternalError>(
+        &self,
+        version: u64,
+        ledger_version: u64,
+    ) -> Result<(), E> {
+        let min_readable_version = self
+            .db
+            .get_event_min_readable_version()
+            .map_err(|e| E::internal(e).error_code(AptosErrorCode::ReadFromStorageError))?;
+        match min_readable_version {
+            Some(min_readable_version) if version < min_readable_version => Err(
+                build_version_pruned("Event", version, min_readable_version, ledger_version),
+            ),
+            _ => Ok(()),
+        }
+    }
+
+    /// The oldest version whose events are readable if event `start` of `event_key` has been
+    /// pruned, None if it hasn't. The events are looked up by sequence number, so this is only
+    /// checked once reading them failed: the event was pruned if the stream reaches that far
+    /// while the events are being pruned.
+    pub fn get_event_pruned_min_readable_version(
+        &self,
+        event_key: &EventKey,
+        start: u64,
+    ) -> Result<Option<u64>> {
+        let min_readable_version = match self.db.get_event_min_readable_version()? {
+            Some(min_readable_version) if min_readable_version > 0 => min_readable_version,
+            _ => return Ok(None),
+        };
+        let latest_event = self
+            .db
+            .get_events(event_key, u64::MAX, Order::Descending, 1)?;
+        Ok(latest_event
+            .first()
+            .filter(|event| event.event.sequence_number() >= start)
+            .map(|_| min_readable_version))
+    }
+
     pub fn get_latest_ledger_info_with_signatures(&self) -> Result<LedgerInfoWithSignatures> {
         self.db.get_latest_ledger_info()
     }
@@ -415,6 +534,13 @@ impl Context {
     pub fn health_check_route(&self) -> BoxedFilter<(impl Reply,)> {
         super::health_check::health_check_route(self.db.clone())
     }
+
+    pub fn ready_route(&self) -> BoxedFilter<(impl Reply,)> {
+        super::health_check::ready_route(
+            self.db.clone(),
+            self.node_config.api.readiness_max_staleness_secs(),
+        )
+    }
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -133,12 +133,29 @@ impl Events {
     }
 
     pub fn list(self, page: Page, accept_type: AcceptType) -> Result<impl Reply, Error> {
-        let contract_events = self.context.get_events(
-            &self.key,
-            page.start(0, u64::MAX)?,
-            page.limit()?,
-            self.ledger_info.version(),
-        )?;
+        let start = page.start(0, u64::MAX)?;
+        let contract_events = self
+            .context
+            .get_events(
+                &self.key,
+                start,
+                page.limit(self.context.max_events_page_size())?,
+                self.ledger_info.version(),
+            )
+            .map_err(|e| {
+                match self
+                    .context
+                    .get_event_pruned_min_readable_version(&self.key, start)
+                {
+                    Ok(Some(min_readable_version)) => Error::event_pruned(
+                        aptos_api_types::EventKey::from(self.key),
+                        start,
+                        min_readable_version,
+                        self.ledger_info.version(),
+                    ),
+                    _ => e.into(),
+                }
+            })?;
 
         match accept_type {
             AcceptType::Json => {
@@ -0,0 +1,107 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::Result;
+use aptos_api_types::GasEstimation;
+use aptos_types::transaction::Version;
+use std::collections::VecDeque;
+use storage_interface::DbReader;
+
+/// Number of most recent versions that gas price estimates are derived from.
+const GAS_ESTIMATION_WINDOW: u64 = 1000;
+
+/// The gas unit prices of the user transactions in the most recent versions of the ledger.
+///
+/// The window is kept up to date incrementally: each update only reads the transactions
+/// committed since the previous one, and the estimate is computed once per ledger version.
+#[derive(Default)]
+pub(crate) struct GasPriceWindow {
+    /// The version up to which transactions have been read.
+    latest_version: Option<Version>,
+    /// The versions and gas unit prices of the user transactions in the window, in version order.
+    prices: VecDeque<(Version, u64)>,
+    /// The estimate at `latest_version`, along with the minimum gas unit price it is bounded by.
+    estimation: Option<(u64, GasEstimation)>,
+}
+
+impl GasPriceWindow {
+    /// Returns the estimate at `ledger_version`, reading the transactions committed since the
+    /// last call. Versions older than `oldest_version` are skipped, as they may be pruned.
+    pub fn estimate(
+        &mut self,
+        db: &dyn DbReader,
+        oldest_version: Version,
+        ledger_version: Version,
+        min_gas_unit_price: u64,
+    ) -> Result<GasEstimation> {
+        self.update(db, oldest_version, ledger_version)?;
+        match &self.estimation {
+            Some((min_price, estimation)) if *min_price == min_gas_unit_price => {
+                Ok(estimation.clone())
+            }
+            _ => {
+                let estimation = self.compute(min_gas_unit_price);
+                self.estimation = Some((min_gas_unit_price, estimation.clone()));
+                Ok(estimation)
+            }
+        }
+    }
+
+    fn update(
+        &mut self,
+        db: &dyn DbReader,
+        oldest_version: Version,
+        ledger_version: Version,
+    ) -> Result<()> {
+        if self.latest_version == Some(ledger_version) {
+            return Ok(());
+        }
+        let first_version = ledger_version
+            .saturating_sub(GAS_ESTIMATION_WINDOW - 1)
+            .max(oldest_version);
+        let mut next_version = match self.latest_version {
+            Some(version) if version < ledger_version => (version + 1).max(first_version),
+            // The ledger went backwards, e.g. after a restore, start over.
+            _ => {
+                self.prices.clear();
+                first_version
+            }
+        };
+        while next_version <= ledger_version {
+            let limit = ledger_version - next_version + 1;
+            let txns = db
+                .get_transactions(next_version, limit, ledger_version, false)?
+                .transactions;
+            if txns.is_empty() {
+                break;
+            }
+            for (offset, txn) in txns.iter().enumerate() {
+                if let Ok(user_txn) = txn.as_signed_user_txn() {
+                    self.prices
+                        .push_back((next_version + offset as u64, user_txn.gas_unit_price()));
+                }
+            }
+            next_version += txns.len() as u64;
+        }
+        while matches!(self.prices.front(), Some((version, _)) if *version < first_version) {
+            self.prices.pop_front();
+        }
+        self.latest_version = Some(ledger_version);
+        self.estimation = None;
+        Ok(())
+    }
+
+    /// Nearest rank percentiles of the prices in the window, never below what the chain accepts.
+    fn compute(&self, min_gas_unit_price: u64) -> GasEstimation {
+        let mut prices: Vec<u64> = self.prices.iter().map(|(_, price)| *price).collect();
+        prices.sort_unstable();
+        let percentile = |p: usize| {
+            if prices.is_empty() {
+                return min_gas_unit_price;
+            }
+            let rank = (prices.len() * p + 99) / 100;
+            prices[rank.saturating_sub(1)].max(min_gas_unit_price)
+        };
+        GasEstimation::new(percentile(25), percentile(50), percentile(90))
+    }
+}
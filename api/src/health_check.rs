@@ -1,14 +1,15 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
-use anyhow::{ensure, Result};
+use anyhow::{ensure, Context, Result};
+use aptos_api_types::Error;
 use std::{
     ops::Sub,
     sync::Arc,
     time::{Duration, SystemTime, UNIX_EPOCH},
 };
 use storage_interface::DbReader;
-use warp::{filters::BoxedFilter, reject, Filter, Reply};
+use warp::{filters::BoxedFilter, http::StatusCode, reject, reply, Filter, Reply};
 
 // HealthCheckParams is optional params for different layer's health check.
 // If no param is provided, server return 200 by default to indicate HTTP server is running health.
@@ -50,6 +51,38 @@ async fn health_check(
     Ok(Box::new("aptos-node:ok"))
 }
 
+// Readiness, unlike the health check above, is always checked against the
+// latest ledger info: the node is ready only if its DB can be read and it has
+// committed a ledger info no older than `max_staleness_secs`.
+pub fn ready_route(db: Arc<dyn DbReader>, max_staleness_secs: u64) -> BoxedFilter<(impl Reply,)> {
+    warp::path!("-" / "ready")
+        .and(warp::path::end())
+        .and(warp::any().map(move || db.clone()))
+        .and(warp::any().map(SystemTime::now))
+        .map(move |db: Arc<dyn DbReader>, now: SystemTime| {
+            match check_ready(db, max_staleness_secs, now) {
+                Ok(()) => reply::with_status("aptos-node:ready".to_owned(), StatusCode::OK)
+                    .into_response(),
+                Err(e) => {
+                    let error = Error::new(StatusCode::SERVICE_UNAVAILABLE, format!("{:#}", e));
+                    reply::with_status(reply::json(&error), error.status_code()).into_response()
+                }
+            }
+        })
+        .boxed()
+}
+
+fn check_ready(db: Arc<dyn DbReader>, max_staleness_secs: u64, now: SystemTime) -> Result<()> {
+    let ledger_info = db
+        .get_latest_ledger_info()
+        .context("Failed to read the latest ledger info from the DB")?;
+    let timestamp = ledger_info.ledger_info().timestamp_usecs();
+    check_latest_ledger_info_timestamp(max_staleness_secs, timestamp, now).context(format!(
+        "The latest ledger info is more than {} seconds old",
+        max_staleness_secs
+    ))
+}
+
 pub fn check_latest_ledger_info_timestamp(
     duration_sec: u64,
     timestamp_usecs: u64,
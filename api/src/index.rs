@@ -6,9 +6,8 @@ use crate::{
     context::Context,
     events,
     failpoint::fail_point,
-    log,
     metrics::{metrics, status_metrics},
-    state, transactions,
+    state, stream, transactions,
 };
 use aptos_api_types::{Error, IndexResponse, Response};
 use std::convert::Infallible;
@@ -16,7 +15,7 @@ use warp::{
     body::BodyDeserializeError,
     cors::CorsForbidden,
     filters::BoxedFilter,
-    http::{header, HeaderValue, StatusCode},
+    http::{HeaderValue, StatusCode},
     reject::{LengthRequired, MethodNotAllowed, PayloadTooLarge, UnsupportedMediaType},
     reply, Filter, Rejection, Reply,
 };
@@ -24,7 +23,9 @@ use warp::{
 const OPEN_API_HTML: &str = include_str!("../doc/v0/spec.html");
 const OPEN_API_SPEC: &str = include_str!("../doc/v0/openapi.yaml");
 
-pub fn routes(context: Context) -> impl Filter<Extract = impl Reply, Error = Infallible> + Clone {
+pub fn routes(
+    context: Context,
+) -> impl Filter<Extract = (impl Reply,), Error = Infallible> + Clone {
     index(context.clone())
         .or(openapi_spec())
         .or(accounts::get_account(context.clone()))
@@ -48,18 +49,25 @@ pub fn routes(context: Context) -> impl Filter<Extract = impl Reply, Error = Inf
         .or(state::get_account_resource(context.clone()))
         .or(state::get_account_module(context.clone()))
         .or(state::get_table_item(context.clone()))
+        .or(stream::stream_transactions(context.clone()))
         .or(context.health_check_route().with(metrics("health_check")))
-        .with(
-            warp::cors()
-                .allow_any_origin()
-                .allow_methods(vec!["POST", "GET"])
-                .allow_headers(vec![header::CONTENT_TYPE]),
-        )
+        .or(context.ready_route().with(metrics("ready")))
+        .with(cors(&context))
         .recover(handle_rejection)
-        .with(log::logger())
         .with(status_metrics())
 }
 
+fn cors(context: &Context) -> warp::cors::Builder {
+    let config = context.cors_config();
+    let cors = warp::cors()
+        .allow_methods(config.allowed_methods().iter().map(String::as_str))
+        .allow_headers(config.allowed_headers().iter().map(String::as_str));
+    match &config.allowed_origins {
+        Some(origins) => cors.allow_origins(origins.iter().map(String::as_str)),
+        None => cors.allow_any_origin(),
+    }
+}
+
 // GET /openapi.yaml
 // GET /spec.html
 pub fn openapi_spec() -> BoxedFilter<(impl Reply,)> {
@@ -5,6 +5,7 @@ mod accept_type;
 mod accounts;
 pub mod context;
 mod events;
+mod gas_estimation;
 mod health_check;
 mod index;
 pub mod log;
@@ -12,8 +13,10 @@ pub mod metrics;
 mod page;
 pub mod param;
 mod poem_backend;
+mod rate_limit;
 pub mod runtime;
 mod state;
+mod stream;
 mod transactions;
 pub(crate) mod version;
 
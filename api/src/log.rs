@@ -1,8 +1,13 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
-use std::time::Duration;
+use std::{
+    convert::Infallible,
+    net::SocketAddr,
+    time::{Duration, Instant},
+};
 
+use aptos_crypto::HashValue;
 use aptos_logger::{
     debug, error,
     prelude::{sample, SampleRate},
@@ -10,37 +15,133 @@ use aptos_logger::{
     Schema,
 };
 use warp::{
-    http::header,
-    log::{custom, Info, Log},
+    http::{
+        header::{self, HeaderMap, HeaderValue},
+        Method,
+    },
+    path::FullPath,
+    reply::Response,
+    Filter, Reply,
 };
 
-pub fn logger() -> Log<impl Fn(Info) + Copy> {
-    let func = move |info: Info| {
-        let status = info.status().as_u16();
-        let log = HttpRequestLog {
-            remote_addr: info.remote_addr(),
-            method: info.method().to_string(),
-            path: info.path().to_string(),
-            status,
-            referer: info.referer(),
-            user_agent: info.user_agent(),
-            elapsed: info.elapsed(),
-            forwarded: info
-                .request_headers()
-                .get(header::FORWARDED)
-                .and_then(|v| v.to_str().ok()),
-        };
-        if status >= 500 {
-            sample!(SampleRate::Duration(Duration::from_secs(1)), error!(log));
-        } else {
-            debug!(log);
+/// Header identifying a request across the client, the server logs and the
+/// error response body.
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// Longest request id accepted from a client, longer ones are replaced.
+const MAX_REQUEST_ID_LENGTH: usize = 128;
+
+/// Tags every request with an id, taken from the `X-Request-Id` header if the
+/// client sent a sane one and generated otherwise. The id is echoed in the
+/// response headers, added to JSON error bodies and logged with the request.
+/// The per route latency logs only carry the id if the client sent it, as the
+/// routes see the request before an id is generated. A route that generated
+/// the id itself, e.g. to forward it, echoes it in its response headers and
+/// that id is kept.
+pub fn with_request_id<F, R>(
+    routes: F,
+) -> impl Filter<Extract = (Response,), Error = Infallible> + Clone
+where
+    F: Filter<Extract = (R,), Error = Infallible> + Clone + Send + Sync + 'static,
+    R: Reply,
+{
+    warp::header::headers_cloned()
+        .and(warp::method())
+        .and(warp::path::full())
+        .and(warp::addr::remote())
+        .and(warp::any().map(Instant::now))
+        .and(routes)
+        .and_then(
+            |headers: HeaderMap,
+             method: Method,
+             path: FullPath,
+             remote_addr: Option<SocketAddr>,
+             start: Instant,
+             reply: R| async move {
+                let mut response = reply.into_response();
+                let request_id = match header_str(response.headers(), REQUEST_ID_HEADER) {
+                    Some(request_id) => request_id.to_owned(),
+                    None => {
+                        let request_id = request_id(&headers);
+                        if let Ok(value) = HeaderValue::from_str(&request_id) {
+                            response.headers_mut().insert(REQUEST_ID_HEADER, value);
+                        }
+                        request_id
+                    }
+                };
+                if response.status().is_client_error() || response.status().is_server_error() {
+                    response = add_request_id_to_error(response, &request_id).await;
+                }
+
+                let status = response.status().as_u16();
+                let log = HttpRequestLog {
+                    request_id: Some(&request_id),
+                    remote_addr,
+                    method: method.to_string(),
+                    path: path.as_str().to_string(),
+                    status,
+                    referer: header_str(&headers, header::REFERER),
+                    user_agent: header_str(&headers, header::USER_AGENT),
+                    elapsed: start.elapsed(),
+                    forwarded: header_str(&headers, header::FORWARDED),
+                };
+                if status >= 500 {
+                    sample!(SampleRate::Duration(Duration::from_secs(1)), error!(log));
+                } else {
+                    debug!(log);
+                }
+                Ok::<_, Infallible>(response)
+            },
+        )
+}
+
+/// The id of a request, taken from its `X-Request-Id` header if sane and
+/// generated otherwise.
+pub fn request_id(headers: &HeaderMap) -> String {
+    header_str(headers, REQUEST_ID_HEADER)
+        .filter(|id| {
+            !id.is_empty()
+                && id.len() <= MAX_REQUEST_ID_LENGTH
+                && id.bytes().all(|b| b.is_ascii_graphic())
+        })
+        .map(str::to_owned)
+        .unwrap_or_else(|| HashValue::random().to_hex()[..32].to_owned())
+}
+
+fn header_str<K: header::AsHeaderName>(headers: &HeaderMap, key: K) -> Option<&str> {
+    headers.get(key).and_then(|v| v.to_str().ok())
+}
+
+/// Error bodies are small JSON objects, so they are buffered and rewritten
+/// with the request id. Anything else is passed through untouched.
+async fn add_request_id_to_error(response: Response, request_id: &str) -> Response {
+    let is_json = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .map_or(false, |v| v.starts_with("application/json"));
+    if !is_json {
+        return response;
+    }
+    let (mut parts, body) = response.into_parts();
+    let bytes = match hyper::body::to_bytes(body).await {
+        Ok(bytes) => bytes,
+        Err(_) => return Response::from_parts(parts, hyper::Body::empty()),
+    };
+    let body = match serde_json::from_slice::<serde_json::Value>(&bytes) {
+        Ok(serde_json::Value::Object(mut error)) => {
+            error.insert("request_id".to_owned(), request_id.into());
+            parts.headers.remove(header::CONTENT_LENGTH);
+            serde_json::to_vec(&error).map_or(bytes, Into::into)
         }
+        _ => bytes,
     };
-    custom(func)
+    Response::from_parts(parts, body.into())
 }
 
 #[derive(Schema)]
 pub struct HttpRequestLog<'a> {
+    request_id: Option<&'a str>,
     #[schema(display)]
     remote_addr: Option<std::net::SocketAddr>,
     method: String,
@@ -1,6 +1,8 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
+use crate::log::REQUEST_ID_HEADER;
+use aptos_logger::{debug, Schema};
 use aptos_metrics_core::{register_histogram_vec, HistogramVec};
 
 use once_cell::sync::Lazy;
@@ -24,7 +26,8 @@ pub static RESPONSE_STATUS: Lazy<HistogramVec> = Lazy::new(|| {
     .unwrap()
 });
 
-// Record metrics by method, operation_id and status.
+// Record metrics by method, operation_id and status, and log the latency of
+// the route along with the request id, if the client sent one.
 // The operation_id is the id for the request handler.
 // Should use same `operationId` defined in `openapi.yaml` whenever possible.
 pub fn metrics(operation_id: &'static str) -> Log<impl Fn(Info) + Copy> {
@@ -36,10 +39,28 @@ pub fn metrics(operation_id: &'static str) -> Log<impl Fn(Info) + Copy> {
                 info.status().as_u16().to_string().as_str(),
             ])
             .observe(info.elapsed().as_secs_f64());
+        debug!(RouteLatencyLog {
+            request_id: info
+                .request_headers()
+                .get(REQUEST_ID_HEADER)
+                .and_then(|v| v.to_str().ok()),
+            operation_id,
+            status: info.status().as_u16(),
+            elapsed: info.elapsed(),
+        });
     };
     custom(func)
 }
 
+#[derive(Schema)]
+struct RouteLatencyLog<'a> {
+    request_id: Option<&'a str>,
+    operation_id: &'static str,
+    status: u16,
+    #[schema(debug)]
+    elapsed: std::time::Duration,
+}
+
 // Record metrics by response status.
 // This is for understanding the overview of responses in case server
 // is overloaded by unknown reason.
@@ -10,7 +10,6 @@ use serde::Deserialize;
 use std::num::NonZeroU16;
 
 const DEFAULT_PAGE_SIZE: u16 = 25;
-const MAX_PAGE_SIZE: u16 = 1000;
 
 #[derive(Clone, Debug, Deserialize)]
 pub(crate) struct Page {
@@ -35,17 +34,17 @@ impl Page {
         Ok(version)
     }
 
-    pub fn limit(&self) -> Result<u16, Error> {
+    pub fn limit(&self, max_page_size: u16) -> Result<u16, Error> {
         let limit = self
             .limit
             .clone()
             .map(|v| v.parse("limit"))
             .unwrap_or_else(|| Ok(NonZeroU16::new(DEFAULT_PAGE_SIZE).unwrap()))?
             .get();
-        if limit > MAX_PAGE_SIZE {
+        if limit > max_page_size {
             return Err(Error::invalid_param(
                 "limit",
-                format!("{}, exceed limit {}", limit, MAX_PAGE_SIZE),
+                format!("{}, exceed limit {}", limit, max_page_size),
             ));
         }
         Ok(limit)
@@ -38,7 +38,10 @@ pub struct AccountsApi {
 impl AccountsApi {
     /// Get account
     ///
-    /// Return high level information about an account such as its sequence number.
+    /// Return high level information about an account such as its sequence number
+    /// and authentication key. This is much cheaper than reading all of its
+    /// resources. If the account does not exist at the given ledger version,
+    /// a 404 with the `AccountNotFound` error code is returned.
     #[oai(
         path = "/accounts/:address",
         method = "get",
@@ -133,6 +136,7 @@ impl Account {
                 latest_ledger_info.version(),
             ));
         }
+        context.check_state_not_pruned_poem(ledger_version, latest_ledger_info.version())?;
 
         Ok(Self {
             context,
@@ -156,7 +160,17 @@ impl Account {
 
         let state_value = match state_value {
             Some(state_value) => state_value,
-            None => return Err(self.resource_not_found(&AccountResource::struct_tag())),
+            None => {
+                return Err(build_not_found(
+                    "account",
+                    format!(
+                        "address({}) and ledger version({})",
+                        self.address, self.ledger_version
+                    ),
+                    self.latest_ledger_info.version(),
+                )
+                .error_code(AptosErrorCode::AccountNotFound))
+            }
         };
 
         let account_resource: AccountResource = bcs::from_bytes(&state_value)
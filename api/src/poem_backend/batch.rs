@@ -0,0 +1,206 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use std::convert::TryInto;
+use std::sync::Arc;
+
+use super::accept_type::{parse_accept, AcceptType};
+use super::page::Page;
+use super::{
+    build_not_found, ApiTags, BadRequestError, BasicErrorWith404, BasicResponse,
+    BasicResponseStatus, BasicResultWith404, InternalError,
+};
+use crate::context::Context;
+use crate::failpoint::fail_point_poem;
+use anyhow::{format_err, Context as AnyhowContext, Result};
+use aptos_api_types::{
+    AccountData, AsConverter, BatchReadRequest, EventsRequest, ReadRequest, ReadResult,
+    TransactionId, U64,
+};
+use aptos_state_view::StateView;
+use aptos_types::access_path::AccessPath;
+use aptos_types::account_config::AccountResource;
+use aptos_types::state_store::state_key::StateKey;
+use aptos_vm::data_cache::AsMoveResolver;
+use move_deps::move_core_types::account_address::AccountAddress;
+use move_deps::move_core_types::language_storage::{ResourceKey, StructTag};
+use move_deps::move_core_types::move_resource::MoveStructType;
+use poem::web::Accept;
+use poem_openapi::payload::Json;
+use poem_openapi::OpenApi;
+use serde::Serialize;
+use storage_interface::state_view::DbStateView;
+
+/// Maximum number of reads in a single batch.
+const MAX_BATCH_SIZE: usize = 100;
+
+pub struct BatchApi {
+    pub context: Arc<Context>,
+}
+
+#[OpenApi]
+impl BatchApi {
+    /// Batch read
+    ///
+    /// Serves a list of reads of accounts, account resources and events, all
+    /// at the same ledger version, and returns their results in the same
+    /// order. A read that fails does not fail the batch, its result carries
+    /// an error instead. Only JSON responses are supported.
+    #[oai(
+        path = "/batch",
+        method = "post",
+        operation_id = "batch_read",
+        tag = "ApiTags::General"
+    )]
+    async fn batch_read(
+        &self,
+        accept: Accept,
+        request: Json<BatchReadRequest>,
+    ) -> BasicResultWith404<Vec<ReadResult>> {
+        fail_point_poem("endpoint_batch_read")?;
+        let accept_type = parse_accept(&accept)?;
+        if let AcceptType::Bcs = accept_type {
+            return Err(BasicErrorWith404::bad_request_str(
+                "Batch reads can only be returned as JSON",
+            ));
+        }
+        self.batch_read_inner(&accept_type, request.0)
+    }
+}
+
+impl BatchApi {
+    fn batch_read_inner(
+        &self,
+        accept_type: &AcceptType,
+        request: BatchReadRequest,
+    ) -> BasicResultWith404<Vec<ReadResult>> {
+        if request.requests.len() > MAX_BATCH_SIZE {
+            return Err(BasicErrorWith404::bad_request_str(&format!(
+                "Batch of {} reads is too large, it must be <= {}",
+                request.requests.len(),
+                MAX_BATCH_SIZE
+            )));
+        }
+        let latest_ledger_info = self.context.get_latest_ledger_info_poem()?;
+        let ledger_version = request
+            .ledger_version
+            .map(|v| v.0)
+            .unwrap_or_else(|| latest_ledger_info.version());
+        if ledger_version > latest_ledger_info.version() {
+            return Err(build_not_found(
+                "ledger",
+                TransactionId::Version(U64::from(ledger_version)),
+                latest_ledger_info.version(),
+            ));
+        }
+
+        let state_view = self
+            .context
+            .state_view_at_version(ledger_version)
+            .context("Failed to read state from storage")
+            .map_err(BasicErrorWith404::internal)?;
+        let reader = BatchReader {
+            context: &self.context,
+            ledger_version,
+            state_view,
+        };
+        let results = request
+            .requests
+            .into_iter()
+            .map(|request| match reader.read(request) {
+                Ok(data) => ReadResult::data(data),
+                Err(e) => ReadResult::error(format!("{:#}", e)),
+            })
+            .collect();
+
+        BasicResponse::try_from_rust_value((
+            results,
+            &latest_ledger_info,
+            BasicResponseStatus::Ok,
+            accept_type,
+        ))
+    }
+}
+
+/// Serves every read of a batch from the same state view.
+struct BatchReader<'a> {
+    context: &'a Context,
+    ledger_version: u64,
+    state_view: DbStateView,
+}
+
+impl<'a> BatchReader<'a> {
+    fn read(&self, request: ReadRequest) -> Result<serde_json::Value> {
+        match request {
+            ReadRequest::AccountRequest(request) => {
+                let bytes =
+                    self.resource_bytes(request.address.into(), AccountResource::struct_tag())?;
+                let account_resource: AccountResource = bcs::from_bytes(&bytes)
+                    .context("Failed to deserialize account resource from storage")?;
+                to_json(AccountData::from(account_resource))
+            }
+            ReadRequest::AccountResourcesRequest(request) => {
+                let account_state = self
+                    .context
+                    .get_account_state(request.address.into(), self.ledger_version)?
+                    .ok_or_else(|| format_err!("Account {} not found", request.address))?;
+                let resources = self
+                    .state_view
+                    .as_move_resolver()
+                    .as_converter(self.context.db.clone())
+                    .try_into_resources(account_state.get_resources())
+                    .context("Failed to convert resources from storage")?;
+                to_json(resources)
+            }
+            ReadRequest::AccountResourceRequest(request) => {
+                let resource_type: StructTag = request
+                    .resource_type
+                    .try_into()
+                    .context("Failed to parse given resource type")?;
+                let bytes = self.resource_bytes(request.address.into(), resource_type.clone())?;
+                let resource = self
+                    .state_view
+                    .as_move_resolver()
+                    .as_converter(self.context.db.clone())
+                    .try_into_resource(&resource_type, &bytes)
+                    .context("Failed to convert resource from storage")?;
+                to_json(resource)
+            }
+            ReadRequest::EventsRequest(request) => self.events(request),
+        }
+    }
+
+    fn events(&self, request: EventsRequest) -> Result<serde_json::Value> {
+        let max_page_size = self.context.max_events_page_size();
+        let limit = Page::new(None, request.limit)
+            .limit::<BasicErrorWith404>(max_page_size)
+            .map_err(|_| format_err!("Invalid limit, it must be > 0 and <= {}", max_page_size))?;
+        let contract_events = self.context.get_events(
+            &request.event_key.into(),
+            request.start.map(|v| v.0).unwrap_or(0),
+            limit,
+            self.ledger_version,
+        )?;
+        let events = self
+            .state_view
+            .as_move_resolver()
+            .as_converter(self.context.db.clone())
+            .try_into_events(&contract_events)
+            .context("Failed to convert events from storage")?;
+        to_json(events)
+    }
+
+    fn resource_bytes(&self, address: AccountAddress, resource_type: StructTag) -> Result<Vec<u8>> {
+        let resource_key = ResourceKey::new(address, resource_type);
+        let state_key =
+            StateKey::AccessPath(AccessPath::resource_access_path(resource_key.clone()));
+        self.state_view
+            .get_state_value(&state_key)
+            .context("Failed to read resource from storage")?
+            .ok_or_else(|| format_err!("Resource {:?} not found", resource_key))
+    }
+}
+
+fn to_json<T: Serialize>(value: T) -> Result<serde_json::Value> {
+    serde_json::to_value(value).context("Failed to serialize read result")
+}
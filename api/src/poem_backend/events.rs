@@ -7,14 +7,14 @@ use super::accept_type::{parse_accept, AcceptType};
 use super::accounts::Account;
 use super::page::Page;
 use super::{
-    ApiTags, BadRequestError, BasicErrorWith404, BasicResponse, BasicResponseStatus,
-    BasicResultWith404, InternalError,
+    build_event_pruned, ApiTags, BadRequestError, BasicErrorWith404, BasicResponse,
+    BasicResponseStatus, BasicResultWith404, InternalError,
 };
 use crate::context::Context;
 use crate::failpoint::fail_point_poem;
 use anyhow::Context as AnyhowContext;
 use aptos_api_types::{Address, EventKey, IdentifierWrapper, MoveStructTagWrapper, U64};
-use aptos_api_types::{AsConverter, Event};
+use aptos_api_types::{AsConverter, Event, VersionedEvent};
 use poem::web::Accept;
 use poem_openapi::param::Query;
 use poem_openapi::{param::Path, OpenApi};
@@ -25,6 +25,53 @@ pub struct EventsApi {
 
 #[OpenApi]
 impl EventsApi {
+    fn list_by_version(
+        &self,
+        accept_type: &AcceptType,
+        page: Page,
+    ) -> BasicResultWith404<Vec<VersionedEvent>> {
+        let latest_ledger_info = self.context.get_latest_ledger_info_poem()?;
+        let ledger_version = latest_ledger_info.version();
+        let limit = page.limit(self.context.max_transactions_page_size())?;
+        let start = page.start(ledger_version.saturating_sub(limit as u64), ledger_version)?;
+        self.context
+            .check_events_not_pruned_poem(start, ledger_version)?;
+
+        let data = self
+            .context
+            .get_transactions(start, limit, ledger_version)
+            .context(format!(
+                "Failed to read transactions from version {}",
+                start
+            ))
+            .map_err(BasicErrorWith404::internal)?;
+
+        let resolver = self.context.move_resolver_poem()?;
+        let converter = resolver.as_converter(self.context.db.clone());
+        let mut events = vec![];
+        for txn in data {
+            let version = txn.version;
+            events.extend(
+                converter
+                    .try_into_events(&txn.events)
+                    .context("Failed to convert events from storage into response")
+                    .map_err(BasicErrorWith404::internal)?
+                    .into_iter()
+                    .map(|event| VersionedEvent {
+                        version: version.into(),
+                        event,
+                    }),
+            );
+        }
+
+        BasicResponse::try_from_rust_value((
+            events,
+            &latest_ledger_info,
+            BasicResponseStatus::Ok,
+            accept_type,
+        ))
+    }
+
     /// Get events by event key
     ///
     /// todo
@@ -49,6 +96,31 @@ impl EventsApi {
         self.list(&accept_type, page, event_key.0)
     }
 
+    /// Get events by version
+    ///
+    /// Returns every event emitted by the transactions in a range of
+    /// versions, across all accounts, in the order they were emitted. `limit`
+    /// is the number of versions to read, not the number of events, so a page
+    /// may hold any number of events. If `start` is not given, the latest
+    /// `limit` versions are read.
+    #[oai(
+        path = "/events/by_version",
+        method = "get",
+        operation_id = "get_events_by_version",
+        tag = "ApiTags::Events"
+    )]
+    async fn get_events_by_version(
+        &self,
+        accept: Accept,
+        start: Query<Option<U64>>,
+        limit: Query<Option<u16>>,
+    ) -> BasicResultWith404<Vec<VersionedEvent>> {
+        fail_point_poem("endpoint_get_events_by_version")?;
+        let accept_type = parse_accept(&accept)?;
+        let page = Page::new(start.0.map(|v| v.0), limit.0);
+        self.list_by_version(&accept_type, page)
+    }
+
     /// Get events by event handle
     ///
     /// This API extracts event key from the account resource identified
@@ -89,18 +161,33 @@ impl EventsApi {
         event_key: EventKey,
     ) -> BasicResultWith404<Vec<Event>> {
         let latest_ledger_info = self.context.get_latest_ledger_info_poem()?;
+        let start = page.start(0, u64::MAX)?;
         let contract_events = self
             .context
             .get_events(
                 &event_key.into(),
-                page.start(0, u64::MAX)?,
-                page.limit()?,
+                start,
+                page.limit(self.context.max_events_page_size())?,
                 latest_ledger_info.version(),
             )
-            // TODO: Previously this was a 500, but I'm making this a 400. I suspect
-            // both could be true depending on the error. Make this more specific.
-            .context(format!("Failed to find events by key {}", event_key))
-            .map_err(BasicErrorWith404::bad_request)?;
+            .map_err(|e| {
+                match self
+                    .context
+                    .get_event_pruned_min_readable_version(&event_key.into(), start)
+                {
+                    Ok(Some(min_readable_version)) => build_event_pruned(
+                        event_key,
+                        start,
+                        min_readable_version,
+                        latest_ledger_info.version(),
+                    ),
+                    // TODO: Previously this was a 500, but I'm making this a 400. I suspect
+                    // both could be true depending on the error. Make this more specific.
+                    _ => BasicErrorWith404::bad_request(
+                        e.context(format!("Failed to find events by key {}", event_key)),
+                    ),
+                }
+            })?;
 
         let resolver = self.context.move_resolver_poem()?;
         let events = resolver
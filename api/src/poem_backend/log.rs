@@ -3,7 +3,7 @@
 
 use std::time::Duration;
 
-use crate::metrics::RESPONSE_STATUS;
+use crate::{log::REQUEST_ID_HEADER, metrics::RESPONSE_STATUS};
 use aptos_logger::{
     debug, error,
     prelude::{sample, SampleRate},
@@ -19,6 +19,10 @@ pub async fn middleware_log<E: Endpoint>(next: E, request: Request) -> Result<Re
     let start = std::time::Instant::now();
 
     let mut log = HttpRequestLog {
+        request_id: request
+            .headers()
+            .get(REQUEST_ID_HEADER)
+            .and_then(|v| v.to_str().ok().map(|v| v.to_string())),
         remote_addr: request.remote_addr().as_socket_addr().cloned(),
         method: request.method().to_string(),
         path: request.uri().path().to_string(),
@@ -62,6 +66,7 @@ pub async fn middleware_log<E: Endpoint>(next: E, request: Request) -> Result<Re
 // original implementation.
 #[derive(Schema)]
 pub struct HttpRequestLog {
+    request_id: Option<String>,
     #[schema(display)]
     remote_addr: Option<std::net::SocketAddr>,
     method: String,
@@ -6,6 +6,7 @@ use poem_openapi::Tags;
 mod accept_type;
 mod accounts;
 mod basic;
+mod batch;
 mod bcs_payload;
 mod check_size;
 mod error_converter;
@@ -13,6 +14,7 @@ mod events;
 mod index;
 mod log;
 mod page;
+mod proofs;
 mod response;
 mod runtime;
 mod state;
@@ -29,6 +31,9 @@ pub enum ApiTags {
     /// General information
     General,
 
+    /// Access to proofs for verifying clients
+    Proofs,
+
     /// Access to tables
     Tables,
 
@@ -39,10 +44,13 @@ pub enum ApiTags {
 pub use accept_type::AcceptType;
 pub use accounts::AccountsApi;
 pub use basic::BasicApi;
+pub use batch::BatchApi;
 pub use events::EventsApi;
 pub use index::IndexApi;
 pub use log::middleware_log;
+pub use proofs::ProofsApi;
 pub use response::*;
 pub use runtime::attach_poem_to_runtime;
 pub use state::StateApi;
+pub(crate) use transactions::without_changes;
 pub use transactions::TransactionsApi;
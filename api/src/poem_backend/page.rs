@@ -2,20 +2,103 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use super::{AptosErrorCode, BadRequestError};
-use serde::Deserialize;
+use anyhow::{bail, ensure, Context as AnyhowContext};
+use aptos_crypto::HashValue;
+use poem_openapi::Enum;
+use serde::{Deserialize, Serialize};
 
 const DEFAULT_PAGE_SIZE: u16 = 25;
-const MAX_PAGE_SIZE: u16 = 1000;
+
+/// Number of bytes of the keyed digest appended to an encoded cursor.
+const CURSOR_MAC_LENGTH: usize = 16;
+
+/// The direction in which a page walks the ledger.
+#[derive(Clone, Copy, Debug, Deserialize, Enum, Eq, PartialEq, Serialize)]
+#[oai(rename_all = "lowercase")]
+pub enum PageOrder {
+    Asc,
+    Desc,
+}
+
+impl Default for PageOrder {
+    fn default() -> Self {
+        PageOrder::Asc
+    }
+}
+
+/// A cursor pins a paginated walk to the ledger version the first page was
+/// served at, so subsequent pages never observe the ledger advancing. It is
+/// handed to clients as an opaque, tamper evident hex string, see `encode`.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Cursor {
+    /// The version the next page starts at.
+    pub version: u64,
+    /// The ledger version the walk is pinned to.
+    pub ledger_version: u64,
+    pub order: PageOrder,
+}
+
+impl Cursor {
+    pub fn new(version: u64, ledger_version: u64, order: PageOrder) -> Self {
+        Self {
+            version,
+            ledger_version,
+            order,
+        }
+    }
+
+    /// Encodes the cursor as hex(bcs(cursor) || mac), where the mac is a
+    /// truncated sha3 of the key followed by the BCS bytes. The key never
+    /// leaves the node, so clients cannot forge or alter a cursor.
+    pub fn encode(&self, key: &HashValue) -> String {
+        let mut bytes = bcs::to_bytes(self).expect("Cursor serialization cannot fail");
+        let mac = Self::mac(key, &bytes);
+        bytes.extend_from_slice(&mac);
+        hex::encode(bytes)
+    }
+
+    pub fn decode(encoded: &str, key: &HashValue) -> anyhow::Result<Self> {
+        let bytes = hex::decode(encoded).context("Cursor is not valid hex")?;
+        ensure!(bytes.len() > CURSOR_MAC_LENGTH, "Cursor is too short");
+        let (payload, mac) = bytes.split_at(bytes.len() - CURSOR_MAC_LENGTH);
+        if Self::mac(key, payload)[..] != mac[..] {
+            bail!("Cursor was not issued by this node");
+        }
+        bcs::from_bytes(payload).context("Cursor payload is malformed")
+    }
+
+    fn mac(key: &HashValue, payload: &[u8]) -> [u8; CURSOR_MAC_LENGTH] {
+        let mut buffer = key.to_vec();
+        buffer.extend_from_slice(payload);
+        let digest = HashValue::sha3_256_of(&buffer);
+        let mut mac = [0u8; CURSOR_MAC_LENGTH];
+        mac.copy_from_slice(&digest.to_vec()[..CURSOR_MAC_LENGTH]);
+        mac
+    }
+}
 
 #[derive(Clone, Debug, Deserialize)]
 pub(crate) struct Page {
     start: Option<u64>,
     limit: Option<u16>,
+    cursor: Option<String>,
+    order: Option<PageOrder>,
 }
 
 impl Page {
     pub fn new(start: Option<u64>, limit: Option<u16>) -> Self {
-        Self { start, limit }
+        Self {
+            start,
+            limit,
+            cursor: None,
+            order: None,
+        }
+    }
+
+    pub fn with_cursor(mut self, cursor: Option<String>, order: Option<PageOrder>) -> Self {
+        self.cursor = cursor;
+        self.order = order;
+        self
     }
 
     pub fn start<E: BadRequestError>(&self, default: u64, max: u64) -> Result<u64, E> {
@@ -30,7 +113,7 @@ impl Page {
         Ok(start)
     }
 
-    pub fn limit<E: BadRequestError>(&self) -> Result<u16, E> {
+    pub fn limit<E: BadRequestError>(&self, max_page_size: u16) -> Result<u16, E> {
         let limit = self.limit.unwrap_or(DEFAULT_PAGE_SIZE);
         if limit == 0 {
             return Err(E::bad_request_str(&format!(
@@ -39,13 +122,66 @@ impl Page {
             ))
             .error_code(AptosErrorCode::InvalidLimitParam));
         }
-        if limit > MAX_PAGE_SIZE {
+        if limit > max_page_size {
             return Err(E::bad_request_str(&format!(
                 "Given limit value ({}) is too large, it must be < {}",
-                limit, MAX_PAGE_SIZE
+                limit, max_page_size
             ))
             .error_code(AptosErrorCode::InvalidLimitParam));
         }
         Ok(limit)
     }
+
+    pub fn order(&self) -> PageOrder {
+        self.order.unwrap_or_default()
+    }
+
+    /// Decodes the cursor, if one was given. A cursor already carries its own
+    /// start and order, so it cannot be combined with either.
+    pub fn cursor<E: BadRequestError>(&self, key: &HashValue) -> Result<Option<Cursor>, E> {
+        let encoded = match &self.cursor {
+            Some(encoded) => encoded,
+            None => return Ok(None),
+        };
+        if self.start.is_some() || self.order.is_some() {
+            return Err(E::bad_request_str(
+                "The cursor param cannot be combined with the start or order params",
+            )
+            .error_code(AptosErrorCode::InvalidCursorParam));
+        }
+        Cursor::decode(encoded, key)
+            .context("Given cursor is invalid")
+            .map(Some)
+            .map_err(|e| E::bad_request(e).error_code(AptosErrorCode::InvalidCursorParam))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cursor_round_trip() {
+        let key = HashValue::random();
+        let cursor = Cursor::new(42, 100, PageOrder::Desc);
+        let encoded = cursor.encode(&key);
+        assert_eq!(Cursor::decode(&encoded, &key).unwrap(), cursor);
+    }
+
+    #[test]
+    fn test_cursor_rejects_other_key() {
+        let cursor = Cursor::new(42, 100, PageOrder::Asc);
+        let encoded = cursor.encode(&HashValue::random());
+        assert!(Cursor::decode(&encoded, &HashValue::random()).is_err());
+    }
+
+    #[test]
+    fn test_cursor_rejects_tampering() {
+        let key = HashValue::random();
+        let mut bytes = hex::decode(Cursor::new(42, 100, PageOrder::Asc).encode(&key)).unwrap();
+        bytes[0] ^= 1;
+        assert!(Cursor::decode(&hex::encode(bytes), &key).is_err());
+        assert!(Cursor::decode("not hex", &key).is_err());
+        assert!(Cursor::decode("00", &key).is_err());
+    }
 }
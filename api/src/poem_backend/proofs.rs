@@ -0,0 +1,239 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+//! Endpoints that serve the data a verifying client needs to authenticate
+//! what the rest of the API returns. These types have no JSON representation,
+//! so each endpoint returns BCS, or the same BCS bytes hex encoded if the
+//! client asks for JSON.
+
+use std::convert::TryInto;
+use std::sync::Arc;
+
+use super::accept_type::{parse_accept, AcceptType};
+use super::bcs_payload::Bcs;
+use super::{
+    ApiTags, AptosErrorCode, AptosErrorResponse, BadRequestError, BasicErrorWith404, BasicResponse,
+    BasicResponseStatus, BasicResultWith404, InternalError, NotFoundError,
+};
+use crate::context::Context;
+use crate::failpoint::fail_point_poem;
+use anyhow::Context as AnyhowContext;
+use aptos_api_types::{
+    Address, HexEncodedBytes, LedgerInfo, MoveStructTag, MoveStructTagWrapper, U64,
+};
+use aptos_types::access_path::AccessPath;
+use aptos_types::ledger_info::LedgerInfoWithSignatures;
+use aptos_types::state_store::state_key::StateKey;
+use aptos_types::state_store::state_value::StateValueWithProof;
+use move_deps::move_core_types::language_storage::{ResourceKey, StructTag};
+use poem::web::Accept;
+use poem_openapi::param::{Path, Query};
+use poem_openapi::payload::Json;
+use poem_openapi::OpenApi;
+use serde::Serialize;
+
+pub struct ProofsApi {
+    pub context: Arc<Context>,
+}
+
+#[OpenApi]
+impl ProofsApi {
+    /// Get ledger info with signatures
+    ///
+    /// Returns the latest LedgerInfoWithSignatures as BCS. This is the root
+    /// of trust that every other proof is verified against.
+    #[oai(
+        path = "/ledger_info",
+        method = "get",
+        operation_id = "get_ledger_info_with_signatures",
+        tag = "ApiTags::Proofs"
+    )]
+    async fn get_ledger_info_with_signatures(
+        &self,
+        accept: Accept,
+    ) -> BasicResultWith404<HexEncodedBytes> {
+        fail_point_poem("endpoint_get_ledger_info_with_signatures")?;
+        let accept_type = parse_accept(&accept)?;
+        let (ledger_info_with_sigs, ledger_info) = self.ledger_info()?;
+        render_bcs(&ledger_info_with_sigs, &ledger_info, &accept_type)
+    }
+
+    /// Get epoch change proof
+    ///
+    /// Returns an EpochChangeProof as BCS, containing the epoch ending ledger
+    /// infos from `start_epoch` (inclusive) to `end_epoch` (exclusive). If
+    /// `end_epoch` is not given, it defaults to the current epoch. The number
+    /// of ledger infos returned is capped by the node, check `more` in the
+    /// proof and request the rest from the last epoch returned.
+    #[oai(
+        path = "/epochs/proof",
+        method = "get",
+        operation_id = "get_epoch_change_proof",
+        tag = "ApiTags::Proofs"
+    )]
+    async fn get_epoch_change_proof(
+        &self,
+        accept: Accept,
+        start_epoch: Query<U64>,
+        end_epoch: Query<Option<U64>>,
+    ) -> BasicResultWith404<HexEncodedBytes> {
+        fail_point_poem("endpoint_get_epoch_change_proof")?;
+        let accept_type = parse_accept(&accept)?;
+        self.epoch_change_proof(&accept_type, start_epoch.0 .0, end_epoch.0.map(|v| v.0))
+    }
+
+    /// Get account resource with proof
+    ///
+    /// Returns the latest LedgerInfoWithSignatures and a StateValueWithProof
+    /// for the given resource, as a BCS encoded tuple. The proof holds even if
+    /// the resource does not exist. The state is read at `ledger_version`,
+    /// which must be a state checkpoint, and defaults to the latest one.
+    #[oai(
+        path = "/accounts/:address/resource/:resource_type/proof",
+        method = "get",
+        operation_id = "get_account_resource_with_proof",
+        tag = "ApiTags::Proofs"
+    )]
+    async fn get_account_resource_with_proof(
+        &self,
+        accept: Accept,
+        address: Path<Address>,
+        resource_type: Path<MoveStructTagWrapper>,
+        ledger_version: Query<Option<U64>>,
+    ) -> BasicResultWith404<HexEncodedBytes> {
+        fail_point_poem("endpoint_get_account_resource_with_proof")?;
+        let accept_type = parse_accept(&accept)?;
+        let resource_type: MoveStructTag = resource_type.0.into();
+        let resource_type: StructTag = resource_type
+            .try_into()
+            .context("Failed to parse given resource type")
+            .map_err(BasicErrorWith404::bad_request)?;
+        let resource_key = ResourceKey::new(address.0.into(), resource_type);
+        let state_key = StateKey::AccessPath(AccessPath::resource_access_path(resource_key));
+        self.state_value_with_proof(&accept_type, state_key, ledger_version.0.map(|v| v.0))
+    }
+}
+
+impl ProofsApi {
+    /// Reads the latest ledger info once, so the signed ledger info in the
+    /// body and the one described by the headers are always the same.
+    fn ledger_info<E: InternalError>(&self) -> Result<(LedgerInfoWithSignatures, LedgerInfo), E> {
+        let ledger_info_with_sigs = self
+            .context
+            .get_latest_ledger_info_with_signatures()
+            .context("Failed to read latest ledger info from storage")
+            .map_err(|e| E::internal(e).error_code(AptosErrorCode::ReadFromStorageError))?;
+        let oldest_version = self
+            .context
+            .db
+            .get_first_txn_version()
+            .context("Failed to read oldest version from storage")
+            .map_err(|e| E::internal(e).error_code(AptosErrorCode::ReadFromStorageError))?
+            .unwrap_or_default();
+        let ledger_info = LedgerInfo::new(
+            &self.context.chain_id(),
+            &ledger_info_with_sigs,
+            oldest_version,
+        );
+        Ok((ledger_info_with_sigs, ledger_info))
+    }
+
+    fn epoch_change_proof(
+        &self,
+        accept_type: &AcceptType,
+        start_epoch: u64,
+        end_epoch: Option<u64>,
+    ) -> BasicResultWith404<HexEncodedBytes> {
+        let (_, ledger_info) = self.ledger_info()?;
+        let current_epoch = ledger_info.epoch();
+        let end_epoch = end_epoch.unwrap_or(current_epoch);
+        if start_epoch > end_epoch || end_epoch > current_epoch {
+            return Err(BasicErrorWith404::bad_request_str(&format!(
+                "Invalid epoch range [{}, {}), the current epoch is {}",
+                start_epoch, end_epoch, current_epoch
+            )));
+        }
+
+        let proof = self
+            .context
+            .db
+            .get_epoch_ending_ledger_infos(start_epoch, end_epoch)
+            .context("Failed to read epoch ending ledger infos from storage")
+            .map_err(|e| {
+                BasicErrorWith404::internal(e).error_code(AptosErrorCode::ReadFromStorageError)
+            })?;
+        render_bcs(&proof, &ledger_info, accept_type)
+    }
+
+    fn state_value_with_proof(
+        &self,
+        accept_type: &AcceptType,
+        state_key: StateKey,
+        version: Option<u64>,
+    ) -> BasicResultWith404<HexEncodedBytes> {
+        let (ledger_info_with_sigs, ledger_info) = self.ledger_info()?;
+        let version = match version {
+            Some(version) => version,
+            None => self
+                .context
+                .db
+                .get_latest_state_checkpoint_version()
+                .context("Failed to read latest state checkpoint version from storage")
+                .map_err(BasicErrorWith404::internal)?
+                .ok_or_else(|| {
+                    BasicErrorWith404::internal_str("There is no state checkpoint yet")
+                })?,
+        };
+        if version > ledger_info.version() {
+            return Err(BasicErrorWith404::not_found_str(&format!(
+                "Ledger version {} is higher than the current ledger version {}",
+                version,
+                ledger_info.version()
+            ))
+            .aptos_ledger_version(ledger_info.version()));
+        }
+
+        let (value, proof) = self
+            .context
+            .db
+            .get_state_value_with_proof_by_version(&state_key, version)
+            .context(format!(
+                "Failed to read state with proof at version {}, is it a state checkpoint?",
+                version
+            ))
+            .map_err(BasicErrorWith404::bad_request)?;
+        let transaction_info_with_proof = self
+            .context
+            .db
+            .get_transaction_by_version(version, ledger_info.version(), false)
+            .context("Failed to read transaction info with proof from storage")
+            .map_err(BasicErrorWith404::internal)?
+            .proof;
+
+        let state_value_with_proof =
+            StateValueWithProof::new(version, value, proof, transaction_info_with_proof);
+        render_bcs(
+            &(ledger_info_with_sigs, state_value_with_proof),
+            &ledger_info,
+            accept_type,
+        )
+    }
+}
+
+/// Returns the BCS of `value` as is, or hex encoded if JSON was requested.
+fn render_bcs<T: Serialize, E: InternalError>(
+    value: &T,
+    ledger_info: &LedgerInfo,
+    accept_type: &AcceptType,
+) -> Result<BasicResponse<HexEncodedBytes>, E> {
+    let bytes = bcs::to_bytes(value)
+        .map_err(|e| E::internal(e.into()).error_code(AptosErrorCode::BcsSerializationError))?;
+    Ok(match accept_type {
+        AcceptType::Bcs => BasicResponse::from((Bcs(bytes), ledger_info, BasicResponseStatus::Ok)),
+        AcceptType::Json => BasicResponse::from((
+            Json(HexEncodedBytes::from(bytes)),
+            ledger_info,
+            BasicResponseStatus::Ok,
+        )),
+    })
+}
@@ -94,6 +94,16 @@ pub enum AptosErrorCode {
 
     /// The limit param given for paging is invalid.
     InvalidLimitParam = 5,
+
+    /// The cursor param given for paging is invalid.
+    InvalidCursorParam = 6,
+
+    /// The requested account does not exist at the given ledger version.
+    AccountNotFound = 7,
+
+    /// The requested version has been pruned from the node, the error message
+    /// says the oldest version it still has.
+    VersionPruned = 8,
 }
 
 #[derive(ResponseContent)]
@@ -241,6 +251,9 @@ macro_rules! generate_success_response {
                 #[oai(header = "X-Aptos-Ledger-Oldest-Version")] u64,
                 #[oai(header = "X-Aptos-Ledger-TimestampUsec")] u64,
                 #[oai(header = "X-Aptos-Epoch")] u64,
+                // An opaque cursor for fetching the next page, only set by
+                // paginated endpoints when there is a next page.
+                #[oai(header = "X-Aptos-Cursor")] Option<String>,
             ),
             )*
         }
@@ -278,6 +291,7 @@ macro_rules! generate_success_response {
                             ledger_info.oldest_ledger_version.into(),
                             ledger_info.ledger_timestamp.into(),
                             ledger_info.epoch.into(),
+                            None,
                         )
                     },
                     )*
@@ -285,6 +299,20 @@ macro_rules! generate_success_response {
             }
         }
 
+        // Generate a helper that attaches the cursor for the next page to
+        // the response, whichever status it is.
+        impl<T: poem_openapi::types::ToJSON + Send + Sync> $enum_name<T> {
+            pub fn with_cursor(self, cursor: Option<String>) -> Self {
+                match self {
+                    $(
+                    $enum_name::$name(value, chain_id, ledger_version, oldest_ledger_version, timestamp, epoch, _) => {
+                        $enum_name::$name(value, chain_id, ledger_version, oldest_ledger_version, timestamp, epoch, cursor)
+                    },
+                    )*
+                }
+            }
+        }
+
         // Generate a From impl that builds a response from a Json<T> and friends.
         impl<T: poem_openapi::types::ToJSON + Send + Sync> From<(poem_openapi::payload::Json<T>, &aptos_api_types::LedgerInfo, [<$enum_name Status>])>
             for $enum_name<T>
@@ -384,3 +412,33 @@ pub fn build_not_found<S: Display, E: NotFoundError>(
     E::not_found_str(&format!("{} not found by {}", resource, identifier))
         .aptos_ledger_version(ledger_version)
 }
+
+// And this one for data at a version that has been pruned from the node.
+pub fn build_version_pruned<E: NotFoundError>(
+    resource: &str,
+    version: u64,
+    min_readable_version: u64,
+    ledger_version: u64,
+) -> E {
+    E::not_found_str(&format!(
+        "{} at version {} has been pruned, the oldest version available is {}",
+        resource, version, min_readable_version
+    ))
+    .aptos_ledger_version(ledger_version)
+    .error_code(AptosErrorCode::VersionPruned)
+}
+
+// Like `build_version_pruned`, for the events looked up by sequence number.
+pub fn build_event_pruned<K: Display, E: NotFoundError>(
+    event_key: K,
+    sequence_number: u64,
+    min_readable_version: u64,
+    ledger_version: u64,
+) -> E {
+    E::not_found_str(&format!(
+        "Event {} of {} has been pruned, the oldest version available is {}",
+        sequence_number, event_key, min_readable_version
+    ))
+    .aptos_ledger_version(ledger_version)
+    .error_code(AptosErrorCode::VersionPruned)
+}
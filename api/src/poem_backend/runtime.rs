@@ -3,19 +3,19 @@
 
 use std::{net::SocketAddr, sync::Arc};
 
-use super::{middleware_log, AccountsApi, BasicApi, EventsApi, IndexApi};
+use super::{middleware_log, AccountsApi, BasicApi, BatchApi, EventsApi, IndexApi};
 
 use crate::{
     context::Context,
     poem_backend::{
-        check_size::PostSizeLimit, error_converter::convert_error, StateApi, TransactionsApi,
+        check_size::PostSizeLimit, error_converter::convert_error, ProofsApi, StateApi,
+        TransactionsApi,
     },
 };
 use anyhow::Context as AnyhowContext;
 use aptos_config::config::NodeConfig;
 use aptos_logger::info;
 use poem::{
-    http::{header, Method},
     listener::{Listener, RustlsCertificate, RustlsConfig, TcpListener},
     middleware::Cors,
     EndpointExt, Route, Server,
@@ -38,6 +38,7 @@ pub fn attach_poem_to_runtime(
     let context = Arc::new(context);
 
     let size_limit = context.content_length_limit();
+    let cors_config = context.cors_config().clone();
 
     let apis = (
         AccountsApi {
@@ -46,12 +47,18 @@ pub fn attach_poem_to_runtime(
         BasicApi {
             context: context.clone(),
         },
+        BatchApi {
+            context: context.clone(),
+        },
         EventsApi {
             context: context.clone(),
         },
         IndexApi {
             context: context.clone(),
         },
+        ProofsApi {
+            context: context.clone(),
+        },
         StateApi {
             context: context.clone(),
         },
@@ -114,8 +121,12 @@ pub fn attach_poem_to_runtime(
         .context("Failed to get socket addr from local addr for Poem webserver")?;
     runtime_handle.spawn(async move {
         let cors = Cors::new()
-            .allow_methods(vec![Method::GET, Method::POST])
-            .allow_headers(vec![header::CONTENT_TYPE, header::ACCEPT]);
+            .allow_methods(cors_config.allowed_methods().iter().map(String::as_str))
+            .allow_headers(cors_config.allowed_headers().iter().map(String::as_str));
+        let cors = match &cors_config.allowed_origins {
+            Some(origins) => cors.allow_origins(origins.iter().map(String::as_str)),
+            None => cors,
+        };
         let route = Route::new()
             .nest("/", api_service)
             .at("/spec.json", spec_json)
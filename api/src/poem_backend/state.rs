@@ -2,6 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use super::accept_type::{parse_accept, AcceptType};
+use super::bcs_payload::Bcs;
 use super::{
     build_not_found, ApiTags, BadRequestError, BasicResponse, BasicResponseStatus, InternalError,
     NotFoundError,
@@ -11,8 +12,9 @@ use crate::context::Context;
 use crate::failpoint::fail_point_poem;
 use anyhow::Context as AnyhowContext;
 use aptos_api_types::{
-    Address, AsConverter, IdentifierWrapper, MoveModuleBytecode, MoveStructTag,
-    MoveStructTagWrapper, MoveValue, TableItemRequest, TransactionId, U128, U64,
+    Address, AsConverter, HexEncodedBytes, IdentifierWrapper, MoveModuleBytecode, MoveStructTag,
+    MoveStructTagWrapper, MoveValue, RawTableItemRequest, TableItemRequest, TransactionId, U128,
+    U64,
 };
 use aptos_api_types::{LedgerInfo, MoveResource};
 use aptos_state_view::StateView;
@@ -24,11 +26,21 @@ use move_deps::move_core_types::language_storage::{ModuleId, ResourceKey, Struct
 use poem::web::Accept;
 use poem_openapi::param::Query;
 use poem_openapi::payload::Json;
-use poem_openapi::{param::Path, OpenApi};
+use poem_openapi::{param::Path, ApiRequest, OpenApi};
 use std::convert::TryInto;
 use std::sync::Arc;
 use storage_interface::state_view::DbStateView;
 
+// The key of a table item can be given either as JSON or as raw BCS bytes.
+#[derive(ApiRequest, Debug)]
+pub enum TableItemPost {
+    #[oai(content_type = "application/json")]
+    Json(Json<TableItemRequest>),
+
+    #[oai(content_type = "application/x.aptos.table_item_request+bcs")]
+    Bcs(Bcs),
+}
+
 pub struct StateApi {
     pub context: Arc<Context>,
 }
@@ -91,7 +103,10 @@ impl StateApi {
 
     /// Get table item
     ///
-    /// todo
+    /// Returns the item stored under the given key in the table with the
+    /// given handle, decoded as `value_type`. The key is either given as JSON,
+    /// in the form the API returns Move values in, or as a BCS encoded
+    /// RawTableItemRequest holding the raw BCS bytes of the key.
     #[oai(
         path = "/tables/:table_handle/item",
         method = "post",
@@ -102,7 +117,7 @@ impl StateApi {
         &self,
         accept: Accept,
         table_handle: Path<U128>,
-        table_item_request: Json<TableItemRequest>,
+        table_item_request: TableItemPost,
         ledger_version: Query<Option<U64>>,
     ) -> BasicResultWith404<MoveValue> {
         fail_point_poem("endpoint_get_table_item")?;
@@ -110,7 +125,7 @@ impl StateApi {
         self.table_item(
             &accept_type,
             table_handle.0,
-            table_item_request.0,
+            table_item_request,
             ledger_version.0,
         )
     }
@@ -133,6 +148,8 @@ impl StateApi {
                 latest_ledger_info.version(),
             ));
         }
+        self.context
+            .check_state_not_pruned_poem(ledger_version, latest_ledger_info.version())?;
 
         let state_view = self.context.state_view_at_version(ledger_version)
             .context(format!("Failed to get state view at version {} even after confirming the ledger has advanced past that version to {}", ledger_version, latest_ledger_info.version()))
@@ -212,34 +229,50 @@ impl StateApi {
         &self,
         accept_type: &AcceptType,
         table_handle: U128,
-        table_item_request: TableItemRequest,
+        table_item_request: TableItemPost,
         ledger_version: Option<U64>,
     ) -> BasicResultWith404<MoveValue> {
-        let key_type = table_item_request
-            .key_type
-            .try_into()
-            .context("Failed to parse key_type")
-            .map_err(BasicErrorWith404::bad_request)?;
-        let value_type = table_item_request
-            .value_type
-            .try_into()
-            .context("Failed to parse value_type")
-            .map_err(BasicErrorWith404::bad_request)?;
-        let key = table_item_request.key;
-
         let (ledger_info, ledger_version, state_view) = self.preprocess_request(ledger_version)?;
 
         let resolver = state_view.as_move_resolver();
         let converter = resolver.as_converter(self.context.db.clone());
 
-        let vm_key = converter
-            .try_into_vm_value(&key_type, key.clone())
-            .map_err(BasicErrorWith404::bad_request)?;
-        let raw_key = vm_key
-            .undecorate()
-            .simple_serialize()
-            .ok_or_else(|| BasicErrorWith404::internal_str("Failed to serialize table key"))?;
+        let (value_type, raw_key) = match table_item_request {
+            TableItemPost::Json(request) => {
+                let request = request.0;
+                let key_type = request
+                    .key_type
+                    .try_into()
+                    .context("Failed to parse key_type")
+                    .map_err(BasicErrorWith404::bad_request)?;
+                let value_type = request
+                    .value_type
+                    .try_into()
+                    .context("Failed to parse value_type")
+                    .map_err(BasicErrorWith404::bad_request)?;
+                let vm_key = converter
+                    .try_into_vm_value(&key_type, request.key)
+                    .map_err(BasicErrorWith404::bad_request)?;
+                let raw_key = vm_key.undecorate().simple_serialize().ok_or_else(|| {
+                    BasicErrorWith404::internal_str("Failed to serialize table key")
+                })?;
+                (value_type, raw_key)
+            }
+            TableItemPost::Bcs(data) => {
+                let request: RawTableItemRequest = bcs::from_bytes(&data.0)
+                    .context("Failed to deserialize input into RawTableItemRequest")
+                    .map_err(BasicErrorWith404::bad_request)?;
+                // Make sure the key is a valid value of the key type, as it
+                // would be if it had been given as JSON.
+                converter
+                    .try_into_move_value(&request.key_type, &request.key)
+                    .context("Failed to deserialize key as key_type")
+                    .map_err(BasicErrorWith404::bad_request)?;
+                (request.value_type, request.key)
+            }
+        };
 
+        let key = HexEncodedBytes::from(raw_key.clone());
         let state_key = StateKey::table_item(TableHandle(table_handle.0), raw_key);
         let bytes = state_view
             .get_state_value(&state_key)
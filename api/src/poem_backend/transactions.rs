@@ -8,10 +8,11 @@ use std::sync::Arc;
 
 use super::accept_type::{parse_accept, AcceptType};
 use super::bcs_payload::Bcs;
-use super::page::Page;
+use super::page::{Cursor, Page, PageOrder};
 use super::{
-    ApiTags, AptosErrorResponse, BasicError, BasicErrorWith404, BasicResponse, BasicResponseStatus,
-    BasicResult, BasicResultWith404, InternalError, NotFoundError,
+    build_version_pruned, ApiTags, AptosErrorResponse, BasicError, BasicErrorWith404,
+    BasicResponse, BasicResponseStatus, BasicResult, BasicResultWith404, InternalError,
+    NotFoundError,
 };
 use super::{AptosErrorCode, BadRequestError, InsufficientStorageError};
 use crate::context::Context;
@@ -19,12 +20,13 @@ use crate::failpoint::fail_point_poem;
 use crate::{generate_error_response, generate_success_response};
 use anyhow::Context as AnyhowContext;
 use aptos_api_types::{
-    Address, AsConverter, EncodeSubmissionRequest, HashValue, HexEncodedBytes, LedgerInfo,
-    PendingTransaction, SubmitTransactionRequest, Transaction, TransactionData,
+    Address, AsConverter, EncodeSubmissionRequest, GasEstimation, HashValue, HexEncodedBytes,
+    LedgerInfo, PendingTransaction, SubmitTransactionRequest, Transaction, TransactionData,
     TransactionOnChainData, U64,
 };
 use aptos_crypto::signing_message;
 use aptos_types::mempool_status::MempoolStatusCode;
+use aptos_types::on_chain_config::{OnChainConfig, VMConfig};
 use aptos_types::transaction::{
     ExecutionStatus, RawTransaction, RawTransactionWithData, SignedTransaction, TransactionStatus,
 };
@@ -32,7 +34,8 @@ use aptos_vm::AptosVM;
 use poem::web::Accept;
 use poem_openapi::param::{Path, Query};
 use poem_openapi::payload::Json;
-use poem_openapi::{ApiRequest, OpenApi};
+use poem_openapi::{ApiRequest, Enum, OpenApi};
+use storage_interface::MoveStorage;
 
 generate_success_response!(SubmitTransactionResponse, (202, Accepted));
 generate_error_response!(
@@ -66,6 +69,43 @@ pub enum SubmitTransactionPost {
     Bcs(Bcs),
 }
 
+/// The kinds of transaction that `/transactions` can be filtered down to.
+#[derive(Clone, Copy, Debug, Enum, Eq, PartialEq)]
+#[oai(rename_all = "snake_case")]
+pub enum TransactionTypeFilter {
+    User,
+    Genesis,
+    BlockMetadata,
+    StateCheckpoint,
+}
+
+#[derive(Clone, Debug, Default)]
+struct TransactionFilter {
+    transaction_type: Option<TransactionTypeFilter>,
+    sender: Option<Address>,
+}
+
+impl TransactionFilter {
+    fn matches(&self, txn: &TransactionOnChainData) -> bool {
+        use aptos_types::transaction::Transaction::*;
+        let type_matches = match (self.transaction_type, &txn.transaction) {
+            (None, _) => true,
+            (Some(TransactionTypeFilter::User), UserTransaction(_)) => true,
+            (Some(TransactionTypeFilter::Genesis), GenesisTransaction(_)) => true,
+            (Some(TransactionTypeFilter::BlockMetadata), BlockMetadata(_)) => true,
+            (Some(TransactionTypeFilter::StateCheckpoint), StateCheckpoint(_)) => true,
+            _ => false,
+        };
+        // Only user transactions have a sender.
+        let sender_matches = match (&self.sender, &txn.transaction) {
+            (None, _) => true,
+            (Some(sender), UserTransaction(signed_txn)) => sender.inner() == &signed_txn.sender(),
+            _ => false,
+        };
+        type_matches && sender_matches
+    }
+}
+
 pub struct TransactionsApi {
     pub context: Arc<Context>,
 }
@@ -74,7 +114,18 @@ pub struct TransactionsApi {
 impl TransactionsApi {
     /// Get transactions
     ///
-    /// todo
+    /// Returns a page of committed transactions. If the response has an
+    /// X-Aptos-Cursor header, pass it back as the `cursor` param to fetch the
+    /// next page. All pages reached through a cursor are read at the ledger
+    /// version the first page was served at, so they stay consistent while
+    /// the ledger advances. Use `order=desc` to walk the ledger backwards.
+    ///
+    /// The `type` and `sender` params filter the transactions within the page
+    /// of versions being read, so a page may hold fewer than `limit`
+    /// transactions, or none at all. Keep following the cursor to continue.
+    ///
+    /// Each committed transaction carries its decoded write set changes
+    /// unless `include_changes=false` is given.
     #[oai(
         path = "/transactions",
         method = "get",
@@ -86,16 +137,31 @@ impl TransactionsApi {
         accept: Accept,
         start: Query<Option<U64>>,
         limit: Query<Option<u16>>,
+        cursor: Query<Option<String>>,
+        order: Query<Option<PageOrder>>,
+        #[oai(name = "type")] transaction_type: Query<Option<TransactionTypeFilter>>,
+        sender: Query<Option<Address>>,
+        include_changes: Query<Option<bool>>,
     ) -> BasicResultWith404<Vec<Transaction>> {
         fail_point_poem("endppoint_get_transactions")?;
         let accept_type = parse_accept(&accept)?;
-        let page = Page::new(start.0.map(|v| v.0), limit.0);
-        self.list(&accept_type, page)
+        let page = Page::new(start.0.map(|v| v.0), limit.0).with_cursor(cursor.0, order.0);
+        let filter = TransactionFilter {
+            transaction_type: transaction_type.0,
+            sender: sender.0,
+        };
+        self.list(
+            &accept_type,
+            page,
+            filter,
+            include_changes.0.unwrap_or(true),
+        )
     }
 
     /// Get transaction by hash
     ///
-    /// todo
+    /// A committed transaction carries its decoded write set changes unless
+    /// `include_changes=false` is given.
     #[oai(
         path = "/transactions/by_hash/:txn_hash",
         method = "get",
@@ -106,17 +172,23 @@ impl TransactionsApi {
         &self,
         accept: Accept,
         txn_hash: Path<HashValue>,
+        include_changes: Query<Option<bool>>,
         // TODO: Use a new request type that can't return 507.
     ) -> BasicResultWith404<Transaction> {
         fail_point_poem("endpoint_transaction_by_hash")?;
         let accept_type = parse_accept(&accept)?;
-        self.get_transaction_by_hash_inner(&accept_type, txn_hash.0)
-            .await
+        self.get_transaction_by_hash_inner(
+            &accept_type,
+            txn_hash.0,
+            include_changes.0.unwrap_or(true),
+        )
+        .await
     }
 
     /// Get transaction by version
     ///
-    /// todo
+    /// The transaction carries its decoded write set changes unless
+    /// `include_changes=false` is given.
     #[oai(
         path = "/transactions/by_version/:txn_version",
         method = "get",
@@ -127,16 +199,22 @@ impl TransactionsApi {
         &self,
         accept: Accept,
         txn_version: Path<U64>,
+        include_changes: Query<Option<bool>>,
     ) -> BasicResultWith404<Transaction> {
         fail_point_poem("endpoint_transaction_by_version")?;
         let accept_type = parse_accept(&accept)?;
-        self.get_transaction_by_version_inner(&accept_type, txn_version.0)
-            .await
+        self.get_transaction_by_version_inner(
+            &accept_type,
+            txn_version.0,
+            include_changes.0.unwrap_or(true),
+        )
+        .await
     }
 
     /// Get account transactions
     ///
-    /// todo
+    /// Each transaction carries its decoded write set changes unless
+    /// `include_changes=false` is given.
     #[oai(
         path = "/accounts/:address/transactions",
         method = "get",
@@ -150,11 +228,17 @@ impl TransactionsApi {
         address: Path<Address>,
         start: Query<Option<U64>>,
         limit: Query<Option<u16>>,
+        include_changes: Query<Option<bool>>,
     ) -> BasicResultWith404<Vec<Transaction>> {
         fail_point_poem("endpoint_get_accounts_transactions")?;
         let accept_type = parse_accept(&accept)?;
         let page = Page::new(start.0.map(|v| v.0), limit.0);
-        self.list_by_account(&accept_type, page, address.0)
+        self.list_by_account(
+            &accept_type,
+            page,
+            address.0,
+            include_changes.0.unwrap_or(true),
+        )
     }
 
     //
@@ -236,29 +320,140 @@ impl TransactionsApi {
         let accept_type = parse_accept(&accept)?;
         self.get_signing_message(&accept_type, data.0)
     }
+
+    /// Estimate gas price
+    ///
+    /// Returns gas unit prices derived from the user transactions in the most
+    /// recent versions of the ledger: `low_gas_unit_price` is the 25th
+    /// percentile, `gas_unit_price` the median and `priority_gas_unit_price`
+    /// the 90th percentile. Each is at least the minimum gas unit price the
+    /// chain accepts, which is also what all three default to when there have
+    /// been no recent user transactions.
+    #[oai(
+        path = "/estimate_gas_price",
+        method = "get",
+        operation_id = "estimate_gas_price",
+        tag = "ApiTags::Transactions"
+    )]
+    async fn estimate_gas_price(&self, accept: Accept) -> BasicResult<GasEstimation> {
+        fail_point_poem("endpoint_estimate_gas_price")?;
+        let accept_type = parse_accept(&accept)?;
+        self.estimate_gas_price_inner(&accept_type)
+    }
 }
 
 impl TransactionsApi {
-    fn list(&self, accept_type: &AcceptType, page: Page) -> BasicResultWith404<Vec<Transaction>> {
+    fn estimate_gas_price_inner(&self, accept_type: &AcceptType) -> BasicResult<GasEstimation> {
         let latest_ledger_info = self.context.get_latest_ledger_info_poem()?;
         let ledger_version = latest_ledger_info.version();
-        let limit = page.limit()?;
-        // TODO: https://github.com/aptos-labs/aptos-core/issues/2286
-        let last_page_start = if ledger_version > (limit as u64) {
-            ledger_version - (limit as u64)
-        } else {
-            0
+        let min_gas_unit_price = self.min_gas_unit_price(ledger_version)?;
+
+        let estimation = self
+            .context
+            .estimate_gas_price(&latest_ledger_info, min_gas_unit_price)
+            .context("Failed to read recent transactions from storage")
+            .map_err(|e| {
+                BasicError::internal(e).error_code(AptosErrorCode::ReadFromStorageError)
+            })?;
+
+        BasicResponse::try_from_rust_value((
+            estimation,
+            &latest_ledger_info,
+            BasicResponseStatus::Ok,
+            accept_type,
+        ))
+    }
+
+    /// Reads the minimum gas unit price from the gas schedule on chain.
+    fn min_gas_unit_price<E: InternalError>(&self, version: u64) -> Result<u64, E> {
+        let bytes = self
+            .context
+            .db
+            .as_ref()
+            .fetch_config_by_version(VMConfig::CONFIG_ID, version)
+            .context("Failed to read the VM config from storage")
+            .map_err(|e| E::internal(e).error_code(AptosErrorCode::ReadFromStorageError))?;
+        let vm_config = VMConfig::deserialize_into_config(&bytes)
+            .context("Failed to deserialize the VM config")
+            .map_err(|e| E::internal(e).error_code(AptosErrorCode::InvalidBcsInStorageError))?;
+        Ok(vm_config
+            .gas_schedule
+            .gas_constants
+            .min_price_per_gas_unit
+            .get())
+    }
+
+    fn list(
+        &self,
+        accept_type: &AcceptType,
+        page: Page,
+        filter: TransactionFilter,
+        include_changes: bool,
+    ) -> BasicResultWith404<Vec<Transaction>> {
+        let latest_ledger_info = self.context.get_latest_ledger_info_poem()?;
+        let limit = page.limit(self.context.max_transactions_page_size())?;
+
+        // A cursor pins the walk to the ledger version of the first page.
+        let (start_version, ledger_version, order) = match page.cursor(self.context.cursor_key())? {
+            Some(cursor) => (cursor.version, cursor.ledger_version, cursor.order),
+            None => {
+                let ledger_version = latest_ledger_info.version();
+                let order = page.order();
+                let default_start = match order {
+                    // TODO: https://github.com/aptos-labs/aptos-core/issues/2286
+                    PageOrder::Asc => ledger_version.saturating_sub(limit as u64),
+                    PageOrder::Desc => ledger_version,
+                };
+                (
+                    page.start(default_start, ledger_version)?,
+                    ledger_version,
+                    order,
+                )
+            }
         };
-        let start_version = page.start(last_page_start, ledger_version)?;
 
-        let data = self
+        // Storage only reads forwards, so a descending page is read as the
+        // range ending at the start version and then reversed.
+        let (first_version, count) = match order {
+            PageOrder::Asc => (start_version, limit),
+            PageOrder::Desc => {
+                let first_version = start_version.saturating_sub(limit as u64 - 1);
+                (first_version, (start_version - first_version + 1) as u16)
+            }
+        };
+
+        let mut data = self
             .context
-            .get_transactions(start_version, limit, ledger_version)
+            .get_transactions(first_version, count, ledger_version)
             .context("Failed to read raw transactions from storage")
             .map_err(BasicErrorWith404::internal)
             .map_err(|e| e.error_code(AptosErrorCode::InvalidBcsInStorageError))?;
 
-        self.render_transactions(data, accept_type, &latest_ledger_info)
+        let next_cursor = match order {
+            PageOrder::Asc => {
+                let next_version = first_version + data.len() as u64;
+                if data.len() == count as usize && next_version <= ledger_version {
+                    Some(Cursor::new(next_version, ledger_version, order))
+                } else {
+                    None
+                }
+            }
+            PageOrder::Desc => {
+                data.reverse();
+                first_version
+                    .checked_sub(1)
+                    .map(|next_version| Cursor::new(next_version, ledger_version, order))
+            }
+        };
+
+        // Filter only after computing the next cursor, it must advance past
+        // the whole page of versions that was read.
+        data.retain(|txn| filter.matches(txn));
+
+        self.render_transactions(data, accept_type, &latest_ledger_info, include_changes)
+            .map(|response| {
+                response.with_cursor(next_cursor.map(|c| c.encode(self.context.cursor_key())))
+            })
     }
 
     fn render_transactions<E: InternalError>(
@@ -266,6 +461,7 @@ impl TransactionsApi {
         data: Vec<TransactionOnChainData>,
         accept_type: &AcceptType,
         latest_ledger_info: &LedgerInfo,
+        include_changes: bool,
     ) -> Result<BasicResponse<Vec<Transaction>>, E> {
         if data.is_empty() {
             let data: Vec<Transaction> = vec![];
@@ -284,7 +480,8 @@ impl TransactionsApi {
             .map(|t| {
                 let version = t.version;
                 let timestamp = self.context.get_block_timestamp(version)?;
-                let txn = converter.try_into_onchain_transaction(timestamp, t)?;
+                let txn = converter
+                    .try_into_onchain_transaction(timestamp, without_changes(t, include_changes))?;
                 Ok(txn)
             })
             .collect::<Result<_, anyhow::Error>>()
@@ -303,6 +500,7 @@ impl TransactionsApi {
         &self,
         accept_type: &AcceptType,
         hash: HashValue,
+        include_changes: bool,
     ) -> BasicResultWith404<Transaction> {
         let ledger_info = self.context.get_latest_ledger_info_poem()?;
         let txn_data = self
@@ -313,7 +511,7 @@ impl TransactionsApi {
             .context(format!("Failed to find transaction with hash: {}", hash))
             .map_err(BasicErrorWith404::not_found)?;
 
-        self.get_transaction_inner(accept_type, txn_data, &ledger_info)
+        self.get_transaction_inner(accept_type, txn_data, &ledger_info, include_changes)
             .await
     }
 
@@ -321,8 +519,17 @@ impl TransactionsApi {
         &self,
         accept_type: &AcceptType,
         version: U64,
+        include_changes: bool,
     ) -> BasicResultWith404<Transaction> {
         let ledger_info = self.context.get_latest_ledger_info_poem()?;
+        if version.0 < ledger_info.oldest_ledger_version.0 {
+            return Err(build_version_pruned(
+                "Transaction",
+                version.0,
+                ledger_info.oldest_ledger_version.0,
+                ledger_info.version(),
+            ));
+        }
         let txn_data = self
             .get_by_version(version.0, &ledger_info)
             .context(format!("Failed to get transaction by version {}", version))
@@ -333,7 +540,7 @@ impl TransactionsApi {
             ))
             .map_err(BasicErrorWith404::not_found)?;
 
-        self.get_transaction_inner(accept_type, txn_data, &ledger_info)
+        self.get_transaction_inner(accept_type, txn_data, &ledger_info, include_changes)
             .await
     }
 
@@ -342,6 +549,7 @@ impl TransactionsApi {
         accept_type: &AcceptType,
         transaction_data: TransactionData,
         ledger_info: &LedgerInfo,
+        include_changes: bool,
     ) -> BasicResultWith404<Transaction> {
         let resolver = self.context.move_resolver_poem()?;
         let transaction = match transaction_data {
@@ -353,7 +561,7 @@ impl TransactionsApi {
                     .map_err(BasicErrorWith404::internal)?;
                 resolver
                     .as_converter(self.context.db.clone())
-                    .try_into_onchain_transaction(timestamp, txn)
+                    .try_into_onchain_transaction(timestamp, without_changes(txn, include_changes))
                     .context("Failed to convert on chain transaction to Transaction")
                     .map_err(BasicErrorWith404::internal)?
             }
@@ -414,6 +622,7 @@ impl TransactionsApi {
         accept_type: &AcceptType,
         page: Page,
         address: Address,
+        include_changes: bool,
     ) -> BasicResultWith404<Vec<Transaction>> {
         let latest_ledger_info = self.context.get_latest_ledger_info_poem()?;
         // TODO: Return more specific errors from within this function.
@@ -422,13 +631,13 @@ impl TransactionsApi {
             .get_account_transactions(
                 address.into(),
                 page.start(0, u64::MAX)?,
-                page.limit()?,
+                page.limit(self.context.max_transactions_page_size())?,
                 latest_ledger_info.version(),
             )
             .context("Failed to get account transactions for the given account")
             .map_err(BasicErrorWith404::internal)?;
 
-        self.render_transactions(data, accept_type, &latest_ledger_info)
+        self.render_transactions(data, accept_type, &latest_ledger_info, include_changes)
     }
 
     fn get_signed_transaction(
@@ -538,7 +747,7 @@ impl TransactionsApi {
             changes: output.write_set().clone(),
         };
 
-        self.render_transactions(vec![simulated_txn], accept_type, &ledger_info)
+        self.render_transactions(vec![simulated_txn], accept_type, &ledger_info, true)
     }
 
     pub fn get_signing_message(
@@ -575,3 +784,15 @@ impl TransactionsApi {
         ))
     }
 }
+
+/// Drops the write set of a transaction unless its changes were asked for,
+/// which also skips decoding them.
+pub(crate) fn without_changes(
+    mut txn: TransactionOnChainData,
+    include_changes: bool,
+) -> TransactionOnChainData {
+    if !include_changes {
+        txn.changes = Default::default();
+    }
+    txn
+}
@@ -0,0 +1,204 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use aptos_api_types::Error;
+use aptos_config::config::ApiRateLimitConfig;
+use std::{
+    collections::HashMap,
+    net::{IpAddr, SocketAddr},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+use warp::{
+    http::{header, HeaderValue, StatusCode},
+    path::FullPath,
+    reply::{self, Response},
+    Filter, Rejection, Reply,
+};
+
+/// Once this many IP addresses are tracked, the ones whose buckets have
+/// refilled completely are forgotten.
+const MAX_TRACKED_IPS: usize = 100_000;
+
+/// A token bucket holding up to `capacity` tokens, refilled at `rate` tokens
+/// per second. Every request takes one token.
+#[derive(Debug)]
+struct TokenBucket {
+    capacity: f64,
+    rate: f64,
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    fn new(rate: u32, burst: Option<u32>, now: Instant) -> Self {
+        let capacity = burst.unwrap_or(rate).max(1) as f64;
+        Self {
+            capacity,
+            rate: rate.max(1) as f64,
+            tokens: capacity,
+            last_refill: now,
+        }
+    }
+
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now
+            .saturating_duration_since(self.last_refill)
+            .as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.rate).min(self.capacity);
+        self.last_refill = now;
+    }
+
+    /// Takes a token, or returns how long until one is available.
+    fn try_acquire(&mut self, now: Instant) -> Result<(), Duration> {
+        self.refill(now);
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64((1.0 - self.tokens) / self.rate))
+        }
+    }
+
+    fn is_full(&mut self, now: Instant) -> bool {
+        self.refill(now);
+        self.tokens >= self.capacity
+    }
+}
+
+#[derive(Debug)]
+pub struct RateLimiter {
+    config: ApiRateLimitConfig,
+    global: Option<Mutex<TokenBucket>>,
+    per_ip: Mutex<HashMap<IpAddr, TokenBucket>>,
+}
+
+impl RateLimiter {
+    pub fn new(config: ApiRateLimitConfig) -> Self {
+        let global = config
+            .global_requests_per_sec
+            .map(|rate| Mutex::new(TokenBucket::new(rate, config.burst, Instant::now())));
+        Self {
+            config,
+            global,
+            per_ip: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Admits a request from the given address, or returns how long the
+    /// client should wait before retrying. The per IP limit is checked first,
+    /// so a single noisy client cannot drain the global budget.
+    fn check(&self, ip: Option<IpAddr>, now: Instant) -> Result<(), Duration> {
+        if let (Some(rate), Some(ip)) = (self.config.per_ip_requests_per_sec, ip) {
+            let mut per_ip = self.per_ip.lock().unwrap();
+            if per_ip.len() >= MAX_TRACKED_IPS {
+                per_ip.retain(|_, bucket| !bucket.is_full(now));
+            }
+            per_ip
+                .entry(ip)
+                .or_insert_with(|| TokenBucket::new(rate, self.config.burst, now))
+                .try_acquire(now)?;
+        }
+        if let Some(global) = &self.global {
+            global.lock().unwrap().try_acquire(now)?;
+        }
+        Ok(())
+    }
+}
+
+/// Answers requests over the rate limit with 429 and a Retry-After header.
+/// Requests within the limit are rejected, so they fall through to the API.
+/// Health checks are never limited.
+pub fn rate_limited(
+    limiter: Arc<RateLimiter>,
+) -> impl Filter<Extract = (Response,), Error = Rejection> + Clone {
+    warp::path::full().and(warp::addr::remote()).and_then(
+        move |path: FullPath, remote: Option<SocketAddr>| {
+            let limiter = limiter.clone();
+            async move {
+                if path.as_str().starts_with("/-/") {
+                    return Err(warp::reject());
+                }
+                match limiter.check(remote.map(|addr| addr.ip()), Instant::now()) {
+                    Ok(()) => Err(warp::reject()),
+                    Err(retry_after) => Ok(too_many_requests(retry_after)),
+                }
+            }
+        },
+    )
+}
+
+fn too_many_requests(retry_after: Duration) -> Response {
+    let retry_after_secs = retry_after.as_secs_f64().ceil().max(1.0) as u64;
+    let error = Error::new(
+        StatusCode::TOO_MANY_REQUESTS,
+        format!(
+            "Too many requests, retry after {} seconds",
+            retry_after_secs
+        ),
+    );
+    let mut response = reply::with_status(reply::json(&error), error.status_code()).into_response();
+    response
+        .headers_mut()
+        .insert(header::RETRY_AFTER, HeaderValue::from(retry_after_secs));
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(per_ip: Option<u32>, global: Option<u32>, burst: Option<u32>) -> ApiRateLimitConfig {
+        ApiRateLimitConfig {
+            per_ip_requests_per_sec: per_ip,
+            global_requests_per_sec: global,
+            burst,
+        }
+    }
+
+    #[test]
+    fn test_token_bucket_refills() {
+        let now = Instant::now();
+        let mut bucket = TokenBucket::new(2, Some(3), now);
+        for _ in 0..3 {
+            assert!(bucket.try_acquire(now).is_ok());
+        }
+        let retry_after = bucket.try_acquire(now).unwrap_err();
+        assert_eq!(retry_after, Duration::from_millis(500));
+        assert!(bucket.try_acquire(now + Duration::from_millis(500)).is_ok());
+        assert!(bucket.is_full(now + Duration::from_secs(10)));
+    }
+
+    #[test]
+    fn test_per_ip_limit() {
+        let limiter = RateLimiter::new(config(Some(1), None, None));
+        let now = Instant::now();
+        let a = Some("10.0.0.1".parse().unwrap());
+        let b = Some("10.0.0.2".parse().unwrap());
+        assert!(limiter.check(a, now).is_ok());
+        assert!(limiter.check(a, now).is_err());
+        assert!(limiter.check(b, now).is_ok());
+        assert!(limiter.check(a, now + Duration::from_secs(1)).is_ok());
+    }
+
+    #[test]
+    fn test_global_limit() {
+        let limiter = RateLimiter::new(config(None, Some(1), Some(2)));
+        let now = Instant::now();
+        assert!(limiter
+            .check(Some("10.0.0.1".parse().unwrap()), now)
+            .is_ok());
+        assert!(limiter
+            .check(Some("10.0.0.2".parse().unwrap()), now)
+            .is_ok());
+        assert!(limiter.check(None, now).is_err());
+    }
+
+    #[test]
+    fn test_no_limits() {
+        let limiter = RateLimiter::new(ApiRateLimitConfig::default());
+        for _ in 0..1000 {
+            assert!(limiter.check(None, Instant::now()).is_ok());
+        }
+    }
+}
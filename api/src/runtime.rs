@@ -1,16 +1,34 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{context::Context, index, poem_backend::attach_poem_to_runtime};
+use crate::{
+    context::Context,
+    index,
+    log::{request_id, with_request_id, REQUEST_ID_HEADER},
+    poem_backend::attach_poem_to_runtime,
+    rate_limit::{rate_limited, RateLimiter},
+    stream,
+};
 use anyhow::Context as AnyhowContext;
 use aptos_config::config::{ApiConfig, NodeConfig};
 use aptos_mempool::MempoolClientSender;
 use aptos_types::chain_id::ChainId;
+use hyper::body::Bytes;
 use std::{convert::Infallible, net::SocketAddr, sync::Arc};
 use storage_interface::DbReader;
 use tokio::runtime::{Builder, Runtime};
-use warp::{Filter, Reply};
-use warp_reverse_proxy::reverse_proxy_filter;
+use warp::{
+    http::{
+        header::{self, HeaderMap, HeaderValue},
+        Method,
+    },
+    path::FullPath,
+    reply::Response,
+    Filter, Rejection, Reply,
+};
+use warp_reverse_proxy::{
+    extract_request_data_filter, proxy_to_and_forward_response, QueryParameters,
+};
 
 /// Creates HTTP server (warp-based) serves for both REST and JSON-RPC API.
 /// When api and json-rpc are configured with same port, both API will be served for the port.
@@ -48,11 +66,145 @@ pub fn get_routes_with_poem(
     poem_address: SocketAddr,
     context: Context,
 ) -> impl Filter<Extract = impl Reply, Error = Infallible> + Clone {
-    let proxy = warp::path!("v1" / ..).and(reverse_proxy_filter(
-        "v1".to_string(),
-        format!("http://{}", poem_address),
-    ));
-    proxy.or(index::routes(context))
+    // The reverse proxy buffers whole responses, so streams are served by
+    // warp directly, ahead of the proxy.
+    let stream = warp::path!("v1" / ..).and(stream::stream_transactions(context.clone()));
+    let proxy = warp::path!("v1" / ..).and(proxy_with_request_id(poem_address));
+    let limiter = Arc::new(RateLimiter::new(context.rate_limit_config().clone()));
+    let routes = with_request_id(
+        rate_limited(limiter)
+            .or(stream)
+            .or(proxy)
+            .or(legacy_routes(context)),
+    );
+    // Compressing a stream would hold events back until enough of them have
+    // been buffered, so streams are never compressed.
+    is_stream_request()
+        .and(routes.clone())
+        .or(with_compression(routes))
+}
+
+/// Proxies a request to the poem server with its request id, generating one if
+/// the client did not send any, so both servers log the request under the same
+/// id. The id is echoed in the response for `with_request_id` to keep.
+fn proxy_with_request_id(
+    poem_address: SocketAddr,
+) -> impl Filter<Extract = (Response,), Error = Rejection> + Clone {
+    extract_request_data_filter().and_then(
+        move |uri: FullPath,
+              params: QueryParameters,
+              method: Method,
+              mut headers: HeaderMap,
+              body: Bytes| async move {
+            let request_id = HeaderValue::from_str(&request_id(&headers)).ok();
+            if let Some(request_id) = &request_id {
+                headers.insert(REQUEST_ID_HEADER, request_id.clone());
+            }
+            let response = proxy_to_and_forward_response(
+                format!("http://{}", poem_address),
+                "v1".to_string(),
+                uri,
+                params,
+                method,
+                headers,
+                body,
+            )
+            .await?;
+            let mut response = Reply::into_response(response);
+            if let Some(request_id) = request_id {
+                response.headers_mut().insert(REQUEST_ID_HEADER, request_id);
+            }
+            Ok::<_, Rejection>(response)
+        },
+    )
+}
+
+fn is_stream_request() -> impl Filter<Extract = (), Error = Rejection> + Clone {
+    warp::path::full()
+        .and_then(|path: FullPath| async move {
+            if path.as_str().starts_with("/stream/") || path.as_str().starts_with("/v1/stream/") {
+                Ok(())
+            } else {
+                Err(warp::reject())
+            }
+        })
+        .untuple_one()
+}
+
+/// Compresses responses with brotli or gzip if the client accepts either,
+/// preferring brotli.
+fn with_compression<F, R>(
+    routes: F,
+) -> impl Filter<Extract = impl Reply, Error = Infallible> + Clone
+where
+    F: Filter<Extract = (R,), Error = Infallible> + Clone + Send + Sync + 'static,
+    R: Reply,
+{
+    accepts_encoding("br")
+        .and(routes.clone().with(warp::compression::brotli()))
+        .or(accepts_encoding("gzip").and(routes.clone().with(warp::compression::gzip())))
+        .or(routes)
+}
+
+/// Passes only if the Accept-Encoding header lists the given encoding without
+/// ruling it out with a zero quality value.
+fn accepts_encoding(
+    encoding: &'static str,
+) -> impl Filter<Extract = (), Error = Rejection> + Clone {
+    warp::header::optional::<String>("accept-encoding")
+        .and_then(move |accept_encoding: Option<String>| async move {
+            let accepted = accept_encoding.map_or(false, |accept_encoding| {
+                is_encoding_accepted(&accept_encoding, encoding)
+            });
+            if accepted {
+                Ok(())
+            } else {
+                Err(warp::reject())
+            }
+        })
+        .untuple_one()
+}
+
+/// Whether the value of an Accept-Encoding header lists the encoding with a
+/// quality value above zero, see RFC 7231. An encoding with an unparsable
+/// quality value is treated as not accepted.
+fn is_encoding_accepted(accept_encoding: &str, encoding: &str) -> bool {
+    accept_encoding.split(',').any(|candidate| {
+        let mut parts = candidate.split(';').map(str::trim);
+        parts.next() == Some(encoding)
+            && parts.all(|param| match param.strip_prefix("q=") {
+                Some(q) => q.parse::<f32>().map_or(false, |q| q > 0.0),
+                None => true,
+            })
+    })
+}
+
+/// The unversioned legacy API, with every response marked as deprecated in
+/// favour of /v1, see RFC 8594. Health checks are not part of either API
+/// version and are left alone.
+fn legacy_routes(
+    context: Context,
+) -> impl Filter<Extract = (Response,), Error = Infallible> + Clone {
+    let sunset = context
+        .legacy_api_sunset()
+        .and_then(|sunset| HeaderValue::from_str(sunset).ok());
+    warp::path::full()
+        .and(index::routes(context))
+        .map(move |path: FullPath, reply| {
+            let mut response = Reply::into_response(reply);
+            if !path.as_str().starts_with("/-/") {
+                let headers = response.headers_mut();
+                headers.insert("deprecation", HeaderValue::from_static("true"));
+                headers.insert(
+                    header::LINK,
+                    HeaderValue::from_static("</v1>; rel=\"successor-version\""),
+                );
+                if let Some(sunset) = &sunset {
+                    headers.insert("sunset", sunset.clone());
+                }
+            }
+            response
+        })
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -108,10 +260,22 @@ mod tests {
     use aptos_types::chain_id::ChainId;
 
     use crate::{
-        runtime::bootstrap,
+        runtime::{bootstrap, is_encoding_accepted},
         tests::{new_test_context, TestContext},
     };
 
+    #[test]
+    fn test_is_encoding_accepted() {
+        assert!(is_encoding_accepted("gzip", "gzip"));
+        assert!(is_encoding_accepted("br, gzip;q=0.5", "gzip"));
+        assert!(is_encoding_accepted("gzip;q=1.0", "gzip"));
+        assert!(!is_encoding_accepted("br", "gzip"));
+        assert!(!is_encoding_accepted("gzip;q=0", "gzip"));
+        assert!(!is_encoding_accepted("gzip;q=0.0", "gzip"));
+        assert!(!is_encoding_accepted("gzip; q=0.000", "gzip"));
+        assert!(!is_encoding_accepted("gzip;q=invalid", "gzip"));
+    }
+
     #[test]
     fn test_bootstrap_jsonprc_and_api_configured_at_different_port() {
         let mut cfg = NodeConfig::default();
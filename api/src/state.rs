@@ -139,6 +139,7 @@ impl State {
                 latest_ledger_info.version(),
             ));
         }
+        context.check_state_not_pruned(ledger_version, latest_ledger_info.version())?;
 
         let state_view = context.state_view_at_version(ledger_version)?;
 
@@ -0,0 +1,255 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    context::Context,
+    failpoint::fail_point,
+    metrics::metrics,
+    param::{AddressParam, MoveStructTagParam, TransactionVersionParam},
+};
+use anyhow::Result;
+use aptos_api_types::{
+    AsConverter, Error, Transaction, TransactionId, TransactionOnChainData, U64,
+};
+use aptos_logger::warn;
+use aptos_types::account_address::AccountAddress;
+use futures::{stream, Stream};
+use move_deps::move_core_types::language_storage::{StructTag, TypeTag};
+use serde::Deserialize;
+use std::{collections::VecDeque, convert::Infallible, convert::TryInto, time::Duration};
+use warp::{filters::BoxedFilter, reply, sse, Filter, Rejection, Reply};
+
+/// Maximum number of transactions read from storage per poll.
+const STREAM_BATCH_SIZE: u16 = 100;
+/// How long to wait before polling storage again once caught up.
+const STREAM_POLL_INTERVAL: Duration = Duration::from_millis(100);
+/// Upper bound of the backoff between retries of failed reads.
+const STREAM_MAX_RETRY_INTERVAL: Duration = Duration::from_secs(10);
+/// The stream ends with an error event after this many failed reads in a row.
+const STREAM_MAX_CONSECUTIVE_FAILURES: u32 = 10;
+
+#[derive(Clone, Debug, Deserialize)]
+pub(crate) struct StreamParams {
+    start: Option<TransactionVersionParam>,
+    address: Option<AddressParam>,
+    event_type: Option<MoveStructTagParam>,
+}
+
+// GET /stream/transactions?start={u64}&address={address}&event_type={struct_tag}
+pub fn stream_transactions(context: Context) -> BoxedFilter<(impl Reply,)> {
+    warp::path!("stream" / "transactions")
+        .and(warp::get())
+        .and(warp::query::<StreamParams>())
+        .and(context.filter())
+        .and_then(handle_stream_transactions)
+        .with(metrics("stream_transactions"))
+        .boxed()
+}
+
+async fn handle_stream_transactions(
+    params: StreamParams,
+    context: Context,
+) -> Result<impl Reply, Rejection> {
+    fail_point("endpoint_stream_transactions")?;
+    // Errors are turned into replies here rather than rejections: under /v1
+    // this filter sits ahead of the reverse proxy, which would otherwise
+    // swallow the rejection and forward the request instead.
+    match TransactionStream::new(params, context) {
+        Ok(stream) => {
+            Ok(sse::reply(sse::keep_alive().stream(stream.into_stream())).into_response())
+        }
+        Err(error) => {
+            Ok(reply::with_status(reply::json(&error), error.status_code()).into_response())
+        }
+    }
+}
+
+/// Only transactions matching every given filter are streamed.
+struct StreamFilter {
+    address: Option<AccountAddress>,
+    event_type: Option<TypeTag>,
+}
+
+impl StreamFilter {
+    fn matches(&self, txn: &TransactionOnChainData) -> bool {
+        let address_matches = match &self.address {
+            None => true,
+            Some(address) => txn
+                .transaction
+                .as_signed_user_txn()
+                .map_or(false, |user_txn| &user_txn.sender() == address),
+        };
+        let event_type_matches = match &self.event_type {
+            None => true,
+            Some(event_type) => txn
+                .events
+                .iter()
+                .any(|event| event.type_tag() == event_type),
+        };
+        address_matches && event_type_matches
+    }
+}
+
+/// Follows the ledger from a start version, pushing each committed
+/// transaction that passes the filter as a server sent event.
+struct TransactionStream {
+    context: Context,
+    next_version: u64,
+    filter: StreamFilter,
+    pending: VecDeque<(u64, Transaction)>,
+    consecutive_failures: u32,
+}
+
+impl TransactionStream {
+    fn new(params: StreamParams, context: Context) -> Result<Self, Error> {
+        let ledger_version = context.get_latest_ledger_info()?.version();
+        let next_version = match params.start {
+            Some(start) => {
+                let start = start.parse("start")?;
+                if start > ledger_version {
+                    return Err(Error::not_found(
+                        "transaction",
+                        TransactionId::Version(U64::from(start)),
+                        ledger_version,
+                    ));
+                }
+                start
+            }
+            // Without a start version, only transactions committed from now on are streamed.
+            None => ledger_version + 1,
+        };
+        let event_type = match params.event_type {
+            Some(event_type) => {
+                let struct_tag: StructTag = event_type
+                    .parse("event_type")?
+                    .try_into()
+                    .map_err(|_| Error::invalid_param("event_type", "invalid struct tag"))?;
+                Some(TypeTag::Struct(struct_tag))
+            }
+            None => None,
+        };
+        let address = params
+            .address
+            .map(|address| address.parse("address"))
+            .transpose()?
+            .map(Into::into);
+
+        Ok(Self {
+            context,
+            next_version,
+            filter: StreamFilter {
+                address,
+                event_type,
+            },
+            pending: VecDeque::new(),
+            consecutive_failures: 0,
+        })
+    }
+
+    fn into_stream(self) -> impl Stream<Item = Result<sse::Event, Infallible>> {
+        stream::unfold(Some(self), |state| async move {
+            let mut state = state?;
+            loop {
+                if let Some((version, txn)) = state.pending.pop_front() {
+                    match sse::Event::default()
+                        .event("transaction")
+                        .id(version.to_string())
+                        .json_data(&txn)
+                    {
+                        Ok(event) => return Some((Ok(event), Some(state))),
+                        Err(e) => {
+                            warn!("Failed to serialize transaction {}: {:?}", version, e);
+                            continue;
+                        }
+                    }
+                }
+                // Storage reads block, so they are kept off the async runtime's threads.
+                let (returned, result) = match tokio::task::spawn_blocking(move || {
+                    let result = state.fetch_next_batch();
+                    (state, result)
+                })
+                .await
+                {
+                    Ok(returned) => returned,
+                    Err(e) => {
+                        warn!("Transaction stream read task failed: {:?}", e);
+                        return None;
+                    }
+                };
+                state = returned;
+                match result {
+                    Ok(true) => {
+                        state.consecutive_failures = 0;
+                        continue;
+                    }
+                    Ok(false) => {
+                        state.consecutive_failures = 0;
+                        tokio::time::sleep(STREAM_POLL_INTERVAL).await;
+                    }
+                    Err(e) => {
+                        state.consecutive_failures += 1;
+                        warn!(
+                            "Failed to read transactions from version {} ({} times in a row): {:?}",
+                            state.next_version, state.consecutive_failures, e
+                        );
+                        if state.consecutive_failures >= STREAM_MAX_CONSECUTIVE_FAILURES {
+                            // Tell the client why the stream ends, so that it can reconnect
+                            // from the last event id it received.
+                            let error = Error::internal(e);
+                            return sse::Event::default()
+                                .event("error")
+                                .json_data(&error)
+                                .ok()
+                                .map(|event| (Ok(event), None));
+                        }
+                        tokio::time::sleep(state.retry_interval()).await;
+                    }
+                }
+            }
+        })
+    }
+
+    /// Backs off exponentially from the poll interval while reads keep failing.
+    fn retry_interval(&self) -> Duration {
+        std::cmp::min(
+            STREAM_POLL_INTERVAL * 2u32.saturating_pow(self.consecutive_failures),
+            STREAM_MAX_RETRY_INTERVAL,
+        )
+    }
+
+    /// Reads the next batch of committed transactions into `pending`. Returns
+    /// whether any transactions were read, whether or not they matched.
+    fn fetch_next_batch(&mut self) -> Result<bool> {
+        let ledger_version = self
+            .context
+            .get_latest_ledger_info_with_signatures()?
+            .ledger_info()
+            .version();
+        if self.next_version > ledger_version {
+            return Ok(false);
+        }
+        let data =
+            self.context
+                .get_transactions(self.next_version, STREAM_BATCH_SIZE, ledger_version)?;
+        if data.is_empty() {
+            return Ok(false);
+        }
+        let next_version = self.next_version + data.len() as u64;
+
+        let resolver = self.context.move_resolver()?;
+        let converter = resolver.as_converter(self.context.db.clone());
+        let txns = data
+            .into_iter()
+            .filter(|txn| self.filter.matches(txn))
+            .map(|txn| {
+                let version = txn.version;
+                let timestamp = self.context.get_block_timestamp(version)?;
+                let txn = converter.try_into_onchain_transaction(timestamp, txn)?;
+                Ok((version, txn))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        self.pending.extend(txns);
+        self.next_version = next_version;
+        Ok(true)
+    }
+}
@@ -11,7 +11,8 @@ use aptos_api_types::{
     X_APTOS_LEDGER_TIMESTAMP, X_APTOS_LEDGER_VERSION,
 };
 use aptos_config::config::{
-    NodeConfig, RocksdbConfigs, NO_OP_STORAGE_PRUNER_CONFIG, TARGET_SNAPSHOT_SIZE,
+    NodeConfig, RocksdbConfigs, StoragePrunerConfig, NO_OP_STORAGE_PRUNER_CONFIG,
+    TARGET_SNAPSHOT_SIZE,
 };
 use aptos_crypto::{hash::HashValue, SigningKey};
 use aptos_mempool::mocks::MockSharedMempool;
@@ -32,19 +33,26 @@ use aptos_types::{
     transaction::{Transaction, TransactionStatus},
 };
 use aptos_vm::AptosVM;
-use aptosdb::AptosDB;
+use aptosdb::{AptosDB, PrunerIndex};
 use bytes::Bytes;
 use executor::{block_executor::BlockExecutor, db_bootstrapper};
 use executor_types::BlockExecutorTrait;
 use hyper::{HeaderMap, Response};
 use mempool_notifications::MempoolNotificationSender;
-use storage_interface::DbReaderWriter;
+use storage_interface::{DbReader, DbReaderWriter};
 
 use aptos_config::keys::ConfigKey;
 use aptos_crypto::ed25519::Ed25519PrivateKey;
 use rand::SeedableRng;
 use serde_json::{json, Value};
-use std::{boxed::Box, collections::BTreeMap, iter::once, net::SocketAddr, sync::Arc};
+use std::{
+    boxed::Box,
+    collections::BTreeMap,
+    iter::once,
+    net::SocketAddr,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 use storage_interface::state_view::DbStateView;
 use vm_validator::vm_validator::VMValidator;
 use warp::http::header::CONTENT_TYPE;
@@ -97,7 +105,31 @@ impl ApiSpecificConfig {
     }
 }
 
+/// Windows wide enough that nothing is pruned in a test but what `TestContext::prune_to` prunes.
+pub const PRUNE_TO_ONLY_PRUNER_CONFIG: StoragePrunerConfig = StoragePrunerConfig {
+    state_store_prune_window: Some(1_000_000),
+    ledger_prune_window: Some(1_000_000),
+    state_store_prune_window_secs: None,
+    ledger_prune_window_secs: None,
+    event_store_prune_window: Some(1_000_000),
+    epoch_snapshots_to_retain: 0,
+    ledger_pruning_batch_size: 1,
+    state_store_pruning_batch_size: 1,
+    ledger_max_versions_per_sec: None,
+    state_store_max_versions_per_sec: None,
+    compaction_threshold_versions: None,
+    prune_only_backed_up: false,
+};
+
 pub fn new_test_context(test_name: String, api_version: &str) -> TestContext {
+    new_test_context_with_pruner_config(test_name, api_version, NO_OP_STORAGE_PRUNER_CONFIG)
+}
+
+pub fn new_test_context_with_pruner_config(
+    test_name: String,
+    api_version: &str,
+    pruner_config: StoragePrunerConfig,
+) -> TestContext {
     let tmp_dir = TempPath::new();
     tmp_dir.create_as_dir().unwrap();
 
@@ -120,8 +152,8 @@ pub fn new_test_context(test_name: String, api_version: &str) -> TestContext {
     let (db, db_rw) = DbReaderWriter::wrap(
         AptosDB::open(
             &tmp_dir,
-            false,                       /* readonly */
-            NO_OP_STORAGE_PRUNER_CONFIG, /* pruner */
+            false, /* readonly */
+            pruner_config,
             RocksdbConfigs::default(),
             true, /* indexer */
             TARGET_SNAPSHOT_SIZE,
@@ -213,7 +245,7 @@ impl TestContext {
         }
     }
 
-    pub fn check_golden_output(&mut self, msg: Value) {
+    pub fn check_golden_output(&mut self, mut msg: Value) {
         // Temporary while the old API still lives at /
         let version_dir = self.api_specific_config.get_version_dir();
         if self.golden_output.is_none() {
@@ -223,6 +255,10 @@ impl TestContext {
             ));
         }
 
+        // Request ids are random unless the client sends one.
+        if let Some(error) = msg.as_object_mut() {
+            error.remove("request_id");
+        }
         let msg = pretty(&msg);
         let re = regex::Regex::new("hash\": \".*\"").unwrap();
         let msg = re.replace_all(&msg, "hash\": \"\"");
@@ -294,6 +330,44 @@ impl TestContext {
             .unwrap()
     }
 
+    /// Commits a few empty blocks and prunes the versions before the last one, returning the first
+    /// version left. Needs a context created with `PRUNE_TO_ONLY_PRUNER_CONFIG`.
+    pub async fn commit_blocks_and_prune(&mut self) -> u64 {
+        for _ in 0..3 {
+            self.commit_block(&[]).await;
+        }
+        let min_readable_version = self.get_latest_ledger_info().version();
+        // a block after it, so the new block events aren't all pruned
+        self.commit_block(&[]).await;
+        self.prune_to(min_readable_version).await;
+        min_readable_version
+    }
+
+    /// Prunes the state, the transactions and the events before `target_version`, and waits until
+    /// they are pruned.
+    pub async fn prune_to(&self, target_version: u64) {
+        for pruner_index in [
+            PrunerIndex::StateStorePrunerIndex,
+            PrunerIndex::LedgerPrunerIndex,
+            PrunerIndex::EventStorePrunerIndex,
+        ] {
+            self.db.prune_to(pruner_index, target_version).unwrap();
+        }
+
+        let end = Instant::now() + Duration::from_secs(10);
+        loop {
+            let state_pruned =
+                self.db.get_state_min_readable_version().unwrap() >= Some(target_version);
+            // the first transaction version is where both the transactions and events are readable
+            let ledger_pruned = self.db.get_first_txn_version().unwrap() >= Some(target_version);
+            if state_pruned && ledger_pruned {
+                return;
+            }
+            assert!(Instant::now() < end, "Timeout waiting for the pruners.");
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    }
+
     pub fn expect_status_code(&self, status_code: u16) -> TestContext {
         let mut ret = self.clone();
         ret.expect_status_code = status_code;
@@ -1,8 +1,10 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
-use super::new_test_context;
+use super::{new_prunable_test_context, new_test_context};
 use crate::current_function_name;
+use aptos_api_types::EventKey;
+use aptos_types::block_metadata::new_block_event_key;
 use percent_encoding::{utf8_percent_encode, NON_ALPHANUMERIC};
 
 static EVENT_KEY: &str =
@@ -131,3 +133,17 @@ async fn test_get_events_by_struct_type_has_generic_type_parameter() {
     let resp = context.expect_status_code(404).get(path.as_str()).await;
     context.check_golden_output(resp);
 }
+
+#[tokio::test]
+async fn test_get_pruned_events() {
+    let mut context = new_prunable_test_context(current_function_name!());
+    context.commit_blocks_and_prune().await;
+
+    // the first new block events are pruned, the last one is left
+    let event_key = EventKey::from(new_block_event_key());
+    let resp = context
+        .expect_status_code(404)
+        .get(&format!("/events/{}?start=0", event_key))
+        .await;
+    assert_eq!(resp["error_code"], "version_pruned");
+}
@@ -1,7 +1,7 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
-use super::TestContext;
+use super::{TestContext, PRUNE_TO_ONLY_PRUNER_CONFIG};
 
 mod accounts_test;
 mod events_test;
@@ -17,3 +17,8 @@ pub const API_VERSION: &str = "v0";
 pub fn new_test_context(test_name: String) -> TestContext {
     super::new_test_context(test_name, API_VERSION)
 }
+
+/// A context whose DB `TestContext::prune_to` can prune.
+pub fn new_prunable_test_context(test_name: String) -> TestContext {
+    super::new_test_context_with_pruner_config(test_name, API_VERSION, PRUNE_TO_ONLY_PRUNER_CONFIG)
+}
@@ -2,7 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use super::super::{assert_json, pretty, TestContext};
-use super::new_test_context;
+use super::{new_prunable_test_context, new_test_context};
 use crate::current_function_name;
 
 use aptos_api_types::HexEncodedBytes;
@@ -486,6 +486,25 @@ async fn test_get_transaction_by_version() {
     assert_json(resp, txns[0].clone())
 }
 
+#[tokio::test]
+async fn test_get_transaction_without_changes() {
+    let mut context = new_test_context(current_function_name!());
+    let account = context.gen_account();
+    let txn = context.create_user_account(&account);
+    context.commit_block(&vec![txn.clone()]).await;
+
+    let resp = context.get("/transactions/2").await;
+    assert!(!resp["changes"].as_array().unwrap().is_empty());
+
+    let resp = context.get("/transactions/2?include_changes=false").await;
+    assert!(resp["changes"].as_array().unwrap().is_empty());
+
+    let txns = context
+        .get("/transactions?start=2&limit=1&include_changes=false")
+        .await;
+    assert_json(txns[0].clone(), resp);
+}
+
 #[tokio::test]
 async fn test_get_pending_transaction_by_hash() {
     let mut context = new_test_context(current_function_name!());
@@ -1183,3 +1202,12 @@ fn gen_string(len: u64) -> String {
         .map(char::from)
         .collect()
 }
+
+#[tokio::test]
+async fn test_get_transaction_by_pruned_version() {
+    let mut context = new_prunable_test_context(current_function_name!());
+    context.commit_blocks_and_prune().await;
+
+    let resp = context.expect_status_code(404).get("/transactions/1").await;
+    assert_eq!(resp["error_code"], "version_pruned");
+}
@@ -2,7 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use super::super::find_value;
-use super::new_test_context;
+use super::{new_prunable_test_context, new_test_context};
 use crate::current_function_name;
 use serde_json::json;
 
@@ -215,6 +215,24 @@ async fn test_get_core_account_data_not_found() {
     context.check_golden_output(resp);
 }
 
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_account_resources_by_pruned_ledger_version() {
+    let mut context = new_prunable_test_context(current_function_name!());
+    let min_readable_version = context.commit_blocks_and_prune().await;
+
+    let resp = context
+        .expect_status_code(404)
+        .get(&account_resources_with_ledger_version("0x1", 1))
+        .await;
+    assert_eq!(resp["error_code"], "VersionPruned");
+    context
+        .get(&account_resources_with_ledger_version(
+            "0x1",
+            min_readable_version as i128,
+        ))
+        .await;
+}
+
 fn account_resources(address: &str) -> String {
     format!("/accounts/{}/resources", address)
 }
@@ -0,0 +1,67 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use super::new_test_context;
+use crate::current_function_name;
+use serde_json::json;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_batch_read() {
+    let context = new_test_context(current_function_name!());
+    let resp = context
+        .post(
+            "/batch",
+            json!({
+                "requests": [
+                    {"type": "account_request", "address": "0xa550c18"},
+                    {
+                        "type": "account_resource_request",
+                        "address": "0xa550c18",
+                        "resource_type": "0x1::guid::Generator"
+                    },
+                    {"type": "account_resources_request", "address": "0xa550c19"},
+                    {
+                        "type": "events_request",
+                        "event_key": "0x7700000000000000000000000000000000000000000000000000000000000000000000000a550c18",
+                        "limit": 10
+                    },
+                ]
+            }),
+        )
+        .await;
+
+    let results = resp.as_array().unwrap();
+    assert_eq!(results.len(), 4);
+    assert_eq!(results[0]["data"]["sequence_number"], "0");
+    assert!(results[1]["data"]["data"].is_object());
+    assert!(results[2]["error"].as_str().unwrap().contains("not found"));
+    assert_eq!(results[3]["data"], json!([]));
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_batch_read_at_ledger_version() {
+    let mut context = new_test_context(current_function_name!());
+    let ledger_version = context.get_latest_ledger_info().version();
+    let account = context.gen_account();
+    let txn = context.create_user_account(&account);
+    context.commit_block(&vec![txn]).await;
+
+    let request = json!({
+        "ledger_version": ledger_version.to_string(),
+        "requests": [
+            {"type": "account_request", "address": "0xa550c18"},
+            {"type": "account_request", "address": account.address().to_hex_literal()},
+        ]
+    });
+    let resp = context.post("/batch", request).await;
+    assert_eq!(resp[0]["data"]["sequence_number"], "0");
+    assert!(resp[1]["error"].is_string());
+
+    context
+        .expect_status_code(404)
+        .post(
+            "/batch",
+            json!({"ledger_version": "1000000", "requests": []}),
+        )
+        .await;
+}
@@ -1,8 +1,10 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
-use super::new_test_context;
+use super::{new_prunable_test_context, new_test_context};
 use crate::current_function_name;
+use aptos_api_types::EventKey;
+use aptos_types::block_metadata::new_block_event_key;
 use percent_encoding::{utf8_percent_encode, NON_ALPHANUMERIC};
 
 static EVENT_KEY: &str =
@@ -44,6 +46,29 @@ async fn test_get_events_filter_by_limit_page_size() {
     assert_eq!(resp.as_array().unwrap().len(), 2);
 }
 
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_events_by_version() {
+    let mut context = new_test_context(current_function_name!());
+    let account = context.gen_account();
+    let txn = context.create_user_account(&account);
+    context.commit_block(&vec![txn]).await;
+
+    let txn = context.get("/transactions/by_version/2").await;
+    let mut expected = txn["events"].as_array().unwrap().clone();
+    assert!(!expected.is_empty());
+    for event in expected.iter_mut() {
+        event["version"] = "2".into();
+    }
+
+    let resp = context.get("/events/by_version?start=2&limit=1").await;
+    assert_eq!(resp.as_array().unwrap(), &expected);
+
+    context
+        .expect_status_code(400)
+        .get("/events/by_version?start=1000")
+        .await;
+}
+
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
 async fn test_get_events_by_invalid_key() {
     let mut context = new_test_context(current_function_name!());
@@ -131,3 +156,23 @@ async fn test_get_events_by_struct_type_has_generic_type_parameter() {
     let resp = context.expect_status_code(404).get(path.as_str()).await;
     context.check_golden_output(resp);
 }
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_pruned_events() {
+    let mut context = new_prunable_test_context(current_function_name!());
+    context.commit_blocks_and_prune().await;
+
+    // the first new block events are pruned, the last one is left
+    let event_key = EventKey::from(new_block_event_key());
+    let resp = context
+        .expect_status_code(404)
+        .get(&format!("/events/{}?start=0", event_key))
+        .await;
+    assert_eq!(resp["error_code"], "VersionPruned");
+
+    let resp = context
+        .expect_status_code(404)
+        .get("/events/by_version?start=1&limit=1")
+        .await;
+    assert_eq!(resp["error_code"], "VersionPruned");
+}
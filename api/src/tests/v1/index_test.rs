@@ -42,6 +42,92 @@ async fn test_health_check() {
     assert_eq!(resp.status(), 200)
 }
 
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_ready_with_stale_ledger() {
+    let mut context = new_test_context(current_function_name!());
+    // The test context commits blocks with fake timestamps close to the epoch,
+    // so the ledger is always stale by wall clock time.
+    context.commit_block(&[]).await;
+    let resp = context
+        .reply(warp::test::request().method("GET").path("/-/ready"))
+        .await;
+    assert_eq!(resp.status(), 503);
+    let body: serde_json::Value = serde_json::from_slice(resp.body()).unwrap();
+    assert_eq!(body["code"], 503);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_legacy_routes_are_deprecated() {
+    let context = new_test_context(current_function_name!());
+    let resp = context
+        .reply(warp::test::request().method("GET").path("/transactions"))
+        .await;
+    assert_eq!(resp.headers()["deprecation"], "true");
+    assert_eq!(resp.headers()["link"], "</v1>; rel=\"successor-version\"");
+
+    let resp = context
+        .reply(warp::test::request().method("GET").path("/v1/transactions"))
+        .await;
+    assert!(resp.headers().get("deprecation").is_none());
+
+    let resp = context
+        .reply(warp::test::request().method("GET").path("/-/healthy"))
+        .await;
+    assert!(resp.headers().get("deprecation").is_none());
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_request_id() {
+    let context = new_test_context(current_function_name!());
+    let resp = context
+        .reply(
+            warp::test::request()
+                .method("GET")
+                .path("/v1/transactions/by_version/1000000")
+                .header("x-request-id", "my-request"),
+        )
+        .await;
+    assert_eq!(resp.status(), 404);
+    assert_eq!(resp.headers()["x-request-id"], "my-request");
+    let body: serde_json::Value = serde_json::from_slice(resp.body()).unwrap();
+    assert_eq!(body["request_id"], "my-request");
+
+    // Without one from the client, an id is generated.
+    let resp = context
+        .reply(warp::test::request().method("GET").path("/-/healthy"))
+        .await;
+    assert_eq!(resp.status(), 200);
+    assert!(!resp.headers()["x-request-id"].is_empty());
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_compression() {
+    let context = new_test_context(current_function_name!());
+    for (accept_encoding, content_encoding) in [
+        ("gzip", Some("gzip")),
+        ("gzip, br", Some("br")),
+        ("br;q=0, gzip", Some("gzip")),
+        ("br;q=0.000, gzip", Some("gzip")),
+        ("identity", None),
+    ] {
+        let resp = context
+            .reply(
+                warp::test::request()
+                    .method("GET")
+                    .path("/v1/transactions")
+                    .header("accept-encoding", accept_encoding),
+            )
+            .await;
+        assert_eq!(resp.status(), 200);
+        assert_eq!(
+            resp.headers()
+                .get("content-encoding")
+                .map(|v| v.to_str().unwrap()),
+            content_encoding
+        );
+    }
+}
+
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
 async fn test_openapi_spec() {
     let context = new_test_context(current_function_name!());
@@ -2,19 +2,26 @@
 // SPDX-License-Identifier: Apache-2.0
 
 mod accounts_test;
+mod batch_test;
 mod converter_test;
 mod events_test;
 mod index_test;
 mod invalid_post_request_test;
+mod proofs_test;
 mod state_test;
 mod string_resource_test;
 mod transaction_vector_test;
 mod transactions_test;
 
-use super::TestContext;
+use super::{TestContext, PRUNE_TO_ONLY_PRUNER_CONFIG};
 
 pub const API_VERSION: &str = "v1";
 
 pub fn new_test_context(test_name: String) -> TestContext {
     super::new_test_context(test_name, API_VERSION)
 }
+
+/// A context whose DB `TestContext::prune_to` can prune.
+pub fn new_prunable_test_context(test_name: String) -> TestContext {
+    super::new_test_context_with_pruner_config(test_name, API_VERSION, PRUNE_TO_ONLY_PRUNER_CONFIG)
+}
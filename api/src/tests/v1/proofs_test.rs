@@ -0,0 +1,66 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use super::new_test_context;
+use crate::current_function_name;
+use aptos_api_types::HexEncodedBytes;
+use aptos_types::{
+    access_path::AccessPath,
+    account_address::AccountAddress,
+    epoch_change::EpochChangeProof,
+    ledger_info::LedgerInfoWithSignatures,
+    state_store::{state_key::StateKey, state_value::StateValueWithProof},
+};
+use move_deps::move_core_types::{language_storage::ResourceKey, parser::parse_struct_tag};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+fn decode<T: DeserializeOwned>(resp: Value) -> T {
+    let bytes: HexEncodedBytes = serde_json::from_value(resp).unwrap();
+    bcs::from_bytes(&bytes.0).unwrap()
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_ledger_info_with_signatures() {
+    let context = new_test_context(current_function_name!());
+    let resp = context.get("/ledger_info").await;
+    let ledger_info: LedgerInfoWithSignatures = decode(resp);
+    assert_eq!(
+        ledger_info.ledger_info().version(),
+        context.get_latest_ledger_info().version()
+    );
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_epoch_change_proof() {
+    let context = new_test_context(current_function_name!());
+    let resp = context.get("/epochs/proof?start_epoch=0&end_epoch=1").await;
+    let proof: EpochChangeProof = decode(resp);
+    assert_eq!(proof.ledger_info_with_sigs.len(), 1);
+    assert!(!proof.more);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_epoch_change_proof_with_invalid_range() {
+    let context = new_test_context(current_function_name!());
+    context
+        .expect_status_code(400)
+        .get("/epochs/proof?start_epoch=2&end_epoch=1")
+        .await;
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_account_resource_with_proof() {
+    let context = new_test_context(current_function_name!());
+    let resp = context
+        .get("/accounts/0xA550C18/resource/0x1::guid::Generator/proof")
+        .await;
+    let (ledger_info, proof): (LedgerInfoWithSignatures, StateValueWithProof) = decode(resp);
+
+    let state_key = StateKey::AccessPath(AccessPath::resource_access_path(ResourceKey::new(
+        AccountAddress::from_hex_literal("0xA550C18").unwrap(),
+        parse_struct_tag("0x1::guid::Generator").unwrap(),
+    )));
+    assert!(proof.value.is_some());
+    proof.verify(ledger_info.ledger_info(), &state_key).unwrap();
+}
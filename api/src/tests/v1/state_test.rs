@@ -4,11 +4,16 @@
 use super::super::TestContext;
 use super::new_test_context;
 use crate::current_function_name;
+use aptos_api_types::{mime_types, RawTableItemRequest};
 use aptos_sdk::types::LocalAccount;
-use move_deps::{move_core_types::account_address::AccountAddress, move_package::BuildConfig};
+use move_deps::{
+    move_core_types::{account_address::AccountAddress, language_storage::TypeTag},
+    move_package::BuildConfig,
+};
 use serde::Serialize;
 use serde_json::{json, Value};
 use std::{convert::TryInto, path::PathBuf};
+use warp::http::header::CONTENT_TYPE;
 
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
 async fn test_get_account_resource() {
@@ -174,6 +179,59 @@ async fn test_get_table_item() {
     assert_table_item(ctx, &nested_table, "u8", "u8", 2, 3).await;
 }
 
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_table_item_with_bcs_key() {
+    let mut context = new_test_context(current_function_name!());
+    let ctx = &mut context;
+    let mut account = ctx.gen_account();
+    let acc = &mut account;
+    let txn = ctx.create_user_account(acc);
+    ctx.commit_block(&vec![txn.clone()]).await;
+    make_test_tables(ctx, acc).await;
+
+    let tt = ctx
+        .api_get_account_resource(
+            acc,
+            &acc.address().to_hex_literal(),
+            "TableTestData",
+            "TestTables",
+        )
+        .await["data"]
+        .to_owned();
+    let handle: u128 = tt["u64_table"]["handle"].as_str().unwrap().parse().unwrap();
+
+    let request = RawTableItemRequest {
+        key_type: TypeTag::U64,
+        value_type: TypeTag::U64,
+        key: bcs::to_bytes(&1u64).unwrap(),
+    };
+    let resp = ctx
+        .execute(
+            warp::test::request()
+                .method("POST")
+                .path(&ctx.prepend_path(&get_table_item(handle)))
+                .header(CONTENT_TYPE, mime_types::BCS_TABLE_ITEM_REQUEST)
+                .body(bcs::to_bytes(&request).unwrap()),
+        )
+        .await;
+    assert_eq!(resp, json!("1"));
+
+    // The key must be a valid value of the key type.
+    let request = RawTableItemRequest {
+        key: vec![1],
+        ..request
+    };
+    ctx.expect_status_code(400)
+        .execute(
+            warp::test::request()
+                .method("POST")
+                .path(&ctx.prepend_path(&get_table_item(handle)))
+                .header(CONTENT_TYPE, mime_types::BCS_TABLE_ITEM_REQUEST)
+                .body(bcs::to_bytes(&request).unwrap()),
+        )
+        .await;
+}
+
 fn get_account_resource(address: &str, struct_tag: &str) -> String {
     format!("/accounts/{}/resource/{}", address, struct_tag)
 }
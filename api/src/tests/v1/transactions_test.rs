@@ -2,7 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use super::super::{assert_json, pretty, TestContext};
-use super::new_test_context;
+use super::{new_prunable_test_context, new_test_context};
 use crate::current_function_name;
 
 use aptos_crypto::{
@@ -15,7 +15,7 @@ use aptos_types::{
     account_address::AccountAddress,
     transaction::{
         authenticator::{AuthenticationKey, TransactionAuthenticator},
-        ChangeSet, Script, ScriptFunction, SignedTransaction,
+        ChangeSet, RawTransactionWithData, Script, ScriptFunction, SignedTransaction,
     },
     utility_coin::APTOS_COIN_TYPE,
     write_set::{WriteOp, WriteSetMut},
@@ -114,6 +114,106 @@ async fn test_get_transactions_param_limit_exceeds_limit() {
     context.check_golden_output(resp);
 }
 
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_transactions_with_cursor_is_pinned_to_first_page_ledger_version() {
+    let mut context = new_test_context(current_function_name!());
+    let account = context.gen_account();
+    let txn = context.create_user_account(&account);
+    context.commit_block(&vec![txn.clone()]).await;
+    let ledger_version = context.get_latest_ledger_info().version();
+
+    let resp = context
+        .reply(
+            warp::test::request()
+                .method("GET")
+                .path(&context.prepend_path("/transactions?start=0&limit=2")),
+        )
+        .await;
+    let cursor = resp.headers()["x-aptos-cursor"]
+        .to_str()
+        .unwrap()
+        .to_string();
+
+    // Advance the ledger, the cursor must keep reading at the old version.
+    let account = context.gen_account();
+    let txn = context.create_user_account(&account);
+    context.commit_block(&vec![txn.clone()]).await;
+
+    let resp =
+        context
+            .reply(warp::test::request().method("GET").path(
+                &context.prepend_path(&format!("/transactions?cursor={}&limit=1000", cursor)),
+            ))
+            .await;
+    assert!(resp.headers().get("x-aptos-cursor").is_none());
+    let txns: serde_json::Value = serde_json::from_slice(resp.body()).unwrap();
+    let txns = txns.as_array().unwrap();
+    assert_eq!(txns[0]["version"], "2");
+    assert_eq!(txns.last().unwrap()["version"], ledger_version.to_string());
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_transactions_in_descending_order() {
+    let mut context = new_test_context(current_function_name!());
+    let account = context.gen_account();
+    let txn = context.create_user_account(&account);
+    context.commit_block(&vec![txn.clone()]).await;
+
+    let txns = context
+        .get("/transactions?start=2&limit=2&order=desc")
+        .await;
+    let versions: Vec<_> = txns
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|txn| txn["version"].clone())
+        .collect();
+    assert_eq!(versions, vec![json!("2"), json!("1")]);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_transactions_rejects_cursor_with_start() {
+    let context = new_test_context(current_function_name!());
+    context
+        .expect_status_code(400)
+        .get("/transactions?start=0&cursor=00")
+        .await;
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_transactions_filtered_by_type_and_sender() {
+    let mut context = new_test_context(current_function_name!());
+    let account = context.gen_account();
+    let txn = context.create_user_account(&account);
+    context.commit_block(&vec![txn.clone()]).await;
+
+    let txns = context.get("/transactions?start=0&type=user").await;
+    let txns = txns.as_array().unwrap();
+    assert_eq!(txns.len(), 1);
+    assert_eq!(txns[0]["type"], "user_transaction");
+
+    let txns = context
+        .get("/transactions?start=0&type=block_metadata")
+        .await;
+    assert_eq!(txns.as_array().unwrap().len(), 1);
+    assert_eq!(txns[0]["type"], "block_metadata_transaction");
+
+    let sender = context.root_account().address().to_hex_literal();
+    let txns = context
+        .get(&format!("/transactions?start=0&sender={}", sender))
+        .await;
+    assert_eq!(txns.as_array().unwrap().len(), 1);
+    assert_eq!(txns[0]["sender"], sender);
+
+    let txns = context
+        .get(&format!(
+            "/transactions?start=0&sender={}",
+            account.address().to_hex_literal()
+        ))
+        .await;
+    assert!(txns.as_array().unwrap().is_empty());
+}
+
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
 async fn test_get_transactions_output_user_transaction_with_script_function_payload() {
     let mut context = new_test_context(current_function_name!());
@@ -477,6 +577,27 @@ async fn test_get_transaction_by_version() {
     assert_json(resp, txns[0].clone())
 }
 
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_transaction_without_changes() {
+    let mut context = new_test_context(current_function_name!());
+    let account = context.gen_account();
+    let txn = context.create_user_account(&account);
+    context.commit_block(&vec![txn.clone()]).await;
+
+    let resp = context.get("/transactions/by_version/2").await;
+    assert!(!resp["changes"].as_array().unwrap().is_empty());
+
+    let resp = context
+        .get("/transactions/by_version/2?include_changes=false")
+        .await;
+    assert!(resp["changes"].as_array().unwrap().is_empty());
+
+    let txns = context
+        .get("/transactions?start=2&limit=1&include_changes=false")
+        .await;
+    assert_json(txns[0].clone(), resp);
+}
+
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
 async fn test_get_pending_transaction_by_hash() {
     let mut context = new_test_context(current_function_name!());
@@ -1196,6 +1317,89 @@ async fn test_create_signing_message_rejects_no_content_length_request() {
     context.check_golden_output(resp);
 }
 
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_stream_transactions_with_start_version_is_too_large() {
+    let context = new_test_context(current_function_name!());
+    let resp = context
+        .expect_status_code(404)
+        .get("/stream/transactions?start=1000000")
+        .await;
+    assert_eq!(resp["code"], 404);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_estimate_gas_price() {
+    let mut context = new_test_context(current_function_name!());
+    let account = context.gen_account();
+    let txn = context.create_user_account(&account);
+    context.commit_block(&vec![txn.clone()]).await;
+
+    let resp = context.get("/estimate_gas_price").await;
+    let low: u64 = resp["low_gas_unit_price"]
+        .as_str()
+        .unwrap()
+        .parse()
+        .unwrap();
+    let median: u64 = resp["gas_unit_price"].as_str().unwrap().parse().unwrap();
+    let priority: u64 = resp["priority_gas_unit_price"]
+        .as_str()
+        .unwrap()
+        .parse()
+        .unwrap();
+    assert!(low <= median);
+    assert!(median <= priority);
+    assert_eq!(median, txn.gas_unit_price());
+
+    // Only the versions committed since are read into the window.
+    let account = context.gen_account();
+    let txn = context.create_user_account(&account);
+    context.commit_block(&vec![txn.clone()]).await;
+    let resp = context.get("/estimate_gas_price").await;
+    assert_eq!(
+        resp["gas_unit_price"].as_str().unwrap(),
+        txn.gas_unit_price().to_string()
+    );
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_signing_message_with_secondary_signers() {
+    let mut context = new_test_context(current_function_name!());
+    let account = context.gen_account();
+    let secondary_signer = context.gen_account();
+    let txn = context.create_user_account(&account);
+    let sender = context.root_account();
+    let body = json!({
+        "sender": sender.address().to_hex_literal(),
+        "sequence_number": sender.sequence_number().to_string(),
+        "gas_unit_price": txn.gas_unit_price().to_string(),
+        "max_gas_amount": txn.max_gas_amount().to_string(),
+        "expiration_timestamp_secs": txn.expiration_timestamp_secs().to_string(),
+        "payload": {
+            "type": "script_function_payload",
+            "function": {
+                "module": {
+                    "address": "0x1",
+                    "name": "account",
+                },
+                "name": "create_account",
+            },
+            "type_arguments": [],
+            "arguments": [account.address().to_hex_literal()]
+        },
+        "secondary_signers": [secondary_signer.address().to_hex_literal()],
+    });
+
+    let resp = context.post("/transactions/encode_submission", body).await;
+    let signing_msg = context
+        .api_specific_config
+        .unwrap_signing_message_response(resp);
+    let expected = aptos_crypto::signing_message(&RawTransactionWithData::new_multi_agent(
+        txn.into_raw_transaction(),
+        vec![secondary_signer.address()],
+    ));
+    assert_eq!(signing_msg.inner(), &expected[..]);
+}
+
 fn gen_string(len: u64) -> String {
     let mut rng = thread_rng();
     std::iter::repeat(())
@@ -1209,3 +1413,15 @@ fn gen_string(len: u64) -> String {
 fn build_path(path: &str) -> String {
     format!("/v1/transactions{}", path)
 }
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_transaction_by_pruned_version() {
+    let mut context = new_prunable_test_context(current_function_name!());
+    context.commit_blocks_and_prune().await;
+
+    let resp = context
+        .expect_status_code(404)
+        .get("/transactions/by_version/1")
+        .await;
+    assert_eq!(resp["error_code"], "VersionPruned");
+}
@@ -8,6 +8,7 @@ use crate::{
     metrics::metrics,
     page::Page,
     param::{AddressParam, TransactionIdParam},
+    poem_backend::without_changes,
 };
 
 use aptos_api_types::{
@@ -27,6 +28,7 @@ use aptos_vm::AptosVM;
 
 use anyhow::Result;
 use aptos_types::transaction::{ExecutionStatus, TransactionInfo, TransactionStatus};
+use serde::Deserialize;
 use warp::{
     filters::BoxedFilter,
     http::{
@@ -40,8 +42,9 @@ use warp::{
 pub fn get_json_transaction(context: Context) -> BoxedFilter<(impl Reply,)> {
     warp::path!("transactions" / TransactionIdParam)
         .and(warp::get())
+        .and(warp::query::<ChangesParam>())
         .and(context.filter())
-        .map(|id, context| (id, context, AcceptType::Json))
+        .map(|id, changes, context| (id, changes, context, AcceptType::Json))
         .untuple_one()
         .and_then(handle_get_transaction)
         .with(metrics("get_json_transaction"))
@@ -53,21 +56,25 @@ pub fn get_bcs_transaction(context: Context) -> BoxedFilter<(impl Reply,)> {
     warp::path!("transactions" / TransactionIdParam)
         .and(warp::get())
         .and(warp::header::exact_ignore_case(ACCEPT.as_str(), BCS))
+        .and(warp::query::<ChangesParam>())
         .and(context.filter())
-        .map(|id, context| (id, context, AcceptType::Bcs))
+        .map(|id, changes, context| (id, changes, context, AcceptType::Bcs))
         .untuple_one()
         .and_then(handle_get_transaction)
         .with(metrics("get_bcs_transaction"))
         .boxed()
 }
 
-// GET /transactions?start={u64}&limit={u16}
+// GET /transactions?start={u64}&limit={u16}&include_changes={bool}
 pub fn get_json_transactions(context: Context) -> BoxedFilter<(impl Reply,)> {
     warp::path!("transactions")
         .and(warp::get())
         .and(warp::query::<Page>())
+        .and(warp::query::<ChangesParam>())
         .and(context.filter())
-        .map(|page: Page, context: Context| (page, context, AcceptType::Json))
+        .map(|page: Page, changes: ChangesParam, context: Context| {
+            (page, changes, context, AcceptType::Json)
+        })
         .untuple_one()
         .and_then(handle_get_transactions)
         .with(metrics("get_json_transactions"))
@@ -80,19 +87,23 @@ pub fn get_bcs_transactions(context: Context) -> BoxedFilter<(impl Reply,)> {
         .and(warp::get())
         .and(warp::header::exact_ignore_case(ACCEPT.as_str(), BCS))
         .and(warp::query::<Page>())
+        .and(warp::query::<ChangesParam>())
         .and(context.filter())
-        .map(|page: Page, context: Context| (page, context, AcceptType::Bcs))
+        .map(|page: Page, changes: ChangesParam, context: Context| {
+            (page, changes, context, AcceptType::Bcs)
+        })
         .untuple_one()
         .and_then(handle_get_transactions)
         .with(metrics("get_bcs_transactions"))
         .boxed()
 }
 
-// GET /accounts/{address}/transactions?start={u64}&limit={u16}
+// GET /accounts/{address}/transactions?start={u64}&limit={u16}&include_changes={bool}
 pub fn get_account_transactions(context: Context) -> BoxedFilter<(impl Reply,)> {
     warp::path!("accounts" / AddressParam / "transactions")
         .and(warp::get())
         .and(warp::query::<Page>())
+        .and(warp::query::<ChangesParam>())
         .and(context.filter())
         .and_then(handle_get_account_transactions)
         .with(metrics("get_account_transactions"))
@@ -184,31 +195,51 @@ pub fn create_signing_message(context: Context) -> BoxedFilter<(impl Reply,)> {
 
 async fn handle_get_transaction(
     id: TransactionIdParam,
+    changes: ChangesParam,
     context: Context,
     accept_type: AcceptType,
 ) -> Result<impl Reply, Rejection> {
     fail_point("endpoint_get_transaction")?;
     Ok(Transactions::new(context)?
-        .get_transaction(id.parse("transaction hash or version")?, accept_type)
+        .get_transaction(
+            id.parse("transaction hash or version")?,
+            accept_type,
+            changes.include_changes(),
+        )
         .await?)
 }
 
 async fn handle_get_transactions(
     page: Page,
+    changes: ChangesParam,
     context: Context,
     accept_type: AcceptType,
 ) -> Result<impl Reply, Rejection> {
     fail_point("endpoint_get_transactions")?;
-    Ok(Transactions::new(context)?.list(page, accept_type)?)
+    Ok(Transactions::new(context)?.list(page, accept_type, changes.include_changes())?)
 }
 
 async fn handle_get_account_transactions(
     address: AddressParam,
     page: Page,
+    changes: ChangesParam,
     context: Context,
 ) -> Result<impl Reply, Rejection> {
     fail_point("endpoint_get_account_transactions")?;
-    Ok(Transactions::new(context)?.list_by_account(address, page)?)
+    Ok(Transactions::new(context)?.list_by_account(address, page, changes.include_changes())?)
+}
+
+/// Committed transactions carry their decoded write set changes unless
+/// `include_changes=false` is given.
+#[derive(Clone, Debug, Deserialize)]
+struct ChangesParam {
+    include_changes: Option<bool>,
+}
+
+impl ChangesParam {
+    fn include_changes(&self) -> bool {
+        self.include_changes.unwrap_or(true)
+    }
 }
 
 async fn handle_submit_json_transactions(
@@ -323,13 +354,16 @@ impl Transactions {
             MempoolStatusCode::MempoolIsFull => {
                 Err(Error::insufficient_storage(mempool_status.message))
             }
-            MempoolStatusCode::VmError => Err(Error::bad_request(format!(
-                "invalid transaction: {}",
-                vm_status_opt
-                    .map(|s| format!("{:?}", s))
-                    .unwrap_or_else(|| "UNKNOWN".to_owned())
-            ))),
-            _ => Err(Error::bad_request(format!(
+            MempoolStatusCode::VmError => Err(Error::vm_error(
+                format!(
+                    "invalid transaction: {}",
+                    vm_status_opt
+                        .map(|s| format!("{:?}", s))
+                        .unwrap_or_else(|| "UNKNOWN".to_owned())
+                ),
+                vm_status_opt.map(|s| s as u64),
+            )),
+            _ => Err(Error::mempool_rejected(format!(
                 "transaction is rejected: {}",
                 mempool_status,
             ))),
@@ -371,12 +405,17 @@ impl Transactions {
             changes: output.write_set().clone(),
         };
 
-        self.render_transactions(vec![simulated_txn], AcceptType::Json)
+        self.render_transactions(vec![simulated_txn], AcceptType::Json, true)
     }
 
-    pub fn list(self, page: Page, accept_type: AcceptType) -> Result<impl Reply, Error> {
+    pub fn list(
+        self,
+        page: Page,
+        accept_type: AcceptType,
+        include_changes: bool,
+    ) -> Result<impl Reply, Error> {
         let ledger_version = self.ledger_info.version();
-        let limit = page.limit()?;
+        let limit = page.limit(self.context.max_transactions_page_size())?;
         let last_page_start = if ledger_version > (limit as u64) {
             ledger_version - (limit as u64)
         } else {
@@ -388,23 +427,29 @@ impl Transactions {
             .context
             .get_transactions(start_version, limit, ledger_version)?;
 
-        self.render_transactions(data, accept_type)
+        self.render_transactions(data, accept_type, include_changes)
     }
 
-    pub fn list_by_account(self, address: AddressParam, page: Page) -> Result<impl Reply, Error> {
+    pub fn list_by_account(
+        self,
+        address: AddressParam,
+        page: Page,
+        include_changes: bool,
+    ) -> Result<impl Reply, Error> {
         let data = self.context.get_account_transactions(
             address.parse("account address")?.into(),
             page.start(0, u64::MAX)?,
-            page.limit()?,
+            page.limit(self.context.max_transactions_page_size())?,
             self.ledger_info.version(),
         )?;
-        self.render_transactions(data, AcceptType::Json)
+        self.render_transactions(data, AcceptType::Json, include_changes)
     }
 
     fn render_transactions(
         self,
         data: Vec<TransactionOnChainData>,
         accept_type: AcceptType,
+        include_changes: bool,
     ) -> Result<impl Reply, Error> {
         if accept_type == AcceptType::Bcs {
             return Response::new_bcs(self.ledger_info, &data);
@@ -420,7 +465,8 @@ impl Transactions {
             .map(|t| {
                 let version = t.version;
                 let timestamp = self.context.get_block_timestamp(version)?;
-                let txn = converter.try_into_onchain_transaction(timestamp, t)?;
+                let txn = converter
+                    .try_into_onchain_transaction(timestamp, without_changes(t, include_changes))?;
                 Ok(txn)
             })
             .collect::<Result<_>>()?;
@@ -431,10 +477,21 @@ impl Transactions {
         self,
         id: TransactionId,
         accept_type: AcceptType,
+        include_changes: bool,
     ) -> Result<impl Reply, Error> {
         let txn_data = match id.clone() {
             TransactionId::Hash(hash) => self.get_by_hash(hash.into()).await?,
-            TransactionId::Version(version) => self.get_by_version(version.0)?,
+            TransactionId::Version(version) => {
+                if version.0 < self.ledger_info.oldest_ledger_version.0 {
+                    return Err(Error::version_pruned(
+                        "Transaction",
+                        version.0,
+                        self.ledger_info.oldest_ledger_version.0,
+                        self.ledger_info.version(),
+                    ));
+                }
+                self.get_by_version(version.0)?
+            }
         }
         .ok_or_else(|| self.transaction_not_found(id))?;
 
@@ -448,7 +505,10 @@ impl Transactions {
                 let timestamp = self.context.get_block_timestamp(txn.version)?;
                 resolver
                     .as_converter(self.context.db.clone())
-                    .try_into_onchain_transaction(timestamp, txn)?
+                    .try_into_onchain_transaction(
+                        timestamp,
+                        without_changes(txn, include_changes),
+                    )?
             }
             TransactionData::Pending(txn) => resolver
                 .as_converter(self.context.db.clone())
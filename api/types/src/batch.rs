@@ -0,0 +1,74 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{Address, EventKey, MoveStructTag, U64};
+use poem_openapi::{Object, Union};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// A list of reads served together at a single ledger version.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Object)]
+pub struct BatchReadRequest {
+    /// The ledger version every read is served at, defaults to the latest.
+    pub ledger_version: Option<U64>,
+    pub requests: Vec<ReadRequest>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Union)]
+#[serde(tag = "type", rename_all = "snake_case")]
+#[oai(one_of, discriminator_name = "type", rename_all = "snake_case")]
+pub enum ReadRequest {
+    AccountRequest(AccountRequest),
+    AccountResourcesRequest(AccountResourcesRequest),
+    AccountResourceRequest(AccountResourceRequest),
+    EventsRequest(EventsRequest),
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Object)]
+pub struct AccountRequest {
+    pub address: Address,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Object)]
+pub struct AccountResourcesRequest {
+    pub address: Address,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Object)]
+pub struct AccountResourceRequest {
+    pub address: Address,
+    pub resource_type: MoveStructTag,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Object)]
+pub struct EventsRequest {
+    pub event_key: EventKey,
+    pub start: Option<U64>,
+    pub limit: Option<u16>,
+}
+
+/// The outcome of a single read of a batch: exactly one of `data` and
+/// `error` is set.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Object)]
+pub struct ReadResult {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl ReadResult {
+    pub fn data(data: Value) -> Self {
+        Self {
+            data: Some(data),
+            error: None,
+        }
+    }
+
+    pub fn error(error: String) -> Self {
+        Self {
+            data: None,
+            error: Some(error),
+        }
+    }
+}
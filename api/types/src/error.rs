@@ -10,10 +10,72 @@ use warp::{http::StatusCode, reject::Reject};
 
 use crate::U64;
 
+/// A stable, machine readable classification of an error, so that clients
+/// do not need to match on the status code or message.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCode {
+    /// The request was malformed or referred to something invalid.
+    InvalidInput,
+    NotFound,
+    /// The request was refused, e.g. by the CORS policy.
+    Forbidden,
+    MethodNotAllowed,
+    PayloadTooLarge,
+    UnsupportedMediaType,
+    /// The client or the server as a whole sent too many requests.
+    RateLimited,
+    /// Mempool has no space left to accept the submitted transaction.
+    MempoolFull,
+    /// Mempool rejected the submitted transaction, e.g. because its sequence
+    /// number is too old.
+    MempoolRejected,
+    /// The VM rejected the submitted transaction, see `vm_error_code`.
+    VmError,
+    /// The node is not ready to serve requests, e.g. it is too far behind.
+    ServiceUnavailable,
+    /// The requested version has been pruned from the node, the message says
+    /// the oldest version it still has.
+    VersionPruned,
+    Internal,
+    /// Errors from servers which predate error codes carry none, only the
+    /// status code in `code` applies to them.
+    Unknown,
+}
+
+impl Default for ErrorCode {
+    fn default() -> Self {
+        ErrorCode::Unknown
+    }
+}
+
+impl From<StatusCode> for ErrorCode {
+    fn from(code: StatusCode) -> Self {
+        match code {
+            StatusCode::NOT_FOUND => ErrorCode::NotFound,
+            StatusCode::FORBIDDEN => ErrorCode::Forbidden,
+            StatusCode::METHOD_NOT_ALLOWED => ErrorCode::MethodNotAllowed,
+            StatusCode::PAYLOAD_TOO_LARGE => ErrorCode::PayloadTooLarge,
+            StatusCode::UNSUPPORTED_MEDIA_TYPE => ErrorCode::UnsupportedMediaType,
+            StatusCode::TOO_MANY_REQUESTS => ErrorCode::RateLimited,
+            StatusCode::INSUFFICIENT_STORAGE => ErrorCode::MempoolFull,
+            StatusCode::SERVICE_UNAVAILABLE => ErrorCode::ServiceUnavailable,
+            code if code.is_client_error() => ErrorCode::InvalidInput,
+            _ => ErrorCode::Internal,
+        }
+    }
+}
+
 #[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
 pub struct Error {
     pub code: u16,
     pub message: String,
+    #[serde(default)]
+    pub error_code: ErrorCode,
+    /// The VM status code the transaction was rejected with, only set
+    /// alongside `ErrorCode::VmError`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub vm_error_code: Option<u64>,
     /// Aptos blockchain latest onchain ledger version.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub aptos_ledger_version: Option<U64>,
@@ -24,6 +86,8 @@ impl Error {
         Self {
             code: code.as_u16(),
             message,
+            error_code: code.into(),
+            vm_error_code: None,
             aptos_ledger_version: None,
         }
     }
@@ -44,6 +108,43 @@ impl Error {
         .aptos_ledger_version(ledger_version)
     }
 
+    pub fn version_pruned(
+        resource: &str,
+        version: u64,
+        min_readable_version: u64,
+        ledger_version: u64,
+    ) -> Self {
+        let mut error = Self::new(
+            StatusCode::NOT_FOUND,
+            format!(
+                "{} at version {} has been pruned, the oldest version available is {}",
+                resource, version, min_readable_version
+            ),
+        )
+        .aptos_ledger_version(ledger_version);
+        error.error_code = ErrorCode::VersionPruned;
+        error
+    }
+
+    /// Like `version_pruned`, for the events looked up by sequence number.
+    pub fn event_pruned<K: Display>(
+        event_key: K,
+        sequence_number: u64,
+        min_readable_version: u64,
+        ledger_version: u64,
+    ) -> Self {
+        let mut error = Self::new(
+            StatusCode::NOT_FOUND,
+            format!(
+                "Event {} of {} has been pruned, the oldest version available is {}",
+                sequence_number, event_key, min_readable_version
+            ),
+        )
+        .aptos_ledger_version(ledger_version);
+        error.error_code = ErrorCode::VersionPruned;
+        error
+    }
+
     pub fn invalid_param<S: Display>(name: &str, value: S) -> Self {
         Self::bad_request(format!("invalid parameter {}: {}", name, value))
     }
@@ -56,6 +157,19 @@ impl Error {
         Self::new(StatusCode::INSUFFICIENT_STORAGE, msg.to_string())
     }
 
+    pub fn vm_error<S: Display>(msg: S, vm_error_code: Option<u64>) -> Self {
+        let mut error = Self::bad_request(msg);
+        error.error_code = ErrorCode::VmError;
+        error.vm_error_code = vm_error_code;
+        error
+    }
+
+    pub fn mempool_rejected<S: Display>(msg: S) -> Self {
+        let mut error = Self::bad_request(msg);
+        error.error_code = ErrorCode::MempoolRejected;
+        error
+    }
+
     pub fn internal(err: anyhow::Error) -> Self {
         Self::from_anyhow_error(StatusCode::INTERNAL_SERVER_ERROR, err)
     }
@@ -96,7 +210,8 @@ impl From<serde_json::error::Error> for Error {
 
 #[cfg(test)]
 mod tests {
-    use crate::error::Error;
+    use crate::error::{Error, ErrorCode};
+    use serde_json::json;
     use warp::http::StatusCode;
 
     #[test]
@@ -126,4 +241,78 @@ mod tests {
         let err = Error::internal(anyhow::format_err!("hello"));
         assert_eq!(err.to_string(), "500 Internal Server Error: hello")
     }
+
+    #[test]
+    fn test_error_code_from_status_code() {
+        assert_eq!(Error::bad_request("x").error_code, ErrorCode::InvalidInput);
+        assert_eq!(
+            Error::not_found("account", "0x1", 0).error_code,
+            ErrorCode::NotFound
+        );
+        assert_eq!(
+            Error::insufficient_storage("x").error_code,
+            ErrorCode::MempoolFull
+        );
+        assert_eq!(
+            Error::internal(anyhow::format_err!("x")).error_code,
+            ErrorCode::Internal
+        );
+        assert_eq!(
+            Error::new(StatusCode::FORBIDDEN, "x".to_owned()).error_code,
+            ErrorCode::Forbidden
+        );
+        assert_eq!(
+            Error::new(StatusCode::METHOD_NOT_ALLOWED, "x".to_owned()).error_code,
+            ErrorCode::MethodNotAllowed
+        );
+    }
+
+    #[test]
+    fn test_version_pruned() {
+        let err = Error::version_pruned("State", 1, 10, 20);
+        assert_eq!(err.status_code(), StatusCode::NOT_FOUND);
+        assert_eq!(err.error_code, ErrorCode::VersionPruned);
+        assert_eq!(
+            err.to_string(),
+            "404 Not Found: State at version 1 has been pruned, the oldest version available is \
+             10\nAptos ledger version: 20"
+        );
+    }
+
+    #[test]
+    fn test_event_pruned() {
+        let err = Error::event_pruned("0x1", 1, 10, 20);
+        assert_eq!(err.status_code(), StatusCode::NOT_FOUND);
+        assert_eq!(err.error_code, ErrorCode::VersionPruned);
+        assert_eq!(
+            err.to_string(),
+            "404 Not Found: Event 1 of 0x1 has been pruned, the oldest version available is 10\
+             \nAptos ledger version: 20"
+        );
+    }
+
+    #[test]
+    fn test_error_without_error_code() {
+        let err: Error = serde_json::from_value(json!({
+            "code": 404,
+            "message": "Not Found",
+        }))
+        .unwrap();
+        assert_eq!(err.error_code, ErrorCode::Unknown);
+        assert_eq!(err.status_code(), StatusCode::NOT_FOUND);
+    }
+
+    #[test]
+    fn test_vm_error_json() {
+        let err = Error::vm_error("invalid transaction: INVALID_SIGNATURE", Some(1));
+        assert_eq!(
+            serde_json::to_value(&err).unwrap(),
+            json!({
+                "code": 400,
+                "message": "invalid transaction: INVALID_SIGNATURE",
+                "error_code": "vm_error",
+                "vm_error_code": 1,
+            })
+        );
+    }
 }
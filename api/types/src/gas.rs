@@ -0,0 +1,29 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::U64;
+use poem_openapi::Object as PoemObject;
+use serde::{Deserialize, Serialize};
+
+/// Gas unit prices derived from the user transactions committed in the most
+/// recent versions of the ledger. None of them is ever below the minimum gas
+/// unit price the chain accepts.
+#[derive(Clone, Debug, Deserialize, PartialEq, PoemObject, Serialize)]
+pub struct GasEstimation {
+    /// A price likely to get a transaction committed when the chain is idle.
+    pub low_gas_unit_price: U64,
+    /// The median price paid recently.
+    pub gas_unit_price: U64,
+    /// A price likely to get a transaction prioritized over most others.
+    pub priority_gas_unit_price: U64,
+}
+
+impl GasEstimation {
+    pub fn new(low_gas_unit_price: u64, gas_unit_price: u64, priority_gas_unit_price: u64) -> Self {
+        Self {
+            low_gas_unit_price: low_gas_unit_price.into(),
+            gas_unit_price: gas_unit_price.into(),
+            priority_gas_unit_price: priority_gas_unit_price.into(),
+        }
+    }
+}
@@ -3,12 +3,14 @@
 
 mod account;
 mod address;
+mod batch;
 mod block;
 mod bytecode;
 mod convert;
 mod derives;
 mod error;
 mod event_key;
+mod gas;
 mod hash;
 mod index;
 mod ledger_info;
@@ -21,11 +23,16 @@ mod wrappers;
 
 pub use account::AccountData;
 pub use address::Address;
+pub use batch::{
+    AccountRequest, AccountResourceRequest, AccountResourcesRequest, BatchReadRequest,
+    EventsRequest, ReadRequest, ReadResult,
+};
 pub use block::BlockInfo;
 pub use bytecode::Bytecode;
 pub use convert::{new_vm_utf8_string, AsConverter, MoveConverter};
-pub use error::Error;
+pub use error::{Error, ErrorCode};
 pub use event_key::EventKey;
+pub use gas::GasEstimation;
 pub use hash::HashValue;
 pub use index::IndexResponse;
 pub use ledger_info::LedgerInfo;
@@ -36,14 +43,14 @@ pub use move_types::{
 pub use response::{
     Response, X_APTOS_CHAIN_ID, X_APTOS_EPOCH, X_APTOS_LEDGER_TIMESTAMP, X_APTOS_LEDGER_VERSION,
 };
-pub use table::TableItemRequest;
+pub use table::{RawTableItemRequest, TableItemRequest};
 pub use transaction::{
     BlockMetadataTransaction, DeleteModule, DeleteResource, DeleteTableItem, DirectWriteSet,
     EncodeSubmissionRequest, Event, GenesisTransaction, PendingTransaction, ScriptFunctionPayload,
     ScriptPayload, ScriptWriteSet, SubmitTransactionRequest, Transaction, TransactionData,
     TransactionId, TransactionInfo, TransactionOnChainData, TransactionPayload,
     TransactionSigningMessage, UserCreateSigningMessageRequest, UserTransaction,
-    UserTransactionRequest, WriteModule, WriteResource, WriteSet, WriteSetChange, WriteSetPayload,
-    WriteTableItem,
+    UserTransactionRequest, VersionedEvent, WriteModule, WriteResource, WriteSet, WriteSetChange,
+    WriteSetPayload, WriteTableItem,
 };
 pub use wrappers::{IdentifierWrapper, MoveStructTagWrapper};
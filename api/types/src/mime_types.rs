@@ -4,3 +4,4 @@
 pub const BCS_SIGNED_TRANSACTION: &str = "application/x.aptos.signed_transaction+bcs";
 pub const JSON: &str = "application/json";
 pub const BCS: &str = "application/x.aptos.output+bcs";
+pub const BCS_TABLE_ITEM_REQUEST: &str = "application/x.aptos.table_item_request+bcs";
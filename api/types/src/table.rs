@@ -2,6 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::MoveType;
+use move_deps::move_core_types::language_storage::TypeTag;
 use poem_openapi::Object;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
@@ -12,3 +13,12 @@ pub struct TableItemRequest {
     pub value_type: MoveType,
     pub key: Value,
 }
+
+/// The BCS counterpart of `TableItemRequest`, where the key is given as its
+/// raw BCS bytes rather than as JSON.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RawTableItemRequest {
+    pub key_type: TypeTag,
+    pub value_type: TypeTag,
+    pub key: Vec<u8>,
+}
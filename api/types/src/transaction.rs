@@ -423,6 +423,15 @@ pub struct Event {
     pub data: serde_json::Value,
 }
 
+/// An event along with the version of the transaction that emitted it.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Object)]
+pub struct VersionedEvent {
+    pub version: U64,
+    #[serde(flatten)]
+    #[oai(flatten)]
+    pub event: Event,
+}
+
 impl From<(&ContractEvent, serde_json::Value)> for Event {
     fn from((event, data): (&ContractEvent, serde_json::Value)) -> Self {
         match event {
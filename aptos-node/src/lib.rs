@@ -482,6 +482,7 @@ pub fn setup_environment(node_config: NodeConfig) -> anyhow::Result<AptosHandle>
         )
         .map_err(|err| anyhow!("DB failed to open {}", err))?,
     );
+    inspection_service::inspection_service::set_aptos_db(Arc::clone(&aptos_db));
     let backup_service = start_backup_service(
         node_config.storage.backup_service_address,
         Arc::clone(&aptos_db),
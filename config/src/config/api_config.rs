@@ -18,11 +18,88 @@ pub struct ApiConfig {
     // optional for compatible with old configuration
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub content_length_limit: Option<u64>,
+    // How far behind wall clock time the latest ledger info may be for the
+    // node to report itself ready on /-/ready
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub readiness_max_staleness_secs: Option<u64>,
+    // HTTP-date advertised in the Sunset header of responses from the
+    // unversioned legacy API, once a removal date has been decided
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub legacy_api_sunset: Option<String>,
+    // Largest page size clients may request from the transaction listing routes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_transactions_page_size: Option<u16>,
+    // Largest page size clients may request from the event listing routes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_events_page_size: Option<u16>,
+    #[serde(default)]
+    pub rate_limit: ApiRateLimitConfig,
+    #[serde(default)]
+    pub cors: ApiCorsConfig,
+}
+
+/// Token bucket limits on the requests the API server accepts. Requests over
+/// a limit are answered with 429 and a Retry-After header. Every limit is off
+/// unless set.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ApiRateLimitConfig {
+    // Sustained requests per second accepted from a single IP address
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub per_ip_requests_per_sec: Option<u32>,
+    // Sustained requests per second accepted across all clients
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub global_requests_per_sec: Option<u32>,
+    // How many requests over the sustained rate may be accepted at once,
+    // defaults to one second's worth
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub burst: Option<u32>,
+}
+
+/// Which cross origin requests browsers may make to the API. Every origin is
+/// allowed unless `allowed_origins` is set.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ApiCorsConfig {
+    // Origins allowed to call the API, e.g. "https://wallet.example.com"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub allowed_origins: Option<Vec<String>>,
+    // Methods allowed in cross origin requests, defaults to GET and POST
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub allowed_methods: Option<Vec<String>>,
+    // Request headers allowed in cross origin requests, defaults to
+    // Content-Type and Accept
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub allowed_headers: Option<Vec<String>>,
+}
+
+impl ApiCorsConfig {
+    pub fn allowed_methods(&self) -> Vec<String> {
+        self.allowed_methods.clone().unwrap_or_else(|| {
+            DEFAULT_CORS_ALLOWED_METHODS
+                .iter()
+                .map(|m| m.to_string())
+                .collect()
+        })
+    }
+
+    pub fn allowed_headers(&self) -> Vec<String> {
+        self.allowed_headers.clone().unwrap_or_else(|| {
+            DEFAULT_CORS_ALLOWED_HEADERS
+                .iter()
+                .map(|h| h.to_string())
+                .collect()
+        })
+    }
 }
 
 pub const DEFAULT_ADDRESS: &str = "127.0.0.1";
 pub const DEFAULT_PORT: u16 = 8080;
 pub const DEFAULT_REQUEST_CONTENT_LENGTH_LIMIT: u64 = 4 * 1024 * 1024; // 4mb
+pub const DEFAULT_READINESS_MAX_STALENESS_SECS: u64 = 30;
+pub const DEFAULT_MAX_PAGE_SIZE: u16 = 1000;
+pub const DEFAULT_CORS_ALLOWED_METHODS: &[&str] = &["GET", "POST"];
+pub const DEFAULT_CORS_ALLOWED_HEADERS: &[&str] = &["content-type", "accept"];
 
 fn default_enabled() -> bool {
     true
@@ -38,6 +115,12 @@ impl Default for ApiConfig {
             tls_cert_path: None,
             tls_key_path: None,
             content_length_limit: None,
+            readiness_max_staleness_secs: None,
+            legacy_api_sunset: None,
+            max_transactions_page_size: None,
+            max_events_page_size: None,
+            rate_limit: ApiRateLimitConfig::default(),
+            cors: ApiCorsConfig::default(),
         }
     }
 }
@@ -53,4 +136,18 @@ impl ApiConfig {
             None => DEFAULT_REQUEST_CONTENT_LENGTH_LIMIT,
         }
     }
+
+    pub fn readiness_max_staleness_secs(&self) -> u64 {
+        self.readiness_max_staleness_secs
+            .unwrap_or(DEFAULT_READINESS_MAX_STALENESS_SECS)
+    }
+
+    pub fn max_transactions_page_size(&self) -> u16 {
+        self.max_transactions_page_size
+            .unwrap_or(DEFAULT_MAX_PAGE_SIZE)
+    }
+
+    pub fn max_events_page_size(&self) -> u16 {
+        self.max_events_page_size.unwrap_or(DEFAULT_MAX_PAGE_SIZE)
+    }
 }
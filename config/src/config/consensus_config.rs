@@ -2,6 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::config::SafetyRulesConfig;
+use aptos_types::on_chain_config::LeaderReputationType;
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 
@@ -27,6 +28,19 @@ pub struct ConsensusConfig {
     // the period = (poll_count - 1) * 30ms
     pub quorum_store_poll_count: u64,
     pub intra_consensus_channel_buffer_size: usize,
+    // Number of rounds we expect committed storage to lag behind the proposer round, used by
+    // leader reputation to decide how far back in history to read. Networks with a higher commit
+    // latency need a larger buffer for the reputation window to stay complete.
+    pub proposer_round_behind_storage_buffer: usize,
+    // How often leader reputation reads newly committed blocks in the background (in milliseconds),
+    // so that electing a proposer usually doesn't have to wait on storage. Elections still read
+    // storage when the prefetched window doesn't reach their round yet. 0 disables prefetching.
+    pub leader_reputation_prefetch_interval_ms: u64,
+    // Leader reputation heuristic and window to use instead of the on-chain ones, when the
+    // on-chain proposer election is leader reputation. Validators that elect proposers
+    // differently reject each other's proposals, so this is only meant to be rolled out
+    // to every validator at once, e.g. to recover before governance can fix the on-chain config.
+    pub leader_reputation_override: Option<LeaderReputationType>,
 }
 
 impl Default for ConsensusConfig {
@@ -44,6 +58,9 @@ impl Default for ConsensusConfig {
             quorum_store_pull_timeout_ms: 1000,
             quorum_store_poll_count: 20,
             intra_consensus_channel_buffer_size: 10,
+            proposer_round_behind_storage_buffer: 10,
+            leader_reputation_prefetch_interval_ms: 100,
+            leader_reputation_override: None,
         }
     }
 }
@@ -65,4 +82,20 @@ mod test {
 
         serde_yaml::from_str::<ConsensusConfig>(&s).unwrap();
     }
+
+    #[test]
+    fn test_leader_reputation_override() {
+        let config: ConsensusConfig = serde_yaml::from_str(
+            "leader_reputation_override:\n  active_inactive:\n    active_weight: 100\n    \
+             inactive_weight: 1\n    window_num_validators_multiplier: 5\n",
+        )
+        .unwrap();
+        assert!(matches!(
+            config.leader_reputation_override,
+            Some(LeaderReputationType::ActiveInactive(_))
+        ));
+
+        let s = serde_yaml::to_string(&config).unwrap();
+        assert_eq!(serde_yaml::from_str::<ConsensusConfig>(&s).unwrap(), config);
+    }
 }
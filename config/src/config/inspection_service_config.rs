@@ -11,6 +11,9 @@ pub struct InspectionServiceConfig {
     pub port: u16,
     pub expose_configuration: bool,
     pub expose_system_information: bool,
+    /// Whether operators may trigger and pause the storage pruners, and the backup coordinator may
+    /// report the backed up versions for `prune_only_backed_up`.
+    pub expose_pruner_controls: bool,
 }
 
 impl Default for InspectionServiceConfig {
@@ -20,6 +23,7 @@ impl Default for InspectionServiceConfig {
             port: 9101,
             expose_configuration: false,
             expose_system_information: true,
+            expose_pruner_controls: false,
         }
     }
 }
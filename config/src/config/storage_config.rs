@@ -90,8 +90,16 @@ pub struct StorageConfig {
 pub const NO_OP_STORAGE_PRUNER_CONFIG: StoragePrunerConfig = StoragePrunerConfig {
     state_store_prune_window: None,
     ledger_prune_window: None,
+    state_store_prune_window_secs: None,
+    ledger_prune_window_secs: None,
+    event_store_prune_window: None,
+    epoch_snapshots_to_retain: 0,
     ledger_pruning_batch_size: 10_000,
     state_store_pruning_batch_size: 10_000,
+    ledger_max_versions_per_sec: None,
+    state_store_max_versions_per_sec: None,
+    compaction_threshold_versions: None,
+    prune_only_backed_up: false,
 };
 
 #[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
@@ -102,8 +110,28 @@ pub struct StoragePrunerConfig {
     pub state_store_prune_window: Option<u64>,
     /// This is the default pruning window for any other store except for state store. State store
     /// being big in size, we might want to configure a smaller window for state store vs other
-    /// store.
+    /// store. The stores pruned on this window are the transactions, their write sets, infos and
+    /// accumulator, the ledger counters and, unless `event_store_prune_window` is set, the events.
     pub ledger_prune_window: Option<u64>,
+    /// Age window of the state store, e.g. 7 * 86400 to retain a week. Ages are measured with the
+    /// block timestamps, relative to the latest block. With both windows set, a version is only
+    /// pruned once it is outside both of them.
+    #[serde(default)]
+    pub state_store_prune_window_secs: Option<u64>,
+    /// Age window of the other stores, like `ledger_prune_window`.
+    #[serde(default)]
+    pub ledger_prune_window_secs: Option<u64>,
+    /// Window of the event store, to keep events for a shorter or longer time than the other
+    /// stores, e.g. to drop historical events while keeping the transactions. None prunes the
+    /// events along with the other stores. If it's shorter than the ledger window, the
+    /// transactions are only advertised (to the API and state sync) from the first version whose
+    /// events are still readable, since they're served together.
+    #[serde(default)]
+    pub event_store_prune_window: Option<u64>,
+    /// The number of latest epoch ending state snapshots the state store pruner keeps even once
+    /// they are outside its window, so epoch ending backups and state sync can still read them.
+    #[serde(default = "default_epoch_snapshots_to_retain")]
+    pub epoch_snapshots_to_retain: usize,
     /// Batch size of the versions to be sent to the ledger pruner - this is to avoid slowdown due to
     /// issuing too many DB calls and batch prune instead. For ledger pruner, this means the number
     /// of versions to prune a time.
@@ -111,6 +139,27 @@ pub struct StoragePrunerConfig {
     /// Similar to the variable above but for state store pruner. It means the number of stale
     /// nodes to prune a time.
     pub state_store_pruning_batch_size: usize,
+    /// Caps how fast the ledger pruner deletes, in versions per second, so catching up with a
+    /// large backlog doesn't starve the commits of disk I/O. None prunes as fast as possible.
+    #[serde(default)]
+    pub ledger_max_versions_per_sec: Option<u64>,
+    /// Similar to the variable above but for the state store pruner.
+    #[serde(default)]
+    pub state_store_max_versions_per_sec: Option<u64>,
+    /// Once a pruner has pruned this many versions since it last did, it compacts the range it
+    /// pruned, to give the disk space back sooner than RocksDB's own compactions would. None
+    /// leaves it to RocksDB.
+    #[serde(default)]
+    pub compaction_threshold_versions: Option<u64>,
+    /// Never prune the versions that aren't backed up yet. The backup coordinator reports how far
+    /// the backup goes to the inspection service as it goes, which needs `expose_pruner_controls`.
+    /// Until its first report, nothing is pruned.
+    #[serde(default)]
+    pub prune_only_backed_up: bool,
+}
+
+fn default_epoch_snapshots_to_retain() -> usize {
+    2
 }
 
 impl StoragePrunerConfig {
@@ -123,10 +172,30 @@ impl StoragePrunerConfig {
         StoragePrunerConfig {
             state_store_prune_window,
             ledger_prune_window: ledger_store_prune_window,
+            state_store_prune_window_secs: None,
+            ledger_prune_window_secs: None,
+            event_store_prune_window: None,
+            epoch_snapshots_to_retain: default_epoch_snapshots_to_retain(),
             ledger_pruning_batch_size,
             state_store_pruning_batch_size,
+            ledger_max_versions_per_sec: None,
+            state_store_max_versions_per_sec: None,
+            compaction_threshold_versions: None,
+            prune_only_backed_up: false,
         }
     }
+
+    pub fn state_store_pruning_enabled(&self) -> bool {
+        self.state_store_prune_window.is_some() || self.state_store_prune_window_secs.is_some()
+    }
+
+    pub fn ledger_pruning_enabled(&self) -> bool {
+        self.ledger_prune_window.is_some() || self.ledger_prune_window_secs.is_some()
+    }
+
+    pub fn event_store_pruning_enabled(&self) -> bool {
+        self.event_store_prune_window.is_some()
+    }
 }
 
 impl Default for StorageConfig {
@@ -145,10 +214,18 @@ impl Default for StorageConfig {
             storage_pruner_config: StoragePrunerConfig {
                 state_store_prune_window: Some(1_000_000),
                 ledger_prune_window: Some(10_000_000),
+                state_store_prune_window_secs: None,
+                ledger_prune_window_secs: None,
+                event_store_prune_window: None,
+                epoch_snapshots_to_retain: default_epoch_snapshots_to_retain(),
                 ledger_pruning_batch_size: 500,
                 // A 10k transaction block (touching 60k state values, in the case of the account
                 // creation benchmark) on a 4B items DB (or 1.33B accounts) yields 300k JMT nodes
                 state_store_pruning_batch_size: 1_000,
+                ledger_max_versions_per_sec: None,
+                state_store_max_versions_per_sec: None,
+                compaction_threshold_versions: None,
+                prune_only_backed_up: false,
             },
             data_dir: PathBuf::from("/opt/aptos/data"),
             // Default read/write/connection timeout, in milliseconds
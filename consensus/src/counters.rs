@@ -111,6 +111,27 @@ pub static COMMITTED_VOTES_IN_WINDOW: Lazy<IntGaugeVec> = Lazy::new(|| {
     .unwrap()
 });
 
+/// Current election weight of each candidate when using LeaderReputation as the ProposerElection,
+/// after jailing is applied
+pub static LEADER_REPUTATION_WEIGHT: Lazy<IntGaugeVec> = Lazy::new(|| {
+    register_int_gauge_vec!(
+        "aptos_leader_reputation_weight",
+        "Current weight of the candidate in leader reputation election",
+        &["address"]
+    )
+    .unwrap()
+});
+
+/// Number of rounds each candidate was elected the primary proposer for by LeaderReputation
+pub static LEADER_REPUTATION_ELECTED_COUNT: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "aptos_leader_reputation_elected_count",
+        "Number of rounds the candidate was elected the primary proposer for",
+        &["address"]
+    )
+    .unwrap()
+});
+
 /// The number of block events the LeaderReputation uses
 pub static LEADER_REPUTATION_ROUND_HISTORY_SIZE: Lazy<IntGauge> = Lazy::new(|| {
     register_int_gauge!(
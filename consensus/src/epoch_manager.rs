@@ -14,8 +14,9 @@ use crate::{
     liveness::{
         cached_proposer_election::CachedProposerElection,
         leader_reputation::{
-            ActiveInactiveHeuristic, AptosDBBackend, LeaderReputation, ProposerAndVoterHeuristic,
-            ReputationHeuristic,
+            ActiveInactiveHeuristic, AptosDBBackend, CompositeHeuristic,
+            FailedProposalPenaltyHeuristic, LeaderReputation, ProposerAndVoterHeuristic,
+            ReputationHeuristic, VoteParticipationHeuristic,
         },
         proposal_generator::ProposalGenerator,
         proposer_election::ProposerElection,
@@ -45,8 +46,8 @@ use aptos_types::{
     epoch_change::EpochChangeProof,
     epoch_state::EpochState,
     on_chain_config::{
-        LeaderReputationType, OnChainConfigPayload, OnChainConsensusConfig, ProposerElectionType,
-        ValidatorSet,
+        LeaderReputationType, OnChainConfig, OnChainConfigPayload, OnChainConsensusConfig,
+        ProposerElectionType, ValidatorSet,
     },
     validator_verifier::ValidatorVerifier,
 };
@@ -77,9 +78,6 @@ use std::{
 /// Range of rounds (window) that we might be calling proposer election
 /// functions with at any given time, in addition to the proposer history length.
 const PROPSER_ELECTION_CACHING_WINDOW_ADDITION: usize = 3;
-/// Number of rounds we expect storage to be ahead of the proposer round,
-/// used for fetching data from DB.
-const PROPSER_ROUND_BEHIND_STORAGE_BUFFER: usize = 10;
 
 #[allow(clippy::large_enum_variant)]
 pub enum LivenessStorageData {
@@ -185,6 +183,89 @@ impl EpochManager {
         RoundState::new(time_interval, time_service, timeout_sender)
     }
 
+    /// Create the heuristic for leader reputation, and the size of the history window it needs
+    fn create_reputation_heuristic(
+        &self,
+        num_proposers: usize,
+        leader_reputation_type: &LeaderReputationType,
+    ) -> (Box<dyn ReputationHeuristic>, usize) {
+        match leader_reputation_type {
+            LeaderReputationType::ActiveInactive(active_inactive_config) => {
+                let window_size =
+                    num_proposers * active_inactive_config.window_num_validators_multiplier;
+                let heuristic: Box<dyn ReputationHeuristic> =
+                    Box::new(ActiveInactiveHeuristic::new(
+                        self.author,
+                        active_inactive_config.active_weight,
+                        active_inactive_config.inactive_weight,
+                        window_size,
+                    ));
+                (heuristic, window_size)
+            }
+            LeaderReputationType::ProposerAndVoter(proposer_and_voter_config) => {
+                let proposer_window_size = num_proposers
+                    * proposer_and_voter_config.proposer_window_num_validators_multiplier;
+                let voter_window_size = num_proposers
+                    * proposer_and_voter_config.voter_window_num_validators_multiplier;
+                let heuristic: Box<dyn ReputationHeuristic> =
+                    Box::new(ProposerAndVoterHeuristic::new(
+                        self.author,
+                        proposer_and_voter_config.active_weight,
+                        proposer_and_voter_config.inactive_weight,
+                        proposer_and_voter_config.failed_weight,
+                        proposer_and_voter_config.failure_threshold_percent,
+                        voter_window_size,
+                        proposer_window_size,
+                    ));
+                (
+                    heuristic,
+                    std::cmp::max(proposer_window_size, voter_window_size),
+                )
+            }
+            LeaderReputationType::FailedProposalPenalty(failed_proposal_penalty_config) => {
+                let window_size =
+                    num_proposers * failed_proposal_penalty_config.window_num_validators_multiplier;
+                let heuristic: Box<dyn ReputationHeuristic> =
+                    Box::new(FailedProposalPenaltyHeuristic::new(
+                        self.author,
+                        failed_proposal_penalty_config.active_weight,
+                        failed_proposal_penalty_config.min_weight,
+                        failed_proposal_penalty_config.failure_penalty_percent,
+                        failed_proposal_penalty_config.penalty_half_life_rounds,
+                        window_size,
+                    ));
+                (heuristic, window_size)
+            }
+            LeaderReputationType::VoteParticipation(vote_participation_config) => {
+                let window_size =
+                    num_proposers * vote_participation_config.window_num_validators_multiplier;
+                let heuristic: Box<dyn ReputationHeuristic> =
+                    Box::new(VoteParticipationHeuristic::new(
+                        self.author,
+                        vote_participation_config.max_weight,
+                        vote_participation_config.min_weight,
+                        window_size,
+                    ));
+                (heuristic, window_size)
+            }
+            LeaderReputationType::Composite(weighted_configs) => {
+                let mut window_size = 0;
+                let heuristics = weighted_configs
+                    .iter()
+                    .map(|weighted_config| {
+                        let (heuristic, heuristic_window_size) = self
+                            .create_reputation_heuristic(num_proposers, &weighted_config.heuristic);
+                        window_size = std::cmp::max(window_size, heuristic_window_size);
+                        (weighted_config.weight, heuristic)
+                    })
+                    .collect();
+                let heuristic: Box<dyn ReputationHeuristic> =
+                    Box::new(CompositeHeuristic::new(heuristics));
+                (heuristic, window_size)
+            }
+        }
+    }
+
     /// Create a proposer election handler based on proposers
     fn create_proposer_election(
         &self,
@@ -205,55 +286,41 @@ impl EpochManager {
                 Box::new(RotatingProposer::new(vec![proposer], *contiguous_rounds))
             }
             ProposerElectionType::LeaderReputation(leader_reputation_type) => {
-                let (heuristic, window_size) = match &leader_reputation_type {
-                    LeaderReputationType::ActiveInactive(active_inactive_config) => {
-                        let window_size = proposers.len()
-                            * active_inactive_config.window_num_validators_multiplier;
-                        let heuristic: Box<dyn ReputationHeuristic> =
-                            Box::new(ActiveInactiveHeuristic::new(
-                                self.author,
-                                active_inactive_config.active_weight,
-                                active_inactive_config.inactive_weight,
-                                window_size,
-                            ));
-                        (heuristic, window_size)
-                    }
-                    LeaderReputationType::ProposerAndVoter(proposer_and_voter_config) => {
-                        let proposer_window_size = proposers.len()
-                            * proposer_and_voter_config.proposer_window_num_validators_multiplier;
-                        let voter_window_size = proposers.len()
-                            * proposer_and_voter_config.voter_window_num_validators_multiplier;
-                        let heuristic: Box<dyn ReputationHeuristic> =
-                            Box::new(ProposerAndVoterHeuristic::new(
-                                self.author,
-                                proposer_and_voter_config.active_weight,
-                                proposer_and_voter_config.inactive_weight,
-                                proposer_and_voter_config.failed_weight,
-                                proposer_and_voter_config.failure_threshold_percent,
-                                voter_window_size,
-                                proposer_window_size,
-                            ));
-                        (
-                            heuristic,
-                            std::cmp::max(proposer_window_size, voter_window_size),
-                        )
+                let leader_reputation_type = match &self.config.leader_reputation_override {
+                    Some(leader_reputation_override) => {
+                        warn!(
+                            "Overriding on-chain leader reputation {:?} with local {:?}",
+                            leader_reputation_type, leader_reputation_override
+                        );
+                        leader_reputation_override
                     }
+                    None => leader_reputation_type,
                 };
+                let (heuristic, window_size) =
+                    self.create_reputation_heuristic(proposers.len(), leader_reputation_type);
 
-                let backend = Box::new(AptosDBBackend::new(
+                let backend = Arc::new(AptosDBBackend::new(
                     epoch_state.epoch,
                     window_size,
                     onchain_config.leader_reputation_exclude_round() as usize
                         + onchain_config.max_failed_authors_to_store()
-                        + PROPSER_ROUND_BEHIND_STORAGE_BUFFER,
+                        + self.config.proposer_round_behind_storage_buffer,
                     self.storage.aptos_db(),
                 ));
+                if self.config.leader_reputation_prefetch_interval_ms > 0 {
+                    tokio::spawn(AptosDBBackend::prefetch_periodically(
+                        Arc::downgrade(&backend),
+                        Duration::from_millis(self.config.leader_reputation_prefetch_interval_ms),
+                    ));
+                }
                 let proposer_election = Box::new(LeaderReputation::new(
                     epoch_state.epoch,
                     proposers,
-                    backend,
+                    Box::new(backend),
                     heuristic,
                     onchain_config.leader_reputation_exclude_round(),
+                    onchain_config.leader_reputation_num_fallback_proposers(),
+                    onchain_config.leader_jail_config(),
                 ));
                 // LeaderReputation is not cheap, so we can cache the amount of rounds round_manager needs.
                 Box::new(CachedProposerElection::new(
@@ -571,10 +638,20 @@ impl EpochManager {
             verifier: (&validator_set).into(),
         };
 
-        let onchain_config: anyhow::Result<OnChainConsensusConfig> = payload.get();
-        if let Err(error) = &onchain_config {
-            error!("Failed to read on-chain consensus config {}", error);
-        }
+        // A config that is set but can't be read, e.g. a version this node doesn't know about,
+        // must not fall back to the defaults: the node would elect different proposers than
+        // the rest of the validators.
+        let onchain_config = if payload
+            .configs()
+            .contains_key(&OnChainConsensusConfig::CONFIG_ID)
+        {
+            payload
+                .get::<OnChainConsensusConfig>()
+                .expect("failed to read on-chain consensus config, the node needs an upgrade")
+        } else {
+            warn!("On-chain consensus config is not initialized, using the default");
+            OnChainConsensusConfig::default()
+        };
 
         self.epoch_state = Some(epoch_state.clone());
 
@@ -582,12 +659,8 @@ impl EpochManager {
             .storage
             .start()
             .expect_recovery_data("Consensusdb is corrupted, need to do a backup and restore");
-        self.start_round_manager(
-            initial_data,
-            epoch_state,
-            onchain_config.unwrap_or_default(),
-        )
-        .await;
+        self.start_round_manager(initial_data, epoch_state, onchain_config)
+            .await;
     }
 
     async fn process_message(
@@ -5,13 +5,16 @@ use std::collections::BTreeMap;
 
 use aptos_infallible::Mutex;
 use consensus_types::common::{Author, Round};
+use short_hex_str::AsShortHexStr;
 
 use super::proposer_election::ProposerElection;
+use crate::counters::{LEADER_REPUTATION_ELECTED_COUNT, LEADER_REPUTATION_WEIGHT};
 
 // Wrapper around ProposerElection.
 //
 // Function get_valid_proposer can be expensive, and we want to make sure
 // it is computed only once for a given round.
+// As each round is elected once, this is also where the election metrics are updated.
 // Additionally, provides is_valid_proposal that remembers, and rejects if
 // the same leader proposes multiple blocks.
 pub struct CachedProposerElection {
@@ -20,7 +23,7 @@ pub struct CachedProposerElection {
     // to look back (and caller knows how big of a window it needs).
     // LRU cache wouldn't work as well, as access order of the elements
     // would define eviction, and could lead to evicting still needed elements.
-    recent_elections: Mutex<BTreeMap<Round, Author>>,
+    recent_elections: Mutex<BTreeMap<Round, Vec<Author>>>,
     window: usize,
 }
 
@@ -36,14 +39,32 @@ impl CachedProposerElection {
 
 impl ProposerElection for CachedProposerElection {
     fn get_valid_proposer(&self, round: Round) -> Author {
+        self.get_valid_proposers(round)[0]
+    }
+
+    fn get_valid_proposers(&self, round: Round) -> Vec<Author> {
         let mut recent_elections = self.recent_elections.lock();
 
         if round > self.window as u64 {
             *recent_elections = recent_elections.split_off(&(round - self.window as u64));
         }
 
-        *recent_elections
+        recent_elections
             .entry(round)
-            .or_insert_with(|| self.proposer_election.get_valid_proposer(round))
+            .or_insert_with(|| {
+                let (proposers, weights) = self
+                    .proposer_election
+                    .get_valid_proposers_and_weights(round);
+                for (candidate, weight) in weights.unwrap_or_default() {
+                    LEADER_REPUTATION_WEIGHT
+                        .with_label_values(&[candidate.short_str().as_str()])
+                        .set(weight as i64);
+                }
+                LEADER_REPUTATION_ELECTED_COUNT
+                    .with_label_values(&[proposers[0].short_str().as_str()])
+                    .inc();
+                proposers
+            })
+            .clone()
     }
 }
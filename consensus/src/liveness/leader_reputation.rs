@@ -8,12 +8,21 @@ use crate::{
     },
     liveness::proposer_election::{next, ProposerElection},
 };
-use aptos_infallible::{Mutex, MutexGuard};
+use aptos_infallible::Mutex;
 use aptos_logger::prelude::*;
-use aptos_types::{account_config::NewBlockEvent, block_metadata::new_block_event_key};
+use aptos_types::{
+    account_config::NewBlockEvent, block_metadata::new_block_event_key,
+    contract_event::EventWithVersion, on_chain_config::LeaderJailConfig,
+};
 use consensus_types::common::{Author, Round};
 use short_hex_str::AsShortHexStr;
-use std::{cmp::Ordering, collections::HashMap, convert::TryFrom, sync::Arc};
+use std::{
+    cmp::Ordering,
+    collections::HashMap,
+    convert::TryFrom,
+    sync::{Arc, Weak},
+    time::Duration,
+};
 use storage_interface::{DbReader, Order};
 
 /// Interface to query committed BlockMetadata.
@@ -23,12 +32,25 @@ pub trait MetadataBackend: Send + Sync {
     fn get_block_metadata(&self, target_round: Round) -> Vec<NewBlockEvent>;
 }
 
+/// Events read from the DB so far, newest first, kept across calls so that
+/// only the events committed since the last read need to be fetched.
+#[derive(Clone, Default)]
+struct DbResult {
+    events: Vec<NewBlockEvent>,
+    // latest DB version the events are known to be complete up to
+    version: u64,
+    // whether the events go back to the start of the event stream
+    hit_end: bool,
+    // sequence number of the next event to fetch, None if nothing was fetched yet
+    next_seq_num: Option<u64>,
+}
+
 pub struct AptosDBBackend {
     epoch: u64,
     window_size: usize,
     seek_len: usize,
     aptos_db: Arc<dyn DbReader>,
-    db_result: Mutex<(Vec<NewBlockEvent>, u64, bool)>,
+    db_result: Mutex<DbResult>,
 }
 
 impl AptosDBBackend {
@@ -43,51 +65,153 @@ impl AptosDBBackend {
             window_size,
             seek_len,
             aptos_db,
-            db_result: Mutex::new((vec![], 0u64, true)),
+            db_result: Mutex::new(DbResult {
+                hit_end: true,
+                ..DbResult::default()
+            }),
         }
     }
 
-    fn refresh_db_result(
+    /// Reads the events committed since next_seq_num, or the latest ones if there is nothing
+    /// to continue from. Returns whether the read events continue from next_seq_num.
+    fn read_events(
         &self,
-        mut locked: MutexGuard<'_, (Vec<NewBlockEvent>, u64, bool)>,
-        lastest_db_version: u64,
-    ) -> anyhow::Result<(Vec<NewBlockEvent>, u64, bool)> {
+        next_seq_num: Option<u64>,
+    ) -> anyhow::Result<(Vec<EventWithVersion>, bool)> {
         // assumes target round is not too far from latest commit
         let limit = self.window_size + self.seek_len;
 
-        // there is a race condition between the next two lines, and new events being added.
-        // I.e. when latest_db_version is fetched, and get_events are called.
-        // if in between a new entry gets added max_returned_version will be larger than
-        // latest_db_version, and so we should take the max of the two.
+        if let Some(next_seq_num) = next_seq_num {
+            // Only read the events committed since the last refresh. If there are as many
+            // of them as the whole window, the cached ones are useless, so reread from the top.
+            let mut events = self.aptos_db.get_events(
+                &new_block_event_key(),
+                next_seq_num,
+                Order::Ascending,
+                limit as u64,
+            )?;
+            if events.len() < limit {
+                events.reverse();
+                return Ok((events, true));
+            }
+        }
 
-        // we cannot reorder those two functions, as if get_events is first,
-        // and then new entry gets added before get_latest_version is called,
-        // we would incorrectly think that we have a newer version.
         let events = self.aptos_db.get_events(
             &new_block_event_key(),
             u64::max_value(),
             Order::Descending,
             limit as u64,
         )?;
+        Ok((events, false))
+    }
+
+    fn apply_events(
+        &self,
+        db_result: &mut DbResult,
+        events: Vec<EventWithVersion>,
+        continues_cached: bool,
+        lastest_db_version: u64,
+    ) -> anyhow::Result<()> {
+        let limit = self.window_size + self.seek_len;
+        *db_result = if continues_cached {
+            self.merge_events(events, db_result, lastest_db_version, limit)?
+        } else {
+            let hit_end = events.len() < limit;
+            let mut result =
+                self.merge_events(events, &DbResult::default(), lastest_db_version, limit)?;
+            result.hit_end = hit_end;
+            result
+        };
+        Ok(())
+    }
+
+    /// Reads newly committed events into the cache. The DB is read without holding the lock,
+    /// so elections served from the cache are never blocked on storage.
+    pub fn prefetch(&self) -> anyhow::Result<()> {
+        // there is a race condition between the next two reads, and new events being added.
+        // I.e. when latest_db_version is fetched, and get_events are called.
+        // if in between a new entry gets added max_returned_version will be larger than
+        // latest_db_version, and so we should take the max of the two.
+
+        // we cannot reorder those two reads, as if get_events is first,
+        // and then new entry gets added before get_latest_version is called,
+        // we would incorrectly think that we have a newer version.
+        let lastest_db_version = self.aptos_db.get_latest_version()?;
+        let next_seq_num = {
+            let locked = self.db_result.lock();
+            if locked.version >= lastest_db_version {
+                return Ok(());
+            }
+            locked.next_seq_num
+        };
 
-        let max_returned_version = events.first().map_or(0, |first| first.transaction_version);
+        let (events, continues_cached) = self.read_events(next_seq_num)?;
 
-        let new_block_events: Vec<NewBlockEvent> = itertools::process_results(
-            events
+        let mut locked = self.db_result.lock();
+        // another read refreshed the cache in the meantime, what we read might be stale
+        if locked.next_seq_num != next_seq_num {
+            return Ok(());
+        }
+        self.apply_events(&mut locked, events, continues_cached, lastest_db_version)
+    }
+
+    /// Prefetches every interval, until the backend is dropped at the end of the epoch.
+    pub async fn prefetch_periodically(backend: Weak<Self>, interval: Duration) {
+        let mut interval = tokio::time::interval(interval);
+        loop {
+            interval.tick().await;
+            let backend = match backend.upgrade() {
+                Some(backend) => backend,
+                None => break,
+            };
+            match tokio::task::spawn_blocking(move || backend.prefetch()).await {
+                Ok(Ok(())) => (),
+                Ok(Err(e)) => {
+                    warn!(error = ?e, "[leader reputation] Fail to prefetch window");
+                }
+                Err(e) => {
+                    error!(error = ?e, "[leader reputation] Prefetch task failed");
+                }
+            }
+        }
+    }
+
+    /// Puts the newly read events (newest first) in front of the cached ones,
+    /// and trims the result to the number of events we read at most.
+    fn merge_events(
+        &self,
+        new_events: Vec<EventWithVersion>,
+        cached: &DbResult,
+        lastest_db_version: u64,
+        limit: usize,
+    ) -> anyhow::Result<DbResult> {
+        let max_returned_version = new_events
+            .first()
+            .map_or(0, |first| first.transaction_version);
+        let next_seq_num = new_events
+            .first()
+            .map(|first| first.event.sequence_number() + 1)
+            .or(cached.next_seq_num);
+
+        let mut events: Vec<NewBlockEvent> = itertools::process_results(
+            new_events
                 .into_iter()
                 .map(|event| bcs::from_bytes::<NewBlockEvent>(event.event.event_data())),
             |iter| iter.filter(|e| e.epoch() == self.epoch).collect(),
         )?;
-
-        let hit_end = new_block_events.len() < limit;
-
-        let result = (
-            new_block_events,
-            std::cmp::max(lastest_db_version, max_returned_version),
+        events.extend(cached.events.iter().cloned());
+        let hit_end = cached.hit_end && events.len() <= limit;
+        events.truncate(limit);
+
+        Ok(DbResult {
+            events,
+            version: std::cmp::max(
+                std::cmp::max(lastest_db_version, max_returned_version),
+                cached.version,
+            ),
             hit_end,
-        );
-        *locked = result.clone();
-        Ok(result)
+            next_seq_num,
+        })
     }
 
     fn get_from_db_result(
@@ -113,30 +237,34 @@ impl AptosDBBackend {
 impl MetadataBackend for AptosDBBackend {
     // assume the target_round only increases
     fn get_block_metadata(&self, target_round: Round) -> Vec<NewBlockEvent> {
-        let locked = self.db_result.lock();
-        let events = &locked.0;
-        let version = locked.1;
-        let hit_end = locked.2;
-
-        let has_larger = events.first().map_or(false, |e| e.round() >= target_round);
-        let lastest_db_version = self.aptos_db.get_latest_version().unwrap_or(0);
-        // check if fresher data has potential to give us different result
-        if !has_larger && version < lastest_db_version {
-            let fresh_db_result = self.refresh_db_result(locked, lastest_db_version);
-            match fresh_db_result {
-                Ok((events, _version, hit_end)) => {
-                    self.get_from_db_result(target_round, &events, hit_end)
-                }
-                Err(e) => {
-                    error!(
-                        error = ?e, "[leader reputation] Fail to refresh window",
-                    );
-                    vec![]
-                }
+        // The prefetched window is only served as is once it reaches target_round, otherwise
+        // every validator would elect from however far its own prefetching got.
+        let needs_refresh = {
+            let locked = self.db_result.lock();
+            let has_larger = locked
+                .events
+                .first()
+                .map_or(false, |e| e.round() >= target_round);
+            let lastest_db_version = self.aptos_db.get_latest_version().unwrap_or(0);
+            // check if fresher data has potential to give us different result
+            !has_larger && locked.version < lastest_db_version
+        };
+        if needs_refresh {
+            if let Err(e) = self.prefetch() {
+                error!(
+                    error = ?e, "[leader reputation] Fail to refresh window",
+                );
+                return vec![];
             }
-        } else {
-            self.get_from_db_result(target_round, events, hit_end)
         }
+        let locked = self.db_result.lock();
+        self.get_from_db_result(target_round, &locked.events, locked.hit_end)
+    }
+}
+
+impl<T: MetadataBackend + ?Sized> MetadataBackend for Arc<T> {
+    fn get_block_metadata(&self, target_round: Round) -> Vec<NewBlockEvent> {
+        self.as_ref().get_block_metadata(target_round)
     }
 }
 
@@ -280,6 +408,11 @@ impl NewBlockEventAggregation {
         )
     }
 
+    /// Number of blocks within the voter window, i.e. the number of times each voter could have voted.
+    pub fn count_voting_opportunities(&self, epoch: u64, history: &[NewBlockEvent]) -> u32 {
+        Self::history_iter(history, epoch, self.voter_window_size).count() as u32
+    }
+
     pub fn count_proposals(&self, epoch: u64, history: &[NewBlockEvent]) -> HashMap<Author, u32> {
         Self::history_iter(history, epoch, self.proposer_window_size).fold(
             HashMap::new(),
@@ -320,6 +453,43 @@ impl NewBlockEventAggregation {
             },
         )
     }
+
+    /// Rounds within the proposer window in which each candidate was the elected proposer,
+    /// but no block got committed. Failed proposers of a block are listed in the order of
+    /// the rounds immediately preceding the block's round.
+    pub fn failed_proposal_rounds(
+        &self,
+        epoch: u64,
+        candidates: &[Author],
+        history: &[NewBlockEvent],
+    ) -> HashMap<Author, Vec<Round>> {
+        Self::history_iter(history, epoch, self.proposer_window_size).fold(
+            HashMap::new(),
+            |mut map, meta| {
+                match Self::indices_to_validators(candidates, meta.failed_proposer_indices()) {
+                    Ok(failed_proposers) => {
+                        let first_failed_round = meta
+                            .round()
+                            .saturating_sub(failed_proposers.len() as u64);
+                        for (i, &failed_proposer) in failed_proposers.iter().enumerate() {
+                            map.entry(failed_proposer)
+                                .or_insert_with(Vec::new)
+                                .push(first_failed_round + i as u64);
+                        }
+                    }
+                    Err(msg) => {
+                        error!(
+                            "Failed proposer conversion from indices failed at epoch {}, round {}: {}",
+                            meta.epoch(),
+                            meta.round(),
+                            msg
+                        )
+                    }
+                }
+                map
+            },
+        )
+    }
 }
 
 /// If candidate appear in the history, it's assigned active_weight otherwise inactive weight.
@@ -456,14 +626,198 @@ impl ReputationHeuristic for ProposerAndVoterHeuristic {
     }
 }
 
+/// Heuristic that penalizes candidates for the rounds they were elected proposer in,
+/// but failed to get a block committed, with older failures weighing less.
+///
+/// Each failure within the proposer window adds failure_penalty_percent to the candidate's penalty,
+/// halved for every penalty_half_life_rounds rounds the failure is behind the latest round in the window.
+/// The weight is then active_weight scaled down by the penalty, i.e.
+/// active_weight * 100 / (100 + penalty), but never below min_weight.
+///
+/// Unlike the threshold in ProposerAndVoterHeuristic, a single failure only slightly reduces a node's
+/// chances, while a node that keeps failing is driven towards min_weight, and recovers gradually once
+/// its failures age out. All arithmetic is on integers, so every validator computes the same weights.
+pub struct FailedProposalPenaltyHeuristic {
+    #[allow(unused)]
+    author: Author,
+    active_weight: u64,
+    min_weight: u64,
+    failure_penalty_percent: u64,
+    penalty_half_life_rounds: u64,
+    aggregation: NewBlockEventAggregation,
+}
+
+impl FailedProposalPenaltyHeuristic {
+    pub fn new(
+        author: Author,
+        active_weight: u64,
+        min_weight: u64,
+        failure_penalty_percent: u64,
+        penalty_half_life_rounds: u64,
+        window_size: usize,
+    ) -> Self {
+        Self {
+            author,
+            active_weight,
+            min_weight,
+            failure_penalty_percent,
+            // a zero half life would divide by zero, treat it as failures decaying every round
+            penalty_half_life_rounds: std::cmp::max(penalty_half_life_rounds, 1),
+            aggregation: NewBlockEventAggregation::new(window_size, window_size),
+        }
+    }
+
+    fn penalty(&self, failed_rounds: &[Round], latest_round: Round) -> u64 {
+        failed_rounds
+            .iter()
+            .map(|round| {
+                let halvings = latest_round.saturating_sub(*round) / self.penalty_half_life_rounds;
+                self.failure_penalty_percent
+                    .checked_shr(u32::try_from(halvings).unwrap_or(u32::MAX))
+                    .unwrap_or(0)
+            })
+            .fold(0u64, |total, penalty| total.saturating_add(penalty))
+    }
+}
+
+impl ReputationHeuristic for FailedProposalPenaltyHeuristic {
+    fn get_weights(
+        &self,
+        epoch: u64,
+        candidates: &[Author],
+        history: &[NewBlockEvent],
+    ) -> Vec<u64> {
+        let failed_rounds = self
+            .aggregation
+            .failed_proposal_rounds(epoch, candidates, history);
+        let latest_round = history
+            .iter()
+            .filter(|meta| meta.epoch() == epoch)
+            .map(|meta| meta.round())
+            .max()
+            .unwrap_or(0);
+
+        candidates
+            .iter()
+            .map(|author| {
+                let penalty = failed_rounds
+                    .get(author)
+                    .map_or(0, |rounds| self.penalty(rounds, latest_round));
+                let weight = u128::from(self.active_weight) * 100 / (100 + u128::from(penalty));
+                std::cmp::max(weight as u64, self.min_weight)
+            })
+            .collect()
+    }
+}
+
+/// Heuristic that rewards consistently voting validators, whether or not they got to propose.
+///
+/// The weight grows linearly with the share of blocks in the voter window whose votes bitmap
+/// includes the candidate, from min_weight for a validator that never voted to max_weight for
+/// one that voted on every block. With no blocks in the window, everyone gets max_weight.
+pub struct VoteParticipationHeuristic {
+    #[allow(unused)]
+    author: Author,
+    max_weight: u64,
+    min_weight: u64,
+    aggregation: NewBlockEventAggregation,
+}
+
+impl VoteParticipationHeuristic {
+    pub fn new(author: Author, max_weight: u64, min_weight: u64, window_size: usize) -> Self {
+        Self {
+            author,
+            max_weight,
+            min_weight: std::cmp::min(min_weight, max_weight),
+            aggregation: NewBlockEventAggregation::new(window_size, window_size),
+        }
+    }
+}
+
+impl ReputationHeuristic for VoteParticipationHeuristic {
+    fn get_weights(
+        &self,
+        epoch: u64,
+        candidates: &[Author],
+        history: &[NewBlockEvent],
+    ) -> Vec<u64> {
+        let votes = self.aggregation.count_votes(epoch, candidates, history);
+        let opportunities = self.aggregation.count_voting_opportunities(epoch, history);
+        if opportunities == 0 {
+            return vec![self.max_weight; candidates.len()];
+        }
+
+        candidates
+            .iter()
+            .map(|author| {
+                let cur_votes = *votes.get(author).unwrap_or(&0);
+                self.min_weight
+                    + (u128::from(self.max_weight - self.min_weight) * u128::from(cur_votes)
+                        / u128::from(opportunities)) as u64
+            })
+            .collect()
+    }
+}
+
+/// Heuristic combining the weights of several heuristics, as an average weighted by
+/// the weight given to each of them. Heuristics are best configured with weights on a
+/// similar scale, as otherwise the one with the largest weights dominates the others.
+pub struct CompositeHeuristic {
+    heuristics: Vec<(u64, Box<dyn ReputationHeuristic>)>,
+}
+
+impl CompositeHeuristic {
+    pub fn new(heuristics: Vec<(u64, Box<dyn ReputationHeuristic>)>) -> Self {
+        Self { heuristics }
+    }
+}
+
+impl ReputationHeuristic for CompositeHeuristic {
+    fn get_weights(
+        &self,
+        epoch: u64,
+        candidates: &[Author],
+        history: &[NewBlockEvent],
+    ) -> Vec<u64> {
+        let mut combined = vec![0u128; candidates.len()];
+        let mut total_weight = 0u128;
+        for (weight, heuristic) in &self.heuristics {
+            let weights = heuristic.get_weights(epoch, candidates, history);
+            assert_eq!(weights.len(), candidates.len());
+            for (sum, w) in combined.iter_mut().zip(weights) {
+                *sum += u128::from(*weight) * u128::from(w);
+            }
+            total_weight += u128::from(*weight);
+        }
+        combined
+            .into_iter()
+            .map(|sum| (sum / std::cmp::max(total_weight, 1)) as u64)
+            .collect()
+    }
+}
+
 /// Committed history based proposer election implementation that could help bias towards
 /// successful leaders to help improve performance.
+///
+/// Besides the primary proposer, each round can have num_fallback_proposers further valid
+/// proposers, drawn by weight from the remaining candidates. Any of them can propose, so
+/// a round can still succeed while its primary is down.
+///
+/// With a jail config, a candidate that failed as the proposer in the configured number of
+/// consecutive rounds it was elected for can't be elected for jail_rounds rounds after
+/// the block recording its last failure. Jailing never leaves a round without a candidate.
+///
+/// Weights, penalties and jails are all recomputed from the NewBlockEvents committed to the DB,
+/// and never persisted on their own: a restarted validator reads the same history back, and
+/// elects exactly the same proposers as the rest of the network, which it has to anyway.
 pub struct LeaderReputation {
     epoch: u64,
     proposers: Vec<Author>,
     backend: Box<dyn MetadataBackend>,
     heuristic: Box<dyn ReputationHeuristic>,
     exclude_round: u64,
+    num_fallback_proposers: usize,
+    jail_config: Option<LeaderJailConfig>,
 }
 
 impl LeaderReputation {
@@ -473,6 +827,8 @@ impl LeaderReputation {
         backend: Box<dyn MetadataBackend>,
         heuristic: Box<dyn ReputationHeuristic>,
         exclude_round: u64,
+        num_fallback_proposers: usize,
+        jail_config: Option<LeaderJailConfig>,
     ) -> Self {
         // assert!(proposers.is_sorted()) implementation from new api
         assert!(proposers.windows(2).all(|w| {
@@ -487,34 +843,143 @@ impl LeaderReputation {
             backend,
             heuristic,
             exclude_round,
+            num_fallback_proposers,
+            jail_config,
+        }
+    }
+
+    /// Whether each proposer is jailed in the given round, based on its failures in the history.
+    fn jailed_proposers(
+        &self,
+        jail_config: &LeaderJailConfig,
+        history: &[NewBlockEvent],
+        round: Round,
+    ) -> Vec<bool> {
+        let mut events: Vec<&NewBlockEvent> = history
+            .iter()
+            .filter(|meta| meta.epoch() == self.epoch)
+            .collect();
+        events.sort_by_key(|meta| meta.round());
+
+        // consecutive failures, and the round until which the proposer is jailed
+        let mut failures: HashMap<&Author, (u32, Round)> = HashMap::new();
+        for meta in events {
+            match NewBlockEventAggregation::indices_to_validators(
+                &self.proposers,
+                meta.failed_proposer_indices(),
+            ) {
+                Ok(failed_proposers) => {
+                    // With fallback proposers a round can have several failed proposers, so
+                    // the rounds of the failures aren't known, only that of the block.
+                    for failed_proposer in failed_proposers {
+                        let (count, jailed_until) = failures.entry(failed_proposer).or_default();
+                        *count += 1;
+                        if *count >= jail_config.consecutive_failures {
+                            *jailed_until = meta.round() + jail_config.jail_rounds;
+                        }
+                    }
+                }
+                Err(msg) => {
+                    error!(
+                        "Failed proposer conversion from indices failed at epoch {}, round {}: {}",
+                        meta.epoch(),
+                        meta.round(),
+                        msg
+                    )
+                }
+            }
+            if let Some((count, _)) = failures.get_mut(&meta.proposer()) {
+                *count = 0;
+            }
         }
+
+        self.proposers
+            .iter()
+            .map(|proposer| {
+                failures
+                    .get(proposer)
+                    .map_or(false, |(_, jailed_until)| round <= *jailed_until)
+            })
+            .collect()
+    }
+
+    /// Picks an index with probability proportional to its weight,
+    /// weights are expected not to be all zero.
+    fn choose_index(weights: &[u64], state: &mut Vec<u8>) -> usize {
+        let mut total_weight = 0;
+        let accumulated: Vec<u64> = weights
+            .iter()
+            .map(|w| {
+                total_weight += *w;
+                total_weight
+            })
+            .collect();
+        let chosen_weight = next(state) % total_weight;
+        accumulated
+            .binary_search_by(|w| {
+                if *w <= chosen_weight {
+                    Ordering::Less
+                } else {
+                    Ordering::Greater
+                }
+            })
+            .unwrap_err()
     }
 }
 
 impl ProposerElection for LeaderReputation {
     fn get_valid_proposer(&self, round: Round) -> Author {
+        self.get_valid_proposers(round)[0]
+    }
+
+    fn get_valid_proposers(&self, round: Round) -> Vec<Author> {
+        self.get_valid_proposers_and_weights(round).0
+    }
+
+    fn get_valid_proposers_and_weights(
+        &self,
+        round: Round,
+    ) -> (Vec<Author>, Option<Vec<(Author, u64)>>) {
         let target_round = round.saturating_sub(self.exclude_round);
         let sliding_window = self.backend.get_block_metadata(target_round);
         let mut weights = self
             .heuristic
             .get_weights(self.epoch, &self.proposers, &sliding_window);
         assert_eq!(weights.len(), self.proposers.len());
-        let mut total_weight = 0;
-        for w in &mut weights {
-            total_weight += *w;
-            *w = total_weight;
+
+        if let Some(jail_config) = &self.jail_config {
+            let jailed = self.jailed_proposers(jail_config, &sliding_window, round);
+            let has_unjailed_candidate = weights
+                .iter()
+                .zip(jailed.iter())
+                .any(|(w, is_jailed)| *w > 0 && !is_jailed);
+            if has_unjailed_candidate {
+                for (w, is_jailed) in weights.iter_mut().zip(jailed.iter()) {
+                    if *is_jailed {
+                        *w = 0;
+                    }
+                }
+            }
         }
+
+        let candidate_weights = self
+            .proposers
+            .iter()
+            .copied()
+            .zip(weights.iter().copied())
+            .collect();
+
+        // Fallbacks are drawn without replacement from the same state, so the primary
+        // proposer is the same as without fallbacks.
         let mut state = round.to_le_bytes().to_vec();
-        let chosen_weight = next(&mut state) % total_weight;
-        let chosen_index = weights
-            .binary_search_by(|w| {
-                if *w <= chosen_weight {
-                    Ordering::Less
-                } else {
-                    Ordering::Greater
-                }
-            })
-            .unwrap_err();
-        self.proposers[chosen_index]
+        let mut chosen = vec![];
+        while chosen.len() <= self.num_fallback_proposers
+            && (chosen.is_empty() || weights.iter().any(|w| *w > 0))
+        {
+            let chosen_index = Self::choose_index(&weights, &mut state);
+            weights[chosen_index] = 0;
+            chosen.push(self.proposers[chosen_index]);
+        }
+        (chosen, Some(candidate_weights))
     }
 }
@@ -1,7 +1,7 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
-use std::{collections::HashMap, sync::Arc};
+use std::{collections::HashMap, sync::Arc, time::Duration};
 
 use crate::liveness::{
     leader_reputation::{
@@ -18,6 +18,7 @@ use aptos_types::{
     block_metadata::new_block_event_key,
     contract_event::{ContractEvent, EventWithVersion},
     event::EventKey,
+    on_chain_config::LeaderJailConfig,
     transaction::Version,
     validator_signer::ValidatorSigner,
 };
@@ -26,7 +27,10 @@ use itertools::Itertools;
 use move_deps::move_core_types::{language_storage::TypeTag, move_resource::MoveStructType};
 use storage_interface::{DbReader, Order};
 
-use super::leader_reputation::{AptosDBBackend, ProposerAndVoterHeuristic};
+use super::leader_reputation::{
+    AptosDBBackend, CompositeHeuristic, FailedProposalPenaltyHeuristic, ProposerAndVoterHeuristic,
+    VoteParticipationHeuristic,
+};
 
 struct MockHistory {
     window_size: usize,
@@ -279,6 +283,128 @@ fn test_proposer_and_voter_heuristic() {
     );
 }
 
+#[test]
+fn test_aggregation_failed_proposal_rounds() {
+    let mut example1 = Example1::new();
+    let validators = example1.validators.clone();
+    let aggregation = NewBlockEventAggregation::new(2, 5);
+
+    example1.step1();
+    assert_eq!(
+        aggregation.failed_proposal_rounds(0, &validators, &example1.history),
+        HashMap::from([(validators[3], vec![1]), (validators[2], vec![4]),])
+    );
+
+    example1.step2();
+    assert_eq!(
+        aggregation.failed_proposal_rounds(0, &validators, &example1.history),
+        HashMap::from([(validators[2], vec![4]), (validators[1], vec![7, 9]),])
+    );
+}
+
+#[test]
+fn test_failed_proposal_penalty_heuristic() {
+    let mut example1 = Example1::new();
+    let validators = example1.validators.clone();
+    let heuristic = FailedProposalPenaltyHeuristic::new(validators[0], 100, 1, 100, 2, 5);
+
+    // no failures, everyone is active
+    assert_eq!(
+        heuristic.get_weights(0, &validators, &example1.history),
+        vec![100, 100, 100, 100]
+    );
+
+    // latest round is 6, failure of 3 in round 1 is halved twice, failure of 2 in round 4 once
+    example1.step1();
+    assert_eq!(
+        heuristic.get_weights(0, &validators, &example1.history),
+        vec![100, 100, 66, 80]
+    );
+
+    // failure of 3 left the window, 1 failed in rounds 7 and 9, latest round is 10
+    example1.step2();
+    assert_eq!(
+        heuristic.get_weights(0, &validators, &example1.history),
+        vec![100, 40, 89, 100]
+    );
+
+    // failures from previous epoch are not counted
+    example1.step3();
+    assert_eq!(
+        heuristic.get_weights(1, &validators, &example1.history),
+        vec![50, 100, 100, 100]
+    );
+}
+
+#[test]
+fn test_failed_proposal_penalty_heuristic_min_weight() {
+    let validators: Vec<_> = (0..2).into_iter().map(|_| Author::random()).collect();
+    let mut block_builder = TestBlockBuilder::new();
+    let history: Vec<_> = (0..10)
+        .into_iter()
+        .map(|_| block_builder.create_block(validators[0], vec![true, false], vec![1]))
+        .collect();
+    let heuristic = FailedProposalPenaltyHeuristic::new(validators[0], 1000, 5, 1000, 100, 20);
+
+    assert_eq!(
+        heuristic.get_weights(0, &validators, &history),
+        vec![1000, 5]
+    );
+}
+
+#[test]
+fn test_vote_participation_heuristic() {
+    let mut example1 = Example1::new();
+    let validators = example1.validators.clone();
+    let heuristic = VoteParticipationHeuristic::new(validators[0], 100, 10, 5);
+
+    // without any blocks, nobody missed a vote
+    assert_eq!(
+        heuristic.get_weights(0, &validators, &example1.history),
+        vec![100, 100, 100, 100]
+    );
+
+    // 0 voted on 2 out of 4 blocks, 1 and 2 on 3 of them, 3 never voted
+    example1.step1();
+    assert_eq!(
+        heuristic.get_weights(0, &validators, &example1.history),
+        vec![55, 77, 77, 10]
+    );
+
+    // 0 and 1 voted on 4 out of 5 blocks, 2 on 2 of them
+    example1.step2();
+    assert_eq!(
+        heuristic.get_weights(0, &validators, &example1.history),
+        vec![82, 82, 46, 10]
+    );
+}
+
+#[test]
+fn test_composite_heuristic() {
+    let mut example1 = Example1::new();
+    let validators = example1.validators.clone();
+    let active_inactive: Box<dyn ReputationHeuristic> =
+        Box::new(ActiveInactiveHeuristic::new(validators[0], 100, 10, 5));
+    let failed_proposal_penalty: Box<dyn ReputationHeuristic> = Box::new(
+        FailedProposalPenaltyHeuristic::new(validators[0], 100, 1, 100, 2, 5),
+    );
+    let heuristic =
+        CompositeHeuristic::new(vec![(70, active_inactive), (30, failed_proposal_penalty)]);
+
+    example1.step1();
+    // active/inactive gives [100, 100, 100, 10], failed proposal penalty [100, 100, 66, 80]
+    assert_eq!(
+        heuristic.get_weights(0, &validators, &example1.history),
+        vec![100, 100, 89, 31]
+    );
+
+    // no heuristics, no weights
+    assert_eq!(
+        CompositeHeuristic::new(vec![]).get_weights(0, &validators, &example1.history),
+        vec![0, 0, 0, 0]
+    );
+}
+
 /// #### ActiveInactiveHeuristic tests ####
 
 #[test]
@@ -465,6 +591,8 @@ fn test_api() {
             proposers.len(),
         )),
         4,
+        0,
+        None,
     );
     let round = 42u64;
     // first metadata is ignored because of window size 1
@@ -496,6 +624,114 @@ fn test_api() {
     assert!(!leader_reputation.is_valid_proposer(proposers[unexpected_index], 42));
 }
 
+#[test]
+fn test_fallback_proposers() {
+    let proposers: Vec<AccountAddress> =
+        (0..5).map(|_| AccountAddress::random()).sorted().collect();
+    let mut block_builder = TestBlockBuilder::new();
+    // only proposers 0 and 1 are active
+    let history = vec![block_builder.create_block(
+        proposers[0],
+        vec![false, true, false, false, false],
+        vec![],
+    )];
+    let new_leader_reputation = |num_fallback_proposers| {
+        LeaderReputation::new(
+            0,
+            proposers.clone(),
+            Box::new(MockHistory::new(1, history.clone())),
+            Box::new(ActiveInactiveHeuristic::new(
+                proposers[0],
+                1000,
+                0,
+                proposers.len(),
+            )),
+            4,
+            num_fallback_proposers,
+            None,
+        )
+    };
+    let without_fallbacks = new_leader_reputation(0);
+    let with_fallbacks = new_leader_reputation(2);
+
+    for round in 0..100 {
+        let primary = without_fallbacks.get_valid_proposer(round);
+        assert_eq!(without_fallbacks.get_valid_proposers(round), vec![primary]);
+
+        // the primary proposer doesn't change, and inactive nodes with zero weight are never chosen
+        let valid_proposers = with_fallbacks.get_valid_proposers(round);
+        assert_eq!(valid_proposers.len(), 2);
+        assert_eq!(valid_proposers[0], primary);
+        assert_eq!(with_fallbacks.get_valid_proposer(round), primary);
+        for (i, proposer) in proposers.iter().enumerate() {
+            assert_eq!(with_fallbacks.is_valid_proposer(*proposer, round), i < 2);
+        }
+    }
+}
+
+#[test]
+fn test_leader_jailing() {
+    let proposers: Vec<AccountAddress> =
+        (0..4).map(|_| AccountAddress::random()).sorted().collect();
+    let all_voted = vec![true; 4];
+    let new_leader_reputation = |history: Vec<NewBlockEvent>| {
+        LeaderReputation::new(
+            0,
+            proposers.clone(),
+            Box::new(MockHistory::new(10, history)),
+            Box::new(ActiveInactiveHeuristic::new(
+                proposers[0],
+                1000,
+                10,
+                proposers.len(),
+            )),
+            4,
+            3,
+            Some(LeaderJailConfig {
+                consecutive_failures: 2,
+                jail_rounds: 10,
+            }),
+        )
+    };
+
+    // proposer 1 fails in rounds 1 and 3, recorded by the blocks of rounds 2 and 4,
+    // and is jailed until round 14
+    let mut block_builder = TestBlockBuilder::new();
+    let leader_reputation = new_leader_reputation(vec![
+        block_builder.create_block(proposers[0], all_voted.clone(), vec![1]),
+        block_builder.create_block(proposers[0], all_voted.clone(), vec![1]),
+    ]);
+    for round in 5..=14 {
+        assert!(!leader_reputation.is_valid_proposer(proposers[1], round));
+        assert_eq!(leader_reputation.get_valid_proposers(round).len(), 3);
+    }
+    for round in 15..20 {
+        assert!(leader_reputation.is_valid_proposer(proposers[1], round));
+    }
+
+    // a successful proposal in between resets the count
+    let mut block_builder = TestBlockBuilder::new();
+    let leader_reputation = new_leader_reputation(vec![
+        block_builder.create_block(proposers[0], all_voted.clone(), vec![1]),
+        block_builder.create_block(proposers[1], all_voted.clone(), vec![]),
+        block_builder.create_block(proposers[0], all_voted.clone(), vec![1]),
+    ]);
+    for round in 6..20 {
+        assert!(leader_reputation.is_valid_proposer(proposers[1], round));
+    }
+
+    // jailing doesn't apply when it would leave no candidate,
+    // blocks are proposed by a validator outside of the candidates
+    let mut block_builder = TestBlockBuilder::new();
+    let outsider = AccountAddress::random();
+    let leader_reputation = new_leader_reputation(
+        (0..2)
+            .map(|_| block_builder.create_block(outsider, all_voted.clone(), vec![0, 1, 2, 3]))
+            .collect(),
+    );
+    assert_eq!(leader_reputation.get_valid_proposers(11).len(), 4);
+}
+
 struct MockDbReader {
     events: Mutex<Vec<EventWithVersion>>,
     random_address: Author,
@@ -504,6 +740,7 @@ struct MockDbReader {
     to_add_event_after_call: Mutex<Option<(u64, Round)>>,
 
     fetched: Mutex<usize>,
+    fetched_events: Mutex<usize>,
 }
 
 impl MockDbReader {
@@ -515,10 +752,24 @@ impl MockDbReader {
             idx: Mutex::new(0),
             to_add_event_after_call: Mutex::new(None),
             fetched: Mutex::new(0),
+            fetched_events: Mutex::new(0),
         }
     }
 
     pub fn add_event(&self, epoch: u64, round: Round) {
+        self.add_block_event(NewBlockEvent::new(
+            epoch,
+            round,
+            round,
+            vec![],
+            self.random_address,
+            vec![],
+            *self.last_timestamp.lock(),
+        ));
+        *self.last_timestamp.lock() += 100;
+    }
+
+    pub fn add_block_event(&self, event: NewBlockEvent) {
         let mut idx = self.idx.lock();
         *idx += 1;
         self.events.lock().push(EventWithVersion::new(
@@ -527,19 +778,9 @@ impl MockDbReader {
                 new_block_event_key(),
                 *idx,
                 TypeTag::Struct(NewBlockEvent::struct_tag()),
-                bcs::to_bytes(&NewBlockEvent::new(
-                    epoch,
-                    round,
-                    round,
-                    vec![],
-                    self.random_address,
-                    vec![],
-                    *self.last_timestamp.lock(),
-                ))
-                .unwrap(),
+                bcs::to_bytes(&event).unwrap(),
             ),
         ));
-        *self.last_timestamp.lock() += 100;
     }
 
     pub fn add_another_transaction(&self) {
@@ -553,6 +794,10 @@ impl MockDbReader {
     fn fetched(&self) -> usize {
         *self.fetched.lock()
     }
+
+    fn fetched_events(&self) -> usize {
+        *self.fetched_events.lock()
+    }
 }
 
 impl DbReader for MockDbReader {
@@ -564,16 +809,27 @@ impl DbReader for MockDbReader {
         limit: u64,
     ) -> anyhow::Result<Vec<EventWithVersion>> {
         *self.fetched.lock() += 1;
-        assert_eq!(start, u64::max_value());
-        assert!(order == Order::Descending);
         let events = self.events.lock();
         // println!("Events {:?}", *events);
-        Ok(events
-            .iter()
-            .skip(events.len().saturating_sub(limit as usize))
-            .rev()
-            .cloned()
-            .collect())
+        let result: Vec<_> = match order {
+            Order::Descending => {
+                assert_eq!(start, u64::max_value());
+                events
+                    .iter()
+                    .skip(events.len().saturating_sub(limit as usize))
+                    .rev()
+                    .cloned()
+                    .collect()
+            }
+            Order::Ascending => events
+                .iter()
+                .filter(|e| e.event.sequence_number() >= start)
+                .take(limit as usize)
+                .cloned()
+                .collect(),
+        };
+        *self.fetched_events.lock() += result.len();
+        Ok(result)
     }
 
     /// Returns the latest version, error on on non-bootstrapped DB.
@@ -666,3 +922,186 @@ fn backend_wrapper_test() {
     assert_history(14, vec![13, 12, 11], true);
     assert_history(14, vec![13, 12, 11], false);
 }
+
+#[test]
+fn backend_incremental_fetch_test() {
+    let aptos_db = Arc::new(MockDbReader::new());
+    let backend = AptosDBBackend::new(1, 3, 3, aptos_db.clone());
+
+    for i in 1..10 {
+        aptos_db.add_event(1, i);
+    }
+    let history = |round| -> Vec<Round> {
+        backend
+            .get_block_metadata(round)
+            .iter()
+            .map(|e| e.round())
+            .collect()
+    };
+
+    // first read fills the whole window
+    assert_eq!(history(10), vec![9, 8, 7]);
+    assert_eq!(aptos_db.fetched_events(), 6);
+
+    // afterwards only new events are read
+    aptos_db.add_event(1, 10);
+    assert_eq!(history(11), vec![10, 9, 8]);
+    assert_eq!(aptos_db.fetched_events(), 7);
+    aptos_db.add_event(1, 11);
+    aptos_db.add_event(1, 12);
+    assert_eq!(history(13), vec![12, 11, 10]);
+    assert_eq!(aptos_db.fetched_events(), 9);
+    // cached events older than the new ones are still served
+    assert_eq!(history(9), vec![9, 8, 7]);
+
+    // events from other epochs are skipped, but not read again
+    aptos_db.add_event(2, 1);
+    assert_eq!(history(13), vec![12, 11, 10]);
+    assert_eq!(aptos_db.fetched_events(), 10);
+    assert_eq!(history(14), vec![12, 11, 10]);
+    assert_eq!(aptos_db.fetched_events(), 10);
+
+    // when more events than the window arrive, everything is reread
+    for i in 13..20 {
+        aptos_db.add_event(1, i);
+    }
+    assert_eq!(history(20), vec![19, 18, 17]);
+    assert_eq!(aptos_db.fetched(), 6);
+}
+
+#[test]
+fn backend_prefetch_test() {
+    let aptos_db = Arc::new(MockDbReader::new());
+    let backend = AptosDBBackend::new(1, 3, 3, aptos_db.clone());
+    for i in 1..5 {
+        aptos_db.add_event(1, i);
+    }
+
+    // prefetching fills the cache, so the election path doesn't read the DB
+    backend.prefetch().unwrap();
+    assert_eq!(aptos_db.fetched(), 1);
+    let history = |round| -> Vec<Round> {
+        backend
+            .get_block_metadata(round)
+            .iter()
+            .map(|e| e.round())
+            .collect()
+    };
+    assert_eq!(history(5), vec![4, 3, 2]);
+    assert_eq!(aptos_db.fetched(), 1);
+
+    // nothing new, nothing to prefetch
+    backend.prefetch().unwrap();
+    assert_eq!(aptos_db.fetched(), 1);
+
+    aptos_db.add_event(1, 5);
+    backend.prefetch().unwrap();
+    assert_eq!(aptos_db.fetched(), 2);
+    assert_eq!(aptos_db.fetched_events(), 5);
+    assert_eq!(history(6), vec![5, 4, 3]);
+    assert_eq!(aptos_db.fetched(), 2);
+}
+
+#[tokio::test]
+async fn backend_refreshes_when_prefetch_lags() {
+    let aptos_db = Arc::new(MockDbReader::new());
+    for i in 1..5 {
+        aptos_db.add_event(1, i);
+    }
+    let backend = Arc::new(AptosDBBackend::new(1, 3, 3, aptos_db.clone()));
+    let prefetch = tokio::spawn(AptosDBBackend::prefetch_periodically(
+        Arc::downgrade(&backend),
+        Duration::from_secs(3600),
+    ));
+    while aptos_db.fetched() == 0 {
+        tokio::time::sleep(Duration::from_millis(1)).await;
+    }
+    let history = |round| -> Vec<Round> {
+        backend
+            .get_block_metadata(round)
+            .iter()
+            .map(|e| e.round())
+            .collect()
+    };
+    assert_eq!(history(4), vec![4, 3, 2]);
+    assert_eq!(history(5), vec![4, 3, 2]);
+    assert_eq!(aptos_db.fetched(), 1);
+
+    // the prefetched window already reaches round 4, so the election doesn't read the DB
+    aptos_db.add_event(1, 5);
+    assert_eq!(history(4), vec![4, 3, 2]);
+    assert_eq!(aptos_db.fetched(), 1);
+
+    // the prefetcher hasn't caught up with round 5 yet, so the election reads it itself
+    assert_eq!(history(5), vec![5, 4, 3]);
+    assert_eq!(aptos_db.fetched(), 2);
+
+    drop(backend);
+    prefetch.await.unwrap();
+}
+
+#[tokio::test]
+async fn backend_prefetch_stops_with_backend() {
+    let aptos_db = Arc::new(MockDbReader::new());
+    aptos_db.add_event(1, 1);
+    let backend = Arc::new(AptosDBBackend::new(1, 3, 3, aptos_db.clone()));
+    let prefetch = tokio::spawn(AptosDBBackend::prefetch_periodically(
+        Arc::downgrade(&backend),
+        Duration::from_millis(1),
+    ));
+    while aptos_db.fetched() == 0 {
+        tokio::time::sleep(Duration::from_millis(1)).await;
+    }
+    drop(backend);
+    prefetch.await.unwrap();
+}
+
+#[test]
+fn test_restart_recomputes_reputation() {
+    let proposers: Vec<AccountAddress> =
+        (0..4).map(|_| AccountAddress::random()).sorted().collect();
+    let aptos_db = Arc::new(MockDbReader::new());
+    let mut block_builder = TestBlockBuilder::new();
+    // proposer 3 keeps failing, everyone else is active
+    for i in 0..6 {
+        aptos_db.add_block_event(block_builder.create_block(
+            proposers[i % 3],
+            vec![true, true, true, false],
+            vec![3],
+        ));
+    }
+    let new_leader_reputation = || {
+        LeaderReputation::new(
+            0,
+            proposers.clone(),
+            Box::new(AptosDBBackend::new(0, 20, 10, aptos_db.clone())),
+            Box::new(FailedProposalPenaltyHeuristic::new(
+                proposers[0],
+                1000,
+                1,
+                100,
+                5,
+                20,
+            )),
+            0,
+            1,
+            Some(LeaderJailConfig {
+                consecutive_failures: 3,
+                jail_rounds: 20,
+            }),
+        )
+    };
+
+    // Reputation is derived from the committed history only, so a validator that restarts
+    // (with a new LeaderReputation and an empty cache) elects the same proposers,
+    // and doesn't forget about jailed ones.
+    let before_restart = new_leader_reputation();
+    let elected: Vec<_> = (13..30)
+        .map(|round| before_restart.get_valid_proposers(round))
+        .collect();
+    let after_restart = new_leader_reputation();
+    for (round, proposers_before) in (13..30).zip(elected) {
+        assert_eq!(after_restart.get_valid_proposers(round), proposers_before);
+        assert!(!after_restart.is_valid_proposer(proposers[3], round));
+    }
+}
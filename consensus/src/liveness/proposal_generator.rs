@@ -88,7 +88,7 @@ impl ProposalGenerator {
         let failed_authors = self.compute_failed_authors(
             round, // to include current round, as that is what failed
             quorum_cert.certified_block().round(),
-            true,
+            None,
             proposer_election,
         );
         Ok(Block::new_nil(round, quorum_cert, failed_authors))
@@ -177,7 +177,7 @@ impl ProposalGenerator {
         let failed_authors = self.compute_failed_authors(
             round,
             quorum_cert.certified_block().round(),
-            false,
+            Some(self.author),
             proposer_election,
         );
         // create block proposal
@@ -208,22 +208,35 @@ impl ProposalGenerator {
     }
 
     /// Compute the list of consecutive proposers from the
-    /// immediately preceeding rounds that didn't produce a successful block
+    /// immediately preceeding rounds that didn't produce a successful block.
+    /// Every valid proposer of a round without a block failed, as each had its
+    /// turn to propose. In the current round, the proposers whose turn came
+    /// before `proposer` failed, or all of them for a NIL block (`None`).
     pub fn compute_failed_authors(
         &self,
         round: Round,
         previous_round: Round,
-        include_cur_round: bool,
+        proposer: Option<Author>,
         proposer_election: &mut UnequivocalProposerElection,
     ) -> Vec<(Round, Author)> {
-        let end_round = round + (if include_cur_round { 1 } else { 0 });
+        let end_round = round + (if proposer.is_none() { 1 } else { 0 });
         let mut failed_authors = Vec::new();
         let start = std::cmp::max(
             previous_round + 1,
             end_round.saturating_sub(self.max_failed_authors_to_store as u64),
         );
         for i in start..end_round {
-            failed_authors.push((i, proposer_election.get_valid_proposer(i)));
+            for author in proposer_election.get_valid_proposers(i) {
+                failed_authors.push((i, author));
+            }
+        }
+        if let Some(proposer) = proposer {
+            for author in proposer_election.get_valid_proposers(round) {
+                if author == proposer {
+                    break;
+                }
+                failed_authors.push((round, author));
+            }
         }
 
         failed_authors
@@ -4,7 +4,8 @@
 use crate::{
     block_storage::BlockReader,
     liveness::{
-        proposal_generator::ProposalGenerator, rotating_proposer_election::RotatingProposer,
+        proposal_generator::ProposalGenerator, proposer_election::ProposerElection,
+        rotating_proposer_election::RotatingProposer,
         unequivocal_proposer_election::UnequivocalProposerElection,
     },
     test_utils::{build_empty_tree, MockPayloadManager, TreeInserter},
@@ -13,7 +14,7 @@ use crate::{
 use aptos_types::validator_signer::ValidatorSigner;
 use consensus_types::{
     block::{block_test_utils::certificate_for_genesis, Block},
-    common::Author,
+    common::{Author, Round},
 };
 use futures::{future::BoxFuture, FutureExt};
 use std::sync::Arc;
@@ -22,6 +23,19 @@ fn empty_callback() -> BoxFuture<'static, ()> {
     async move {}.boxed()
 }
 
+/// The same primary and fallback proposers in every round
+struct FixedProposers(Vec<Author>);
+
+impl ProposerElection for FixedProposers {
+    fn get_valid_proposer(&self, _round: Round) -> Author {
+        self.0[0]
+    }
+
+    fn get_valid_proposers(&self, _round: Round) -> Vec<Author> {
+        self.0.clone()
+    }
+}
+
 #[tokio::test]
 async fn test_proposal_generation_empty_tree() {
     let signer = ValidatorSigner::random(None);
@@ -189,3 +203,43 @@ async fn test_correct_failed_authors() {
     assert_eq!(result.failed_authors().unwrap()[3], (4, peer1));
     assert_eq!(result.failed_authors().unwrap()[4], (5, peer2));
 }
+
+#[tokio::test]
+async fn test_failed_authors_with_fallback_proposers() {
+    let signer = ValidatorSigner::random(None);
+    let proposal_generator = ProposalGenerator::new(
+        signer.author(),
+        build_empty_tree(),
+        Arc::new(MockPayloadManager::new(None)),
+        Arc::new(SimulatedTimeService::new()),
+        1,
+        10,
+    );
+    let primary = Author::random();
+    let first_fallback = Author::random();
+    let proposers = vec![primary, first_fallback, signer.author()];
+    let mut proposer_election =
+        UnequivocalProposerElection::new(Box::new(FixedProposers(proposers.clone())));
+
+    // Every proposer of a round without a block failed, and in the current round those whose
+    // turn came before the author of the block.
+    let failed_authors = proposal_generator.compute_failed_authors(
+        3,
+        1,
+        Some(signer.author()),
+        &mut proposer_election,
+    );
+    let mut expected: Vec<_> = proposers.iter().map(|author| (2, *author)).collect();
+    expected.extend([(3, primary), (3, first_fallback)]);
+    assert_eq!(failed_authors, expected);
+
+    let failed_authors =
+        proposal_generator.compute_failed_authors(3, 2, Some(primary), &mut proposer_election);
+    assert!(failed_authors.is_empty());
+
+    // Nobody proposed in the round of a NIL block
+    let failed_authors =
+        proposal_generator.compute_failed_authors(3, 2, None, &mut proposer_election);
+    let expected: Vec<_> = proposers.iter().map(|author| (3, *author)).collect();
+    assert_eq!(failed_authors, expected);
+}
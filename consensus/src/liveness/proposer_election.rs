@@ -10,12 +10,27 @@ pub trait ProposerElection {
     /// otherwise return None.
     /// Note that this function is synchronous.
     fn is_valid_proposer(&self, author: Author, round: Round) -> bool {
-        self.get_valid_proposer(round) == author
+        self.get_valid_proposers(round).contains(&author)
     }
 
     /// Return the valid proposer for a given round (this information can be
     /// used by e.g., voters for choosing the destinations for sending their votes to).
     fn get_valid_proposer(&self, round: Round) -> Author;
+
+    /// Return all valid proposers for a given round, primary proposer first,
+    /// followed by the fallback proposers, if the election has any.
+    fn get_valid_proposers(&self, round: Round) -> Vec<Author> {
+        vec![self.get_valid_proposer(round)]
+    }
+
+    /// Return the valid proposers for a given round, along with the weight each candidate
+    /// was elected with, if the election weighs candidates.
+    fn get_valid_proposers_and_weights(
+        &self,
+        round: Round,
+    ) -> (Vec<Author>, Option<Vec<(Author, u64)>>) {
+        (self.get_valid_proposers(round), None)
+    }
 }
 
 // next continuously mutates a state and returns a u64-index
@@ -150,6 +150,8 @@ pub struct RoundState {
     pending_votes: PendingVotes,
     // Vote sent locally for the current round.
     vote_sent: Option<Vote>,
+    // Index, among the valid proposers of the current round, of the one whose turn it is to propose.
+    proposer_turn: usize,
     // The handle to cancel previous timeout task when moving to next round.
     abort_handle: Option<AbortHandle>,
 }
@@ -196,6 +198,7 @@ impl RoundState {
             timeout_sender,
             pending_votes: PendingVotes::new(),
             vote_sent: None,
+            proposer_turn: 0,
             abort_handle: None,
         }
     }
@@ -239,6 +242,7 @@ impl RoundState {
             self.current_round = new_round;
             self.pending_votes = PendingVotes::new();
             self.vote_sent = None;
+            self.proposer_turn = 0;
             let timeout = self.setup_timeout(1);
             // The new round reason is QCReady in case both QC.round + 1 == new_round, otherwise
             // it's Timeout and TC.round + 1 == new_round.
@@ -283,6 +287,18 @@ impl RoundState {
         self.vote_sent.clone()
     }
 
+    /// Return the index, among the valid proposers of the current round, of the one whose turn it
+    /// is to propose.
+    pub fn proposer_turn(&self) -> usize {
+        self.proposer_turn
+    }
+
+    /// Pass the turn to propose in the current round to the next proposer, and return its index.
+    pub fn next_proposer_turn(&mut self) -> usize {
+        self.proposer_turn += 1;
+        self.proposer_turn
+    }
+
     /// Setup a longer timeout task for leader because it enters the round earlier.
     pub fn setup_leader_timeout(&mut self) {
         self.setup_timeout(2);
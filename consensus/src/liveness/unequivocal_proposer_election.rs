@@ -1,7 +1,7 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
-use std::cmp::Ordering;
+use std::{cmp::Ordering, collections::HashMap};
 
 use aptos_crypto::HashValue;
 use aptos_infallible::Mutex;
@@ -16,23 +16,28 @@ use super::proposer_election::ProposerElection;
 // Wrapper around ProposerElection.
 //
 // Provides is_valid_proposal that remembers, and rejects if
-// the same leader proposes multiple blocks.
+// the same leader proposes multiple blocks. With fallback proposers,
+// each of the round's proposers may propose one block.
 pub struct UnequivocalProposerElection {
     proposer_election: Box<dyn ProposerElection + Send + Sync>,
-    already_proposed: Mutex<(Round, HashValue)>,
+    already_proposed: Mutex<(Round, HashMap<Author, HashValue>)>,
 }
 
 impl ProposerElection for UnequivocalProposerElection {
     fn get_valid_proposer(&self, round: Round) -> Author {
         self.proposer_election.get_valid_proposer(round)
     }
+
+    fn get_valid_proposers(&self, round: Round) -> Vec<Author> {
+        self.proposer_election.get_valid_proposers(round)
+    }
 }
 
 impl UnequivocalProposerElection {
     pub fn new(proposer_election: Box<dyn ProposerElection + Send + Sync>) -> Self {
         Self {
             proposer_election,
-            already_proposed: Mutex::new((0, HashValue::zero())),
+            already_proposed: Mutex::new((0, HashMap::new())),
         }
     }
 
@@ -61,24 +66,27 @@ impl UnequivocalProposerElection {
             match block.round().cmp(&already_proposed.0) {
                 Ordering::Greater => {
                     already_proposed.0 = block.round();
-                    already_proposed.1 = block.id();
+                    already_proposed.1 = HashMap::from([(author, block.id())]);
                     true
                 }
-                Ordering::Equal => {
-                    if already_proposed.1 != block.id() {
+                Ordering::Equal => match already_proposed.1.get(&author) {
+                    Some(already_proposed_id) if *already_proposed_id != block.id() => {
                         error!(
                             SecurityEvent::InvalidConsensusProposal,
                             "Multiple proposals from {} for round {}: {} and {}",
                             author,
                             block.round(),
-                            already_proposed.1,
+                            already_proposed_id,
                             block.id()
                         );
                         false
-                    } else {
+                    }
+                    Some(_) => true,
+                    None => {
+                        already_proposed.1.insert(author, block.id());
                         true
                     }
-                }
+                },
                 Ordering::Less => {
                     println!("Older Block");
                     false
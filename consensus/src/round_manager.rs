@@ -202,6 +202,9 @@ impl RoundManager {
     /// once per round, we ensure that only at most one proposal can get generated per round to
     /// avoid accidental equivocation of proposals.
     ///
+    /// Only the primary proposer proposes right away, fallback proposers wait for their turn,
+    /// see `process_local_timeout`.
+    ///
     /// Replica:
     ///
     /// Do nothing
@@ -225,28 +228,31 @@ impl RoundManager {
         );
         if self
             .proposer_election
-            .is_valid_proposer(self.proposal_generator.author(), new_round_event.round)
+            .get_valid_proposer(new_round_event.round)
+            == self.proposal_generator.author()
         {
-            self.round_state.setup_leader_timeout();
-            let proposal_msg = self.generate_proposal(new_round_event).await?;
-            let mut network = self.network.clone();
-            #[cfg(feature = "failpoints")]
-            {
-                if self.check_whether_to_inject_reconfiguration_error() {
-                    self.attempt_to_inject_reconfiguration_error(&proposal_msg)
-                        .await?;
-                }
+            self.propose(new_round_event.round).await?;
+        }
+        Ok(())
+    }
+
+    async fn propose(&mut self, round: Round) -> anyhow::Result<()> {
+        self.round_state.setup_leader_timeout();
+        let proposal_msg = self.generate_proposal(round).await?;
+        let mut network = self.network.clone();
+        #[cfg(feature = "failpoints")]
+        {
+            if self.check_whether_to_inject_reconfiguration_error() {
+                self.attempt_to_inject_reconfiguration_error(&proposal_msg)
+                    .await?;
             }
-            network.broadcast_proposal(proposal_msg).await;
-            counters::PROPOSALS_COUNT.inc();
         }
+        network.broadcast_proposal(proposal_msg).await;
+        counters::PROPOSALS_COUNT.inc();
         Ok(())
     }
 
-    async fn generate_proposal(
-        &mut self,
-        new_round_event: NewRoundEvent,
-    ) -> anyhow::Result<ProposalMsg> {
+    async fn generate_proposal(&mut self, round: Round) -> anyhow::Result<ProposalMsg> {
         // Proposal generator will ensure that at most one proposal is generated per round
         let sync_info = self.block_store.sync_info();
         let mut sender = self.network.clone();
@@ -257,7 +263,7 @@ impl RoundManager {
 
         let proposal = self
             .proposal_generator
-            .generate_proposal(new_round_event.round, &mut self.proposer_election, callback)
+            .generate_proposal(round, &mut self.proposer_election, callback)
             .await?;
         let signature = self.safety_rules.lock().sign_proposal(&proposal)?;
         let signed_proposal =
@@ -407,6 +413,32 @@ impl RoundManager {
         }
     }
 
+    /// With fallback proposers, the round's proposers take turns: until each has had its turn, a
+    /// local timeout passes the turn to propose on to the next one, unless this replica already
+    /// voted in this round. Only then does the replica time out the round, see `timeout_round`.
+    /// Returns Ok(()) when the timeout is stale or passed the turn on.
+    pub async fn process_local_timeout(&mut self, round: Round) -> anyhow::Result<()> {
+        if !self.round_state.process_local_timeout(round) {
+            return Ok(());
+        }
+
+        let proposers = self.proposer_election.get_valid_proposers(round);
+        if self.round_state.vote_sent().is_none()
+            && self.round_state.proposer_turn() + 1 < proposers.len()
+        {
+            let turn = self.round_state.next_proposer_turn();
+            info!(
+                self.new_log(LogEvent::Timeout).remote_peer(proposers[turn]),
+                "No proposal yet, fallback proposer {} may propose", turn
+            );
+            if proposers[turn] == self.proposal_generator.author() {
+                self.propose(round).await?;
+            }
+            return Ok(());
+        }
+        self.timeout_round(round).await
+    }
+
     /// The replica broadcasts a "timeout vote message", which includes the round signature, which
     /// can be aggregated to a TimeoutCertificate.
     /// The timeout vote message can be one of the following three options:
@@ -414,12 +446,8 @@ impl RoundManager {
     /// a timeout.
     /// 2) Otherwise vote for a NIL block and sign a timeout.
     /// Note this function returns Err even if messages are broadcasted successfully because timeout
-    /// is considered as error. It only returns Ok(()) when the timeout is stale.
-    pub async fn process_local_timeout(&mut self, round: Round) -> anyhow::Result<()> {
-        if !self.round_state.process_local_timeout(round) {
-            return Ok(());
-        }
-
+    /// is considered as error.
+    async fn timeout_round(&mut self, round: Round) -> anyhow::Result<()> {
         if self.sync_only() {
             self.network
                 .broadcast_sync_info(self.block_store.sync_info())
@@ -503,7 +531,7 @@ impl RoundManager {
         let expected_failed_authors = self.proposal_generator.compute_failed_authors(
             proposal.round(),
             proposal.quorum_cert().certified_block().round(),
-            false,
+            Some(author),
             &mut self.proposer_election,
         );
         ensure!(
@@ -532,17 +560,18 @@ impl RoundManager {
             .await
             .context("[RoundManager] Process proposal")?;
 
-        let recipient = self
+        // Any of the next round's proposers can aggregate the votes into a QC.
+        let recipients = self
             .proposer_election
-            .get_valid_proposer(proposal_round + 1);
+            .get_valid_proposers(proposal_round + 1);
         info!(
-            self.new_log(LogEvent::Vote).remote_peer(recipient),
+            self.new_log(LogEvent::Vote).remote_peer(recipients[0]),
             "{}", vote
         );
 
         self.round_state.record_vote(vote.clone());
         let vote_msg = VoteMsg::new(vote, self.block_store.sync_info());
-        self.network.send_vote(vote_msg, vec![recipient]).await;
+        self.network.send_vote(vote_msg, recipients).await;
         Ok(())
     }
 
@@ -674,8 +703,14 @@ impl RoundManager {
             VoteReceptionResult::New2ChainTimeoutCertificate(tc) => {
                 self.new_2chain_tc_aggregated(tc).await
             }
+            // Enough validators timed out the round that it can't succeed anymore, however
+            // many fallback proposers are left.
             VoteReceptionResult::EchoTimeout(_) if !self.round_state.is_vote_timeout() => {
-                self.process_local_timeout(round).await
+                if self.round_state.process_local_timeout(round) {
+                    self.timeout_round(round).await
+                } else {
+                    Ok(())
+                }
             }
             _ => Ok(()),
         }
@@ -66,13 +66,26 @@ use safety_rules::{PersistentSafetyStorage, SafetyRulesManager};
 use std::{iter::FromIterator, sync::Arc, time::Duration};
 use tokio::runtime::Handle;
 
+/// The same primary and fallback proposers in every round
+struct FixedProposers(Vec<Author>);
+
+impl ProposerElection for FixedProposers {
+    fn get_valid_proposer(&self, _round: Round) -> Author {
+        self.0[0]
+    }
+
+    fn get_valid_proposers(&self, _round: Round) -> Vec<Author> {
+        self.0.clone()
+    }
+}
+
 /// Auxiliary struct that is setting up node environment for the test.
 pub struct NodeSetup {
     block_store: Arc<BlockStore>,
     round_manager: RoundManager,
     storage: Arc<MockStorage>,
     signer: ValidatorSigner,
-    proposer_author: Author,
+    proposers: Vec<Author>,
     safety_rules_manager: SafetyRulesManager,
     all_events: Box<dyn Stream<Item = Event<ConsensusMsg>> + Send + Unpin>,
     commit_cb_receiver: mpsc::UnboundedReceiver<LedgerInfoWithSignatures>,
@@ -88,17 +101,44 @@ impl NodeSetup {
         RoundState::new(time_interval, time_service, round_timeout_sender)
     }
 
-    fn create_proposer_election(author: Author) -> Box<dyn ProposerElection + Send + Sync> {
-        Box::new(RotatingProposer::new(vec![author], 1))
+    fn create_proposer_election(proposers: Vec<Author>) -> Box<dyn ProposerElection + Send + Sync> {
+        if proposers.len() == 1 {
+            Box::new(RotatingProposer::new(proposers, 1))
+        } else {
+            Box::new(FixedProposers(proposers))
+        }
     }
 
     fn create_nodes(
         playground: &mut NetworkPlayground,
         executor: Handle,
         num_nodes: usize,
+    ) -> Vec<Self> {
+        Self::create_nodes_with_proposers(playground, executor, num_nodes, 1)
+    }
+
+    /// Like `create_nodes`, with the other nodes as the fallback proposers of the first one in
+    /// every round.
+    fn create_nodes_with_fallback_proposers(
+        playground: &mut NetworkPlayground,
+        executor: Handle,
+        num_nodes: usize,
+    ) -> Vec<Self> {
+        Self::create_nodes_with_proposers(playground, executor, num_nodes, num_nodes)
+    }
+
+    fn create_nodes_with_proposers(
+        playground: &mut NetworkPlayground,
+        executor: Handle,
+        num_nodes: usize,
+        num_proposers: usize,
     ) -> Vec<Self> {
         let (signers, validators) = random_validator_verifier(num_nodes, None, false);
-        let proposer_author = signers[0].author();
+        let proposers: Vec<_> = signers
+            .iter()
+            .take(num_proposers)
+            .map(|signer| signer.author())
+            .collect();
         let validator_set = (&validators).into();
         let waypoint =
             Waypoint::new_epoch_boundary(&LedgerInfo::mock_genesis(Some(validator_set))).unwrap();
@@ -131,7 +171,7 @@ impl NodeSetup {
                 playground,
                 executor.clone(),
                 signer.to_owned(),
-                proposer_author,
+                proposers.clone(),
                 storage,
                 initial_data,
                 safety_rules_manager,
@@ -145,7 +185,7 @@ impl NodeSetup {
         playground: &mut NetworkPlayground,
         executor: Handle,
         signer: ValidatorSigner,
-        proposer_author: Author,
+        proposers: Vec<Author>,
         storage: Arc<MockStorage>,
         initial_data: RecoveryData,
         safety_rules_manager: SafetyRulesManager,
@@ -207,7 +247,7 @@ impl NodeSetup {
         );
 
         let round_state = Self::create_round_state(time_service);
-        let proposer_election = Self::create_proposer_election(proposer_author);
+        let proposer_election = Self::create_proposer_election(proposers.clone());
         let mut safety_rules =
             MetricsSafetyRules::new(safety_rules_manager.client(), storage.clone());
         safety_rules.perform_initialize().unwrap();
@@ -230,7 +270,7 @@ impl NodeSetup {
             round_manager,
             storage,
             signer,
-            proposer_author,
+            proposers,
             safety_rules_manager,
             all_events,
             commit_cb_receiver,
@@ -248,7 +288,7 @@ impl NodeSetup {
             playground,
             executor,
             self.signer,
-            self.proposer_author,
+            self.proposers,
             self.storage,
             recover_data,
             self.safety_rules_manager,
@@ -546,6 +586,81 @@ fn no_vote_on_invalid_proposer() {
     });
 }
 
+#[test]
+/// A fallback proposer's proposal arriving after the primary's in the same round is not taken for
+/// an equivocation, while a second proposal from the fallback proposer still is
+fn fallback_proposal_after_primary_proposal() {
+    let mut runtime = consensus_runtime();
+    let mut playground = NetworkPlayground::new(runtime.handle().clone());
+    let mut nodes = NodeSetup::create_nodes_with_fallback_proposers(
+        &mut playground,
+        runtime.handle().clone(),
+        2,
+    );
+    let fallback_proposer = nodes.pop().unwrap();
+    let mut node = nodes.pop().unwrap();
+    let genesis_qc = certificate_for_genesis();
+    let primary_block = Block::new_proposal(
+        Payload::new_empty(),
+        1,
+        1,
+        genesis_qc.clone(),
+        &node.signer,
+        Vec::new(),
+    );
+    // the primary's turn came first
+    let failed_authors = vec![(1, node.signer.author())];
+    let fallback_block = Block::new_proposal(
+        Payload::new_empty(),
+        1,
+        1,
+        genesis_qc.clone(),
+        &fallback_proposer.signer,
+        failed_authors.clone(),
+    );
+    let fallback_duplicate_block = Block::new_proposal(
+        Payload::new_empty(),
+        1,
+        2,
+        genesis_qc,
+        &fallback_proposer.signer,
+        failed_authors,
+    );
+    let primary_block_id = primary_block.id();
+    let fallback_block_id = fallback_block.id();
+    let fallback_duplicate_block_id = fallback_duplicate_block.id();
+    timed_block_on(&mut runtime, async {
+        // clear the message queue
+        node.next_proposal().await;
+
+        node.round_manager
+            .process_proposal(primary_block)
+            .await
+            .unwrap();
+        let vote_msg = node.next_vote().await;
+        assert_eq!(
+            vote_msg.vote().vote_data().proposed().id(),
+            primary_block_id
+        );
+
+        // the node already voted in this round, but the block is valid and gets added
+        node.round_manager
+            .process_proposal(fallback_block)
+            .await
+            .unwrap_err();
+        assert!(node.block_store.get_block(fallback_block_id).is_some());
+
+        node.round_manager
+            .process_proposal(fallback_duplicate_block)
+            .await
+            .unwrap_err();
+        assert!(node
+            .block_store
+            .get_block(fallback_duplicate_block_id)
+            .is_none());
+    });
+}
+
 #[test]
 /// We allow to 'skip' round if proposal carries timeout certificate for next round
 fn new_round_on_timeout_certificate() {
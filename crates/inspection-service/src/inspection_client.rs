@@ -78,4 +78,12 @@ impl InspectionClient {
             })
             .collect()
     }
+
+    /// Tells the node all versions up to `version` are backed up, so it can prune them.
+    pub async fn post_backup_watermark(&self, version: u64) -> Result<()> {
+        let mut url = self.url.clone();
+        url.set_path(&format!("backup_watermark/{}", version));
+        self.client.post(url).send().await?.error_for_status()?;
+        Ok(())
+    }
 }
@@ -3,10 +3,13 @@
 
 use crate::{gather_metrics, json_encoder::JsonEncoder, NUM_METRICS};
 use aptos_config::config::NodeConfig;
+use aptos_types::transaction::Version;
+use aptosdb::{AptosDB, PrunerIndex};
 use hyper::{
     service::{make_service_fn, service_fn},
     Body, Method, Request, Response, Server, StatusCode,
 };
+use once_cell::sync::OnceCell;
 use prometheus::{
     proto::{MetricFamily, MetricType},
     Encoder, TextEncoder,
@@ -15,6 +18,7 @@ use std::{
     collections::HashMap,
     convert::Infallible,
     net::{SocketAddr, ToSocketAddrs},
+    sync::Arc,
     thread,
 };
 use tokio::runtime;
@@ -23,6 +27,15 @@ use tokio::runtime;
 const DISABLED_ENDPOINT_MESSAGE: &str =
     "This endpoint is disabled! Enable it in the InspectionServiceConfig.";
 
+// The DB the pruner controls act on. The service starts before the DB is opened, so it's only set
+// afterwards, with `set_aptos_db()`.
+static APTOS_DB: OnceCell<Arc<AptosDB>> = OnceCell::new();
+
+/// Gives the service the DB, once it's opened.
+pub fn set_aptos_db(aptos_db: Arc<AptosDB>) {
+    let _ = APTOS_DB.set(aptos_db);
+}
+
 fn encode_metrics(encoder: impl Encoder) -> Vec<u8> {
     let metric_families = gather_metrics();
     let mut buffer = vec![];
@@ -81,7 +94,77 @@ pub fn get_all_metrics() -> HashMap<String, String> {
     get_metrics(all_metric_families)
 }
 
-async fn serve_requests(
+/// Returns the metrics of the storage pruners: their windows, batch sizes and progress (i.e.,
+/// min readable version, target version, lag and last batch duration).
+pub fn get_pruner_metrics() -> HashMap<String, String> {
+    let pruner_metric_families = gather_metrics()
+        .into_iter()
+        .filter(|metric_family| metric_family.get_name().contains("prune"))
+        .collect();
+    get_metrics(pruner_metric_families)
+}
+
+/// Handles `/prune/<ledger|state_store|event_store>/<target_version>`, which makes the pruner prune
+/// up to the target version right away, e.g. to free disk space.
+fn prune(path: &str) -> (StatusCode, String) {
+    let mut segments = path.trim_start_matches("/prune/").split('/');
+    let pruner_index = match segments.next() {
+        Some("ledger") => PrunerIndex::LedgerPrunerIndex,
+        Some("state_store") => PrunerIndex::StateStorePrunerIndex,
+        Some("event_store") => PrunerIndex::EventStorePrunerIndex,
+        _ => return (StatusCode::NOT_FOUND, String::new()),
+    };
+    let target_version = match (segments.next().map(str::parse::<Version>), segments.next()) {
+        (Some(Ok(target_version)), None) => target_version,
+        _ => {
+            return (
+                StatusCode::BAD_REQUEST,
+                "Expected /prune/<pruner>/<target_version>".into(),
+            )
+        }
+    };
+    with_aptos_db(|aptos_db| aptos_db.prune_to(pruner_index, target_version))
+}
+
+/// Handles `/pruners/<pause|resume>`, e.g. to hold the deletions while a backup is taken.
+fn pause_or_resume_pruners(path: &str) -> (StatusCode, String) {
+    let paused = match path {
+        "/pruners/pause" => true,
+        "/pruners/resume" => false,
+        _ => return (StatusCode::NOT_FOUND, String::new()),
+    };
+    with_aptos_db(|aptos_db| aptos_db.set_pruners_paused(paused))
+}
+
+/// Handles `/backup_watermark/<version>`, with which the backup coordinator reports how far the
+/// backup goes, so the pruners can go as far with `prune_only_backed_up`.
+fn set_backup_watermark(path: &str) -> (StatusCode, String) {
+    match path
+        .trim_start_matches("/backup_watermark/")
+        .parse::<Version>()
+    {
+        Ok(version) => with_aptos_db(|aptos_db| aptos_db.set_backup_watermark(version)),
+        Err(_) => (
+            StatusCode::BAD_REQUEST,
+            "Expected /backup_watermark/<version>".into(),
+        ),
+    }
+}
+
+fn with_aptos_db(f: impl FnOnce(&AptosDB) -> anyhow::Result<()>) -> (StatusCode, String) {
+    match APTOS_DB.get() {
+        Some(aptos_db) => match f(aptos_db) {
+            Ok(()) => (StatusCode::OK, String::new()),
+            Err(error) => (StatusCode::INTERNAL_SERVER_ERROR, error.to_string()),
+        },
+        None => (
+            StatusCode::SERVICE_UNAVAILABLE,
+            "The DB isn't open yet.".into(),
+        ),
+    }
+}
+
+pub(crate) async fn serve_requests(
     req: Request<Body>,
     node_config: NodeConfig,
 ) -> Result<Response<Body>, hyper::Error> {
@@ -117,6 +200,34 @@ async fn serve_requests(
             let encoded_metrics = serde_json::to_string(&metrics).unwrap();
             *resp.body_mut() = Body::from(encoded_metrics);
         }
+        // Exposes the storage pruners' progress, to tell if pruning keeps up with the commits
+        (&Method::GET, "/pruner_progress") => {
+            let metrics = get_pruner_metrics();
+            let encoded_metrics = serde_json::to_string(&metrics).unwrap();
+            *resp.body_mut() = Body::from(encoded_metrics);
+        }
+        // Lets operators trigger and pause the storage pruners, which deletes data, and lets the backup
+        // coordinator report the backed up versions the pruners may wait for
+        (&Method::POST, path)
+            if path.starts_with("/prune/")
+                || path.starts_with("/pruners/")
+                || path.starts_with("/backup_watermark/") =>
+        {
+            if node_config.inspection_service.expose_pruner_controls {
+                let (status, message) = if path.starts_with("/prune/") {
+                    prune(path)
+                } else if path.starts_with("/pruners/") {
+                    pause_or_resume_pruners(path)
+                } else {
+                    set_backup_watermark(path)
+                };
+                *resp.status_mut() = status;
+                *resp.body_mut() = Body::from(message);
+            } else {
+                *resp.status_mut() = StatusCode::FORBIDDEN;
+                *resp.body_mut() = Body::from(DISABLED_ENDPOINT_MESSAGE);
+            }
+        }
         // Expose the system and build information
         (&Method::GET, "/system_information") => {
             if node_config.inspection_service.expose_system_information {
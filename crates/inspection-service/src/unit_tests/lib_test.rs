@@ -1,15 +1,23 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::inspection_service::get_all_metrics;
+use crate::inspection_service::{get_all_metrics, get_pruner_metrics, serve_requests};
+use aptos_config::config::NodeConfig;
 use assert_approx_eq::assert_approx_eq;
+use hyper::{Body, Request, StatusCode};
 use once_cell::sync::Lazy;
-use prometheus::{proto::MetricFamily, register_int_counter, Counter, IntCounter, Opts, Registry};
+use prometheus::{
+    proto::MetricFamily, register_int_counter, register_int_gauge, Counter, IntCounter, IntGauge,
+    Opts, Registry,
+};
 use rusty_fork::rusty_fork_test;
 
 const INT_COUNTER_NAME: &str = "INT_COUNTER";
 pub static INT_COUNTER: Lazy<IntCounter> =
     Lazy::new(|| register_int_counter!(INT_COUNTER_NAME, "An integer counter").unwrap());
+const PRUNER_GAUGE_NAME: &str = "aptos_pruner_test_lag";
+pub static PRUNER_GAUGE: Lazy<IntGauge> =
+    Lazy::new(|| register_int_gauge!(PRUNER_GAUGE_NAME, "A pruner gauge").unwrap());
 
 rusty_fork_test! {
 #[test]
@@ -70,3 +78,61 @@ fn get_all_metrics_test() {
     }
 }
 }
+
+rusty_fork_test! {
+#[test]
+fn get_pruner_metrics_test() {
+    INT_COUNTER.inc();
+    PRUNER_GAUGE.set(42);
+
+    let metrics = get_pruner_metrics();
+    assert_eq!(metrics.len(), 1);
+    assert_eq!(metrics.get(&format!("{}{{}}", PRUNER_GAUGE_NAME)).unwrap(), "42");
+}
+}
+
+#[test]
+fn pruner_controls_test() {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let post = |node_config: &NodeConfig, path: &str| {
+        let request = Request::post(path).body(Body::empty()).unwrap();
+        runtime
+            .block_on(serve_requests(request, node_config.clone()))
+            .unwrap()
+            .status()
+    };
+
+    // Disabled by default
+    let mut node_config = NodeConfig::default();
+    assert_eq!(post(&node_config, "/prune/ledger/0"), StatusCode::FORBIDDEN);
+    assert_eq!(post(&node_config, "/pruners/pause"), StatusCode::FORBIDDEN);
+    assert_eq!(
+        post(&node_config, "/backup_watermark/0"),
+        StatusCode::FORBIDDEN
+    );
+
+    // Malformed requests are turned down before going to the DB, which isn't there in tests
+    node_config.inspection_service.expose_pruner_controls = true;
+    assert_eq!(post(&node_config, "/prune/x/0"), StatusCode::NOT_FOUND);
+    assert_eq!(
+        post(&node_config, "/prune/ledger/x"),
+        StatusCode::BAD_REQUEST
+    );
+    assert_eq!(post(&node_config, "/pruners/x"), StatusCode::NOT_FOUND);
+    assert_eq!(
+        post(&node_config, "/backup_watermark/x"),
+        StatusCode::BAD_REQUEST
+    );
+    assert_eq!(
+        post(&node_config, "/prune/ledger/0"),
+        StatusCode::SERVICE_UNAVAILABLE
+    );
+    assert_eq!(
+        post(&node_config, "/pruners/pause"),
+        StatusCode::SERVICE_UNAVAILABLE
+    );
+    assert_eq!(
+        post(&node_config, "/backup_watermark/0"),
+        StatusCode::SERVICE_UNAVAILABLE
+    );
+}
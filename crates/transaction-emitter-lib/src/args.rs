@@ -13,6 +13,8 @@ use clap::{ArgEnum, Parser};
 use serde::{Deserialize, Serialize};
 use url::Url;
 
+use crate::emitter::traffic_profile::TrafficProfile;
+
 const DEFAULT_API_PORT: u16 = 8080;
 
 #[derive(Clone, Debug, Default, Deserialize, Parser, Serialize)]
@@ -99,6 +101,25 @@ pub struct EmitArgs {
 
     #[clap(long, arg_enum, default_value = "p2p", ignore_case = true)]
     pub transaction_type: TransactionType,
+
+    /// Weighted transaction types to emit instead of --transaction-type, e.g.
+    /// `--transaction-mix p2p:70 account-generation:30`
+    #[clap(long, multiple_values = true, parse(try_from_str = parse_transaction_mix_entry))]
+    pub transaction_mix: Vec<(TransactionType, usize)>,
+
+    /// Vary the target TPS over time instead of emitting as fast as possible, e.g.
+    /// `ramp:100:1000:60`, `burst:100:1000:60:10` or `plateaus:100@30,500@30`
+    #[clap(long)]
+    pub traffic_profile: Option<TrafficProfile>,
+}
+
+fn parse_transaction_mix_entry(entry: &str) -> Result<(TransactionType, usize)> {
+    let (transaction_type, weight) = entry
+        .split_once(':')
+        .ok_or_else(|| format_err!("Expected <transaction type>:<weight>, got {}", entry))?;
+    let transaction_type = TransactionType::from_str(transaction_type, true)
+        .map_err(|e| format_err!("Invalid transaction type {}: {}", transaction_type, e))?;
+    Ok((transaction_type, weight.parse()?))
 }
 
 fn parse_target(target: &str) -> Result<Url> {
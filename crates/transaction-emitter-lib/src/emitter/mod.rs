@@ -4,10 +4,11 @@
 pub mod account_minter;
 pub mod stats;
 pub mod submission_worker;
+pub mod traffic_profile;
 
 use ::aptos_logger::*;
 use again::RetryPolicy;
-use anyhow::{anyhow, format_err, Result};
+use anyhow::{anyhow, bail, format_err, Result};
 use aptos_rest_client::Client as RestClient;
 use aptos_sdk::{
     move_types::account_address::AccountAddress,
@@ -33,10 +34,15 @@ use tokio::{runtime::Handle, task::JoinHandle, time};
 
 use crate::{
     args::TransactionType,
-    emitter::{account_minter::AccountMinter, submission_worker::SubmissionWorker},
+    emitter::{
+        account_minter::AccountMinter,
+        submission_worker::SubmissionWorker,
+        traffic_profile::{TrafficProfile, TrafficShaper},
+    },
     transaction_generator::{
         account_generator::AccountGeneratorCreator, nft_mint::NFTMintGeneratorCreator,
-        p2p_transaction_generator::P2PTransactionGeneratorCreator, TransactionGeneratorCreator,
+        p2p_transaction_generator::P2PTransactionGeneratorCreator,
+        transaction_mix_generator::TxnMixGeneratorCreator, TransactionGeneratorCreator,
     },
 };
 use aptos_sdk::transaction_builder::aptos_stdlib;
@@ -91,6 +97,9 @@ pub struct EmitJobRequest {
     pub duration: Duration,
     vasp: bool,
     transaction_type: TransactionType,
+    /// Weighted transaction types, which take precedence over `transaction_type` if not empty
+    transaction_mix: Vec<(TransactionType, usize)>,
+    traffic_profile: Option<TrafficProfile>,
 }
 
 impl Default for EmitJobRequest {
@@ -105,6 +114,8 @@ impl Default for EmitJobRequest {
             duration: Duration::from_secs(300),
             vasp: false,
             transaction_type: TransactionType::P2P,
+            transaction_mix: Vec::new(),
+            traffic_profile: None,
         }
     }
 }
@@ -149,7 +160,16 @@ impl EmitJobRequest {
         self
     }
 
-    pub fn fixed_tps(self, target_tps: NonZeroU64) -> Self {
+    /// Emits each transaction type with a probability proportional to its weight, e.g. 70% of
+    /// transfers and 30% of account creations with `[(P2P, 70), (AccountGeneration, 30)]`
+    pub fn transaction_mix(mut self, transaction_mix: Vec<(TransactionType, usize)>) -> Self {
+        self.transaction_mix = transaction_mix;
+        self
+    }
+
+    /// Emits a constant TPS, which overrides any traffic profile
+    pub fn fixed_tps(mut self, target_tps: NonZeroU64) -> Self {
+        self.traffic_profile = None;
         let clients_count = self.rest_clients.len() as u64;
         let num_workers = target_tps.get() / clients_count + 1;
         let wait_time = clients_count * num_workers * 1000 / target_tps.get();
@@ -164,6 +184,14 @@ impl EmitJobRequest {
             .accounts_per_client(1)
     }
 
+    /// Shapes the emitted TPS over time. Once the endpoints are known, workers are sized for the
+    /// peak TPS the way `fixed_tps` sizes them, and wait between transactions according to the
+    /// profile's current TPS
+    pub fn traffic_profile(mut self, traffic_profile: TrafficProfile) -> Self {
+        self.traffic_profile = Some(traffic_profile);
+        self
+    }
+
     pub fn vasp(mut self) -> Self {
         self.vasp = true;
         self
@@ -187,6 +215,26 @@ pub struct EmitJob {
     stats: Arc<StatsAccumulator>,
 }
 
+impl EmitJob {
+    /// The stats of the job so far, without stopping it
+    pub fn peek_stats(&self) -> TxnStats {
+        self.stats.accumulate()
+    }
+
+    /// Stops the job without handing its accounts back to the emitter which started it, so a
+    /// job can keep running after the emitter, and the root account it borrows, are dropped
+    pub async fn stop(self) -> TxnStats {
+        self.stop.store(true, Ordering::Relaxed);
+        for worker in self.workers {
+            worker
+                .join_handle
+                .await
+                .expect("TxnEmitter worker thread failed");
+        }
+        self.stats.accumulate()
+    }
+}
+
 #[derive(Debug)]
 pub struct TxnEmitter<'t> {
     accounts: Vec<LocalAccount>,
@@ -245,6 +293,20 @@ impl<'t> TxnEmitter<'t> {
     }
 
     pub async fn start_job(&mut self, req: EmitJobRequest) -> Result<EmitJob> {
+        if !req.transaction_mix.is_empty()
+            && req.transaction_mix.iter().all(|(_, weight)| *weight == 0)
+        {
+            bail!("At least one transaction type of the mix must have a weight");
+        }
+        let req = match req.traffic_profile.clone() {
+            Some(traffic_profile) => {
+                let peak_tps = NonZeroU64::new(traffic_profile.peak_tps().max(1)).unwrap();
+                let mut req = req.fixed_tps(peak_tps);
+                req.traffic_profile = Some(traffic_profile);
+                req
+            }
+            None => req,
+        };
         let workers_per_endpoint = match req.workers_per_endpoint {
             Some(x) => x,
             None => {
@@ -281,26 +343,23 @@ impl<'t> TxnEmitter<'t> {
         let stop = Arc::new(AtomicBool::new(false));
         let stats = Arc::new(StatsAccumulator::default());
         let tokio_handle = Handle::current();
-        let txn_generator_creator: Box<dyn TransactionGeneratorCreator> = match req.transaction_type
-        {
-            TransactionType::P2P => Box::new(P2PTransactionGeneratorCreator::new(
-                self.from_rng(),
-                self.txn_factory.clone(),
-                SEND_AMOUNT,
-            )),
-            TransactionType::AccountGeneration => Box::new(AccountGeneratorCreator::new(
-                self.from_rng(),
-                self.txn_factory.clone(),
-            )),
-            TransactionType::NftMint => Box::new(
-                NFTMintGeneratorCreator::new(
-                    self.from_rng(),
-                    self.txn_factory.clone(),
-                    self.root_account,
-                    req.rest_clients[0].clone(),
-                )
-                .await,
-            ),
+        let traffic_shaper = req
+            .traffic_profile
+            .clone()
+            .map(|profile| Arc::new(TrafficShaper::new(profile, num_clients)));
+        let txn_generator_creator = if req.transaction_mix.is_empty() {
+            self.create_generator_creator(req.transaction_type, &req.rest_clients[0])
+                .await
+        } else {
+            let mut creators = vec![];
+            for (transaction_type, weight) in &req.transaction_mix {
+                creators.push((
+                    self.create_generator_creator(*transaction_type, &req.rest_clients[0])
+                        .await,
+                    *weight,
+                ));
+            }
+            Box::new(TxnMixGeneratorCreator::new(self.from_rng(), creators))
         };
         for client in req.rest_clients {
             for _ in 0..workers_per_endpoint {
@@ -319,6 +378,7 @@ impl<'t> TxnEmitter<'t> {
                     stats,
                     txn_generator_creator.create_transaction_generator(),
                     req.invalid_transaction_ratio,
+                    traffic_shaper.clone(),
                     self.from_rng(),
                 );
                 let join_handle = tokio_handle.spawn(worker.run(req.gas_price).boxed());
@@ -333,6 +393,33 @@ impl<'t> TxnEmitter<'t> {
         })
     }
 
+    async fn create_generator_creator(
+        &mut self,
+        transaction_type: TransactionType,
+        client: &RestClient,
+    ) -> Box<dyn TransactionGeneratorCreator> {
+        match transaction_type {
+            TransactionType::P2P => Box::new(P2PTransactionGeneratorCreator::new(
+                self.from_rng(),
+                self.txn_factory.clone(),
+                SEND_AMOUNT,
+            )),
+            TransactionType::AccountGeneration => Box::new(AccountGeneratorCreator::new(
+                self.from_rng(),
+                self.txn_factory.clone(),
+            )),
+            TransactionType::NftMint => Box::new(
+                NFTMintGeneratorCreator::new(
+                    self.from_rng(),
+                    self.txn_factory.clone(),
+                    self.root_account,
+                    client.clone(),
+                )
+                .await,
+            ),
+        }
+    }
+
     pub async fn stop_job(&mut self, job: EmitJob) -> TxnStats {
         job.stop.store(true, Ordering::Relaxed);
         for worker in job.workers {
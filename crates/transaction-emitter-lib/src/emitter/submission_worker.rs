@@ -3,8 +3,8 @@
 
 use crate::{
     emitter::{
-        stats::StatsAccumulator, wait_for_accounts_sequence, MAX_TXN_BATCH_SIZE,
-        TXN_EXPIRATION_SECONDS,
+        stats::StatsAccumulator, traffic_profile::TrafficShaper, wait_for_accounts_sequence,
+        MAX_TXN_BATCH_SIZE, TXN_EXPIRATION_SECONDS,
     },
     transaction_generator::TransactionGenerator,
     EmitThreadParams,
@@ -35,6 +35,8 @@ pub struct SubmissionWorker {
     stats: Arc<StatsAccumulator>,
     txn_generator: Box<dyn TransactionGenerator>,
     invalid_transaction_ratio: usize,
+    /// Overrides `params.wait_millis` when the job follows a traffic profile
+    traffic_shaper: Option<Arc<TrafficShaper>>,
     rng: ::rand::rngs::StdRng,
 }
 
@@ -53,6 +55,7 @@ impl SubmissionWorker {
         stats: Arc<StatsAccumulator>,
         txn_generator: Box<dyn TransactionGenerator>,
         invalid_transaction_ratio: usize,
+        traffic_shaper: Option<Arc<TrafficShaper>>,
         rng: ::rand::rngs::StdRng,
     ) -> Self {
         Self {
@@ -64,6 +67,7 @@ impl SubmissionWorker {
             stats,
             txn_generator,
             invalid_transaction_ratio,
+            traffic_shaper,
             rng,
         }
     }
@@ -82,6 +86,17 @@ impl SubmissionWorker {
         let mut total_num_requests = 0;
 
         while !self.stop.load(Ordering::Relaxed) {
+            let wait_duration = match &self.traffic_shaper {
+                Some(traffic_shaper) => match traffic_shaper.wait_duration(self.accounts.len()) {
+                    Some(wait_duration) => wait_duration,
+                    None => {
+                        // the profile doesn't emit anything for now
+                        sleep(Duration::from_millis(100)).await;
+                        continue;
+                    }
+                },
+                None => wait_duration,
+            };
             let requests = self.gen_requests(gas_price);
             let num_requests = requests.len();
             total_num_requests += num_requests;
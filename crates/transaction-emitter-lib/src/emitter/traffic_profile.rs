@@ -0,0 +1,250 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::{bail, format_err, Result};
+use serde::{Deserialize, Serialize};
+use std::{
+    str::FromStr,
+    time::{Duration, Instant},
+};
+
+/// How the target TPS of a job changes over time, rather than staying constant
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub enum TrafficProfile {
+    /// Goes linearly from `start_tps` to `end_tps` over `duration`, and stays at `end_tps` after
+    Ramp {
+        start_tps: u64,
+        end_tps: u64,
+        duration: Duration,
+    },
+    /// A square wave: `burst_tps` for `burst_duration` at the start of every `period`, and
+    /// `base_tps` for the rest of it
+    Burst {
+        base_tps: u64,
+        burst_tps: u64,
+        period: Duration,
+        burst_duration: Duration,
+    },
+    /// Sustains each TPS for its duration in turn, and the last one once all have elapsed
+    Plateaus(Vec<(u64, Duration)>),
+}
+
+impl TrafficProfile {
+    /// The TPS to emit at `elapsed` since the start of the job
+    pub fn target_tps(&self, elapsed: Duration) -> u64 {
+        match self {
+            TrafficProfile::Ramp {
+                start_tps,
+                end_tps,
+                duration,
+            } => {
+                if elapsed >= *duration {
+                    return *end_tps;
+                }
+                let progress = elapsed.as_secs_f64() / duration.as_secs_f64();
+                (*start_tps as f64 + (*end_tps as f64 - *start_tps as f64) * progress) as u64
+            }
+            TrafficProfile::Burst {
+                base_tps,
+                burst_tps,
+                period,
+                burst_duration,
+            } => {
+                let period_ms = period.as_millis().max(1);
+                if elapsed.as_millis() % period_ms < burst_duration.as_millis() {
+                    *burst_tps
+                } else {
+                    *base_tps
+                }
+            }
+            TrafficProfile::Plateaus(plateaus) => {
+                let mut end = Duration::ZERO;
+                for (tps, duration) in plateaus {
+                    end += *duration;
+                    if elapsed < end {
+                        return *tps;
+                    }
+                }
+                plateaus.last().map_or(0, |(tps, _)| *tps)
+            }
+        }
+    }
+
+    /// The highest TPS of the profile, which the job's workers are sized for
+    pub fn peak_tps(&self) -> u64 {
+        match self {
+            TrafficProfile::Ramp {
+                start_tps, end_tps, ..
+            } => *start_tps.max(end_tps),
+            TrafficProfile::Burst {
+                base_tps,
+                burst_tps,
+                ..
+            } => *base_tps.max(burst_tps),
+            TrafficProfile::Plateaus(plateaus) => {
+                plateaus.iter().map(|(tps, _)| *tps).max().unwrap_or(0)
+            }
+        }
+    }
+}
+
+/// Parses a profile from the command line, with durations in seconds:
+/// - `ramp:<start_tps>:<end_tps>:<duration>`
+/// - `burst:<base_tps>:<burst_tps>:<period>:<burst_duration>`
+/// - `plateaus:<tps>@<duration>,<tps>@<duration>,...`
+impl FromStr for TrafficProfile {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (kind, params) = s
+            .split_once(':')
+            .ok_or_else(|| format_err!("Expected <profile>:<parameters>, got {}", s))?;
+        let numbers = |count: usize| -> Result<Vec<u64>> {
+            let numbers = params
+                .split(':')
+                .map(|number| {
+                    number
+                        .parse()
+                        .map_err(|e| format_err!("Invalid number {} in {}: {}", number, s, e))
+                })
+                .collect::<Result<Vec<u64>>>()?;
+            if numbers.len() != count {
+                bail!("Expected {} parameters for {}, got {}", count, kind, s);
+            }
+            Ok(numbers)
+        };
+        match kind {
+            "ramp" => {
+                let numbers = numbers(3)?;
+                Ok(TrafficProfile::Ramp {
+                    start_tps: numbers[0],
+                    end_tps: numbers[1],
+                    duration: Duration::from_secs(numbers[2]),
+                })
+            }
+            "burst" => {
+                let numbers = numbers(4)?;
+                Ok(TrafficProfile::Burst {
+                    base_tps: numbers[0],
+                    burst_tps: numbers[1],
+                    period: Duration::from_secs(numbers[2]),
+                    burst_duration: Duration::from_secs(numbers[3]),
+                })
+            }
+            "plateaus" => {
+                let plateaus = params
+                    .split(',')
+                    .map(|plateau| {
+                        let (tps, duration) = plateau.split_once('@').ok_or_else(|| {
+                            format_err!("Expected <tps>@<duration>, got {}", plateau)
+                        })?;
+                        Ok((tps.parse()?, Duration::from_secs(duration.parse()?)))
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+                Ok(TrafficProfile::Plateaus(plateaus))
+            }
+            _ => bail!(
+                "Unknown traffic profile {}, expected ramp, burst or plateaus",
+                kind
+            ),
+        }
+    }
+}
+
+/// Spreads the target TPS of a profile evenly across the workers of a job
+#[derive(Debug)]
+pub struct TrafficShaper {
+    profile: TrafficProfile,
+    num_workers: usize,
+    start: Instant,
+}
+
+impl TrafficShaper {
+    pub fn new(profile: TrafficProfile, num_workers: usize) -> Self {
+        Self {
+            profile,
+            num_workers,
+            start: Instant::now(),
+        }
+    }
+
+    /// How long a worker which sends `num_requests` at once waits before sending again, or None
+    /// if nothing should be sent for now
+    pub fn wait_duration(&self, num_requests: usize) -> Option<Duration> {
+        let target_tps = self.profile.target_tps(self.start.elapsed());
+        if target_tps == 0 {
+            return None;
+        }
+        Some(Duration::from_millis(
+            (num_requests * self.num_workers) as u64 * 1000 / target_tps,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_target_tps() {
+        let ramp = TrafficProfile::Ramp {
+            start_tps: 100,
+            end_tps: 1100,
+            duration: Duration::from_secs(10),
+        };
+        assert_eq!(ramp.target_tps(Duration::ZERO), 100);
+        assert_eq!(ramp.target_tps(Duration::from_secs(5)), 600);
+        assert_eq!(ramp.target_tps(Duration::from_secs(20)), 1100);
+        assert_eq!(ramp.peak_tps(), 1100);
+
+        let burst = TrafficProfile::Burst {
+            base_tps: 100,
+            burst_tps: 1000,
+            period: Duration::from_secs(60),
+            burst_duration: Duration::from_secs(10),
+        };
+        assert_eq!(burst.target_tps(Duration::from_secs(5)), 1000);
+        assert_eq!(burst.target_tps(Duration::from_secs(30)), 100);
+        assert_eq!(burst.target_tps(Duration::from_secs(65)), 1000);
+
+        let plateaus = TrafficProfile::Plateaus(vec![
+            (100, Duration::from_secs(10)),
+            (500, Duration::from_secs(10)),
+        ]);
+        assert_eq!(plateaus.target_tps(Duration::from_secs(5)), 100);
+        assert_eq!(plateaus.target_tps(Duration::from_secs(15)), 500);
+        assert_eq!(plateaus.target_tps(Duration::from_secs(60)), 500);
+        assert_eq!(plateaus.peak_tps(), 500);
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            "ramp:100:1100:10".parse::<TrafficProfile>().unwrap(),
+            TrafficProfile::Ramp {
+                start_tps: 100,
+                end_tps: 1100,
+                duration: Duration::from_secs(10),
+            }
+        );
+        assert_eq!(
+            "burst:100:1000:60:10".parse::<TrafficProfile>().unwrap(),
+            TrafficProfile::Burst {
+                base_tps: 100,
+                burst_tps: 1000,
+                period: Duration::from_secs(60),
+                burst_duration: Duration::from_secs(10),
+            }
+        );
+        assert_eq!(
+            "plateaus:100@10,500@20".parse::<TrafficProfile>().unwrap(),
+            TrafficProfile::Plateaus(vec![
+                (100, Duration::from_secs(10)),
+                (500, Duration::from_secs(20)),
+            ])
+        );
+        assert!("ramp:100:1100".parse::<TrafficProfile>().is_err());
+        assert!("plateaus:100".parse::<TrafficProfile>().is_err());
+        assert!("sine:100:10".parse::<TrafficProfile>().is_err());
+    }
+}
@@ -20,6 +20,7 @@ pub use cluster::Cluster;
 pub use emitter::{
     query_sequence_numbers,
     stats::{TxnStats, TxnStatsRate},
+    traffic_profile::TrafficProfile,
     EmitJob, EmitJobRequest, EmitThreadParams, TxnEmitter,
 };
 pub use wrappers::emit_transactions_with_cluster;
@@ -0,0 +1,85 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+use crate::transaction_generator::{TransactionGenerator, TransactionGeneratorCreator};
+use aptos_sdk::{
+    move_types::account_address::AccountAddress,
+    types::{transaction::SignedTransaction, LocalAccount},
+};
+use rand::{
+    distributions::{Distribution, WeightedIndex},
+    prelude::StdRng,
+};
+use std::{fmt::Debug, sync::Arc};
+
+/// Picks the generator of each account's transaction at random, according to the weights
+#[derive(Debug)]
+pub struct TxnMixGenerator {
+    rng: StdRng,
+    generators: Vec<Box<dyn TransactionGenerator>>,
+    weights: WeightedIndex<usize>,
+}
+
+impl TxnMixGenerator {
+    pub fn new(rng: StdRng, generators: Vec<(Box<dyn TransactionGenerator>, usize)>) -> Self {
+        let (generators, weights): (Vec<_>, Vec<_>) = generators.into_iter().unzip();
+        Self {
+            rng,
+            generators,
+            weights: WeightedIndex::new(weights).expect("the transaction mix weights are invalid"),
+        }
+    }
+}
+
+impl TransactionGenerator for TxnMixGenerator {
+    fn generate_transactions(
+        &mut self,
+        accounts: Vec<&mut LocalAccount>,
+        all_addresses: Arc<Vec<AccountAddress>>,
+        invalid_transaction_ratio: usize,
+        gas_price: u64,
+    ) -> Vec<SignedTransaction> {
+        let mut accounts_per_generator: Vec<Vec<&mut LocalAccount>> =
+            self.generators.iter().map(|_| vec![]).collect();
+        for account in accounts {
+            accounts_per_generator[self.weights.sample(&mut self.rng)].push(account);
+        }
+
+        let mut requests = vec![];
+        for (generator, accounts) in self.generators.iter_mut().zip(accounts_per_generator) {
+            if !accounts.is_empty() {
+                requests.extend(generator.generate_transactions(
+                    accounts,
+                    all_addresses.clone(),
+                    invalid_transaction_ratio,
+                    gas_price,
+                ));
+            }
+        }
+        requests
+    }
+}
+
+#[derive(Debug)]
+pub struct TxnMixGeneratorCreator {
+    rng: StdRng,
+    creators: Vec<(Box<dyn TransactionGeneratorCreator>, usize)>,
+}
+
+impl TxnMixGeneratorCreator {
+    /// The weights must not all be zero
+    pub fn new(rng: StdRng, creators: Vec<(Box<dyn TransactionGeneratorCreator>, usize)>) -> Self {
+        Self { rng, creators }
+    }
+}
+
+impl TransactionGeneratorCreator for TxnMixGeneratorCreator {
+    fn create_transaction_generator(&self) -> Box<dyn TransactionGenerator> {
+        Box::new(TxnMixGenerator::new(
+            self.rng.clone(),
+            self.creators
+                .iter()
+                .map(|(creator, weight)| (creator.create_transaction_generator(), *weight))
+                .collect(),
+        ))
+    }
+}
@@ -54,11 +54,15 @@ pub async fn emit_transactions_with_cluster(
             .thread_params(thread_params)
             .invalid_transaction_ratio(args.invalid_tx)
             .transaction_type(args.transaction_type)
+            .transaction_mix(args.transaction_mix.clone())
             .duration(duration)
             .gas_price(1);
     if let Some(workers_per_endpoint) = args.workers_per_ac {
         emit_job_request = emit_job_request.workers_per_endpoint(workers_per_endpoint);
     }
+    if let Some(traffic_profile) = &args.traffic_profile {
+        emit_job_request = emit_job_request.traffic_profile(traffic_profile.clone());
+    }
     if vasp {
         emit_job_request = emit_job_request.vasp();
     }
@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE IF EXISTS token_activities
+    ALTER COLUMN amount TYPE NUMERIC;
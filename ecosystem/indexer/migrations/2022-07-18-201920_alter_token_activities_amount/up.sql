@@ -0,0 +1,3 @@
+-- Your SQL goes here
+ALTER TABLE token_activities
+ALTER COLUMN amount TYPE BIGINT;
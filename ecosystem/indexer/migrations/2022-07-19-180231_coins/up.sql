@@ -0,0 +1,29 @@
+-- Your SQL goes here
+CREATE TABLE coin_balances
+(
+    owner_address VARCHAR NOT NULL,
+    coin_type VARCHAR NOT NULL,
+    amount BIGINT NOT NULL,
+    -- the version at which this balance was observed
+    transaction_version BIGINT NOT NULL,
+    inserted_at TIMESTAMP NOT NULL DEFAULT NOW(),
+
+    PRIMARY KEY (owner_address, coin_type)
+);
+
+CREATE TABLE coin_activities
+(
+    event_key VARCHAR NOT NULL,
+    sequence_number BIGINT NOT NULL,
+    owner_address VARCHAR NOT NULL,
+    coin_type VARCHAR NOT NULL,
+    activity_type VARCHAR NOT NULL,
+    amount BIGINT NOT NULL,
+    transaction_version BIGINT NOT NULL,
+    transaction_timestamp TIMESTAMP NOT NULL,
+    inserted_at TIMESTAMP NOT NULL DEFAULT NOW(),
+
+    PRIMARY KEY (event_key, sequence_number)
+);
+
+CREATE INDEX coin_activities_owner_index ON coin_activities (owner_address, coin_type, transaction_version);
@@ -0,0 +1,9 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX IF EXISTS events_type_index;
+DROP INDEX IF EXISTS events_account_creation_number_sequence_number_index;
+ALTER TABLE IF EXISTS events
+    DROP COLUMN IF EXISTS account_address,
+    DROP COLUMN IF EXISTS creation_number,
+    DROP COLUMN IF EXISTS type_address,
+    DROP COLUMN IF EXISTS type_module,
+    DROP COLUMN IF EXISTS type_name;
@@ -0,0 +1,30 @@
+-- Your SQL goes here
+ALTER TABLE events
+ADD COLUMN account_address VARCHAR NOT NULL DEFAULT '',
+ADD COLUMN creation_number BIGINT NOT NULL DEFAULT 0,
+ADD COLUMN type_address VARCHAR,
+ADD COLUMN type_module VARCHAR,
+ADD COLUMN type_name VARCHAR;
+
+-- An event key is the creation number as 8 little endian bytes followed by
+-- the 32 byte account address, hex encoded with a 0x prefix
+UPDATE events SET
+    account_address = '0x' || COALESCE(NULLIF(ltrim(substring(key from 19), '0'), ''), '0'),
+    creation_number = ('x' || substring(key from 17 for 2) || substring(key from 15 for 2)
+        || substring(key from 13 for 2) || substring(key from 11 for 2)
+        || substring(key from 9 for 2) || substring(key from 7 for 2)
+        || substring(key from 5 for 2) || substring(key from 3 for 2))::bit(64)::bigint;
+
+UPDATE events SET
+    type_address = split_part(type, '::', 1),
+    type_module = split_part(type, '::', 2),
+    type_name = split_part(split_part(type, '::', 3), '<', 1)
+WHERE type LIKE '%::%::%';
+
+ALTER TABLE events
+ALTER COLUMN account_address DROP DEFAULT,
+ALTER COLUMN creation_number DROP DEFAULT;
+
+CREATE INDEX events_account_creation_number_sequence_number_index
+    ON events (account_address, creation_number, sequence_number);
+CREATE INDEX events_type_index ON events (type_address, type_module, type_name);
@@ -0,0 +1,9 @@
+-- Your SQL goes here
+-- The chain the indexed data comes from, recorded on the first run so the
+-- indexer can refuse to mix in data from a different (e.g. reset) chain
+CREATE TABLE ledger_infos (
+  chain_id BIGINT NOT NULL,
+  genesis_hash VARCHAR(66) NOT NULL,
+  inserted_at TIMESTAMP NOT NULL DEFAULT NOW(),
+  PRIMARY KEY (chain_id)
+);
@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE processor_statuses
+  DROP COLUMN retry_count,
+  DROP COLUMN dead_letter;
@@ -0,0 +1,6 @@
+-- Your SQL goes here
+-- How many times a failed version has been retried, and whether the indexer
+-- gave up on it. Dead lettered versions are no longer retried.
+ALTER TABLE processor_statuses
+  ADD COLUMN retry_count INT NOT NULL DEFAULT 0,
+  ADD COLUMN dead_letter BOOLEAN NOT NULL DEFAULT FALSE;
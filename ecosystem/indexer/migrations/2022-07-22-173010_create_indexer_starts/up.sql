@@ -0,0 +1,10 @@
+-- Your SQL goes here
+-- Each start strategy the indexer was started with, and the version it
+-- started from, so restarting with the same strategy resumes instead of
+-- applying it again
+CREATE TABLE indexer_starts (
+  strategy VARCHAR(100) NOT NULL,
+  start_version BIGINT NOT NULL,
+  inserted_at TIMESTAMP NOT NULL DEFAULT NOW(),
+  PRIMARY KEY (inserted_at)
+);
@@ -0,0 +1,9 @@
+-- Your SQL goes here
+-- The schema version each processor's rows were written with, so the indexer
+-- can tell when a processor changed in a way that needs a reindex
+CREATE TABLE processor_versions (
+  name VARCHAR(50) NOT NULL,
+  schema_version INT NOT NULL,
+  updated_at TIMESTAMP NOT NULL DEFAULT NOW(),
+  PRIMARY KEY (name)
+);
@@ -0,0 +1,23 @@
+-- Your SQL goes here
+-- Every resource written or deleted by a transaction, so the state of an
+-- account at any version is the latest change of each of its resources at or
+-- below that version
+CREATE TABLE resource_changes (
+  transaction_version BIGINT NOT NULL,
+  -- The position of the change in the transaction's write set
+  write_set_change_index BIGINT NOT NULL,
+  address VARCHAR(66) NOT NULL,
+  resource_type TEXT NOT NULL,
+  type_address VARCHAR(66) NOT NULL,
+  type_module VARCHAR(255) NOT NULL,
+  type_name VARCHAR(255) NOT NULL,
+  is_deletion BOOLEAN NOT NULL,
+  -- NULL for deletions
+  data JSONB,
+  inserted_at TIMESTAMP NOT NULL DEFAULT NOW(),
+  PRIMARY KEY (transaction_version, write_set_change_index)
+);
+
+CREATE INDEX resource_changes_address_type_version_index
+  ON resource_changes (address, resource_type, transaction_version DESC);
+CREATE INDEX resource_changes_type_index ON resource_changes (type_address, type_module, type_name);
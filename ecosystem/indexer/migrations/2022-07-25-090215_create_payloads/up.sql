@@ -0,0 +1,26 @@
+-- Your SQL goes here
+-- The payload of each user transaction, with the function it calls, if any,
+-- broken down so calls can be grouped by module and function
+CREATE TABLE payloads (
+  transaction_hash VARCHAR(255) NOT NULL,
+  transaction_version BIGINT NOT NULL,
+  sender VARCHAR(66) NOT NULL,
+  -- script_function_payload, script_payload, module_bundle_payload or write_set_payload
+  payload_type VARCHAR(50) NOT NULL,
+  -- The function called, for script function payloads
+  module_address VARCHAR(66),
+  module_name VARCHAR(255),
+  function_name VARCHAR(255),
+  -- Arrays of the type arguments, as strings, and of the arguments, as JSON
+  type_arguments JSONB NOT NULL,
+  arguments JSONB NOT NULL,
+  success BOOLEAN NOT NULL,
+  inserted_at TIMESTAMP NOT NULL DEFAULT NOW(),
+  PRIMARY KEY (transaction_hash),
+  CONSTRAINT fk_transactions
+    FOREIGN KEY (transaction_hash)
+      REFERENCES transactions (hash)
+);
+
+CREATE INDEX payloads_function_index ON payloads (module_address, module_name, function_name);
+CREATE INDEX payloads_version_index ON payloads (transaction_version);
@@ -0,0 +1,73 @@
+-- This file should undo anything in `up.sql`
+-- Fails if the database holds more than one chain, as keys would collide
+ALTER TABLE user_transactions DROP CONSTRAINT fk_transactions;
+ALTER TABLE block_metadata_transactions DROP CONSTRAINT fk_transactions;
+ALTER TABLE events DROP CONSTRAINT fk_transactions;
+ALTER TABLE write_set_changes DROP CONSTRAINT fk_transactions;
+ALTER TABLE token_activities DROP CONSTRAINT fk_transactions;
+ALTER TABLE payloads DROP CONSTRAINT fk_transactions;
+
+ALTER TABLE transactions
+  DROP CONSTRAINT transactions_pkey,
+  DROP CONSTRAINT transactions_chain_id_version_key,
+  ADD PRIMARY KEY (hash),
+  ADD UNIQUE (version);
+ALTER TABLE user_transactions
+  DROP CONSTRAINT user_transactions_pkey,
+  DROP CONSTRAINT user_transactions_chain_id_sender_sequence_number_key,
+  ADD PRIMARY KEY (hash),
+  ADD UNIQUE (sender, sequence_number);
+ALTER TABLE block_metadata_transactions DROP CONSTRAINT block_metadata_transactions_pkey, ADD PRIMARY KEY (hash);
+ALTER TABLE events DROP CONSTRAINT events_pkey, ADD PRIMARY KEY (key, sequence_number);
+ALTER TABLE write_set_changes DROP CONSTRAINT write_set_changes_pkey, ADD PRIMARY KEY (transaction_hash, hash);
+ALTER TABLE payloads DROP CONSTRAINT payloads_pkey, ADD PRIMARY KEY (transaction_hash);
+ALTER TABLE processor_statuses DROP CONSTRAINT processor_statuses_pkey, ADD PRIMARY KEY (name, version);
+ALTER TABLE collections DROP CONSTRAINT collections_pkey, ADD PRIMARY KEY (collection_id);
+ALTER TABLE tokens DROP CONSTRAINT tokens_pkey, ADD PRIMARY KEY (token_id);
+ALTER TABLE ownerships DROP CONSTRAINT ownerships_pkey, ADD PRIMARY KEY (ownership_id);
+ALTER TABLE metadatas DROP CONSTRAINT metadatas_pkey, ADD PRIMARY KEY (token_id);
+ALTER TABLE token_activities DROP CONSTRAINT token_activities_pkey, ADD PRIMARY KEY (event_key, sequence_number);
+ALTER TABLE coin_balances DROP CONSTRAINT coin_balances_pkey, ADD PRIMARY KEY (owner_address, coin_type);
+ALTER TABLE coin_activities DROP CONSTRAINT coin_activities_pkey, ADD PRIMARY KEY (event_key, sequence_number);
+ALTER TABLE resource_changes
+  DROP CONSTRAINT resource_changes_pkey,
+  ADD PRIMARY KEY (transaction_version, write_set_change_index);
+ALTER TABLE indexer_starts DROP CONSTRAINT indexer_starts_pkey, ADD PRIMARY KEY (inserted_at);
+ALTER TABLE processor_versions DROP CONSTRAINT processor_versions_pkey, ADD PRIMARY KEY (name);
+
+ALTER TABLE user_transactions
+  ADD CONSTRAINT fk_transactions FOREIGN KEY (hash) REFERENCES transactions (hash);
+ALTER TABLE block_metadata_transactions
+  ADD CONSTRAINT fk_transactions FOREIGN KEY (hash) REFERENCES transactions (hash);
+ALTER TABLE events
+  ADD CONSTRAINT fk_transactions FOREIGN KEY (transaction_hash) REFERENCES transactions (hash);
+ALTER TABLE write_set_changes
+  ADD CONSTRAINT fk_transactions FOREIGN KEY (transaction_hash) REFERENCES transactions (hash);
+ALTER TABLE token_activities
+  ADD CONSTRAINT fk_transactions FOREIGN KEY (transaction_hash) REFERENCES transactions (hash);
+ALTER TABLE payloads
+  ADD CONSTRAINT fk_transactions FOREIGN KEY (transaction_hash) REFERENCES transactions (hash);
+
+DO $$
+DECLARE
+  indexed_table TEXT;
+  archive_table TEXT;
+BEGIN
+  FOREACH indexed_table IN ARRAY ARRAY[
+    'transactions', 'user_transactions', 'block_metadata_transactions', 'events', 'write_set_changes', 'payloads',
+    'processor_statuses', 'collections', 'tokens', 'ownerships', 'metadatas', 'token_activities', 'coin_balances',
+    'coin_activities', 'resource_changes', 'indexer_starts', 'processor_versions', 'ledger_infos'
+  ] LOOP
+    EXECUTE format('DROP POLICY chain_isolation ON %I', indexed_table);
+    EXECUTE format('ALTER TABLE %I DISABLE ROW LEVEL SECURITY, NO FORCE ROW LEVEL SECURITY', indexed_table);
+    IF indexed_table <> 'ledger_infos' THEN
+      EXECUTE format('ALTER TABLE %I DROP COLUMN chain_id', indexed_table);
+    END IF;
+  END LOOP;
+
+  FOR archive_table IN
+    SELECT tablename FROM pg_tables WHERE schemaname = current_schema() AND tablename LIKE '%\_archive'
+  LOOP
+    EXECUTE format('ALTER TABLE %I DROP COLUMN IF EXISTS chain_id', archive_table);
+  END LOOP;
+END $$;
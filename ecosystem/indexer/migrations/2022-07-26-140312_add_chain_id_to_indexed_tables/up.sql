@@ -0,0 +1,117 @@
+-- Your SQL goes here
+-- The chain each row comes from, so several chains can be indexed into one
+-- database. Connections of the indexer set `indexer.chain_id` to the chain
+-- they index: new rows default to it, and row level security hides the rows
+-- of other chains from them. Rows indexed so far belong to the chain recorded
+-- in ledger_infos.
+DO $$
+DECLARE
+  indexed_chain_id BIGINT;
+  indexed_table TEXT;
+  archive_table TEXT;
+BEGIN
+  SELECT chain_id INTO indexed_chain_id FROM ledger_infos LIMIT 1;
+
+  FOREACH indexed_table IN ARRAY ARRAY[
+    'transactions', 'user_transactions', 'block_metadata_transactions', 'events', 'write_set_changes', 'payloads',
+    'processor_statuses', 'collections', 'tokens', 'ownerships', 'metadatas', 'token_activities', 'coin_balances',
+    'coin_activities', 'resource_changes', 'indexer_starts', 'processor_versions'
+  ] LOOP
+    EXECUTE format(
+      'ALTER TABLE %I ADD COLUMN chain_id BIGINT NOT NULL DEFAULT %s',
+      indexed_table, COALESCE(indexed_chain_id, 0)
+    );
+    EXECUTE format(
+      'ALTER TABLE %I ALTER COLUMN chain_id SET DEFAULT current_setting(''indexer.chain_id'')::BIGINT',
+      indexed_table
+    );
+  END LOOP;
+
+  -- Archives of pruned rows get the column too, so rows keep being moved to them as is
+  FOR archive_table IN
+    SELECT tablename FROM pg_tables WHERE schemaname = current_schema() AND tablename LIKE '%\_archive'
+  LOOP
+    EXECUTE format(
+      'ALTER TABLE %I ADD COLUMN chain_id BIGINT NOT NULL DEFAULT %s',
+      archive_table, COALESCE(indexed_chain_id, 0)
+    );
+    EXECUTE format(
+      'ALTER TABLE %I ALTER COLUMN chain_id SET DEFAULT current_setting(''indexer.chain_id'')::BIGINT',
+      archive_table
+    );
+  END LOOP;
+
+  -- Forced, so the policies apply to the owner of the tables too
+  FOREACH indexed_table IN ARRAY ARRAY[
+    'transactions', 'user_transactions', 'block_metadata_transactions', 'events', 'write_set_changes', 'payloads',
+    'processor_statuses', 'collections', 'tokens', 'ownerships', 'metadatas', 'token_activities', 'coin_balances',
+    'coin_activities', 'resource_changes', 'indexer_starts', 'processor_versions', 'ledger_infos'
+  ] LOOP
+    EXECUTE format('ALTER TABLE %I ENABLE ROW LEVEL SECURITY, FORCE ROW LEVEL SECURITY', indexed_table);
+    EXECUTE format(
+      'CREATE POLICY chain_isolation ON %I '
+      'USING (chain_id = NULLIF(current_setting(''indexer.chain_id'', true), '''')::BIGINT)',
+      indexed_table
+    );
+  END LOOP;
+END $$;
+
+-- Keys are unique per chain
+ALTER TABLE user_transactions DROP CONSTRAINT fk_transactions;
+ALTER TABLE block_metadata_transactions DROP CONSTRAINT fk_transactions;
+ALTER TABLE events DROP CONSTRAINT fk_transactions;
+ALTER TABLE write_set_changes DROP CONSTRAINT fk_transactions;
+ALTER TABLE token_activities DROP CONSTRAINT fk_transactions;
+ALTER TABLE payloads DROP CONSTRAINT fk_transactions;
+
+ALTER TABLE transactions
+  DROP CONSTRAINT transactions_pkey,
+  DROP CONSTRAINT IF EXISTS transactions_hash_key,
+  DROP CONSTRAINT transactions_version_key,
+  ADD PRIMARY KEY (chain_id, hash),
+  ADD UNIQUE (chain_id, version);
+ALTER TABLE user_transactions
+  DROP CONSTRAINT user_transactions_pkey,
+  DROP CONSTRAINT IF EXISTS user_transactions_hash_key,
+  DROP CONSTRAINT user_transactions_sender_sequence_number_key,
+  ADD PRIMARY KEY (chain_id, hash),
+  ADD UNIQUE (chain_id, sender, sequence_number);
+ALTER TABLE block_metadata_transactions
+  DROP CONSTRAINT block_metadata_transactions_pkey,
+  DROP CONSTRAINT IF EXISTS block_metadata_transactions_hash_key,
+  ADD PRIMARY KEY (chain_id, hash);
+ALTER TABLE events DROP CONSTRAINT events_pkey, ADD PRIMARY KEY (chain_id, key, sequence_number);
+ALTER TABLE write_set_changes
+  DROP CONSTRAINT write_set_changes_pkey,
+  ADD PRIMARY KEY (chain_id, transaction_hash, hash);
+ALTER TABLE payloads DROP CONSTRAINT payloads_pkey, ADD PRIMARY KEY (chain_id, transaction_hash);
+ALTER TABLE processor_statuses DROP CONSTRAINT processor_statuses_pkey, ADD PRIMARY KEY (chain_id, name, version);
+ALTER TABLE collections DROP CONSTRAINT collections_pkey, ADD PRIMARY KEY (chain_id, collection_id);
+ALTER TABLE tokens DROP CONSTRAINT tokens_pkey, ADD PRIMARY KEY (chain_id, token_id);
+ALTER TABLE ownerships DROP CONSTRAINT ownerships_pkey, ADD PRIMARY KEY (chain_id, ownership_id);
+ALTER TABLE metadatas DROP CONSTRAINT metadatas_pkey, ADD PRIMARY KEY (chain_id, token_id);
+ALTER TABLE token_activities
+  DROP CONSTRAINT token_activities_pkey,
+  ADD PRIMARY KEY (chain_id, event_key, sequence_number);
+ALTER TABLE coin_balances DROP CONSTRAINT coin_balances_pkey, ADD PRIMARY KEY (chain_id, owner_address, coin_type);
+ALTER TABLE coin_activities
+  DROP CONSTRAINT coin_activities_pkey,
+  ADD PRIMARY KEY (chain_id, event_key, sequence_number);
+ALTER TABLE resource_changes
+  DROP CONSTRAINT resource_changes_pkey,
+  ADD PRIMARY KEY (chain_id, transaction_version, write_set_change_index);
+ALTER TABLE indexer_starts DROP CONSTRAINT indexer_starts_pkey, ADD PRIMARY KEY (chain_id, inserted_at);
+ALTER TABLE processor_versions DROP CONSTRAINT processor_versions_pkey, ADD PRIMARY KEY (chain_id, name);
+
+ALTER TABLE user_transactions
+  ADD CONSTRAINT fk_transactions FOREIGN KEY (chain_id, hash) REFERENCES transactions (chain_id, hash);
+ALTER TABLE block_metadata_transactions
+  ADD CONSTRAINT fk_transactions FOREIGN KEY (chain_id, hash) REFERENCES transactions (chain_id, hash);
+ALTER TABLE events
+  ADD CONSTRAINT fk_transactions FOREIGN KEY (chain_id, transaction_hash) REFERENCES transactions (chain_id, hash);
+ALTER TABLE write_set_changes
+  ADD CONSTRAINT fk_transactions FOREIGN KEY (chain_id, transaction_hash) REFERENCES transactions (chain_id, hash);
+ALTER TABLE token_activities
+  ADD CONSTRAINT fk_transactions FOREIGN KEY (chain_id, transaction_hash) REFERENCES transactions (chain_id, hash);
+ALTER TABLE payloads
+  ADD CONSTRAINT fk_transactions FOREIGN KEY (chain_id, transaction_hash) REFERENCES transactions (chain_id, hash);
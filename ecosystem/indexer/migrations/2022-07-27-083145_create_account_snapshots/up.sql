@@ -0,0 +1,19 @@
+-- Your SQL goes here
+-- The resources of selected accounts every so many versions, as the node's API
+-- renders them, so the state of an account at a version is read from the
+-- latest snapshot at or below it rather than from every change before it
+CREATE TABLE account_snapshots (
+  address VARCHAR(66) NOT NULL,
+  snapshot_version BIGINT NOT NULL,
+  resource_type TEXT NOT NULL,
+  data JSONB NOT NULL,
+  inserted_at TIMESTAMP NOT NULL DEFAULT NOW(),
+  chain_id BIGINT NOT NULL DEFAULT current_setting('indexer.chain_id')::BIGINT,
+  PRIMARY KEY (chain_id, address, snapshot_version, resource_type)
+);
+
+CREATE INDEX account_snapshots_type_index ON account_snapshots (resource_type, snapshot_version);
+
+ALTER TABLE account_snapshots ENABLE ROW LEVEL SECURITY, FORCE ROW LEVEL SECURITY;
+CREATE POLICY chain_isolation ON account_snapshots
+  USING (chain_id = NULLIF(current_setting('indexer.chain_id', true), '')::BIGINT);
@@ -0,0 +1,4 @@
+-- Your SQL goes here
+-- Retention policies with max_age_days look up the first block at or after a
+-- point in time, to age rows by when their transaction was committed
+CREATE INDEX bmt_timestamp_index ON block_metadata_transactions ("timestamp");
@@ -0,0 +1,20 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE IF EXISTS coin_activities;
+DROP TABLE IF EXISTS coin_balances;
+DROP TABLE IF EXISTS token_activities;
+DROP TABLE IF EXISTS metadatas;
+DROP TABLE IF EXISTS ownerships;
+DROP TABLE IF EXISTS tokens;
+DROP TABLE IF EXISTS collections;
+DROP TABLE IF EXISTS ledger_infos;
+DROP TABLE IF EXISTS indexer_starts;
+DROP TABLE IF EXISTS processor_versions;
+DROP TABLE IF EXISTS processor_statuses;
+DROP TABLE IF EXISTS account_snapshots;
+DROP TABLE IF EXISTS resource_changes;
+DROP TABLE IF EXISTS payloads;
+DROP TABLE IF EXISTS write_set_changes;
+DROP TABLE IF EXISTS events;
+DROP TABLE IF EXISTS block_metadata_transactions;
+DROP TABLE IF EXISTS user_transactions;
+DROP TABLE IF EXISTS transactions;
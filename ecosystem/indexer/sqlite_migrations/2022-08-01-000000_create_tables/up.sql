@@ -0,0 +1,257 @@
+-- Your SQL goes here
+-- The schema of the Postgres migrations as of 2022-07-28, for the indexer built
+-- with the `sqlite` feature. A SQLite database only ever holds one chain, so
+-- tables have no chain_id column and no row level security. JSON is stored as
+-- text.
+CREATE TABLE transactions (
+  type VARCHAR(255) NOT NULL,
+  payload TEXT NOT NULL,
+  version BIGINT NOT NULL UNIQUE,
+  hash VARCHAR(255) NOT NULL,
+  state_root_hash VARCHAR(255) NOT NULL,
+  event_root_hash VARCHAR(255) NOT NULL,
+  gas_used BIGINT NOT NULL,
+  success BOOLEAN NOT NULL,
+  vm_status TEXT NOT NULL,
+  accumulator_root_hash VARCHAR(255) NOT NULL,
+  inserted_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  PRIMARY KEY (hash)
+);
+
+CREATE TABLE user_transactions (
+  hash VARCHAR(255) NOT NULL,
+  signature TEXT NOT NULL,
+  sender VARCHAR(255) NOT NULL,
+  sequence_number BIGINT NOT NULL,
+  max_gas_amount BIGINT NOT NULL,
+  expiration_timestamp_secs TIMESTAMP NOT NULL,
+  gas_unit_price BIGINT NOT NULL,
+  timestamp TIMESTAMP NOT NULL,
+  inserted_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  PRIMARY KEY (hash),
+  UNIQUE (sender, sequence_number)
+);
+
+CREATE INDEX ut_sender_index ON user_transactions (sender);
+
+CREATE TABLE block_metadata_transactions (
+  hash VARCHAR(255) NOT NULL,
+  id VARCHAR(255) NOT NULL,
+  round BIGINT NOT NULL,
+  previous_block_votes TEXT NOT NULL,
+  proposer VARCHAR(255) NOT NULL,
+  timestamp TIMESTAMP NOT NULL,
+  inserted_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  epoch BIGINT NOT NULL,
+  previous_block_votes_bitmap TEXT NOT NULL,
+  failed_proposer_indices TEXT NOT NULL,
+  PRIMARY KEY (hash)
+);
+
+CREATE INDEX bmt_timestamp_index ON block_metadata_transactions ("timestamp");
+
+CREATE TABLE events (
+  transaction_hash VARCHAR(255) NOT NULL,
+  key VARCHAR(100) NOT NULL,
+  sequence_number BIGINT NOT NULL,
+  type TEXT NOT NULL,
+  data TEXT NOT NULL,
+  inserted_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  account_address VARCHAR(66) NOT NULL,
+  creation_number BIGINT NOT NULL,
+  type_address VARCHAR(66),
+  type_module VARCHAR(255),
+  type_name VARCHAR(255),
+  PRIMARY KEY (key, sequence_number)
+);
+
+CREATE INDEX event_key_seq_type_index ON events (key, sequence_number, type);
+CREATE INDEX events_account_creation_number_sequence_number_index
+  ON events (account_address, creation_number, sequence_number);
+CREATE INDEX events_type_index ON events (type_address, type_module, type_name);
+
+CREATE TABLE write_set_changes (
+  transaction_hash VARCHAR(255) NOT NULL,
+  hash VARCHAR(255) NOT NULL,
+  type TEXT NOT NULL,
+  address VARCHAR(255) NOT NULL,
+  module TEXT NOT NULL,
+  resource TEXT NOT NULL,
+  data TEXT NOT NULL,
+  inserted_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  PRIMARY KEY (transaction_hash, hash)
+);
+
+CREATE INDEX write_set_changes_tx_hash_addr_type_index
+  ON write_set_changes (transaction_hash, address, type);
+
+CREATE TABLE payloads (
+  transaction_hash VARCHAR(255) NOT NULL,
+  transaction_version BIGINT NOT NULL,
+  sender VARCHAR(255) NOT NULL,
+  payload_type VARCHAR(255) NOT NULL,
+  module_address VARCHAR(66),
+  module_name VARCHAR(255),
+  function_name VARCHAR(255),
+  type_arguments TEXT NOT NULL,
+  arguments TEXT NOT NULL,
+  success BOOLEAN NOT NULL,
+  inserted_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  PRIMARY KEY (transaction_hash)
+);
+
+CREATE INDEX payloads_function_index ON payloads (module_address, module_name, function_name);
+CREATE INDEX payloads_version_index ON payloads (transaction_version);
+
+CREATE TABLE resource_changes (
+  transaction_version BIGINT NOT NULL,
+  write_set_change_index BIGINT NOT NULL,
+  address VARCHAR(66) NOT NULL,
+  resource_type TEXT NOT NULL,
+  type_address VARCHAR(66) NOT NULL,
+  type_module VARCHAR(255) NOT NULL,
+  type_name VARCHAR(255) NOT NULL,
+  is_deletion BOOLEAN NOT NULL,
+  -- NULL for deletions
+  data TEXT,
+  inserted_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  PRIMARY KEY (transaction_version, write_set_change_index)
+);
+
+CREATE INDEX resource_changes_address_type_version_index
+  ON resource_changes (address, resource_type, transaction_version DESC);
+CREATE INDEX resource_changes_type_index ON resource_changes (type_address, type_module, type_name);
+
+CREATE TABLE account_snapshots (
+  address VARCHAR(66) NOT NULL,
+  snapshot_version BIGINT NOT NULL,
+  resource_type TEXT NOT NULL,
+  data TEXT NOT NULL,
+  inserted_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  PRIMARY KEY (address, snapshot_version, resource_type)
+);
+
+CREATE INDEX account_snapshots_type_index ON account_snapshots (resource_type, snapshot_version);
+
+CREATE TABLE processor_statuses (
+  name VARCHAR(50) NOT NULL,
+  version BIGINT NOT NULL,
+  success BOOLEAN NOT NULL,
+  details TEXT,
+  last_updated TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  retry_count INT NOT NULL DEFAULT 0,
+  dead_letter BOOLEAN NOT NULL DEFAULT FALSE,
+  PRIMARY KEY (name, version)
+);
+
+CREATE TABLE processor_versions (
+  name VARCHAR(50) NOT NULL,
+  schema_version INT NOT NULL,
+  updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  PRIMARY KEY (name)
+);
+
+CREATE TABLE indexer_starts (
+  strategy VARCHAR(100) NOT NULL,
+  start_version BIGINT NOT NULL,
+  inserted_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  PRIMARY KEY (inserted_at)
+);
+
+CREATE TABLE ledger_infos (
+  chain_id BIGINT NOT NULL,
+  genesis_hash VARCHAR(66) NOT NULL,
+  inserted_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  PRIMARY KEY (chain_id)
+);
+
+CREATE TABLE collections (
+  collection_id VARCHAR(255) NOT NULL,
+  creator VARCHAR(255) NOT NULL,
+  name VARCHAR(255) NOT NULL,
+  description VARCHAR(255) NOT NULL,
+  max_amount BIGINT,
+  uri VARCHAR(255) NOT NULL,
+  created_at TIMESTAMP NOT NULL,
+  inserted_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  PRIMARY KEY (collection_id)
+);
+
+CREATE TABLE tokens (
+  token_id VARCHAR(255) NOT NULL,
+  creator VARCHAR(255) NOT NULL,
+  collection VARCHAR(255) NOT NULL,
+  name VARCHAR(255) NOT NULL,
+  description VARCHAR(255) NOT NULL,
+  max_amount BIGINT,
+  supply BIGINT NOT NULL,
+  uri VARCHAR(255) NOT NULL,
+  minted_at TIMESTAMP NOT NULL,
+  last_minted_at TIMESTAMP NOT NULL,
+  inserted_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  PRIMARY KEY (token_id)
+);
+
+CREATE TABLE ownerships (
+  ownership_id VARCHAR(255) NOT NULL,
+  token_id VARCHAR(255),
+  owner VARCHAR(255),
+  amount BIGINT NOT NULL,
+  updated_at TIMESTAMP NOT NULL,
+  inserted_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  PRIMARY KEY (ownership_id)
+);
+
+CREATE TABLE metadatas (
+  token_id VARCHAR(255) NOT NULL,
+  name VARCHAR(255),
+  symbol VARCHAR(255),
+  seller_fee_basis_points BIGINT,
+  description VARCHAR(255),
+  image VARCHAR(255) NOT NULL,
+  external_url VARCHAR(255),
+  animation_url VARCHAR(255),
+  attributes TEXT,
+  properties TEXT,
+  last_updated_at TIMESTAMP NOT NULL,
+  inserted_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  PRIMARY KEY (token_id)
+);
+
+CREATE TABLE token_activities (
+  event_key VARCHAR(100) NOT NULL,
+  sequence_number BIGINT NOT NULL,
+  account VARCHAR(100) NOT NULL,
+  token_id VARCHAR(255),
+  event_type VARCHAR(255),
+  amount BIGINT,
+  created_at TIMESTAMP NOT NULL,
+  inserted_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  transaction_hash VARCHAR(255) NOT NULL,
+  PRIMARY KEY (event_key, sequence_number)
+);
+
+CREATE TABLE coin_balances (
+  owner_address VARCHAR(66) NOT NULL,
+  coin_type VARCHAR(5000) NOT NULL,
+  amount BIGINT NOT NULL,
+  transaction_version BIGINT NOT NULL,
+  inserted_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  PRIMARY KEY (owner_address, coin_type)
+);
+
+CREATE TABLE coin_activities (
+  event_key VARCHAR(100) NOT NULL,
+  sequence_number BIGINT NOT NULL,
+  owner_address VARCHAR(66) NOT NULL,
+  coin_type VARCHAR(5000) NOT NULL,
+  activity_type VARCHAR(255) NOT NULL,
+  amount BIGINT NOT NULL,
+  transaction_version BIGINT NOT NULL,
+  transaction_timestamp TIMESTAMP NOT NULL,
+  inserted_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  PRIMARY KEY (event_key, sequence_number)
+);
+
+CREATE INDEX coin_activities_owner_index
+  ON coin_activities (owner_address, coin_type, transaction_version);
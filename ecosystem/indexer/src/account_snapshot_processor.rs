@@ -0,0 +1,206 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    database::{
+        execute_with_better_error, insert_chunks, insert_or_ignore, DbPool, DbPoolConnection,
+    },
+    indexer::{
+        errors::TransactionProcessingError, fetcher::TransactionSource,
+        transaction_processor::TransactionProcessor,
+    },
+    models::account_snapshot::AccountSnapshot,
+    schema,
+};
+use anyhow::{bail, Context};
+use aptos_rest_client::{aptos_api_types::Address, Client as RestClient, Transaction};
+use async_trait::async_trait;
+use serde::Deserialize;
+use std::{fmt::Debug, sync::Arc};
+
+/// The options of the processor in the config file, e.g.
+///
+/// ```yaml
+/// processors:
+///   account_snapshot_processor:
+///     enabled: true
+///     options:
+///       accounts: ["0x1", "0xa550c18"]
+///       interval: 100000
+/// ```
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct AccountSnapshotConfig {
+    /// The accounts to snapshot
+    pub accounts: Vec<Address>,
+    /// Snapshots are taken at every version which is a multiple of this
+    #[serde(default = "default_interval")]
+    pub interval: u64,
+}
+
+fn default_interval() -> u64 {
+    100_000
+}
+
+fn is_snapshot_version(version: u64, interval: u64) -> bool {
+    version % interval == 0
+}
+
+/// Snapshots the resources of selected accounts every `interval` versions, so the state of an account as of a version
+/// can be read from a single snapshot rather than replayed from every write set before it. Resources are fetched from
+/// the node as of each snapshot version once the version is committed, so backfilling old snapshots needs a node
+/// which hasn't pruned that state yet.
+pub struct AccountSnapshotProcessor {
+    connection_pool: DbPool,
+    client: RestClient,
+    config: AccountSnapshotConfig,
+}
+
+impl AccountSnapshotProcessor {
+    pub fn new(
+        connection_pool: DbPool,
+        source: &TransactionSource,
+        config: AccountSnapshotConfig,
+    ) -> anyhow::Result<Self> {
+        if config.interval == 0 {
+            bail!("The snapshot interval must be at least 1 version");
+        }
+        let client = match source {
+            TransactionSource::Node(node_url) => RestClient::new(node_url.clone()),
+            #[cfg(feature = "offline")]
+            TransactionSource::Db(_) => {
+                bail!(
+                    "Account snapshots are fetched from a node, and can't be taken from a database"
+                )
+            }
+        };
+        Ok(Self {
+            connection_pool,
+            client,
+            config,
+        })
+    }
+
+    /// The resources of every selected account as of `version`
+    async fn fetch_snapshots(&self, version: u64) -> anyhow::Result<Vec<AccountSnapshot>> {
+        let mut snapshots = vec![];
+        for address in &self.config.accounts {
+            let resources = self
+                .client
+                .get_account_resources_at_version(*address.inner(), version)
+                .await
+                .with_context(|| {
+                    format!(
+                        "Could not fetch the resources of {} at version {}",
+                        address, version
+                    )
+                })?
+                .into_inner();
+            snapshots.extend(
+                resources
+                    .iter()
+                    .map(|resource| AccountSnapshot::from_resource(address, version, resource)),
+            );
+        }
+        Ok(snapshots)
+    }
+}
+
+impl Debug for AccountSnapshotProcessor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let state = &self.connection_pool.state();
+        write!(
+            f,
+            "AccountSnapshotProcessor {{ connections: {:?}  idle_connections: {:?} }}",
+            state.connections, state.idle_connections
+        )
+    }
+}
+
+fn insert_account_snapshots(
+    conn: &DbPoolConnection,
+    snapshots: &[AccountSnapshot],
+) -> Result<(), diesel::result::Error> {
+    for chunk in insert_chunks(snapshots, AccountSnapshot::NUM_COLUMNS) {
+        execute_with_better_error(
+            conn,
+            insert_or_ignore!(schema::account_snapshots::table, chunk),
+        )?;
+    }
+    Ok(())
+}
+
+#[async_trait]
+impl TransactionProcessor for AccountSnapshotProcessor {
+    fn name(&self) -> &'static str {
+        "account_snapshot_processor"
+    }
+
+    fn tables(&self) -> &'static [&'static str] {
+        &["account_snapshots"]
+    }
+
+    /// Nothing is derived from the transactions themselves, snapshots are taken once they are committed
+    fn process_transaction(
+        &self,
+        _conn: &DbPoolConnection,
+        _transaction: &Transaction,
+    ) -> Result<(), diesel::result::Error> {
+        Ok(())
+    }
+
+    /// Takes the snapshots of the batch's snapshot versions. A snapshot that can't be taken fails its version, so it
+    /// is taken again when the version is retried
+    async fn after_commit(&self, transactions: &[Arc<Transaction>]) {
+        for version in transactions
+            .iter()
+            .filter_map(|transaction| transaction.version())
+            .filter(|version| is_snapshot_version(*version, self.config.interval))
+        {
+            let result = match self.fetch_snapshots(version).await {
+                Ok(snapshots) => insert_account_snapshots(&self.get_conn(), &snapshots)
+                    .context("Could not write the snapshots"),
+                Err(err) => Err(err),
+            };
+            if let Err(err) = result {
+                aptos_logger::warn!(
+                    "[{}] Could not snapshot version {}: {:?}",
+                    self.name(),
+                    version,
+                    err
+                );
+                let tpe = TransactionProcessingError::AfterCommitError((err, version, self.name()));
+                self.update_status_err(&tpe, &[version]);
+            }
+        }
+    }
+
+    fn connection_pool(&self) -> &DbPool {
+        &self.connection_pool
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_config() {
+        let config: AccountSnapshotConfig = serde_json::from_value(serde_json::json!({
+            "accounts": ["0x1", "0xa550c18"],
+        }))
+        .unwrap();
+        assert_eq!(
+            config.accounts,
+            vec![
+                "0x1".parse::<Address>().unwrap(),
+                "0xa550c18".parse::<Address>().unwrap()
+            ]
+        );
+        assert_eq!(config.interval, 100_000);
+
+        assert!(is_snapshot_version(0, 1000));
+        assert!(is_snapshot_version(3000, 1000));
+        assert!(!is_snapshot_version(3001, 1000));
+    }
+}
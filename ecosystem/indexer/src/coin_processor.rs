@@ -0,0 +1,269 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    database::{
+        execute_with_better_error, insert_chunks, insert_or_ignore, DbPool, DbPoolConnection,
+    },
+    indexer::{sink::SinkRecord, transaction_processor::TransactionProcessor},
+    models::{
+        coin::{CoinActivity, CoinBalance, CoinStoreChange},
+        transactions::TransactionModel,
+    },
+    schema,
+};
+use aptos_rest_client::Transaction;
+use async_trait::async_trait;
+use diesel::{
+    sql_types::{BigInt, Text},
+    RunQueryDsl,
+};
+use futures::future::Either;
+use std::{collections::HashMap, fmt::Debug, sync::Arc};
+
+pub struct CoinTransactionProcessor {
+    connection_pool: DbPool,
+}
+
+impl CoinTransactionProcessor {
+    pub fn new(connection_pool: DbPool) -> Self {
+        Self { connection_pool }
+    }
+}
+
+impl Debug for CoinTransactionProcessor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let state = &self.connection_pool.state();
+        write!(
+            f,
+            "CoinTransactionProcessor {{ connections: {:?}  idle_connections: {:?} }}",
+            state.connections, state.idle_connections
+        )
+    }
+}
+
+/// Batches are processed concurrently and may commit out of order, so a
+/// balance only replaces one observed at an older version. All balances are
+/// written in one statement, so each account and coin type may appear once.
+#[cfg(not(feature = "sqlite"))]
+fn upsert_coin_balances(
+    conn: &DbPoolConnection,
+    balances: &[CoinBalance],
+) -> Result<usize, diesel::result::Error> {
+    use diesel::sql_types::Array;
+
+    if balances.is_empty() {
+        return Ok(0);
+    }
+    diesel::sql_query(
+        "INSERT INTO coin_balances (owner_address, coin_type, amount, transaction_version) \
+         SELECT * FROM UNNEST($1::VARCHAR[], $2::VARCHAR[], $3::BIGINT[], $4::BIGINT[]) \
+         ON CONFLICT ON CONSTRAINT coin_balances_pkey DO UPDATE \
+         SET amount = EXCLUDED.amount, transaction_version = EXCLUDED.transaction_version \
+         WHERE coin_balances.transaction_version < EXCLUDED.transaction_version",
+    )
+    .bind::<Array<Text>, _>(
+        balances
+            .iter()
+            .map(|balance| balance.owner_address.clone())
+            .collect::<Vec<_>>(),
+    )
+    .bind::<Array<Text>, _>(
+        balances
+            .iter()
+            .map(|balance| balance.coin_type.clone())
+            .collect::<Vec<_>>(),
+    )
+    .bind::<Array<BigInt>, _>(
+        balances
+            .iter()
+            .map(|balance| balance.amount)
+            .collect::<Vec<_>>(),
+    )
+    .bind::<Array<BigInt>, _>(
+        balances
+            .iter()
+            .map(|balance| balance.transaction_version)
+            .collect::<Vec<_>>(),
+    )
+    .execute(conn)
+}
+
+/// Batches are processed concurrently and may commit out of order, so a
+/// balance only replaces one observed at an older version. SQLite has no
+/// arrays to bind, so balances are written one statement each.
+#[cfg(feature = "sqlite")]
+fn upsert_coin_balances(
+    conn: &DbPoolConnection,
+    balances: &[CoinBalance],
+) -> Result<usize, diesel::result::Error> {
+    let mut written = 0;
+    for balance in balances {
+        written += diesel::sql_query(
+            "INSERT INTO coin_balances (owner_address, coin_type, amount, transaction_version) \
+             VALUES ($1, $2, $3, $4) \
+             ON CONFLICT (owner_address, coin_type) DO UPDATE \
+             SET amount = excluded.amount, transaction_version = excluded.transaction_version \
+             WHERE coin_balances.transaction_version < excluded.transaction_version",
+        )
+        .bind::<Text, _>(&balance.owner_address)
+        .bind::<Text, _>(&balance.coin_type)
+        .bind::<BigInt, _>(balance.amount)
+        .bind::<BigInt, _>(balance.transaction_version)
+        .execute(conn)?;
+    }
+    Ok(written)
+}
+
+/// Keeps the latest balance of each account and coin type
+fn latest_balances(coin_stores: Vec<CoinStoreChange>) -> Vec<CoinBalance> {
+    let mut balances: HashMap<(String, String), CoinBalance> = HashMap::new();
+    for coin_store in coin_stores {
+        let balance = coin_store.balance;
+        let key = (balance.owner_address.clone(), balance.coin_type.clone());
+        match balances.get(&key) {
+            Some(latest) if latest.transaction_version >= balance.transaction_version => {}
+            _ => {
+                balances.insert(key, balance);
+            }
+        }
+    }
+    balances.into_values().collect()
+}
+
+fn insert_coin_activities(
+    conn: &DbPoolConnection,
+    activities: &[CoinActivity],
+) -> Result<(), diesel::result::Error> {
+    for chunk in insert_chunks(activities, CoinActivity::NUM_COLUMNS) {
+        execute_with_better_error(
+            conn,
+            insert_or_ignore!(schema::coin_activities::table, chunk),
+        )?;
+    }
+    Ok(())
+}
+
+/// The coin stores a transaction changed, and the deposits and withdrawals it made
+fn coin_changes(transaction: &Transaction) -> (Vec<CoinStoreChange>, Vec<CoinActivity>) {
+    let version = transaction.version().unwrap_or(0);
+
+    let (_, maybe_details_model, maybe_events, maybe_write_set_changes) =
+        TransactionModel::from_transaction(transaction);
+
+    let coin_stores: Vec<CoinStoreChange> = maybe_write_set_changes
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|change| CoinStoreChange::from_write_set_change(change, version as i64))
+        .collect();
+    let timestamp = match maybe_details_model {
+        Some(Either::Left(user_txn)) => user_txn.timestamp,
+        Some(Either::Right(block_metadata_txn)) => block_metadata_txn.timestamp,
+        None => chrono::NaiveDateTime::from_timestamp(0, 0),
+    };
+    let activities: Vec<CoinActivity> = maybe_events
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|event| {
+            CoinActivity::from_event(event, &coin_stores, version as i64, timestamp)
+        })
+        .collect();
+    (coin_stores, activities)
+}
+
+#[async_trait]
+impl TransactionProcessor for CoinTransactionProcessor {
+    fn name(&self) -> &'static str {
+        "coin_processor"
+    }
+
+    fn tables(&self) -> &'static [&'static str] {
+        &["coin_balances", "coin_activities"]
+    }
+
+    fn process_transaction(
+        &self,
+        conn: &DbPoolConnection,
+        transaction: &Transaction,
+    ) -> Result<(), diesel::result::Error> {
+        let (coin_stores, activities) = coin_changes(transaction);
+        upsert_coin_balances(conn, &latest_balances(coin_stores))?;
+        insert_coin_activities(conn, &activities)
+    }
+
+    /// Writes the balances and activities of the whole batch at once
+    fn process_batch(
+        &self,
+        conn: &DbPoolConnection,
+        transactions: &[Arc<Transaction>],
+    ) -> Result<(), diesel::result::Error> {
+        let mut coin_stores = vec![];
+        let mut activities = vec![];
+        for transaction in transactions {
+            let (transaction_coin_stores, transaction_activities) = coin_changes(transaction);
+            coin_stores.extend(transaction_coin_stores);
+            activities.extend(transaction_activities);
+        }
+        upsert_coin_balances(conn, &latest_balances(coin_stores))?;
+        insert_coin_activities(conn, &activities)
+    }
+
+    fn sink_records(&self, transaction: &Transaction) -> Vec<SinkRecord> {
+        let version = transaction.version().unwrap_or(0);
+        let (coin_stores, activities) = coin_changes(transaction);
+
+        let mut records: Vec<SinkRecord> = coin_stores
+            .iter()
+            .map(|coin_store| {
+                SinkRecord::new(self.name(), "coin_balance", version, &coin_store.balance)
+            })
+            .collect();
+        records.extend(
+            activities
+                .iter()
+                .map(|activity| SinkRecord::new(self.name(), "coin_activity", version, activity)),
+        );
+        records
+    }
+
+    fn connection_pool(&self) -> &DbPool {
+        &self.connection_pool
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn coin_store(owner_address: &str, amount: i64, transaction_version: i64) -> CoinStoreChange {
+        CoinStoreChange {
+            balance: CoinBalance {
+                owner_address: owner_address.to_string(),
+                coin_type: "0x1::aptos_coin::AptosCoin".to_string(),
+                amount,
+                transaction_version,
+                inserted_at: chrono::Utc::now().naive_utc(),
+            },
+            deposit_event_key: "0x0".to_string(),
+            withdraw_event_key: "0x1".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_latest_balances() {
+        let mut balances = latest_balances(vec![
+            coin_store("0xa", 10, 5),
+            coin_store("0xa", 30, 7),
+            coin_store("0xa", 20, 6),
+            coin_store("0xb", 1, 5),
+        ]);
+        balances.sort_by(|a, b| a.owner_address.cmp(&b.owner_address));
+        assert_eq!(
+            balances
+                .iter()
+                .map(|balance| (balance.owner_address.as_str(), balance.amount))
+                .collect::<Vec<_>>(),
+            vec![("0xa", 30), ("0xb", 1)]
+        );
+    }
+}
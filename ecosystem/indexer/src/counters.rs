@@ -2,7 +2,8 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use aptos_metrics_core::{
-    register_int_counter, register_int_counter_vec, IntCounter, IntCounterVec,
+    register_int_counter, register_int_counter_vec, register_int_gauge, register_int_gauge_vec,
+    IntCounter, IntCounterVec, IntGauge, IntGaugeVec,
 };
 use once_cell::sync::Lazy;
 
@@ -71,3 +72,148 @@ pub static FETCHED_TRANSACTION: Lazy<IntCounter> = Lazy::new(|| {
     )
     .unwrap()
 });
+
+/// Number of times the node has pushed back on a request for transactions
+pub static FETCH_BACK_PRESSURE: Lazy<IntCounter> = Lazy::new(|| {
+    register_int_counter!(
+        "indexer_fetch_back_pressure_count",
+        "Number of times the node has pushed back on a request for transactions"
+    )
+    .unwrap()
+});
+
+/// Most versions the indexer requests from the node at a time
+pub static FETCH_BATCH_SIZE_LIMIT: Lazy<IntGauge> = Lazy::new(|| {
+    register_int_gauge!(
+        "indexer_fetch_batch_size_limit",
+        "Most versions the indexer requests from the node at a time"
+    )
+    .unwrap()
+});
+
+/// Milliseconds the indexer waits before each request for transactions
+pub static FETCH_DELAY_MILLIS: Lazy<IntGauge> = Lazy::new(|| {
+    register_int_gauge!(
+        "indexer_fetch_delay_millis",
+        "Milliseconds the indexer waits before each request for transactions"
+    )
+    .unwrap()
+});
+
+/// Version below which the indexer has processed every transaction
+pub static NEXT_VERSION_TO_PROCESS: Lazy<IntGauge> = Lazy::new(|| {
+    register_int_gauge!(
+        "indexer_next_version_to_process",
+        "Version below which the indexer has processed every transaction"
+    )
+    .unwrap()
+});
+
+/// Latest version of the node's ledger
+pub static NODE_LATEST_VERSION: Lazy<IntGauge> = Lazy::new(|| {
+    register_int_gauge!(
+        "indexer_node_latest_version",
+        "Latest version of the node's ledger"
+    )
+    .unwrap()
+});
+
+/// Highest version a given processor has processed, -1 if none
+pub static PROCESSOR_LATEST_VERSION: Lazy<IntGaugeVec> = Lazy::new(|| {
+    register_int_gauge_vec!(
+        "indexer_processor_latest_version",
+        "Highest version a given processor has processed, -1 if none",
+        &["processor_name"]
+    )
+    .unwrap()
+});
+
+/// Number of versions a given processor is behind the node
+pub static PROCESSOR_LAG: Lazy<IntGaugeVec> = Lazy::new(|| {
+    register_int_gauge_vec!(
+        "indexer_processor_lag",
+        "Number of versions a given processor is behind the node",
+        &["processor_name"]
+    )
+    .unwrap()
+});
+
+/// Number of versions a given processor is missing below the versions it has processed
+pub static GAP_VERSIONS: Lazy<IntGaugeVec> = Lazy::new(|| {
+    register_int_gauge_vec!(
+        "indexer_gap_version_count",
+        "Number of versions a given processor is missing below the versions it has processed",
+        &["processor_name"]
+    )
+    .unwrap()
+});
+
+/// Number of versions a given processor has failed to process, and will retry
+pub static FAILED_VERSIONS: Lazy<IntGaugeVec> = Lazy::new(|| {
+    register_int_gauge_vec!(
+        "indexer_failed_version_count",
+        "Number of versions a given processor has failed to process, and will retry",
+        &["processor_name"]
+    )
+    .unwrap()
+});
+
+/// Number of versions a given processor has given up on after retrying them too many times
+pub static DEAD_LETTER_VERSIONS: Lazy<IntGaugeVec> = Lazy::new(|| {
+    register_int_gauge_vec!(
+        "indexer_dead_letter_version_count",
+        "Number of versions a given processor has given up on after retrying them too many times",
+        &["processor_name"]
+    )
+    .unwrap()
+});
+
+/// Number of times a given processor has retried a failed version
+pub static RETRIED_VERSIONS: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "indexer_retried_version_count",
+        "Number of times a given processor has retried a failed version",
+        &["processor_name"]
+    )
+    .unwrap()
+});
+
+/// Number of missing versions a given processor has been run on
+pub static BACKFILLED_VERSIONS: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "indexer_backfilled_version_count",
+        "Number of missing versions a given processor has been run on",
+        &["processor_name"]
+    )
+    .unwrap()
+});
+
+/// Number of records published to a given sink
+pub static SINK_RECORDS: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "indexer_sink_record_count",
+        "Number of records published to a given sink",
+        &["sink_name"]
+    )
+    .unwrap()
+});
+
+/// Number of times a given sink has failed to publish a batch of records
+pub static SINK_ERRORS: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "indexer_sink_error_count",
+        "Number of times a given sink has failed to publish a batch of records",
+        &["sink_name"]
+    )
+    .unwrap()
+});
+
+/// Number of rows pruned from a given table by its retention policy
+pub static PRUNED_ROWS: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "indexer_pruned_row_count",
+        "Number of rows pruned from a given table by its retention policy",
+        &["table"]
+    )
+    .unwrap()
+});
@@ -0,0 +1,146 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+//! Database-related functions
+//!
+//! The indexer runs against Postgres, or against SQLite when built with the `sqlite` feature. What differs between
+//! the two, connecting, migrating, JSON columns and upserts, is in the `postgres` and `sqlite` modules, which export
+//! the same items; everything else goes through diesel's query builder and runs on either.
+use std::{ops::Deref, sync::Arc};
+
+use diesel::{
+    r2d2::{ConnectionManager, Pool, PooledConnection},
+    Connection,
+};
+
+#[cfg(not(feature = "sqlite"))]
+mod postgres;
+#[cfg(not(feature = "sqlite"))]
+pub use postgres::*;
+
+#[cfg(feature = "sqlite")]
+mod sqlite;
+#[cfg(feature = "sqlite")]
+pub use sqlite::*;
+
+pub type DbPool = Arc<Pool<ConnectionManager<DbConnection>>>;
+pub type DbPoolConnection = PooledConnection<ConnectionManager<DbConnection>>;
+
+/// The value of a `Jsonb` column. The backends store JSON differently, and diesel only maps `serde_json::Value` to
+/// Postgres' types, so models hold this instead.
+#[derive(
+    AsExpression,
+    Clone,
+    Debug,
+    Default,
+    Eq,
+    FromSqlRow,
+    PartialEq,
+    serde::Deserialize,
+    serde::Serialize,
+)]
+#[serde(transparent)]
+#[sql_type = "Jsonb"]
+pub struct JsonValue(pub serde_json::Value);
+
+impl Deref for JsonValue {
+    type Target = serde_json::Value;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl From<serde_json::Value> for JsonValue {
+    fn from(value: serde_json::Value) -> Self {
+        Self(value)
+    }
+}
+
+/// Inserts `values` into `table`, skipping the rows which conflict with rows already there
+#[cfg(not(feature = "sqlite"))]
+macro_rules! insert_or_ignore {
+    ($table:expr, $values:expr) => {
+        diesel::insert_into($table)
+            .values($values)
+            .on_conflict_do_nothing()
+    };
+}
+
+/// Inserts `values` into `table`, skipping the rows which conflict with rows already there
+#[cfg(feature = "sqlite")]
+macro_rules! insert_or_ignore {
+    ($table:expr, $values:expr) => {
+        diesel::insert_or_ignore_into($table).values($values)
+    };
+}
+
+/// Inserts `values` into `table`, and where a row conflicts on `constraint` with a row already there, sets `columns`
+/// of the existing row to the new row's
+#[cfg(not(feature = "sqlite"))]
+macro_rules! upsert {
+    ($table:expr, $values:expr, $constraint:expr, ($($column:expr),+ $(,)?)) => {
+        diesel::insert_into($table)
+            .values($values)
+            .on_conflict(diesel::pg::upsert::on_constraint($constraint))
+            .do_update()
+            .set(($(diesel::ExpressionMethods::eq($column, diesel::pg::upsert::excluded($column)),)+))
+    };
+}
+
+/// Inserts `values` into `table`, and where a row conflicts with a row already there, replaces the existing row.
+/// diesel 1.x can't build `ON CONFLICT ... DO UPDATE` for SQLite, so the other columns of the row are replaced too,
+/// rather than only `columns`.
+#[cfg(feature = "sqlite")]
+macro_rules! upsert {
+    ($table:expr, $values:expr, $constraint:expr, ($($column:expr),+ $(,)?)) => {{
+        let _ = ($constraint, $($column,)+);
+        diesel::replace_into($table).values($values)
+    }};
+}
+
+pub(crate) use insert_or_ignore;
+pub(crate) use upsert;
+
+/// Deletes every row of the connection's chain from `tables`, which are listed with referenced tables first. Unlike
+/// `TRUNCATE`, `DELETE` abides by Postgres' row level security, so the rows of other chains are left alone. A SQLite
+/// database only ever holds one chain.
+pub fn delete_chain_rows(conn: &DbConnection, tables: &[&str]) -> diesel::QueryResult<()> {
+    for table in tables.iter().rev() {
+        conn.execute(&format!("DELETE FROM {}", table))?;
+    }
+    Ok(())
+}
+
+/// Postgres takes at most this many bind parameters in a statement. diesel inserts the rows of a batch one statement
+/// each on SQLite, so it's well under the limit there.
+const MAX_BIND_PARAMETERS: usize = u16::MAX as usize;
+
+/// Splits rows into chunks small enough to insert each in a single statement, given how many columns a row has
+pub fn insert_chunks<T>(rows: &[T], num_columns: usize) -> std::slice::Chunks<'_, T> {
+    rows.chunks((MAX_BIND_PARAMETERS / num_columns).max(1))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_insert_chunks() {
+        let rows: Vec<u32> = (0..10_000).collect();
+        let chunks: Vec<&[u32]> = insert_chunks(&rows, 10).collect();
+        assert_eq!(chunks.len(), 2);
+        assert_eq!(chunks[0].len(), 6553);
+        assert_eq!(insert_chunks(&rows[..0], 10).count(), 0);
+    }
+
+    #[test]
+    fn test_json_value() {
+        let value = JsonValue(serde_json::json!({ "coin": { "value": "1000" } }));
+        assert_eq!(value["coin"]["value"], "1000");
+        assert_eq!(
+            serde_json::to_string(&value).unwrap(),
+            r#"{"coin":{"value":"1000"}}"#
+        );
+    }
+}
@@ -2,13 +2,14 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    database::{execute_with_better_error, PgDbPool, PgPoolConnection},
-    indexer::{
-        errors::TransactionProcessingError, processing_result::ProcessingResult,
-        transaction_processor::TransactionProcessor,
+    database::{
+        execute_with_better_error, insert_chunks, insert_or_ignore, upsert, DbPool,
+        DbPoolConnection,
     },
+    indexer::{sink::SinkRecord, transaction_processor::TransactionProcessor},
     models::{
         events::EventModel,
+        payload::Payload,
         transactions::{BlockMetadataTransactionModel, TransactionModel, UserTransactionModel},
         write_set_changes::WriteSetChangeModel,
     },
@@ -16,16 +17,15 @@ use crate::{
 };
 use aptos_rest_client::Transaction;
 use async_trait::async_trait;
-use diesel::Connection;
 use futures::future::Either;
 use std::{fmt::Debug, sync::Arc};
 
 pub struct DefaultTransactionProcessor {
-    connection_pool: PgDbPool,
+    connection_pool: DbPool,
 }
 
 impl DefaultTransactionProcessor {
-    pub fn new(connection_pool: PgDbPool) -> Self {
+    pub fn new(connection_pool: DbPool) -> Self {
         Self { connection_pool }
     }
 }
@@ -41,85 +41,174 @@ impl Debug for DefaultTransactionProcessor {
     }
 }
 
-fn insert_events(conn: &PgPoolConnection, events: &Vec<EventModel>) {
-    execute_with_better_error(
-        conn,
-        diesel::insert_into(schema::events::table)
-            .values(events)
-            .on_conflict_do_nothing(),
-    )
-    .expect("Error inserting row into database");
+fn insert_events(
+    conn: &DbPoolConnection,
+    events: &[EventModel],
+) -> Result<(), diesel::result::Error> {
+    for chunk in insert_chunks(events, EventModel::NUM_COLUMNS) {
+        execute_with_better_error(conn, insert_or_ignore!(schema::events::table, chunk))?;
+    }
+    Ok(())
+}
+
+fn insert_write_set_changes(
+    conn: &DbPoolConnection,
+    write_set_changes: &[WriteSetChangeModel],
+) -> Result<(), diesel::result::Error> {
+    for chunk in insert_chunks(write_set_changes, WriteSetChangeModel::NUM_COLUMNS) {
+        execute_with_better_error(
+            conn,
+            insert_or_ignore!(schema::write_set_changes::table, chunk),
+        )?;
+    }
+    Ok(())
 }
 
-fn insert_write_set_changes(conn: &PgPoolConnection, write_set_changes: &Vec<WriteSetChangeModel>) {
-    execute_with_better_error(
-        conn,
-        diesel::insert_into(schema::write_set_changes::table)
-            .values(write_set_changes)
-            .on_conflict_do_nothing(),
-    )
-    .expect("Error inserting row into database");
+fn insert_payloads(
+    conn: &DbPoolConnection,
+    payloads: &[Payload],
+) -> Result<(), diesel::result::Error> {
+    for chunk in insert_chunks(payloads, Payload::NUM_COLUMNS) {
+        execute_with_better_error(conn, insert_or_ignore!(schema::payloads::table, chunk))?;
+    }
+    Ok(())
+}
+
+fn insert_transactions(
+    conn: &DbPoolConnection,
+    transaction_models: &[TransactionModel],
+) -> Result<(), diesel::result::Error> {
+    use schema::transactions::dsl::*;
+
+    for chunk in insert_chunks(transaction_models, TransactionModel::NUM_COLUMNS) {
+        execute_with_better_error(
+            conn,
+            upsert!(
+                schema::transactions::table,
+                chunk,
+                "transactions_pkey",
+                (
+                    type_,
+                    payload,
+                    version,
+                    state_root_hash,
+                    event_root_hash,
+                    gas_used,
+                    success,
+                    vm_status,
+                    accumulator_root_hash,
+                )
+            ),
+        )?;
+    }
+    Ok(())
 }
 
-fn insert_transaction(conn: &PgPoolConnection, version: u64, transaction_model: &TransactionModel) {
-    aptos_logger::trace!(
-        "[default_processor] inserting 'transaction' version {} with hash {}",
-        version,
-        transaction_model.hash
-    );
-    execute_with_better_error(
-        conn,
-        diesel::insert_into(schema::transactions::table)
-            .values(transaction_model)
-            .on_conflict(schema::transactions::dsl::hash)
-            .do_update()
-            .set(transaction_model),
-    )
-    .expect("Error inserting row into database");
+fn insert_user_transactions(
+    conn: &DbPoolConnection,
+    user_transaction_models: &[UserTransactionModel],
+) -> Result<(), diesel::result::Error> {
+    use schema::user_transactions::dsl::*;
+
+    for chunk in insert_chunks(user_transaction_models, UserTransactionModel::NUM_COLUMNS) {
+        execute_with_better_error(
+            conn,
+            upsert!(
+                schema::user_transactions::table,
+                chunk,
+                "user_transactions_pkey",
+                (
+                    signature,
+                    sender,
+                    sequence_number,
+                    max_gas_amount,
+                    expiration_timestamp_secs,
+                    gas_unit_price,
+                    timestamp,
+                )
+            ),
+        )?;
+    }
+    Ok(())
 }
 
-fn insert_user_transaction(
-    conn: &PgPoolConnection,
-    version: u64,
-    transaction_model: &TransactionModel,
-    user_transaction_model: &UserTransactionModel,
-) {
-    aptos_logger::trace!(
-        "[default_processor] inserting 'user_transaction' version {} with hash {}",
-        version,
-        &transaction_model.hash
-    );
-    execute_with_better_error(
-        conn,
-        diesel::insert_into(schema::user_transactions::table)
-            .values(user_transaction_model)
-            .on_conflict(schema::user_transactions::dsl::hash)
-            .do_update()
-            .set(user_transaction_model),
-    )
-    .expect("Error inserting row into database");
+fn insert_block_metadata_transactions(
+    conn: &DbPoolConnection,
+    block_metadata_transaction_models: &[BlockMetadataTransactionModel],
+) -> Result<(), diesel::result::Error> {
+    use schema::block_metadata_transactions::dsl::*;
+
+    for chunk in insert_chunks(
+        block_metadata_transaction_models,
+        BlockMetadataTransactionModel::NUM_COLUMNS,
+    ) {
+        execute_with_better_error(
+            conn,
+            upsert!(
+                schema::block_metadata_transactions::table,
+                chunk,
+                "block_metadata_transactions_pkey",
+                (
+                    id,
+                    round,
+                    previous_block_votes,
+                    proposer,
+                    timestamp,
+                    epoch,
+                    previous_block_votes_bitmap,
+                    failed_proposer_indices,
+                )
+            ),
+        )?;
+    }
+    Ok(())
 }
 
-fn insert_block_metadata_transaction(
-    conn: &PgPoolConnection,
-    version: u64,
-    transaction_model: &TransactionModel,
-    block_metadata_transaction_model: &BlockMetadataTransactionModel,
-) {
-    aptos_logger::trace!(
-        "[default_processor] inserting 'block_metadata_transaction' version {} with hash {}",
-        version,
-        &transaction_model.hash
-    );
-    execute_with_better_error(
-        conn,
-        diesel::insert_into(schema::block_metadata_transactions::table)
-            .values(block_metadata_transaction_model)
-            .on_conflict(schema::block_metadata_transactions::dsl::hash)
-            .do_update()
-            .set(block_metadata_transaction_model),
-    )
-    .expect("Error inserting row into database");
+/// The rows derived from some transactions, to insert together
+#[derive(Default)]
+struct Rows {
+    transactions: Vec<TransactionModel>,
+    user_transactions: Vec<UserTransactionModel>,
+    block_metadata_transactions: Vec<BlockMetadataTransactionModel>,
+    events: Vec<EventModel>,
+    write_set_changes: Vec<WriteSetChangeModel>,
+    payloads: Vec<Payload>,
+}
+
+impl Rows {
+    fn add(&mut self, transaction: &Transaction) {
+        let (transaction_model, maybe_details_model, maybe_events, maybe_write_set_changes) =
+            TransactionModel::from_transaction(transaction);
+        aptos_logger::trace!(
+            "[default_processor] inserting 'transaction' version {} with hash {}",
+            transaction_model.version,
+            transaction_model.hash
+        );
+        self.transactions.push(transaction_model);
+        match maybe_details_model {
+            Some(Either::Left(user_transaction_model)) => {
+                self.user_transactions.push(user_transaction_model)
+            }
+            Some(Either::Right(block_metadata_transaction_model)) => self
+                .block_metadata_transactions
+                .push(block_metadata_transaction_model),
+            None => {}
+        };
+        self.events.extend(maybe_events.unwrap_or_default());
+        self.write_set_changes
+            .extend(maybe_write_set_changes.unwrap_or_default());
+        self.payloads.extend(Payload::from_transaction(transaction));
+    }
+
+    fn insert(&self, conn: &DbPoolConnection) -> Result<(), diesel::result::Error> {
+        insert_transactions(conn, &self.transactions)?;
+        insert_user_transactions(conn, &self.user_transactions)?;
+        insert_block_metadata_transactions(conn, &self.block_metadata_transactions)?;
+        insert_events(conn, &self.events)?;
+        insert_write_set_changes(conn, &self.write_set_changes)?;
+        insert_payloads(conn, &self.payloads)?;
+        Ok(())
+    }
 }
 
 #[async_trait]
@@ -128,60 +217,67 @@ impl TransactionProcessor for DefaultTransactionProcessor {
         "default_processor"
     }
 
-    async fn process_transaction(
+    /// 2: payloads of user transactions
+    fn schema_version(&self) -> i32 {
+        2
+    }
+
+    fn tables(&self) -> &'static [&'static str] {
+        &[
+            "transactions",
+            "user_transactions",
+            "block_metadata_transactions",
+            "events",
+            "write_set_changes",
+            "payloads",
+        ]
+    }
+
+    fn process_transaction(
         &self,
-        transaction: Arc<Transaction>,
-    ) -> Result<ProcessingResult, TransactionProcessingError> {
-        let version = transaction.version().unwrap_or(0);
+        conn: &DbPoolConnection,
+        transaction: &Transaction,
+    ) -> Result<(), diesel::result::Error> {
+        let mut rows = Rows::default();
+        rows.add(transaction);
+        rows.insert(conn)
+    }
 
-        let (transaction_model, maybe_details_model, maybe_events, maybe_write_set_changes) =
-            TransactionModel::from_transaction(&transaction);
-
-        let conn = self.get_conn();
-
-        let tx_result = conn.transaction::<(), diesel::result::Error, _>(|| {
-            insert_transaction(&conn, version, &transaction_model);
-            if let Some(tx_details_model) = maybe_details_model {
-                match tx_details_model {
-                    Either::Left(user_transaction_model) => {
-                        insert_user_transaction(
-                            &conn,
-                            version,
-                            &transaction_model,
-                            &user_transaction_model,
-                        );
-                    }
-                    Either::Right(block_metadata_transaction_model) => {
-                        insert_block_metadata_transaction(
-                            &conn,
-                            version,
-                            &transaction_model,
-                            &block_metadata_transaction_model,
-                        );
-                    }
-                };
-            };
-
-            if let Some(events) = maybe_events {
-                insert_events(&conn, &events);
-            };
-            if let Some(write_set_changes) = maybe_write_set_changes {
-                insert_write_set_changes(&conn, &write_set_changes);
-            };
-            Ok(())
-        });
-
-        match tx_result {
-            Ok(_) => Ok(ProcessingResult::new(self.name(), version)),
-            Err(err) => Err(TransactionProcessingError::TransactionCommitError((
-                anyhow::Error::from(err),
-                version,
-                self.name(),
-            ))),
+    /// Gathers the rows of the whole batch, so each table gets a few multi-row inserts rather than some per
+    /// transaction
+    fn process_batch(
+        &self,
+        conn: &DbPoolConnection,
+        transactions: &[Arc<Transaction>],
+    ) -> Result<(), diesel::result::Error> {
+        let mut rows = Rows::default();
+        for transaction in transactions {
+            rows.add(transaction);
         }
+        rows.insert(conn)
+    }
+
+    fn sink_records(&self, transaction: &Transaction) -> Vec<SinkRecord> {
+        let version = transaction.version().unwrap_or(0);
+        let (transaction_model, _, maybe_events, _) =
+            TransactionModel::from_transaction(transaction);
+
+        let mut records = vec![SinkRecord::new(
+            self.name(),
+            "transaction",
+            version,
+            &transaction_model,
+        )];
+        records.extend(
+            maybe_events
+                .unwrap_or_default()
+                .iter()
+                .map(|event| SinkRecord::new(self.name(), "event", version, event)),
+        );
+        records
     }
 
-    fn connection_pool(&self) -> &PgDbPool {
+    fn connection_pool(&self) -> &DbPool {
         &self.connection_pool
     }
 }
@@ -0,0 +1,47 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+//! A read only GraphQL API over the indexed data, so frontends don't need
+//! access to Postgres. Only built with the `graphql` feature.
+
+pub mod query;
+pub mod types;
+
+use crate::{database::DbPool, graphql::query::QueryRoot};
+use async_graphql::{
+    http::{playground_source, GraphQLPlaygroundConfig},
+    EmptyMutation, EmptySubscription, Schema,
+};
+use async_graphql_warp::GraphQLResponse;
+use std::{convert::Infallible, net::SocketAddr};
+use warp::Filter;
+
+/// Queries nesting deeper than this are rejected
+const MAX_QUERY_DEPTH: usize = 16;
+
+pub type IndexerSchema = Schema<QueryRoot, EmptyMutation, EmptySubscription>;
+
+pub fn build_schema(connection_pool: DbPool) -> IndexerSchema {
+    Schema::build(QueryRoot, EmptyMutation, EmptySubscription)
+        .data(connection_pool)
+        .limit_depth(MAX_QUERY_DEPTH)
+        .finish()
+}
+
+/// Serves the API at `/graphql`, and a playground to try it out at `/`
+pub async fn serve(connection_pool: DbPool, address: SocketAddr) {
+    let schema = build_schema(connection_pool);
+    let graphql = warp::path("graphql").and(warp::path::end()).and(
+        async_graphql_warp::graphql(schema).and_then(
+            |(schema, request): (IndexerSchema, async_graphql::Request)| async move {
+                Ok::<_, Infallible>(GraphQLResponse::from(schema.execute(request).await))
+            },
+        ),
+    );
+    let playground = warp::path::end()
+        .and(warp::get())
+        .map(|| warp::reply::html(playground_source(GraphQLPlaygroundConfig::new("/graphql"))));
+
+    aptos_logger::info!("Serving the GraphQL API on {}", address);
+    warp::serve(playground.or(graphql)).run(address).await;
+}
@@ -0,0 +1,256 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    database::DbPool,
+    graphql::types::{CoinActivity, Event, TokenActivity, TokenOwnership, Transaction},
+    models::{
+        coin::CoinActivity as CoinActivityModel, events::EventModel,
+        token_activity::TokenActivity as TokenActivityModel, transactions::TransactionModel,
+    },
+    schema::{coin_activities, events, ownerships, token_activities, transactions},
+};
+use async_graphql::{
+    connection::{query, Connection, Edge},
+    Context, Object, OutputType, Result,
+};
+use diesel::{ExpressionMethods, QueryDsl, RunQueryDsl};
+
+/// Page size when the client doesn't ask for one
+const DEFAULT_PAGE_SIZE: usize = 25;
+/// The most items a page can hold
+const MAX_PAGE_SIZE: usize = 100;
+
+/// Loads a page of a connection. Cursors are positions in the ordered,
+/// filtered rows; only forward pagination (`first` and `after`) is supported.
+/// `load` gets the offset and limit of the rows to load.
+async fn paginate<Row, Node, F>(
+    after: Option<String>,
+    first: Option<i32>,
+    load: F,
+) -> Result<Connection<usize, Node>>
+where
+    Node: OutputType + From<Row>,
+    F: FnOnce(i64, i64) -> Result<Vec<Row>> + Send,
+{
+    query(
+        after,
+        None,
+        first,
+        None,
+        |after: Option<usize>, _: Option<usize>, first, _| async move {
+            let offset = after.map(|after| after + 1).unwrap_or(0);
+            let limit = first.unwrap_or(DEFAULT_PAGE_SIZE).min(MAX_PAGE_SIZE);
+            // One more row than asked for tells whether there is a next page
+            let mut rows = load(offset as i64, limit as i64 + 1)?;
+            let has_next_page = rows.len() > limit;
+            rows.truncate(limit);
+
+            let mut connection = Connection::new(offset > 0, has_next_page);
+            connection.edges.extend(
+                rows.into_iter()
+                    .enumerate()
+                    .map(|(i, row)| Edge::new(offset + i, Node::from(row))),
+            );
+            Ok::<_, async_graphql::Error>(connection)
+        },
+    )
+    .await
+}
+
+pub struct QueryRoot;
+
+#[Object]
+impl QueryRoot {
+    /// Transactions, by version
+    async fn transactions(
+        &self,
+        ctx: &Context<'_>,
+        after: Option<String>,
+        first: Option<i32>,
+        success: Option<bool>,
+        #[graphql(name = "type")] type_: Option<String>,
+    ) -> Result<Connection<usize, Transaction>> {
+        let pool = ctx.data::<DbPool>()?.clone();
+        paginate(after, first, move |offset, limit| {
+            let mut query = transactions::table.into_boxed();
+            if let Some(success) = success {
+                query = query.filter(transactions::success.eq(success));
+            }
+            if let Some(type_) = type_ {
+                query = query.filter(transactions::type_.eq(type_));
+            }
+            Ok(query
+                .order(transactions::version)
+                .offset(offset)
+                .limit(limit)
+                .load::<TransactionModel>(&pool.get()?)?)
+        })
+        .await
+    }
+
+    /// Events, by event handle and sequence number. `type` is the full type of
+    /// the event, e.g. "0x1::coin::DepositEvent"
+    async fn events(
+        &self,
+        ctx: &Context<'_>,
+        after: Option<String>,
+        first: Option<i32>,
+        account_address: Option<String>,
+        creation_number: Option<i64>,
+        #[graphql(name = "type")] type_: Option<String>,
+    ) -> Result<Connection<usize, Event>> {
+        let pool = ctx.data::<DbPool>()?.clone();
+        paginate(after, first, move |offset, limit| {
+            let mut query = events::table.into_boxed();
+            if let Some(account_address) = account_address {
+                query = query.filter(events::account_address.eq(account_address));
+            }
+            if let Some(creation_number) = creation_number {
+                query = query.filter(events::creation_number.eq(creation_number));
+            }
+            if let Some(type_) = type_ {
+                query = query.filter(events::type_.eq(type_));
+            }
+            Ok(query
+                .order((
+                    events::account_address,
+                    events::creation_number,
+                    events::sequence_number,
+                ))
+                .offset(offset)
+                .limit(limit)
+                .load::<EventModel>(&pool.get()?)?)
+        })
+        .await
+    }
+
+    /// How many of each token accounts hold
+    async fn token_ownerships(
+        &self,
+        ctx: &Context<'_>,
+        after: Option<String>,
+        first: Option<i32>,
+        owner: Option<String>,
+        token_id: Option<String>,
+    ) -> Result<Connection<usize, TokenOwnership>> {
+        let pool = ctx.data::<DbPool>()?.clone();
+        paginate(after, first, move |offset, limit| {
+            let mut query = ownerships::table.into_boxed();
+            if let Some(owner) = owner {
+                query = query.filter(ownerships::owner.eq(owner));
+            }
+            if let Some(token_id) = token_id {
+                query = query.filter(ownerships::token_id.eq(token_id));
+            }
+            Ok(query
+                .select((
+                    ownerships::token_id,
+                    ownerships::owner,
+                    ownerships::amount,
+                    ownerships::updated_at,
+                ))
+                .order(ownerships::ownership_id)
+                .offset(offset)
+                .limit(limit)
+                .load::<(Option<String>, Option<String>, i64, chrono::NaiveDateTime)>(
+                    &pool.get()?,
+                )?)
+        })
+        .await
+    }
+
+    /// The coin deposits and withdrawals of an account, oldest first
+    async fn coin_activities(
+        &self,
+        ctx: &Context<'_>,
+        after: Option<String>,
+        first: Option<i32>,
+        owner_address: String,
+        coin_type: Option<String>,
+    ) -> Result<Connection<usize, CoinActivity>> {
+        let pool = ctx.data::<DbPool>()?.clone();
+        paginate(after, first, move |offset, limit| {
+            let mut query = coin_activities::table
+                .filter(coin_activities::owner_address.eq(owner_address))
+                .into_boxed();
+            if let Some(coin_type) = coin_type {
+                query = query.filter(coin_activities::coin_type.eq(coin_type));
+            }
+            Ok(query
+                .order((
+                    coin_activities::transaction_version,
+                    coin_activities::event_key,
+                    coin_activities::sequence_number,
+                ))
+                .offset(offset)
+                .limit(limit)
+                .load::<CoinActivityModel>(&pool.get()?)?)
+        })
+        .await
+    }
+
+    /// The token events of an account, oldest first
+    async fn token_activities(
+        &self,
+        ctx: &Context<'_>,
+        after: Option<String>,
+        first: Option<i32>,
+        account: String,
+        token_id: Option<String>,
+    ) -> Result<Connection<usize, TokenActivity>> {
+        let pool = ctx.data::<DbPool>()?.clone();
+        paginate(after, first, move |offset, limit| {
+            let mut query = token_activities::table
+                .filter(token_activities::account.eq(account))
+                .into_boxed();
+            if let Some(token_id) = token_id {
+                query = query.filter(token_activities::token_id.eq(token_id));
+            }
+            Ok(query
+                .order((
+                    token_activities::created_at,
+                    token_activities::event_key,
+                    token_activities::sequence_number,
+                ))
+                .offset(offset)
+                .limit(limit)
+                .load::<TokenActivityModel>(&pool.get()?)?)
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_paginate() {
+        let rows: Vec<i32> = (0..30).collect();
+        let load = |offset: i64, limit: i64| {
+            Ok(rows
+                .iter()
+                .skip(offset as usize)
+                .take(limit as usize)
+                .copied()
+                .collect())
+        };
+
+        let page: Connection<usize, i32> = paginate(None, Some(10), load).await.unwrap();
+        assert_eq!(page.edges.len(), 10);
+        assert!(!page.has_previous_page);
+        assert!(page.has_next_page);
+
+        let page: Connection<usize, i32> = paginate(Some("19".to_string()), Some(20), load)
+            .await
+            .unwrap();
+        assert_eq!(page.edges.first().unwrap().node, 20);
+        assert_eq!(page.edges.len(), 10);
+        assert!(page.has_previous_page);
+        assert!(!page.has_next_page);
+
+        let page: Connection<usize, i32> = paginate(None, None, load).await.unwrap();
+        assert_eq!(page.edges.len(), DEFAULT_PAGE_SIZE);
+    }
+}
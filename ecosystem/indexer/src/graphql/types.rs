@@ -0,0 +1,156 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+//! The objects the GraphQL API returns. They mirror the database models, but
+//! are kept apart so the models don't depend on the `graphql` feature.
+
+use crate::models::{
+    coin::CoinActivity as CoinActivityModel, events::EventModel,
+    token_activity::TokenActivity as TokenActivityModel, transactions::TransactionModel,
+};
+use async_graphql::{Json, SimpleObject};
+
+#[derive(SimpleObject)]
+pub struct Transaction {
+    pub version: i64,
+    pub hash: String,
+    #[graphql(name = "type")]
+    pub type_: String,
+    pub payload: Json<serde_json::Value>,
+    pub state_root_hash: String,
+    pub event_root_hash: String,
+    pub accumulator_root_hash: String,
+    pub gas_used: i64,
+    pub success: bool,
+    pub vm_status: String,
+}
+
+impl From<TransactionModel> for Transaction {
+    fn from(transaction: TransactionModel) -> Self {
+        Self {
+            version: transaction.version,
+            hash: transaction.hash,
+            type_: transaction.type_,
+            payload: Json(transaction.payload.0),
+            state_root_hash: transaction.state_root_hash,
+            event_root_hash: transaction.event_root_hash,
+            accumulator_root_hash: transaction.accumulator_root_hash,
+            gas_used: transaction.gas_used,
+            success: transaction.success,
+            vm_status: transaction.vm_status,
+        }
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct Event {
+    pub transaction_hash: String,
+    pub key: String,
+    pub sequence_number: i64,
+    pub account_address: String,
+    pub creation_number: i64,
+    #[graphql(name = "type")]
+    pub type_: String,
+    pub type_address: Option<String>,
+    pub type_module: Option<String>,
+    pub type_name: Option<String>,
+    pub data: Json<serde_json::Value>,
+}
+
+impl From<EventModel> for Event {
+    fn from(event: EventModel) -> Self {
+        Self {
+            transaction_hash: event.transaction_hash,
+            key: event.key,
+            sequence_number: event.sequence_number,
+            account_address: event.account_address,
+            creation_number: event.creation_number,
+            type_: event.type_,
+            type_address: event.type_address,
+            type_module: event.type_module,
+            type_name: event.type_name,
+            data: Json(event.data.0),
+        }
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct TokenOwnership {
+    pub token_id: Option<String>,
+    pub owner: Option<String>,
+    pub amount: i64,
+    pub updated_at: chrono::NaiveDateTime,
+}
+
+impl From<(Option<String>, Option<String>, i64, chrono::NaiveDateTime)> for TokenOwnership {
+    fn from(
+        (token_id, owner, amount, updated_at): (
+            Option<String>,
+            Option<String>,
+            i64,
+            chrono::NaiveDateTime,
+        ),
+    ) -> Self {
+        Self {
+            token_id,
+            owner,
+            amount,
+            updated_at,
+        }
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct CoinActivity {
+    pub owner_address: String,
+    pub coin_type: String,
+    /// "deposit" or "withdraw"
+    pub activity_type: String,
+    pub amount: i64,
+    pub transaction_version: i64,
+    pub transaction_timestamp: chrono::NaiveDateTime,
+    pub event_key: String,
+    pub sequence_number: i64,
+}
+
+impl From<CoinActivityModel> for CoinActivity {
+    fn from(activity: CoinActivityModel) -> Self {
+        Self {
+            owner_address: activity.owner_address,
+            coin_type: activity.coin_type,
+            activity_type: activity.activity_type,
+            amount: activity.amount,
+            transaction_version: activity.transaction_version,
+            transaction_timestamp: activity.transaction_timestamp,
+            event_key: activity.event_key,
+            sequence_number: activity.sequence_number,
+        }
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct TokenActivity {
+    pub account: String,
+    pub token_id: Option<String>,
+    pub event_type: Option<String>,
+    pub amount: Option<i64>,
+    pub transaction_hash: String,
+    pub created_at: chrono::NaiveDateTime,
+    pub event_key: String,
+    pub sequence_number: i64,
+}
+
+impl From<TokenActivityModel> for TokenActivity {
+    fn from(activity: TokenActivityModel) -> Self {
+        Self {
+            account: activity.account,
+            token_id: activity.token_id,
+            event_type: activity.event_type,
+            amount: activity.amount,
+            transaction_hash: activity.transaction_hash,
+            created_at: activity.created_at,
+            event_key: activity.event_key,
+            sequence_number: activity.sequence_number,
+        }
+    }
+}
@@ -0,0 +1,147 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+//! Endpoints for monitoring the indexer:
+//!
+//! - `GET /health`: 200 while every processor is at most `max_lag` versions behind the node, 503 otherwise, with the
+//!   latest `LagReport` as the body
+//! - `GET /metrics`: the indexer's metrics, in the Prometheus text format
+
+use crate::indexer::lag_monitor::{LagReport, SharedLagReport};
+use aptos_metrics_core::{Encoder, TextEncoder};
+use serde::Serialize;
+use std::{net::SocketAddr, time::Duration};
+use warp::{
+    http::StatusCode,
+    reply::{self, Response},
+    Filter, Rejection, Reply,
+};
+
+#[derive(Debug, Serialize)]
+pub struct Health {
+    pub healthy: bool,
+    /// Why the indexer isn't healthy, if it isn't
+    pub reason: Option<String>,
+    pub report: Option<LagReport>,
+}
+
+impl Health {
+    /// A report older than `max_report_age` means the node couldn't be reached
+    /// for a while, which is unhealthy too
+    pub fn from_report(
+        report: Option<LagReport>,
+        max_lag: u64,
+        max_report_age: Duration,
+        now: chrono::NaiveDateTime,
+    ) -> Self {
+        let reason = match &report {
+            None => Some("processor lag has not been checked yet".to_string()),
+            Some(report)
+                if chrono::Duration::from_std(max_report_age)
+                    .map_or(false, |max_age| now - report.checked_at > max_age) =>
+            {
+                Some(format!(
+                    "processor lag was last checked at {}",
+                    report.checked_at
+                ))
+            }
+            Some(report) => {
+                let lagging = report.lagging_processors(max_lag);
+                if lagging.is_empty() {
+                    None
+                } else {
+                    Some(format!(
+                        "{} more than {} versions behind the node",
+                        lagging.join(", "),
+                        max_lag
+                    ))
+                }
+            }
+        };
+        Self {
+            healthy: reason.is_none(),
+            reason,
+            report,
+        }
+    }
+}
+
+fn metrics() -> Response {
+    let mut buffer = vec![];
+    match TextEncoder::new().encode(&aptos_metrics_core::gather(), &mut buffer) {
+        Ok(()) => Response::new(buffer.into()),
+        Err(err) => {
+            reply::with_status(err.to_string(), StatusCode::INTERNAL_SERVER_ERROR).into_response()
+        }
+    }
+}
+
+pub fn routes(
+    report: SharedLagReport,
+    max_lag: u64,
+    max_report_age: Duration,
+) -> impl Filter<Extract = (Response,), Error = Rejection> + Clone {
+    let health = warp::path!("health").and(warp::get()).map(move || {
+        let report = report.read().unwrap().clone();
+        let health = Health::from_report(
+            report,
+            max_lag,
+            max_report_age,
+            chrono::Utc::now().naive_utc(),
+        );
+        let status = if health.healthy {
+            StatusCode::OK
+        } else {
+            StatusCode::SERVICE_UNAVAILABLE
+        };
+        reply::with_status(reply::json(&health), status).into_response()
+    });
+    let metrics = warp::path!("metrics").and(warp::get()).map(metrics);
+    health.or(metrics).unify()
+}
+
+pub async fn serve(
+    report: SharedLagReport,
+    max_lag: u64,
+    max_report_age: Duration,
+    address: SocketAddr,
+) {
+    aptos_logger::info!("Serving health and metrics on {}", address);
+    warp::serve(routes(report, max_lag, max_report_age))
+        .run(address)
+        .await;
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::indexer::lag_monitor::ProcessorLag;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn test_health_from_report() {
+        let now = chrono::Utc::now().naive_utc();
+        let max_age = Duration::from_secs(60);
+        let report = |lag: u64, checked_secs_ago: i64| LagReport {
+            node_version: 1000,
+            processors: BTreeMap::from([(
+                "default_processor",
+                ProcessorLag {
+                    latest_version: Some(1000 - lag),
+                    lag,
+                },
+            )]),
+            checked_at: now - chrono::Duration::seconds(checked_secs_ago),
+        };
+
+        assert!(Health::from_report(Some(report(10, 0)), 100, max_age, now).healthy);
+        let health = Health::from_report(Some(report(500, 0)), 100, max_age, now);
+        assert!(!health.healthy);
+        assert_eq!(
+            health.reason.unwrap(),
+            "default_processor more than 100 versions behind the node"
+        );
+        assert!(!Health::from_report(Some(report(10, 120)), 100, max_age, now).healthy);
+        assert!(!Health::from_report(None, 100, max_age, now).healthy);
+    }
+}
@@ -12,6 +12,8 @@ pub enum TransactionProcessingError {
     ConnectionPoolError(ErrorWithVersionAndName),
     /// Could not commit the transaction
     TransactionCommitError(ErrorWithVersionAndName),
+    /// Could not do the work following the commit, see `TransactionProcessor::after_commit`
+    AfterCommitError(ErrorWithVersionAndName),
 }
 
 impl TransactionProcessingError {
@@ -19,6 +21,7 @@ impl TransactionProcessingError {
         match self {
             TransactionProcessingError::ConnectionPoolError(ewv) => ewv,
             TransactionProcessingError::TransactionCommitError(ewv) => ewv,
+            TransactionProcessingError::AfterCommitError(ewv) => ewv,
         }
     }
 }
@@ -1,34 +1,124 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::counters::{FETCHED_TRANSACTION, UNABLE_TO_FETCH_TRANSACTION};
+#[cfg(feature = "offline")]
+use crate::indexer::offline_db::OfflineDb;
+use crate::{
+    counters::{FETCHED_TRANSACTION, UNABLE_TO_FETCH_TRANSACTION},
+    indexer::throttle::{is_back_pressure, FetchThrottle, ThrottleConfig},
+};
 use aptos_rest_client::{Client as RestClient, Transaction};
-use std::time::Duration;
+#[cfg(feature = "offline")]
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tokio::sync::Mutex;
 use url::Url;
 
 // TODO: make this configurable
 const RETRY_TIME_MILLIS: u64 = 5000;
-const TRANSACTION_FETCH_BATCH_SIZE: u16 = 500;
+pub const TRANSACTION_FETCH_BATCH_SIZE: u16 = 500;
+
+/// Where transactions are fetched from
+#[derive(Clone, Debug)]
+pub enum TransactionSource {
+    /// The REST API of a node
+    Node(Url),
+    /// An AptosDB on disk, e.g. restored from backups. Requires the `offline` feature
+    #[cfg(feature = "offline")]
+    Db(Arc<OfflineDb>),
+}
+
+#[derive(Debug)]
+enum Backend {
+    Node(RestClient),
+    #[cfg(feature = "offline")]
+    Db(Arc<OfflineDb>),
+}
+
+impl Backend {
+    async fn get_transactions(&self, version: u64, limit: u16) -> anyhow::Result<Vec<Transaction>> {
+        match self {
+            Backend::Node(client) => Ok(client
+                .get_transactions(Some(version), Some(limit))
+                .await?
+                .into_inner()),
+            #[cfg(feature = "offline")]
+            Backend::Db(db) => {
+                let db = db.clone();
+                tokio::task::spawn_blocking(move || db.get_transactions(version, limit)).await?
+            }
+        }
+    }
+
+    async fn get_transaction(&self, version: u64) -> anyhow::Result<Transaction> {
+        match self {
+            Backend::Node(client) => Ok(client
+                .get_transaction_by_version(version)
+                .await?
+                .into_inner()),
+            #[cfg(feature = "offline")]
+            Backend::Db(db) => {
+                let db = db.clone();
+                tokio::task::spawn_blocking(move || db.get_transaction(version)).await?
+            }
+        }
+    }
+
+    async fn get_chain_id(&self) -> anyhow::Result<u8> {
+        match self {
+            Backend::Node(client) => {
+                Ok(client.get_ledger_information().await?.into_inner().chain_id)
+            }
+            #[cfg(feature = "offline")]
+            Backend::Db(db) => db.chain_id(),
+        }
+    }
+
+    async fn get_ledger_version(&self) -> anyhow::Result<u64> {
+        match self {
+            Backend::Node(client) => {
+                Ok(client.get_ledger_information().await?.into_inner().version)
+            }
+            #[cfg(feature = "offline")]
+            Backend::Db(db) => db.latest_version(),
+        }
+    }
+}
 
 #[derive(Debug)]
 pub struct TransactionFetcher {
-    client: RestClient,
+    backend: Backend,
     version: u64,
     transactions_buffer: Mutex<Vec<Transaction>>,
+    throttle: FetchThrottle,
 }
 
 impl TransactionFetcher {
     pub fn new(node_url: Url, starting_version: Option<u64>) -> Self {
-        let client = RestClient::new(node_url);
+        Self::from_source(TransactionSource::Node(node_url), starting_version)
+    }
+
+    pub fn from_source(source: TransactionSource, starting_version: Option<u64>) -> Self {
+        let backend = match source {
+            TransactionSource::Node(node_url) => Backend::Node(RestClient::new(node_url)),
+            #[cfg(feature = "offline")]
+            TransactionSource::Db(db) => Backend::Db(db),
+        };
 
         Self {
-            client,
+            backend,
             version: starting_version.unwrap_or(0),
             transactions_buffer: Default::default(),
+            throttle: FetchThrottle::new(ThrottleConfig::default()),
         }
     }
 
+    /// Sets how batch requests adapt to the node, see `FetchThrottle`
+    pub fn with_throttle_config(mut self, config: ThrottleConfig) -> Self {
+        self.throttle = FetchThrottle::new(config);
+        self
+    }
+
     pub fn set_version(&mut self, version: u64) {
         self.version = version;
     }
@@ -40,60 +130,104 @@ impl TransactionFetcher {
         let mut transactions_buffer = self.transactions_buffer.lock().await;
         if transactions_buffer.is_empty() {
             // Fill it up!
-            loop {
-                let res = self
-                    .client
-                    .get_transactions(Some(self.version), Some(TRANSACTION_FETCH_BATCH_SIZE))
-                    .await;
-                match res {
-                    Ok(response) => {
+            let mut transactions = self
+                .fetch_batch(self.version, TRANSACTION_FETCH_BATCH_SIZE)
+                .await;
+            transactions.reverse();
+            *transactions_buffer = transactions;
+        }
+        // At this point we're guaranteed to have something in the buffer
+        let transaction = transactions_buffer.pop().unwrap();
+        self.version += 1;
+        transaction
+    }
+
+    /// Fetches up to `limit` versions starting at `version`. Fewer are returned if the node doesn't
+    /// have `limit` versions past `version` yet, or is pushing back, but never none.
+    /// In the event it can't fetch, it will keep retrying every RETRY_TIME_MILLIS ms, or sooner if the node
+    /// pushed back, as set by the `FetchThrottle`
+    pub async fn fetch_batch(&self, version: u64, limit: u16) -> Vec<Transaction> {
+        loop {
+            let delay = self.throttle.delay();
+            if !delay.is_zero() {
+                tokio::time::sleep(delay).await;
+            }
+            let batch_size = self.throttle.batch_size(limit);
+            let started_at = Instant::now();
+            let res = self.backend.get_transactions(version, batch_size).await;
+            match res {
+                Ok(transactions) => {
+                    self.throttle
+                        .record_response(batch_size, started_at.elapsed());
+                    if !transactions.is_empty() {
                         FETCHED_TRANSACTION.inc();
-                        let mut transactions = response.into_inner();
-                        transactions.reverse();
-                        *transactions_buffer = transactions;
-                        break;
+                        return transactions;
                     }
-                    Err(err) => {
-                        let err_str = err.to_string();
-                        // If it's a 404, then we're all caught up; no need to increment the `UNABLE_TO_FETCH_TRANSACTION` counter
-                        if err_str.contains("404") {
-                            aptos_logger::debug!(
+                    aptos_logger::debug!(
+                        "No transactions starting at {}: all caught up. Will check again in {}ms.",
+                        version,
+                        RETRY_TIME_MILLIS,
+                    );
+                }
+                Err(err) if is_back_pressure(&err) => {
+                    self.throttle.record_back_pressure(batch_size);
+                    aptos_logger::warn!(
+                        "Node pushed back on fetching {} transactions starting at {}, will wait {:?} and request at most {}. Err: {:?}",
+                        batch_size,
+                        version,
+                        self.throttle.delay(),
+                        self.throttle.batch_size(limit),
+                        err
+                    );
+                    continue;
+                }
+                Err(err) => {
+                    // If it's a 404, then we're all caught up; no need to increment the `UNABLE_TO_FETCH_TRANSACTION` counter
+                    if err.to_string().contains("404") {
+                        aptos_logger::debug!(
                             "Could not fetch {} transactions starting at {}: all caught up. Will check again in {}ms.",
-                            TRANSACTION_FETCH_BATCH_SIZE,
-                            self.version,
+                            batch_size,
+                            version,
                             RETRY_TIME_MILLIS,
                         );
-                            tokio::time::sleep(Duration::from_millis(RETRY_TIME_MILLIS)).await;
-                            continue;
-                        }
+                    } else {
                         UNABLE_TO_FETCH_TRANSACTION.inc();
                         aptos_logger::error!(
                             "Could not fetch {} transactions starting at {}, will retry in {}ms. Err: {:?}",
-                            TRANSACTION_FETCH_BATCH_SIZE,
-                            self.version,
+                            batch_size,
+                            version,
                             RETRY_TIME_MILLIS,
                             err
                         );
-                        tokio::time::sleep(Duration::from_millis(RETRY_TIME_MILLIS)).await;
                     }
-                };
-            }
+                }
+            };
+            tokio::time::sleep(Duration::from_millis(RETRY_TIME_MILLIS)).await;
         }
-        // At this point we're guaranteed to have something in the buffer
-        let transaction = transactions_buffer.pop().unwrap();
-        self.version += 1;
-        transaction
+    }
+
+    /// Fetches the chain id of the node, and the hash of its genesis transaction
+    pub async fn fetch_chain_identity(&self) -> anyhow::Result<(u8, String)> {
+        let chain_id = self.backend.get_chain_id().await?;
+        let genesis = self.backend.get_transaction(0).await?;
+        let genesis_hash = genesis.transaction_info()?.hash.to_string();
+        Ok((chain_id, genesis_hash))
+    }
+
+    /// Fetches the latest version of the node's ledger
+    pub async fn fetch_ledger_version(&self) -> anyhow::Result<u64> {
+        self.backend.get_ledger_version().await
     }
 
     /// fetches one version; this used for error checking/repair/etc
     /// In the event it can't, it will keep retrying every RETRY_TIME_MILLIS ms
     pub async fn fetch_version(&self, version: u64) -> Transaction {
         loop {
-            let res = self.client.get_transaction_by_version(version).await;
+            let res = self.backend.get_transaction(version).await;
             match res {
-                Ok(response) => {
+                Ok(transaction) => {
                     FETCHED_TRANSACTION.inc();
-                    return response.into_inner();
+                    return transaction;
                 }
                 Err(err) => {
                     UNABLE_TO_FETCH_TRANSACTION.inc();
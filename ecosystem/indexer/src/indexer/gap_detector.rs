@@ -0,0 +1,116 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    counters::{BACKFILLED_VERSIONS, GAP_VERSIONS},
+    indexer::{
+        fetcher::{TransactionFetcher, TRANSACTION_FETCH_BATCH_SIZE},
+        tailer::{remove_null_bytes_from_txn, Tailer},
+        transaction_processor::TransactionProcessor,
+    },
+};
+use aptos_logger::{debug, info};
+use aptos_rest_client::Transaction;
+use std::{
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+/// Periodically looks for versions each processor never got to, and runs the
+/// processor on them. Versions that failed are retried by the `RetryScheduler`.
+///
+/// Only versions below `processed_version` are considered: above it, versions
+/// may still be in flight, and in flight versions have no status yet.
+pub struct GapDetector {
+    tailer: Tailer,
+    transaction_fetcher: TransactionFetcher,
+    processed_version: Arc<AtomicU64>,
+    scan_interval: Duration,
+    max_backfill_versions: u64,
+}
+
+impl GapDetector {
+    pub fn new(
+        tailer: Tailer,
+        processed_version: Arc<AtomicU64>,
+        scan_interval: Duration,
+        max_backfill_versions: u64,
+    ) -> Self {
+        let transaction_fetcher = TransactionFetcher::from_source(tailer.source().clone(), None);
+        Self {
+            tailer,
+            transaction_fetcher,
+            processed_version,
+            scan_interval,
+            max_backfill_versions,
+        }
+    }
+
+    /// Scans every `scan_interval`, forever
+    pub async fn run(&self) {
+        loop {
+            tokio::time::sleep(self.scan_interval).await;
+            self.scan_and_backfill().await;
+        }
+    }
+
+    /// Scans each processor's statuses, updates the gap metrics, and backfills
+    /// up to `max_backfill_versions` versions per processor
+    pub async fn scan_and_backfill(&self) {
+        let below_version = self.processed_version.load(Ordering::SeqCst);
+        for processor in self.tailer.processors() {
+            let name = processor.name();
+            let gaps = processor.get_gaps(below_version);
+            let gap_versions: i64 = gaps.iter().map(|gap| gap.gap_end - gap.gap_start + 1).sum();
+            GAP_VERSIONS.with_label_values(&[name]).set(gap_versions);
+            if gaps.is_empty() {
+                debug!("[{}] No gaps below version {}", name, below_version);
+                continue;
+            }
+            info!(
+                "[{}] Found {} missing versions in {} gaps below version {}",
+                name,
+                gap_versions,
+                gaps.len(),
+                below_version,
+            );
+
+            let mut budget = self.max_backfill_versions;
+            for gap in gaps {
+                let mut version = gap.gap_start as u64;
+                while version <= gap.gap_end as u64 && budget > 0 {
+                    let limit = (gap.gap_end as u64 - version + 1)
+                        .min(budget)
+                        .min(TRANSACTION_FETCH_BATCH_SIZE as u64);
+                    let transactions = self
+                        .transaction_fetcher
+                        .fetch_batch(version, limit as u16)
+                        .await;
+                    for txn in transactions {
+                        self.backfill(processor, Arc::new(txn)).await;
+                        version += 1;
+                        budget -= 1;
+                    }
+                }
+            }
+            if budget == 0 {
+                info!(
+                    "[{}] Backfilled {} versions, will continue on the next scan",
+                    name, self.max_backfill_versions
+                );
+            }
+        }
+    }
+
+    async fn backfill(&self, processor: &Arc<dyn TransactionProcessor>, txn: Arc<Transaction>) {
+        let txn = remove_null_bytes_from_txn(txn);
+        // The outcome is recorded in `processor_statuses`, failures are retried by the `RetryScheduler`
+        let _ = self.tailer.run_processor(processor, vec![txn]).await;
+        BACKFILLED_VERSIONS
+            .with_label_values(&[processor.name()])
+            .inc();
+    }
+}
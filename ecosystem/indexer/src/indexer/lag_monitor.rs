@@ -0,0 +1,112 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    counters::{NODE_LATEST_VERSION, PROCESSOR_LAG, PROCESSOR_LATEST_VERSION},
+    indexer::{fetcher::TransactionFetcher, tailer::Tailer},
+};
+use aptos_logger::warn;
+use serde::Serialize;
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, RwLock},
+    time::Duration,
+};
+
+/// How far behind the node a processor is
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct ProcessorLag {
+    /// The highest version the processor has a status for, if any
+    pub latest_version: Option<u64>,
+    /// How many versions the processor is behind the node
+    pub lag: u64,
+}
+
+/// What the `LagMonitor` found on its last check
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct LagReport {
+    pub node_version: u64,
+    pub processors: BTreeMap<&'static str, ProcessorLag>,
+    pub checked_at: chrono::NaiveDateTime,
+}
+
+impl LagReport {
+    /// The processors more than `max_lag` versions behind the node
+    pub fn lagging_processors(&self, max_lag: u64) -> Vec<&'static str> {
+        self.processors
+            .iter()
+            .filter(|(_, processor_lag)| processor_lag.lag > max_lag)
+            .map(|(name, _)| *name)
+            .collect()
+    }
+}
+
+/// The last report of a `LagMonitor`, shared with whoever reports health
+pub type SharedLagReport = Arc<RwLock<Option<LagReport>>>;
+
+/// Periodically compares each processor's latest version to the node's, and
+/// exports both and the difference as metrics
+pub struct LagMonitor {
+    tailer: Tailer,
+    transaction_fetcher: TransactionFetcher,
+    check_interval: Duration,
+    report: SharedLagReport,
+}
+
+impl LagMonitor {
+    pub fn new(tailer: Tailer, check_interval: Duration) -> Self {
+        let transaction_fetcher = TransactionFetcher::from_source(tailer.source().clone(), None);
+        Self {
+            tailer,
+            transaction_fetcher,
+            check_interval,
+            report: Arc::new(RwLock::new(None)),
+        }
+    }
+
+    pub fn report(&self) -> SharedLagReport {
+        self.report.clone()
+    }
+
+    /// Checks every `check_interval`, forever
+    pub async fn run(&self) {
+        loop {
+            match self.check().await {
+                Ok(report) => *self.report.write().unwrap() = Some(report),
+                Err(err) => warn!(
+                    "Could not check how far behind the node processors are: {:?}",
+                    err
+                ),
+            }
+            tokio::time::sleep(self.check_interval).await;
+        }
+    }
+
+    pub async fn check(&self) -> anyhow::Result<LagReport> {
+        let node_version = self.transaction_fetcher.fetch_ledger_version().await?;
+        NODE_LATEST_VERSION.set(node_version as i64);
+
+        let mut processors = BTreeMap::new();
+        for processor in self.tailer.processors() {
+            let name = processor.name();
+            let latest_version = processor.get_max_version();
+            let lag = node_version.saturating_sub(latest_version.unwrap_or_default());
+            PROCESSOR_LATEST_VERSION
+                .with_label_values(&[name])
+                .set(latest_version.map_or(-1, |version| version as i64));
+            PROCESSOR_LAG.with_label_values(&[name]).set(lag as i64);
+            processors.insert(
+                name,
+                ProcessorLag {
+                    latest_version,
+                    lag,
+                },
+            );
+        }
+        Ok(LagReport {
+            node_version,
+            processors,
+            checked_at: chrono::Utc::now().naive_utc(),
+        })
+    }
+}
@@ -3,7 +3,19 @@
 
 pub mod errors;
 pub mod fetcher;
+pub mod gap_detector;
+pub mod lag_monitor;
 pub mod metadata_fetcher;
+#[cfg(feature = "offline")]
+pub mod offline_db;
+pub mod pipeline;
 pub mod processing_result;
+pub mod processor_registry;
+pub mod reindex;
+pub mod retention;
+pub mod retry_scheduler;
+pub mod sink;
+pub mod start_strategy;
 pub mod tailer;
+pub mod throttle;
 pub mod transaction_processor;
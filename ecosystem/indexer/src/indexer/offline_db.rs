@@ -0,0 +1,106 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+//! Reads transactions straight from an AptosDB on disk, such as one restored from backups with `db-restore`, in the
+//! form the REST API serves them. Transaction backups hold neither the write sets nor the state that decoding
+//! resources and payloads needs, so they are replayed into a database first rather than fed to the processors as is.
+
+use anyhow::Context;
+use aptos_config::config::{RocksdbConfigs, NO_OP_STORAGE_PRUNER_CONFIG, TARGET_SNAPSHOT_SIZE};
+use aptos_rest_client::{
+    aptos_api_types::{AsConverter, TransactionOnChainData},
+    Transaction,
+};
+use aptos_state_view::account_with_state_view::AsAccountWithStateView;
+use aptos_types::{account_config::CORE_CODE_ADDRESS, account_view::AccountView};
+use aptos_vm::data_cache::IntoMoveResolver;
+use aptosdb::AptosDB;
+use std::{fmt, path::Path, sync::Arc};
+use storage_interface::{state_view::LatestDbStateCheckpointView, DbReader};
+
+pub struct OfflineDb {
+    db: Arc<dyn DbReader>,
+}
+
+impl fmt::Debug for OfflineDb {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "OfflineDb")
+    }
+}
+
+impl OfflineDb {
+    /// Opens the database read only, so it can keep being restored into while indexing
+    pub fn open(path: &Path) -> anyhow::Result<Self> {
+        let db = AptosDB::open(
+            path,
+            true, /* readonly */
+            NO_OP_STORAGE_PRUNER_CONFIG,
+            RocksdbConfigs::default(),
+            false, /* enable_indexer */
+            TARGET_SNAPSHOT_SIZE,
+        )
+        .with_context(|| format!("Could not open AptosDB at {:?}", path))?;
+        Ok(Self { db: Arc::new(db) })
+    }
+
+    pub fn latest_version(&self) -> anyhow::Result<u64> {
+        self.db.get_latest_version()
+    }
+
+    pub fn chain_id(&self) -> anyhow::Result<u8> {
+        let chain_id = self
+            .db
+            .latest_state_checkpoint_view()?
+            .as_account_with_state_view(&CORE_CODE_ADDRESS)
+            .get_chain_id_resource()?
+            .context("The database has no chain id resource")?;
+        Ok(chain_id.chain_id().id())
+    }
+
+    /// Up to `limit` transactions starting at `start_version`, none if the database doesn't have `start_version` yet
+    pub fn get_transactions(
+        &self,
+        start_version: u64,
+        limit: u16,
+    ) -> anyhow::Result<Vec<Transaction>> {
+        let ledger_version = self.db.get_latest_version()?;
+        if start_version > ledger_version {
+            return Ok(vec![]);
+        }
+        let limit = (limit as u64).min(ledger_version - start_version + 1);
+        let outputs = self
+            .db
+            .get_transaction_outputs(start_version, limit, ledger_version)?;
+
+        let resolver = self.db.latest_state_checkpoint_view()?.into_move_resolver();
+        let converter = resolver.as_converter(self.db.clone());
+        outputs
+            .transactions_and_outputs
+            .into_iter()
+            .zip(outputs.proof.transaction_infos.into_iter())
+            .enumerate()
+            .map(|(i, ((transaction, output), info))| {
+                let version = start_version + i as u64;
+                let (write_set, events, _, _) = output.unpack();
+                let accumulator_root_hash = self.db.get_accumulator_root_hash(version)?;
+                let data: TransactionOnChainData = (
+                    version,
+                    transaction,
+                    info,
+                    events,
+                    accumulator_root_hash,
+                    write_set,
+                )
+                    .into();
+                let timestamp = self.db.get_block_timestamp(version)?;
+                converter.try_into_onchain_transaction(timestamp, data)
+            })
+            .collect()
+    }
+
+    pub fn get_transaction(&self, version: u64) -> anyhow::Result<Transaction> {
+        self.get_transactions(version, 1)?
+            .pop()
+            .with_context(|| format!("The database has no version {} yet", version))
+    }
+}
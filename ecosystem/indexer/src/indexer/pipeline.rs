@@ -0,0 +1,305 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    counters::NEXT_VERSION_TO_PROCESS,
+    indexer::{
+        fetcher::{TransactionFetcher, TRANSACTION_FETCH_BATCH_SIZE},
+        tailer::{remove_null_bytes_from_txn, Tailer},
+        throttle::ThrottleConfig,
+    },
+};
+use aptos_logger::{error, info};
+use aptos_rest_client::Transaction;
+use std::{
+    collections::BTreeMap,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+};
+use tokio::sync::{mpsc, OwnedSemaphorePermit, Semaphore};
+
+/// How the indexing pipeline is sized
+#[derive(Clone, Copy, Debug)]
+pub struct PipelineConfig {
+    /// How many tasks fetch transactions from the node concurrently
+    pub fetcher_tasks: usize,
+    /// How many versions each fetcher task claims, and asks the node for, at a time
+    pub fetch_batch_size: u16,
+    /// How many claimed ranges of versions may be fetched or waiting to be processed before
+    /// fetching pauses
+    pub channel_size: usize,
+    /// How fetching adapts when the node is slow or pushes back
+    pub throttle: ThrottleConfig,
+}
+
+impl Default for PipelineConfig {
+    fn default() -> Self {
+        Self {
+            fetcher_tasks: 2,
+            fetch_batch_size: TRANSACTION_FETCH_BATCH_SIZE,
+            channel_size: 10,
+            throttle: ThrottleConfig::default(),
+        }
+    }
+}
+
+/// Consecutive transactions, starting at version `start`
+struct Batch {
+    start: u64,
+    transactions: Vec<Transaction>,
+    /// Held until every processor is done with the batch, see `Pipeline::run_range`
+    permit: Arc<OwnedSemaphorePermit>,
+}
+
+/// A batch handed to a processor task, with the version it ends at (exclusive)
+type ProcessorBatch = (u64, Arc<Vec<Arc<Transaction>>>, Arc<OwnedSemaphorePermit>);
+
+/// Batches that have been fetched, in the order they have to be processed. Fetcher tasks
+/// complete out of order, so batches are held here until the ones before them arrive.
+pub struct OrderedBuffer<T> {
+    next_version: u64,
+    /// Batches past `next_version`, by their first version, along with how many versions they hold
+    pending: BTreeMap<u64, (u64, T)>,
+}
+
+impl<T> OrderedBuffer<T> {
+    pub fn new(starting_version: u64) -> Self {
+        Self {
+            next_version: starting_version,
+            pending: BTreeMap::new(),
+        }
+    }
+
+    /// Adds a batch of `count` versions, starting at version `start`
+    pub fn push(&mut self, start: u64, count: u64, batch: T) {
+        self.pending.insert(start, (count, batch));
+    }
+
+    /// Takes the batch starting at the next version, if it has arrived
+    pub fn pop(&mut self) -> Option<T> {
+        let (count, batch) = self.pending.remove(&self.next_version)?;
+        self.next_version += count;
+        Some(batch)
+    }
+
+    /// The first version that hasn't been handed out yet
+    pub fn next_version(&self) -> u64 {
+        self.next_version
+    }
+}
+
+/// Runs the `Tailer`'s processors over the chain as a pipeline: fetcher tasks
+/// claim ranges of versions and pull them from the node, handing the batches
+/// over a bounded channel to an `OrderedBuffer`, which passes them on in
+/// version order to one task per processor. When the processors fall behind
+/// the channel fills up, and fetching waits until it drains. When the node
+/// falls behind, fetching slows down too, see `FetchThrottle`.
+///
+/// Each processor commits a batch in a single DB transaction, and commits the
+/// batches one after another in version order, so what it has written never
+/// has gaps. Processors run concurrently, so progress is reported up to the
+/// version every processor has reached.
+pub struct Pipeline {
+    tailer: Tailer,
+    config: PipelineConfig,
+    processed_version: Arc<AtomicU64>,
+}
+
+impl Pipeline {
+    pub fn new(tailer: Tailer, config: PipelineConfig) -> Self {
+        Self {
+            tailer,
+            config,
+            processed_version: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    /// The version below which the pipeline has processed every version, kept
+    /// up to date while it runs
+    pub fn processed_version(&self) -> Arc<AtomicU64> {
+        self.processed_version.clone()
+    }
+
+    /// Indexes from `starting_version` on, logging every `emit_every` versions
+    /// (never if 0). Only returns if every processor task has exited.
+    pub async fn run(&self, starting_version: u64, emit_every: usize) {
+        self.run_range(starting_version, None, emit_every).await
+    }
+
+    /// Indexes from `starting_version` up to (exclusive) `end_version`, or
+    /// forever if `None`, logging every `emit_every` versions (never if 0).
+    /// Returns once every version of the range has been processed.
+    pub async fn run_range(
+        &self,
+        starting_version: u64,
+        end_version: Option<u64>,
+        emit_every: usize,
+    ) {
+        let channel_size = self.config.channel_size.max(1);
+        let (batch_sender, mut batch_receiver) = mpsc::channel::<Batch>(channel_size);
+        let (done_sender, mut done_receiver) = mpsc::unbounded_channel::<(usize, u64)>();
+
+        // A fetcher task takes a permit before claiming a range, and the range's batches
+        // give it back once every processor is done with them. This bounds how far
+        // fetching runs ahead of the slowest range, and so how much the `OrderedBuffer`
+        // holds. The range the buffer waits for already has its permit, so it can't stall.
+        let window = Arc::new(Semaphore::new(channel_size));
+        // Shared by every fetcher task, so they all back off together
+        let fetcher = Arc::new(
+            TransactionFetcher::from_source(self.tailer.source().clone(), None)
+                .with_throttle_config(self.config.throttle),
+        );
+        let next_version = Arc::new(AtomicU64::new(starting_version));
+        let fetch_batch_size = self.config.fetch_batch_size.max(1);
+        for _ in 0..self.config.fetcher_tasks.max(1) {
+            let fetcher = fetcher.clone();
+            let next_version = next_version.clone();
+            let batch_sender = batch_sender.clone();
+            let window = window.clone();
+            tokio::task::spawn(async move {
+                loop {
+                    let permit = match window.clone().acquire_owned().await {
+                        Ok(permit) => Arc::new(permit),
+                        Err(_) => return,
+                    };
+                    let start = next_version.fetch_add(fetch_batch_size as u64, Ordering::SeqCst);
+                    let end = start + fetch_batch_size as u64;
+                    let end = match end_version {
+                        Some(end_version) if start >= end_version => return,
+                        Some(end_version) => end.min(end_version),
+                        None => end,
+                    };
+                    let mut version = start;
+                    // Near the head of the chain the node may only have part of the range,
+                    // so hand out what it has rather than waiting for the rest
+                    while version < end {
+                        let transactions =
+                            fetcher.fetch_batch(version, (end - version) as u16).await;
+                        let count = transactions.len() as u64;
+                        let batch = Batch {
+                            start: version,
+                            transactions,
+                            permit: permit.clone(),
+                        };
+                        if batch_sender.send(batch).await.is_err() {
+                            return;
+                        }
+                        version += count;
+                    }
+                }
+            });
+        }
+        drop(batch_sender);
+
+        let processors = self.tailer.processors().to_vec();
+        let mut processor_senders = vec![];
+        for (index, processor) in processors.iter().cloned().enumerate() {
+            let (sender, mut receiver) = mpsc::channel::<ProcessorBatch>(channel_size);
+            processor_senders.push(sender);
+            let tailer = self.tailer.clone();
+            let done_sender = done_sender.clone();
+            tokio::task::spawn(async move {
+                while let Some((end, transactions, _permit)) = receiver.recv().await {
+                    // Failures are recorded in `processor_statuses` by the processor
+                    let _ = tailer
+                        .run_processor(&processor, transactions.as_ref().clone())
+                        .await;
+                    if done_sender.send((index, end)).is_err() {
+                        return;
+                    }
+                }
+            });
+        }
+
+        // Hands the batches to every processor task in version order
+        tokio::task::spawn(async move {
+            let mut buffer = OrderedBuffer::new(starting_version);
+            while let Some(batch) = batch_receiver.recv().await {
+                buffer.push(batch.start, batch.transactions.len() as u64, batch);
+                while let Some(batch) = buffer.pop() {
+                    let end = batch.start + batch.transactions.len() as u64;
+                    let transactions: Vec<Arc<Transaction>> = batch
+                        .transactions
+                        .into_iter()
+                        .map(|txn| remove_null_bytes_from_txn(Arc::new(txn)))
+                        .collect();
+                    let transactions = Arc::new(transactions);
+                    for sender in &processor_senders {
+                        let message = (end, transactions.clone(), batch.permit.clone());
+                        if sender.send(message).await.is_err() {
+                            return;
+                        }
+                    }
+                    if processor_senders.is_empty() {
+                        let _ = done_sender.send((0, end));
+                    }
+                }
+            }
+        });
+
+        info!(
+            "Indexing pipeline started from version {} with {:?}",
+            starting_version, self.config
+        );
+        // The version each processor has processed up to, or the version every batch has been
+        // handed out up to if there are no processors
+        let mut processor_versions = vec![starting_version; processors.len().max(1)];
+        self.processed_version
+            .store(starting_version, Ordering::SeqCst);
+        NEXT_VERSION_TO_PROCESS.set(starting_version as i64);
+        let mut processed = starting_version;
+        let mut base = Self::emit_base(starting_version, emit_every);
+        while let Some((index, end)) = done_receiver.recv().await {
+            processor_versions[index] = end;
+            processed = processor_versions.iter().copied().min().unwrap();
+            self.processed_version.store(processed, Ordering::SeqCst);
+            NEXT_VERSION_TO_PROCESS.set(processed as i64);
+            let new_base = Self::emit_base(processed, emit_every);
+            if base != new_base {
+                base = new_base;
+                info!("Indexer has processed {} versions", processed);
+            }
+        }
+        match end_version {
+            Some(end_version) if processed >= end_version => info!(
+                "Indexing pipeline processed every version up to {}",
+                end_version
+            ),
+            _ => error!(
+                "All processor tasks exited, indexing stopped at version {}",
+                processed
+            ),
+        }
+    }
+
+    fn emit_base(version: u64, emit_every: usize) -> u64 {
+        if emit_every == 0 {
+            0
+        } else {
+            version / emit_every as u64
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_ordered_buffer_waits_for_gaps() {
+        let mut buffer = OrderedBuffer::new(100);
+        buffer.push(110, 10, "b");
+        buffer.push(120, 5, "c");
+        assert_eq!(buffer.pop(), None);
+        buffer.push(100, 10, "a");
+        assert_eq!(buffer.pop(), Some("a"));
+        assert_eq!(buffer.pop(), Some("b"));
+        assert_eq!(buffer.pop(), Some("c"));
+        assert_eq!(buffer.pop(), None);
+        buffer.push(125, 5, "d");
+        assert_eq!(buffer.pop(), Some("d"));
+        assert_eq!(buffer.next_version(), 130);
+    }
+}
@@ -0,0 +1,259 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    account_snapshot_processor::{AccountSnapshotConfig, AccountSnapshotProcessor},
+    coin_processor::CoinTransactionProcessor,
+    database::DbPool,
+    default_processor::DefaultTransactionProcessor,
+    indexer::{
+        fetcher::TransactionSource, retention::RetentionPolicy, sink::SinkConfig,
+        start_strategy::StartStrategy, transaction_processor::TransactionProcessor,
+    },
+    token_processor::TokenTransactionProcessor,
+    write_set_processor::WriteSetTransactionProcessor,
+};
+use anyhow::{bail, Context};
+use serde::Deserialize;
+use std::{collections::BTreeMap, path::Path, sync::Arc};
+
+/// Builds a processor from the connection pool, where transactions are fetched from and its own section of the config
+pub type ProcessorBuilder = fn(
+    DbPool,
+    &TransactionSource,
+    &ProcessorConfig,
+) -> anyhow::Result<Arc<dyn TransactionProcessor>>;
+
+/// Configuration of a single processor, keyed by its name in `IndexerConfig`
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct ProcessorConfig {
+    /// Overrides whether the processor runs. If unset, the processor's default is used
+    pub enabled: Option<bool>,
+    /// Processor specific settings, handed to the processor's builder as is
+    pub options: serde_json::Value,
+}
+
+/// The indexer config file, e.g.
+///
+/// ```yaml
+/// start_strategy: head_minus:1000
+/// processors:
+///   default_processor:
+///     enabled: true
+///   token_processor:
+///     enabled: false
+/// sinks:
+///   - type: webhook
+///     url: http://localhost:8000/indexed
+/// ```
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct IndexerConfig {
+    /// Where to start fetching from. If unset, `latest`
+    pub start_strategy: Option<StartStrategy>,
+    pub processors: BTreeMap<String, ProcessorConfig>,
+    /// Where processors publish what they derive, in addition to Postgres
+    pub sinks: Vec<SinkConfig>,
+    /// How long the rows of each table are kept, keyed by table. Tables without a policy are kept forever
+    pub retention: BTreeMap<String, RetentionPolicy>,
+}
+
+impl IndexerConfig {
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read indexer config {:?}", path))?;
+        serde_yaml::from_str(&contents)
+            .with_context(|| format!("Failed to parse indexer config {:?}", path))
+    }
+
+    /// Enables or disables a processor, overriding what the config file says
+    pub fn set_enabled(&mut self, name: &str, enabled: bool) {
+        self.processors.entry(name.to_string()).or_default().enabled = Some(enabled);
+    }
+}
+
+struct RegisteredProcessor {
+    enabled_by_default: bool,
+    builder: ProcessorBuilder,
+}
+
+/// All processors the indexer knows how to run, keyed by name. The name must be
+/// the one the processor reports from `TransactionProcessor::name`, as that is
+/// what its progress is recorded under.
+#[derive(Default)]
+pub struct ProcessorRegistry {
+    processors: BTreeMap<&'static str, RegisteredProcessor>,
+}
+
+impl ProcessorRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The registry of the processors shipped with the indexer
+    pub fn with_builtin_processors() -> Self {
+        let mut registry = Self::new();
+        registry.register("default_processor", true, |pool, _, _| {
+            Ok(Arc::new(DefaultTransactionProcessor::new(pool)))
+        });
+        registry.register("token_processor", false, |pool, _, _| {
+            Ok(Arc::new(TokenTransactionProcessor::new(pool)))
+        });
+        registry.register("coin_processor", false, |pool, _, _| {
+            Ok(Arc::new(CoinTransactionProcessor::new(pool)))
+        });
+        registry.register("write_set_processor", false, |pool, _, _| {
+            Ok(Arc::new(WriteSetTransactionProcessor::new(pool)))
+        });
+        registry.register(
+            "account_snapshot_processor",
+            false,
+            |pool, source, config| {
+                let options: AccountSnapshotConfig = serde_json::from_value(config.options.clone())
+                    .context("Invalid options, expected `accounts` and optionally `interval`")?;
+                Ok(Arc::new(AccountSnapshotProcessor::new(
+                    pool, source, options,
+                )?))
+            },
+        );
+        registry
+    }
+
+    pub fn register(
+        &mut self,
+        name: &'static str,
+        enabled_by_default: bool,
+        builder: ProcessorBuilder,
+    ) {
+        let previous = self.processors.insert(
+            name,
+            RegisteredProcessor {
+                enabled_by_default,
+                builder,
+            },
+        );
+        assert!(previous.is_none(), "Processor {} registered twice", name);
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.processors.keys().copied()
+    }
+
+    /// Builds a single processor with its section of `config`, whether or not it is enabled
+    pub fn build(
+        &self,
+        name: &str,
+        connection_pool: DbPool,
+        source: &TransactionSource,
+        config: &IndexerConfig,
+    ) -> anyhow::Result<Arc<dyn TransactionProcessor>> {
+        let (name, registered) = match self.processors.get_key_value(name) {
+            Some(entry) => entry,
+            None => bail!(
+                "Unknown processor {}, expected one of: {}",
+                name,
+                self.names().collect::<Vec<_>>().join(", ")
+            ),
+        };
+        let default_config = ProcessorConfig::default();
+        let processor_config = config.processors.get(*name).unwrap_or(&default_config);
+        let processor = (registered.builder)(connection_pool, source, processor_config)
+            .with_context(|| format!("Failed to build processor {}", name))?;
+        assert_eq!(
+            processor.name(),
+            *name,
+            "Processor registered under a different name than it reports"
+        );
+        Ok(processor)
+    }
+
+    /// Builds every processor enabled by `config`, or enabled by default and not
+    /// disabled by `config`. Fails if `config` names a processor that doesn't exist.
+    pub fn build_enabled(
+        &self,
+        connection_pool: DbPool,
+        source: &TransactionSource,
+        config: &IndexerConfig,
+    ) -> anyhow::Result<Vec<Arc<dyn TransactionProcessor>>> {
+        for name in config.processors.keys() {
+            if !self.processors.contains_key(name.as_str()) {
+                bail!(
+                    "Unknown processor {}, expected one of: {}",
+                    name,
+                    self.names().collect::<Vec<_>>().join(", ")
+                );
+            }
+        }
+
+        let default_config = ProcessorConfig::default();
+        let mut enabled = vec![];
+        for (name, registered) in &self.processors {
+            let processor_config = config.processors.get(*name).unwrap_or(&default_config);
+            if !processor_config
+                .enabled
+                .unwrap_or(registered.enabled_by_default)
+            {
+                continue;
+            }
+            enabled.push(self.build(name, connection_pool.clone(), source, config)?);
+        }
+        Ok(enabled)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_config() {
+        let config: IndexerConfig = serde_yaml::from_str(
+            r#"
+processors:
+  default_processor:
+    enabled: false
+  token_processor:
+    enabled: true
+    options:
+      fetch_metadata: false
+"#,
+        )
+        .unwrap();
+        assert_eq!(
+            config.processors["default_processor"],
+            ProcessorConfig {
+                enabled: Some(false),
+                options: serde_json::Value::Null,
+            }
+        );
+        assert_eq!(config.processors["token_processor"].enabled, Some(true));
+        assert_eq!(
+            config.processors["token_processor"].options["fetch_metadata"],
+            serde_json::Value::Bool(false)
+        );
+    }
+
+    #[test]
+    fn test_set_enabled_overrides_config() {
+        let mut config = IndexerConfig::default();
+        config.set_enabled("token_processor", true);
+        config.set_enabled("token_processor", false);
+        assert_eq!(config.processors["token_processor"].enabled, Some(false));
+    }
+
+    #[test]
+    fn test_builtin_processor_names() {
+        let registry = ProcessorRegistry::with_builtin_processors();
+        assert_eq!(
+            registry.names().collect::<Vec<_>>(),
+            vec![
+                "account_snapshot_processor",
+                "coin_processor",
+                "default_processor",
+                "token_processor",
+                "write_set_processor"
+            ]
+        );
+    }
+}
@@ -0,0 +1,106 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    database::{delete_chain_rows, DbPool},
+    indexer::{
+        fetcher::TransactionFetcher,
+        pipeline::{Pipeline, PipelineConfig},
+        tailer::Tailer,
+        transaction_processor::TransactionProcessor,
+    },
+    models::processor_version::ProcessorVersion,
+    schema::processor_statuses::dsl,
+};
+use anyhow::{bail, Context};
+use aptos_logger::{info, warn};
+use diesel::{prelude::*, Connection, RunQueryDsl};
+use std::sync::Arc;
+
+/// Deletes everything `processor` has written: the rows of its tables and its statuses
+fn wipe_processor(
+    connection_pool: &DbPool,
+    processor: &Arc<dyn TransactionProcessor>,
+) -> anyhow::Result<()> {
+    if processor.tables().is_empty() {
+        bail!(
+            "Processor {} doesn't list the tables it writes to, so it can't be reindexed",
+            processor.name()
+        );
+    }
+    let conn = connection_pool.get()?;
+    conn.transaction::<(), diesel::result::Error, _>(|| {
+        delete_chain_rows(&conn, processor.tables())?;
+        diesel::delete(dsl::processor_statuses.filter(dsl::name.eq(processor.name())))
+            .execute(&conn)?;
+        Ok(())
+    })
+    .with_context(|| {
+        format!(
+            "Could not delete the rows of processor {}",
+            processor.name()
+        )
+    })?;
+    warn!(
+        "Deleted every row of processor {} from {}",
+        processor.name(),
+        processor.tables().join(", ")
+    );
+    Ok(())
+}
+
+/// Deletes everything the processors of `tailer` have written, then processes history again with them, from
+/// `from_version` up to the node's latest version when the reindex started. Once done, each processor's current
+/// schema version is recorded. Rows below `from_version` are gone for good, so anything other than 0 only makes
+/// sense for processors with nothing to derive from earlier versions.
+///
+/// The reindexed records aren't published to sinks, which have already received them.
+pub async fn reindex(
+    tailer: Tailer,
+    connection_pool: DbPool,
+    from_version: u64,
+    config: PipelineConfig,
+    emit_every: usize,
+) -> anyhow::Result<()> {
+    for processor in tailer.processors() {
+        wipe_processor(&connection_pool, processor)?;
+    }
+
+    let head = TransactionFetcher::from_source(tailer.source().clone(), None)
+        .fetch_ledger_version()
+        .await
+        .context("Could not fetch the latest version of the node")?;
+    info!(
+        "Reindexing versions {} to {} with {}",
+        from_version,
+        head,
+        tailer
+            .processors()
+            .iter()
+            .map(|processor| processor.name())
+            .collect::<Vec<_>>()
+            .join(", ")
+    );
+    Pipeline::new(tailer.clone(), config)
+        .run_range(from_version, Some(head + 1), emit_every)
+        .await;
+
+    let conn = connection_pool.get()?;
+    for processor in tailer.processors() {
+        let failed = processor.get_error_versions().len();
+        if failed > 0 {
+            warn!(
+                "Processor {} failed on {} versions while reindexing, which will be retried once indexing resumes",
+                processor.name(),
+                failed
+            );
+        }
+        ProcessorVersion::new(processor.name(), processor.schema_version()).upsert(&conn)?;
+        info!(
+            "Reindexed processor {} at schema version {}",
+            processor.name(),
+            processor.schema_version()
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,450 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    counters::PRUNED_ROWS,
+    database::{DbPool, DbPoolConnection},
+};
+use anyhow::{bail, Context};
+use aptos_logger::{info, warn};
+use chrono::NaiveDateTime;
+use diesel::{
+    sql_types::{BigInt, Nullable, Timestamp},
+    Connection, RunQueryDsl,
+};
+use serde::Deserialize;
+use std::{collections::BTreeMap, time::Duration};
+
+/// Rows deleted per statement, so pruning a large backlog doesn't hold locks for long
+const PRUNE_BATCH_SIZE: i64 = 10_000;
+
+/// How long a table's rows are kept, e.g. in the indexer config file
+///
+/// ```yaml
+/// retention:
+///   events:
+///     max_age_days: 30
+///   write_set_changes:
+///     keep_versions: 1000000
+///     archive: true
+/// ```
+///
+/// Rows older than either bound are pruned. Tables without a policy are kept forever.
+///
+/// Tables referring to `transactions` have to be pruned at least as much as it is, or deleting the transactions
+/// would break their foreign keys: a policy for `transactions` requires one as strict for each of those tables.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct RetentionPolicy {
+    /// Prune rows of transactions committed more than this many days ago
+    pub max_age_days: Option<u64>,
+    /// Prune rows of versions more than this many versions below the latest processed version
+    pub keep_versions: Option<u64>,
+    /// Move pruned rows to `<table>_archive` instead of deleting them
+    pub archive: bool,
+}
+
+/// How the rows of a table map to versions
+#[derive(Clone, Copy, Debug)]
+enum VersionColumn {
+    /// The table has a column holding the version
+    Version(&'static str),
+    /// The table has a column holding the hash of the transaction, to look up in `transactions`
+    TransactionHash(&'static str),
+}
+
+/// The tables retention policies can apply to, those holding history. Tables looking up versions in `transactions`
+/// come before it, so they are pruned while the transactions they refer to are still there.
+const PRUNABLE_TABLES: &[(&str, VersionColumn)] = &[
+    ("events", VersionColumn::TransactionHash("transaction_hash")),
+    (
+        "write_set_changes",
+        VersionColumn::TransactionHash("transaction_hash"),
+    ),
+    (
+        "payloads",
+        VersionColumn::TransactionHash("transaction_hash"),
+    ),
+    ("user_transactions", VersionColumn::TransactionHash("hash")),
+    (
+        "block_metadata_transactions",
+        VersionColumn::TransactionHash("hash"),
+    ),
+    (
+        "token_activities",
+        VersionColumn::TransactionHash("transaction_hash"),
+    ),
+    (
+        "coin_activities",
+        VersionColumn::Version("transaction_version"),
+    ),
+    (
+        "resource_changes",
+        VersionColumn::Version("transaction_version"),
+    ),
+    (
+        "account_snapshots",
+        VersionColumn::Version("snapshot_version"),
+    ),
+    ("processor_statuses", VersionColumn::Version("version")),
+    ("transactions", VersionColumn::Version("version")),
+];
+
+/// Tables holding the current state of accounts and tokens, which is kept however old the transaction that last
+/// changed it
+const CURRENT_STATE_TABLES: &[&str] = &[
+    "coin_balances",
+    "collections",
+    "tokens",
+    "ownerships",
+    "metadatas",
+];
+
+/// The tables with a foreign key to `transactions`
+const TRANSACTION_DEPENDENT_TABLES: &[&str] = &[
+    "events",
+    "write_set_changes",
+    "payloads",
+    "user_transactions",
+    "block_metadata_transactions",
+    "token_activities",
+];
+
+/// The condition selecting the rows of `table` to prune. `$1` is the lowest version to keep
+fn prune_condition(table: &str, version_column: VersionColumn) -> String {
+    match version_column {
+        VersionColumn::Version(column) => format!("{} < $1", column),
+        VersionColumn::TransactionHash(column) => format!(
+            "{}.{} IN (SELECT hash FROM transactions WHERE version < $1)",
+            table, column
+        ),
+    }
+}
+
+/// Whether `policy` prunes at least every row `than` prunes, comparing versions and ages
+fn is_as_strict(policy: &RetentionPolicy, than: &RetentionPolicy) -> bool {
+    let as_strict = |bound: Option<u64>, than: Option<u64>| match (bound, than) {
+        (_, None) => true,
+        (Some(bound), Some(than)) => bound <= than,
+        (None, Some(_)) => false,
+    };
+    as_strict(policy.keep_versions, than.keep_versions)
+        && as_strict(policy.max_age_days, than.max_age_days)
+}
+
+#[derive(QueryableByName)]
+struct FirstVersion {
+    #[sql_type = "Nullable<BigInt>"]
+    version: Option<i64>,
+}
+
+/// The version of the first block committed at or after `since`, below which every transaction was committed
+/// before it. `None` if there's no such block yet, or if blocks before `since` have been pruned, in which case
+/// the boundary can't be told.
+fn first_version_since(
+    conn: &DbPoolConnection,
+    since: NaiveDateTime,
+) -> anyhow::Result<Option<i64>> {
+    let first_version = diesel::sql_query(
+        "SELECT CASE WHEN EXISTS (SELECT 1 FROM block_metadata_transactions WHERE \"timestamp\" < $1) THEN ( \
+           SELECT t.version FROM block_metadata_transactions b JOIN transactions t ON t.hash = b.hash \
+           WHERE b.\"timestamp\" >= $1 ORDER BY b.\"timestamp\" LIMIT 1 \
+         ) END AS version",
+    )
+    .bind::<Timestamp, _>(since)
+    .get_result::<FirstVersion>(conn)?;
+    Ok(first_version.version)
+}
+
+/// Creates `<table>_archive`, with the columns of `table`, unless it already exists
+#[cfg(not(feature = "sqlite"))]
+fn create_archive_table(conn: &DbPoolConnection, table: &str) -> diesel::QueryResult<usize> {
+    conn.execute(&format!(
+        "CREATE TABLE IF NOT EXISTS {table}_archive (LIKE {table} INCLUDING DEFAULTS)",
+        table = table
+    ))
+}
+
+/// Creates `<table>_archive`, with the columns of `table`, unless it already exists
+#[cfg(feature = "sqlite")]
+fn create_archive_table(conn: &DbPoolConnection, table: &str) -> diesel::QueryResult<usize> {
+    conn.execute(&format!(
+        "CREATE TABLE IF NOT EXISTS {table}_archive AS SELECT * FROM {table} WHERE 0",
+        table = table
+    ))
+}
+
+/// Prunes up to `PRUNE_BATCH_SIZE` rows of `table` matching `condition`, moving them to the archive if `archive`
+/// is set. Returns how many rows were pruned.
+#[cfg(not(feature = "sqlite"))]
+fn prune_batch(
+    conn: &DbPoolConnection,
+    table: &str,
+    condition: &str,
+    archive: bool,
+    min_version: i64,
+) -> diesel::QueryResult<usize> {
+    let query = if archive {
+        format!(
+            "WITH pruned AS ( \
+               DELETE FROM {table} WHERE ctid IN (SELECT ctid FROM {table} WHERE {condition} LIMIT {limit}) \
+               RETURNING * \
+             ) INSERT INTO {table}_archive SELECT * FROM pruned",
+            table = table,
+            condition = condition,
+            limit = PRUNE_BATCH_SIZE
+        )
+    } else {
+        format!(
+            "DELETE FROM {table} WHERE ctid IN (SELECT ctid FROM {table} WHERE {condition} LIMIT {limit})",
+            table = table,
+            condition = condition,
+            limit = PRUNE_BATCH_SIZE
+        )
+    };
+    diesel::sql_query(&query)
+        .bind::<BigInt, _>(min_version)
+        .execute(conn)
+}
+
+/// Prunes up to `PRUNE_BATCH_SIZE` rows of `table` matching `condition`, moving them to the archive if `archive`
+/// is set. Returns how many rows were pruned. SQLite can't return deleted rows, so archived rows are copied first,
+/// in the same transaction.
+#[cfg(feature = "sqlite")]
+fn prune_batch(
+    conn: &DbPoolConnection,
+    table: &str,
+    condition: &str,
+    archive: bool,
+    min_version: i64,
+) -> diesel::QueryResult<usize> {
+    let batch = format!(
+        "SELECT rowid FROM {table} WHERE {condition} ORDER BY rowid LIMIT {limit}",
+        table = table,
+        condition = condition,
+        limit = PRUNE_BATCH_SIZE
+    );
+    conn.transaction(|| {
+        if archive {
+            diesel::sql_query(format!(
+                "INSERT INTO {table}_archive SELECT * FROM {table} WHERE rowid IN ({batch})",
+                table = table,
+                batch = batch
+            ))
+            .bind::<BigInt, _>(min_version)
+            .execute(conn)?;
+        }
+        diesel::sql_query(format!(
+            "DELETE FROM {table} WHERE rowid IN ({batch})",
+            table = table,
+            batch = batch
+        ))
+        .bind::<BigInt, _>(min_version)
+        .execute(conn)
+    })
+}
+
+/// Periodically prunes indexed rows according to each table's `RetentionPolicy`, so the database doesn't grow
+/// without bound
+pub struct Retention {
+    connection_pool: DbPool,
+    policies: BTreeMap<String, RetentionPolicy>,
+}
+
+impl Retention {
+    pub fn new(
+        connection_pool: DbPool,
+        policies: BTreeMap<String, RetentionPolicy>,
+    ) -> anyhow::Result<Self> {
+        for (table, policy) in &policies {
+            if CURRENT_STATE_TABLES.contains(&table.as_str()) {
+                bail!(
+                    "Table {} holds current state rather than history, it can't have a retention policy",
+                    table
+                );
+            }
+            if !PRUNABLE_TABLES.iter().any(|(name, _)| name == table) {
+                bail!(
+                    "No retention policy can be set for table {}, expected one of {}",
+                    table,
+                    PRUNABLE_TABLES
+                        .iter()
+                        .map(|(name, _)| *name)
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                );
+            }
+        }
+        if let Some(transactions_policy) = policies.get("transactions") {
+            for table in TRANSACTION_DEPENDENT_TABLES {
+                let as_strict = policies
+                    .get(*table)
+                    .map_or(false, |policy| is_as_strict(policy, transactions_policy));
+                if !as_strict {
+                    bail!(
+                        "Table {} refers to transactions, so it needs a retention policy at least as strict as the \
+                         one of transactions",
+                        table
+                    );
+                }
+            }
+        }
+        Ok(Self {
+            connection_pool,
+            policies,
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.policies.is_empty()
+    }
+
+    /// Prunes every `interval`, forever
+    pub async fn run(&self, interval: Duration) {
+        loop {
+            if let Err(err) = self.prune() {
+                warn!("Could not prune indexed rows: {:?}", err);
+            }
+            tokio::time::sleep(interval).await;
+        }
+    }
+
+    /// Prunes each table with a retention policy
+    pub fn prune(&self) -> anyhow::Result<()> {
+        let conn = self.connection_pool.get()?;
+        let latest_version = diesel::select(diesel::dsl::sql::<Nullable<BigInt>>(
+            "(SELECT MAX(version) FROM processor_statuses WHERE success)",
+        ))
+        .get_result::<Option<i64>>(&conn)?;
+        let now = chrono::Utc::now().naive_utc();
+
+        for (table, version_column) in PRUNABLE_TABLES {
+            let policy = match self.policies.get(*table) {
+                Some(policy) => policy,
+                None => continue,
+            };
+            let min_version_by_age = match policy.max_age_days {
+                Some(days) => {
+                    first_version_since(&conn, now - chrono::Duration::days(days as i64))?
+                }
+                None => None,
+            };
+            let min_version_by_count = policy
+                .keep_versions
+                .zip(latest_version)
+                .map(|(keep_versions, latest_version)| latest_version - keep_versions as i64);
+            // Rows are pruned if they are too old by either bound
+            let min_version = match min_version_by_age.max(min_version_by_count) {
+                Some(min_version) => min_version,
+                None => continue,
+            };
+
+            if policy.archive {
+                create_archive_table(&conn, table)?;
+            }
+            let condition = prune_condition(table, *version_column);
+
+            let mut pruned = 0;
+            loop {
+                let rows = prune_batch(&conn, table, &condition, policy.archive, min_version)
+                    .with_context(|| format!("Could not prune table {}", table))?;
+                pruned += rows;
+                PRUNED_ROWS.with_label_values(&[table]).inc_by(rows as u64);
+                if (rows as i64) < PRUNE_BATCH_SIZE {
+                    break;
+                }
+            }
+            if pruned > 0 {
+                info!(
+                    "Pruned {} rows of {}{}",
+                    pruned,
+                    table,
+                    if policy.archive {
+                        " to the archive"
+                    } else {
+                        ""
+                    }
+                );
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_prune_condition() {
+        assert_eq!(
+            prune_condition(
+                "coin_activities",
+                VersionColumn::Version("transaction_version")
+            ),
+            "transaction_version < $1"
+        );
+        assert_eq!(
+            prune_condition("events", VersionColumn::TransactionHash("transaction_hash")),
+            "events.transaction_hash IN (SELECT hash FROM transactions WHERE version < $1)"
+        );
+    }
+
+    #[test]
+    fn test_transaction_dependent_tables_are_at_least_as_strict() {
+        let policy = |max_age_days, keep_versions| RetentionPolicy {
+            max_age_days,
+            keep_versions,
+            archive: false,
+        };
+        assert!(is_as_strict(
+            &policy(Some(7), None),
+            &policy(Some(30), None)
+        ));
+        assert!(is_as_strict(
+            &policy(Some(30), Some(10)),
+            &policy(Some(30), None)
+        ));
+        assert!(!is_as_strict(
+            &policy(None, Some(10)),
+            &policy(Some(30), None)
+        ));
+        assert!(!is_as_strict(
+            &policy(Some(60), None),
+            &policy(Some(30), None)
+        ));
+        assert!(!is_as_strict(
+            &policy(Some(7), Some(100)),
+            &policy(None, Some(10))
+        ));
+    }
+
+    #[test]
+    fn test_parse_retention_policies() {
+        let policies: BTreeMap<String, RetentionPolicy> = serde_yaml::from_str(
+            r#"
+events:
+  max_age_days: 30
+write_set_changes:
+  keep_versions: 1000000
+  archive: true
+"#,
+        )
+        .unwrap();
+        assert_eq!(
+            policies["events"],
+            RetentionPolicy {
+                max_age_days: Some(30),
+                ..RetentionPolicy::default()
+            }
+        );
+        assert_eq!(
+            policies["write_set_changes"],
+            RetentionPolicy {
+                max_age_days: None,
+                keep_versions: Some(1_000_000),
+                archive: true,
+            }
+        );
+    }
+}
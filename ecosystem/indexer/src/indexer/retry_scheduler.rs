@@ -0,0 +1,173 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    counters::{DEAD_LETTER_VERSIONS, FAILED_VERSIONS, RETRIED_VERSIONS},
+    indexer::{
+        tailer::{remove_null_bytes_from_txn, Tailer},
+        transaction_processor::TransactionProcessor,
+    },
+    models::processor_statuses::FailedVersion,
+};
+use aptos_logger::{debug, info};
+use std::{sync::Arc, time::Duration};
+
+/// How often, and how many times, a failed version is retried
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RetryPolicy {
+    /// Retries after which a version is given up on, and moved to the dead letter state
+    pub max_retries: i32,
+    /// How long to wait after a version first failed before retrying it. The wait doubles on each retry
+    pub base_delay: Duration,
+    /// The longest to wait between two retries
+    pub max_delay: Duration,
+}
+
+impl RetryPolicy {
+    /// How long to wait before retrying a version that has been retried `retry_count` times
+    pub fn delay(&self, retry_count: i32) -> Duration {
+        2u32.checked_pow(retry_count.max(0) as u32)
+            .and_then(|factor| self.base_delay.checked_mul(factor))
+            .map_or(self.max_delay, |delay| delay.min(self.max_delay))
+    }
+
+    pub fn is_exhausted(&self, failed_version: &FailedVersion) -> bool {
+        failed_version.retry_count >= self.max_retries
+    }
+
+    pub fn is_due(&self, failed_version: &FailedVersion, now: chrono::NaiveDateTime) -> bool {
+        chrono::Duration::from_std(self.delay(failed_version.retry_count))
+            .ok()
+            .and_then(|delay| failed_version.last_updated.checked_add_signed(delay))
+            .map_or(false, |due| due <= now)
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 5,
+            base_delay: Duration::from_secs(30),
+            max_delay: Duration::from_secs(3600),
+        }
+    }
+}
+
+/// Periodically retries the versions each processor failed on, backing off
+/// exponentially. Versions that keep failing are moved to the dead letter
+/// state once `max_retries` is reached, so one bad transaction isn't retried
+/// forever; they stay in `processor_statuses` to be looked into.
+pub struct RetryScheduler {
+    tailer: Tailer,
+    scan_interval: Duration,
+    policy: RetryPolicy,
+}
+
+impl RetryScheduler {
+    pub fn new(tailer: Tailer, scan_interval: Duration, policy: RetryPolicy) -> Self {
+        Self {
+            tailer,
+            scan_interval,
+            policy,
+        }
+    }
+
+    /// Scans every `scan_interval`, forever
+    pub async fn run(&self) {
+        loop {
+            tokio::time::sleep(self.scan_interval).await;
+            self.retry_due_versions().await;
+        }
+    }
+
+    /// Dead letters the failed versions that ran out of retries, and retries
+    /// those whose backoff has elapsed
+    pub async fn retry_due_versions(&self) {
+        for processor in self.tailer.processors() {
+            self.retry_processor(processor).await;
+        }
+    }
+
+    async fn retry_processor(&self, processor: &Arc<dyn TransactionProcessor>) {
+        let name = processor.name();
+        let (exhausted, failed_versions): (Vec<FailedVersion>, Vec<FailedVersion>) = processor
+            .get_failed_versions()
+            .into_iter()
+            .partition(|failed_version| self.policy.is_exhausted(failed_version));
+        if !exhausted.is_empty() {
+            let versions: Vec<u64> = exhausted
+                .iter()
+                .map(|failed_version| failed_version.version as u64)
+                .collect();
+            processor.mark_dead_letter(&versions);
+        }
+        FAILED_VERSIONS
+            .with_label_values(&[name])
+            .set(failed_versions.len() as i64);
+        DEAD_LETTER_VERSIONS
+            .with_label_values(&[name])
+            .set(processor.count_dead_letter_versions());
+
+        let now = chrono::Utc::now().naive_utc();
+        let due_versions: Vec<u64> = failed_versions
+            .iter()
+            .filter(|failed_version| self.policy.is_due(failed_version, now))
+            .map(|failed_version| failed_version.version as u64)
+            .collect();
+        if due_versions.is_empty() {
+            debug!(
+                "[{}] No versions due for a retry out of {} failed versions",
+                name,
+                failed_versions.len()
+            );
+            return;
+        }
+        info!(
+            "[{}] Retrying {} of {} failed versions",
+            name,
+            due_versions.len(),
+            failed_versions.len()
+        );
+        for version in due_versions {
+            let txn = remove_null_bytes_from_txn(self.tailer.get_txn(version).await);
+            // A failure bumps the version's retry count, and it is retried again once its backoff elapsed
+            let _ = self.tailer.run_processor(processor, vec![txn]).await;
+            RETRIED_VERSIONS.with_label_values(&[name]).inc();
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn failed_version(retry_count: i32, failed_secs_ago: i64) -> FailedVersion {
+        FailedVersion {
+            version: 1,
+            retry_count,
+            last_updated: chrono::Utc::now().naive_utc()
+                - chrono::Duration::seconds(failed_secs_ago),
+        }
+    }
+
+    #[test]
+    fn test_retry_policy() {
+        let policy = RetryPolicy {
+            max_retries: 3,
+            base_delay: Duration::from_secs(10),
+            max_delay: Duration::from_secs(60),
+        };
+        assert_eq!(policy.delay(0), Duration::from_secs(10));
+        assert_eq!(policy.delay(2), Duration::from_secs(40));
+        assert_eq!(policy.delay(3), Duration::from_secs(60));
+        assert_eq!(policy.delay(100), Duration::from_secs(60));
+
+        let now = chrono::Utc::now().naive_utc();
+        assert!(!policy.is_due(&failed_version(0, 5), now));
+        assert!(policy.is_due(&failed_version(0, 15), now));
+        assert!(!policy.is_due(&failed_version(2, 15), now));
+
+        assert!(!policy.is_exhausted(&failed_version(2, 0)));
+        assert!(policy.is_exhausted(&failed_version(3, 0)));
+    }
+}
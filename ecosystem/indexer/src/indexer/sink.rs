@@ -0,0 +1,301 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+//! Sinks publish what processors produce to systems other than Postgres, so
+//! downstream systems can react to it as it is indexed. Records are published
+//! once the batch they come from has been committed, at most once: a sink that
+//! fails to publish logs the error and drops the records.
+
+use crate::counters::{SINK_ERRORS, SINK_RECORDS};
+use anyhow::Context;
+use async_trait::async_trait;
+use reqwest_middleware::{ClientBuilder, ClientWithMiddleware};
+use reqwest_retry::{policies::ExponentialBackoff, RetryTransientMiddleware};
+use serde::{Deserialize, Serialize};
+use std::{fmt::Debug, sync::Arc};
+use url::Url;
+
+/// Something a processor derived from a transaction, e.g. a coin deposit
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct SinkRecord {
+    /// The processor that produced the record
+    pub processor: &'static str,
+    /// What the record is, e.g. "coin_activity"
+    pub record_type: &'static str,
+    pub version: u64,
+    pub data: serde_json::Value,
+}
+
+impl SinkRecord {
+    pub fn new<T: Serialize>(
+        processor: &'static str,
+        record_type: &'static str,
+        version: u64,
+        data: &T,
+    ) -> Self {
+        Self {
+            processor,
+            record_type,
+            version,
+            data: serde_json::to_value(data).expect("Records must serialize to JSON"),
+        }
+    }
+}
+
+#[async_trait]
+pub trait Sink: Send + Sync + Debug {
+    /// name of the sink, for logging and metrics
+    fn name(&self) -> &str;
+
+    /// Whether records of the given processor are published to this sink
+    fn accepts(&self, processor_name: &str) -> bool;
+
+    /// Publishes the records, in order
+    async fn publish(&self, records: &[SinkRecord]) -> anyhow::Result<()>;
+}
+
+/// Configuration of a sink, as listed under `sinks` in the indexer config file
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum SinkConfig {
+    /// POSTs each batch of records as a JSON array to `url`
+    Webhook {
+        url: String,
+        /// Only publish records of these processors. If unset, all are published
+        #[serde(default)]
+        processors: Option<Vec<String>>,
+    },
+    /// Produces each record as a JSON message to `topic`, keyed by version.
+    /// Requires the `kafka` feature
+    Kafka {
+        /// Comma separated list of brokers, ex: "localhost:9092"
+        brokers: String,
+        topic: String,
+        #[serde(default)]
+        processors: Option<Vec<String>>,
+    },
+}
+
+pub fn build_sinks(configs: &[SinkConfig]) -> anyhow::Result<Vec<Arc<dyn Sink>>> {
+    configs
+        .iter()
+        .map(|config| -> anyhow::Result<Arc<dyn Sink>> {
+            match config {
+                SinkConfig::Webhook { url, processors } => Ok(Arc::new(WebhookSink::new(
+                    url.parse()
+                        .with_context(|| format!("Invalid webhook url {}", url))?,
+                    processors.clone(),
+                ))),
+                #[cfg(feature = "kafka")]
+                SinkConfig::Kafka {
+                    brokers,
+                    topic,
+                    processors,
+                } => Ok(Arc::new(kafka::KafkaSink::new(
+                    brokers,
+                    topic.clone(),
+                    processors.clone(),
+                )?)),
+                #[cfg(not(feature = "kafka"))]
+                SinkConfig::Kafka { .. } => {
+                    anyhow::bail!(
+                        "Kafka sinks require building the indexer with the `kafka` feature"
+                    )
+                }
+            }
+        })
+        .collect()
+}
+
+/// Publishes the records to every sink that accepts their processor
+pub async fn publish_to_sinks(
+    sinks: &[Arc<dyn Sink>],
+    processor_name: &str,
+    records: &[SinkRecord],
+) {
+    if records.is_empty() {
+        return;
+    }
+    for sink in sinks.iter().filter(|sink| sink.accepts(processor_name)) {
+        match sink.publish(records).await {
+            Ok(()) => SINK_RECORDS
+                .with_label_values(&[sink.name()])
+                .inc_by(records.len() as u64),
+            Err(err) => {
+                SINK_ERRORS.with_label_values(&[sink.name()]).inc();
+                aptos_logger::error!(
+                    "[{}] Could not publish {} records of {}, dropping them. Err: {:?}",
+                    sink.name(),
+                    records.len(),
+                    processor_name,
+                    err
+                );
+            }
+        }
+    }
+}
+
+fn accepts(processors: &Option<Vec<String>>, processor_name: &str) -> bool {
+    processors.as_ref().map_or(true, |processors| {
+        processors.iter().any(|name| name == processor_name)
+    })
+}
+
+#[derive(Debug)]
+pub struct WebhookSink {
+    name: String,
+    url: Url,
+    processors: Option<Vec<String>>,
+    client: ClientWithMiddleware,
+}
+
+impl WebhookSink {
+    pub fn new(url: Url, processors: Option<Vec<String>>) -> Self {
+        let retry_policy = ExponentialBackoff::builder().build_with_max_retries(3);
+        Self {
+            name: format!("webhook {}", url),
+            url,
+            processors,
+            client: ClientBuilder::new(reqwest::Client::new())
+                .with(RetryTransientMiddleware::new_with_policy(retry_policy))
+                .build(),
+        }
+    }
+}
+
+#[async_trait]
+impl Sink for WebhookSink {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn accepts(&self, processor_name: &str) -> bool {
+        accepts(&self.processors, processor_name)
+    }
+
+    async fn publish(&self, records: &[SinkRecord]) -> anyhow::Result<()> {
+        self.client
+            .post(self.url.clone())
+            .json(records)
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+}
+
+#[cfg(feature = "kafka")]
+mod kafka {
+    use super::*;
+    use rdkafka::{
+        config::ClientConfig,
+        producer::{FutureProducer, FutureRecord},
+    };
+    use std::time::Duration;
+
+    /// How long a record may wait in the producer's queue when it is full
+    const QUEUE_TIMEOUT: Duration = Duration::from_secs(30);
+
+    pub struct KafkaSink {
+        name: String,
+        topic: String,
+        processors: Option<Vec<String>>,
+        producer: FutureProducer,
+    }
+
+    impl KafkaSink {
+        pub fn new(
+            brokers: &str,
+            topic: String,
+            processors: Option<Vec<String>>,
+        ) -> anyhow::Result<Self> {
+            let producer = ClientConfig::new()
+                .set("bootstrap.servers", brokers)
+                .set("message.timeout.ms", "30000")
+                .create()
+                .context("Could not create the Kafka producer")?;
+            Ok(Self {
+                name: format!("kafka {}", topic),
+                topic,
+                processors,
+                producer,
+            })
+        }
+    }
+
+    impl Debug for KafkaSink {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "KafkaSink {{ topic: {:?} }}", self.topic)
+        }
+    }
+
+    #[async_trait]
+    impl Sink for KafkaSink {
+        fn name(&self) -> &str {
+            &self.name
+        }
+
+        fn accepts(&self, processor_name: &str) -> bool {
+            accepts(&self.processors, processor_name)
+        }
+
+        async fn publish(&self, records: &[SinkRecord]) -> anyhow::Result<()> {
+            for record in records {
+                let key = record.version.to_string();
+                let payload = serde_json::to_vec(record)?;
+                self.producer
+                    .send(
+                        FutureRecord::to(&self.topic).key(&key).payload(&payload),
+                        QUEUE_TIMEOUT,
+                    )
+                    .await
+                    .map_err(|(err, _)| err)?;
+            }
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_sink_config() {
+        let configs: Vec<SinkConfig> = serde_yaml::from_str(
+            r#"
+- type: webhook
+  url: http://localhost:8000/hook
+  processors: [coin_processor]
+- type: kafka
+  brokers: localhost:9092
+  topic: indexer
+"#,
+        )
+        .unwrap();
+        assert_eq!(
+            configs,
+            vec![
+                SinkConfig::Webhook {
+                    url: "http://localhost:8000/hook".to_string(),
+                    processors: Some(vec!["coin_processor".to_string()]),
+                },
+                SinkConfig::Kafka {
+                    brokers: "localhost:9092".to_string(),
+                    topic: "indexer".to_string(),
+                    processors: None,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_webhook_accepts() {
+        let url: Url = "http://localhost:8000/hook".parse().unwrap();
+        let sink = WebhookSink::new(url.clone(), None);
+        assert!(sink.accepts("token_processor"));
+        let sink = WebhookSink::new(url, Some(vec!["coin_processor".to_string()]));
+        assert!(sink.accepts("coin_processor"));
+        assert!(!sink.accepts("token_processor"));
+    }
+}
@@ -0,0 +1,99 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::{bail, Context};
+use serde::Deserialize;
+use std::{convert::TryFrom, fmt, str::FromStr};
+
+/// Where the indexer starts fetching transactions from. Written the same way on
+/// the command line and in the config file: `genesis`, `latest`,
+/// `version:<version>` or `head_minus:<versions>`
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(try_from = "String")]
+pub enum StartStrategy {
+    /// From the first version
+    Genesis,
+    /// From where the processors left off
+    Latest,
+    /// From a given version
+    Version(u64),
+    /// From this many versions before the node's latest version
+    HeadMinus(u64),
+}
+
+impl Default for StartStrategy {
+    fn default() -> Self {
+        StartStrategy::Latest
+    }
+}
+
+impl FromStr for StartStrategy {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (strategy, argument) = match s.split_once(':') {
+            Some((strategy, argument)) => (strategy, Some(argument)),
+            None => (s, None),
+        };
+        let parse_argument = || -> anyhow::Result<u64> {
+            let argument =
+                argument.with_context(|| format!("Start strategy {} needs a number", strategy))?;
+            argument
+                .parse()
+                .with_context(|| format!("Invalid number {} in start strategy {}", argument, s))
+        };
+        Ok(match strategy {
+            "genesis" if argument.is_none() => StartStrategy::Genesis,
+            "latest" if argument.is_none() => StartStrategy::Latest,
+            "version" => StartStrategy::Version(parse_argument()?),
+            "head_minus" => StartStrategy::HeadMinus(parse_argument()?),
+            _ => bail!(
+                "Invalid start strategy {}, expected one of genesis, latest, version:<version> or head_minus:<versions>",
+                s
+            ),
+        })
+    }
+}
+
+impl TryFrom<String> for StartStrategy {
+    type Error = anyhow::Error;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl fmt::Display for StartStrategy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StartStrategy::Genesis => write!(f, "genesis"),
+            StartStrategy::Latest => write!(f, "latest"),
+            StartStrategy::Version(version) => write!(f, "version:{}", version),
+            StartStrategy::HeadMinus(versions) => write!(f, "head_minus:{}", versions),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_start_strategy() {
+        for strategy in [
+            StartStrategy::Genesis,
+            StartStrategy::Latest,
+            StartStrategy::Version(42),
+            StartStrategy::HeadMinus(1000),
+        ] {
+            assert_eq!(
+                strategy.to_string().parse::<StartStrategy>().unwrap(),
+                strategy
+            );
+        }
+        assert!("version".parse::<StartStrategy>().is_err());
+        assert!("head_minus:many".parse::<StartStrategy>().is_err());
+        assert!("genesis:1".parse::<StartStrategy>().is_err());
+        assert!("tip".parse::<StartStrategy>().is_err());
+    }
+}
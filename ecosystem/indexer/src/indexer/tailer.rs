@@ -1,20 +1,110 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
+#[cfg(not(feature = "sqlite"))]
+use crate::database::scoped_chain_id;
 use crate::{
-    database::PgDbPool,
+    database::{delete_chain_rows, DbPool, DbPoolConnection},
     indexer::{
-        errors::TransactionProcessingError, fetcher::TransactionFetcher,
-        processing_result::ProcessingResult, transaction_processor::TransactionProcessor,
+        errors::TransactionProcessingError,
+        fetcher::{TransactionFetcher, TransactionSource},
+        processing_result::ProcessingResult,
+        sink::{publish_to_sinks, Sink, SinkRecord},
+        start_strategy::StartStrategy,
+        transaction_processor::TransactionProcessor,
     },
+    models::{
+        indexer_start::IndexerStart, ledger_info::LedgerInfo, processor_version::ProcessorVersion,
+    },
+    schema::{indexer_starts, ledger_infos},
 };
-use aptos_logger::info;
+use anyhow::{bail, Context};
+use aptos_logger::{info, warn};
 use aptos_rest_client::Transaction;
+#[cfg(not(feature = "sqlite"))]
+use diesel::sql_types::BigInt;
+use diesel::{Connection, RunQueryDsl};
 use serde_json::Value;
 use std::{fmt::Debug, sync::Arc};
 use tokio::{sync::Mutex, task::JoinHandle};
 use url::{ParseError, Url};
 
+#[cfg(not(feature = "sqlite"))]
 diesel_migrations::embed_migrations!();
+// SQLite lacks much of what the Postgres migrations use, so it has its own
+#[cfg(feature = "sqlite")]
+diesel_migrations::embed_migrations!("sqlite_migrations");
+
+/// Key of the advisory lock held while running migrations, the ASCII of "indexer"
+#[cfg(not(feature = "sqlite"))]
+const MIGRATIONS_LOCK_ID: i64 = 0x69_6e_64_65_78_65_72;
+
+/// Every table holding indexed data, which is deleted when the node's chain was reset. Tables referencing
+/// `transactions` come after it.
+const INDEXED_TABLES: &[&str] = &[
+    "transactions",
+    "user_transactions",
+    "block_metadata_transactions",
+    "events",
+    "write_set_changes",
+    "payloads",
+    "processor_statuses",
+    "collections",
+    "tokens",
+    "ownerships",
+    "metadatas",
+    "token_activities",
+    "coin_balances",
+    "coin_activities",
+    "resource_changes",
+    "account_snapshots",
+    "ledger_infos",
+    "indexer_starts",
+    "processor_versions",
+];
+
+/// Makes sure the connections only see the rows of chain `chain_id`
+#[cfg(not(feature = "sqlite"))]
+fn check_chain_scope(conn: &DbPoolConnection, chain_id: u8) -> anyhow::Result<()> {
+    let scoped_chain_id = scoped_chain_id(conn)?;
+    if scoped_chain_id != Some(chain_id) {
+        bail!(
+            "The node is on chain id {}, but the connections to the database are scoped to chain id {:?}",
+            chain_id,
+            scoped_chain_id
+        );
+    }
+    let other_chains = LedgerInfo::get_other_chains(conn, chain_id)?;
+    if !other_chains.is_empty() {
+        bail!(
+            "The database also holds chain ids {:?}, which row level security should hide. Index with a role \
+             which is neither a superuser nor has BYPASSRLS, so chains don't overwrite each other.",
+            other_chains
+                .iter()
+                .map(|ledger_info| ledger_info.chain_id)
+                .collect::<Vec<_>>()
+        );
+    }
+    Ok(())
+}
+
+/// Makes sure the database holds no chain other than `chain_id`: a SQLite database has no row level security to
+/// keep chains apart, so it only ever holds one
+#[cfg(feature = "sqlite")]
+fn check_chain_scope(conn: &DbPoolConnection, chain_id: u8) -> anyhow::Result<()> {
+    let other_chains = LedgerInfo::get_other_chains(conn, chain_id)?;
+    if !other_chains.is_empty() {
+        bail!(
+            "The node is on chain id {}, but the database holds chain ids {:?}. A SQLite database holds a single \
+             chain, index this one into another file.",
+            chain_id,
+            other_chains
+                .iter()
+                .map(|ledger_info| ledger_info.chain_id)
+                .collect::<Vec<_>>()
+        );
+    }
+    Ok(())
+}
 
 pub fn string_null_byte_replacement(value: &mut str) -> String {
     value.replace('\u{0000}', "").replace("\\u0000", "")
@@ -51,42 +141,191 @@ pub fn remove_null_bytes_from_txn(txn: Arc<Transaction>) -> Arc<Transaction> {
 
 #[derive(Clone)]
 pub struct Tailer {
+    source: TransactionSource,
     transaction_fetcher: Arc<Mutex<TransactionFetcher>>,
     processors: Vec<Arc<dyn TransactionProcessor>>,
-    connection_pool: PgDbPool,
+    sinks: Vec<Arc<dyn Sink>>,
+    connection_pool: DbPool,
 }
 
 impl Tailer {
-    pub fn new(node_url: &str, connection_pool: PgDbPool) -> Result<Tailer, ParseError> {
+    pub fn new(node_url: &str, connection_pool: DbPool) -> Result<Tailer, ParseError> {
         let url = Url::parse(node_url)?;
-        let transaction_fetcher = TransactionFetcher::new(url, None);
-        Ok(Self {
+        Ok(Self::from_source(
+            TransactionSource::Node(url),
+            connection_pool,
+        ))
+    }
+
+    pub fn from_source(source: TransactionSource, connection_pool: DbPool) -> Tailer {
+        let transaction_fetcher = TransactionFetcher::from_source(source.clone(), None);
+        Self {
+            source,
             transaction_fetcher: Arc::new(Mutex::new(transaction_fetcher)),
             processors: vec![],
+            sinks: vec![],
             connection_pool,
-        })
+        }
+    }
+
+    /// Where the tailer fetches transactions from, for other tasks to fetch from the same place
+    pub fn source(&self) -> &TransactionSource {
+        &self.source
     }
 
+    /// Runs the migrations embedded in the binary which the database is missing. Replicas starting at the same time
+    /// take turns through an advisory lock, so only the first one runs them and the others find nothing left to run.
+    #[cfg(not(feature = "sqlite"))]
     pub fn run_migrations(&self) {
         info!("Running migrations...");
-        embedded_migrations::run_with_output(
-            &self
-                .connection_pool
-                .get()
-                .expect("Could not get connection for migrations"),
-            &mut std::io::stdout(),
-        )
-        .expect("migrations failed!");
+        let conn = self
+            .connection_pool
+            .get()
+            .expect("Could not get connection for migrations");
+        diesel::sql_query("SELECT pg_advisory_lock($1)")
+            .bind::<BigInt, _>(MIGRATIONS_LOCK_ID)
+            .execute(&conn)
+            .expect("Could not acquire the migrations lock");
+        let result = embedded_migrations::run_with_output(&conn, &mut std::io::stdout());
+        // The lock is held by the connection's session, which goes back to the pool rather than closing
+        diesel::sql_query("SELECT pg_advisory_unlock($1)")
+            .bind::<BigInt, _>(MIGRATIONS_LOCK_ID)
+            .execute(&conn)
+            .expect("Could not release the migrations lock");
+        result.expect("migrations failed!");
+        info!("Migrations complete!");
+    }
+
+    /// Runs the migrations embedded in the binary which the database is missing. Each migration runs in a
+    /// transaction, which SQLite only lets one connection write at a time, so replicas can't both run one.
+    #[cfg(feature = "sqlite")]
+    pub fn run_migrations(&self) {
+        info!("Running migrations...");
+        let conn = self
+            .connection_pool
+            .get()
+            .expect("Could not get connection for migrations");
+        embedded_migrations::run_with_output(&conn, &mut std::io::stdout())
+            .expect("migrations failed!");
         info!("Migrations complete!");
     }
 
+    /// Makes sure the database holds data from the chain the node is on, which is recorded on the first run.
+    /// The database may hold other chains too, which the connections of the pool, scoped to the node's chain,
+    /// don't see. If the node's chain was reset since it was recorded (its genesis changed), fails unless
+    /// `force_reset` is set, in which case the chain's indexed data is deleted and its new genesis recorded instead.
+    pub async fn check_chain_identity(&self, force_reset: bool) -> anyhow::Result<()> {
+        let (chain_id, genesis_hash) = self
+            .transaction_fetcher
+            .lock()
+            .await
+            .fetch_chain_identity()
+            .await
+            .context("Could not fetch the chain id and genesis transaction of the node")?;
+        let node_ledger_info = LedgerInfo::new(chain_id, genesis_hash);
+
+        let conn = self.connection_pool.get()?;
+        check_chain_scope(&conn, chain_id)?;
+        match LedgerInfo::get(&conn, chain_id)? {
+            Some(db_ledger_info) if db_ledger_info.is_same_chain(&node_ledger_info) => {
+                info!(
+                    "Node is on the indexed chain: chain id {}, genesis hash {}",
+                    node_ledger_info.chain_id, node_ledger_info.genesis_hash
+                );
+                return Ok(());
+            }
+            Some(db_ledger_info) if !force_reset => bail!(
+                "The database holds data from chain id {} with genesis hash {}, but the node's genesis hash is {}. \
+                 Restart with --force-reset to delete the chain's indexed data and index it from its new genesis.",
+                db_ledger_info.chain_id,
+                db_ledger_info.genesis_hash,
+                node_ledger_info.genesis_hash
+            ),
+            Some(db_ledger_info) => {
+                warn!(
+                    "Deleting all indexed data from chain id {} with genesis hash {}",
+                    db_ledger_info.chain_id, db_ledger_info.genesis_hash
+                );
+            }
+            None => {}
+        }
+
+        conn.transaction::<(), diesel::result::Error, _>(|| {
+            if force_reset {
+                delete_chain_rows(&conn, INDEXED_TABLES)?;
+            }
+            diesel::insert_into(ledger_infos::table)
+                .values(&node_ledger_info)
+                .execute(&conn)?;
+            Ok(())
+        })?;
+        info!(
+            "Recorded the indexed chain: chain id {}, genesis hash {}",
+            node_ledger_info.chain_id, node_ledger_info.genesis_hash
+        );
+        Ok(())
+    }
+
+    pub fn processors(&self) -> &[Arc<dyn TransactionProcessor>] {
+        &self.processors
+    }
+
     pub fn add_processor(&mut self, processor: Arc<dyn TransactionProcessor>) {
         info!("Adding processor to indexer: {}", processor.name());
         self.processors.push(processor);
     }
 
+    pub fn add_sink(&mut self, sink: Arc<dyn Sink>) {
+        info!("Adding sink to indexer: {}", sink.name());
+        self.sinks.push(sink);
+    }
+
+    /// Records the schema version of each processor the first time it runs. A processor whose schema version
+    /// differs from the recorded one wrote its rows with other logic than it has now, and should be reindexed.
+    pub fn check_processor_versions(&self) -> anyhow::Result<()> {
+        let conn = self.connection_pool.get()?;
+        for processor in &self.processors {
+            match ProcessorVersion::get(&conn, processor.name())? {
+                Some(recorded) if recorded.schema_version != processor.schema_version() => warn!(
+                    "Processor {} is at schema version {}, but its rows were written at schema version {}. \
+                     Run `reindex --processor {}` to write them again.",
+                    processor.name(),
+                    processor.schema_version(),
+                    recorded.schema_version,
+                    processor.name()
+                ),
+                Some(_) => {}
+                None => {
+                    ProcessorVersion::new(processor.name(), processor.schema_version())
+                        .upsert(&conn)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Runs a processor on a batch of consecutive transactions, then publishes the records it derived from them to
+    /// the sinks. Nothing is published for a batch that failed; it will be once its versions are retried.
+    pub async fn run_processor(
+        &self,
+        processor: &Arc<dyn TransactionProcessor>,
+        txns: Vec<Arc<Transaction>>,
+    ) -> Result<ProcessingResult, TransactionProcessingError> {
+        let res = processor
+            .process_transactions_with_status(txns.clone())
+            .await;
+        if res.is_ok() && !self.sinks.is_empty() {
+            let records: Vec<SinkRecord> = txns
+                .iter()
+                .flat_map(|txn| processor.sink_records(txn))
+                .collect();
+            publish_to_sinks(&self.sinks, processor.name(), &records).await;
+        }
+        res
+    }
+
     /// For all versions which have an `success=false` in the `processor_status` table, re-run them
-    /// TODO: also handle gaps in sequence numbers (pg query for this is super easy)
+    /// Gaps in versions are handled while indexing, by the `GapDetector`
     pub async fn handle_previous_errors(&self) {
         info!("Checking for previously errored versions...");
         let mut tasks = vec![];
@@ -107,11 +346,7 @@ impl Tailer {
                 let mut fixed = 0;
                 for version in errored_versions {
                     let txn = self2.get_txn(version).await;
-                    if processor2
-                        .process_transaction_with_status(txn)
-                        .await
-                        .is_ok()
-                    {
+                    if self2.run_processor(&processor2, vec![txn]).await.is_ok() {
                         fixed += 1;
                     };
                 }
@@ -147,6 +382,51 @@ impl Tailer {
         lowest
     }
 
+    /// Sets the version of the fetcher according to `strategy`. The strategy is recorded in the database, and a
+    /// strategy other than `latest` is only applied when it differs from the one recorded last: restarting with
+    /// the same strategy resumes from where the processors left off, as with `latest`.
+    pub async fn apply_start_strategy(&self, strategy: &StartStrategy) -> anyhow::Result<u64> {
+        let conn = self.connection_pool.get()?;
+        let recorded = IndexerStart::get_latest(&conn)?;
+        let is_recorded = recorded
+            .as_ref()
+            .map_or(false, |recorded| recorded.strategy == strategy.to_string());
+
+        let version = match strategy {
+            StartStrategy::Latest => self.set_fetcher_to_lowest_processor_version().await,
+            _ if is_recorded => {
+                info!(
+                    "Start strategy {} was already applied, resuming instead",
+                    strategy
+                );
+                self.set_fetcher_to_lowest_processor_version().await
+            }
+            StartStrategy::Genesis => self.set_fetcher_version(0).await,
+            StartStrategy::Version(version) => self.set_fetcher_version(*version).await,
+            StartStrategy::HeadMinus(versions) => {
+                let head = self
+                    .transaction_fetcher
+                    .lock()
+                    .await
+                    .fetch_ledger_version()
+                    .await
+                    .context("Could not fetch the latest version of the node")?;
+                self.set_fetcher_version(head.saturating_sub(*versions))
+                    .await
+            }
+        };
+        if !is_recorded {
+            diesel::insert_into(indexer_starts::table)
+                .values(&IndexerStart::new(strategy.to_string(), version))
+                .execute(&conn)?;
+            info!(
+                "Recorded start strategy {} at version {}",
+                strategy, version
+            );
+        }
+        Ok(version)
+    }
+
     pub async fn set_fetcher_version(&self, version: u64) -> u64 {
         self.transaction_fetcher.lock().await.set_version(version);
         aptos_logger::info!("Will start fetching from version {}", version);
@@ -185,17 +465,25 @@ impl Tailer {
     pub async fn process_transaction(
         &self,
         txn: Arc<Transaction>,
+    ) -> anyhow::Result<Vec<Result<ProcessingResult, TransactionProcessingError>>> {
+        self.process_transactions(vec![txn]).await
+    }
+
+    /// Runs every processor on a batch of consecutive transactions. Each processor commits the batch atomically,
+    /// see `TransactionProcessor::process_transactions`
+    pub async fn process_transactions(
+        &self,
+        txns: Vec<Arc<Transaction>>,
     ) -> anyhow::Result<Vec<Result<ProcessingResult, TransactionProcessingError>>> {
         let mut tasks = vec![];
-        let txn = remove_null_bytes_from_txn(txn.clone());
+        let txns: Vec<Arc<Transaction>> =
+            txns.into_iter().map(remove_null_bytes_from_txn).collect();
         for processor in &self.processors {
             let processor2 = processor.clone();
-            let txn2 = txn.clone();
-            let task = tokio::task::spawn(async move {
-                processor2
-                    .process_transaction_with_status(txn2.clone())
-                    .await
-            });
+            let self2 = self.clone();
+            let txns2 = txns.clone();
+            let task =
+                tokio::task::spawn(async move { self2.run_processor(&processor2, txns2).await });
             tasks.push(task);
         }
         let results = await_tasks(tasks).await;
@@ -233,27 +521,40 @@ pub async fn await_tasks<T: Debug>(tasks: Vec<JoinHandle<T>>) -> Vec<T> {
 mod test {
     use super::*;
     use crate::{
-        database::{new_db_pool, PgPoolConnection},
+        database::new_db_pool,
         default_processor::DefaultTransactionProcessor,
-        models::transactions::TransactionModel,
+        models::{
+            payload::Payload, processor_statuses::VersionGap, transactions::TransactionModel,
+        },
         token_processor::TokenTransactionProcessor,
     };
-    use diesel::Connection;
+    use diesel::{Connection, QueryDsl};
     use serde_json::json;
 
-    pub fn wipe_database(conn: &PgPoolConnection) {
+    /// The chain id of local testnets
+    const TEST_CHAIN_ID: u8 = 4;
+
+    pub fn wipe_database(conn: &DbPoolConnection) {
         for table in [
+            "account_snapshots",
+            "resource_changes",
+            "coin_activities",
+            "coin_balances",
             "metadatas",
             "ownerships",
             "token_activities",
             "tokens",
             "collections",
             "write_set_changes",
+            "payloads",
             "events",
             "user_transactions",
             "block_metadata_transactions",
             "transactions",
             "processor_statuses",
+            "ledger_infos",
+            "indexer_starts",
+            "processor_versions",
             "__diesel_schema_migrations",
         ] {
             conn.execute(&format!("DROP TABLE IF EXISTS {}", table))
@@ -261,10 +562,10 @@ mod test {
         }
     }
 
-    pub fn setup_indexer() -> anyhow::Result<(PgDbPool, Tailer)> {
+    pub fn setup_indexer() -> anyhow::Result<(DbPool, Tailer)> {
         let database_url = std::env::var("INDEXER_DATABASE_URL")
             .expect("must set 'INDEXER_DATABASE_URL' to run tests!");
-        let conn_pool = new_db_pool(database_url.as_str())?;
+        let conn_pool = new_db_pool(database_url.as_str(), TEST_CHAIN_ID)?;
         wipe_database(&conn_pool.get()?);
 
         let mut tailer = Tailer::new("http://fake-url.aptos.dev", conn_pool.clone())?;
@@ -277,6 +578,102 @@ mod test {
         Ok((conn_pool, tailer))
     }
 
+    #[tokio::test]
+    async fn test_get_gaps() {
+        if crate::should_skip_pg_tests() {
+            return;
+        }
+        let (_conn_pool, tailer) = setup_indexer().unwrap();
+        let processor = tailer.processors()[0].clone();
+        for version in [0, 1, 4, 7, 8] {
+            processor.mark_version_started(version);
+        }
+        let gaps = processor.get_gaps(10);
+        assert_eq!(
+            gaps,
+            vec![
+                VersionGap {
+                    gap_start: 2,
+                    gap_end: 3
+                },
+                VersionGap {
+                    gap_start: 5,
+                    gap_end: 6
+                },
+            ]
+        );
+        assert_eq!(processor.get_gaps(5).len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_failed_versions_retry_count() {
+        if crate::should_skip_pg_tests() {
+            return;
+        }
+        let (_conn_pool, tailer) = setup_indexer().unwrap();
+        let processor = tailer.processors()[0].clone();
+        let tpe = TransactionProcessingError::TransactionCommitError((
+            anyhow::anyhow!("bad transaction"),
+            3,
+            processor.name(),
+        ));
+        processor.update_status_err(&tpe, &[3, 4]);
+        processor.update_status_err(&tpe, &[3]);
+        let retry_counts: Vec<(i64, i32)> = processor
+            .get_failed_versions()
+            .iter()
+            .map(|failed_version| (failed_version.version, failed_version.retry_count))
+            .collect();
+        assert_eq!(retry_counts, vec![(3, 1), (4, 0)]);
+
+        processor.mark_dead_letter(&[3]);
+        assert_eq!(processor.get_error_versions(), vec![4]);
+        assert_eq!(processor.count_dead_letter_versions(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_processor_versions() {
+        if crate::should_skip_pg_tests() {
+            return;
+        }
+        let (conn_pool, tailer) = setup_indexer().unwrap();
+        let conn = conn_pool.get().unwrap();
+        ProcessorVersion::new("token_processor", 0)
+            .upsert(&conn)
+            .unwrap();
+        tailer.check_processor_versions().unwrap();
+
+        let schema_version = |name| {
+            ProcessorVersion::get(&conn, name)
+                .unwrap()
+                .map(|recorded| recorded.schema_version)
+        };
+        assert_eq!(schema_version("default_processor"), Some(1));
+        // A processor at another version is left for a reindex to update
+        assert_eq!(schema_version("token_processor"), Some(0));
+    }
+
+    #[cfg(not(feature = "sqlite"))]
+    #[tokio::test]
+    async fn test_chain_scope() {
+        if crate::should_skip_pg_tests() {
+            return;
+        }
+        let (conn_pool, _tailer) = setup_indexer().unwrap();
+        let conn = conn_pool.get().unwrap();
+        assert_eq!(scoped_chain_id(&conn).unwrap(), Some(TEST_CHAIN_ID));
+
+        ProcessorVersion::new("default_processor", 1)
+            .upsert(&conn)
+            .unwrap();
+        let chain_ids = diesel::select(diesel::dsl::sql::<diesel::sql_types::Array<BigInt>>(
+            "ARRAY(SELECT chain_id FROM processor_versions)",
+        ))
+        .get_result::<Vec<i64>>(&conn)
+        .unwrap();
+        assert_eq!(chain_ids, vec![TEST_CHAIN_ID as i64]);
+    }
+
     #[tokio::test]
     async fn test_parsing_and_writing() {
         if crate::should_skip_pg_tests() {
@@ -699,6 +1096,27 @@ mod test {
         assert_eq!(events2.get(1).unwrap().type_, "0x1::Whatever::FakeEvent2");
         assert_eq!(wsc2.len(), 2);
 
+        let payload: Payload = crate::schema::payloads::table
+            .find(&tx2.hash)
+            .first(&conn_pool.get().unwrap())
+            .unwrap();
+        assert_eq!(payload.payload_type, "script_function_payload");
+        assert_eq!(
+            (
+                payload.module_address.as_deref(),
+                payload.module_name.as_deref(),
+                payload.function_name.as_deref()
+            ),
+            (Some("0x1"), Some("aptos_coin"), Some("mint"))
+        );
+        assert_eq!(
+            *payload.arguments,
+            json!([
+                "0x45b44793724a5ecc6ad85fa60949d0824cfc7f61d6bd74490b13598379313142",
+                "20000"
+            ])
+        );
+
         // Fetch the latest status
         let latest_version = tailer.set_fetcher_to_lowest_processor_version().await;
         assert_eq!(latest_version, 691595);
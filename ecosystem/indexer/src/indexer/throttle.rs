@@ -0,0 +1,172 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::counters::{FETCH_BACK_PRESSURE, FETCH_BATCH_SIZE_LIMIT, FETCH_DELAY_MILLIS};
+use std::{sync::Mutex, time::Duration};
+
+/// The shortest wait between requests once the node has pushed back
+const MIN_BACK_PRESSURE_DELAY: Duration = Duration::from_millis(500);
+/// Below this, the wait between requests is dropped altogether
+const NEGLIGIBLE_DELAY: Duration = Duration::from_millis(10);
+
+/// How a `FetchThrottle` adapts to the node
+#[derive(Clone, Copy, Debug)]
+pub struct ThrottleConfig {
+    /// The fewest versions to request at a time, however much the node pushes back
+    pub min_batch_size: u16,
+    /// Responses slower than this shrink the batch size
+    pub target_latency: Duration,
+    /// The longest to wait between requests while the node pushes back
+    pub max_delay: Duration,
+}
+
+impl Default for ThrottleConfig {
+    fn default() -> Self {
+        Self {
+            min_batch_size: 10,
+            target_latency: Duration::from_secs(2),
+            max_delay: Duration::from_secs(30),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct ThrottleState {
+    /// The most versions to request at a time
+    batch_size_limit: u16,
+    /// How long to wait before each request
+    delay: Duration,
+}
+
+/// Keeps the fetchers of an indexer from overwhelming their node. Every request
+/// reports back how it went: the batch size shrinks when the node is slow to
+/// respond, and when it pushes back (429 or 503, or a timeout) the batch size is
+/// halved and requests are spaced out, the wait doubling while it keeps pushing
+/// back. Fast responses grow the batch size again and shorten the wait.
+#[derive(Debug)]
+pub struct FetchThrottle {
+    config: ThrottleConfig,
+    state: Mutex<ThrottleState>,
+}
+
+impl FetchThrottle {
+    pub fn new(config: ThrottleConfig) -> Self {
+        Self {
+            config,
+            state: Mutex::new(ThrottleState {
+                batch_size_limit: u16::MAX,
+                delay: Duration::ZERO,
+            }),
+        }
+    }
+
+    /// How many versions to request, when `requested` are wanted
+    pub fn batch_size(&self, requested: u16) -> u16 {
+        requested
+            .min(self.state.lock().unwrap().batch_size_limit)
+            .max(1)
+    }
+
+    /// How long to wait before the next request
+    pub fn delay(&self) -> Duration {
+        self.state.lock().unwrap().delay
+    }
+
+    /// Records that a request for `batch_size` versions was answered in `latency`
+    pub fn record_response(&self, batch_size: u16, latency: Duration) {
+        let mut state = self.state.lock().unwrap();
+        if latency > self.config.target_latency {
+            state.batch_size_limit = (batch_size - batch_size / 4).max(self.config.min_batch_size);
+        } else {
+            state.batch_size_limit = state
+                .batch_size_limit
+                .saturating_add((state.batch_size_limit / 10).max(1));
+        }
+        state.delay /= 2;
+        if state.delay < NEGLIGIBLE_DELAY {
+            state.delay = Duration::ZERO;
+        }
+        Self::export(&state);
+    }
+
+    /// Records that the node pushed back on a request for `batch_size` versions
+    pub fn record_back_pressure(&self, batch_size: u16) {
+        FETCH_BACK_PRESSURE.inc();
+        let mut state = self.state.lock().unwrap();
+        state.batch_size_limit = (batch_size / 2).max(self.config.min_batch_size);
+        state.delay = (state.delay * 2)
+            .max(MIN_BACK_PRESSURE_DELAY)
+            .min(self.config.max_delay);
+        Self::export(&state);
+    }
+
+    fn export(state: &ThrottleState) {
+        FETCH_BATCH_SIZE_LIMIT.set(state.batch_size_limit as i64);
+        FETCH_DELAY_MILLIS.set(state.delay.as_millis() as i64);
+    }
+}
+
+/// Whether a failed request means the node wants fewer requests: it answered 429 or 503, or didn't answer in time
+pub fn is_back_pressure(err: &anyhow::Error) -> bool {
+    if let Some(err) = err.downcast_ref::<reqwest::Error>() {
+        if err.is_timeout() {
+            return true;
+        }
+        if let Some(status) = err.status() {
+            return status.as_u16() == 429 || status.as_u16() == 503;
+        }
+    }
+    // Errors the node describes come back as text
+    let message = format!("{:?}", err);
+    message.contains("code: 429") || message.contains("code: 503")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_throttle_adapts_to_node() {
+        let throttle = FetchThrottle::new(ThrottleConfig {
+            min_batch_size: 10,
+            target_latency: Duration::from_secs(1),
+            max_delay: Duration::from_secs(4),
+        });
+        assert_eq!(throttle.batch_size(500), 500);
+
+        // Slow responses shrink batches
+        throttle.record_response(500, Duration::from_secs(2));
+        assert_eq!(throttle.batch_size(500), 375);
+        assert_eq!(throttle.delay(), Duration::ZERO);
+
+        // Pushing back halves them and spaces requests out, up to `max_delay`
+        throttle.record_back_pressure(375);
+        assert_eq!(throttle.batch_size(500), 187);
+        assert_eq!(throttle.delay(), MIN_BACK_PRESSURE_DELAY);
+        for _ in 0..5 {
+            throttle.record_back_pressure(throttle.batch_size(500));
+        }
+        assert_eq!(throttle.batch_size(500), 10);
+        assert_eq!(throttle.delay(), Duration::from_secs(4));
+
+        // Fast responses grow them back and shorten the wait
+        throttle.record_response(10, Duration::from_millis(100));
+        assert_eq!(throttle.batch_size(500), 11);
+        assert_eq!(throttle.delay(), Duration::from_secs(2));
+        for _ in 0..100 {
+            throttle.record_response(throttle.batch_size(500), Duration::from_millis(100));
+        }
+        assert_eq!(throttle.batch_size(500), 500);
+        assert_eq!(throttle.delay(), Duration::ZERO);
+    }
+
+    #[test]
+    fn test_is_back_pressure() {
+        assert!(is_back_pressure(&anyhow::anyhow!(
+            "Request failed: RestError {{ code: 429, message: \"Too many requests\", aptos_ledger_version: None }}"
+        )));
+        assert!(!is_back_pressure(&anyhow::anyhow!(
+            "Request failed: RestError {{ code: 404, message: \"Not found\", aptos_ledger_version: None }}"
+        )));
+    }
+}
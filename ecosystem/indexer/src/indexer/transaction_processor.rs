@@ -6,17 +6,95 @@ use crate::{
         GOT_CONNECTION, PROCESSOR_ERRORS, PROCESSOR_INVOCATIONS, PROCESSOR_SUCCESSES,
         UNABLE_TO_GET_CONNECTION,
     },
-    database::{execute_with_better_error, PgDbPool, PgPoolConnection},
-    indexer::{errors::TransactionProcessingError, processing_result::ProcessingResult},
-    models::processor_statuses::ProcessorStatusModel,
+    database::{execute_with_better_error, upsert, DbPool, DbPoolConnection},
+    indexer::{
+        errors::TransactionProcessingError, processing_result::ProcessingResult, sink::SinkRecord,
+    },
+    models::processor_statuses::{FailedVersion, ProcessorStatusModel, VersionGap},
     schema,
 };
 use aptos_rest_client::Transaction;
 use async_trait::async_trait;
-use diesel::{prelude::*, RunQueryDsl};
+use diesel::{
+    prelude::*,
+    sql_types::{BigInt, Text},
+    RunQueryDsl,
+};
 use schema::processor_statuses::{self, dsl};
 use std::{fmt::Debug, sync::Arc};
 
+/// Writes the statuses of many versions at once, replacing any previous status of those versions
+fn apply_processor_statuses(
+    conn: &DbPoolConnection,
+    statuses: &[ProcessorStatusModel],
+) -> Result<usize, diesel::result::Error> {
+    execute_with_better_error(
+        conn,
+        upsert!(
+            processor_statuses::table,
+            statuses,
+            "processor_statuses_pkey",
+            (
+                dsl::success,
+                dsl::details,
+                dsl::last_updated,
+                dsl::retry_count,
+                dsl::dead_letter,
+            )
+        ),
+    )
+}
+
+/// Writes the statuses of versions that failed. A version that already has a status is being retried, so its
+/// `retry_count` goes up by one
+#[cfg(not(feature = "sqlite"))]
+fn apply_failed_statuses(
+    conn: &DbPoolConnection,
+    statuses: &[ProcessorStatusModel],
+) -> Result<usize, diesel::result::Error> {
+    use diesel::pg::upsert::{excluded, on_constraint};
+
+    execute_with_better_error(
+        conn,
+        diesel::insert_into(processor_statuses::table)
+            .values(statuses)
+            .on_conflict(on_constraint("processor_statuses_pkey"))
+            .do_update()
+            .set((
+                dsl::success.eq(excluded(dsl::success)),
+                dsl::details.eq(excluded(dsl::details)),
+                dsl::last_updated.eq(excluded(dsl::last_updated)),
+                dsl::retry_count.eq(dsl::retry_count + 1),
+            )),
+    )
+}
+
+/// Writes the statuses of versions that failed. A version that already has a status is being retried, so its
+/// `retry_count` goes up by one. diesel can't build an upsert computing from the existing row on SQLite, so the
+/// existing statuses are updated first, and the others inserted.
+#[cfg(feature = "sqlite")]
+fn apply_failed_statuses(
+    conn: &DbPoolConnection,
+    statuses: &[ProcessorStatusModel],
+) -> Result<usize, diesel::result::Error> {
+    use crate::database::insert_or_ignore;
+
+    conn.transaction(|| {
+        for status in statuses {
+            diesel::update(dsl::processor_statuses.find((&status.name, status.version)))
+                .set((
+                    dsl::success.eq(status.success),
+                    dsl::details.eq(&status.details),
+                    dsl::last_updated.eq(status.last_updated),
+                    dsl::retry_count.eq(dsl::retry_count + 1),
+                ))
+                .execute(conn)?;
+        }
+        execute_with_better_error(conn, insert_or_ignore!(processor_statuses::table, statuses))?;
+        Ok(statuses.len())
+    })
+}
+
 /// The `TransactionProcessor` is used by an instance of a `Tailer` to process transactions
 #[async_trait]
 pub trait TransactionProcessor: Send + Sync + Debug {
@@ -24,23 +102,61 @@ pub trait TransactionProcessor: Send + Sync + Debug {
     /// This will get stored in the database for each (`TransactionProcessor`, transaction_version) pair
     fn name(&self) -> &'static str;
 
-    /// Accepts a transaction, and processes it. This method will be called from `process_transaction_with_status`
-    /// In case a transaction cannot be processed, returns an error: the `Tailer` will mark it as failed in the database,
-    /// and it will be retried next time the indexer is started.
-    async fn process_transaction(
+    /// Accepts a transaction, and writes what it derives from it through `conn`. This method will be called from
+    /// `process_transactions`, inside a DB transaction spanning the whole batch and the batch's statuses: in case a
+    /// transaction cannot be processed, returns an error, and nothing of the batch is written. The `Tailer` then
+    /// marks the batch as failed in the database, and its versions will be retried.
+    fn process_transaction(
         &self,
-        transaction: Arc<Transaction>,
-    ) -> Result<ProcessingResult, TransactionProcessingError>;
+        conn: &DbPoolConnection,
+        transaction: &Transaction,
+    ) -> Result<(), diesel::result::Error>;
+
+    /// Writes what the processor derives from a batch of consecutive transactions through `conn`, inside the
+    /// batch's DB transaction. By default calls `process_transaction` on each transaction; processors can instead
+    /// gather the rows of the whole batch and write them with a few multi-row inserts.
+    fn process_batch(
+        &self,
+        conn: &DbPoolConnection,
+        transactions: &[Arc<Transaction>],
+    ) -> Result<(), diesel::result::Error> {
+        for transaction in transactions {
+            self.process_transaction(conn, transaction)?;
+        }
+        Ok(())
+    }
+
+    /// Called once a batch has been committed, for work that doesn't belong in a DB transaction, such as fetching
+    /// data from outside the chain. This is best effort: the batch is already recorded as processed.
+    async fn after_commit(&self, _transactions: &[Arc<Transaction>]) {}
+
+    /// The records derived from a transaction to publish to the configured `Sink`s once its batch is committed.
+    /// Processors that don't publish anything can leave this out.
+    fn sink_records(&self, _transaction: &Transaction) -> Vec<SinkRecord> {
+        vec![]
+    }
+
+    /// The version of what the processor writes. Bump it whenever a change makes the rows written by earlier
+    /// versions wrong or incomplete: the indexer warns on startup until the processor has been reindexed.
+    fn schema_version(&self) -> i32 {
+        1
+    }
+
+    /// The tables the processor writes to, which are emptied when the processor is reindexed. Processors leaving
+    /// this out can't be reindexed.
+    fn tables(&self) -> &'static [&'static str] {
+        &[]
+    }
 
     /// Gets a reference to the connection pool
     /// This is used by the `get_conn()` helper below
-    fn connection_pool(&self) -> &PgDbPool;
+    fn connection_pool(&self) -> &DbPool;
 
     //* Below are helper methods that don't need to be implemented *//
 
     /// Gets the connection.
     /// If it was unable to do so (default timeout: 30s), it will keep retrying until it can.
-    fn get_conn(&self) -> PgPoolConnection {
+    fn get_conn(&self) -> DbPoolConnection {
         let pool = self.connection_pool();
         loop {
             match pool.get() {
@@ -60,25 +176,89 @@ pub trait TransactionProcessor: Send + Sync + Debug {
         }
     }
 
+    /// Processes a batch of consecutive transactions in a single DB transaction, which also records each version
+    /// as successfully processed. Either all of the batch's rows and statuses are committed or none are, so a
+    /// crash or error mid batch can't leave rows behind that would be written again when the batch is retried.
+    async fn process_transactions(
+        &self,
+        transactions: &[Arc<Transaction>],
+    ) -> Result<ProcessingResult, TransactionProcessingError> {
+        let versions: Vec<u64> = transactions
+            .iter()
+            .map(|transaction| transaction.version().unwrap())
+            .collect();
+        let first_version = versions.first().copied().unwrap_or_default();
+        let last_version = versions.last().copied().unwrap_or_default();
+
+        {
+            let conn = self.get_conn();
+            conn.transaction::<(), diesel::result::Error, _>(|| {
+                self.process_batch(&conn, transactions)?;
+                let statuses: Vec<ProcessorStatusModel> = versions
+                    .iter()
+                    .map(|version| {
+                        ProcessorStatusModel::from_processing_result_ok(&ProcessingResult::new(
+                            self.name(),
+                            *version,
+                        ))
+                    })
+                    .collect();
+                apply_processor_statuses(&conn, &statuses)?;
+                Ok(())
+            })
+            .map_err(|err| {
+                TransactionProcessingError::TransactionCommitError((
+                    anyhow::Error::from(err),
+                    first_version,
+                    self.name(),
+                ))
+            })?;
+        }
+
+        self.after_commit(transactions).await;
+        Ok(ProcessingResult::new(self.name(), last_version))
+    }
+
     /// This is a helper method, tying together the other helper methods to allow tracking status in the DB
-    async fn process_transaction_with_status(
+    async fn process_transactions_with_status(
         &self,
-        transaction: Arc<Transaction>,
+        transactions: Vec<Arc<Transaction>>,
     ) -> Result<ProcessingResult, TransactionProcessingError> {
         PROCESSOR_INVOCATIONS
             .with_label_values(&[self.name()])
             .inc();
 
-        self.mark_version_started(transaction.version().unwrap());
-        let res = self.process_transaction(transaction).await;
-        // Handle version success/failure
+        let res = self.process_transactions(&transactions).await;
+        // Successes are recorded along with the batch, failures once it has been rolled back
         match res.as_ref() {
-            Ok(processing_result) => self.update_status_success(processing_result),
-            Err(tpe) => self.update_status_err(tpe),
+            Ok(processing_result) => {
+                aptos_logger::debug!(
+                    "[{}] Processed versions up to {}",
+                    self.name(),
+                    processing_result.version
+                );
+                PROCESSOR_SUCCESSES.with_label_values(&[self.name()]).inc();
+            }
+            Err(tpe) => {
+                let versions: Vec<u64> = transactions
+                    .iter()
+                    .map(|transaction| transaction.version().unwrap())
+                    .collect();
+                self.update_status_err(tpe, &versions);
+            }
         };
         res
     }
 
+    /// Processes a single transaction, see `process_transactions_with_status`
+    async fn process_transaction_with_status(
+        &self,
+        transaction: Arc<Transaction>,
+    ) -> Result<ProcessingResult, TransactionProcessingError> {
+        self.process_transactions_with_status(vec![transaction])
+            .await
+    }
+
     /// Writes that a version has been started for this `TransactionProcessor` to the DB
     fn mark_version_started(&self, version: u64) {
         aptos_logger::debug!(
@@ -90,46 +270,32 @@ pub trait TransactionProcessor: Send + Sync + Debug {
         self.apply_processor_status(&psm);
     }
 
-    /// Writes that a version has been completed successfully for this `TransactionProcessor` to the DB
-    fn update_status_success(&self, processing_result: &ProcessingResult) {
+    /// Writes that the versions of a batch have errored for this `TransactionProcessor` to the DB
+    fn update_status_err(&self, tpe: &TransactionProcessingError, versions: &[u64]) {
         aptos_logger::debug!(
-            "[{}] Marking processing version OK: {}",
-            self.name(),
-            processing_result.version
-        );
-        PROCESSOR_SUCCESSES.with_label_values(&[self.name()]).inc();
-        let psm = ProcessorStatusModel::from_processing_result_ok(processing_result);
-        self.apply_processor_status(&psm);
-    }
-
-    /// Writes that a version has errored for this `TransactionProcessor` to the DB
-    fn update_status_err(&self, tpe: &TransactionProcessingError) {
-        aptos_logger::debug!(
-            "[{}] Marking processing version Err: {:?}",
+            "[{}] Marking processing versions {:?} Err: {:?}",
             self.name(),
+            versions,
             tpe
         );
         PROCESSOR_ERRORS.with_label_values(&[self.name()]).inc();
-        let psm = ProcessorStatusModel::from_transaction_processing_err(tpe);
-        self.apply_processor_status(&psm);
+        let statuses: Vec<ProcessorStatusModel> = versions
+            .iter()
+            .map(|version| ProcessorStatusModel::from_transaction_processing_err(tpe, *version))
+            .collect();
+        apply_failed_statuses(&self.get_conn(), &statuses)
+            .expect("Error updating Processor Status!");
     }
 
     /// Actually performs the write for a `ProcessorStatusModel` changeset
     fn apply_processor_status(&self, psm: &ProcessorStatusModel) {
         let conn = self.get_conn();
-        execute_with_better_error(
-            &conn,
-            diesel::insert_into(processor_statuses::table)
-                .values(psm)
-                .on_conflict((dsl::name, dsl::version))
-                .do_update()
-                .set(psm),
-        )
-        .expect("Error updating Processor Status!");
+        apply_processor_statuses(&conn, std::slice::from_ref(psm))
+            .expect("Error updating Processor Status!");
     }
 
-    /// Gets all versions which were not successfully processed for this `TransactionProcessor` from the DB
-    /// This is so the `Tailer` can know which versions to retry
+    /// Gets all versions which were not successfully processed for this `TransactionProcessor` from the DB,
+    /// leaving out dead lettered ones. This is so the `Tailer` can know which versions to retry
     fn get_error_versions(&self) -> Vec<u64> {
         let conn = self.get_conn();
 
@@ -138,6 +304,7 @@ pub trait TransactionProcessor: Send + Sync + Debug {
             .filter(
                 dsl::success
                     .eq(false)
+                    .and(dsl::dead_letter.eq(false))
                     .and(dsl::name.eq(self.name().to_string())),
             )
             .load::<i64>(&conn)
@@ -147,6 +314,78 @@ pub trait TransactionProcessor: Send + Sync + Debug {
             .collect()
     }
 
+    /// Gets the versions which were not successfully processed and aren't dead lettered, with how many times they
+    /// have been retried. This is so the `RetryScheduler` can tell which are due for a retry
+    fn get_failed_versions(&self) -> Vec<FailedVersion> {
+        let conn = self.get_conn();
+
+        dsl::processor_statuses
+            .select((dsl::version, dsl::retry_count, dsl::last_updated))
+            .filter(
+                dsl::success
+                    .eq(false)
+                    .and(dsl::dead_letter.eq(false))
+                    .and(dsl::name.eq(self.name().to_string())),
+            )
+            .order(dsl::version)
+            .load::<FailedVersion>(&conn)
+            .expect("Error loading the failed versions query")
+    }
+
+    /// Gives up on versions: they are kept as failed, but no longer retried
+    fn mark_dead_letter(&self, versions: &[u64]) {
+        aptos_logger::warn!(
+            "[{}] Giving up on versions {:?}, moving them to the dead letter state",
+            self.name(),
+            versions
+        );
+        let versions: Vec<i64> = versions.iter().map(|version| *version as i64).collect();
+        diesel::update(
+            dsl::processor_statuses.filter(
+                dsl::name
+                    .eq(self.name().to_string())
+                    .and(dsl::version.eq_any(versions)),
+            ),
+        )
+        .set(dsl::dead_letter.eq(true))
+        .execute(&self.get_conn())
+        .expect("Error updating Processor Status!");
+    }
+
+    /// Gets how many versions this `TransactionProcessor` has given up on
+    fn count_dead_letter_versions(&self) -> i64 {
+        let conn = self.get_conn();
+
+        dsl::processor_statuses
+            .filter(
+                dsl::dead_letter
+                    .eq(true)
+                    .and(dsl::name.eq(self.name().to_string())),
+            )
+            .count()
+            .get_result::<i64>(&conn)
+            .expect("Error loading the dead letter count query")
+    }
+
+    /// Gets the ranges of versions below `below_version` that this `TransactionProcessor` has no status for,
+    /// although it has statuses for versions on both sides. This is so the `GapDetector` can backfill them
+    fn get_gaps(&self, below_version: u64) -> Vec<VersionGap> {
+        let conn = self.get_conn();
+
+        diesel::sql_query(
+            "SELECT version + 1 AS gap_start, next_version - 1 AS gap_end FROM ( \
+               SELECT version, LEAD(version) OVER (ORDER BY version) AS next_version \
+               FROM processor_statuses WHERE name = $1 AND version < $2 \
+             ) versions \
+             WHERE next_version > version + 1 \
+             ORDER BY version",
+        )
+        .bind::<Text, _>(self.name())
+        .bind::<BigInt, _>(below_version as i64)
+        .load::<VersionGap>(&conn)
+        .expect("Error loading the version gaps query")
+    }
+
     /// Gets the highest version for this `TransactionProcessor` from the DB
     /// This is so we know where to resume from on restarts
     fn get_max_version(&self) -> Option<u64> {
@@ -11,15 +11,23 @@ extern crate diesel_migrations;
 #[macro_use]
 extern crate diesel;
 
+pub mod account_snapshot_processor;
+pub mod coin_processor;
 pub mod counters;
 pub mod database;
 pub mod default_processor;
+#[cfg(feature = "graphql")]
+pub mod graphql;
+pub mod health;
 pub mod indexer;
 pub mod models;
+pub mod read_api;
 pub mod schema;
 pub mod token_processor;
+pub mod write_set_processor;
 
-/// By default, skips test unless `INDEXER_DATABASE_URL` is set.
+/// By default, skips test unless `INDEXER_DATABASE_URL` is set, to a Postgres URL, or to the path of a SQLite
+/// database file when built with the `sqlite` feature.
 /// In CI, will explode if `INDEXER_DATABASE_URL` is NOT set.
 pub fn should_skip_pg_tests() -> bool {
     if std::env::var("CIRCLECI").is_ok() {
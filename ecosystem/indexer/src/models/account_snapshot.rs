@@ -0,0 +1,35 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{database::JsonValue, schema::account_snapshots};
+use aptos_rest_client::{aptos_api_types::Address, Resource};
+use serde::Serialize;
+
+/// A resource of an account as of a snapshot version
+#[derive(Debug, Identifiable, Insertable, Queryable, Serialize, Clone, PartialEq)]
+#[diesel(table_name = "account_snapshots")]
+#[primary_key(address, snapshot_version, resource_type)]
+pub struct AccountSnapshot {
+    pub address: String,
+    pub snapshot_version: i64,
+    /// The resource's struct tag, e.g. `0x1::coin::CoinStore<0x1::aptos_coin::AptosCoin>`
+    pub resource_type: String,
+    pub data: JsonValue,
+    pub inserted_at: chrono::NaiveDateTime,
+}
+
+impl AccountSnapshot {
+    /// Columns an inserted row binds, see `insert_chunks`
+    pub const NUM_COLUMNS: usize = 5;
+
+    pub fn from_resource(address: &Address, snapshot_version: u64, resource: &Resource) -> Self {
+        Self {
+            // Addresses are stored the way the node's API renders them
+            address: address.to_string(),
+            snapshot_version: snapshot_version as i64,
+            resource_type: resource.resource_type.to_string(),
+            data: JsonValue(resource.data.clone()),
+            inserted_at: chrono::Utc::now().naive_utc(),
+        }
+    }
+}
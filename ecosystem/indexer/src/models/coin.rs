@@ -0,0 +1,253 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    models::{events::Event, write_set_changes::WriteSetChange},
+    schema::{coin_activities, coin_balances},
+};
+use aptos_rest_client::types;
+use serde::{Deserialize, Serialize};
+
+const COIN_STORE_TYPE_PREFIX: &str = "0x1::coin::CoinStore<";
+const DEPOSIT_EVENT_TYPE: &str = "0x1::coin::DepositEvent";
+const WITHDRAW_EVENT_TYPE: &str = "0x1::coin::WithdrawEvent";
+
+/// The balance of a coin held by an account, as of `transaction_version`
+#[derive(Debug, Identifiable, Insertable, Queryable, Serialize, Clone)]
+#[diesel(table_name = "coin_balances")]
+#[primary_key(owner_address, coin_type)]
+pub struct CoinBalance {
+    pub owner_address: String,
+    pub coin_type: String,
+    pub amount: i64,
+    pub transaction_version: i64,
+    pub inserted_at: chrono::NaiveDateTime,
+}
+
+/// A deposit or withdrawal of a coin
+#[derive(Debug, Identifiable, Insertable, Queryable, Serialize, Clone)]
+#[diesel(table_name = "coin_activities")]
+#[primary_key(event_key, sequence_number)]
+pub struct CoinActivity {
+    pub event_key: String,
+    pub sequence_number: i64,
+    pub owner_address: String,
+    pub coin_type: String,
+    pub activity_type: String,
+    pub amount: i64,
+    pub transaction_version: i64,
+    pub transaction_timestamp: chrono::NaiveDateTime,
+    pub inserted_at: chrono::NaiveDateTime,
+}
+
+#[derive(Deserialize, Debug)]
+struct CoinStoreResource {
+    coin: CoinResource,
+    deposit_events: EventHandleResource,
+    withdraw_events: EventHandleResource,
+}
+
+#[derive(Deserialize, Debug)]
+struct CoinResource {
+    #[serde(deserialize_with = "types::deserialize_from_string")]
+    value: i64,
+}
+
+#[derive(Deserialize, Debug)]
+struct EventHandleResource {
+    guid: GuidResource,
+}
+
+#[derive(Deserialize, Debug)]
+struct GuidResource {
+    id: GuidIdResource,
+}
+
+#[derive(Deserialize, Debug)]
+struct GuidIdResource {
+    #[serde(deserialize_with = "types::deserialize_from_string")]
+    creation_num: u64,
+    addr: String,
+}
+
+impl GuidIdResource {
+    /// The key of the event handle, formatted the way the API formats event
+    /// keys: the creation number as little endian bytes, then the address
+    fn event_key(&self) -> String {
+        let creation_num: String = self
+            .creation_num
+            .to_le_bytes()
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect();
+        format!(
+            "0x{}{:0>64}",
+            creation_num,
+            self.addr.trim_start_matches("0x")
+        )
+    }
+}
+
+/// A `CoinStore` written by a transaction: the new balance, and the keys of
+/// the store's event handles so its deposit and withdraw events can be tied
+/// back to the coin type
+#[derive(Debug)]
+pub struct CoinStoreChange {
+    pub balance: CoinBalance,
+    pub deposit_event_key: String,
+    pub withdraw_event_key: String,
+}
+
+impl CoinStoreChange {
+    pub fn from_write_set_change(
+        write_set_change: &WriteSetChange,
+        transaction_version: i64,
+    ) -> Option<Self> {
+        if write_set_change.type_ != "write_resource" {
+            return None;
+        }
+        let resource_type = write_set_change.data["type"].as_str()?;
+        let coin_type = resource_type
+            .strip_prefix(COIN_STORE_TYPE_PREFIX)?
+            .strip_suffix('>')?;
+        let coin_store: CoinStoreResource =
+            serde_json::from_value(write_set_change.data["data"].clone()).ok()?;
+        Some(Self {
+            balance: CoinBalance {
+                owner_address: write_set_change.address.clone(),
+                coin_type: coin_type.to_string(),
+                amount: coin_store.coin.value,
+                transaction_version,
+                inserted_at: chrono::Utc::now().naive_utc(),
+            },
+            deposit_event_key: coin_store.deposit_events.guid.id.event_key(),
+            withdraw_event_key: coin_store.withdraw_events.guid.id.event_key(),
+        })
+    }
+}
+
+#[derive(Deserialize, Debug)]
+struct CoinEventData {
+    #[serde(deserialize_with = "types::deserialize_from_string")]
+    amount: i64,
+}
+
+impl CoinActivity {
+    /// Columns an inserted row binds, see `insert_chunks`
+    pub const NUM_COLUMNS: usize = 9;
+
+    /// Builds the activity for a coin deposit or withdraw event. The event only
+    /// carries the amount, the owner and coin type come from the `CoinStore`
+    /// that emitted it, which the same transaction always writes.
+    pub fn from_event(
+        event: &Event,
+        coin_stores: &[CoinStoreChange],
+        transaction_version: i64,
+        transaction_timestamp: chrono::NaiveDateTime,
+    ) -> Option<Self> {
+        let (activity_type, coin_store) = match event.type_.as_str() {
+            DEPOSIT_EVENT_TYPE => (
+                "deposit",
+                coin_stores
+                    .iter()
+                    .find(|store| store.deposit_event_key == event.key)?,
+            ),
+            WITHDRAW_EVENT_TYPE => (
+                "withdraw",
+                coin_stores
+                    .iter()
+                    .find(|store| store.withdraw_event_key == event.key)?,
+            ),
+            _ => return None,
+        };
+        let data: CoinEventData = serde_json::from_value(event.data.0.clone()).ok()?;
+        Some(Self {
+            event_key: event.key.clone(),
+            sequence_number: event.sequence_number,
+            owner_address: coin_store.balance.owner_address.clone(),
+            coin_type: coin_store.balance.coin_type.clone(),
+            activity_type: activity_type.to_string(),
+            amount: data.amount,
+            transaction_version,
+            transaction_timestamp,
+            inserted_at: chrono::Utc::now().naive_utc(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::database::JsonValue;
+    use serde_json::json;
+
+    fn coin_store_change() -> WriteSetChange {
+        WriteSetChange {
+            transaction_hash: "0x1234".to_string(),
+            hash: "0x5678".to_string(),
+            type_: "write_resource".to_string(),
+            address: "0xa550c18".to_string(),
+            module: Default::default(),
+            resource: Default::default(),
+            data: JsonValue(json!({
+                "type": "0x1::coin::CoinStore<0x1::aptos_coin::AptosCoin>",
+                "data": {
+                    "coin": { "value": "1000" },
+                    "deposit_events": {
+                        "counter": "1",
+                        "guid": { "id": { "addr": "0xa550c18", "creation_num": "2" } }
+                    },
+                    "withdraw_events": {
+                        "counter": "0",
+                        "guid": { "id": { "addr": "0xa550c18", "creation_num": "3" } }
+                    }
+                }
+            })),
+            inserted_at: chrono::Utc::now().naive_utc(),
+        }
+    }
+
+    #[test]
+    fn test_coin_store_change() {
+        let change = CoinStoreChange::from_write_set_change(&coin_store_change(), 10).unwrap();
+        assert_eq!(change.balance.owner_address, "0xa550c18");
+        assert_eq!(change.balance.coin_type, "0x1::aptos_coin::AptosCoin");
+        assert_eq!(change.balance.amount, 1000);
+        assert_eq!(change.balance.transaction_version, 10);
+        assert_eq!(
+            change.deposit_event_key,
+            "0x0200000000000000000000000000000000000000000000000000000000000000000000000a550c18"
+        );
+    }
+
+    #[test]
+    fn test_coin_activity_from_event() {
+        let coin_stores =
+            vec![CoinStoreChange::from_write_set_change(&coin_store_change(), 10).unwrap()];
+        let event = Event {
+            transaction_hash: "0x1234".to_string(),
+            key: coin_stores[0].deposit_event_key.clone(),
+            sequence_number: 0,
+            type_: DEPOSIT_EVENT_TYPE.to_string(),
+            data: JsonValue(json!({ "amount": "100" })),
+            inserted_at: chrono::Utc::now().naive_utc(),
+            account_address: "0xa550c18".to_string(),
+            creation_number: 2,
+            type_address: Some("0x1".to_string()),
+            type_module: Some("coin".to_string()),
+            type_name: Some("DepositEvent".to_string()),
+        };
+        let timestamp = chrono::Utc::now().naive_utc();
+        let activity = CoinActivity::from_event(&event, &coin_stores, 10, timestamp).unwrap();
+        assert_eq!(activity.activity_type, "deposit");
+        assert_eq!(activity.owner_address, "0xa550c18");
+        assert_eq!(activity.coin_type, "0x1::aptos_coin::AptosCoin");
+        assert_eq!(activity.amount, 100);
+
+        let withdraw = Event {
+            type_: WITHDRAW_EVENT_TYPE.to_string(),
+            ..event
+        };
+        assert!(CoinActivity::from_event(&withdraw, &coin_stores, 10, timestamp).is_none());
+    }
+}
@@ -1,8 +1,8 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{models::transactions::Transaction, schema::events};
-use aptos_rest_client::aptos_api_types::Event as APIEvent;
+use crate::{database::JsonValue, models::transactions::Transaction, schema::events};
+use aptos_rest_client::aptos_api_types::{Address, Event as APIEvent, MoveType};
 use serde::Serialize;
 
 #[derive(Associations, Debug, Identifiable, Insertable, Queryable, Serialize)]
@@ -15,21 +15,47 @@ pub struct Event {
     pub sequence_number: i64,
     #[diesel(column_name = type)]
     pub type_: String,
-    pub data: serde_json::Value,
+    pub data: JsonValue,
 
     // Default time columns
     pub inserted_at: chrono::NaiveDateTime,
+
+    /// The account and creation number the event key is made of
+    pub account_address: String,
+    pub creation_number: i64,
+    /// The parts of the event type, if it is a struct
+    pub type_address: Option<String>,
+    pub type_module: Option<String>,
+    pub type_name: Option<String>,
 }
 
 impl Event {
+    /// Columns an inserted row binds, see `insert_chunks`
+    pub const NUM_COLUMNS: usize = 12;
+
     pub fn from_event(transaction_hash: String, event: &APIEvent) -> Self {
+        let key = event.key.to_string();
+        let (account_address, creation_number) = parse_event_key(&key).unwrap_or_default();
+        let (type_address, type_module, type_name) = match &event.typ {
+            MoveType::Struct(tag) => (
+                Some(tag.address.to_string()),
+                Some(tag.module.to_string()),
+                Some(tag.name.to_string()),
+            ),
+            _ => (None, None, None),
+        };
         Event {
             transaction_hash,
-            key: event.key.to_string(),
+            key,
             sequence_number: event.sequence_number.0 as i64,
             type_: event.typ.to_string(),
-            data: event.data.clone(),
+            data: JsonValue(event.data.clone()),
             inserted_at: chrono::Utc::now().naive_utc(),
+            account_address,
+            creation_number,
+            type_address,
+            type_module,
+            type_name,
         }
     }
 
@@ -48,3 +74,38 @@ impl Event {
 
 // Prevent conflicts with other things named `Event`
 pub type EventModel = Event;
+
+/// Splits an event key into the address of the account that created the event
+/// handle and the handle's creation number. An event key is the creation
+/// number as 8 little endian bytes followed by the address, hex encoded.
+pub fn parse_event_key(event_key: &str) -> Option<(String, i64)> {
+    let hex = event_key.strip_prefix("0x").unwrap_or(event_key);
+    if hex.len() != 80 || !hex.is_ascii() {
+        return None;
+    }
+    let mut creation_number_bytes = [0u8; 8];
+    for (i, byte) in creation_number_bytes.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&hex[2 * i..2 * i + 2], 16).ok()?;
+    }
+    let address = hex[16..].parse::<Address>().ok()?;
+    Some((
+        address.to_string(),
+        u64::from_le_bytes(creation_number_bytes) as i64,
+    ))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_event_key() {
+        assert_eq!(
+            parse_event_key(
+                "0x0500000000000000000000000000000000000000000000000000000000000000000000000a550c18"
+            ),
+            Some(("0xa550c18".to_string(), 5))
+        );
+        assert_eq!(parse_event_key("0x0500"), None);
+    }
+}
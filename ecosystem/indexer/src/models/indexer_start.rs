@@ -0,0 +1,34 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{database::DbPoolConnection, schema::indexer_starts};
+use diesel::{prelude::*, RunQueryDsl};
+use serde::Serialize;
+
+/// A start strategy the indexer was started with, and the version it resolved to
+#[derive(Debug, Identifiable, Insertable, Queryable, Serialize, Clone, PartialEq)]
+#[diesel(table_name = "indexer_starts")]
+#[primary_key(inserted_at)]
+pub struct IndexerStart {
+    pub strategy: String,
+    pub start_version: i64,
+    pub inserted_at: chrono::NaiveDateTime,
+}
+
+impl IndexerStart {
+    pub fn new(strategy: String, start_version: u64) -> Self {
+        Self {
+            strategy,
+            start_version: start_version as i64,
+            inserted_at: chrono::Utc::now().naive_utc(),
+        }
+    }
+
+    /// The strategy recorded last, if any
+    pub fn get_latest(conn: &DbPoolConnection) -> diesel::QueryResult<Option<Self>> {
+        indexer_starts::table
+            .order(indexer_starts::inserted_at.desc())
+            .first::<Self>(conn)
+            .optional()
+    }
+}
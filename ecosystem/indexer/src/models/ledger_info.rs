@@ -0,0 +1,49 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{database::DbPoolConnection, schema::ledger_infos};
+use diesel::{prelude::*, RunQueryDsl};
+use serde::Serialize;
+
+/// The chain the indexed data comes from
+#[derive(Debug, Identifiable, Insertable, Queryable, Serialize, Clone, PartialEq)]
+#[diesel(table_name = "ledger_infos")]
+#[primary_key(chain_id)]
+pub struct LedgerInfo {
+    pub chain_id: i64,
+    pub genesis_hash: String,
+    pub inserted_at: chrono::NaiveDateTime,
+}
+
+impl LedgerInfo {
+    pub fn new(chain_id: u8, genesis_hash: String) -> Self {
+        Self {
+            chain_id: chain_id as i64,
+            genesis_hash,
+            inserted_at: chrono::Utc::now().naive_utc(),
+        }
+    }
+
+    /// Whether both describe the same chain, regardless of when they were recorded
+    pub fn is_same_chain(&self, other: &LedgerInfo) -> bool {
+        self.chain_id == other.chain_id && self.genesis_hash == other.genesis_hash
+    }
+
+    pub fn get(conn: &DbPoolConnection, chain_id: u8) -> diesel::QueryResult<Option<Self>> {
+        ledger_infos::table
+            .find(chain_id as i64)
+            .first::<Self>(conn)
+            .optional()
+    }
+
+    /// The other chains recorded in the database which the connection can see. Row level security hides them from
+    /// connections scoped to a chain, unless the role bypasses it
+    pub fn get_other_chains(
+        conn: &DbPoolConnection,
+        chain_id: u8,
+    ) -> diesel::QueryResult<Vec<Self>> {
+        ledger_infos::table
+            .filter(ledger_infos::chain_id.ne(chain_id as i64))
+            .load::<Self>(conn)
+    }
+}
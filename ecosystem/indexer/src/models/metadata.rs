@@ -1,7 +1,7 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::schema::metadatas;
+use crate::{database::JsonValue, schema::metadatas};
 use serde::{Deserialize, Serialize};
 
 #[derive(Associations, Debug, Identifiable, Insertable, Queryable, Serialize, Clone)]
@@ -16,8 +16,8 @@ pub struct Metadata {
     pub image: String,
     pub external_url: Option<String>,
     pub animation_url: Option<String>,
-    pub attributes: Option<serde_json::Value>,
-    pub properties: Option<serde_json::Value>,
+    pub attributes: Option<JsonValue>,
+    pub properties: Option<JsonValue>,
     pub last_updated_at: chrono::NaiveDateTime,
     pub inserted_at: chrono::NaiveDateTime,
 }
@@ -34,8 +34,8 @@ impl Metadata {
                 image: token_uri.image.unwrap(),
                 external_url: token_uri.external_url,
                 animation_url: token_uri.animation_url,
-                attributes: token_uri.attributes,
-                properties: token_uri.properties,
+                attributes: token_uri.attributes.map(JsonValue),
+                properties: token_uri.properties.map(JsonValue),
                 last_updated_at: chrono::Utc::now().naive_utc(),
                 inserted_at: chrono::Utc::now().naive_utc(),
             })
@@ -1,11 +1,19 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
+pub mod account_snapshot;
+pub mod coin;
 pub mod collection;
 pub mod events;
+pub mod indexer_start;
+pub mod ledger_info;
 pub mod metadata;
 pub mod ownership;
+pub mod payload;
 pub mod processor_statuses;
+pub mod processor_version;
+pub mod resource_change;
 pub mod token;
+pub mod token_activity;
 pub mod transactions;
 pub mod write_set_changes;
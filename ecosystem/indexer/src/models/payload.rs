@@ -0,0 +1,85 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{database::JsonValue, models::transactions::Transaction, schema::payloads};
+use aptos_rest_client::aptos_api_types::{
+    MoveType, ScriptFunctionPayload, ScriptPayload, Transaction as APITransaction,
+    TransactionPayload,
+};
+use serde::Serialize;
+
+/// The payload of a user transaction, with the function it calls broken down
+#[derive(Associations, Debug, Identifiable, Insertable, Queryable, Serialize, Clone, PartialEq)]
+#[diesel(table_name = "payloads")]
+#[belongs_to(Transaction, foreign_key = "transaction_hash")]
+#[primary_key(transaction_hash)]
+pub struct Payload {
+    pub transaction_hash: String,
+    pub transaction_version: i64,
+    pub sender: String,
+    pub payload_type: String,
+    /// The module and function called, for script function payloads
+    pub module_address: Option<String>,
+    pub module_name: Option<String>,
+    pub function_name: Option<String>,
+    /// The type arguments, as strings
+    pub type_arguments: JsonValue,
+    pub arguments: JsonValue,
+    pub success: bool,
+    pub inserted_at: chrono::NaiveDateTime,
+}
+
+fn type_arguments_to_json(type_arguments: &[MoveType]) -> JsonValue {
+    JsonValue(
+        type_arguments
+            .iter()
+            .map(|type_argument| serde_json::Value::String(type_argument.to_string()))
+            .collect(),
+    )
+}
+
+impl Payload {
+    /// Columns an inserted row binds, see `insert_chunks`
+    pub const NUM_COLUMNS: usize = 11;
+
+    /// The payload of a transaction, if it is a user transaction
+    pub fn from_transaction(transaction: &APITransaction) -> Option<Self> {
+        let user_transaction = match transaction {
+            APITransaction::UserTransaction(user_transaction) => user_transaction,
+            _ => return None,
+        };
+        let payload = &user_transaction.request.payload;
+        let payload_type = match payload {
+            TransactionPayload::ScriptFunctionPayload(_) => "script_function_payload",
+            TransactionPayload::ScriptPayload(_) => "script_payload",
+            TransactionPayload::ModuleBundlePayload(_) => "module_bundle_payload",
+            TransactionPayload::WriteSetPayload(_) => "write_set_payload",
+        };
+        let (function, type_arguments, arguments) = match payload {
+            TransactionPayload::ScriptFunctionPayload(ScriptFunctionPayload {
+                function,
+                type_arguments,
+                arguments,
+            }) => (Some(function), type_arguments.as_slice(), arguments.clone()),
+            TransactionPayload::ScriptPayload(ScriptPayload {
+                type_arguments,
+                arguments,
+                ..
+            }) => (None, type_arguments.as_slice(), arguments.clone()),
+            _ => (None, &[][..], vec![]),
+        };
+        Some(Self {
+            transaction_hash: user_transaction.info.hash.to_string(),
+            transaction_version: user_transaction.info.version.0 as i64,
+            sender: user_transaction.request.sender.to_string(),
+            payload_type: payload_type.to_string(),
+            module_address: function.map(|function| function.module.address.to_string()),
+            module_name: function.map(|function| function.module.name.to_string()),
+            function_name: function.map(|function| function.name.to_string()),
+            type_arguments: type_arguments_to_json(type_arguments),
+            arguments: JsonValue(serde_json::Value::Array(arguments)),
+            success: user_transaction.info.success,
+            inserted_at: chrono::Utc::now().naive_utc(),
+        })
+    }
+}
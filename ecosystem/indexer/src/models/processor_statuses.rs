@@ -14,6 +14,10 @@ pub struct ProcessorStatus {
     pub success: bool,
     pub details: Option<String>,
     pub last_updated: chrono::NaiveDateTime,
+    /// How many times the version has been retried since it first failed
+    pub retry_count: i32,
+    /// Set once the version has failed too many times to be retried again
+    pub dead_letter: bool,
 }
 
 impl ProcessorStatus {
@@ -24,6 +28,8 @@ impl ProcessorStatus {
             success,
             details,
             last_updated: chrono::Utc::now().naive_utc(),
+            retry_count: 0,
+            dead_letter: false,
         }
     }
 
@@ -36,10 +42,11 @@ impl ProcessorStatus {
         )
     }
 
-    pub fn from_transaction_processing_err(tpe: &TransactionProcessingError) -> Self {
-        let (error, version, name) = tpe.inner();
+    /// The status of `version`, which was part of the batch that failed with `tpe`
+    pub fn from_transaction_processing_err(tpe: &TransactionProcessingError, version: u64) -> Self {
+        let (error, _, name) = tpe.inner();
 
-        Self::new(name, *version as i64, false, Some(error.to_string()))
+        Self::new(name, version as i64, false, Some(error.to_string()))
     }
 
     pub fn for_mark_started(name: &'static str, version: i64) -> Self {
@@ -47,5 +54,24 @@ impl ProcessorStatus {
     }
 }
 
+/// A version a processor failed on, and is still to retry
+#[derive(Debug, PartialEq, Queryable)]
+pub struct FailedVersion {
+    pub version: i64,
+    pub retry_count: i32,
+    /// When the version last failed
+    pub last_updated: chrono::NaiveDateTime,
+}
+
+/// Versions `gap_start..=gap_end` that a processor has no status for, although
+/// it has statuses for versions on both sides
+#[derive(Debug, PartialEq, QueryableByName)]
+pub struct VersionGap {
+    #[sql_type = "diesel::sql_types::BigInt"]
+    pub gap_start: i64,
+    #[sql_type = "diesel::sql_types::BigInt"]
+    pub gap_end: i64,
+}
+
 // Prevent conflicts with other things named `ProcessorStatus`
 pub type ProcessorStatusModel = ProcessorStatus;
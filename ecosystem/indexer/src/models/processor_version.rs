@@ -0,0 +1,50 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    database::{upsert, DbPoolConnection},
+    schema::processor_versions,
+};
+use diesel::{prelude::*, RunQueryDsl};
+use serde::Serialize;
+
+/// The schema version a processor's rows were written with
+#[derive(Debug, Identifiable, Insertable, Queryable, Serialize, Clone, PartialEq)]
+#[diesel(table_name = "processor_versions")]
+#[primary_key(name)]
+pub struct ProcessorVersion {
+    pub name: String,
+    pub schema_version: i32,
+    pub updated_at: chrono::NaiveDateTime,
+}
+
+impl ProcessorVersion {
+    pub fn new(name: &str, schema_version: i32) -> Self {
+        Self {
+            name: name.to_string(),
+            schema_version,
+            updated_at: chrono::Utc::now().naive_utc(),
+        }
+    }
+
+    pub fn get(conn: &DbPoolConnection, name: &str) -> diesel::QueryResult<Option<Self>> {
+        processor_versions::table
+            .find(name)
+            .first::<Self>(conn)
+            .optional()
+    }
+
+    /// Records this version, replacing the one previously recorded for the processor
+    pub fn upsert(&self, conn: &DbPoolConnection) -> diesel::QueryResult<usize> {
+        upsert!(
+            processor_versions::table,
+            self,
+            "processor_versions_pkey",
+            (
+                processor_versions::schema_version,
+                processor_versions::updated_at,
+            )
+        )
+        .execute(conn)
+    }
+}
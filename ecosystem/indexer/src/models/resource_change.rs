@@ -0,0 +1,163 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{database::JsonValue, schema::resource_changes};
+use aptos_rest_client::{
+    aptos_api_types::{
+        DeleteResource, MoveStructTag, WriteResource, WriteSetChange as APIWriteSetChange,
+    },
+    Transaction,
+};
+use serde::Serialize;
+
+/// A resource written or deleted by a transaction
+#[derive(Debug, Identifiable, Insertable, Queryable, Serialize, Clone, PartialEq)]
+#[diesel(table_name = "resource_changes")]
+#[primary_key(transaction_version, write_set_change_index)]
+pub struct ResourceChange {
+    pub transaction_version: i64,
+    /// The position of the change in the transaction's write set
+    pub write_set_change_index: i64,
+    pub address: String,
+    /// The resource's struct tag, e.g. `0x1::coin::CoinStore<0x1::aptos_coin::AptosCoin>`
+    pub resource_type: String,
+    /// The parts of the struct tag, without its type parameters
+    pub type_address: String,
+    pub type_module: String,
+    pub type_name: String,
+    pub is_deletion: bool,
+    /// The value of the resource after the write, `None` if it was deleted
+    pub data: Option<JsonValue>,
+    pub inserted_at: chrono::NaiveDateTime,
+}
+
+impl ResourceChange {
+    /// Columns an inserted row binds, see `insert_chunks`
+    pub const NUM_COLUMNS: usize = 10;
+
+    fn new(
+        transaction_version: i64,
+        write_set_change_index: i64,
+        address: String,
+        tag: &MoveStructTag,
+        data: Option<serde_json::Value>,
+    ) -> Self {
+        Self {
+            transaction_version,
+            write_set_change_index,
+            address,
+            resource_type: tag.to_string(),
+            type_address: tag.address.to_string(),
+            type_module: tag.module.to_string(),
+            type_name: tag.name.to_string(),
+            is_deletion: data.is_none(),
+            data: data.map(JsonValue),
+            inserted_at: chrono::Utc::now().naive_utc(),
+        }
+    }
+
+    /// The resource change of a write set change, if it changes a resource rather than a module or table item
+    pub fn from_write_set_change(
+        write_set_change: &APIWriteSetChange,
+        transaction_version: i64,
+        write_set_change_index: i64,
+    ) -> Option<Self> {
+        match write_set_change {
+            APIWriteSetChange::WriteResource(WriteResource { address, data, .. }) => {
+                Some(Self::new(
+                    transaction_version,
+                    write_set_change_index,
+                    address.to_string(),
+                    &data.typ,
+                    Some(serde_json::to_value(&data.data).unwrap()),
+                ))
+            }
+            APIWriteSetChange::DeleteResource(DeleteResource {
+                address, resource, ..
+            }) => Some(Self::new(
+                transaction_version,
+                write_set_change_index,
+                address.to_string(),
+                resource,
+                None,
+            )),
+            _ => None,
+        }
+    }
+
+    /// The resources a transaction wrote or deleted. Pending transactions have none
+    pub fn from_transaction(transaction: &Transaction) -> Vec<Self> {
+        let (version, info) = match (transaction.version(), transaction.transaction_info()) {
+            (Some(version), Ok(info)) => (version as i64, info),
+            _ => return vec![],
+        };
+        info.changes
+            .iter()
+            .enumerate()
+            .filter_map(|(index, change)| {
+                Self::from_write_set_change(change, version, index as i64)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_from_write_set_change() {
+        let write: APIWriteSetChange = serde_json::from_value(json!({
+            "type": "write_resource",
+            "address": "0x1",
+            "state_key_hash": "3502b05382fba777545b45a0a9d40e86cdde7c3afbde19c748ce8b5f142c2b46",
+            "data": {
+                "type": "0x1::coin::CoinStore<0x1::aptos_coin::AptosCoin>",
+                "data": { "coin": { "value": "1000" } }
+            }
+        }))
+        .unwrap();
+        let change = ResourceChange::from_write_set_change(&write, 10, 2).unwrap();
+        assert_eq!(change.transaction_version, 10);
+        assert_eq!(change.write_set_change_index, 2);
+        assert_eq!(change.address, "0x1");
+        assert_eq!(
+            change.resource_type,
+            "0x1::coin::CoinStore<0x1::aptos_coin::AptosCoin>"
+        );
+        assert_eq!(
+            (
+                change.type_address.as_str(),
+                change.type_module.as_str(),
+                change.type_name.as_str()
+            ),
+            ("0x1", "coin", "CoinStore")
+        );
+        assert!(!change.is_deletion);
+        assert_eq!(
+            change.data,
+            Some(JsonValue(json!({ "coin": { "value": "1000" } })))
+        );
+
+        let delete: APIWriteSetChange = serde_json::from_value(json!({
+            "type": "delete_resource",
+            "address": "0x1",
+            "state_key_hash": "3502b05382fba777545b45a0a9d40e86cdde7c3afbde19c748ce8b5f142c2b46",
+            "resource": "0x1::coin::CoinStore<0x1::aptos_coin::AptosCoin>"
+        }))
+        .unwrap();
+        let change = ResourceChange::from_write_set_change(&delete, 11, 0).unwrap();
+        assert!(change.is_deletion);
+        assert_eq!(change.data, None);
+
+        let module: APIWriteSetChange = serde_json::from_value(json!({
+            "type": "delete_module",
+            "address": "0x1",
+            "state_key_hash": "e428253ccf0b18f3d8300c6a0d29de93abcdc526e88728abeb85d57aec558935",
+            "module": "0x1::chain_id"
+        }))
+        .unwrap();
+        assert_eq!(ResourceChange::from_write_set_change(&module, 11, 1), None);
+    }
+}
@@ -110,6 +110,8 @@ pub struct DepositEventType {
 pub struct CreationEventType {
     pub id: TokenId,
     pub token_data: TokenData,
+    #[serde(deserialize_with = "types::deserialize_from_string")]
+    pub initial_balance: i64,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -141,7 +143,7 @@ pub enum TokenEvent {
 
 impl TokenEvent {
     pub fn from_event(event: &Event) -> Option<TokenEvent> {
-        let data = event.data.clone();
+        let data = event.data.0.clone();
         match event.type_.as_str() {
             "0x1::token::WithdrawEvent" => {
                 let event = serde_json::from_value::<WithdrawEventType>(data).unwrap();
@@ -159,7 +161,24 @@ impl TokenEvent {
                 let event = serde_json::from_value::<CreateCollectionEventType>(data).unwrap();
                 Some(TokenEvent::CollectionCreationEvent(event))
             }
+            "0x1::token::MintTokenEvent" => {
+                let event = serde_json::from_value::<MintEventType>(data).unwrap();
+                Some(TokenEvent::MintEvent(event))
+            }
             _ => None,
         }
     }
+
+    /// The token and amount the event is about, if any
+    pub fn token_id_and_amount(&self) -> (Option<String>, Option<i64>) {
+        match self {
+            TokenEvent::WithdrawEvent(event) => (Some(event.id.to_string()), Some(event.amount)),
+            TokenEvent::DepositEvent(event) => (Some(event.id.to_string()), Some(event.amount)),
+            TokenEvent::CreationEvent(event) => {
+                (Some(event.id.to_string()), Some(event.initial_balance))
+            }
+            TokenEvent::MintEvent(event) => (Some(event.id.to_string()), Some(event.amount)),
+            TokenEvent::CollectionCreationEvent(_) | TokenEvent::BurnEvent => (None, None),
+        }
+    }
 }
@@ -1,2 +1,22 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
+
+use crate::schema::token_activities;
+use serde::Serialize;
+
+/// A token event, kept so the history of a token can be read back
+#[derive(Debug, Identifiable, Insertable, Queryable, Serialize, Clone)]
+#[diesel(table_name = "token_activities")]
+#[primary_key(event_key, sequence_number)]
+pub struct TokenActivity {
+    pub event_key: String,
+    pub sequence_number: i64,
+    /// The account that emitted the event, e.g. the receiver of a deposit
+    pub account: String,
+    pub token_id: Option<String>,
+    pub event_type: Option<String>,
+    pub amount: Option<i64>,
+    pub created_at: chrono::NaiveDateTime,
+    pub inserted_at: chrono::NaiveDateTime,
+    pub transaction_hash: String,
+}
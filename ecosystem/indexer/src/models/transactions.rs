@@ -5,7 +5,7 @@
 #![allow(clippy::unused_unit)]
 
 use crate::{
-    database::PgPoolConnection,
+    database::{DbPoolConnection, JsonValue},
     models::{events::EventModel, write_set_changes::WriteSetChangeModel},
     schema::{block_metadata_transactions, transactions, user_transactions},
 };
@@ -25,7 +25,7 @@ use serde::Serialize;
 pub struct Transaction {
     #[diesel(column_name = type)]
     pub type_: String,
-    pub payload: serde_json::Value,
+    pub payload: JsonValue,
     pub version: i64,
     pub hash: String,
     pub state_root_hash: String,
@@ -39,10 +39,13 @@ pub struct Transaction {
 }
 
 impl Transaction {
+    /// Columns an inserted row binds, see `insert_chunks`
+    pub const NUM_COLUMNS: usize = 11;
+
     pub fn get_many_by_version(
         start_version: i64,
         number_to_get: i64,
-        connection: &PgPoolConnection,
+        connection: &DbPoolConnection,
     ) -> diesel::QueryResult<
         Vec<(
             Transaction,
@@ -93,7 +96,7 @@ impl Transaction {
 
     pub fn get_by_version(
         version: u64,
-        connection: &PgPoolConnection,
+        connection: &DbPoolConnection,
     ) -> diesel::QueryResult<(
         Transaction,
         Option<UserTransaction>,
@@ -119,7 +122,7 @@ impl Transaction {
 
     pub fn get_by_hash(
         transaction_hash: &str,
-        connection: &PgPoolConnection,
+        connection: &DbPoolConnection,
     ) -> diesel::QueryResult<(
         Transaction,
         Option<UserTransaction>,
@@ -145,7 +148,7 @@ impl Transaction {
 
     fn get_details_for_transaction(
         &self,
-        connection: &PgPoolConnection,
+        connection: &DbPoolConnection,
     ) -> diesel::QueryResult<(
         Option<UserTransaction>,
         Option<BlockMetadataTransaction>,
@@ -261,7 +264,7 @@ impl Transaction {
     ) -> Self {
         Self {
             type_,
-            payload,
+            payload: JsonValue(payload),
             version: *info.version.inner() as i64,
             hash: info.hash.to_string(),
             state_root_hash: info.state_root_hash.to_string(),
@@ -281,7 +284,7 @@ impl Transaction {
 #[diesel(table_name = "user_transactions")]
 pub struct UserTransaction {
     pub hash: String,
-    pub signature: serde_json::Value,
+    pub signature: JsonValue,
     pub sender: String,
     pub sequence_number: i64,
     pub max_gas_amount: i64,
@@ -298,10 +301,13 @@ pub struct UserTransaction {
 }
 
 impl UserTransaction {
+    /// Columns an inserted row binds, see `insert_chunks`
+    pub const NUM_COLUMNS: usize = 9;
+
     pub fn from_transaction(tx: &APIUserTransaction) -> Self {
         Self {
             hash: tx.info.hash.to_string(),
-            signature: serde_json::to_value(&tx.request.signature).unwrap(),
+            signature: JsonValue(serde_json::to_value(&tx.request.signature).unwrap()),
             sender: tx.request.sender.inner().to_hex_literal(),
             sequence_number: *tx.request.sequence_number.inner() as i64,
             max_gas_amount: *tx.request.max_gas_amount.inner() as i64,
@@ -324,32 +330,39 @@ pub struct BlockMetadataTransaction {
     pub hash: String,
     pub id: String,
     pub round: i64,
-    pub previous_block_votes: serde_json::Value,
+    pub previous_block_votes: JsonValue,
     pub proposer: String,
     pub timestamp: chrono::NaiveDateTime,
 
     // Default time columns
     pub inserted_at: chrono::NaiveDateTime,
     pub epoch: i64,
-    pub previous_block_votes_bitmap: serde_json::Value,
-    pub failed_proposer_indices: serde_json::Value,
+    pub previous_block_votes_bitmap: JsonValue,
+    pub failed_proposer_indices: JsonValue,
 }
 
 impl BlockMetadataTransaction {
+    /// Columns an inserted row binds, see `insert_chunks`
+    pub const NUM_COLUMNS: usize = 10;
+
     pub fn from_transaction(tx: &APIBlockMetadataTransaction) -> Self {
         Self {
             hash: tx.info.hash.to_string(),
             id: tx.id.to_string(),
             round: *tx.round.inner() as i64,
             // TODO: Deprecated, use previous_block_votes_bitmap instead. Column kept to not break indexer users (e.g., explorer), writing an empty vector.
-            previous_block_votes: serde_json::to_value(vec![] as Vec<Address>).unwrap(),
+            previous_block_votes: JsonValue(serde_json::to_value(vec![] as Vec<Address>).unwrap()),
             proposer: tx.proposer.inner().to_hex_literal(),
             // time is in milliseconds, but chronos wants seconds
             timestamp: parse_timestamp(tx.timestamp, tx.info.version),
             inserted_at: chrono::Utc::now().naive_utc(),
             epoch: *tx.epoch.inner() as i64,
-            previous_block_votes_bitmap: serde_json::to_value(&tx.previous_block_votes).unwrap(),
-            failed_proposer_indices: serde_json::to_value(&tx.failed_proposer_indices).unwrap(),
+            previous_block_votes_bitmap: JsonValue(
+                serde_json::to_value(&tx.previous_block_votes).unwrap(),
+            ),
+            failed_proposer_indices: JsonValue(
+                serde_json::to_value(&tx.failed_proposer_indices).unwrap(),
+            ),
         }
     }
 }
@@ -1,7 +1,7 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{models::transactions::Transaction, schema::write_set_changes};
+use crate::{database::JsonValue, models::transactions::Transaction, schema::write_set_changes};
 use aptos_rest_client::aptos_api_types::{
     DeleteModule, DeleteResource, DeleteTableItem, WriteModule, WriteResource,
     WriteSetChange as APIWriteSetChange, WriteTableItem,
@@ -19,15 +19,18 @@ pub struct WriteSetChange {
     #[diesel(column_name = type)]
     pub type_: String,
     pub address: String,
-    pub module: serde_json::Value,
-    pub resource: serde_json::Value,
-    pub data: serde_json::Value,
+    pub module: JsonValue,
+    pub resource: JsonValue,
+    pub data: JsonValue,
 
     // Default time columns
     pub inserted_at: chrono::NaiveDateTime,
 }
 
 impl WriteSetChange {
+    /// Columns an inserted row binds, see `insert_chunks`
+    pub const NUM_COLUMNS: usize = 8;
+
     pub fn from_write_set_change(
         transaction_hash: String,
         write_set_change: &APIWriteSetChange,
@@ -42,7 +45,7 @@ impl WriteSetChange {
                 hash: state_key_hash.clone(),
                 type_: write_set_change.type_str().to_string(),
                 address: address.to_string(),
-                module: serde_json::to_value(module).unwrap(),
+                module: JsonValue(serde_json::to_value(module).unwrap()),
                 resource: Default::default(),
                 data: Default::default(),
                 inserted_at: chrono::Utc::now().naive_utc(),
@@ -57,7 +60,7 @@ impl WriteSetChange {
                 type_: write_set_change.type_str().to_string(),
                 address: address.to_string(),
                 module: Default::default(),
-                resource: serde_json::to_value(resource).unwrap(),
+                resource: JsonValue(serde_json::to_value(resource).unwrap()),
                 data: Default::default(),
                 inserted_at: chrono::Utc::now().naive_utc(),
             },
@@ -73,10 +76,10 @@ impl WriteSetChange {
                 address: "".to_owned(),
                 module: Default::default(),
                 resource: Default::default(),
-                data: json!({
+                data: JsonValue(json!({
                     "handle": handle,
                     "key": key,
-                }),
+                })),
                 inserted_at: chrono::Utc::now().naive_utc(),
             },
             APIWriteSetChange::WriteModule(WriteModule {
@@ -90,7 +93,7 @@ impl WriteSetChange {
                 address: address.to_string(),
                 module: Default::default(),
                 resource: Default::default(),
-                data: serde_json::to_value(data).unwrap(),
+                data: JsonValue(serde_json::to_value(data).unwrap()),
                 inserted_at: chrono::Utc::now().naive_utc(),
             },
             APIWriteSetChange::WriteResource(WriteResource {
@@ -104,7 +107,7 @@ impl WriteSetChange {
                 address: address.to_string(),
                 module: Default::default(),
                 resource: Default::default(),
-                data: serde_json::to_value(data).unwrap(),
+                data: JsonValue(serde_json::to_value(data).unwrap()),
                 inserted_at: chrono::Utc::now().naive_utc(),
             },
             APIWriteSetChange::WriteTableItem(WriteTableItem {
@@ -120,11 +123,11 @@ impl WriteSetChange {
                 address: "".to_owned(),
                 module: Default::default(),
                 resource: Default::default(),
-                data: json!({
+                data: JsonValue(json!({
                     "handle": handle,
                     "key": key,
                     "value": value,
-                }),
+                })),
                 inserted_at: chrono::Utc::now().naive_utc(),
             },
         }
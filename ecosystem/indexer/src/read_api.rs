@@ -0,0 +1,296 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+//! A small read only REST API over the indexed data, so downstream services can
+//! consume the index without SQL credentials:
+//!
+//! - `GET /accounts/{address}/activity`: the coin deposits and withdrawals of an account
+//! - `GET /accounts/{address}/snapshot`: the resources of an account as of the latest snapshot at or below the
+//!   `version` query parameter, or the latest snapshot without one
+//! - `GET /tokens/{token_id}/history`: the events of a token
+//! - `GET /processor_status`: how far each processor got, and how many versions it failed or gave up on
+//!
+//! Lists are paged with the `start` (offset) and `limit` query parameters, and
+//! the response's `next_start` is where the next page starts, if any.
+
+use crate::{
+    database::DbPool,
+    models::{
+        account_snapshot::AccountSnapshot, coin::CoinActivity, token_activity::TokenActivity,
+    },
+    schema::{account_snapshots, coin_activities, token_activities},
+};
+use aptos_rest_client::aptos_api_types::Address;
+use diesel::{
+    sql_types::{BigInt, Nullable, Text},
+    ExpressionMethods, OptionalExtension, QueryDsl, RunQueryDsl,
+};
+use percent_encoding::percent_decode_str;
+use serde::{Deserialize, Serialize};
+use std::{convert::Infallible, net::SocketAddr};
+use warp::{
+    http::StatusCode,
+    reply::{self, Response},
+    Filter, Rejection, Reply,
+};
+
+/// Page size when the client doesn't ask for one
+const DEFAULT_PAGE_SIZE: i64 = 25;
+/// The most items a page can hold
+const MAX_PAGE_SIZE: i64 = 100;
+
+#[derive(Debug, Default, Deserialize)]
+pub struct PageParams {
+    pub start: Option<i64>,
+    pub limit: Option<i64>,
+}
+
+impl PageParams {
+    fn offset(&self) -> i64 {
+        self.start.unwrap_or(0).max(0)
+    }
+
+    fn limit(&self) -> i64 {
+        self.limit
+            .unwrap_or(DEFAULT_PAGE_SIZE)
+            .clamp(1, MAX_PAGE_SIZE)
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub next_start: Option<i64>,
+}
+
+impl<T> Page<T> {
+    /// Builds the page from rows loaded with one more than the page's limit,
+    /// the extra row only telling whether there is a next page
+    fn from_rows(params: &PageParams, mut rows: Vec<T>) -> Self {
+        let limit = params.limit();
+        let next_start = if rows.len() as i64 > limit {
+            rows.truncate(limit as usize);
+            Some(params.offset() + limit)
+        } else {
+            None
+        };
+        Self {
+            items: rows,
+            next_start,
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct SnapshotParams {
+    pub version: Option<i64>,
+}
+
+/// The resources of an account as of a version
+#[derive(Debug, Serialize)]
+pub struct Snapshot {
+    pub snapshot_version: i64,
+    pub resources: Vec<AccountSnapshot>,
+}
+
+#[derive(Debug, QueryableByName, Serialize)]
+pub struct ProcessorStatusSummary {
+    #[sql_type = "Text"]
+    pub name: String,
+    /// The highest version the processor has processed successfully
+    #[sql_type = "Nullable<BigInt>"]
+    pub latest_version: Option<i64>,
+    #[sql_type = "BigInt"]
+    pub failed_versions: i64,
+    /// Failed versions that are no longer retried
+    #[sql_type = "BigInt"]
+    pub dead_letter_versions: i64,
+}
+
+fn account_activity(
+    pool: &DbPool,
+    address: Address,
+    params: &PageParams,
+) -> anyhow::Result<Page<CoinActivity>> {
+    // Addresses are stored the way the node's API renders them
+    let address = address.to_string();
+    let rows = coin_activities::table
+        .filter(coin_activities::owner_address.eq(address))
+        .order((
+            coin_activities::transaction_version,
+            coin_activities::event_key,
+            coin_activities::sequence_number,
+        ))
+        .offset(params.offset())
+        .limit(params.limit() + 1)
+        .load::<CoinActivity>(&pool.get()?)?;
+    Ok(Page::from_rows(params, rows))
+}
+
+fn account_snapshot(
+    pool: &DbPool,
+    address: Address,
+    params: &SnapshotParams,
+) -> anyhow::Result<Option<Snapshot>> {
+    let address = address.to_string();
+    let conn = pool.get()?;
+    let snapshot_version = account_snapshots::table
+        .filter(account_snapshots::address.eq(&address))
+        .filter(account_snapshots::snapshot_version.le(params.version.unwrap_or(i64::MAX)))
+        .select(account_snapshots::snapshot_version)
+        .order(account_snapshots::snapshot_version.desc())
+        .first::<i64>(&conn)
+        .optional()?;
+    let snapshot_version = match snapshot_version {
+        Some(snapshot_version) => snapshot_version,
+        None => return Ok(None),
+    };
+    let resources = account_snapshots::table
+        .filter(account_snapshots::address.eq(&address))
+        .filter(account_snapshots::snapshot_version.eq(snapshot_version))
+        .order(account_snapshots::resource_type)
+        .load::<AccountSnapshot>(&conn)?;
+    Ok(Some(Snapshot {
+        snapshot_version,
+        resources,
+    }))
+}
+
+fn token_history(
+    pool: &DbPool,
+    token_id: &str,
+    params: &PageParams,
+) -> anyhow::Result<Page<TokenActivity>> {
+    let rows = token_activities::table
+        .filter(token_activities::token_id.eq(token_id))
+        .order((
+            token_activities::created_at,
+            token_activities::event_key,
+            token_activities::sequence_number,
+        ))
+        .offset(params.offset())
+        .limit(params.limit() + 1)
+        .load::<TokenActivity>(&pool.get()?)?;
+    Ok(Page::from_rows(params, rows))
+}
+
+fn processor_status(pool: &DbPool) -> anyhow::Result<Vec<ProcessorStatusSummary>> {
+    Ok(diesel::sql_query(
+        "SELECT name, \
+         MAX(version) FILTER (WHERE success) AS latest_version, \
+         COUNT(*) FILTER (WHERE NOT success) AS failed_versions, \
+         COUNT(*) FILTER (WHERE dead_letter) AS dead_letter_versions \
+         FROM processor_statuses GROUP BY name ORDER BY name",
+    )
+    .load::<ProcessorStatusSummary>(&pool.get()?)?)
+}
+
+fn error_response(status: StatusCode, message: String) -> Response {
+    reply::with_status(
+        reply::json(&serde_json::json!({ "message": message })),
+        status,
+    )
+    .into_response()
+}
+
+fn respond<T: Serialize>(result: anyhow::Result<T>) -> Response {
+    match result {
+        Ok(body) => reply::json(&body).into_response(),
+        Err(err) => {
+            aptos_logger::warn!("Error serving read API request: {:?}", err);
+            error_response(StatusCode::INTERNAL_SERVER_ERROR, err.to_string())
+        }
+    }
+}
+
+fn with_pool(pool: DbPool) -> impl Filter<Extract = (DbPool,), Error = Infallible> + Clone {
+    warp::any().map(move || pool.clone())
+}
+
+pub fn routes(pool: DbPool) -> impl Filter<Extract = (Response,), Error = Rejection> + Clone {
+    let account_activity = warp::path!("accounts" / String / "activity")
+        .and(warp::get())
+        .and(warp::query::<PageParams>())
+        .and(with_pool(pool.clone()))
+        .map(
+            |address: String, params: PageParams, pool: DbPool| match address.parse() {
+                Ok(address) => respond(account_activity(&pool, address, &params)),
+                Err(err) => error_response(
+                    StatusCode::BAD_REQUEST,
+                    format!("invalid address {}: {}", address, err),
+                ),
+            },
+        );
+    let account_snapshot = warp::path!("accounts" / String / "snapshot")
+        .and(warp::get())
+        .and(warp::query::<SnapshotParams>())
+        .and(with_pool(pool.clone()))
+        .map(
+            |address: String, params: SnapshotParams, pool: DbPool| match address.parse() {
+                Ok(address) => match account_snapshot(&pool, address, &params) {
+                    Ok(None) => error_response(
+                        StatusCode::NOT_FOUND,
+                        format!("no snapshot of {} at or below the version", address),
+                    ),
+                    result => respond(result),
+                },
+                Err(err) => error_response(
+                    StatusCode::BAD_REQUEST,
+                    format!("invalid address {}: {}", address, err),
+                ),
+            },
+        );
+    let token_history = warp::path!("tokens" / String / "history")
+        .and(warp::get())
+        .and(warp::query::<PageParams>())
+        .and(with_pool(pool.clone()))
+        .map(|token_id: String, params: PageParams, pool: DbPool| {
+            // Token ids are "creator::collection::name", names may need percent encoding
+            let token_id = percent_decode_str(&token_id).decode_utf8_lossy();
+            respond(token_history(&pool, &token_id, &params))
+        });
+    let processor_status = warp::path!("processor_status")
+        .and(warp::get())
+        .and(with_pool(pool))
+        .map(|pool: DbPool| respond(processor_status(&pool)));
+
+    account_activity
+        .or(account_snapshot)
+        .unify()
+        .or(token_history)
+        .unify()
+        .or(processor_status)
+        .unify()
+}
+
+pub async fn serve(pool: DbPool, address: SocketAddr) {
+    aptos_logger::info!("Serving the read API on {}", address);
+    warp::serve(routes(pool)).run(address).await;
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_page_from_rows() {
+        let params = PageParams {
+            start: Some(10),
+            limit: Some(3),
+        };
+        let page = Page::from_rows(&params, vec![1, 2, 3, 4]);
+        assert_eq!(page.items, vec![1, 2, 3]);
+        assert_eq!(page.next_start, Some(13));
+
+        let page = Page::from_rows(&params, vec![1, 2]);
+        assert_eq!(page.next_start, None);
+
+        assert_eq!(PageParams::default().limit(), DEFAULT_PAGE_SIZE);
+        let params = PageParams {
+            start: Some(-5),
+            limit: Some(1000),
+        };
+        assert_eq!(params.offset(), 0);
+        assert_eq!(params.limit(), MAX_PAGE_SIZE);
+    }
+}
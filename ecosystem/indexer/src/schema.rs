@@ -2,6 +2,22 @@
 // SPDX-License-Identifier: Apache-2.0
 
 table! {
+    use diesel::sql_types::*;
+    use crate::database::Jsonb;
+
+    account_snapshots (address, snapshot_version, resource_type) {
+        address -> Varchar,
+        snapshot_version -> Int8,
+        resource_type -> Text,
+        data -> Jsonb,
+        inserted_at -> Timestamp,
+    }
+}
+
+table! {
+    use diesel::sql_types::*;
+    use crate::database::Jsonb;
+
     block_metadata_transactions (hash) {
         hash -> Varchar,
         id -> Varchar,
@@ -16,6 +32,30 @@ table! {
     }
 }
 
+table! {
+    coin_activities (event_key, sequence_number) {
+        event_key -> Varchar,
+        sequence_number -> Int8,
+        owner_address -> Varchar,
+        coin_type -> Varchar,
+        activity_type -> Varchar,
+        amount -> Int8,
+        transaction_version -> Int8,
+        transaction_timestamp -> Timestamp,
+        inserted_at -> Timestamp,
+    }
+}
+
+table! {
+    coin_balances (owner_address, coin_type) {
+        owner_address -> Varchar,
+        coin_type -> Varchar,
+        amount -> Int8,
+        transaction_version -> Int8,
+        inserted_at -> Timestamp,
+    }
+}
+
 table! {
     collections (collection_id) {
         collection_id -> Varchar,
@@ -30,6 +70,9 @@ table! {
 }
 
 table! {
+    use diesel::sql_types::*;
+    use crate::database::Jsonb;
+
     events (key, sequence_number) {
         transaction_hash -> Varchar,
         key -> Varchar,
@@ -38,10 +81,34 @@ table! {
         type_ -> Text,
         data -> Jsonb,
         inserted_at -> Timestamp,
+        account_address -> Varchar,
+        creation_number -> Int8,
+        type_address -> Nullable<Varchar>,
+        type_module -> Nullable<Varchar>,
+        type_name -> Nullable<Varchar>,
+    }
+}
+
+table! {
+    indexer_starts (inserted_at) {
+        strategy -> Varchar,
+        start_version -> Int8,
+        inserted_at -> Timestamp,
+    }
+}
+
+table! {
+    ledger_infos (chain_id) {
+        chain_id -> Int8,
+        genesis_hash -> Varchar,
+        inserted_at -> Timestamp,
     }
 }
 
 table! {
+    use diesel::sql_types::*;
+    use crate::database::Jsonb;
+
     metadatas (token_id) {
         token_id -> Varchar,
         name -> Nullable<Varchar>,
@@ -69,6 +136,25 @@ table! {
     }
 }
 
+table! {
+    use diesel::sql_types::*;
+    use crate::database::Jsonb;
+
+    payloads (transaction_hash) {
+        transaction_hash -> Varchar,
+        transaction_version -> Int8,
+        sender -> Varchar,
+        payload_type -> Varchar,
+        module_address -> Nullable<Varchar>,
+        module_name -> Nullable<Varchar>,
+        function_name -> Nullable<Varchar>,
+        type_arguments -> Jsonb,
+        arguments -> Jsonb,
+        success -> Bool,
+        inserted_at -> Timestamp,
+    }
+}
+
 table! {
     processor_statuses (name, version) {
         name -> Varchar,
@@ -76,6 +162,34 @@ table! {
         success -> Bool,
         details -> Nullable<Text>,
         last_updated -> Timestamp,
+        retry_count -> Int4,
+        dead_letter -> Bool,
+    }
+}
+
+table! {
+    processor_versions (name) {
+        name -> Varchar,
+        schema_version -> Int4,
+        updated_at -> Timestamp,
+    }
+}
+
+table! {
+    use diesel::sql_types::*;
+    use crate::database::Jsonb;
+
+    resource_changes (transaction_version, write_set_change_index) {
+        transaction_version -> Int8,
+        write_set_change_index -> Int8,
+        address -> Varchar,
+        resource_type -> Text,
+        type_address -> Varchar,
+        type_module -> Varchar,
+        type_name -> Varchar,
+        is_deletion -> Bool,
+        data -> Nullable<Jsonb>,
+        inserted_at -> Timestamp,
     }
 }
 
@@ -86,7 +200,7 @@ table! {
         account -> Varchar,
         token_id -> Nullable<Varchar>,
         event_type -> Nullable<Varchar>,
-        amount -> Nullable<Numeric>,
+        amount -> Nullable<Int8>,
         created_at -> Timestamp,
         inserted_at -> Timestamp,
         transaction_hash -> Varchar,
@@ -110,6 +224,9 @@ table! {
 }
 
 table! {
+    use diesel::sql_types::*;
+    use crate::database::Jsonb;
+
     transactions (hash) {
         #[sql_name = "type"]
         type_ -> Varchar,
@@ -127,6 +244,9 @@ table! {
 }
 
 table! {
+    use diesel::sql_types::*;
+    use crate::database::Jsonb;
+
     user_transactions (hash) {
         hash -> Varchar,
         signature -> Jsonb,
@@ -141,6 +261,9 @@ table! {
 }
 
 table! {
+    use diesel::sql_types::*;
+    use crate::database::Jsonb;
+
     write_set_changes (transaction_hash, hash) {
         transaction_hash -> Varchar,
         hash -> Varchar,
@@ -155,12 +278,20 @@ table! {
 }
 
 allow_tables_to_appear_in_same_query!(
+    account_snapshots,
     block_metadata_transactions,
+    coin_activities,
+    coin_balances,
     collections,
     events,
+    indexer_starts,
+    ledger_infos,
     metadatas,
     ownerships,
+    payloads,
     processor_statuses,
+    processor_versions,
+    resource_changes,
     token_activities,
     tokens,
     transactions,
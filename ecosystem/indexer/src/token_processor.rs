@@ -2,10 +2,10 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    database::{execute_with_better_error, PgDbPool, PgPoolConnection},
+    database::{execute_with_better_error, insert_or_ignore, DbPool, DbPoolConnection},
     indexer::{
-        errors::TransactionProcessingError, metadata_fetcher::MetaDataFetcher,
-        processing_result::ProcessingResult, transaction_processor::TransactionProcessor,
+        metadata_fetcher::MetaDataFetcher, sink::SinkRecord,
+        transaction_processor::TransactionProcessor,
     },
     models::{
         collection::Collection,
@@ -13,26 +13,27 @@ use crate::{
         metadata::Metadata,
         ownership::Ownership,
         token::{CreateCollectionEventType, CreationEventType, MintEventType, Token, TokenEvent},
+        token_activity::TokenActivity,
         transactions::{TransactionModel, UserTransaction},
     },
     schema,
     schema::{
-        ownerships::{dsl::amount as ownership_amount, ownership_id},
+        ownerships::dsl::amount as ownership_amount,
         tokens::dsl::{last_minted_at, supply, tokens},
     },
 };
 use aptos_rest_client::Transaction;
 use async_trait::async_trait;
-use diesel::{Connection, ExpressionMethods, QueryDsl, RunQueryDsl};
+use diesel::{ExpressionMethods, QueryDsl, RunQueryDsl};
 use futures::future::Either;
 use std::{fmt::Debug, sync::Arc};
 
 pub struct TokenTransactionProcessor {
-    connection_pool: PgDbPool,
+    connection_pool: DbPool,
 }
 
 impl TokenTransactionProcessor {
-    pub fn new(connection_pool: PgDbPool) -> Self {
+    pub fn new(connection_pool: DbPool) -> Self {
         Self { connection_pool }
     }
 }
@@ -48,13 +49,18 @@ impl Debug for TokenTransactionProcessor {
     }
 }
 
-fn update_mint_token(conn: &PgPoolConnection, event_data: MintEventType, txn: &UserTransaction) {
+fn update_mint_token(
+    conn: &DbPoolConnection,
+    event_data: MintEventType,
+    txn: &UserTransaction,
+) -> Result<(), diesel::result::Error> {
     let last_mint_time = txn.timestamp;
     let query = diesel::update(tokens.find(event_data.id.to_string())).set((
         supply.eq(supply + event_data.amount),
         last_minted_at.eq(last_mint_time),
     ));
-    query.execute(conn).expect("Error updating row in token");
+    query.execute(conn)?;
+    Ok(())
 }
 
 async fn get_all_metadata(uris: &Vec<(String, String)>, res: &mut Vec<Metadata>) {
@@ -70,7 +76,11 @@ async fn get_all_metadata(uris: &Vec<(String, String)>, res: &mut Vec<Metadata>)
     }
 }
 
-fn insert_token(conn: &PgPoolConnection, event_data: CreationEventType, txn: &UserTransaction) {
+fn insert_token(
+    conn: &DbPoolConnection,
+    event_data: CreationEventType,
+    txn: &UserTransaction,
+) -> Result<(), diesel::result::Error> {
     let token = Token {
         token_id: event_data.id.to_string(),
         creator: event_data.id.creator,
@@ -78,30 +88,27 @@ fn insert_token(conn: &PgPoolConnection, event_data: CreationEventType, txn: &Us
         name: event_data.id.name,
         description: event_data.token_data.description,
         max_amount: event_data.token_data.maximum.value,
-        supply: 1, //TODO add initial balance to event
+        supply: event_data.initial_balance,
         uri: event_data.token_data.uri,
         minted_at: txn.timestamp,
         inserted_at: chrono::Utc::now().naive_utc(),
         last_minted_at: txn.timestamp,
     };
-    execute_with_better_error(
-        conn,
-        diesel::insert_into(schema::tokens::table)
-            .values(&token)
-            .on_conflict_do_nothing(),
-    )
-    .expect("Error inserting row into token");
+    execute_with_better_error(conn, insert_or_ignore!(schema::tokens::table, &token))?;
+    Ok(())
 }
 
+#[cfg(not(feature = "sqlite"))]
 fn update_token_ownership(
-    conn: &PgPoolConnection,
+    conn: &DbPoolConnection,
     token_id: String,
+    owner: String,
     txn: &UserTransaction,
     amount_update: i64,
-) {
+) -> Result<(), diesel::result::Error> {
     let ownership = Ownership::new(
         token_id,
-        txn.sender.clone(),
+        owner,
         amount_update,
         txn.timestamp,
         chrono::Utc::now().naive_utc(),
@@ -110,18 +117,47 @@ fn update_token_ownership(
         conn,
         diesel::insert_into(schema::ownerships::table)
             .values(&ownership)
-            .on_conflict(ownership_id)
+            .on_conflict(diesel::pg::upsert::on_constraint("ownerships_pkey"))
             .do_update()
             .set(ownership_amount.eq(ownership_amount + ownership.amount)),
-    )
-    .expect("Error update token ownership");
+    )?;
+    Ok(())
+}
+
+/// diesel can't build an upsert computing from the existing row on SQLite, so the ownership is inserted if there
+/// was none to update
+#[cfg(feature = "sqlite")]
+fn update_token_ownership(
+    conn: &DbPoolConnection,
+    token_id: String,
+    owner: String,
+    txn: &UserTransaction,
+    amount_update: i64,
+) -> Result<(), diesel::result::Error> {
+    let ownership = Ownership::new(
+        token_id,
+        owner,
+        amount_update,
+        txn.timestamp,
+        chrono::Utc::now().naive_utc(),
+    );
+    let updated = diesel::update(schema::ownerships::table.find(&ownership.ownership_id))
+        .set(ownership_amount.eq(ownership_amount + ownership.amount))
+        .execute(conn)?;
+    if updated == 0 {
+        execute_with_better_error(
+            conn,
+            diesel::insert_into(schema::ownerships::table).values(&ownership),
+        )?;
+    }
+    Ok(())
 }
 
 fn insert_collection(
-    conn: &PgPoolConnection,
+    conn: &DbPoolConnection,
     event_data: CreateCollectionEventType,
     txn: &UserTransaction,
-) {
+) -> Result<(), diesel::result::Error> {
     let collection = Collection::new(
         event_data.creator,
         event_data.collection_name,
@@ -133,50 +169,108 @@ fn insert_collection(
     );
     execute_with_better_error(
         conn,
-        diesel::insert_into(schema::collections::table)
-            .values(&collection)
-            .on_conflict_do_nothing(),
-    )
-    .expect("Error inserting row into collections");
+        insert_or_ignore!(schema::collections::table, &collection),
+    )?;
+    Ok(())
+}
+
+fn token_activity(
+    event: &EventModel,
+    token_event: &TokenEvent,
+    account: String,
+    txn: &UserTransaction,
+) -> TokenActivity {
+    let (token_id, amount) = token_event.token_id_and_amount();
+    TokenActivity {
+        event_key: event.key.clone(),
+        sequence_number: event.sequence_number,
+        account,
+        token_id,
+        event_type: Some(event.type_.clone()),
+        amount,
+        created_at: txn.timestamp,
+        inserted_at: chrono::Utc::now().naive_utc(),
+        transaction_hash: event.transaction_hash.clone(),
+    }
+}
+
+fn insert_token_activity(
+    conn: &DbPoolConnection,
+    activity: &TokenActivity,
+) -> Result<(), diesel::result::Error> {
+    execute_with_better_error(
+        conn,
+        insert_or_ignore!(schema::token_activities::table, activity),
+    )?;
+    Ok(())
 }
 
 fn process_token_on_chain_data(
-    conn: &PgPoolConnection,
+    conn: &DbPoolConnection,
     events: &[EventModel],
     txn: &UserTransaction,
-    uris: &mut Vec<(String, String)>,
-) {
+) -> Result<(), diesel::result::Error> {
     // filter events to only keep token events
     let token_events = events
         .iter()
-        .map(TokenEvent::from_event)
-        .filter(|e| e.is_some())
-        .collect::<Vec<Option<TokenEvent>>>();
+        .filter_map(|e| TokenEvent::from_event(e).map(|token_event| (e, token_event)))
+        .collect::<Vec<(&EventModel, TokenEvent)>>();
     // for create token event, insert a new token to token table,
     // if token exists, increase the supply
-    for event in token_events {
-        match event.unwrap() {
+    for (event, token_event) in token_events {
+        // Deposits and withdrawals are emitted by the owner's token store, so
+        // the account of the event handle is the owner, not the sender.
+        let account = event.account_address.clone();
+        insert_token_activity(
+            conn,
+            &token_activity(event, &token_event, account.clone(), txn),
+        )?;
+        match token_event {
             TokenEvent::CreationEvent(event_data) => {
-                let uri = event_data.token_data.uri.clone();
-                let tid = event_data.id.to_string();
-                insert_token(conn, event_data, txn);
-                uris.push((tid, uri));
+                insert_token(conn, event_data, txn)?;
             }
             TokenEvent::MintEvent(event_data) => {
-                update_mint_token(conn, event_data, txn);
+                update_mint_token(conn, event_data, txn)?;
             }
             TokenEvent::CollectionCreationEvent(event_data) => {
-                insert_collection(conn, event_data, txn);
+                insert_collection(conn, event_data, txn)?;
             }
             TokenEvent::DepositEvent(event_data) => {
-                update_token_ownership(conn, event_data.id.to_string(), txn, event_data.amount);
+                update_token_ownership(
+                    conn,
+                    event_data.id.to_string(),
+                    account,
+                    txn,
+                    event_data.amount,
+                )?;
             }
             TokenEvent::WithdrawEvent(event_data) => {
-                update_token_ownership(conn, event_data.id.to_string(), txn, -event_data.amount);
+                update_token_ownership(
+                    conn,
+                    event_data.id.to_string(),
+                    account,
+                    txn,
+                    -event_data.amount,
+                )?;
             }
             _ => (),
         }
     }
+    Ok(())
+}
+
+/// The ids and uris of the tokens created by the transactions
+fn created_token_uris(transactions: &[Arc<Transaction>]) -> Vec<(String, String)> {
+    let mut uris = vec![];
+    for transaction in transactions {
+        let (_, _, maybe_events, _) = TransactionModel::from_transaction(transaction);
+        for event in maybe_events.unwrap_or_default() {
+            if let Some(TokenEvent::CreationEvent(event_data)) = TokenEvent::from_event(&event) {
+                uris.push((event_data.id.to_string(), event_data.token_data.uri));
+            }
+        }
+    }
+    uris
 }
 
 #[async_trait]
@@ -185,60 +279,85 @@ impl TransactionProcessor for TokenTransactionProcessor {
         "token_processor"
     }
 
-    async fn process_transaction(
-        &self,
-        transaction: Arc<Transaction>,
-    ) -> Result<ProcessingResult, TransactionProcessingError> {
-        let version = transaction.version().unwrap_or(0);
+    fn tables(&self) -> &'static [&'static str] {
+        &[
+            "collections",
+            "tokens",
+            "ownerships",
+            "metadatas",
+            "token_activities",
+        ]
+    }
 
+    fn process_transaction(
+        &self,
+        conn: &DbPoolConnection,
+        transaction: &Transaction,
+    ) -> Result<(), diesel::result::Error> {
         let (_, maybe_details_model, maybe_events, _) =
-            TransactionModel::from_transaction(&transaction);
+            TransactionModel::from_transaction(transaction);
 
-        let conn = self.get_conn();
-        let mut token_uris: Vec<(String, String)> = vec![];
-
-        let tx_result = conn.transaction::<(), diesel::result::Error, _>(|| {
-            if let Some(Either::Left(user_txn)) = maybe_details_model {
-                if let Some(events) = maybe_events {
-                    process_token_on_chain_data(&conn, &events, &user_txn, &mut token_uris);
-                }
+        if let Some(Either::Left(user_txn)) = maybe_details_model {
+            if let Some(events) = maybe_events {
+                process_token_on_chain_data(conn, &events, &user_txn)?;
             }
-            Ok(())
-        });
+        }
+        Ok(())
+    }
 
-        if let Err(err) = tx_result {
-            return Err(TransactionProcessingError::TransactionCommitError((
-                anyhow::Error::from(err),
-                version,
-                self.name(),
-            )));
-        };
+    fn sink_records(&self, transaction: &Transaction) -> Vec<SinkRecord> {
+        let version = transaction.version().unwrap_or(0);
+        let (_, maybe_details_model, maybe_events, _) =
+            TransactionModel::from_transaction(transaction);
 
+        match (maybe_details_model, maybe_events) {
+            (Some(Either::Left(user_txn)), Some(events)) => events
+                .iter()
+                .filter_map(|event| {
+                    let token_event = TokenEvent::from_event(event)?;
+                    let activity = token_activity(
+                        event,
+                        &token_event,
+                        event.account_address.clone(),
+                        &user_txn,
+                    );
+                    Some(SinkRecord::new(
+                        self.name(),
+                        "token_activity",
+                        version,
+                        &activity,
+                    ))
+                })
+                .collect(),
+            _ => vec![],
+        }
+    }
+
+    /// Token metadata lives off chain, so it is fetched once the tokens are committed
+    async fn after_commit(&self, transactions: &[Arc<Transaction>]) {
+        let token_uris = created_token_uris(transactions);
+        if token_uris.is_empty() {
+            return;
+        }
         let mut res: Vec<Metadata> = vec![];
         get_all_metadata(&token_uris, &mut res).await;
-        let tx_result = conn.transaction::<(), diesel::result::Error, _>(|| {
-            for metadata in res {
-                execute_with_better_error(
-                    &conn,
-                    diesel::insert_into(schema::metadatas::table)
-                        .values(&metadata)
-                        .on_conflict_do_nothing(),
-                )
-                .expect("Error inserting row into metadatas");
+        let conn = self.get_conn();
+        for metadata in res {
+            if let Err(err) = execute_with_better_error(
+                &conn,
+                insert_or_ignore!(schema::metadatas::table, &metadata),
+            ) {
+                aptos_logger::warn!(
+                    "[{}] Could not insert metadata of token {}: {:?}",
+                    self.name(),
+                    metadata.token_id,
+                    err
+                );
             }
-            Ok(())
-        });
-        match tx_result {
-            Ok(_) => Ok(ProcessingResult::new(self.name(), version)),
-            Err(err) => Err(TransactionProcessingError::TransactionCommitError((
-                anyhow::Error::from(err),
-                version,
-                self.name(),
-            ))),
         }
     }
 
-    fn connection_pool(&self) -> &PgDbPool {
+    fn connection_pool(&self) -> &DbPool {
         &self.connection_pool
     }
 }
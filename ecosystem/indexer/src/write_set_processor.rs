@@ -0,0 +1,94 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    database::{
+        execute_with_better_error, insert_chunks, insert_or_ignore, DbPool, DbPoolConnection,
+    },
+    indexer::{sink::SinkRecord, transaction_processor::TransactionProcessor},
+    models::resource_change::ResourceChange,
+    schema,
+};
+use aptos_rest_client::Transaction;
+use async_trait::async_trait;
+use std::{fmt::Debug, sync::Arc};
+
+/// Stores every resource each transaction writes or deletes, so the state of an
+/// account at any version can be read from the index rather than the node
+pub struct WriteSetTransactionProcessor {
+    connection_pool: DbPool,
+}
+
+impl WriteSetTransactionProcessor {
+    pub fn new(connection_pool: DbPool) -> Self {
+        Self { connection_pool }
+    }
+}
+
+impl Debug for WriteSetTransactionProcessor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let state = &self.connection_pool.state();
+        write!(
+            f,
+            "WriteSetTransactionProcessor {{ connections: {:?}  idle_connections: {:?} }}",
+            state.connections, state.idle_connections
+        )
+    }
+}
+
+fn insert_resource_changes(
+    conn: &DbPoolConnection,
+    changes: &[ResourceChange],
+) -> Result<(), diesel::result::Error> {
+    for chunk in insert_chunks(changes, ResourceChange::NUM_COLUMNS) {
+        execute_with_better_error(
+            conn,
+            insert_or_ignore!(schema::resource_changes::table, chunk),
+        )?;
+    }
+    Ok(())
+}
+
+#[async_trait]
+impl TransactionProcessor for WriteSetTransactionProcessor {
+    fn name(&self) -> &'static str {
+        "write_set_processor"
+    }
+
+    fn tables(&self) -> &'static [&'static str] {
+        &["resource_changes"]
+    }
+
+    fn process_transaction(
+        &self,
+        conn: &DbPoolConnection,
+        transaction: &Transaction,
+    ) -> Result<(), diesel::result::Error> {
+        insert_resource_changes(conn, &ResourceChange::from_transaction(transaction))
+    }
+
+    /// Writes the resource changes of the whole batch at once
+    fn process_batch(
+        &self,
+        conn: &DbPoolConnection,
+        transactions: &[Arc<Transaction>],
+    ) -> Result<(), diesel::result::Error> {
+        let changes: Vec<ResourceChange> = transactions
+            .iter()
+            .flat_map(|transaction| ResourceChange::from_transaction(transaction))
+            .collect();
+        insert_resource_changes(conn, &changes)
+    }
+
+    fn sink_records(&self, transaction: &Transaction) -> Vec<SinkRecord> {
+        let version = transaction.version().unwrap_or(0);
+        ResourceChange::from_transaction(transaction)
+            .iter()
+            .map(|change| SinkRecord::new(self.name(), "resource_change", version, change))
+            .collect()
+    }
+
+    fn connection_pool(&self) -> &DbPool {
+        &self.connection_pool
+    }
+}
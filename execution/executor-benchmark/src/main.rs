@@ -38,8 +38,17 @@ impl PrunerOpt {
             } else {
                 Some(self.ledger_prune_window as u64)
             },
+            // the benchmark's blocks don't advance time
+            state_store_prune_window_secs: None,
+            ledger_prune_window_secs: None,
+            event_store_prune_window: None,
+            epoch_snapshots_to_retain: 0,
             ledger_pruning_batch_size: self.ledger_pruning_batch_size,
             state_store_pruning_batch_size: self.state_store_pruning_batch_size,
+            ledger_max_versions_per_sec: None,
+            state_store_max_versions_per_sec: None,
+            compaction_threshold_versions: None,
+            prune_only_backed_up: false,
         }
     }
 }
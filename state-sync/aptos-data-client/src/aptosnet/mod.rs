@@ -41,7 +41,10 @@ use storage_service_types::requests::{
     NewTransactionsWithProofRequest, StateValuesWithProofRequest, StorageServiceRequest,
     TransactionOutputsWithProofRequest, TransactionsWithProofRequest,
 };
-use storage_service_types::responses::{StorageServerSummary, StorageServiceResponse};
+use storage_service_types::responses::{
+    ServerProtocolVersion, StorageServerSummary, StorageServiceResponse,
+    EPOCH_ENDING_STATES_PROTOCOL_VERSION,
+};
 use storage_service_types::Epoch;
 use tokio::{runtime::Handle, task::JoinHandle};
 
@@ -133,6 +136,13 @@ impl AptosNetDataClient {
         self.peer_states.write().update_summary(peer, summary)
     }
 
+    /// Update a peer's epoch ending states.
+    fn update_epoch_ending_states(&self, peer: PeerNetworkId, epoch_ending_states: Vec<Version>) {
+        self.peer_states
+            .write()
+            .update_epoch_ending_states(peer, epoch_ending_states)
+    }
+
     /// Recompute and update the global data summary cache.
     fn update_global_summary_cache(&self) {
         let aggregate = self.peer_states.read().calculate_aggregate_summary();
@@ -751,6 +761,17 @@ pub(crate) fn poll_peer(
             }
         };
 
+        // Fetch the epoch ending states the peer holds before its states
+        if let Err(error) = poll_epoch_ending_states(&data_client, peer, &storage_summary).await {
+            error!(
+                (LogSchema::new(LogEntry::StorageSummaryResponse)
+                    .event(LogEvent::PeerPollingError)
+                    .message("Error encountered when polling peer for epoch ending states!")
+                    .error(&error)
+                    .peer(&peer))
+            );
+        }
+
         // Update the summary for the peer
         data_client.update_summary(peer, storage_summary);
 
@@ -781,6 +802,55 @@ pub(crate) fn poll_peer(
     }
 }
 
+/// Fetches the epoch ending states of the given peer. Only a peer whose states
+/// are pruned can hold any before its advertised states, and only those
+/// running a recent enough protocol version serve them, so the peer is asked
+/// for its protocol version first (once).
+async fn poll_epoch_ending_states(
+    data_client: &AptosNetDataClient,
+    peer: PeerNetworkId,
+    storage_summary: &StorageServerSummary,
+) -> Result<()> {
+    let states_pruned = storage_summary
+        .data_summary
+        .states
+        .map_or(false, |states| states.lowest() > 0);
+    if !states_pruned {
+        data_client.update_epoch_ending_states(peer, vec![]);
+        return Ok(());
+    }
+
+    let known_protocol_version = data_client.peer_states.read().get_protocol_version(&peer);
+    let protocol_version = match known_protocol_version {
+        Some(protocol_version) => protocol_version,
+        None => {
+            let server_protocol_version: ServerProtocolVersion = data_client
+                .send_request_to_peer_and_decode(
+                    peer,
+                    StorageServiceRequest::GetServerProtocolVersion,
+                )
+                .await?
+                .into_payload();
+            let protocol_version = server_protocol_version.protocol_version;
+            data_client
+                .peer_states
+                .write()
+                .update_protocol_version(peer, protocol_version);
+            protocol_version
+        }
+    };
+    if protocol_version < EPOCH_ENDING_STATES_PROTOCOL_VERSION {
+        return Ok(());
+    }
+
+    let epoch_ending_states: Vec<Version> = data_client
+        .send_request_to_peer_and_decode(peer, StorageServiceRequest::GetEpochEndingStates)
+        .await?
+        .into_payload();
+    data_client.update_epoch_ending_states(peer, epoch_ending_states);
+    Ok(())
+}
+
 /// Updates the advertised data metrics using the given global
 /// data summary.
 fn update_advertised_data_metrics(global_data_summary: GlobalDataSummary) {
@@ -10,6 +10,7 @@ use aptos_config::{
     network_id::{NetworkId, PeerNetworkId},
 };
 use aptos_logger::prelude::*;
+use aptos_types::transaction::Version;
 use itertools::Itertools;
 use netcore::transport::ConnectionOrigin;
 use network::application::storage::PeerMetadataStorage;
@@ -19,7 +20,7 @@ use std::{
     sync::Arc,
 };
 use storage_service_types::requests::StorageServiceRequest;
-use storage_service_types::responses::StorageServerSummary;
+use storage_service_types::responses::{CompleteDataRange, StorageServerSummary};
 
 /// Scores for peer rankings based on preferences and behavior.
 const MAX_SCORE: f64 = 100.0;
@@ -59,6 +60,12 @@ struct PeerState {
     /// The latest observed advertised data for this peer, or `None` if we
     /// haven't polled them yet.
     storage_summary: Option<StorageServerSummary>,
+    /// The storage server protocol version run by this peer, or `None` if we
+    /// haven't asked them yet.
+    protocol_version: Option<u64>,
+    /// The versions of the epoch ending state snapshots this peer holds
+    /// before its advertised states (lowest to highest).
+    epoch_ending_states: Vec<Version>,
     /// For now, a simplified port of the original state-sync v1 scoring system.
     score: f64,
 }
@@ -67,6 +74,8 @@ impl Default for PeerState {
     fn default() -> Self {
         Self {
             storage_summary: None,
+            protocol_version: None,
+            epoch_ending_states: vec![],
             score: STARTING_SCORE,
         }
     }
@@ -152,8 +161,14 @@ impl PeerStates {
 
         self.peer_to_state
             .get(peer)
-            .and_then(PeerState::storage_summary_if_not_ignored)
-            .map(|summary| summary.can_service(request))
+            .and_then(|peer_state| {
+                peer_state.storage_summary_if_not_ignored().map(|summary| {
+                    summary.can_service_with_epoch_ending_states(
+                        request,
+                        &peer_state.epoch_ending_states,
+                    )
+                })
+            })
             .unwrap_or(false)
     }
 
@@ -285,14 +300,41 @@ impl PeerStates {
             .update_storage_summary(summary);
     }
 
+    /// Returns the storage server protocol version of the given peer, if known
+    pub fn get_protocol_version(&self, peer: &PeerNetworkId) -> Option<u64> {
+        self.peer_to_state
+            .get(peer)
+            .and_then(|peer_state| peer_state.protocol_version)
+    }
+
+    /// Updates the storage server protocol version for the given peer
+    pub fn update_protocol_version(&mut self, peer: PeerNetworkId, protocol_version: u64) {
+        self.peer_to_state.entry(peer).or_default().protocol_version = Some(protocol_version);
+    }
+
+    /// Updates the epoch ending states for the given peer
+    pub fn update_epoch_ending_states(
+        &mut self,
+        peer: PeerNetworkId,
+        epoch_ending_states: Vec<Version>,
+    ) {
+        self.peer_to_state
+            .entry(peer)
+            .or_default()
+            .epoch_ending_states = epoch_ending_states;
+    }
+
     /// Calculates a global data summary using all known storage summaries
     pub fn calculate_aggregate_summary(&self) -> GlobalDataSummary {
         // Only include likely-not-malicious peers in the data summary aggregation
-        let summaries: Vec<StorageServerSummary> = self
+        let summaries: Vec<(StorageServerSummary, Vec<Version>)> = self
             .peer_to_state
             .values()
-            .filter_map(PeerState::storage_summary_if_not_ignored)
-            .cloned()
+            .filter_map(|peer_state| {
+                peer_state
+                    .storage_summary_if_not_ignored()
+                    .map(|summary| (summary.clone(), peer_state.epoch_ending_states.clone()))
+            })
             .collect();
 
         // If we have no peers, return an empty global summary
@@ -306,7 +348,7 @@ impl PeerStates {
         let mut max_state_chunk_sizes = vec![];
         let mut max_transaction_chunk_sizes = vec![];
         let mut max_transaction_output_chunk_sizes = vec![];
-        for summary in summaries {
+        for (summary, epoch_ending_states) in summaries {
             // Collect aggregate data advertisements
             if let Some(epoch_ending_ledger_infos) = summary.data_summary.epoch_ending_ledger_infos
             {
@@ -317,6 +359,11 @@ impl PeerStates {
             if let Some(states) = summary.data_summary.states {
                 advertised_data.states.push(states);
             }
+            for version in epoch_ending_states {
+                if let Ok(states) = CompleteDataRange::new(version, version) {
+                    advertised_data.states.push(states);
+                }
+            }
             if let Some(synced_ledger_info) = summary.data_summary.synced_ledger_info.as_ref() {
                 advertised_data
                     .synced_ledger_infos
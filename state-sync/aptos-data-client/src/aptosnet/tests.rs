@@ -38,7 +38,8 @@ use storage_service_types::requests::{
     TransactionOutputsWithProofRequest, TransactionsWithProofRequest,
 };
 use storage_service_types::responses::{
-    CompleteDataRange, DataSummary, ProtocolMetadata, StorageServerSummary, StorageServiceResponse,
+    CompleteDataRange, DataSummary, ProtocolMetadata, ServerProtocolVersion, StorageServerSummary,
+    StorageServiceResponse, EPOCH_ENDING_STATES_PROTOCOL_VERSION,
 };
 use storage_service_types::{StorageServiceError, StorageServiceMessage};
 
@@ -271,6 +272,62 @@ async fn request_works_only_when_data_available() {
     assert_eq!(response.payload, TransactionListWithProof::new_empty());
 }
 
+#[tokio::test]
+async fn epoch_ending_states_are_polled() {
+    ::aptos_logger::Logger::init_for_testing();
+    let (mut mock_network, mock_time, client, poller) = MockNetwork::new(None, None, None);
+
+    tokio::spawn(poller.start_poller());
+
+    // Add a connected peer
+    let expected_peer = mock_network.add_peer(true);
+
+    // Advance time so the poller sends a data summary request
+    tokio::task::yield_now().await;
+    mock_time.advance_async(Duration::from_millis(1_000)).await;
+
+    // Respond with a summary in which the states are pruned
+    let (_, _, request, response_sender) = mock_network.next_request().await.unwrap();
+    assert_matches!(request, StorageServiceRequest::GetStorageServerSummary);
+    let mut summary = mock_storage_summary(200);
+    summary.data_summary.states = Some(CompleteDataRange::new(100, 200).unwrap());
+    response_sender.send(Ok(StorageServiceResponse::StorageServerSummary(summary)));
+
+    // The poller asks for the protocol version, and then for the epoch ending states
+    let (_, _, request, response_sender) = mock_network.next_request().await.unwrap();
+    assert_matches!(request, StorageServiceRequest::GetServerProtocolVersion);
+    response_sender.send(Ok(StorageServiceResponse::ServerProtocolVersion(
+        ServerProtocolVersion {
+            protocol_version: EPOCH_ENDING_STATES_PROTOCOL_VERSION,
+        },
+    )));
+    let (_, _, request, response_sender) = mock_network.next_request().await.unwrap();
+    assert_matches!(request, StorageServiceRequest::GetEpochEndingStates);
+    response_sender.send(Ok(StorageServiceResponse::EpochEndingStates(vec![50])));
+
+    // Wait until the poller has processed the responses
+    let epoch_ending_states_request = StorageServiceRequest::GetNumberOfStatesAtVersion(50);
+    while !client
+        .peer_states
+        .read()
+        .can_service_request(&expected_peer, &epoch_ending_states_request)
+    {
+        tokio::task::yield_now().await;
+    }
+
+    // The epoch ending states are advertised, but not the other pruned states
+    assert!(!client.peer_states.read().can_service_request(
+        &expected_peer,
+        &StorageServiceRequest::GetNumberOfStatesAtVersion(51)
+    ));
+    client.update_global_summary_cache();
+    let global_summary = client.get_global_data_summary();
+    assert!(global_summary
+        .advertised_data
+        .states
+        .contains(&CompleteDataRange::new(50, 50).unwrap()));
+}
+
 #[tokio::test]
 async fn fetch_peers_frequency() {
     ::aptos_logger::Logger::init_for_testing();
@@ -25,7 +25,7 @@ use futures::stream::StreamExt;
 use lru::LruCache;
 use serde::{Deserialize, Serialize};
 use std::{
-    cmp::min,
+    cmp::{max, min},
     collections::HashMap,
     sync::Arc,
     time::{Duration, Instant},
@@ -37,7 +37,7 @@ use storage_service_types::requests::{
 };
 use storage_service_types::responses::{
     CompleteDataRange, DataSummary, ProtocolMetadata, ServerProtocolVersion, StorageServerSummary,
-    StorageServiceResponse,
+    StorageServiceResponse, EPOCH_ENDING_STATES_PROTOCOL_VERSION,
 };
 use storage_service_types::{Result, StorageServiceError};
 use thiserror::Error;
@@ -51,7 +51,7 @@ pub mod network;
 mod tests;
 
 /// Storage server constants.
-const STORAGE_SERVER_VERSION: u64 = 1;
+const STORAGE_SERVER_VERSION: u64 = EPOCH_ENDING_STATES_PROTOCOL_VERSION;
 const SUMMARY_LOG_FREQUENCY_SECS: u64 = 5;
 
 #[derive(Clone, Debug, Deserialize, Error, PartialEq, Serialize)]
@@ -658,6 +658,7 @@ impl<T: StorageReaderInterface> Handler<T> {
 
         // Process the request
         let response = match &request {
+            StorageServiceRequest::GetEpochEndingStates => self.get_epoch_ending_states(),
             StorageServiceRequest::GetServerProtocolVersion => self.get_server_protocol_version(),
             StorageServiceRequest::GetStorageServerSummary => self.get_storage_server_summary(),
             _ => self.process_cachable_request(protocol, &request),
@@ -798,6 +799,25 @@ impl<T: StorageReaderInterface> Handler<T> {
         ))
     }
 
+    fn get_epoch_ending_states(&self) -> Result<StorageServiceResponse, Error> {
+        // The versions within the advertised states range are served anyway
+        let states = self
+            .cached_storage_server_summary
+            .read()
+            .data_summary
+            .states;
+        let epoch_ending_states = self
+            .storage
+            .get_epoch_ending_states()?
+            .into_iter()
+            .filter(|version| states.map_or(true, |range| *version < range.lowest()))
+            .collect();
+
+        Ok(StorageServiceResponse::EpochEndingStates(
+            epoch_ending_states,
+        ))
+    }
+
     fn get_number_of_states_at_version(
         &self,
         version: Version,
@@ -895,6 +915,10 @@ pub trait StorageReaderInterface: Clone + Send + 'static {
         end_version: u64,
     ) -> Result<TransactionOutputListWithProof, Error>;
 
+    /// Returns the versions of the state snapshots retained at the end of the
+    /// last epochs (lowest to highest), which are held even if pruned otherwise.
+    fn get_epoch_ending_states(&self) -> Result<Vec<Version>, Error>;
+
     /// Returns the number of states in the state tree at the specified version.
     fn get_number_of_states(&self, version: u64) -> Result<u64, Error>;
 
@@ -935,6 +959,12 @@ impl StorageReader {
             .get_state_prune_window()
             .map_err(|error| Error::StorageErrorEncountered(error.to_string()))?
             .map(|window| window as u64);
+        // The pruner can also be past the window, e.g. after a manual prune.
+        let min_readable_version = self
+            .storage
+            .get_state_min_readable_version()
+            .map_err(|error| Error::StorageErrorEncountered(error.to_string()))?
+            .unwrap_or(0);
         if let Some(pruning_window) = pruning_window {
             if latest_version > pruning_window {
                 // lowest_state_version = latest_version - pruning_window + 1;
@@ -947,6 +977,7 @@ impl StorageReader {
                 lowest_state_version = lowest_state_version.checked_add(1).ok_or_else(|| {
                     Error::UnexpectedErrorEncountered("Lowest state version has overflown!".into())
                 })?;
+                lowest_state_version = max(lowest_state_version, min_readable_version);
 
                 // Create the state range
                 let state_range = CompleteDataRange::new(lowest_state_version, latest_version)
@@ -955,8 +986,16 @@ impl StorageReader {
             }
         }
 
-        // No pruning has occurred. Return the transactions range.
-        Ok(*transactions_range)
+        // No pruning has occurred within the window. Return the transactions range, unless the
+        // state is pruned beyond its lowest version anyway.
+        match transactions_range {
+            Some(transactions_range) if transactions_range.lowest() < min_readable_version => {
+                let state_range = CompleteDataRange::new(min_readable_version, latest_version)
+                    .map_err(|error| Error::UnexpectedErrorEncountered(error.to_string()))?;
+                Ok(Some(state_range))
+            }
+            _ => Ok(*transactions_range),
+        }
     }
 
     /// Returns the transaction range held in the database (lowest to highest).
@@ -1119,6 +1158,12 @@ impl StorageReaderInterface for StorageReader {
         Ok(output_list_with_proof)
     }
 
+    fn get_epoch_ending_states(&self) -> Result<Vec<Version>, Error> {
+        self.storage
+            .get_state_retained_snapshot_versions()
+            .map_err(|error| Error::StorageErrorEncountered(error.to_string()))
+    }
+
     fn get_number_of_states(&self, version: u64) -> Result<u64, Error> {
         let number_of_states = self
             .storage
@@ -65,7 +65,7 @@ use tokio::time::timeout;
 
 /// Various test constants for storage
 const MAX_RESPONSE_TIMEOUT_SECS: u64 = 30;
-const PROTOCOL_VERSION: u64 = 1;
+const PROTOCOL_VERSION: u64 = 2;
 
 #[tokio::test]
 async fn test_cachable_requests_eviction() {
@@ -724,6 +724,10 @@ async fn test_get_storage_server_summary() {
         .expect_get_state_prune_window()
         .times(1)
         .return_once(move || Ok(Some(state_prune_window)));
+    db_reader
+        .expect_get_state_min_readable_version()
+        .times(1)
+        .return_once(move || Ok(Some(lowest_version)));
 
     // Create the storage client and server
     let (mut mock_client, service, mock_time) = MockClient::new(Some(db_reader));
@@ -1012,6 +1016,68 @@ async fn test_get_epoch_ending_ledger_infos_invalid() {
     }
 }
 
+#[tokio::test]
+async fn test_get_epoch_ending_states() {
+    // Create test data
+    let highest_version = 506;
+    let highest_epoch = 30;
+    let lowest_version = 101;
+    let state_prune_window = 50;
+    let epoch_ending_versions = vec![80, highest_version - 10];
+    let highest_ledger_info = create_test_ledger_info_with_sigs(highest_epoch, highest_version);
+
+    // Create the mock db reader
+    let mut db_reader = create_mock_db_reader();
+    db_reader
+        .expect_get_latest_ledger_info()
+        .times(1)
+        .return_once(move || Ok(highest_ledger_info));
+    db_reader
+        .expect_get_first_txn_version()
+        .times(1)
+        .return_once(move || Ok(Some(lowest_version)));
+    db_reader
+        .expect_get_first_write_set_version()
+        .times(1)
+        .return_once(move || Ok(Some(lowest_version)));
+    db_reader
+        .expect_get_state_prune_window()
+        .times(1)
+        .return_once(move || Ok(Some(state_prune_window)));
+    db_reader
+        .expect_get_state_min_readable_version()
+        .times(1)
+        .return_once(move || Ok(Some(lowest_version)));
+    let epoch_ending_versions_clone = epoch_ending_versions.clone();
+    db_reader
+        .expect_get_state_retained_snapshot_versions()
+        .times(2)
+        .returning(move || Ok(epoch_ending_versions_clone.clone()));
+
+    // Create the storage client and server
+    let (mut mock_client, service, mock_time) = MockClient::new(Some(db_reader));
+    tokio::spawn(service.start());
+
+    // Nothing is advertised yet, so all the epoch ending states are returned
+    let request = StorageServiceRequest::GetEpochEndingStates;
+    let response = mock_client.process_request(request).await.unwrap();
+    assert_eq!(
+        response,
+        StorageServiceResponse::EpochEndingStates(epoch_ending_versions)
+    );
+
+    // Elapse enough time to force a cache update
+    advance_storage_refresh_time(&mock_time).await;
+
+    // Only the epoch ending states below the advertised states range are returned
+    let request = StorageServiceRequest::GetEpochEndingStates;
+    let response = mock_client.process_request(request).await.unwrap();
+    assert_eq!(
+        response,
+        StorageServiceResponse::EpochEndingStates(vec![80])
+    );
+}
+
 /// A wrapper around the inbound network interface/channel for easily sending
 /// mock client requests to a [`StorageServiceServer`].
 struct MockClient {
@@ -1190,6 +1256,9 @@ fn create_mock_db_for_subscription(
         .expect_get_state_prune_window()
         .return_once(move || Ok(Some(100)));
     db_reader
+        .expect_get_state_min_readable_version()
+        .return_once(move || Ok(Some(lowest_version)));
+    db_reader
 }
 
 /// Sets an expectation on the given mock db for a call to fetch transactions
@@ -1545,5 +1614,9 @@ mock! {
         ) -> Result<StateValueChunkWithProof>;
 
         fn get_state_prune_window(&self) -> Result<Option<usize>>;
+
+        fn get_state_min_readable_version(&self) -> Result<Option<Version>>;
+
+        fn get_state_retained_snapshot_versions(&self) -> Result<Vec<Version>>;
     }
 }
@@ -16,6 +16,8 @@ pub enum StorageServiceRequest {
     GetStorageServerSummary,             // Fetches a summary of the storage server state
     GetTransactionOutputsWithProof(TransactionOutputsWithProofRequest), // Fetches a list of transaction outputs with a proof
     GetTransactionsWithProof(TransactionsWithProofRequest), // Fetches a list of transactions with a proof
+    // Requests added since protocol version 1 go last, so the others keep their BCS encoding
+    GetEpochEndingStates, // Fetches the versions of the retained epoch ending state snapshots
 }
 
 impl StorageServiceRequest {
@@ -23,6 +25,7 @@ impl StorageServiceRequest {
     pub fn get_label(&self) -> &'static str {
         match self {
             Self::GetEpochEndingLedgerInfos(_) => "get_epoch_ending_ledger_infos",
+            Self::GetEpochEndingStates => "get_epoch_ending_states",
             Self::GetNewTransactionOutputsWithProof(_) => "get_new_transaction_outputs_with_proof",
             Self::GetNewTransactionsWithProof(_) => "get_new_transactions_with_proof",
             Self::GetNumberOfStatesAtVersion(_) => "get_number_of_states_at_version",
@@ -37,6 +37,8 @@ pub enum StorageServiceResponse {
     StorageServerSummary(StorageServerSummary),
     TransactionOutputsWithProof(TransactionOutputListWithProof),
     TransactionsWithProof(TransactionListWithProof),
+    // Responses added since protocol version 1 go last, so the others keep their BCS encoding
+    EpochEndingStates(Vec<Version>),
 }
 
 // TODO(philiphayes): is there a proc-macro for this?
@@ -45,6 +47,7 @@ impl StorageServiceResponse {
     pub fn get_label(&self) -> &'static str {
         match self {
             Self::EpochEndingLedgerInfos(_) => "epoch_ending_ledger_infos",
+            Self::EpochEndingStates(_) => "epoch_ending_states",
             Self::NewTransactionOutputsWithProof(_) => "new_transaction_outputs_with_proof",
             Self::NewTransactionsWithProof(_) => "new_transactions_with_proof",
             Self::NumberOfStatesAtVersion(_) => "number_of_states_at_version",
@@ -101,6 +104,19 @@ impl TryFrom<StorageServiceResponse> for EpochChangeProof {
     }
 }
 
+impl TryFrom<StorageServiceResponse> for Vec<Version> {
+    type Error = crate::responses::Error;
+    fn try_from(response: StorageServiceResponse) -> crate::Result<Self, Self::Error> {
+        match response {
+            StorageServiceResponse::EpochEndingStates(inner) => Ok(inner),
+            _ => Err(UnexpectedResponseError(format!(
+                "expected epoch_ending_states, found {}",
+                response.get_label()
+            ))),
+        }
+    }
+}
+
 impl TryFrom<StorageServiceResponse>
     for (TransactionOutputListWithProof, LedgerInfoWithSignatures)
 {
@@ -201,6 +217,9 @@ pub struct ServerProtocolVersion {
     pub protocol_version: u64, // The storage server version run by this instance.
 }
 
+/// The first protocol version in which servers handle `GetEpochEndingStates`.
+pub const EPOCH_ENDING_STATES_PROTOCOL_VERSION: u64 = 2;
+
 /// A storage server summary, containing a summary of the information held
 /// by the corresponding server instance. This is useful for identifying the
 /// data that a server instance can provide, as well as relevant metadata.
@@ -229,9 +248,21 @@ pub struct ProtocolMetadata {
 impl ProtocolMetadata {
     /// Returns true iff the request can be serviced
     pub fn can_service(&self, request: &StorageServiceRequest) -> bool {
+        self.can_service_with_epoch_ending_states(request, &[])
+    }
+
+    /// Returns true iff the request can be serviced, given the versions of
+    /// the epoch ending state snapshots held before `states` (as returned
+    /// for a `GetEpochEndingStates` request).
+    pub fn can_service_with_epoch_ending_states(
+        &self,
+        request: &StorageServiceRequest,
+        epoch_ending_states: &[Version],
+    ) -> bool {
         use crate::StorageServiceRequest::*;
         match request {
-            GetNewTransactionsWithProof(_)
+            GetEpochEndingStates
+            | GetNewTransactionsWithProof(_)
             | GetNewTransactionOutputsWithProof(_)
             | GetNumberOfStatesAtVersion(_)
             | GetServerProtocolVersion
@@ -330,17 +361,13 @@ impl DataSummary {
                     .map(|range| range.superset_of(&desired_range))
                     .unwrap_or(false)
             }
-            GetNumberOfStatesAtVersion(version) => self
-                .states
-                .map(|range| range.contains(*version))
-                .unwrap_or(false),
+            GetNumberOfStatesAtVersion(version) => {
+                self.can_serve_states(*version, epoch_ending_states)
+            }
             GetStateValuesWithProof(request) => {
                 let proof_version = request.version;
 
-                let can_serve_states = self
-                    .states
-                    .map(|range| range.contains(request.version))
-                    .unwrap_or(false);
+                let can_serve_states = self.can_serve_states(request.version, epoch_ending_states);
 
                 let can_create_proof = self
                     .synced_ledger_info
@@ -392,6 +419,14 @@ impl DataSummary {
             }
         }
     }
+
+    /// Returns true iff all states are held at the given version
+    fn can_serve_states(&self, version: Version, epoch_ending_states: &[Version]) -> bool {
+        self.states
+            .map(|range| range.contains(version))
+            .unwrap_or(false)
+            || epoch_ending_states.contains(&version)
+    }
 }
 
 #[derive(Clone, Debug, Error)]
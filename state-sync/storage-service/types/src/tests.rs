@@ -5,7 +5,7 @@ use crate::requests::{
     EpochEndingLedgerInfoRequest, StateValuesWithProofRequest, TransactionOutputsWithProofRequest,
     TransactionsWithProofRequest,
 };
-use crate::responses::{CompleteDataRange, DataSummary, ProtocolMetadata};
+use crate::responses::{CompleteDataRange, DataSummary, ProtocolMetadata, StorageServiceResponse};
 use crate::{Epoch, StorageServiceRequest};
 use aptos_crypto::hash::HashValue;
 use aptos_types::ledger_info::LedgerInfoWithSignatures;
@@ -139,6 +139,7 @@ fn test_data_summary_can_service_state_chunk_request() {
         states: Some(create_range(100, 300)),
         ..Default::default()
     };
+    let epoch_ending_states = [50];
 
     // in range and can provide proof => can service
     assert!(summary.can_service(&create_get_states_request(100)));
@@ -152,6 +153,38 @@ fn test_data_summary_can_service_state_chunk_request() {
     // can provide proof, but out of range ==> cannot service
     assert!(!summary.can_service(&create_get_states_request(50)));
     assert!(!summary.can_service(&create_get_states_request(99)));
+
+    // out of range, but at a retained epoch ending snapshot => can service
+    assert!(summary.can_service_with_epoch_ending_states(
+        &create_get_states_request(50),
+        &epoch_ending_states
+    ));
+    assert!(!summary.can_service_with_epoch_ending_states(
+        &create_get_states_request(49),
+        &epoch_ending_states
+    ));
+}
+
+#[test]
+fn test_wire_format_of_version_1_messages() {
+    // Nodes still running version 1 of the protocol must be able to decode
+    // the requests and responses they know about.
+    assert_eq!(
+        bcs::to_bytes(&StorageServiceRequest::GetStorageServerSummary).unwrap(),
+        vec![6]
+    );
+    assert_eq!(
+        bcs::to_bytes(&StorageServiceRequest::GetEpochEndingStates).unwrap(),
+        vec![9]
+    );
+    assert_eq!(
+        bcs::to_bytes(&DataSummary::default()).unwrap(),
+        vec![0, 0, 0, 0, 0]
+    );
+    assert_eq!(
+        bcs::to_bytes(&StorageServiceResponse::EpochEndingStates(vec![])).unwrap(),
+        vec![9, 0]
+    );
 }
 
 #[test]
@@ -10,7 +10,7 @@ use crate::{
     pruner::{Pruner, PrunerIndex},
     test_helper,
     test_helper::{arb_blocks_to_commit, put_as_state_root, put_transaction_info},
-    AptosDB, ROCKSDB_PROPERTIES,
+    AptosDB, LEDGER_DB_NAME, ROCKSDB_PROPERTIES, STATE_MERKLE_DB_NAME,
 };
 use aptos_config::config::StoragePrunerConfig;
 use aptos_crypto::{hash::CryptoHash, HashValue};
@@ -89,11 +89,19 @@ fn test_error_if_version_is_pruned() {
         StoragePrunerConfig {
             state_store_prune_window: Some(0),
             ledger_prune_window: Some(0),
+            state_store_prune_window_secs: None,
+            ledger_prune_window_secs: None,
+            event_store_prune_window: None,
+            epoch_snapshots_to_retain: 0,
             ledger_pruning_batch_size: 1,
             state_store_pruning_batch_size: 1,
+            ledger_max_versions_per_sec: None,
+            state_store_max_versions_per_sec: None,
+            compaction_threshold_versions: None,
+            prune_only_backed_up: false,
         },
     );
-    pruner.testonly_update_min_version(&[Some(5), Some(10)]);
+    pruner.testonly_update_min_version(&[Some(5), Some(10), None]);
     let pruner = Some(pruner);
     assert_eq!(
         error_if_version_is_pruned(&pruner, PrunerIndex::StateStorePrunerIndex, "State", 4)
@@ -116,6 +124,72 @@ fn test_error_if_version_is_pruned() {
     );
 }
 
+#[test]
+fn test_prune_only_backed_up() {
+    let tmp_dir = TempPath::new();
+    let aptos_db = AptosDB::new_for_test(&tmp_dir);
+    let storage_pruner_config = StoragePrunerConfig {
+        state_store_prune_window: None,
+        ledger_prune_window: Some(0),
+        state_store_prune_window_secs: None,
+        ledger_prune_window_secs: None,
+        event_store_prune_window: None,
+        epoch_snapshots_to_retain: 0,
+        ledger_pruning_batch_size: 1,
+        state_store_pruning_batch_size: 1,
+        ledger_max_versions_per_sec: None,
+        state_store_max_versions_per_sec: None,
+        compaction_threshold_versions: None,
+        prune_only_backed_up: true,
+    };
+    let pruner = Pruner::new(
+        Arc::clone(&aptos_db.ledger_db),
+        Arc::clone(&aptos_db.state_merkle_db),
+        storage_pruner_config,
+    );
+    // nothing is backed up yet
+    assert!(pruner.prune_to(PrunerIndex::LedgerPrunerIndex, 1).is_err());
+
+    pruner.set_backup_watermark(9).unwrap();
+    assert!(pruner.prune_to(PrunerIndex::LedgerPrunerIndex, 10).is_ok());
+    assert!(pruner.prune_to(PrunerIndex::LedgerPrunerIndex, 11).is_err());
+
+    // the watermark never goes back
+    pruner.set_backup_watermark(5).unwrap();
+    assert!(pruner.prune_to(PrunerIndex::LedgerPrunerIndex, 10).is_ok());
+    drop(pruner);
+
+    // nor is it forgotten on restart
+    let pruner = Pruner::new(
+        Arc::clone(&aptos_db.ledger_db),
+        Arc::clone(&aptos_db.state_merkle_db),
+        storage_pruner_config,
+    );
+    assert!(pruner.prune_to(PrunerIndex::LedgerPrunerIndex, 10).is_ok());
+    assert!(pruner.prune_to(PrunerIndex::LedgerPrunerIndex, 11).is_err());
+}
+
+#[test]
+fn test_get_db_size_report() {
+    let tmp_dir = TempPath::new();
+    let db = AptosDB::new_for_test(&tmp_dir);
+    let report = db.get_db_size_report().unwrap();
+
+    let db_names: Vec<_> = report.dbs.iter().map(|db| db.db_name.as_str()).collect();
+    assert_eq!(db_names, vec![LEDGER_DB_NAME, STATE_MERKLE_DB_NAME]);
+    for db_size in report.dbs {
+        assert!(!db_size.column_families.is_empty());
+        assert_eq!(
+            db_size.size_bytes,
+            db_size
+                .column_families
+                .iter()
+                .map(|cf| cf.size_bytes)
+                .sum::<u64>()
+        );
+    }
+}
+
 #[test]
 fn test_get_latest_executed_trees() {
     let tmp_dir = TempPath::new();
@@ -13,6 +13,7 @@ const VERSION_SIZE: usize = std::mem::size_of::<Version>();
 pub(super) fn ledger_db_column_families() -> Vec<ColumnFamilyName> {
     vec![
         /* empty cf */ DEFAULT_COLUMN_FAMILY_NAME,
+        BACKUP_WATERMARK_CF_NAME,
         EPOCH_BY_VERSION_CF_NAME,
         EVENT_ACCUMULATOR_CF_NAME,
         EVENT_BY_KEY_CF_NAME,
@@ -20,6 +21,7 @@ pub(super) fn ledger_db_column_families() -> Vec<ColumnFamilyName> {
         EVENT_CF_NAME,
         LEDGER_COUNTERS_CF_NAME,
         LEDGER_INFO_CF_NAME,
+        PRUNER_PROGRESS_CF_NAME,
         STALE_NODE_INDEX_CF_NAME,
         STATE_VALUE_CF_NAME,
         TRANSACTION_CF_NAME,
@@ -35,7 +37,9 @@ pub(super) fn state_merkle_db_column_families() -> Vec<ColumnFamilyName> {
     vec![
         /* empty cf */ DEFAULT_COLUMN_FAMILY_NAME,
         JELLYFISH_MERKLE_NODE_CF_NAME,
+        PRUNER_PROGRESS_CF_NAME,
         STALE_NODE_INDEX_CF_NAME,
+        STALE_NODE_INDEX_CROSS_EPOCH_CF_NAME,
     ]
 }
 
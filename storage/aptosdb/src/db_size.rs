@@ -0,0 +1,58 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+//! This module provides the approximate sizes of the DBs making up an `AptosDB` and of their
+//! column families, e.g. to find out which prune window is worth tightening.
+
+use anyhow::Result;
+use schemadb::{ColumnFamilyName, DB};
+use serde::{Deserialize, Serialize};
+
+/// Approximate sizes of the DBs, like the ledger DB, and of their column families.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct DbSizeReport {
+    pub dbs: Vec<DbSize>,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct DbSize {
+    pub db_name: String,
+    /// The sum of `size_bytes` over the column families.
+    pub size_bytes: u64,
+    pub column_families: Vec<ColumnFamilySize>,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct ColumnFamilySize {
+    pub cf_name: String,
+    /// The size on disk plus the size of the mem tables.
+    pub size_bytes: u64,
+    /// The estimated size of the live data, which excludes what is deleted but not compacted yet.
+    pub live_data_size_bytes: u64,
+    pub estimate_num_keys: u64,
+}
+
+pub(crate) fn get_db_size(
+    db_name: &str,
+    db: &DB,
+    column_families: Vec<ColumnFamilyName>,
+) -> Result<DbSize> {
+    let column_families = column_families
+        .into_iter()
+        .map(|cf_name| {
+            Ok(ColumnFamilySize {
+                cf_name: cf_name.to_string(),
+                size_bytes: db.get_property(cf_name, "rocksdb.total-sst-files-size")?
+                    + db.get_property(cf_name, "rocksdb.size-all-mem-tables")?,
+                live_data_size_bytes: db
+                    .get_property(cf_name, "rocksdb.estimate-live-data-size")?,
+                estimate_num_keys: db.get_property(cf_name, "rocksdb.estimate-num-keys")?,
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+    Ok(DbSize {
+        db_name: db_name.to_string(),
+        size_bytes: column_families.iter().map(|cf| cf.size_bytes).sum(),
+        column_families,
+    })
+}
@@ -127,6 +127,18 @@ impl EventStore {
         ))
     }
 
+    /// Get the oldest sequence number on `event_key` that's not pruned yet. None if there are no
+    /// events in the event stream.
+    pub fn get_oldest_sequence_number(&self, event_key: &EventKey) -> Result<Option<u64>> {
+        let mut iter = self.db.iter::<EventByKeySchema>(ReadOptions::default())?;
+        iter.seek(&(*event_key, 0))?;
+
+        Ok(iter
+            .next()
+            .transpose()?
+            .and_then(|((key, seq), _)| if &key == event_key { Some(seq) } else { None }))
+    }
+
     /// Get the next sequence number for specified event key.
     /// Returns 0 if there's no events already in the event stream.
     pub fn get_next_sequence_number(
@@ -258,24 +270,27 @@ impl EventStore {
             .collect::<Result<Vec<_>>>()
     }
 
-    /// Finds the first event sequence number in a specified stream on which `comp` returns false.
-    /// (assuming the whole stream is partitioned by `comp`)
+    /// Finds the first event sequence number from `begin` on in a specified stream on which `comp`
+    /// returns false. (assuming the whole stream is partitioned by `comp`)
     fn search_for_event_lower_bound<C>(
         &self,
         event_key: &EventKey,
         mut comp: C,
+        mut begin: u64,
         ledger_version: Version,
     ) -> Result<Option<u64>>
     where
         C: FnMut(&ContractEvent) -> Result<bool>,
     {
-        let mut begin = 0u64;
         let mut end = match self.get_latest_sequence_number(ledger_version, event_key)? {
             Some(s) => s
                 .checked_add(1)
                 .ok_or_else(|| format_err!("event sequence number overflew."))?,
             None => return Ok(None),
         };
+        if begin >= end {
+            return Ok(None);
+        }
 
         // overflow not possible
         #[allow(clippy::integer_arithmetic)]
@@ -304,18 +319,22 @@ impl EventStore {
     /// Gets the version of the last transaction committed before timestamp,
     /// a commited block at or after the required timestamp must exist (otherwise it's possible
     /// the next block committed as a timestamp smaller than the one in the request).
+    ///
+    /// Only the blocks whose events are not pruned yet are searched.
     pub(crate) fn get_last_version_before_timestamp(
         &self,
         timestamp: u64,
         ledger_version: Version,
     ) -> Result<Version> {
         let event_key = new_block_event_key();
+        let oldest_seq = self.get_oldest_sequence_number(&event_key)?.unwrap_or(0);
         let seq_at_or_after_ts = self.search_for_event_lower_bound(
             &event_key,
             |event| {
                 let new_block_event: NewBlockEvent = event.try_into()?;
                 Ok(new_block_event.proposed_time() < timestamp)
             },
+            oldest_seq,
             ledger_version,
         )?.ok_or_else(|| format_err!(
             "No new block found beyond timestmap {}, so can't determine the last version before it.",
@@ -323,8 +342,8 @@ impl EventStore {
         ))?;
 
         ensure!(
-            seq_at_or_after_ts > 0,
-            "First block started at or after timestamp {}.",
+            seq_at_or_after_ts > oldest_seq,
+            "First block not pruned started at or after timestamp {}.",
             timestamp,
         );
 
@@ -14,6 +14,7 @@
 pub mod test_helper;
 
 pub mod backup;
+pub mod db_size;
 pub mod errors;
 pub mod metrics;
 pub mod schema;
@@ -32,6 +33,8 @@ mod transaction_store;
 #[cfg(test)]
 mod aptosdb_test;
 
+pub use crate::pruner::PrunerIndex;
+
 #[cfg(any(test, feature = "fuzzing"))]
 use crate::state_store::buffered_state::BufferedState;
 use crate::{
@@ -41,6 +44,7 @@ use crate::{
         gen_ledger_cfds, gen_state_merkle_cfds, ledger_db_column_families,
         state_merkle_db_column_families,
     },
+    db_size::{get_db_size, DbSizeReport},
     errors::AptosDbError,
     event_store::EventStore,
     ledger_counters::LedgerCounters,
@@ -49,7 +53,7 @@ use crate::{
         API_LATENCY_SECONDS, COMMITTED_TXNS, LATEST_TXN_VERSION, LEDGER_VERSION, NEXT_BLOCK_EPOCH,
         OTHER_TIMERS_SECONDS, ROCKSDB_PROPERTIES, STATE_ITEM_COUNT,
     },
-    pruner::{utils, Pruner, PrunerIndex},
+    pruner::{utils, Pruner},
     schema::*,
     state_store::StateStore,
     system_store::SystemStore,
@@ -188,6 +192,20 @@ fn error_if_version_is_pruned(
     Ok(())
 }
 
+/// Like `error_if_version_is_pruned()` for the state, except that the snapshots retained at the end
+/// of the last epochs stay readable below the min readable version.
+fn error_if_state_version_is_pruned(pruner: &Option<Pruner>, version: Version) -> Result<()> {
+    if let Some(pruner) = pruner.as_ref() {
+        if pruner
+            .get_state_retained_snapshot_versions()
+            .contains(&version)
+        {
+            return Ok(());
+        }
+    }
+    error_if_version_is_pruned(pruner, PrunerIndex::StateStorePrunerIndex, "State", version)
+}
+
 fn update_rocksdb_properties(ledger_rocksdb: &DB, state_merkle_rocksdb: &DB) -> Result<()> {
     let _timer = OTHER_TIMERS_SECONDS
         .with_label_values(&["update_rocksdb_properties"])
@@ -282,8 +300,9 @@ impl AptosDB {
         let arc_ledger_rocksdb = Arc::new(ledger_rocksdb);
         let arc_state_merkle_rocksdb = Arc::new(state_merkle_rocksdb);
         let pruner_config = storage_pruner_config;
-        let pruner = if pruner_config.ledger_prune_window.is_none()
-            && pruner_config.state_store_prune_window.is_none()
+        let pruner = if !pruner_config.ledger_pruning_enabled()
+            && !pruner_config.state_store_pruning_enabled()
+            && !pruner_config.event_store_pruning_enabled()
         {
             None
         } else {
@@ -514,6 +533,69 @@ impl AptosDB {
         update_rocksdb_properties(&self.ledger_db, &self.state_merkle_db)
     }
 
+    /// Makes the pruner at `pruner_index` prune up to `target_version` right away, ahead of its
+    /// window, e.g. to free disk space. The target can't be beyond the latest committed version.
+    pub fn prune_to(&self, pruner_index: PrunerIndex, target_version: Version) -> Result<()> {
+        let latest_version = self
+            .ledger_store
+            .get_latest_ledger_info_option()
+            .map(|li| li.ledger_info().version());
+        ensure!(
+            latest_version.map_or(false, |latest| target_version <= latest),
+            "Can't prune to version {}, the latest committed version is {:?}.",
+            target_version,
+            latest_version
+        );
+        match self.pruner.as_ref() {
+            Some(pruner) => pruner.prune_to(pruner_index, target_version),
+            None => bail!("Pruning is disabled."),
+        }
+    }
+
+    /// Pauses or resumes the pruners, e.g. to hold deletions while a backup is taken. They carry on
+    /// with their backlog once resumed.
+    pub fn set_pruners_paused(&self, paused: bool) -> Result<()> {
+        match self.pruner.as_ref() {
+            Some(pruner) => {
+                pruner.set_paused(paused);
+                Ok(())
+            }
+            None => bail!("Pruning is disabled."),
+        }
+    }
+
+    /// Records that all the versions up to `version` are backed up, which the pruners wait for
+    /// with `prune_only_backed_up`. Nothing beyond the latest committed version can be backed up,
+    /// so the watermark is capped there. Does nothing if pruning is disabled.
+    pub fn set_backup_watermark(&self, version: Version) -> Result<()> {
+        let pruner = match self.pruner.as_ref() {
+            Some(pruner) => pruner,
+            None => return Ok(()),
+        };
+        match self.ledger_store.get_latest_ledger_info_option() {
+            Some(latest_li) => {
+                pruner.set_backup_watermark(version.min(latest_li.ledger_info().version()))
+            }
+            // Nothing is committed, so nothing is backed up either.
+            None => Ok(()),
+        }
+    }
+
+    /// Reports the approximate sizes of the ledger DB and the state merkle DB, and of each of their
+    /// column families.
+    pub fn get_db_size_report(&self) -> Result<DbSizeReport> {
+        Ok(DbSizeReport {
+            dbs: vec![
+                get_db_size(LEDGER_DB_NAME, &self.ledger_db, ledger_db_column_families())?,
+                get_db_size(
+                    STATE_MERKLE_DB_NAME,
+                    &self.state_merkle_db,
+                    state_merkle_db_column_families(),
+                )?,
+            ],
+        })
+    }
+
     /// Returns ledger infos reflecting epoch bumps starting with the given epoch. If there are no
     /// more than `MAX_NUM_EPOCH_ENDING_LEDGER_INFO` results, this function returns all of them,
     /// otherwise the first `MAX_NUM_EPOCH_ENDING_LEDGER_INFO` results are returned and a flag
@@ -597,6 +679,12 @@ impl AptosDB {
 
         // If events were requested, also fetch those.
         let events = if fetch_events {
+            error_if_version_is_pruned(
+                &self.pruner,
+                PrunerIndex::EventStorePrunerIndex,
+                "Event",
+                version,
+            )?;
             Some(self.event_store.get_events_by_version(version)?)
         } else {
             None
@@ -964,6 +1052,12 @@ impl DbReader for AptosDB {
                 .map(|version| self.ledger_store.get_transaction_info(version))
                 .collect::<Result<Vec<_>>>()?;
             let events = if fetch_events {
+                error_if_version_is_pruned(
+                    &self.pruner,
+                    PrunerIndex::EventStorePrunerIndex,
+                    "Event",
+                    start_version,
+                )?;
                 Some(
                     (start_version..start_version + limit)
                         .map(|version| self.event_store.get_events_by_version(version))
@@ -991,11 +1085,14 @@ impl DbReader for AptosDB {
     }
 
     /// Get the first version that txn starts existent.
+    ///
+    /// Transactions are served along with their events, so when the events are pruned on a shorter
+    /// window of their own, this is the first version whose events are still readable.
     fn get_first_txn_version(&self) -> Result<Option<Version>> {
         gauged_api("get_first_txn_version", || {
             if let Some(pruner) = self.pruner.as_ref() {
                 // If pruning is enabled, we can get the min readable version from the pruner.
-                Ok(pruner.get_min_readable_ledger_version())
+                Ok(pruner.get_min_readable_version_with_events())
             } else {
                 self.transaction_store.get_first_txn_version()
             }
@@ -1003,11 +1100,13 @@ impl DbReader for AptosDB {
     }
 
     /// Get the first version that write set starts existent.
+    ///
+    /// Like `get_first_txn_version()`, since the transaction outputs include the events.
     fn get_first_write_set_version(&self) -> Result<Option<Version>> {
         gauged_api("get_first_write_set_version", || {
             if let Some(pruner) = self.pruner.as_ref() {
                 // If pruning is enabled, we can get the min readable version from the pruner.
-                Ok(pruner.get_min_readable_ledger_version())
+                Ok(pruner.get_min_readable_version_with_events())
             } else {
                 self.transaction_store.get_first_write_set_version()
             }
@@ -1039,6 +1138,12 @@ impl DbReader for AptosDB {
                 "Transaction",
                 start_version,
             )?;
+            error_if_version_is_pruned(
+                &self.pruner,
+                PrunerIndex::EventStorePrunerIndex,
+                "Event",
+                start_version,
+            )?;
 
             let limit = std::cmp::min(limit, ledger_version - start_version + 1);
 
@@ -1157,12 +1262,7 @@ impl DbReader for AptosDB {
         version: Version,
     ) -> Result<Option<StateValue>> {
         gauged_api("get_state_value_by_version", || {
-            error_if_version_is_pruned(
-                &self.pruner,
-                PrunerIndex::StateStorePrunerIndex,
-                "State",
-                version,
-            )?;
+            error_if_state_version_is_pruned(&self.pruner, version)?;
 
             self.state_store
                 .get_state_value_by_version(state_store_key, version)
@@ -1176,12 +1276,7 @@ impl DbReader for AptosDB {
         version: Version,
     ) -> Result<SparseMerkleProof> {
         gauged_api("get_proof_by_version", || {
-            error_if_version_is_pruned(
-                &self.pruner,
-                PrunerIndex::StateStorePrunerIndex,
-                "State",
-                version,
-            )?;
+            error_if_state_version_is_pruned(&self.pruner, version)?;
 
             self.state_store
                 .get_state_proof_by_version(state_key, version)
@@ -1244,12 +1339,7 @@ impl DbReader for AptosDB {
         version: Version,
     ) -> Result<(Option<StateValue>, SparseMerkleProof)> {
         gauged_api("get_state_value_with_proof_by_version", || {
-            error_if_version_is_pruned(
-                &self.pruner,
-                PrunerIndex::StateStorePrunerIndex,
-                "State",
-                version,
-            )?;
+            error_if_state_version_is_pruned(&self.pruner, version)?;
 
             self.state_store
                 .get_state_value_with_proof_by_version(state_store_key, version)
@@ -1400,6 +1490,33 @@ impl DbReader for AptosDB {
         })
     }
 
+    fn get_state_min_readable_version(&self) -> Result<Option<Version>> {
+        gauged_api("get_state_min_readable_version", || {
+            Ok(self.pruner.as_ref().and_then(|pruner| {
+                pruner.get_min_readable_version_by_pruner_index(PrunerIndex::StateStorePrunerIndex)
+            }))
+        })
+    }
+
+    fn get_state_retained_snapshot_versions(&self) -> Result<Vec<Version>> {
+        gauged_api("get_state_retained_snapshot_versions", || {
+            Ok(self.pruner.as_ref().map_or_else(Vec::new, |pruner| {
+                pruner.get_state_retained_snapshot_versions()
+            }))
+        })
+    }
+
+    fn get_event_min_readable_version(&self) -> Result<Option<Version>> {
+        gauged_api("get_event_min_readable_version", || {
+            // Without a window of their own, the events are pruned along with the ledger.
+            Ok(self.pruner.as_ref().and_then(|pruner| {
+                pruner
+                    .get_min_readable_version_by_pruner_index(PrunerIndex::EventStorePrunerIndex)
+                    .or_else(|| pruner.get_min_readable_ledger_version())
+            }))
+        })
+    }
+
     fn get_table_info(&self, handle: TableHandle) -> Result<TableInfo> {
         gauged_api("get_table_info", || {
             self.get_table_info_option(handle)?
@@ -1658,10 +1775,20 @@ impl DbWriter for AptosDB {
     fn delete_genesis(&self) -> Result<()> {
         gauged_api("delete_genesis", || {
             // Create all the db pruners
-            let state_pruner_option =
-                utils::create_state_pruner(Arc::clone(&self.state_merkle_db), self.pruner_config);
+            // genesis is the ending of epoch 0, so its snapshot mustn't be retained
+            let state_pruner_option = utils::create_state_pruner(
+                Arc::clone(&self.ledger_db),
+                Arc::clone(&self.state_merkle_db),
+                Arc::new(Mutex::new(vec![])),
+                StoragePrunerConfig {
+                    epoch_snapshots_to_retain: 0,
+                    ..self.pruner_config
+                },
+            );
             let ledger_pruner_option =
                 utils::create_ledger_pruner(Arc::clone(&self.ledger_db), self.pruner_config);
+            let event_pruner_option =
+                utils::create_event_pruner(Arc::clone(&self.ledger_db), self.pruner_config);
 
             // Execute each pruner to clean up the genesis state
             let target_version = 1; // The genesis version is 0. Delete [0,1) (exclusive).
@@ -1676,6 +1803,11 @@ impl DbWriter for AptosDB {
                 ledger_pruner.lock().set_target_version(target_version);
                 ledger_pruner.lock().prune(max_version)?;
             }
+
+            if let Some(event_pruner) = event_pruner_option {
+                event_pruner.lock().set_target_version(target_version);
+                event_pruner.lock().prune(max_version)?;
+            }
             Ok(())
         })
     }
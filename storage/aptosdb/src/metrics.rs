@@ -98,6 +98,60 @@ pub static PRUNER_BATCH_SIZE: Lazy<IntGaugeVec> = Lazy::new(|| {
     .unwrap()
 });
 
+/// The version each pruner is asked to prune up to.
+pub static PRUNER_TARGET_VERSION: Lazy<IntGaugeVec> = Lazy::new(|| {
+    register_int_gauge_vec!(
+        // metric name
+        "aptos_pruner_target_version",
+        // metric description
+        "Aptos pruner target version",
+        // metric labels (dimensions)
+        &["pruner_name",]
+    )
+    .unwrap()
+});
+
+/// The number of versions between a pruner's min readable version and its target version. If it
+/// keeps growing, the pruner isn't keeping up with the commits.
+pub static PRUNER_LAG: Lazy<IntGaugeVec> = Lazy::new(|| {
+    register_int_gauge_vec!(
+        // metric name
+        "aptos_pruner_lag",
+        // metric description
+        "Aptos pruner versions left to prune up to the target version",
+        // metric labels (dimensions)
+        &["pruner_name",]
+    )
+    .unwrap()
+});
+
+/// How long the last batch of each pruner took, in milliseconds.
+pub static PRUNER_LAST_BATCH_DURATION_MS: Lazy<IntGaugeVec> = Lazy::new(|| {
+    register_int_gauge_vec!(
+        // metric name
+        "aptos_pruner_last_batch_duration_ms",
+        // metric description
+        "Aptos pruner duration of the last batch in milliseconds",
+        // metric labels (dimensions)
+        &["pruner_name",]
+    )
+    .unwrap()
+});
+
+/// How long each pruner is holding its next batch back as RocksDB is stalling writes, in
+/// milliseconds, 0 when not backing off.
+pub static PRUNER_WRITE_STALL_BACKOFF_MS: Lazy<IntGaugeVec> = Lazy::new(|| {
+    register_int_gauge_vec!(
+        // metric name
+        "aptos_pruner_write_stall_backoff_ms",
+        // metric description
+        "Aptos pruner backoff on RocksDB write stalls in milliseconds",
+        // metric labels (dimensions)
+        &["pruner_name",]
+    )
+    .unwrap()
+});
+
 pub static API_LATENCY_SECONDS: Lazy<HistogramVec> = Lazy::new(|| {
     register_histogram_vec!(
         // metric name
@@ -61,6 +61,10 @@ pub trait DBPruner {
             self.target_version(),
         )
     }
+    /// Compacts what the pruner deleted for the versions in [begin, end), to reclaim the disk
+    /// space.
+    fn compact(&self, begin: Version, end: Version) -> anyhow::Result<()>;
+
     /// Records the current progress of the pruner by updating the least readable version
     fn record_progress(&self, min_readable_version: Version);
 
@@ -76,4 +80,7 @@ pub enum Command {
         /// The target DB version for the pruner.
         target_db_version: Option<Version>,
     },
+    /// Stops pruning, while still taking in the targets, until `Resume` is received.
+    Pause,
+    Resume,
 }
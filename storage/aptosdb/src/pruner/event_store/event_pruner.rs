@@ -0,0 +1,117 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+use crate::{
+    event::EventSchema,
+    metrics::PRUNER_LEAST_READABLE_VERSION,
+    pruner::{
+        db_pruner::DBPruner, db_sub_pruner::DBSubPruner,
+        event_store::event_store_pruner::EventStorePruner, PrunerIndex,
+    },
+    schema::pruner_progress::{PrunerProgress, PrunerProgressSchema},
+    EventStore,
+};
+use aptos_types::transaction::{AtomicVersion, Version};
+use schemadb::{ReadOptions, SchemaBatch, DB};
+use std::sync::{atomic::Ordering, Arc};
+
+pub const EVENT_PRUNER_NAME: &str = "event pruner";
+
+/// Prunes the event store on its own window, instead of along with the other ledger stores in the
+/// `LedgerPruner`.
+pub struct EventPruner {
+    db: Arc<DB>,
+    /// Keeps track of the target version that the pruner needs to achieve.
+    target_version: AtomicVersion,
+    min_readable_version: AtomicVersion,
+    event_store_pruner: Arc<dyn DBSubPruner + Send + Sync>,
+}
+
+impl DBPruner for EventPruner {
+    fn name(&self) -> &'static str {
+        EVENT_PRUNER_NAME
+    }
+
+    fn prune(&self, max_versions: usize) -> anyhow::Result<Version> {
+        if !self.is_pruning_pending() {
+            return Ok(self.min_readable_version());
+        }
+        let mut db_batch = SchemaBatch::new();
+        let min_readable_version = self.min_readable_version();
+        // Current target version might be less than the target version to ensure we don't prune
+        // more than max_version in one go.
+        let current_target_version = self.get_currrent_batch_target(max_versions as Version);
+
+        self.event_store_pruner.prune(
+            &mut db_batch,
+            min_readable_version,
+            current_target_version,
+        )?;
+
+        db_batch.put::<PrunerProgressSchema>(
+            &PrunerIndex::EventStorePrunerIndex,
+            &PrunerProgress {
+                min_readable_version: current_target_version,
+                target_version: self.target_version(),
+            },
+        )?;
+
+        self.record_progress(current_target_version);
+        self.db.write_schemas(db_batch)?;
+        Ok(current_target_version)
+    }
+
+    fn initialize_min_readable_version(&self) -> anyhow::Result<Version> {
+        if let Some(progress) = self
+            .db
+            .get::<PrunerProgressSchema>(&PrunerIndex::EventStorePrunerIndex)?
+        {
+            // Carry on with the prune in flight when the pruner stopped, if any.
+            self.set_target_version(progress.target_version);
+            return Ok(progress.min_readable_version);
+        }
+        let mut iter = self.db.iter::<EventSchema>(ReadOptions::default())?;
+        iter.seek_to_first();
+        let version = iter
+            .next()
+            .transpose()?
+            .map_or(0, |((version, _index), _)| version);
+        Ok(version)
+    }
+
+    fn min_readable_version(&self) -> Version {
+        self.min_readable_version.load(Ordering::Relaxed)
+    }
+
+    fn set_target_version(&self, target_version: Version) {
+        self.target_version.store(target_version, Ordering::Relaxed)
+    }
+
+    fn target_version(&self) -> Version {
+        self.target_version.load(Ordering::Relaxed)
+    }
+
+    fn compact(&self, begin: Version, end: Version) -> anyhow::Result<()> {
+        self.db.compact_range::<EventSchema, Version>(&begin, &end)
+    }
+
+    fn record_progress(&self, min_readable_version: Version) {
+        self.min_readable_version
+            .store(min_readable_version, Ordering::Relaxed);
+        PRUNER_LEAST_READABLE_VERSION
+            .with_label_values(&["event_store"])
+            .set(min_readable_version as i64);
+    }
+}
+
+impl EventPruner {
+    pub(in crate::pruner) fn new(db: Arc<DB>, event_store: Arc<EventStore>) -> Self {
+        let pruner = EventPruner {
+            db,
+            target_version: AtomicVersion::new(0),
+            min_readable_version: AtomicVersion::new(0),
+            event_store_pruner: Arc::new(EventStorePruner::new(event_store)),
+        };
+        pruner.initialize();
+        pruner
+    }
+}
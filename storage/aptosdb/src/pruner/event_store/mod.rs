@@ -1,6 +1,7 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
+pub(crate) mod event_pruner;
 pub(crate) mod event_store_pruner;
 #[cfg(test)]
 mod test;
@@ -47,6 +47,61 @@ proptest! {
         verify_event_store_pruner_disabled(event_batches);
     }
 
+    #[test]
+    fn test_event_store_pruner_own_window(
+        mut universe in any_with::<AccountInfoUniverse>(3),
+        gen_batches in vec(vec((any::<Index>(), any::<ContractEventGen>()), 0..=2), 0..100),
+    ) {
+        let event_batches = gen_batches
+            .into_iter()
+            .map(|gens| {
+                gens.into_iter()
+                    .map(|(index, gen)| gen.materialize(*index, &mut universe))
+                    .collect()
+            })
+            .collect();
+
+        verify_event_store_pruner_own_window(event_batches);
+    }
+
+}
+
+#[test]
+fn test_min_readable_version_with_events() {
+    let tmp_dir = TempPath::new();
+    let aptos_db = AptosDB::new_for_test(&tmp_dir);
+    let pruner = Pruner::new(
+        Arc::clone(&aptos_db.ledger_db),
+        Arc::clone(&aptos_db.state_merkle_db),
+        StoragePrunerConfig {
+            state_store_prune_window: Some(0),
+            ledger_prune_window: Some(4),
+            state_store_prune_window_secs: None,
+            ledger_prune_window_secs: None,
+            event_store_prune_window: Some(2),
+            epoch_snapshots_to_retain: 0,
+            ledger_pruning_batch_size: 1,
+            state_store_pruning_batch_size: 100,
+            ledger_max_versions_per_sec: None,
+            state_store_max_versions_per_sec: None,
+            compaction_threshold_versions: None,
+            prune_only_backed_up: false,
+        },
+    );
+
+    pruner
+        .wake_and_wait_ledger_pruner(10 /* latest_version */)
+        .unwrap();
+    pruner
+        .wake_and_wait_event_pruner(10 /* latest_version */)
+        .unwrap();
+    assert_eq!(pruner.get_min_readable_ledger_version(), Some(6));
+    assert_eq!(
+        pruner.get_min_readable_version_by_pruner_index(PrunerIndex::EventStorePrunerIndex),
+        Some(8)
+    );
+    // the transactions before 8 are still there, but not their events
+    assert_eq!(pruner.get_min_readable_version_with_events(), Some(8));
 }
 
 fn verify_event_store_pruner(events: Vec<Vec<ContractEvent>>) {
@@ -61,8 +116,16 @@ fn verify_event_store_pruner(events: Vec<Vec<ContractEvent>>) {
         StoragePrunerConfig {
             state_store_prune_window: Some(0),
             ledger_prune_window: Some(0),
+            state_store_prune_window_secs: None,
+            ledger_prune_window_secs: None,
+            event_store_prune_window: None,
+            epoch_snapshots_to_retain: 0,
             ledger_pruning_batch_size: 1,
             state_store_pruning_batch_size: 100,
+            ledger_max_versions_per_sec: None,
+            state_store_max_versions_per_sec: None,
+            compaction_threshold_versions: None,
+            prune_only_backed_up: false,
         },
     );
 
@@ -94,6 +157,60 @@ fn verify_event_store_pruner(events: Vec<Vec<ContractEvent>>) {
     }
 }
 
+fn verify_event_store_pruner_own_window(events: Vec<Vec<ContractEvent>>) {
+    let tmp_dir = TempPath::new();
+    let aptos_db = AptosDB::new_for_test(&tmp_dir);
+    let event_store = &aptos_db.event_store;
+    let mut cs = ChangeSet::new();
+    let num_versions = events.len();
+    let pruner = Pruner::new(
+        Arc::clone(&aptos_db.ledger_db),
+        Arc::clone(&aptos_db.state_merkle_db),
+        StoragePrunerConfig {
+            state_store_prune_window: Some(0),
+            ledger_prune_window: None,
+            state_store_prune_window_secs: None,
+            ledger_prune_window_secs: None,
+            event_store_prune_window: Some(0),
+            epoch_snapshots_to_retain: 0,
+            ledger_pruning_batch_size: 1,
+            state_store_pruning_batch_size: 100,
+            ledger_max_versions_per_sec: None,
+            state_store_max_versions_per_sec: None,
+            compaction_threshold_versions: None,
+            prune_only_backed_up: false,
+        },
+    );
+
+    // Write events to DB
+    for (version, events_for_version) in events.iter().enumerate() {
+        event_store
+            .put_events(version as u64, events_for_version, &mut cs)
+            .unwrap();
+    }
+    aptos_db.ledger_db.write_schemas(cs.batch).unwrap();
+
+    // the events are pruned although the ledger pruner is disabled
+    pruner
+        .ensure_disabled(PrunerIndex::LedgerPrunerIndex)
+        .unwrap();
+    for i in (0..=num_versions).step_by(2) {
+        pruner
+            .wake_and_wait_event_pruner(i as u64 /* latest_version */)
+            .unwrap();
+        for j in 0..i {
+            verify_events_not_in_store(j as u64, event_store);
+            verify_event_by_key_not_in_store(&events, j as u64, event_store);
+            verify_event_by_version_not_in_store(&events, j as u64, event_store);
+        }
+        for j in i..num_versions {
+            verify_events_in_store(&events, j as u64, event_store);
+            verify_event_by_key_in_store(&events, j as u64, event_store);
+            verify_event_by_version_in_store(&events, j as u64, event_store);
+        }
+    }
+}
+
 fn verify_event_store_pruner_disabled(events: Vec<Vec<ContractEvent>>) {
     let tmp_dir = TempPath::new();
     let aptos_db = AptosDB::new_for_test(&tmp_dir);
@@ -106,8 +223,16 @@ fn verify_event_store_pruner_disabled(events: Vec<Vec<ContractEvent>>) {
         StoragePrunerConfig {
             state_store_prune_window: Some(0),
             ledger_prune_window: None,
+            state_store_prune_window_secs: None,
+            ledger_prune_window_secs: None,
+            event_store_prune_window: None,
+            epoch_snapshots_to_retain: 0,
             ledger_pruning_batch_size: 1,
             state_store_pruning_batch_size: 100,
+            ledger_max_versions_per_sec: None,
+            state_store_max_versions_per_sec: None,
+            compaction_threshold_versions: None,
+            prune_only_backed_up: false,
         },
     );
 
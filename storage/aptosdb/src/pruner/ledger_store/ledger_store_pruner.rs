@@ -1,6 +1,7 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 use crate::{
+    event::EventSchema,
     metrics::PRUNER_LEAST_READABLE_VERSION,
     pruner::{
         db_pruner::DBPruner,
@@ -10,6 +11,13 @@ use crate::{
         transaction_store::{
             transaction_store_pruner::TransactionStorePruner, write_set_pruner::WriteSetPruner,
         },
+        PrunerIndex,
+    },
+    schema::{
+        ledger_counters::LedgerCountersSchema,
+        pruner_progress::{PrunerProgress, PrunerProgressSchema},
+        transaction_info::TransactionInfoSchema,
+        write_set::WriteSetSchema,
     },
     transaction::TransactionSchema,
     EventStore, LedgerStore, TransactionStore,
@@ -26,7 +34,8 @@ pub struct LedgerPruner {
     target_version: AtomicVersion,
     min_readable_version: AtomicVersion,
     transaction_store_pruner: Arc<dyn DBSubPruner + Send + Sync>,
-    event_store_pruner: Arc<dyn DBSubPruner + Send + Sync>,
+    /// None if the event store is pruned on its own window, by the `EventPruner`.
+    event_store_pruner: Option<Arc<dyn DBSubPruner + Send + Sync>>,
     write_set_pruner: Arc<dyn DBSubPruner + Send + Sync>,
     ledger_counter_pruner: Arc<dyn DBSubPruner + Send + Sync>,
 }
@@ -59,10 +68,20 @@ impl DBPruner for LedgerPruner {
             current_target_version,
         )?;
 
-        self.event_store_pruner.prune(
-            &mut db_batch,
-            min_readable_version,
-            current_target_version,
+        if let Some(event_store_pruner) = &self.event_store_pruner {
+            event_store_pruner.prune(
+                &mut db_batch,
+                min_readable_version,
+                current_target_version,
+            )?;
+        }
+
+        db_batch.put::<PrunerProgressSchema>(
+            &PrunerIndex::LedgerPrunerIndex,
+            &PrunerProgress {
+                min_readable_version: current_target_version,
+                target_version: self.target_version(),
+            },
         )?;
 
         self.record_progress(current_target_version);
@@ -72,6 +91,14 @@ impl DBPruner for LedgerPruner {
     }
 
     fn initialize_min_readable_version(&self) -> anyhow::Result<Version> {
+        if let Some(progress) = self
+            .db
+            .get::<PrunerProgressSchema>(&PrunerIndex::LedgerPrunerIndex)?
+        {
+            // Carry on with the prune in flight when the pruner stopped, if any.
+            self.set_target_version(progress.target_version);
+            return Ok(progress.min_readable_version);
+        }
         let mut iter = self.db.iter::<TransactionSchema>(ReadOptions::default())?;
         iter.seek_to_first();
         let version = iter.next().transpose()?.map_or(0, |(version, _)| version);
@@ -90,6 +117,22 @@ impl DBPruner for LedgerPruner {
         self.target_version.load(Ordering::Relaxed)
     }
 
+    fn compact(&self, begin: Version, end: Version) -> anyhow::Result<()> {
+        self.db
+            .compact_range::<TransactionSchema, Version>(&begin, &end)?;
+        self.db
+            .compact_range::<TransactionInfoSchema, Version>(&begin, &end)?;
+        self.db
+            .compact_range::<WriteSetSchema, Version>(&begin, &end)?;
+        self.db
+            .compact_range::<LedgerCountersSchema, Version>(&begin, &end)?;
+        if self.event_store_pruner.is_some() {
+            self.db
+                .compact_range::<EventSchema, Version>(&begin, &end)?;
+        }
+        Ok(())
+    }
+
     fn record_progress(&self, min_readable_version: Version) {
         self.min_readable_version
             .store(min_readable_version, Ordering::Relaxed);
@@ -105,6 +148,7 @@ impl LedgerPruner {
         transaction_store: Arc<TransactionStore>,
         event_store: Arc<EventStore>,
         ledger_store: Arc<LedgerStore>,
+        prune_events: bool,
     ) -> Self {
         let pruner = LedgerPruner {
             db,
@@ -114,7 +158,9 @@ impl LedgerPruner {
             transaction_store_pruner: Arc::new(TransactionStorePruner::new(
                 transaction_store.clone(),
             )),
-            event_store_pruner: Arc::new(EventStorePruner::new(event_store)),
+            event_store_pruner: prune_events.then(|| {
+                Arc::new(EventStorePruner::new(event_store)) as Arc<dyn DBSubPruner + Send + Sync>
+            }),
             write_set_pruner: Arc::new(WriteSetPruner::new(transaction_store)),
         };
         pruner.initialize();
@@ -7,23 +7,30 @@
 mod db_pruner;
 pub(crate) mod db_sub_pruner;
 pub(crate) mod event_store;
-pub(crate) mod ledger_pruner_worker;
 mod ledger_store;
-pub(crate) mod state_pruner_worker;
+pub(crate) mod pruner_worker;
 pub(crate) mod state_store;
 pub(crate) mod transaction_store;
 pub mod utils;
 
-use crate::metrics::{PRUNER_BATCH_SIZE, PRUNER_WINDOW};
+use crate::{
+    backup_watermark::BackupWatermarkSchema,
+    metrics::{PRUNER_BATCH_SIZE, PRUNER_WINDOW},
+    EventStore, TransactionStore,
+};
 
+use anyhow::{ensure, Result};
 use aptos_config::config::StoragePrunerConfig;
 use aptos_infallible::Mutex;
+use aptos_logger::error;
 
 use crate::pruner::PrunerIndex::LedgerPrunerIndex;
 use aptos_types::transaction::Version;
-use ledger_pruner_worker::LedgerPrunerWorker;
+#[cfg(test)]
+use proptest_derive::Arbitrary;
+use pruner_worker::PrunerWorker;
 use schemadb::DB;
-use state_pruner_worker::StatePrunerWorker;
+use serde::{Deserialize, Serialize};
 use std::{
     sync::{
         mpsc::{channel, Sender},
@@ -45,6 +52,21 @@ pub(crate) struct Pruner {
     /// DB version window, which dictates how many version of other stores like transaction, ledger
     /// info, events etc to keep.
     ledger_prune_window: Option<Version>,
+    /// Age windows in seconds, on top of the version windows above.
+    state_store_prune_window_secs: Option<u64>,
+    ledger_prune_window_secs: Option<u64>,
+    /// DB version window of the event store, if it is pruned apart from the other ledger stores.
+    event_store_prune_window: Option<Version>,
+    /// The version windows the pruners were last woken with, which the age windows translate to.
+    /// Without an age window, this is the version window.
+    state_store_effective_prune_window: Mutex<Option<Version>>,
+    ledger_effective_prune_window: Mutex<Option<Version>>,
+    /// The targets the age windows were last looked up as.
+    state_store_age_window_target: Mutex<Option<AgeWindowTarget>>,
+    ledger_age_window_target: Mutex<Option<AgeWindowTarget>>,
+    /// Used to find the versions at the edge of the age windows.
+    transaction_store: TransactionStore,
+    event_store: EventStore,
     /// The worker thread handle for state_pruner, created upon Pruner instance construction and
     /// joined upon its destruction. It only becomes `None` after joined in `drop()`.
     state_pruner_worker_thread: Option<JoinHandle<()>>,
@@ -55,26 +77,56 @@ pub(crate) struct Pruner {
     ledger_pruner_worker_thread: Option<JoinHandle<()>>,
     /// The sender side of the channel talking to the ledger pruner worker thread.
     ledger_pruner_command_sender: Mutex<Sender<db_pruner::Command>>,
+    /// The worker thread handle for event_pruner, like the ones above.
+    event_pruner_worker_thread: Option<JoinHandle<()>>,
+    /// The sender side of the channel talking to the event pruner worker thread.
+    event_pruner_command_sender: Mutex<Sender<db_pruner::Command>>,
     /// A way for the worker thread to inform the `Pruner` the pruning progress. If it
     /// sets value to `V`, all versions before `V` can no longer be accessed. This is protected by
     /// Mutex as this is accessed both by the Pruner thread and the worker thread.
     #[allow(dead_code)]
     state_pruner_min_readable_version: Arc<Mutex<Option<Version>>>,
     ledger_pruner_min_readable_version: Arc<Mutex<Option<Version>>>,
+    event_pruner_min_readable_version: Arc<Mutex<Option<Version>>>,
+    /// The versions of the epoch ending snapshots the state pruner retains, as it last looked them
+    /// up. They stay readable below its min readable version.
+    state_pruner_retained_snapshot_versions: Arc<Mutex<Vec<Version>>>,
     /// We send a batch of version to the underlying pruners for performance reason. This tracks the
     /// last version we sent to the pruners.
     last_version_sent_to_state_pruner: Arc<Mutex<Version>>,
     last_version_sent_to_ledger_pruner: Arc<Mutex<Version>>,
+    last_version_sent_to_event_pruner: Arc<Mutex<Version>>,
     /// Ideal batch size of the versions to be sent to the ledger pruner
     ledger_pruner_pruning_batch_size: usize,
+    /// The state pruner is woken on every commit, this is how many versions it waits for before
+    /// looking up its age window again.
+    state_store_pruning_batch_size: usize,
     /// latest version
     latest_version: Arc<Mutex<Version>>,
+    /// Whether to cap the pruning targets at `backup_watermark`.
+    prune_only_backed_up: bool,
+    /// The last version backed up, as last reported by the backup coordinator. None until the
+    /// first report.
+    backup_watermark: Mutex<Option<Version>>,
+    /// The ledger DB, where the backup watermark is kept across restarts.
+    ledger_db: Arc<DB>,
+}
+
+/// The pruning target of an age window, as of a lookup.
+#[derive(Clone, Copy, Debug)]
+struct AgeWindowTarget {
+    /// The latest version at the time of the lookup.
+    latest_version: Version,
+    target_version: Version,
 }
 
+/// Identifies one of the pruners.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[cfg_attr(test, derive(Arbitrary))]
 pub enum PrunerIndex {
-    #[allow(dead_code)]
     StateStorePrunerIndex,
     LedgerPrunerIndex,
+    EventStorePrunerIndex,
 }
 
 impl Pruner {
@@ -86,21 +138,35 @@ impl Pruner {
     ) -> Self {
         let (state_pruner_command_sender, state_pruner_command_receiver) = channel();
         let (ledger_pruner_command_sender, ledger_pruner_command_receiver) = channel();
+        let (event_pruner_command_sender, event_pruner_command_receiver) = channel();
 
         let state_pruner_min_readable_version = Arc::new(Mutex::new(
-            storage_pruner_config.state_store_prune_window.map(|_| 0),
+            storage_pruner_config
+                .state_store_pruning_enabled()
+                .then(|| 0),
         ));
 
         let state_pruner_min_readable_version_clone =
             Arc::clone(&state_pruner_min_readable_version);
 
         let ledger_pruner_min_readable_version = Arc::new(Mutex::new(
-            storage_pruner_config.ledger_prune_window.map(|_| 0),
+            storage_pruner_config.ledger_pruning_enabled().then(|| 0),
         ));
 
         let ledger_pruner_min_readable_version_clone =
             Arc::clone(&ledger_pruner_min_readable_version);
 
+        let event_pruner_min_readable_version = Arc::new(Mutex::new(
+            storage_pruner_config
+                .event_store_pruning_enabled()
+                .then(|| 0),
+        ));
+
+        let event_pruner_min_readable_version_clone =
+            Arc::clone(&event_pruner_min_readable_version);
+
+        let state_pruner_retained_snapshot_versions = Arc::new(Mutex::new(vec![]));
+
         PRUNER_WINDOW
             .with_label_values(&["state_pruner"])
             .set((storage_pruner_config.state_store_prune_window.unwrap_or(0)) as i64);
@@ -109,6 +175,10 @@ impl Pruner {
             .with_label_values(&["ledger_pruner"])
             .set((storage_pruner_config.ledger_prune_window.unwrap_or(0)) as i64);
 
+        PRUNER_WINDOW
+            .with_label_values(&["event_pruner"])
+            .set((storage_pruner_config.event_store_prune_window.unwrap_or(0)) as i64);
+
         PRUNER_BATCH_SIZE
             .with_label_values(&["ledger_pruner"])
             .set(storage_pruner_config.ledger_pruning_batch_size as i64);
@@ -117,19 +187,37 @@ impl Pruner {
             .with_label_values(&["state_store_pruner"])
             .set(storage_pruner_config.state_store_pruning_batch_size as i64);
 
-        let state_pruner_worker = StatePrunerWorker::new(
+        let state_pruner_worker = PrunerWorker::new(
+            PrunerIndex::StateStorePrunerIndex,
+            utils::create_state_pruner(
+                Arc::clone(&ledger_rocksdb),
+                Arc::clone(&state_merkle_rocksdb),
+                Arc::clone(&state_pruner_retained_snapshot_versions),
+                storage_pruner_config,
+            ),
             state_merkle_rocksdb,
             state_pruner_command_receiver,
             state_pruner_min_readable_version,
             storage_pruner_config,
         );
 
-        let ledger_pruner_worker = LedgerPrunerWorker::new(
-            ledger_rocksdb,
+        let ledger_pruner_worker = PrunerWorker::new(
+            PrunerIndex::LedgerPrunerIndex,
+            utils::create_ledger_pruner(Arc::clone(&ledger_rocksdb), storage_pruner_config),
+            Arc::clone(&ledger_rocksdb),
             ledger_pruner_command_receiver,
             ledger_pruner_min_readable_version,
             storage_pruner_config,
         );
+
+        let event_pruner_worker = PrunerWorker::new(
+            PrunerIndex::EventStorePrunerIndex,
+            utils::create_event_pruner(Arc::clone(&ledger_rocksdb), storage_pruner_config),
+            Arc::clone(&ledger_rocksdb),
+            event_pruner_command_receiver,
+            event_pruner_min_readable_version,
+            storage_pruner_config,
+        );
         let state_pruner_worker_thread = std::thread::Builder::new()
             .name("aptosdb_state_pruner".into())
             .spawn(move || state_pruner_worker.work())
@@ -140,28 +228,56 @@ impl Pruner {
             .spawn(move || ledger_pruner_worker.work())
             .expect("Creating ledger pruner thread should succeed.");
 
+        let event_pruner_worker_thread = std::thread::Builder::new()
+            .name("aptosdb_event_pruner".into())
+            .spawn(move || event_pruner_worker.work())
+            .expect("Creating event pruner thread should succeed.");
+
         Self {
             state_store_prune_window: storage_pruner_config.state_store_prune_window,
             ledger_prune_window: storage_pruner_config.ledger_prune_window,
+            state_store_prune_window_secs: storage_pruner_config.state_store_prune_window_secs,
+            ledger_prune_window_secs: storage_pruner_config.ledger_prune_window_secs,
+            event_store_prune_window: storage_pruner_config.event_store_prune_window,
+            state_store_effective_prune_window: Mutex::new(
+                storage_pruner_config.state_store_prune_window,
+            ),
+            ledger_effective_prune_window: Mutex::new(storage_pruner_config.ledger_prune_window),
+            state_store_age_window_target: Mutex::new(None),
+            ledger_age_window_target: Mutex::new(None),
+            transaction_store: TransactionStore::new(Arc::clone(&ledger_rocksdb)),
+            event_store: EventStore::new(Arc::clone(&ledger_rocksdb)),
             state_pruner_worker_thread: Some(state_pruner_worker_thread),
             state_pruner_command_sender: Mutex::new(state_pruner_command_sender),
             ledger_pruner_worker_thread: Some(ledger_pruner_worker_thread),
             ledger_pruner_command_sender: Mutex::new(ledger_pruner_command_sender),
+            event_pruner_worker_thread: Some(event_pruner_worker_thread),
+            event_pruner_command_sender: Mutex::new(event_pruner_command_sender),
             state_pruner_min_readable_version: state_pruner_min_readable_version_clone,
             ledger_pruner_min_readable_version: ledger_pruner_min_readable_version_clone,
+            event_pruner_min_readable_version: event_pruner_min_readable_version_clone,
+            state_pruner_retained_snapshot_versions,
             last_version_sent_to_state_pruner: Arc::new(Mutex::new(0)),
             last_version_sent_to_ledger_pruner: Arc::new(Mutex::new(0)),
+            last_version_sent_to_event_pruner: Arc::new(Mutex::new(0)),
             ledger_pruner_pruning_batch_size: storage_pruner_config.ledger_pruning_batch_size,
+            state_store_pruning_batch_size: storage_pruner_config.state_store_pruning_batch_size,
             latest_version: Arc::new(Mutex::new(0)),
+            prune_only_backed_up: storage_pruner_config.prune_only_backed_up,
+            backup_watermark: Mutex::new(Self::load_backup_watermark(&ledger_rocksdb)),
+            ledger_db: ledger_rocksdb,
         }
     }
 
+    /// The number of versions the state store retains. With an age window, this is as of the last
+    /// time the pruner was woken, and None before that.
     pub fn get_state_store_pruner_window(&self) -> Option<Version> {
-        self.state_store_prune_window
+        *self.state_store_effective_prune_window.lock()
     }
 
+    /// The number of versions the other stores retain, like `get_state_store_pruner_window()`.
     pub fn get_ledger_pruner_window(&self) -> Option<Version> {
-        self.ledger_prune_window
+        *self.ledger_effective_prune_window.lock()
     }
 
     pub fn get_min_readable_version_by_pruner_index(
@@ -175,12 +291,33 @@ impl Pruner {
             PrunerIndex::LedgerPrunerIndex => {
                 self.ledger_pruner_min_readable_version.lock().map(|x| x)
             }
+            PrunerIndex::EventStorePrunerIndex => {
+                self.event_pruner_min_readable_version.lock().map(|x| x)
+            }
         };
     }
 
     pub fn get_min_readable_ledger_version(&self) -> Option<Version> {
         self.get_min_readable_version_by_pruner_index(LedgerPrunerIndex)
     }
+
+    /// The first version whose transaction and events are both readable, since the events are
+    /// pruned on a window of their own if `event_store_prune_window` is set. None if the ledger
+    /// pruner is disabled.
+    pub fn get_min_readable_version_with_events(&self) -> Option<Version> {
+        let min_readable_ledger_version = self.get_min_readable_ledger_version()?;
+        Some(
+            self.get_min_readable_version_by_pruner_index(PrunerIndex::EventStorePrunerIndex)
+                .map_or(min_readable_ledger_version, |min_readable_event_version| {
+                    min_readable_ledger_version.max(min_readable_event_version)
+                }),
+        )
+    }
+
+    /// The versions of the epoch ending snapshots the state pruner retains, in ascending order.
+    pub fn get_state_retained_snapshot_versions(&self) -> Vec<Version> {
+        self.state_pruner_retained_snapshot_versions.lock().clone()
+    }
     /// Sends pruning command to the worker thread when necessary.
     pub fn maybe_wake_pruner(&self, latest_version: Version) {
         *self.latest_version.lock() = latest_version;
@@ -198,28 +335,245 @@ impl Pruner {
             self.wake_ledger_pruner(latest_version);
             *self.last_version_sent_to_ledger_pruner.as_ref().lock() = latest_version;
         }
+
+        // Same for the event pruner.
+        if latest_version
+            >= *self.last_version_sent_to_event_pruner.as_ref().lock()
+                + self.ledger_pruner_pruning_batch_size as u64
+        {
+            self.wake_event_pruner(latest_version);
+            *self.last_version_sent_to_event_pruner.as_ref().lock() = latest_version;
+        }
     }
 
     fn wake_state_pruner(&self, latest_version: Version) {
+        let target_db_version = self.target_version(
+            latest_version,
+            self.state_store_prune_window,
+            self.state_store_prune_window_secs,
+            &self.state_store_age_window_target,
+            self.state_store_pruning_batch_size as u64,
+        );
+        if self.state_store_prune_window_secs.is_some() {
+            self.record_effective_window(
+                "state_pruner",
+                &self.state_store_effective_prune_window,
+                latest_version,
+                target_db_version,
+            );
+        }
+        let target_db_version = self.cap_at_backup_watermark(target_db_version);
         self.state_pruner_command_sender
             .lock()
-            .send(db_pruner::Command::Prune {
-                target_db_version: self
-                    .state_store_prune_window
-                    .map(|x| latest_version.saturating_sub(x)),
-            })
+            .send(db_pruner::Command::Prune { target_db_version })
             .expect("Receiver should not destruct prematurely.");
     }
 
     fn wake_ledger_pruner(&self, latest_version: Version) {
+        let target_db_version = self.target_version(
+            latest_version,
+            self.ledger_prune_window,
+            self.ledger_prune_window_secs,
+            &self.ledger_age_window_target,
+            self.ledger_pruner_pruning_batch_size as u64,
+        );
+        if self.ledger_prune_window_secs.is_some() {
+            self.record_effective_window(
+                "ledger_pruner",
+                &self.ledger_effective_prune_window,
+                latest_version,
+                target_db_version,
+            );
+        }
+        let target_db_version = self.cap_at_backup_watermark(target_db_version);
         self.ledger_pruner_command_sender
+            .lock()
+            .send(db_pruner::Command::Prune { target_db_version })
+            .expect("Receiver should not destruct prematurely.");
+    }
+
+    fn wake_event_pruner(&self, latest_version: Version) {
+        let target_db_version = self
+            .event_store_prune_window
+            .map(|x| latest_version.saturating_sub(x));
+        let target_db_version = self.cap_at_backup_watermark(target_db_version);
+        self.event_pruner_command_sender
+            .lock()
+            .send(db_pruner::Command::Prune { target_db_version })
+            .expect("Receiver should not destruct prematurely.");
+    }
+
+    /// The version to prune up to, so both the version window and the age window are retained.
+    /// None if the pruner is disabled.
+    fn target_version(
+        &self,
+        latest_version: Version,
+        prune_window: Option<Version>,
+        prune_window_secs: Option<u64>,
+        age_window_target: &Mutex<Option<AgeWindowTarget>>,
+        lookup_interval: Version,
+    ) -> Option<Version> {
+        let by_versions = prune_window.map(|x| latest_version.saturating_sub(x));
+        let by_age = prune_window_secs.map(|window_secs| {
+            self.age_window_target_version(
+                latest_version,
+                window_secs,
+                age_window_target,
+                lookup_interval,
+            )
+        });
+        match (by_versions, by_age) {
+            (Some(by_versions), Some(by_age)) => Some(by_versions.min(by_age)),
+            (by_versions, by_age) => by_versions.or(by_age),
+        }
+    }
+
+    /// The version to prune up to so the age window is retained. The lookup is a binary search
+    /// over the new block events, so it's only redone once `lookup_interval` versions were
+    /// committed since the last one. The target stays where it was if the lookup fails.
+    fn age_window_target_version(
+        &self,
+        latest_version: Version,
+        window_secs: u64,
+        age_window_target: &Mutex<Option<AgeWindowTarget>>,
+        lookup_interval: Version,
+    ) -> Version {
+        let mut age_window_target = age_window_target.lock();
+        if let Some(last_target) = *age_window_target {
+            if latest_version < last_target.latest_version.saturating_add(lookup_interval) {
+                return last_target.target_version;
+            }
+        }
+
+        let last_target_version = age_window_target.map_or(0, |target| target.target_version);
+        let target_version = match utils::min_version_in_age_window(
+            &self.transaction_store,
+            &self.event_store,
+            latest_version,
+            window_secs,
+        ) {
+            Ok(target_version) => target_version.map_or(last_target_version, |target_version| {
+                target_version.max(last_target_version)
+            }),
+            Err(e) => {
+                error!(
+                    error = ?e,
+                    latest_version = latest_version,
+                    window_secs = window_secs,
+                    "Error looking up the version at the edge of the age window.",
+                );
+                last_target_version
+            }
+        };
+        *age_window_target = Some(AgeWindowTarget {
+            latest_version,
+            target_version,
+        });
+        target_version
+    }
+
+    /// Caps a pruning target so the versions not backed up yet are kept, if so configured.
+    fn cap_at_backup_watermark(&self, target_db_version: Option<Version>) -> Option<Version> {
+        if !self.prune_only_backed_up {
+            return target_db_version;
+        }
+        // Versions before the target are pruned, so the watermark itself can go.
+        let max_target_version = self.backup_watermark.lock().map_or(0, |v| v + 1);
+        target_db_version.map(|target| target.min(max_target_version))
+    }
+
+    /// Records that all the versions up to `version` are backed up, so they can be pruned, and
+    /// wakes the pruners to catch up with it. The watermark never goes back, and is kept across
+    /// restarts.
+    pub fn set_backup_watermark(&self, version: Version) -> Result<()> {
+        {
+            let mut backup_watermark = self.backup_watermark.lock();
+            if backup_watermark.map_or(false, |watermark| watermark >= version) {
+                return Ok(());
+            }
+            self.ledger_db.put::<BackupWatermarkSchema>(&(), &version)?;
+            *backup_watermark = Some(version);
+        }
+        if self.prune_only_backed_up {
+            let latest_version = *self.latest_version.lock();
+            self.wake_state_pruner(latest_version);
+            self.wake_ledger_pruner(latest_version);
+            self.wake_event_pruner(latest_version);
+        }
+        Ok(())
+    }
+
+    /// The backup watermark as of the last report before the node restarted, if any.
+    fn load_backup_watermark(ledger_db: &DB) -> Option<Version> {
+        ledger_db
+            .get::<BackupWatermarkSchema>(&())
+            .unwrap_or_else(|e| {
+                error!(error = ?e, "Error reading the backup watermark.");
+                None
+            })
+    }
+
+    fn record_effective_window(
+        &self,
+        pruner_name: &str,
+        effective_window: &Mutex<Option<Version>>,
+        latest_version: Version,
+        target_db_version: Option<Version>,
+    ) {
+        let window = target_db_version.map(|target| latest_version.saturating_sub(target));
+        *effective_window.lock() = window;
+        PRUNER_WINDOW
+            .with_label_values(&[pruner_name])
+            .set(window.unwrap_or(0) as i64);
+    }
+
+    /// Makes the pruner prune up to `target_version` right away, regardless of its window. A pruner
+    /// never lowers its target, so the following wakes don't cut this short.
+    pub fn prune_to(&self, pruner_index: PrunerIndex, target_version: Version) -> Result<()> {
+        let command_sender = match pruner_index {
+            PrunerIndex::StateStorePrunerIndex => &self.state_pruner_command_sender,
+            PrunerIndex::LedgerPrunerIndex => &self.ledger_pruner_command_sender,
+            PrunerIndex::EventStorePrunerIndex => &self.event_pruner_command_sender,
+        };
+        ensure!(
+            self.get_min_readable_version_by_pruner_index(pruner_index)
+                .is_some(),
+            "The {:?} pruner is disabled.",
+            pruner_index
+        );
+        ensure!(
+            self.cap_at_backup_watermark(Some(target_version)) == Some(target_version),
+            "Versions before {} are not all backed up yet, the backup watermark is {:?}.",
+            target_version,
+            *self.backup_watermark.lock(),
+        );
+        command_sender
             .lock()
             .send(db_pruner::Command::Prune {
-                target_db_version: self
-                    .ledger_prune_window
-                    .map(|x| latest_version.saturating_sub(x)),
+                target_db_version: Some(target_version),
             })
             .expect("Receiver should not destruct prematurely.");
+        Ok(())
+    }
+
+    /// Pauses or resumes all the pruners. While paused, the pruners keep track of their targets
+    /// without deleting anything.
+    pub fn set_paused(&self, paused: bool) {
+        for command_sender in [
+            &self.state_pruner_command_sender,
+            &self.ledger_pruner_command_sender,
+            &self.event_pruner_command_sender,
+        ] {
+            let command = if paused {
+                db_pruner::Command::Pause
+            } else {
+                db_pruner::Command::Resume
+            };
+            command_sender
+                .lock()
+                .send(command)
+                .expect("Receiver should not destruct prematurely.");
+        }
     }
 
     /// (For tests only.) Notifies the worker thread and waits for it to finish its job by polling
@@ -296,6 +650,38 @@ impl Pruner {
         Ok(())
     }
 
+    #[cfg(test)]
+    pub fn wake_and_wait_event_pruner(&self, latest_version: Version) -> anyhow::Result<()> {
+        use std::{
+            thread::sleep,
+            time::{Duration, Instant},
+        };
+
+        *self.latest_version.lock() = latest_version;
+        self.wake_event_pruner(latest_version);
+        *self.last_version_sent_to_event_pruner.as_ref().lock() = latest_version;
+
+        if let Some(event_store_prune_window) = self.event_store_prune_window {
+            let min_readable_event_version =
+                latest_version.saturating_sub(event_store_prune_window);
+
+            // Assuming no big pruning chunks will be issued by a test.
+            const TIMEOUT: Duration = Duration::from_secs(10);
+            let end = Instant::now() + TIMEOUT;
+
+            while Instant::now() < end {
+                if self.event_pruner_min_readable_version.lock().unwrap()
+                    >= min_readable_event_version
+                {
+                    return Ok(());
+                }
+                sleep(Duration::from_millis(1));
+            }
+            anyhow::bail!("Timeout waiting for pruner worker.");
+        }
+        Ok(())
+    }
+
     /// (For tests only.) Ensure a pruner is disabled.
     #[cfg(test)]
     pub fn ensure_disabled(&self, pruner_index: PrunerIndex) -> anyhow::Result<()> {
@@ -308,6 +694,10 @@ impl Pruner {
                 assert!(self.ledger_pruner_min_readable_version.lock().is_none());
                 Ok(())
             }
+            PrunerIndex::EventStorePrunerIndex => {
+                assert!(self.event_pruner_min_readable_version.lock().is_none());
+                Ok(())
+            }
         };
     }
 
@@ -319,6 +709,9 @@ impl Pruner {
         ));
         self.ledger_pruner_min_readable_version =
             Arc::new(Mutex::new(version[PrunerIndex::LedgerPrunerIndex as usize]));
+        self.event_pruner_min_readable_version = Arc::new(Mutex::new(
+            version[PrunerIndex::EventStorePrunerIndex as usize],
+        ));
     }
 }
 
@@ -343,5 +736,15 @@ impl Drop for Pruner {
             .expect("Ledger pruner worker thread must exist.")
             .join()
             .expect("Ledger pruner worker thread should join peacefully.");
+
+        self.event_pruner_command_sender
+            .lock()
+            .send(db_pruner::Command::Quit)
+            .expect("Event pruner receiver should not destruct.");
+        self.event_pruner_worker_thread
+            .take()
+            .expect("Event pruner worker thread must exist.")
+            .join()
+            .expect("Event pruner worker thread should join peacefully.");
     }
 }
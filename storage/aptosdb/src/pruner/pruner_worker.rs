@@ -0,0 +1,227 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+use aptos_types::transaction::Version;
+use schemadb::DB;
+
+use crate::pruner::{db_pruner, db_pruner::DBPruner, utils, PrunerIndex};
+use aptos_config::config::StoragePrunerConfig;
+use aptos_infallible::Mutex;
+use aptos_logger::info;
+use std::{
+    sync::{mpsc::Receiver, Arc},
+    time::{Duration, Instant},
+};
+
+/// Maintains one of the pruners and periodically calls the db_pruner's prune method to prune the
+/// DB. This also exposes API to report the progress to the parent thread.
+pub struct PrunerWorker {
+    /// The label of the pruner in the metrics.
+    pruner_name: &'static str,
+    command_receiver: Receiver<db_pruner::Command>,
+    /// The pruner. If it is not enabled, its value will be None.
+    pruner: Option<Mutex<Arc<dyn DBPruner + Send + Sync>>>,
+    /// Keeps a record of the pruning progress. If this equals to version `V`, we know versions
+    /// smaller than `V` are no longer readable.
+    /// This being an atomic value is to communicate the info with the Pruner thread (for tests).
+    /// If the pruner is disabled, its value will be None.
+    min_readable_version: Arc<Mutex<Option<Version>>>,
+    /// Indicates if there's NOT any pending work to do currently, to hint
+    /// `Self::receive_commands()` to `recv()` blocking-ly.
+    blocking_recv: bool,
+    /// Whether pruning is paused, with `Command::Pause`.
+    paused: bool,
+    /// Max versions to prune per second, None if not rate limited.
+    max_versions_per_sec: Option<u64>,
+    /// When the next batch may start without exceeding `max_versions_per_sec`.
+    next_batch_at: Instant,
+    /// The DB pruned, to watch for RocksDB write stalls.
+    db: Arc<DB>,
+    /// How long the next batch is held back for a write stall, zero if not stalled.
+    write_stall_backoff: Duration,
+    /// Compact the pruned range every this many versions, None if left to RocksDB.
+    compaction_threshold_versions: Option<u64>,
+    /// Where the range pruned since the last compaction begins.
+    compaction_begin: Version,
+    /// Max items to prune per batch. For the state pruner, this means the max stale nodes to prune
+    /// and for the others, the max versions to prune.
+    max_items_to_prune_per_batch: u64,
+}
+
+impl PrunerWorker {
+    /// Creates the worker of the pruner at `pruner_index`, which prunes `db` and is configured by
+    /// `storage_pruner_config`.
+    pub(crate) fn new(
+        pruner_index: PrunerIndex,
+        pruner: Option<Mutex<Arc<dyn DBPruner + Send + Sync>>>,
+        db: Arc<DB>,
+        command_receiver: Receiver<db_pruner::Command>,
+        min_readable_version: Arc<Mutex<Option<Version>>>,
+        storage_pruner_config: StoragePrunerConfig,
+    ) -> Self {
+        let (pruner_name, max_items_to_prune_per_batch, max_versions_per_sec) = match pruner_index {
+            PrunerIndex::StateStorePrunerIndex => (
+                "state_store",
+                storage_pruner_config.state_store_pruning_batch_size,
+                storage_pruner_config.state_store_max_versions_per_sec,
+            ),
+            PrunerIndex::LedgerPrunerIndex => (
+                "ledger_pruner",
+                storage_pruner_config.ledger_pruning_batch_size,
+                storage_pruner_config.ledger_max_versions_per_sec,
+            ),
+            // The event store is pruned at the pace of the rest of the ledger.
+            PrunerIndex::EventStorePrunerIndex => (
+                "event_store",
+                storage_pruner_config.ledger_pruning_batch_size,
+                storage_pruner_config.ledger_max_versions_per_sec,
+            ),
+        };
+        let compaction_begin = pruner
+            .as_ref()
+            .map_or(0, |pruner| pruner.lock().min_readable_version());
+        // A prune in flight before a restart is picked up right away.
+        let pruning_pending = pruner.as_ref().map_or(false, |pruner| {
+            let pruner = pruner.lock();
+            pruner.target_version() > pruner.min_readable_version()
+        });
+        Self {
+            pruner_name,
+            pruner,
+            command_receiver,
+            min_readable_version,
+            blocking_recv: !pruning_pending,
+            paused: false,
+            max_versions_per_sec,
+            next_batch_at: Instant::now(),
+            db,
+            write_stall_backoff: Duration::ZERO,
+            compaction_threshold_versions: storage_pruner_config.compaction_threshold_versions,
+            compaction_begin,
+            max_items_to_prune_per_batch: max_items_to_prune_per_batch as u64,
+        }
+    }
+
+    pub(crate) fn work(mut self) {
+        // Publish the progress the pruner resumed from.
+        self.record_progress();
+        while self.receive_commands() {
+            if self.paused {
+                // Hold on to the pending work until resumed.
+                self.blocking_recv = true;
+                continue;
+            }
+            if self.pruner.is_some()
+                && utils::back_off_on_write_stall(
+                    self.pruner_name,
+                    &self.db,
+                    &mut self.write_stall_backoff,
+                    &mut self.next_batch_at,
+                )
+            {
+                self.blocking_recv = false;
+                continue;
+            }
+            // Process a reasonably small batch of work before trying to receive commands again,
+            // in case `Command::Quit` is received (that's when we should quit.)
+            let mut error_in_pruning = false;
+            let mut pruning_pending = false;
+
+            if let Some(pruner) = &self.pruner {
+                let pruner = pruner.lock();
+                let start = Instant::now();
+                let min_readable_version = pruner.min_readable_version();
+                pruner
+                    .prune(self.max_items_to_prune_per_batch as usize)
+                    .map_err(|_| error_in_pruning = true)
+                    .ok();
+                let pruned_versions = pruner
+                    .min_readable_version()
+                    .saturating_sub(min_readable_version);
+                self.next_batch_at =
+                    start + utils::min_batch_duration(pruned_versions, self.max_versions_per_sec);
+                utils::record_pruner_progress(self.pruner_name, pruner.as_ref(), start.elapsed());
+                if let Some(compaction_threshold_versions) = self.compaction_threshold_versions {
+                    utils::compact_if_due(
+                        pruner.as_ref(),
+                        &mut self.compaction_begin,
+                        compaction_threshold_versions,
+                    );
+                }
+
+                if pruner.is_pruning_pending() {
+                    pruning_pending = true;
+                }
+            }
+
+            if !pruning_pending || error_in_pruning {
+                self.blocking_recv = true;
+            } else {
+                self.blocking_recv = false;
+            }
+            self.record_progress();
+        }
+    }
+
+    fn record_progress(&mut self) {
+        *self.min_readable_version.lock() = self
+            .pruner
+            .as_ref()
+            .map(|pruner| pruner.lock().min_readable_version());
+    }
+
+    /// Tries to receive all pending commands, blocking waits for the next command if no work needs
+    /// to be done, otherwise quits with `true` to allow the outer loop to do some work before
+    /// getting back here.
+    ///
+    /// Returns `false` if `Command::Quit` is received, to break the outer loop and let
+    /// `work_loop()` return.
+    fn receive_commands(&mut self) -> bool {
+        loop {
+            let command = if self.blocking_recv {
+                // Worker has nothing to do, blocking wait for the next command.
+                self.command_receiver
+                    .recv()
+                    .expect("Sender should not destruct prematurely.")
+            } else {
+                // Worker has pending work to do, non-blocking recv, unless the rate limit holds
+                // the next batch back, in which case keep receiving until it is due.
+                let timeout = self.next_batch_at.saturating_duration_since(Instant::now());
+                match self.command_receiver.recv_timeout(timeout) {
+                    Ok(command) => command,
+                    // Channel has drained (and the next batch is due), yield control to the outer
+                    // loop.
+                    Err(_) => return true,
+                }
+            };
+
+            match command {
+                // On `Command::Quit` inform the outer loop to quit by returning `false`.
+                db_pruner::Command::Quit => return false,
+                db_pruner::Command::Pause => {
+                    info!(pruner_name = self.pruner_name, "Pruner paused.");
+                    self.paused = true;
+                    self.blocking_recv = true;
+                }
+                db_pruner::Command::Resume => {
+                    info!(pruner_name = self.pruner_name, "Pruner resumed.");
+                    self.paused = false;
+                    // Get to the work that piled up while paused.
+                    self.blocking_recv = false;
+                }
+                db_pruner::Command::Prune { target_db_version } => {
+                    if let Some(pruner_target_version) = target_db_version {
+                        if let Some(pruner) = &self.pruner {
+                            if pruner_target_version > pruner.lock().target_version() {
+                                // Never lower the target, which could cut a manual prune short.
+                                pruner.lock().set_target_version(pruner_target_version);
+                                // Switch to non-blocking to allow some work to be done after the
+                                // channel has drained.
+                                self.blocking_recv = false;
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -2,11 +2,18 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    jellyfish_merkle_node::JellyfishMerkleNodeSchema, metrics::PRUNER_LEAST_READABLE_VERSION,
-    pruner::db_pruner::DBPruner, stale_node_index::StaleNodeIndexSchema, OTHER_TIMERS_SECONDS,
+    epoch_by_version::EpochByVersionSchema,
+    jellyfish_merkle_node::JellyfishMerkleNodeSchema,
+    metrics::PRUNER_LEAST_READABLE_VERSION,
+    pruner::{db_pruner::DBPruner, PrunerIndex},
+    pruner_progress::{PrunerProgress, PrunerProgressSchema},
+    stale_node_index::StaleNodeIndexSchema,
+    stale_node_index_cross_epoch::StaleNodeIndexCrossEpochSchema,
+    OTHER_TIMERS_SECONDS,
 };
 use anyhow::Result;
-use aptos_jellyfish_merkle::StaleNodeIndex;
+use aptos_infallible::Mutex;
+use aptos_jellyfish_merkle::{node_type::NodeKey, StaleNodeIndex};
 use aptos_logger::error;
 use aptos_types::transaction::{AtomicVersion, Version};
 use schemadb::{ReadOptions, SchemaBatch, DB};
@@ -22,12 +29,24 @@ pub const STATE_STORE_PRUNER_NAME: &str = "state store pruner";
 
 pub struct StateStorePruner {
     db: Arc<DB>,
+    /// The ledger DB, which knows the epoch ending versions.
+    ledger_db: Arc<DB>,
+    /// The number of latest epoch ending snapshots kept whatever the target version.
+    epoch_snapshots_to_retain: usize,
+    /// The versions of the retained snapshots as last looked up, shared with the `Pruner` so the
+    /// reads at them are let through.
+    retained_snapshot_versions: Arc<Mutex<Vec<Version>>>,
+    /// The latest epoch ending version as of which `retained_snapshot_versions` is up to date,
+    /// None if they need looking up.
+    retained_snapshots_as_of: Mutex<Option<Version>>,
     /// Keeps track of the target version that the pruner needs to achieve.
     target_version: AtomicVersion,
     min_readable_version: AtomicVersion,
     // Keeps track of if the target version has been fully pruned to see if there is pruning
     // pending.
     pruned_to_the_end_of_target_version: AtomicBool,
+    /// The oldest version of the nodes deleted since the last compaction, `Version::MAX` if none.
+    min_deleted_node_version: AtomicVersion,
 }
 
 impl DBPruner for StateStorePruner {
@@ -56,6 +75,14 @@ impl DBPruner for StateStorePruner {
     }
 
     fn initialize_min_readable_version(&self) -> Result<Version> {
+        if let Some(progress) = self
+            .db
+            .get::<PrunerProgressSchema>(&PrunerIndex::StateStorePrunerIndex)?
+        {
+            // Carry on with the prune in flight when the pruner stopped, if any.
+            self.set_target_version(progress.target_version);
+            return Ok(progress.min_readable_version);
+        }
         let mut iter = self
             .db
             .iter::<StaleNodeIndexSchema>(ReadOptions::default())?;
@@ -80,6 +107,24 @@ impl DBPruner for StateStorePruner {
         self.target_version.load(Ordering::Relaxed)
     }
 
+    fn compact(&self, begin: Version, end: Version) -> Result<()> {
+        self.db
+            .compact_range::<StaleNodeIndexSchema, Version>(&begin, &end)?;
+        // The nodes stale since these versions could have been created at any version before, so
+        // compact from the oldest one deleted.
+        let nodes_begin = self
+            .min_deleted_node_version
+            .swap(Version::MAX, Ordering::Relaxed);
+        if nodes_begin < end {
+            self.db
+                .compact_range::<JellyfishMerkleNodeSchema, (Version, u8)>(
+                    &(nodes_begin, 0),
+                    &(end, 0),
+                )?;
+        }
+        Ok(())
+    }
+
     fn record_progress(&self, min_readable_version: Version) {
         self.min_readable_version
             .store(min_readable_version, Ordering::Relaxed);
@@ -97,14 +142,42 @@ impl DBPruner for StateStorePruner {
 }
 
 impl StateStorePruner {
-    pub fn new(db: Arc<DB>) -> Self {
+    pub fn new(db: Arc<DB>, ledger_db: Arc<DB>, epoch_snapshots_to_retain: usize) -> Self {
+        Self::new_with_retained_snapshot_versions(
+            db,
+            ledger_db,
+            epoch_snapshots_to_retain,
+            Arc::new(Mutex::new(vec![])),
+        )
+    }
+
+    /// Like `new()`, publishing the versions of the retained snapshots to
+    /// `retained_snapshot_versions`.
+    pub fn new_with_retained_snapshot_versions(
+        db: Arc<DB>,
+        ledger_db: Arc<DB>,
+        epoch_snapshots_to_retain: usize,
+        retained_snapshot_versions: Arc<Mutex<Vec<Version>>>,
+    ) -> Self {
         let pruner = StateStorePruner {
             db,
+            ledger_db,
+            epoch_snapshots_to_retain,
+            retained_snapshot_versions,
+            retained_snapshots_as_of: Mutex::new(None),
             target_version: AtomicVersion::new(0),
             min_readable_version: AtomicVersion::new(0),
             pruned_to_the_end_of_target_version: AtomicBool::new(false),
+            min_deleted_node_version: AtomicVersion::new(Version::MAX),
         };
         pruner.initialize();
+        // So the retained snapshots are readable before the first prune.
+        if let Err(e) = pruner.get_retained_snapshot_versions() {
+            error!(
+                error = ?e,
+                "Error looking up the retained epoch snapshots.",
+            );
+        }
         pruner
     }
 
@@ -115,6 +188,8 @@ impl StateStorePruner {
         batch_size: usize,
     ) -> anyhow::Result<Version> {
         assert_ne!(batch_size, 0);
+        let retained_snapshots = self.get_retained_snapshot_versions()?;
+        self.prune_cross_epoch_stale_nodes(&retained_snapshots, target_version, batch_size)?;
         let (indices, is_end_of_target_version) =
             self.get_stale_node_indices(min_readable_version, target_version, batch_size)?;
         if indices.is_empty() {
@@ -129,11 +204,24 @@ impl StateStorePruner {
             let new_min_readable_version =
                 indices.last().expect("Should exist.").stale_since_version;
             let batch = SchemaBatch::new();
-            // Delete stale nodes.
+            // Delete stale nodes, except for those of the retained snapshots which are moved to the
+            // cross epoch index instead.
             indices.into_iter().try_for_each(|index| {
-                batch.delete::<JellyfishMerkleNodeSchema>(&index.node_key)?;
+                if is_in_snapshot(&index, &retained_snapshots) {
+                    batch.put::<StaleNodeIndexCrossEpochSchema>(&index, &())?;
+                } else {
+                    self.record_deleted_node(&index);
+                    batch.delete::<JellyfishMerkleNodeSchema>(&index.node_key)?;
+                }
                 batch.delete::<StaleNodeIndexSchema>(&index)
             })?;
+            batch.put::<PrunerProgressSchema>(
+                &PrunerIndex::StateStorePrunerIndex,
+                &PrunerProgress {
+                    min_readable_version: new_min_readable_version,
+                    target_version,
+                },
+            )?;
             // Delete the stale node indices.
             self.db.write_schemas(batch)?;
             self.pruned_to_the_end_of_target_version
@@ -143,6 +231,91 @@ impl StateStorePruner {
         }
     }
 
+    /// The versions of the snapshots of the last `epoch_snapshots_to_retain` epochs, in ascending
+    /// order. The snapshot of an epoch is the latest one committed up to its last version.
+    ///
+    /// They are only looked up again once an epoch ends, or while the snapshot of the last epoch
+    /// isn't committed yet.
+    fn get_retained_snapshot_versions(&self) -> Result<Vec<Version>> {
+        if self.epoch_snapshots_to_retain == 0 {
+            return Ok(vec![]);
+        }
+        let mut epoch_iter = self
+            .ledger_db
+            .rev_iter::<EpochByVersionSchema>(ReadOptions::default())?;
+        epoch_iter.seek_to_last();
+        let epoch_ending_versions = epoch_iter
+            .take(self.epoch_snapshots_to_retain)
+            .map(|item| item.map(|(epoch_ending_version, _epoch)| epoch_ending_version))
+            .collect::<Result<Vec<_>>>()?;
+        let latest_epoch_ending_version = match epoch_ending_versions.first() {
+            Some(version) => *version,
+            None => return Ok(vec![]),
+        };
+
+        let mut retained_snapshots_as_of = self.retained_snapshots_as_of.lock();
+        if *retained_snapshots_as_of == Some(latest_epoch_ending_version) {
+            return Ok(self.retained_snapshot_versions.lock().clone());
+        }
+        let mut versions = vec![];
+        for epoch_ending_version in epoch_ending_versions {
+            let mut node_iter = self
+                .db
+                .rev_iter::<JellyfishMerkleNodeSchema>(ReadOptions::default())?;
+            node_iter.seek_for_prev(&NodeKey::new_empty_path(epoch_ending_version))?;
+            if let Some((node_key, _node)) = node_iter.next().transpose()? {
+                versions.push(node_key.version());
+            }
+        }
+        versions.sort_unstable();
+        versions.dedup();
+        // The snapshot at the end of the last epoch can be committed after the epoch change.
+        *retained_snapshots_as_of = versions
+            .contains(&latest_epoch_ending_version)
+            .then(|| latest_epoch_ending_version);
+        *self.retained_snapshot_versions.lock() = versions.clone();
+        Ok(versions)
+    }
+
+    /// Deletes the nodes which were kept for snapshots that aren't retained anymore, i.e. those
+    /// which became stale before the oldest retained snapshot.
+    fn prune_cross_epoch_stale_nodes(
+        &self,
+        retained_snapshots: &[Version],
+        target_version: Version,
+        batch_size: usize,
+    ) -> Result<()> {
+        let prune_until = retained_snapshots
+            .first()
+            .map_or(target_version, |oldest| target_version.min(*oldest));
+        let mut iter = self
+            .db
+            .iter::<StaleNodeIndexCrossEpochSchema>(ReadOptions::default())?;
+        iter.seek_to_first();
+        let batch = SchemaBatch::new();
+        let mut num_pruned = 0;
+        for item in iter.take(batch_size) {
+            let (index, _) = item?;
+            if index.stale_since_version > prune_until {
+                break;
+            }
+            self.record_deleted_node(&index);
+            batch.delete::<JellyfishMerkleNodeSchema>(&index.node_key)?;
+            batch.delete::<StaleNodeIndexCrossEpochSchema>(&index)?;
+            num_pruned += 1;
+        }
+        if num_pruned > 0 {
+            self.db.write_schemas(batch)?;
+        }
+        Ok(())
+    }
+
+    /// Keeps track of the oldest node deleted, for the next compaction.
+    fn record_deleted_node(&self, index: &StaleNodeIndex) {
+        self.min_deleted_node_version
+            .fetch_min(index.node_key.version(), Ordering::Relaxed);
+    }
+
     fn get_stale_node_indices(
         &self,
         start_version: Version,
@@ -188,3 +361,11 @@ impl StateStorePruner {
         Ok((indices, is_end_of_target_version))
     }
 }
+
+/// Whether the stale node is part of the tree of one of the snapshots, i.e. it was created at or
+/// before the snapshot and became stale after it.
+fn is_in_snapshot(index: &StaleNodeIndex, snapshots: &[Version]) -> bool {
+    snapshots.iter().any(|snapshot| {
+        index.node_key.version() <= *snapshot && *snapshot < index.stale_since_version
+    })
+}
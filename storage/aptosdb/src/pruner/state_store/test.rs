@@ -9,8 +9,14 @@ use aptos_types::state_store::{state_key::StateKey, state_value::StateValue};
 use schemadb::ReadOptions;
 use storage_interface::{jmt_update_refs, jmt_updates, DbReader};
 
+use crate::epoch_by_version::EpochByVersionSchema;
 use crate::stale_node_index::StaleNodeIndexSchema;
-use crate::{change_set::ChangeSet, pruner::*, state_store::StateStore, AptosDB};
+use crate::{
+    change_set::ChangeSet,
+    pruner::{db_pruner::DBPruner, state_store::StateStorePruner, *},
+    state_store::StateStore,
+    AptosDB,
+};
 
 fn put_value_set(
     db: &DB,
@@ -75,8 +81,16 @@ fn test_state_store_pruner() {
         StoragePrunerConfig {
             state_store_prune_window: Some(0),
             ledger_prune_window: Some(0),
+            state_store_prune_window_secs: None,
+            ledger_prune_window_secs: None,
+            event_store_prune_window: None,
+            epoch_snapshots_to_retain: 0,
             ledger_pruning_batch_size: prune_batch_size,
             state_store_pruning_batch_size: prune_batch_size,
+            ledger_max_versions_per_sec: None,
+            state_store_max_versions_per_sec: None,
+            compaction_threshold_versions: None,
+            prune_only_backed_up: false,
         },
     );
 
@@ -129,6 +143,86 @@ fn test_state_store_pruner() {
     }
 }
 
+#[test]
+fn test_state_store_pruner_retains_epoch_snapshots() {
+    let key = StateKey::Raw(String::from("test_key1").into_bytes());
+
+    let num_versions = 25;
+    let epoch_ending_version = 5;
+    let tmp_dir = TempPath::new();
+    let aptos_db = AptosDB::new_for_test(&tmp_dir);
+    let state_store = &StateStore::new(
+        Arc::clone(&aptos_db.ledger_db),
+        Arc::clone(&aptos_db.state_merkle_db),
+        1000,  /* snapshot_size_threshold, does not matter */
+        false, /* hack_for_tests */
+    );
+    let pruner = Pruner::new(
+        Arc::clone(&aptos_db.ledger_db),
+        Arc::clone(&aptos_db.state_merkle_db),
+        StoragePrunerConfig {
+            state_store_prune_window: Some(0),
+            ledger_prune_window: Some(0),
+            state_store_prune_window_secs: None,
+            ledger_prune_window_secs: None,
+            event_store_prune_window: None,
+            epoch_snapshots_to_retain: 1,
+            ledger_pruning_batch_size: 100,
+            state_store_pruning_batch_size: 100,
+            ledger_max_versions_per_sec: None,
+            state_store_max_versions_per_sec: None,
+            compaction_threshold_versions: None,
+            prune_only_backed_up: false,
+        },
+    );
+
+    for i in 0..num_versions {
+        let value = StateValue::from(vec![i as u8]);
+        put_value_set(
+            &aptos_db.ledger_db,
+            state_store,
+            vec![(key.clone(), value.clone())],
+            i as u64, /* version */
+        );
+    }
+    aptos_db
+        .ledger_db
+        .put::<EpochByVersionSchema>(&epoch_ending_version, &1)
+        .unwrap();
+
+    // Prune till version=10, everything before it but the snapshot of the epoch ending is gone.
+    pruner
+        .wake_and_wait_state_pruner(10 /* latest_version */)
+        .unwrap();
+    // It's published so the reads at it are let through.
+    assert_eq!(
+        pruner.get_state_retained_snapshot_versions(),
+        vec![epoch_ending_version]
+    );
+    for i in 0..10 {
+        if i == epoch_ending_version {
+            verify_state_in_store(
+                state_store,
+                key.clone(),
+                Some(&StateValue::from(vec![i as u8])),
+                i,
+            );
+        } else {
+            assert!(state_store
+                .get_state_value_with_proof_by_version(&key, i)
+                .is_err());
+        }
+    }
+    for i in 10..num_versions {
+        verify_state_in_store(
+            state_store,
+            key.clone(),
+            Some(&StateValue::from(vec![i as u8])),
+            i,
+        );
+    }
+}
+
 #[test]
 fn test_state_store_pruner_partial_version() {
     // ```text
@@ -166,8 +260,16 @@ fn test_state_store_pruner_partial_version() {
         StoragePrunerConfig {
             state_store_prune_window: Some(0),
             ledger_prune_window: Some(0),
+            state_store_prune_window_secs: None,
+            ledger_prune_window_secs: None,
+            event_store_prune_window: None,
+            epoch_snapshots_to_retain: 0,
             ledger_pruning_batch_size: prune_batch_size,
             state_store_pruning_batch_size: prune_batch_size,
+            ledger_max_versions_per_sec: None,
+            state_store_max_versions_per_sec: None,
+            compaction_threshold_versions: None,
+            prune_only_backed_up: false,
         },
     );
 
@@ -268,8 +370,16 @@ fn test_state_store_pruner_disabled() {
         StoragePrunerConfig {
             state_store_prune_window: None,
             ledger_prune_window: Some(0),
+            state_store_prune_window_secs: None,
+            ledger_prune_window_secs: None,
+            event_store_prune_window: None,
+            epoch_snapshots_to_retain: 0,
             ledger_pruning_batch_size: prune_batch_size,
             state_store_pruning_batch_size: prune_batch_size,
+            ledger_max_versions_per_sec: None,
+            state_store_max_versions_per_sec: None,
+            compaction_threshold_versions: None,
+            prune_only_backed_up: false,
         },
     );
 
@@ -356,16 +466,32 @@ fn test_worker_quit_eagerly() {
 
     {
         let (command_sender, command_receiver) = channel();
-        let worker = StatePrunerWorker::new(
+        let storage_pruner_config = StoragePrunerConfig {
+            state_store_prune_window: Some(1),
+            ledger_prune_window: Some(1),
+            state_store_prune_window_secs: None,
+            ledger_prune_window_secs: None,
+            event_store_prune_window: None,
+            epoch_snapshots_to_retain: 0,
+            ledger_pruning_batch_size: 100,
+            state_store_pruning_batch_size: 100,
+            ledger_max_versions_per_sec: None,
+            state_store_max_versions_per_sec: None,
+            compaction_threshold_versions: None,
+            prune_only_backed_up: false,
+        };
+        let worker = PrunerWorker::new(
+            PrunerIndex::StateStorePrunerIndex,
+            utils::create_state_pruner(
+                Arc::clone(&aptos_db.ledger_db),
+                Arc::clone(&aptos_db.state_merkle_db),
+                Arc::new(Mutex::new(vec![])), /* retained_snapshot_versions */
+                storage_pruner_config,
+            ),
             Arc::clone(&aptos_db.state_merkle_db),
             command_receiver,
             Arc::new(Mutex::new(Some(0))), /* progress */
-            StoragePrunerConfig {
-                state_store_prune_window: Some(1),
-                ledger_prune_window: Some(1),
-                ledger_pruning_batch_size: 100,
-                state_store_pruning_batch_size: 100,
-            },
+            storage_pruner_config,
         );
         command_sender
             .send(db_pruner::Command::Prune {
@@ -385,3 +511,47 @@ fn test_worker_quit_eagerly() {
         verify_state_in_store(state_store, key, Some(&value2), 2);
     }
 }
+
+#[test]
+fn test_state_store_pruner_resumes_from_progress() {
+    let key = StateKey::Raw(String::from("test_key1").into_bytes());
+
+    let tmp_dir = TempPath::new();
+    let aptos_db = AptosDB::new_for_test(&tmp_dir);
+    let state_store = &aptos_db.state_store;
+    for i in 0..3 {
+        put_value_set(
+            &aptos_db.ledger_db,
+            state_store,
+            vec![(key.clone(), StateValue::from(vec![i as u8]))],
+            i as u64, /* version */
+        );
+    }
+
+    {
+        let pruner = StateStorePruner::new(
+            Arc::clone(&aptos_db.state_merkle_db),
+            Arc::clone(&aptos_db.ledger_db),
+            0, /* epoch_snapshots_to_retain */
+        );
+        pruner.set_target_version(2);
+        // Stop half way, as if the node crashed.
+        assert_eq!(pruner.prune(1 /* batch_size */).unwrap(), 1);
+    }
+
+    // A new pruner picks up where the last one stopped, instead of inferring it from the data.
+    let pruner = StateStorePruner::new(
+        Arc::clone(&aptos_db.state_merkle_db),
+        Arc::clone(&aptos_db.ledger_db),
+        0, /* epoch_snapshots_to_retain */
+    );
+    assert_eq!(pruner.min_readable_version(), 1);
+    assert_eq!(pruner.target_version(), 2);
+    assert!(pruner.is_pruning_pending());
+
+    assert_eq!(pruner.prune(1 /* batch_size */).unwrap(), 2);
+    assert!(state_store
+        .get_state_value_with_proof_by_version(&key, 1)
+        .is_err());
+    verify_state_in_store(state_store, key, Some(&StateValue::from(vec![2])), 2);
+}
@@ -5,11 +5,19 @@ use crate::{pruner::*, AptosDB, ChangeSet, LedgerStore, TransactionStore};
 use aptos_temppath::TempPath;
 use proptest::proptest;
 
+use aptos_crypto::HashValue;
 use aptos_types::{
     account_address::AccountAddress,
-    block_metadata::BlockMetadata,
+    account_config::NewBlockEvent,
+    block_metadata::{new_block_event_key, BlockMetadata},
+    contract_event::ContractEvent,
     transaction::{SignedTransaction, Transaction},
 };
+use move_deps::move_core_types::{language_storage::TypeTag, move_resource::MoveStructType};
+use std::{
+    thread::sleep,
+    time::{Duration, Instant},
+};
 
 use aptos_types::{
     transaction::{TransactionInfo, Version},
@@ -41,6 +49,105 @@ proptest! {
         }
 }
 
+#[test]
+fn test_ledger_pruner_age_window() {
+    let tmp_dir = TempPath::new();
+    let aptos_db = AptosDB::new_for_test(&tmp_dir);
+    let transaction_store = &aptos_db.transaction_store;
+    let event_store = &aptos_db.event_store;
+
+    let pruner = Pruner::new(
+        Arc::clone(&aptos_db.ledger_db),
+        Arc::clone(&aptos_db.state_merkle_db),
+        StoragePrunerConfig {
+            state_store_prune_window: None,
+            ledger_prune_window: None,
+            state_store_prune_window_secs: None,
+            ledger_prune_window_secs: Some(5),
+            event_store_prune_window: None,
+            epoch_snapshots_to_retain: 0,
+            ledger_pruning_batch_size: 1,
+            state_store_pruning_batch_size: 100,
+            ledger_max_versions_per_sec: None,
+            state_store_max_versions_per_sec: None,
+            compaction_threshold_versions: None,
+            prune_only_backed_up: false,
+        },
+    );
+
+    // a block per version, a second apart
+    let mut cs = ChangeSet::new();
+    for version in 0..=40 {
+        let timestamp_usecs = version * 1_000_000;
+        let block_metadata = BlockMetadata::new(
+            HashValue::zero(),
+            0,       /* epoch */
+            version, /* round */
+            vec![],
+            AccountAddress::ZERO,
+            vec![],
+            timestamp_usecs,
+        );
+        transaction_store
+            .put_transaction(
+                version,
+                &Transaction::BlockMetadata(block_metadata),
+                &mut cs,
+            )
+            .unwrap();
+        let new_block_event = NewBlockEvent::new(
+            0,       /* epoch */
+            version, /* round */
+            version, /* height */
+            vec![],
+            AccountAddress::ZERO,
+            vec![],
+            timestamp_usecs,
+        );
+        let event = ContractEvent::new(
+            new_block_event_key(),
+            version, /* sequence_number */
+            TypeTag::Struct(NewBlockEvent::struct_tag()),
+            bcs::to_bytes(&new_block_event).unwrap(),
+        );
+        event_store.put_events(version, &[event], &mut cs).unwrap();
+    }
+    aptos_db.ledger_db.write_schemas(cs.batch).unwrap();
+
+    // The second time, the blocks the search starts at are pruned already.
+    for (latest_version, expected_min_readable_version) in [(30, 25), (40, 35)] {
+        pruner.wake_and_wait_ledger_pruner(latest_version).unwrap();
+        wait_for_min_readable_ledger_version(&pruner, expected_min_readable_version);
+
+        for version in 0..expected_min_readable_version {
+            assert!(transaction_store.get_transaction(version).is_err());
+            assert!(event_store
+                .get_events_by_version(version)
+                .unwrap()
+                .is_empty());
+        }
+        for version in expected_min_readable_version..=40 {
+            assert!(transaction_store.get_transaction(version).is_ok());
+            assert!(event_store
+                .get_event_by_key(&new_block_event_key(), version, 40)
+                .is_ok());
+        }
+    }
+}
+
+fn wait_for_min_readable_ledger_version(pruner: &Pruner, min_readable_version: Version) {
+    // Assuming no big pruning chunks will be issued by a test.
+    let end = Instant::now() + Duration::from_secs(10);
+    while pruner.get_min_readable_ledger_version().unwrap() < min_readable_version {
+        assert!(Instant::now() < end, "Timeout waiting for pruner worker.");
+        sleep(Duration::from_millis(1));
+    }
+    assert_eq!(
+        pruner.get_min_readable_ledger_version(),
+        Some(min_readable_version)
+    );
+}
+
 fn verify_write_set_pruner(write_sets: Vec<WriteSet>) {
     let tmp_dir = TempPath::new();
     let aptos_db = AptosDB::new_for_test(&tmp_dir);
@@ -53,8 +160,16 @@ fn verify_write_set_pruner(write_sets: Vec<WriteSet>) {
         StoragePrunerConfig {
             state_store_prune_window: Some(0),
             ledger_prune_window: Some(0),
+            state_store_prune_window_secs: None,
+            ledger_prune_window_secs: None,
+            event_store_prune_window: None,
+            epoch_snapshots_to_retain: 0,
             ledger_pruning_batch_size: 1,
             state_store_pruning_batch_size: 100,
+            ledger_max_versions_per_sec: None,
+            state_store_max_versions_per_sec: None,
+            compaction_threshold_versions: None,
+            prune_only_backed_up: false,
         },
     );
 
@@ -100,8 +215,16 @@ fn verify_txn_store_pruner(
         StoragePrunerConfig {
             state_store_prune_window: Some(0),
             ledger_prune_window: Some(0),
+            state_store_prune_window_secs: None,
+            ledger_prune_window_secs: None,
+            event_store_prune_window: None,
+            epoch_snapshots_to_retain: 0,
             ledger_pruning_batch_size: 1,
             state_store_pruning_batch_size: 100,
+            ledger_max_versions_per_sec: None,
+            state_store_max_versions_per_sec: None,
+            compaction_threshold_versions: None,
+            prune_only_backed_up: false,
         },
     );
 
@@ -4,26 +4,49 @@
 //! This module provides common utilities for the DB pruner.
 
 use crate::{
+    metrics::{
+        PRUNER_LAG, PRUNER_LAST_BATCH_DURATION_MS, PRUNER_TARGET_VERSION,
+        PRUNER_WRITE_STALL_BACKOFF_MS,
+    },
     pruner::{
-        db_pruner::DBPruner, ledger_store::ledger_store_pruner::LedgerPruner,
-        state_store::StateStorePruner,
+        db_pruner::DBPruner, event_store::event_pruner::EventPruner,
+        ledger_store::ledger_store_pruner::LedgerPruner, state_store::StateStorePruner,
     },
     EventStore, LedgerStore, TransactionStore,
 };
+use anyhow::Result;
 use aptos_config::config::StoragePrunerConfig;
 use aptos_infallible::Mutex;
-use schemadb::DB;
-use std::sync::Arc;
+use aptos_logger::error;
+use aptos_types::{
+    account_config::NewBlockEvent, block_metadata::new_block_event_key, transaction::Version,
+};
+use schemadb::{DB, DEFAULT_COLUMN_FAMILY_NAME};
+use std::{
+    convert::TryInto,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+const MIN_WRITE_STALL_BACKOFF: Duration = Duration::from_millis(100);
+const MAX_WRITE_STALL_BACKOFF: Duration = Duration::from_secs(10);
 
 /// Utility functions to instantiate pruners.
 pub fn create_state_pruner(
+    ledger_db: Arc<DB>,
     state_merkle_db: Arc<DB>,
+    retained_snapshot_versions: Arc<Mutex<Vec<Version>>>,
     storage_pruner_config: StoragePrunerConfig,
 ) -> Option<Mutex<Arc<dyn DBPruner + Send + Sync>>> {
-    if storage_pruner_config.state_store_prune_window.is_some() {
-        Some(Mutex::new(Arc::new(StateStorePruner::new(Arc::clone(
-            &state_merkle_db,
-        )))))
+    if storage_pruner_config.state_store_pruning_enabled() {
+        Some(Mutex::new(Arc::new(
+            StateStorePruner::new_with_retained_snapshot_versions(
+                Arc::clone(&state_merkle_db),
+                Arc::clone(&ledger_db),
+                storage_pruner_config.epoch_snapshots_to_retain,
+                retained_snapshot_versions,
+            ),
+        )))
     } else {
         None
     }
@@ -33,14 +56,153 @@ pub fn create_ledger_pruner(
     ledger_db: Arc<DB>,
     storage_pruner_config: StoragePrunerConfig,
 ) -> Option<Mutex<Arc<dyn DBPruner + Send + Sync>>> {
-    if storage_pruner_config.ledger_prune_window.is_some() {
+    if storage_pruner_config.ledger_pruning_enabled() {
         Some(Mutex::new(Arc::new(LedgerPruner::new(
             Arc::clone(&ledger_db),
             Arc::new(TransactionStore::new(Arc::clone(&ledger_db))),
             Arc::new(EventStore::new(Arc::clone(&ledger_db))),
             Arc::new(LedgerStore::new(Arc::clone(&ledger_db))),
+            // with a window of its own, the event store is left to the event pruner
+            !storage_pruner_config.event_store_pruning_enabled(),
         ))))
     } else {
         None
     }
 }
+
+pub fn create_event_pruner(
+    ledger_db: Arc<DB>,
+    storage_pruner_config: StoragePrunerConfig,
+) -> Option<Mutex<Arc<dyn DBPruner + Send + Sync>>> {
+    if storage_pruner_config.event_store_pruning_enabled() {
+        Some(Mutex::new(Arc::new(EventPruner::new(
+            Arc::clone(&ledger_db),
+            Arc::new(EventStore::new(Arc::clone(&ledger_db))),
+        ))))
+    } else {
+        None
+    }
+}
+
+/// The first version committed less than `window_secs` before the block of `latest_version`, so
+/// pruning the versions before it retains the last `window_secs` of the ledger. None if no block
+/// left in the DB was committed before the window, so there's nothing more to prune yet.
+pub(crate) fn min_version_in_age_window(
+    transaction_store: &TransactionStore,
+    event_store: &EventStore,
+    latest_version: Version,
+    window_secs: u64,
+) -> Result<Option<Version>> {
+    let latest_timestamp_usecs = match transaction_store.get_block_metadata(latest_version)? {
+        Some((_, block_meta)) => block_meta.timestamp_usecs(),
+        // genesis
+        None => return Ok(None),
+    };
+    let cutoff_usecs =
+        match latest_timestamp_usecs.checked_sub(window_secs.saturating_mul(1_000_000)) {
+            Some(cutoff_usecs) => cutoff_usecs,
+            None => return Ok(None),
+        };
+    // The search only covers the blocks not pruned yet, the oldest of them must be before the
+    // window.
+    let event_key = new_block_event_key();
+    let oldest_seq = match event_store.get_oldest_sequence_number(&event_key)? {
+        Some(oldest_seq) => oldest_seq,
+        None => return Ok(None),
+    };
+    let oldest_block: NewBlockEvent =
+        (&event_store.get_event_by_key(&event_key, oldest_seq, latest_version)?).try_into()?;
+    if oldest_block.proposed_time() >= cutoff_usecs {
+        return Ok(None);
+    }
+    event_store
+        .get_last_version_before_timestamp(cutoff_usecs, latest_version)
+        .map(|version| Some(version + 1))
+}
+
+/// Updates the progress gauges of a pruner after it pruned a batch, labeled like its
+/// `PRUNER_LEAST_READABLE_VERSION`.
+pub(crate) fn record_pruner_progress(
+    pruner_name: &str,
+    pruner: &(dyn DBPruner + Send + Sync),
+    batch_duration: Duration,
+) {
+    let target_version = pruner.target_version();
+    PRUNER_TARGET_VERSION
+        .with_label_values(&[pruner_name])
+        .set(target_version as i64);
+    PRUNER_LAG
+        .with_label_values(&[pruner_name])
+        .set(target_version.saturating_sub(pruner.min_readable_version()) as i64);
+    PRUNER_LAST_BATCH_DURATION_MS
+        .with_label_values(&[pruner_name])
+        .set(batch_duration.as_millis() as i64);
+}
+
+/// The least time pruning `pruned_versions` versions may take to stay under the rate limit.
+pub(crate) fn min_batch_duration(
+    pruned_versions: Version,
+    max_versions_per_sec: Option<u64>,
+) -> Duration {
+    match max_versions_per_sec {
+        Some(max_versions_per_sec) if max_versions_per_sec > 0 => {
+            Duration::from_secs_f64(pruned_versions as f64 / max_versions_per_sec as f64)
+        }
+        _ => Duration::ZERO,
+    }
+}
+
+/// Compacts what the pruner pruned since `compaction_begin` once that's at least
+/// `compaction_threshold_versions` versions, moving `compaction_begin` past it.
+pub(crate) fn compact_if_due(
+    pruner: &(dyn DBPruner + Send + Sync),
+    compaction_begin: &mut Version,
+    compaction_threshold_versions: u64,
+) {
+    let min_readable_version = pruner.min_readable_version();
+    if min_readable_version.saturating_sub(*compaction_begin) < compaction_threshold_versions {
+        return;
+    }
+    if let Err(e) = pruner.compact(*compaction_begin, min_readable_version) {
+        error!(
+            error = ?e,
+            pruner_name = pruner.name(),
+            "Error compacting the pruned range.",
+        );
+    }
+    // not retried on error, the range is compacted by RocksDB eventually anyway
+    *compaction_begin = min_readable_version;
+}
+
+/// Whether RocksDB is slowing down or stopping the writes to `db`, which it does when compactions
+/// fall behind.
+fn is_write_stalled(db: &DB) -> bool {
+    let property = |name: &str| {
+        db.get_property(DEFAULT_COLUMN_FAMILY_NAME, name)
+            .unwrap_or(0)
+    };
+    property("rocksdb.is-write-stopped") > 0 || property("rocksdb.actual-delayed-write-rate") > 0
+}
+
+/// Holds the next batch back while RocksDB is stalling the writes to `db`, so the pruner doesn't
+/// compete with the commits for disk I/O. The backoff doubles for as long as the stall lasts, up to
+/// `MAX_WRITE_STALL_BACKOFF`, and resets once it's over.
+///
+/// Returns `true` if the pruner should skip its batch for now.
+pub(crate) fn back_off_on_write_stall(
+    pruner_name: &str,
+    db: &DB,
+    backoff: &mut Duration,
+    next_batch_at: &mut Instant,
+) -> bool {
+    if is_write_stalled(db) {
+        *backoff = (*backoff * 2).clamp(MIN_WRITE_STALL_BACKOFF, MAX_WRITE_STALL_BACKOFF);
+        *next_batch_at = Instant::now() + *backoff;
+    } else {
+        *backoff = Duration::ZERO;
+    }
+    PRUNER_WRITE_STALL_BACKOFF_MS
+        .with_label_values(&[pruner_name])
+        .set(backoff.as_millis() as i64);
+    !backoff.is_zero()
+}
@@ -0,0 +1,48 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+//! This module defines physical storage schema for the backup watermark, i.e. the last version
+//! the backup coordinator reported backed up, which the pruners don't go past with
+//! `prune_only_backed_up`. It's kept across restarts so the node doesn't stop pruning until the
+//! next report.
+//!
+//! ```text
+//! |<--key-->|<--value->|
+//! | ()      | version  |
+//! ```
+
+use crate::schema::{ensure_slice_len_eq, BACKUP_WATERMARK_CF_NAME};
+use anyhow::Result;
+use aptos_types::transaction::Version;
+use byteorder::{BigEndian, ReadBytesExt};
+use schemadb::{
+    define_schema,
+    schema::{KeyCodec, ValueCodec},
+};
+use std::mem::size_of;
+
+define_schema!(BackupWatermarkSchema, (), Version, BACKUP_WATERMARK_CF_NAME);
+
+impl KeyCodec<BackupWatermarkSchema> for () {
+    fn encode_key(&self) -> Result<Vec<u8>> {
+        Ok(vec![])
+    }
+
+    fn decode_key(data: &[u8]) -> Result<Self> {
+        ensure_slice_len_eq(data, 0)
+    }
+}
+
+impl ValueCodec<BackupWatermarkSchema> for Version {
+    fn encode_value(&self) -> Result<Vec<u8>> {
+        Ok(self.to_be_bytes().to_vec())
+    }
+
+    fn decode_value(mut data: &[u8]) -> Result<Self> {
+        ensure_slice_len_eq(data, size_of::<Self>())?;
+        Ok(data.read_u64::<BigEndian>()?)
+    }
+}
+
+#[cfg(test)]
+mod test;
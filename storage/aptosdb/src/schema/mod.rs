@@ -6,6 +6,7 @@
 //!
 //! All schemas are `pub(crate)` so not shown in rustdoc, refer to the source code to see details.
 
+pub(crate) mod backup_watermark;
 pub(crate) mod epoch_by_version;
 pub(crate) mod event;
 pub(crate) mod event_accumulator;
@@ -14,7 +15,9 @@ pub(crate) mod event_by_version;
 pub(crate) mod jellyfish_merkle_node;
 pub(crate) mod ledger_counters;
 pub(crate) mod ledger_info;
+pub(crate) mod pruner_progress;
 pub(crate) mod stale_node_index;
+pub(crate) mod stale_node_index_cross_epoch;
 pub(crate) mod state_value;
 pub(crate) mod transaction;
 pub(crate) mod transaction_accumulator;
@@ -26,6 +29,7 @@ pub(crate) mod write_set;
 use anyhow::{ensure, Result};
 use schemadb::ColumnFamilyName;
 
+pub const BACKUP_WATERMARK_CF_NAME: ColumnFamilyName = "backup_watermark";
 pub const EPOCH_BY_VERSION_CF_NAME: ColumnFamilyName = "epoch_by_version";
 pub const EVENT_ACCUMULATOR_CF_NAME: ColumnFamilyName = "event_accumulator";
 pub const EVENT_BY_KEY_CF_NAME: ColumnFamilyName = "event_by_key";
@@ -35,7 +39,9 @@ pub const INDEXER_METADATA_CF_NAME: ColumnFamilyName = "indexer_metadata";
 pub const JELLYFISH_MERKLE_NODE_CF_NAME: ColumnFamilyName = "jellyfish_merkle_node";
 pub const LEDGER_COUNTERS_CF_NAME: ColumnFamilyName = "ledger_counters";
 pub const LEDGER_INFO_CF_NAME: ColumnFamilyName = "ledger_info";
+pub const PRUNER_PROGRESS_CF_NAME: ColumnFamilyName = "pruner_progress";
 pub const STALE_NODE_INDEX_CF_NAME: ColumnFamilyName = "stale_node_index";
+pub const STALE_NODE_INDEX_CROSS_EPOCH_CF_NAME: ColumnFamilyName = "stale_node_index_cross_epoch";
 pub const STATE_VALUE_CF_NAME: ColumnFamilyName = "state_value";
 pub const TABLE_INFO_CF_NAME: ColumnFamilyName = "table_info";
 pub const TRANSACTION_CF_NAME: ColumnFamilyName = "transaction";
@@ -72,6 +78,7 @@ pub mod fuzzing {
     pub fn fuzz_decode(data: &[u8]) {
         #[allow(unused_must_use)]
         {
+            assert_no_panic_decoding::<super::backup_watermark::BackupWatermarkSchema>(data);
             assert_no_panic_decoding::<super::epoch_by_version::EpochByVersionSchema>(data);
             assert_no_panic_decoding::<super::event::EventSchema>(data);
             assert_no_panic_decoding::<super::event_accumulator::EventAccumulatorSchema>(data);
@@ -82,7 +89,11 @@ pub mod fuzzing {
             );
             assert_no_panic_decoding::<super::ledger_counters::LedgerCountersSchema>(data);
             assert_no_panic_decoding::<super::ledger_info::LedgerInfoSchema>(data);
+            assert_no_panic_decoding::<super::pruner_progress::PrunerProgressSchema>(data);
             assert_no_panic_decoding::<super::stale_node_index::StaleNodeIndexSchema>(data);
+            assert_no_panic_decoding::<
+                super::stale_node_index_cross_epoch::StaleNodeIndexCrossEpochSchema,
+            >(data);
             assert_no_panic_decoding::<super::state_value::StateValueSchema>(data);
             assert_no_panic_decoding::<super::transaction::TransactionSchema>(data);
             assert_no_panic_decoding::<super::transaction_accumulator::TransactionAccumulatorSchema>(
@@ -0,0 +1,61 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+//! This module defines physical storage schema for the progress of each pruner, which is written
+//! in the same batch as what the pruner deletes, so a pruner restarted after a crash carries on
+//! from exactly where its last batch left off, including the prune it was in the middle of.
+//!
+//! ```text
+//! |<--key--->|<--value->|
+//! | pruner   | progress |
+//! ```
+
+use crate::{pruner::PrunerIndex, schema::PRUNER_PROGRESS_CF_NAME};
+use anyhow::Result;
+use aptos_types::transaction::Version;
+#[cfg(test)]
+use proptest_derive::Arbitrary;
+use schemadb::{
+    define_schema,
+    schema::{KeyCodec, ValueCodec},
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[cfg_attr(test, derive(Arbitrary))]
+pub(crate) struct PrunerProgress {
+    /// Versions before this one are pruned.
+    pub min_readable_version: Version,
+    /// The version the pruner was pruning up to.
+    pub target_version: Version,
+}
+
+define_schema!(
+    PrunerProgressSchema,
+    PrunerIndex,
+    PrunerProgress,
+    PRUNER_PROGRESS_CF_NAME
+);
+
+impl KeyCodec<PrunerProgressSchema> for PrunerIndex {
+    fn encode_key(&self) -> Result<Vec<u8>> {
+        bcs::to_bytes(self).map_err(Into::into)
+    }
+
+    fn decode_key(data: &[u8]) -> Result<Self> {
+        bcs::from_bytes(data).map_err(Into::into)
+    }
+}
+
+impl ValueCodec<PrunerProgressSchema> for PrunerProgress {
+    fn encode_value(&self) -> Result<Vec<u8>> {
+        bcs::to_bytes(self).map_err(Into::into)
+    }
+
+    fn decode_value(data: &[u8]) -> Result<Self> {
+        bcs::from_bytes(data).map_err(Into::into)
+    }
+}
+
+#[cfg(test)]
+mod test;
@@ -0,0 +1,18 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use super::*;
+use proptest::prelude::*;
+use schemadb::{schema::fuzzing::assert_encode_decode, test_no_panic_decoding};
+
+proptest! {
+    #[test]
+    fn test_encode_decode(
+        pruner_index in any::<PrunerIndex>(),
+        progress in any::<PrunerProgress>(),
+    ) {
+        assert_encode_decode::<PrunerProgressSchema>(&pruner_index, &progress);
+    }
+}
+
+test_no_panic_decoding!(PrunerProgressSchema);
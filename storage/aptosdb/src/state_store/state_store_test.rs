@@ -250,7 +250,11 @@ fn test_stale_node_index() {
     let tmp_dir = TempPath::new();
     let db = AptosDB::new_for_test(&tmp_dir);
     let store = &db.state_store;
-    let pruner = StateStorePruner::new(Arc::clone(&db.state_merkle_db));
+    let pruner = StateStorePruner::new(
+        Arc::clone(&db.state_merkle_db),
+        Arc::clone(&db.ledger_db),
+        0, /* epoch_snapshots_to_retain */
+    );
 
     // Update.
     // ```text
@@ -370,7 +374,11 @@ fn test_stale_node_index_with_target_version() {
     let tmp_dir = TempPath::new();
     let db = AptosDB::new_for_test(&tmp_dir);
     let store = &db.state_store;
-    let pruner = StateStorePruner::new(Arc::clone(&db.state_merkle_db));
+    let pruner = StateStorePruner::new(
+        Arc::clone(&db.state_merkle_db),
+        Arc::clone(&db.ledger_db),
+        0, /* epoch_snapshots_to_retain */
+    );
 
     // Update.
     // ```text
@@ -476,7 +484,11 @@ fn test_stale_node_index_all_at_once() {
     let tmp_dir = TempPath::new();
     let db = AptosDB::new_for_test(&tmp_dir);
     let store = &db.state_store;
-    let pruner = StateStorePruner::new(Arc::clone(&db.state_merkle_db));
+    let pruner = StateStorePruner::new(
+        Arc::clone(&db.state_merkle_db),
+        Arc::clone(&db.ledger_db),
+        0, /* epoch_snapshots_to_retain */
+    );
 
     // Update.
     // ```text
@@ -23,6 +23,8 @@ use aptos_logger::prelude::*;
 use aptos_types::transaction::Version;
 use aptosdb::backup::backup_handler::DbState;
 use futures::{stream, Future, StreamExt};
+use inspection_service::inspection_client::InspectionClient;
+use reqwest::Url;
 use std::{fmt::Debug, sync::Arc};
 use structopt::StructOpt;
 use tokio::{
@@ -48,6 +50,10 @@ pub struct BackupCoordinatorOpt {
     pub transaction_batch_size: usize,
     #[structopt(flatten)]
     pub concurernt_downloads: ConcurrentDownloadsOpt,
+    // For a node that only prunes what's backed up, the backed up versions are reported to its
+    // inspection service, e.g. http://localhost:9101.
+    #[structopt(long)]
+    pub inspection_service_address: Option<Url>,
 }
 
 impl BackupCoordinatorOpt {
@@ -69,6 +75,7 @@ impl BackupCoordinatorOpt {
 
 pub struct BackupCoordinator {
     client: Arc<BackupServiceClient>,
+    inspection_client: Option<InspectionClient>,
     storage: Arc<dyn BackupStorage>,
     global_opt: GlobalBackupOpt,
     metadata_cache_opt: MetadataCacheOpt,
@@ -87,6 +94,9 @@ impl BackupCoordinator {
         opt.validate().unwrap();
         Self {
             client,
+            inspection_client: opt
+                .inspection_service_address
+                .map(InspectionClient::from_url),
             storage,
             global_opt,
             metadata_cache_opt: opt.metadata_cache_opt,
@@ -259,6 +269,15 @@ impl BackupCoordinator {
         loop {
             if let Some(version) = last_transaction_version_in_backup {
                 TRANSACTION_VERSION.set(version as i64);
+                // Let the node prune what's backed up, which it might wait for.
+                if let Some(inspection_client) = &self.inspection_client {
+                    if let Err(e) = inspection_client.post_backup_watermark(version).await {
+                        warn!(
+                            "Failed reporting backup watermark to local node: {}. Will keep trying.",
+                            e
+                        );
+                    }
+                }
             }
             let (first, last) = get_batch_range(
                 last_transaction_version_in_backup,
@@ -9,7 +9,8 @@ use crate::handlers::utils::{
 };
 use aptos_crypto::hash::HashValue;
 use aptos_types::transaction::Version;
-use aptosdb::backup::backup_handler::BackupHandler;
+use aptosdb::{backup::backup_handler::BackupHandler, AptosDB};
+use std::sync::Arc;
 use warp::{filters::BoxedFilter, reply::Reply, Filter};
 
 static DB_STATE: &str = "db_state";
@@ -19,6 +20,7 @@ static STATE_ROOT_PROOF: &str = "state_root_proof";
 static EPOCH_ENDING_LEDGER_INFOS: &str = "epoch_ending_ledger_infos";
 static TRANSACTIONS: &str = "transactions";
 static TRANSACTION_RANGE_PROOF: &str = "transaction_range_proof";
+static DB_SIZE: &str = "db_size";
 
 pub(crate) fn get_routes(backup_handler: BackupHandler) -> BoxedFilter<(impl Reply,)> {
     // GET db_state
@@ -128,3 +130,28 @@ pub(crate) fn get_routes(backup_handler: BackupHandler) -> BoxedFilter<(impl Rep
         }))
         .boxed()
 }
+
+/// Routes for operators rather than backups, e.g. to find what takes up the disk.
+pub(crate) fn get_admin_routes(db: Arc<AptosDB>) -> BoxedFilter<(impl Reply,)> {
+    // GET db_size, in JSON for humans
+    let db_size = warp::path::end()
+        .map(move || {
+            unwrap_or_500(
+                db.get_db_size_report()
+                    .map(|report| Box::new(warp::reply::json(&report)) as Box<dyn Reply>),
+            )
+        })
+        .recover(handle_rejection);
+
+    // Match the path before the method, so other paths are still 404 rather than 405.
+    let routes = warp::path(DB_SIZE).and(warp::get()).and(db_size);
+
+    routes
+        .with(warp::log::custom(|info| {
+            let endpoint = info.path().split('/').nth(1).unwrap_or("-");
+            LATENCY_HISTOGRAM
+                .with_label_values(&[endpoint, info.status().as_str()])
+                .observe(info.elapsed().as_secs_f64())
+        }))
+        .boxed()
+}
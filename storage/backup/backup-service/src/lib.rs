@@ -3,15 +3,16 @@
 
 mod handlers;
 
-use crate::handlers::get_routes;
+use crate::handlers::{get_admin_routes, get_routes};
 use aptos_logger::prelude::*;
 use aptosdb::AptosDB;
 use std::{net::SocketAddr, sync::Arc};
 use tokio::runtime::{Builder, Runtime};
+use warp::Filter;
 
 pub fn start_backup_service(address: SocketAddr, db: Arc<AptosDB>) -> Runtime {
     let backup_handler = db.get_backup_handler();
-    let routes = get_routes(backup_handler);
+    let routes = get_routes(backup_handler).or(get_admin_routes(db));
 
     let runtime = Builder::new_multi_thread()
         .thread_name("backup")
@@ -93,5 +94,20 @@ mod tests {
         assert_eq!(resp.status(), 200);
         assert_eq!(resp.content_length(), None);
         assert!(resp.bytes().is_err());
+
+        // The pruner controls are left to the inspection service.
+        let client = reqwest::blocking::Client::new();
+        let resp = client
+            .post(&format!("http://127.0.0.1:{}/prune/ledger/0", port))
+            .send()
+            .unwrap();
+        assert_eq!(resp.status(), 404);
+        let resp = client
+            .post(&format!("http://127.0.0.1:{}/backup_watermark/0", port))
+            .send()
+            .unwrap();
+        assert_eq!(resp.status(), 404);
+        let resp = get(&format!("http://127.0.0.1:{}/db_size", port)).unwrap();
+        assert_eq!(resp.status(), 200);
     }
 }
@@ -338,6 +338,25 @@ impl DB {
         Ok(())
     }
 
+    /// Compacts all keys in range [begin, end), which reclaims the disk space of the deleted ones
+    /// right away rather than whenever RocksDB gets to compact them on its own.
+    ///
+    /// `SK` has to be an explicit type parameter since
+    /// <https://github.com/rust-lang/rust/issues/44721>
+    pub fn compact_range<S, SK>(&self, begin: &SK, end: &SK) -> Result<()>
+    where
+        S: Schema,
+        SK: SeekKeyCodec<S>,
+    {
+        let raw_begin = begin.encode_seek_key()?;
+        let raw_end = end.encode_seek_key()?;
+        let cf_handle = self.get_cf_handle(S::COLUMN_FAMILY_NAME)?;
+
+        self.inner
+            .compact_range_cf(cf_handle, Some(&raw_begin), Some(&raw_end));
+        Ok(())
+    }
+
     fn iter_with_direction<S: Schema>(
         &self,
         opts: ReadOptions,
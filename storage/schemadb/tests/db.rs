@@ -441,6 +441,26 @@ fn test_report_size() {
     );
 }
 
+#[test]
+fn test_compact_range() {
+    let db = TestDB::new();
+
+    for i in 0..1000 {
+        db.put::<TestSchema1>(&TestField(i), &TestField(i)).unwrap();
+    }
+    db.flush_cf("TestCF1").unwrap();
+    db.range_delete::<TestSchema1, TestField>(&TestField(0), &TestField(1000))
+        .unwrap();
+    db.compact_range::<TestSchema1, TestField>(&TestField(0), &TestField(1000))
+        .unwrap();
+
+    assert_eq!(
+        db.get_property("TestCF1", "rocksdb.estimate-live-data-size")
+            .unwrap(),
+        0
+    );
+}
+
 #[test]
 fn test_checkpoint() {
     let tmpdir = aptos_temppath::TempPath::new();
@@ -526,6 +526,23 @@ pub trait DbReader: Send + Sync {
         unimplemented!()
     }
 
+    /// Get the oldest version of the state that hasn't been pruned, None if the state isn't
+    /// pruned.
+    fn get_state_min_readable_version(&self) -> Result<Option<Version>> {
+        unimplemented!()
+    }
+
+    /// Get the versions of the state snapshots retained at the end of the last epochs, which stay
+    /// readable even if older than `get_state_min_readable_version()`. In ascending order.
+    fn get_state_retained_snapshot_versions(&self) -> Result<Vec<Version>> {
+        unimplemented!()
+    }
+
+    /// Get the oldest version of the events that hasn't been pruned, None if they aren't pruned.
+    fn get_event_min_readable_version(&self) -> Result<Option<Version>> {
+        unimplemented!()
+    }
+
     /// Get table info from the internal indexer.
     fn get_table_info(&self, handle: TableHandle) -> Result<TableInfo> {
         unimplemented!()
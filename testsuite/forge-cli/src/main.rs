@@ -6,14 +6,22 @@ use aptos_rest_client::Client as RestClient;
 use aptos_sdk::{move_types::account_address::AccountAddress, transaction_builder::aptos_stdlib};
 use forge::success_criteria::SuccessCriteria;
 use forge::{ForgeConfig, Options, Result, *};
-use std::{env, num::NonZeroUsize, process, time::Duration};
+use std::{env, num::NonZeroUsize, path::PathBuf, process, time::Duration};
 use structopt::StructOpt;
 use testcases::network_bandwidth_test::NetworkBandwidthTest;
 use testcases::network_latency_test::NetworkLatencyTest;
+use testcases::network_loss_test::NetworkLossTest;
+use testcases::network_node_loss_test::{
+    NetworkNodeLossTest, DEFAULT_LOSSY_VALIDATORS_PERCENTAGE, DEFAULT_LOSS_PERCENTAGE,
+};
 use testcases::{
-    compatibility_test::SimpleValidatorUpgrade, generate_traffic,
-    network_partition_test::NetworkPartitionTest, performance_test::PerformanceBenchmark,
-    reconfiguration_test::ReconfigurationTest, state_sync_performance::StateSyncPerformance,
+    compatibility_test::SimpleValidatorUpgrade, experiment_plan::ExperimentPlan,
+    five_region_test::FiveRegionTest, generate_traffic,
+    network_partition_test::NetworkPartitionTest, network_split_brain_test::NetworkSplitBrainTest,
+    performance_test::PerformanceBenchmark, reconfiguration_test::ReconfigurationTest,
+    soak_test::SoakTest, state_sync_catch_up_test::StateSyncCatchUpTest,
+    state_sync_performance::StateSyncPerformance,
+    validator_restart_test::ValidatorRestartUnderLoadTest,
 };
 use tokio::runtime::Runtime;
 use url::Url;
@@ -31,6 +39,11 @@ struct Args {
     burst: bool,
     #[structopt(long, default_value = "300")]
     duration_secs: usize,
+    #[structopt(
+        long,
+        help = "Vary the target TPS over time, e.g. ramp:100:1000:60, burst:100:1000:60:10 or plateaus:100@30,500@30"
+    )]
+    traffic_profile: Option<TrafficProfile>,
     #[structopt(flatten)]
     options: Options,
     #[structopt(flatten)]
@@ -68,6 +81,7 @@ enum CliCommand {
 #[derive(StructOpt, Debug)]
 enum TestCommand {
     LocalSwarm(LocalSwarm),
+    DockerSwarm(DockerSwarm),
     K8sSwarm(K8sSwarm),
 }
 
@@ -81,6 +95,21 @@ enum OperatorCommand {
 #[derive(StructOpt, Debug)]
 struct LocalSwarm {}
 
+#[derive(StructOpt, Debug)]
+struct DockerSwarm {
+    #[structopt(
+        long,
+        help = "The image used for validators",
+        default_value = "aptoslabs/validator:devnet"
+    )]
+    image: String,
+    #[structopt(
+        long,
+        help = "Image of the validator software to do backward compatibility test"
+    )]
+    base_image: Option<String>,
+}
+
 #[derive(StructOpt, Debug)]
 struct K8sSwarm {
     #[structopt(long, help = "The kubernetes namespace to use for test")]
@@ -119,6 +148,11 @@ struct K8sSwarm {
     keep: bool,
     #[structopt(long, help = "If set, enables HAProxy for each of the validators")]
     enable_haproxy: bool,
+    #[structopt(
+        long,
+        help = "Path to a YAML experiment plan to run instead of the test suite"
+    )]
+    experiment_plan: Option<PathBuf>,
 }
 
 #[derive(StructOpt, Debug)]
@@ -199,6 +233,9 @@ fn main() -> Result<()> {
         global_emit_job_request =
             global_emit_job_request.workers_per_endpoint(workers_per_endpoint);
     }
+    if let Some(traffic_profile) = args.traffic_profile.clone() {
+        global_emit_job_request = global_emit_job_request.traffic_profile(traffic_profile);
+    }
 
     let runtime = Runtime::new()?;
     match args.cli_cmd {
@@ -212,8 +249,35 @@ fn main() -> Result<()> {
                 args.changelog,
                 global_emit_job_request,
             ),
+            TestCommand::DockerSwarm(docker) => {
+                let images = docker
+                    .base_image
+                    .into_iter()
+                    .chain([docker.image])
+                    .collect();
+                run_forge(
+                    local_test_suite(),
+                    DockerFactory::new(images),
+                    &args.options,
+                    success_criteria,
+                    args.changelog,
+                    global_emit_job_request,
+                )
+            }
             TestCommand::K8sSwarm(k8s) => {
+                let experiment_plan = k8s
+                    .experiment_plan
+                    .as_deref()
+                    .map(ExperimentPlan::from_file)
+                    .transpose()?;
+                let experiment_plan_tests = experiment_plan
+                    .iter()
+                    .map(|plan| plan as &dyn NetworkTest)
+                    .collect::<Vec<_>>();
                 let mut test_suite = get_test_suite(args.suite.as_ref());
+                if !experiment_plan_tests.is_empty() {
+                    test_suite = test_suite.with_network_tests(&experiment_plan_tests);
+                }
                 if let Some(move_modules_dir) = k8s.move_modules_dir {
                     test_suite = test_suite.with_genesis_modules_path(move_modules_dir);
                 }
@@ -258,7 +322,9 @@ fn main() -> Result<()> {
                     resize.num_validators,
                     resize.validator_image_tag,
                     resize.testnet_image_tag,
-                    resize.move_modules_dir,
+                    resize.move_modules_dir.map(GenesisConfig::Path).as_ref(),
+                    &NodeResourcesConfig::default(),
+                    &ChainConfig::default(),
                     !resize.connect_directly,
                     resize.enable_haproxy,
                 ))?;
@@ -390,11 +456,38 @@ fn single_test_suite(test_name: &str) -> ForgeConfig<'static> {
     match test_name {
         "bench" => config.with_network_tests(&[&PerformanceBenchmark]),
         "state_sync" => config.with_network_tests(&[&StateSyncPerformance]),
+        "state_sync_catch_up_validator" => config.with_network_tests(&[&StateSyncCatchUpTest {
+            wipe_validator: true,
+        }]),
+        "state_sync_catch_up_fullnode" => config.with_network_tests(&[&StateSyncCatchUpTest {
+            wipe_validator: false,
+        }]),
         "compat" => config.with_network_tests(&[&SimpleValidatorUpgrade]),
         "config" => config.with_network_tests(&[&ReconfigurationTest]),
         "network_partition" => config.with_network_tests(&[&NetworkPartitionTest]),
+        "network_split_brain" => config.with_network_tests(&[&NetworkSplitBrainTest {
+            minority_percentage: 30,
+            partition_secs: 120,
+        }]),
         "network_latency" => config.with_network_tests(&[&NetworkLatencyTest]),
         "network_bandwidth" => config.with_network_tests(&[&NetworkBandwidthTest]),
+        "network_loss" => config.with_network_tests(&[&NetworkLossTest]),
+        "five_region" => config.with_network_tests(&[&FiveRegionTest]),
+        "network_node_loss" => config.with_network_tests(&[&NetworkNodeLossTest {
+            loss_percentage: DEFAULT_LOSS_PERCENTAGE,
+            lossy_validators_percentage: DEFAULT_LOSSY_VALIDATORS_PERCENTAGE,
+        }]),
+        "validator_restart" => config.with_network_tests(&[&ValidatorRestartUnderLoadTest {
+            restart_interval_secs: 120,
+            validators_per_restart: 3,
+            max_recovery_secs: 60,
+        }]),
+        // the chains k8s deploys only change epochs daily
+        "soak" => config.with_network_tests(&[&SoakTest {
+            check_interval_secs: 600,
+            target_tps: 100,
+            max_epoch_secs: None,
+        }]),
         _ => config.with_network_tests(&[&PerformanceBenchmark]),
     }
 }
@@ -0,0 +1,66 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    ChainConfig, Factory, GenesisConfig, LocalFactory, LocalVersion, NodeResourcesConfig, Result,
+    Swarm, Version,
+};
+use rand::rngs::StdRng;
+use std::{collections::HashMap, num::NonZeroUsize};
+
+/// Launches local swarms whose nodes run in docker containers of validator images, rather than from
+/// binaries built in the workspace. This gives multi-process networks of released versions without a
+/// Kubernetes cluster.
+pub struct DockerFactory {
+    local_factory: LocalFactory,
+}
+
+impl DockerFactory {
+    /// The images are given from the oldest version to the newest
+    pub fn new(images: Vec<String>) -> Self {
+        let versions = images
+            .into_iter()
+            .enumerate()
+            .map(|(i, image)| {
+                let version = Version::new(i, image.clone());
+                (
+                    version.clone(),
+                    LocalVersion::from_docker_image(image, version),
+                )
+            })
+            .collect::<HashMap<_, _>>();
+        Self {
+            local_factory: LocalFactory::new(versions),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Factory for DockerFactory {
+    fn versions<'a>(&'a self) -> Box<dyn Iterator<Item = Version> + 'a> {
+        self.local_factory.versions()
+    }
+
+    async fn launch_swarm(
+        &self,
+        rng: &mut StdRng,
+        node_num: NonZeroUsize,
+        version: &Version,
+        genesis_version: &Version,
+        genesis_config: Option<&GenesisConfig>,
+        node_resources: &NodeResourcesConfig,
+        chain_config: &ChainConfig,
+    ) -> Result<Box<dyn Swarm>> {
+        self.local_factory
+            .launch_swarm(
+                rng,
+                node_num,
+                version,
+                genesis_version,
+                genesis_config,
+                node_resources,
+                chain_config,
+            )
+            .await
+    }
+}
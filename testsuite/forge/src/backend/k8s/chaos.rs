@@ -1,12 +1,19 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
-use std::process::Command;
+use std::{
+    collections::{hash_map::DefaultHasher, HashMap},
+    hash::{Hash, Hasher},
+    process::Command,
+};
 
+use anyhow::format_err;
+use aptos_sdk::types::PeerId;
 use tempfile::TempDir;
 
 use crate::{
     dump_string_to_file, Result, SwarmChaos, SwarmNetworkBandwidth, SwarmNetworkDelay,
+    SwarmNetworkLinkDelay, SwarmNetworkLoss, SwarmNetworkNodeLoss, SwarmNetworkNodePartition,
     SwarmNetworkPartition, KUBECTL_BIN,
 };
 
@@ -25,16 +32,50 @@ macro_rules! BANDWIDTH_NETWORK_CHAOS_TEMPLATE {
         "chaos/network_bandwidth.yaml"
     };
 }
+macro_rules! LOSS_NETWORK_CHAOS_TEMPLATE {
+    () => {
+        "chaos/network_loss.yaml"
+    };
+}
+macro_rules! NODE_PARTITION_NETWORK_CHAOS_TEMPLATE {
+    () => {
+        "chaos/network_node_partition.yaml"
+    };
+}
+macro_rules! NODE_LOSS_NETWORK_CHAOS_TEMPLATE {
+    () => {
+        "chaos/network_node_loss.yaml"
+    };
+}
+macro_rules! LINK_DELAY_NETWORK_CHAOS_TEMPLATE {
+    () => {
+        "chaos/network_link_delay.yaml"
+    };
+}
+macro_rules! LINK_BANDWIDTH_NETWORK_CHAOS_TEMPLATE {
+    () => {
+        "chaos/network_link_bandwidth.yaml"
+    };
+}
 
-/// Injects the SwarmChaos into the specified namespace
-pub fn inject_swarm_chaos(kube_namespace: &str, chaos: &SwarmChaos) -> Result<()> {
-    let template = create_chaos_template(kube_namespace, chaos)?;
+/// Injects the SwarmChaos into the specified namespace. Chaos which targets specific nodes finds their
+/// pods in `pod_names`
+pub fn inject_swarm_chaos(
+    kube_namespace: &str,
+    chaos: &SwarmChaos,
+    pod_names: &HashMap<PeerId, String>,
+) -> Result<()> {
+    let template = create_chaos_template(kube_namespace, chaos, pod_names)?;
     inject_chaos_template(kube_namespace, template)
 }
 
 /// Removes the SwarmChaos from the specified namespace, if it exists
-pub fn remove_swarm_chaos(kube_namespace: &str, chaos: &SwarmChaos) -> Result<()> {
-    let template = create_chaos_template(kube_namespace, chaos)?;
+pub fn remove_swarm_chaos(
+    kube_namespace: &str,
+    chaos: &SwarmChaos,
+    pod_names: &HashMap<PeerId, String>,
+) -> Result<()> {
+    let template = create_chaos_template(kube_namespace, chaos, pod_names)?;
     remove_chaos_template(kube_namespace, template)
 }
 
@@ -75,11 +116,121 @@ fn create_network_bandwidth_template(
     )
 }
 
-fn create_chaos_template(kube_namespace: &str, chaos: &SwarmChaos) -> Result<String> {
+fn create_network_loss_template(
+    kube_namespace: &str,
+    swarm_network_loss: &SwarmNetworkLoss,
+) -> String {
+    format!(
+        include_str!(LOSS_NETWORK_CHAOS_TEMPLATE!()),
+        namespace = kube_namespace,
+        loss_percentage = swarm_network_loss.loss_percentage,
+        correlation_percentage = swarm_network_loss.correlation_percentage
+    )
+}
+
+fn join_pod_names(nodes: &[PeerId], pod_names: &HashMap<PeerId, String>) -> Result<String> {
+    let names = nodes
+        .iter()
+        .map(|peer_id| {
+            pod_names
+                .get(peer_id)
+                .cloned()
+                .ok_or_else(|| format_err!("No pod found for node {}", peer_id))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    Ok(names.join(", "))
+}
+
+fn create_network_node_partition_template(
+    kube_namespace: &str,
+    swarm_network_node_partition: &SwarmNetworkNodePartition,
+    pod_names: &HashMap<PeerId, String>,
+) -> Result<String> {
+    // the same partition always gets the same name, so it can be removed again
+    let mut hasher = DefaultHasher::new();
+    swarm_network_node_partition.hash(&mut hasher);
+    Ok(format!(
+        include_str!(NODE_PARTITION_NETWORK_CHAOS_TEMPLATE!()),
+        namespace = kube_namespace,
+        partition_id = format!("{:x}", hasher.finish()),
+        source_pods = join_pod_names(&swarm_network_node_partition.source_nodes, pod_names)?,
+        target_pods = join_pod_names(&swarm_network_node_partition.target_nodes, pod_names)?,
+    ))
+}
+
+fn create_network_node_loss_template(
+    kube_namespace: &str,
+    swarm_network_node_loss: &SwarmNetworkNodeLoss,
+    pod_names: &HashMap<PeerId, String>,
+) -> Result<String> {
+    // the same loss always gets the same name, so it can be removed again
+    let mut hasher = DefaultHasher::new();
+    swarm_network_node_loss.hash(&mut hasher);
+    Ok(format!(
+        include_str!(NODE_LOSS_NETWORK_CHAOS_TEMPLATE!()),
+        namespace = kube_namespace,
+        loss_id = format!("{:x}", hasher.finish()),
+        pods = join_pod_names(&swarm_network_node_loss.nodes, pod_names)?,
+        loss_percentage = swarm_network_node_loss.loss_percentage,
+        correlation_percentage = swarm_network_node_loss.correlation_percentage
+    ))
+}
+
+fn create_network_link_delay_template(
+    kube_namespace: &str,
+    swarm_network_link_delay: &SwarmNetworkLinkDelay,
+    pod_names: &HashMap<PeerId, String>,
+) -> Result<String> {
+    // the same link always gets the same name, so it can be removed again
+    let mut hasher = DefaultHasher::new();
+    swarm_network_link_delay.hash(&mut hasher);
+    let link_id = format!("{:x}", hasher.finish());
+    let source_pods = join_pod_names(&swarm_network_link_delay.source_nodes, pod_names)?;
+    let target_pods = join_pod_names(&swarm_network_link_delay.target_nodes, pod_names)?;
+    let mut template = format!(
+        include_str!(LINK_DELAY_NETWORK_CHAOS_TEMPLATE!()),
+        namespace = kube_namespace,
+        link_id = link_id,
+        source_pods = source_pods,
+        target_pods = target_pods,
+        latency_ms = swarm_network_link_delay.latency_ms,
+        jitter_ms = swarm_network_link_delay.jitter_ms,
+        correlation_percentage = swarm_network_link_delay.correlation_percentage,
+    );
+    // the cap is a second chaos on the same link, applied and deleted along with the delay
+    if let Some(bandwidth) = &swarm_network_link_delay.bandwidth {
+        template.push_str("---\n");
+        template.push_str(&format!(
+            include_str!(LINK_BANDWIDTH_NETWORK_CHAOS_TEMPLATE!()),
+            namespace = kube_namespace,
+            link_id = link_id,
+            source_pods = source_pods,
+            target_pods = target_pods,
+            rate = bandwidth.rate,
+            limit = bandwidth.limit,
+            buffer = bandwidth.buffer
+        ));
+    }
+    Ok(template)
+}
+
+fn create_chaos_template(
+    kube_namespace: &str,
+    chaos: &SwarmChaos,
+    pod_names: &HashMap<PeerId, String>,
+) -> Result<String> {
     let template = match chaos {
         SwarmChaos::Delay(c) => create_network_delay_template(kube_namespace, c),
         SwarmChaos::Partition(c) => create_network_partition_template(kube_namespace, c),
         SwarmChaos::Bandwidth(c) => create_network_bandwidth_template(kube_namespace, c),
+        SwarmChaos::Loss(c) => create_network_loss_template(kube_namespace, c),
+        SwarmChaos::NodePartition(c) => {
+            create_network_node_partition_template(kube_namespace, c, pod_names)?
+        }
+        SwarmChaos::NodeLoss(c) => create_network_node_loss_template(kube_namespace, c, pod_names)?,
+        SwarmChaos::LinkDelay(c) => {
+            create_network_link_delay_template(kube_namespace, c, pod_names)?
+        }
     };
     Ok(template)
 }
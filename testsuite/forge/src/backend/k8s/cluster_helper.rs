@@ -3,25 +3,31 @@
 
 use crate::{
     get_fullnodes, get_validators, k8s_wait_genesis_strategy, k8s_wait_nodes_strategy,
-    nodes_healthcheck, K8sNode, Result, DEFAULT_ROOT_KEY, FULLNODE_HAPROXY_SERVICE_SUFFIX,
-    FULLNODE_SERVICE_SUFFIX, VALIDATOR_HAPROXY_SERVICE_SUFFIX, VALIDATOR_SERVICE_SUFFIX,
+    nodes_healthcheck, ChainConfig, GenesisConfig, K8sNode, NodeResourcesConfig, Result,
+    DEFAULT_CHAIN_ID, DEFAULT_ROOT_KEY, FULLNODE_HAPROXY_SERVICE_SUFFIX, FULLNODE_SERVICE_SUFFIX,
+    VALIDATOR_HAPROXY_SERVICE_SUFFIX, VALIDATOR_SERVICE_SUFFIX,
 };
 use again::RetryPolicy;
 use anyhow::{bail, format_err};
+use aptos_config::config::IdentityBlob;
 use aptos_logger::info;
-use aptos_sdk::types::PeerId;
+use aptos_sdk::{
+    crypto::{bls12381, ed25519::Ed25519PrivateKey, x25519, PrivateKey, Uniform},
+    types::{transaction::authenticator::AuthenticationKey, PeerId},
+};
 use async_trait::async_trait;
 use k8s_openapi::api::{
     apps::v1::{Deployment, StatefulSet},
     batch::v1::Job,
-    core::v1::{ConfigMap, Namespace, PersistentVolumeClaim, Pod},
+    core::v1::{ConfigMap, Namespace, PersistentVolumeClaim, Pod, Secret},
 };
+use k8s_openapi::ByteString;
 use kube::{
     api::{Api, DeleteParams, ListParams, Meta, ObjectMeta, PostParams},
     client::Client as K8sClient,
     Config, Error as KubeError,
 };
-use rand::Rng;
+use rand::{rngs::OsRng, Rng};
 use serde::de::DeserializeOwned;
 use serde_json::Value;
 use std::{
@@ -49,6 +55,12 @@ const APTOS_NODE_HELM_RELEASE_NAME: &str = "aptos-node";
 const GENESIS_HELM_RELEASE_NAME: &str = "genesis";
 const APTOS_NODE_HELM_CHART_PATH: &str = "terraform/helm/aptos-node";
 const GENESIS_HELM_CHART_PATH: &str = "terraform/helm/genesis";
+// configmap holding the move modules genesis is run with, when they are given as bytes
+const GENESIS_MODULES_CONFIGMAP_NAME: &str = "genesis-move-modules";
+
+// files in each validator's genesis secret, see terraform/helm/genesis/files/genesis.sh
+pub(crate) const VALIDATOR_IDENTITY_FILE: &str = "validator-identity.yaml";
+pub(crate) const VALIDATOR_FULL_NODE_IDENTITY_FILE: &str = "validator-full-node-identity.yaml";
 
 // cleanup namespaces after 30 min unless "keep = true"
 const NAMESPACE_CLEANUP_THRESHOLD_SECS: u64 = 1800;
@@ -153,13 +165,26 @@ async fn wait_node_stateful_set(
     kube_client: &K8sClient,
     kube_namespace: &str,
     nodes: &HashMap<PeerId, K8sNode>,
+) -> Result<()> {
+    let sts_names: Vec<String> = nodes
+        .values()
+        .map(|node| node.sts_name().to_string())
+        .collect();
+    wait_stateful_sets(kube_client, kube_namespace, &sts_names).await
+}
+
+/// Waits until all the replicas of the given StatefulSets are ready
+pub(crate) async fn wait_stateful_sets(
+    kube_client: &K8sClient,
+    kube_namespace: &str,
+    sts_names: &[String],
 ) -> Result<()> {
     aptos_retrier::retry_async(k8s_wait_nodes_strategy(), || {
         let sts: Api<StatefulSet> = Api::namespaced(kube_client.clone(), kube_namespace);
         Box::pin(async move {
             // wait for all validators healthy
-            for node in nodes.values() {
-                match sts.get_status(node.sts_name()).await {
+            for sts_name in sts_names {
+                match sts.get_status(sts_name).await {
                     Ok(s) => {
                         let sts_name = &s.name();
                         if let Some(sts_status) = s.status {
@@ -395,6 +420,28 @@ pub async fn uninstall_testnet_resources(kube_namespace: String) -> Result<()> {
     Ok(())
 }
 
+// validators and fullnodes are provisioned alike
+fn node_resources_helm_options(node_resources: &NodeResourcesConfig) -> Vec<String> {
+    let values = [
+        ("resources.requests.cpu", &node_resources.cpu_request),
+        ("resources.limits.cpu", &node_resources.cpu_limit),
+        ("resources.requests.memory", &node_resources.memory_request),
+        ("resources.limits.memory", &node_resources.memory_limit),
+        ("storage.class", &node_resources.storage_class),
+        ("storage.size", &node_resources.storage_size),
+    ];
+    let mut options = vec![];
+    for (key, value) in values {
+        if let Some(value) = value {
+            for role in ["validator", "fullnode"] {
+                options.push("--set".to_string());
+                options.push(format!("{}.{}={}", role, key, value));
+            }
+        }
+    }
+    options
+}
+
 fn generate_new_era() -> String {
     let mut rng = rand::thread_rng();
     let r: u8 = rng.gen();
@@ -406,11 +453,18 @@ pub async fn install_testnet_resources(
     base_num_validators: usize,
     base_validator_image_tag: String,
     base_genesis_image_tag: String,
-    genesis_modules_path: Option<String>,
+    genesis_config: Option<&GenesisConfig>,
+    node_resources: &NodeResourcesConfig,
+    chain_config: &ChainConfig,
     use_port_forward: bool,
     enable_haproxy: bool,
 ) -> Result<(HashMap<PeerId, K8sNode>, HashMap<PeerId, K8sNode>)> {
     assert!(base_num_validators <= MAX_NUM_VALIDATORS);
+    let root_key = match &chain_config.root_key {
+        Some(root_key) => hex::encode(root_key.private_key()?.public_key().to_bytes()),
+        None => DEFAULT_ROOT_KEY.to_string(),
+    };
+    let chain_id = chain_config.chain_id.map_or(DEFAULT_CHAIN_ID, |c| c.id());
 
     let kube_client = create_k8s_client().await;
 
@@ -452,7 +506,8 @@ pub async fn install_testnet_resources(
         num_validators = base_num_validators,
         image_tag = &base_genesis_image_tag,
         era = &new_era,
-        root_key = DEFAULT_ROOT_KEY,
+        root_key = root_key,
+        chain_id = chain_id,
         validator_internal_host_suffix = validator_internal_host_suffix,
         fullnode_internal_host_suffix = fullnode_internal_host_suffix,
         namespace = &kube_namespace,
@@ -464,13 +519,14 @@ pub async fn install_testnet_resources(
     )?;
 
     // combine all helm values
-    let aptos_node_upgrade_options = vec![
+    let mut aptos_node_upgrade_options = vec![
         // use the old values
         "-f".to_string(),
         aptos_node_values_file,
         "-f".to_string(),
         aptos_node_forge_values_file,
     ];
+    aptos_node_upgrade_options.extend(node_resources_helm_options(node_resources));
 
     let mut genesis_upgrade_options = vec![
         // use the old values
@@ -480,12 +536,27 @@ pub async fn install_testnet_resources(
         genesis_forge_values_file,
     ];
 
-    // run genesis from the directory in aptos/init image
-    if let Some(genesis_modules_path) = genesis_modules_path {
-        genesis_upgrade_options.extend([
-            "--set".to_string(),
-            format!("genesis.moveModulesDir={}", genesis_modules_path),
-        ]);
+    match genesis_config {
+        // run genesis from the directory in aptos/init image
+        Some(GenesisConfig::Path(genesis_modules_path)) => {
+            genesis_upgrade_options.extend([
+                "--set".to_string(),
+                format!("genesis.moveModulesDir={}", genesis_modules_path),
+            ]);
+        }
+        // run genesis from the modules mounted from a configmap
+        Some(GenesisConfig::Bytes(genesis_modules)) => {
+            create_genesis_modules_configmap(&kube_client, &kube_namespace, genesis_modules)
+                .await?;
+            genesis_upgrade_options.extend([
+                "--set".to_string(),
+                format!(
+                    "genesis.moveModulesConfigMap={}",
+                    GENESIS_MODULES_CONFIGMAP_NAME
+                ),
+            ]);
+        }
+        None => {}
     }
 
     // upgrade genesis
@@ -512,6 +583,301 @@ pub async fn install_testnet_resources(
     Ok((validators, fullnodes))
 }
 
+/// Replaces the configmap the genesis job reads custom move modules from. Genesis only reads the
+/// `.mv` files, so the modules are named by their index
+async fn create_genesis_modules_configmap(
+    kube_client: &K8sClient,
+    kube_namespace: &str,
+    genesis_modules: &[Vec<u8>],
+) -> Result<()> {
+    let configmaps: Api<ConfigMap> = Api::namespaced(kube_client.clone(), kube_namespace);
+    match configmaps
+        .delete(GENESIS_MODULES_CONFIGMAP_NAME, &DeleteParams::default())
+        .await
+    {
+        Ok(_) => {}
+        Err(KubeError::Api(api_err)) if api_err.code == 404 => {}
+        Err(e) => bail!(
+            "Failed to delete configmap {}: {:?}",
+            GENESIS_MODULES_CONFIGMAP_NAME,
+            e
+        ),
+    }
+
+    let binary_data = genesis_modules
+        .iter()
+        .enumerate()
+        .map(|(i, module)| (format!("{:03}.mv", i), ByteString(module.clone())))
+        .collect::<BTreeMap<_, _>>();
+    let configmap = ConfigMap {
+        binary_data: Some(binary_data),
+        data: None,
+        metadata: ObjectMeta {
+            name: Some(GENESIS_MODULES_CONFIGMAP_NAME.to_string()),
+            ..ObjectMeta::default()
+        },
+    };
+    configmaps
+        .create(&PostParams::default(), &configmap)
+        .await
+        .map_err(|e| {
+            format_err!(
+                "Failed to create configmap {} with {} genesis modules: {:?}",
+                GENESIS_MODULES_CONFIGMAP_NAME,
+                genesis_modules.len(),
+                e
+            )
+        })?;
+    info!(
+        "Created configmap {} with {} genesis modules",
+        GENESIS_MODULES_CONFIGMAP_NAME,
+        genesis_modules.len()
+    );
+    Ok(())
+}
+
+fn generate_validator_identity() -> (IdentityBlob, IdentityBlob) {
+    let mut rng = OsRng;
+    let account_key = Ed25519PrivateKey::generate(&mut rng);
+    let account_address = AuthenticationKey::ed25519(&account_key.public_key()).derived_address();
+    let validator_identity = IdentityBlob {
+        account_address: Some(account_address),
+        account_private_key: Some(account_key),
+        consensus_private_key: Some(bls12381::PrivateKey::generate(&mut rng)),
+        network_private_key: x25519::PrivateKey::generate(&mut rng),
+    };
+    let vfn_identity = IdentityBlob {
+        account_address: Some(account_address),
+        account_private_key: None,
+        consensus_private_key: None,
+        network_private_key: x25519::PrivateKey::generate(&mut rng),
+    };
+    (validator_identity, vfn_identity)
+}
+
+/// Gets the genesis secret of the first validator, along with the era it belongs to
+async fn get_genesis_secret(secrets_api: &Api<Secret>) -> Result<(Secret, String)> {
+    // genesis only keeps the secrets of the current era around
+    let genesis_secret_prefix = format!("{}-0-genesis-e", APTOS_NODE_HELM_RELEASE_NAME);
+    let genesis_secret = secrets_api
+        .list(&ListParams::default())
+        .await?
+        .items
+        .into_iter()
+        .find(|s| s.name().starts_with(&genesis_secret_prefix))
+        .ok_or_else(|| format_err!("No genesis secret found"))?;
+    let era = genesis_secret.name()[genesis_secret_prefix.len()..].to_string();
+    Ok((genesis_secret, era))
+}
+
+/// Gets the era of the running network
+async fn get_era(kube_client: &K8sClient, kube_namespace: &str) -> Result<String> {
+    let secrets_api: Api<Secret> = Api::namespaced(kube_client.clone(), kube_namespace);
+    let (_, era) = get_genesis_secret(&secrets_api).await?;
+    Ok(era)
+}
+
+/// Creates the genesis secret of a validator which was not part of genesis. It carries the genesis blob and waypoint
+/// of the running chain, copied from the first validator's secret, along with a freshly generated identity
+async fn create_new_validator_genesis_secret(
+    kube_client: &K8sClient,
+    kube_namespace: &str,
+    node_id: usize,
+) -> Result<PeerId> {
+    let secrets_api: Api<Secret> = Api::namespaced(kube_client.clone(), kube_namespace);
+    let (genesis_secret, era) = get_genesis_secret(&secrets_api).await?;
+
+    // identities are read as yaml, which json is a subset of
+    let (validator_identity, vfn_identity) = generate_validator_identity();
+    let peer_id = validator_identity
+        .account_address
+        .expect("validator identity has an account address");
+    let mut data = genesis_secret.data.unwrap_or_default();
+    data.insert(
+        VALIDATOR_IDENTITY_FILE.to_string(),
+        ByteString(serde_json::to_vec(&validator_identity)?),
+    );
+    data.insert(
+        VALIDATOR_FULL_NODE_IDENTITY_FILE.to_string(),
+        ByteString(serde_json::to_vec(&vfn_identity)?),
+    );
+
+    let secret_name = format!(
+        "{}-{}-genesis-e{}",
+        APTOS_NODE_HELM_RELEASE_NAME, node_id, era
+    );
+    info!("Creating genesis secret {}", secret_name);
+    let secret = Secret {
+        metadata: ObjectMeta {
+            name: Some(secret_name),
+            ..ObjectMeta::default()
+        },
+        data: Some(data),
+        ..Secret::default()
+    };
+    secrets_api.create(&PostParams::default(), &secret).await?;
+    Ok(peer_id)
+}
+
+/// Adds the validator with index `node_id` to the running aptos-node release, and waits for it to come up.
+/// The new validator is not part of genesis: it syncs the running chain, but needs to be staked and join the
+/// validator set on-chain before it takes part in consensus
+pub async fn install_new_validator(
+    kube_client: &K8sClient,
+    kube_namespace: String,
+    node_id: usize,
+    image_tag: String,
+    use_port_forward: bool,
+    enable_haproxy: bool,
+) -> Result<K8sNode> {
+    if node_id >= MAX_NUM_VALIDATORS {
+        bail!(
+            "Cannot add validator {}, at most {} validators are supported",
+            node_id,
+            MAX_NUM_VALIDATORS
+        );
+    }
+    let peer_id =
+        create_new_validator_genesis_secret(kube_client, &kube_namespace, node_id).await?;
+
+    // the release templates one validator per index below numValidators
+    let aptos_node_upgrade_options = vec![
+        "--set".to_string(),
+        format!("numValidators={}", node_id + 1),
+    ];
+    upgrade_aptos_node_helm(
+        aptos_node_upgrade_options.as_slice(),
+        kube_namespace.clone(),
+    )?;
+
+    let validator = get_validators(
+        kube_client.clone(),
+        &image_tag,
+        &kube_namespace,
+        use_port_forward,
+        enable_haproxy,
+    )
+    .await?
+    .into_values()
+    .find(|v| v.node_id == node_id)
+    .ok_or_else(|| format_err!("Validator {} not found after helm upgrade", node_id))?;
+
+    let mut validators = HashMap::new();
+    validators.insert(peer_id, validator);
+    wait_node_stateful_set(kube_client, &kube_namespace, &validators).await?;
+    if enable_haproxy {
+        wait_node_haproxy(kube_client, &kube_namespace, node_id + 1).await?;
+    }
+    let mut validator = validators.remove(&peer_id).unwrap();
+    if use_port_forward {
+        validator.spawn_port_forward()?;
+    }
+
+    Ok(validator)
+}
+
+/// Removes all validators with an index from `num_validators` on from the running aptos-node release, along
+/// with their genesis secrets, so the indices can be taken by new validators again
+pub async fn uninstall_validators(
+    kube_client: &K8sClient,
+    kube_namespace: String,
+    num_validators: usize,
+) -> Result<()> {
+    let aptos_node_upgrade_options = vec![
+        "--set".to_string(),
+        format!("numValidators={}", num_validators),
+    ];
+    upgrade_aptos_node_helm(
+        aptos_node_upgrade_options.as_slice(),
+        kube_namespace.clone(),
+    )?;
+
+    let secrets_api: Api<Secret> = Api::namespaced(kube_client.clone(), &kube_namespace);
+    let era = get_era(kube_client, &kube_namespace).await?;
+    let removed_secret_names = secrets_api
+        .list(&ListParams::default())
+        .await?
+        .items
+        .into_iter()
+        .map(|s| s.name())
+        .filter(|name| {
+            name.strip_prefix(&format!("{}-", APTOS_NODE_HELM_RELEASE_NAME))
+                .and_then(|name| name.strip_suffix(&format!("-genesis-e{}", era)))
+                .and_then(|node_id| node_id.parse::<usize>().ok())
+                .map_or(false, |node_id| node_id >= num_validators)
+        })
+        .collect::<Vec<_>>();
+    for secret_name in removed_secret_names {
+        info!("Deleting genesis secret {}", secret_name);
+        secrets_api
+            .delete(&secret_name, &DeleteParams::default())
+            .await?;
+    }
+    Ok(())
+}
+
+fn fullnode_helm_options(num_fullnode_groups: usize) -> Vec<String> {
+    // a single group, which gives each of the first num_fullnode_groups validators one fullnode
+    vec![
+        "--set".to_string(),
+        format!("numFullnodeGroups={}", num_fullnode_groups),
+        "--set".to_string(),
+        format!("fullnode.groups[0].name={}", FULLNODE_SERVICE_SUFFIX),
+        "--set".to_string(),
+        "fullnode.groups[0].replicas=1".to_string(),
+    ]
+}
+
+/// Adds a fullnode for the validator with index `node_id` to the running aptos-node release, and waits for it
+/// to come up. The fullnode connects to the validator network through that validator, using the fullnode
+/// identity in its genesis secret
+pub async fn install_new_fullnode(
+    kube_client: &K8sClient,
+    kube_namespace: String,
+    node_id: usize,
+    image_tag: String,
+    use_port_forward: bool,
+    enable_haproxy: bool,
+) -> Result<K8sNode> {
+    upgrade_aptos_node_helm(
+        fullnode_helm_options(node_id + 1).as_slice(),
+        kube_namespace.clone(),
+    )?;
+
+    let era = get_era(kube_client, &kube_namespace).await?;
+    let fullnode = get_fullnodes(
+        kube_client.clone(),
+        &image_tag,
+        &era,
+        &kube_namespace,
+        use_port_forward,
+        enable_haproxy,
+    )
+    .await?
+    .into_values()
+    .find(|f| f.node_id == node_id)
+    .ok_or_else(|| format_err!("Fullnode {} not found after helm upgrade", node_id))?;
+    let peer_id = fullnode.peer_id;
+
+    let mut fullnodes = HashMap::new();
+    fullnodes.insert(peer_id, fullnode);
+    wait_node_stateful_set(kube_client, &kube_namespace, &fullnodes).await?;
+    let mut fullnode = fullnodes.remove(&peer_id).unwrap();
+    if use_port_forward {
+        fullnode.spawn_port_forward()?;
+    }
+
+    Ok(fullnode)
+}
+
+/// Removes the fullnodes of all validators with an index from `num_fullnode_groups` on
+pub fn uninstall_fullnodes(num_fullnode_groups: usize, kube_namespace: String) -> Result<()> {
+    upgrade_aptos_node_helm(
+        fullnode_helm_options(num_fullnode_groups).as_slice(),
+        kube_namespace,
+    )
+}
+
 /// Collect the running nodes in the network into K8sNodes
 pub async fn collect_running_nodes(
     kube_client: &K8sClient,
@@ -521,7 +887,7 @@ pub async fn collect_running_nodes(
     enable_haproxy: bool,
 ) -> Result<(HashMap<PeerId, K8sNode>, HashMap<PeerId, K8sNode>)> {
     // get all validators
-    let validators = get_validators(
+    let mut validators = get_validators(
         kube_client.clone(),
         &base_validator_image_tag,
         &kube_namespace,
@@ -539,9 +905,11 @@ pub async fn collect_running_nodes(
     }
 
     // get all fullnodes
-    let fullnodes = get_fullnodes(
+    let era = get_era(kube_client, &kube_namespace).await?;
+    let mut fullnodes = get_fullnodes(
         kube_client.clone(),
         &base_validator_image_tag,
+        &era,
         &kube_namespace,
         use_port_forward,
         enable_haproxy,
@@ -551,19 +919,17 @@ pub async fn collect_running_nodes(
 
     wait_node_stateful_set(kube_client, &kube_namespace, &fullnodes).await?;
 
-    let nodes = validators
-        .values()
-        // .chain(fullnodes.values())
-        .collect::<Vec<&K8sNode>>();
-
-    // start port-forward for each of the validators
+    // start port-forward for each of the nodes
     if use_port_forward {
-        for node in nodes.iter() {
+        for node in validators.values_mut().chain(fullnodes.values_mut()) {
             node.spawn_port_forward()?;
-            // assume this will always succeed???
         }
     }
 
+    let nodes = validators
+        .values()
+        // .chain(fullnodes.values())
+        .collect::<Vec<&K8sNode>>();
     nodes_healthcheck(nodes).await?;
     Ok((validators, fullnodes))
 }
@@ -575,8 +941,14 @@ pub async fn create_k8s_client() -> K8sClient {
     K8sClient::try_from(config_infer).unwrap()
 }
 
-pub fn scale_stateful_set_replicas(sts_name: &str, replica_num: u64) -> Result<()> {
+pub fn scale_stateful_set_replicas(
+    sts_name: &str,
+    kube_namespace: &str,
+    replica_num: u64,
+) -> Result<()> {
     let scale_sts_args = [
+        "-n",
+        kube_namespace,
         "scale",
         "sts",
         sts_name,
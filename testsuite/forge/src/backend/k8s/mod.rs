@@ -1,7 +1,7 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{Factory, GenesisConfig, Result, Swarm, Version};
+use crate::{ChainConfig, Factory, GenesisConfig, NodeResourcesConfig, Result, Swarm, Version};
 use anyhow::bail;
 use aptos_logger::info;
 use rand::rngs::StdRng;
@@ -17,7 +17,7 @@ pub use cluster_helper::*;
 pub use node::K8sNode;
 pub use swarm::*;
 
-use aptos_sdk::crypto::ed25519::ED25519_PRIVATE_KEY_LENGTH;
+use aptos_sdk::{crypto::ed25519::ED25519_PRIVATE_KEY_LENGTH, types::chain_id::ChainId};
 
 pub struct K8sFactory {
     root_key: [u8; ED25519_PRIVATE_KEY_LENGTH],
@@ -35,6 +35,7 @@ pub const DEFAULT_ROOT_KEY: &str =
     "48136DF3174A3DE92AFDB375FFE116908B69FF6FAB9B1410E548A33FEA1D159D";
 const DEFAULT_ROOT_PRIV_KEY: &str =
     "E25708D90C72A53B400B27FC7602C4D546C7B7469FA6E12544F0EBFB2F16AE19";
+pub const DEFAULT_CHAIN_ID: u8 = 4;
 
 impl K8sFactory {
     pub fn new(
@@ -94,16 +95,16 @@ impl Factory for K8sFactory {
         init_version: &Version,
         genesis_version: &Version,
         genesis_config: Option<&GenesisConfig>,
+        node_resources: &NodeResourcesConfig,
+        chain_config: &ChainConfig,
     ) -> Result<Box<dyn Swarm>> {
-        let genesis_modules_path = match genesis_config {
-            Some(config) => match config {
-                GenesisConfig::Bytes(_) => {
-                    bail!("k8s forge backend does not support raw bytes as genesis modules. please specify a path instead")
-                }
-                GenesisConfig::Path(path) => Some(path.clone()),
-            },
-            None => None,
+        let root_key = match &chain_config.root_key {
+            Some(root_key) => root_key.private_key()?.to_bytes(),
+            None => self.root_key,
         };
+        let chain_id = chain_config
+            .chain_id
+            .unwrap_or_else(|| ChainId::new(DEFAULT_CHAIN_ID));
 
         let (validators, fullnodes) = if self.reuse {
             let kube_client = create_k8s_client().await;
@@ -130,7 +131,9 @@ impl Factory for K8sFactory {
                 node_num.get(),
                 format!("{}", init_version),
                 format!("{}", genesis_version),
-                genesis_modules_path,
+                genesis_config,
+                node_resources,
+                chain_config,
                 self.use_port_forward,
                 self.enable_haproxy,
             )
@@ -145,13 +148,16 @@ impl Factory for K8sFactory {
         };
 
         let swarm = K8sSwarm::new(
-            &self.root_key,
+            &root_key,
+            chain_id,
             &self.image_tag,
             &self.base_image_tag,
             &self.kube_namespace,
             validators,
             fullnodes,
             self.keep,
+            self.use_port_forward,
+            self.enable_haproxy,
         )
         .await
         .unwrap();
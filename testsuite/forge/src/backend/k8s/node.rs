@@ -2,25 +2,32 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    scale_stateful_set_replicas, FullNode, HealthCheckError, Node, NodeExt, Result, Validator,
-    Version, KUBECTL_BIN,
+    create_k8s_client, scale_stateful_set_replicas, wait_stateful_sets, FullNode, HealthCheckError,
+    Node, NodeExt, Result, Validator, Version, KUBECTL_BIN,
 };
 use anyhow::{anyhow, format_err, Context};
-use aptos_config::config::NodeConfig;
+use aptos_config::config::{NodeConfig, PersistableConfig};
 use aptos_logger::info;
 use aptos_rest_client::Client as RestClient;
 use aptos_sdk::types::PeerId;
+use k8s_openapi::api::core::v1::ConfigMap;
+use kube::api::{Api, PostParams};
 use reqwest::Url;
 use serde_json::Value;
 use std::{
+    collections::BTreeMap,
     fmt::{Debug, Formatter},
-    process::{Command, Stdio},
+    fs,
+    process::{Child, Command, Stdio},
     str::FromStr,
     thread,
     time::{Duration, Instant},
 };
+use tempfile::TempDir;
 
 const NODE_METRIC_PORT: u64 = 9101;
+// how many times a port-forward exiting right away is spawned again
+const PORT_FORWARD_ATTEMPTS: usize = 3;
 
 // this is the port on the validator service itself, as opposed to 80 on the validator haproxy service
 pub const REST_API_SERVICE_PORT: u32 = 8080;
@@ -39,6 +46,10 @@ pub struct K8sNode {
     pub namespace: String,
     // this controls whether the connection routes to HAProxy first
     pub enable_haproxy: bool,
+    // whether this node's REST API is reached through a kubectl port-forward
+    pub(crate) use_port_forward: bool,
+    // the kubectl port-forward process reaching this node's REST API, if any
+    pub(crate) port_forward: Option<Child>,
 }
 
 impl K8sNode {
@@ -59,8 +70,7 @@ impl K8sNode {
         self.ip.clone()
     }
 
-    #[allow(dead_code)]
-    fn node_id(&self) -> usize {
+    pub(crate) fn node_id(&self) -> usize {
         self.node_id
     }
 
@@ -76,7 +86,11 @@ impl K8sNode {
         &self.namespace
     }
 
-    pub fn spawn_port_forward(&self) -> Result<()> {
+    /// Reaches the REST API of this node through a kubectl port-forward on its local port, rather than through
+    /// its service's cluster IP. The port-forward is bound to the pod, so it is spawned again whenever the node
+    /// is started, once its StatefulSet is ready
+    pub fn spawn_port_forward(&mut self) -> Result<()> {
+        self.kill_port_forward();
         let remote_rest_api_port = if self.enable_haproxy {
             REST_API_HAPROXY_SERVICE_PORT
         } else {
@@ -89,42 +103,58 @@ impl K8sNode {
             &format!("svc/{}", self.dns()),
             &format!("{}:{}", self.rest_api_port(), remote_rest_api_port),
         ];
-        // spawn a port-forward child process
-        let cmd = Command::new(KUBECTL_BIN)
-            .args(port_forward_args)
-            .stdout(Stdio::null())
-            // .stderr(Stdio::null())
-            .spawn();
-        match cmd {
-            Ok(mut child) => {
-                // sleep a bit and check if port-forward failed for some reason
-                let timeout = Duration::from_secs(1);
-                thread::sleep(timeout);
-                match child.try_wait() {
-                    Ok(Some(status)) => {
-                        info!("Port-forward may have started already: exit {}", status);
-                        Ok(())
-                    }
-                    Ok(None) => {
-                        info!("Port-forward started for {:?}", self);
-                        Ok(())
-                    }
-                    Err(err) => Err(anyhow!(
+        for attempt in 1..=PORT_FORWARD_ATTEMPTS {
+            // spawn a port-forward child process
+            let mut child = Command::new(KUBECTL_BIN)
+                .args(port_forward_args)
+                .stdout(Stdio::null())
+                .spawn()
+                .with_context(|| format!("Port-forward did not start: {:?}", port_forward_args))?;
+            // sleep a bit and check if port-forward failed for some reason
+            thread::sleep(Duration::from_secs(1));
+            match child.try_wait() {
+                Ok(None) => {
+                    info!("Port-forward started for {:?}", self);
+                    self.port_forward = Some(child);
+                    return Ok(());
+                }
+                // e.g. the pod is not running yet, or the local port is taken
+                Ok(Some(status)) => info!(
+                    "Port-forward for {:?} exited with {} (attempt {}/{})",
+                    self, status, attempt, PORT_FORWARD_ATTEMPTS
+                ),
+                Err(err) => {
+                    let _ = child.kill();
+                    return Err(anyhow!(
                         "Port-forward did not work: {:?} error {}",
                         port_forward_args,
                         err
-                    )),
+                    ));
                 }
             }
-            Err(err) => Err(anyhow!(
-                "Port-forward did not start: {:?} error {}",
-                port_forward_args,
-                err
-            )),
+        }
+        Err(anyhow!(
+            "Port-forward kept exiting after {} attempts: {:?}",
+            PORT_FORWARD_ATTEMPTS,
+            port_forward_args
+        ))
+    }
+
+    fn kill_port_forward(&mut self) {
+        if let Some(mut child) = self.port_forward.take() {
+            // the port-forward may have exited on its own already
+            let _ = child.kill();
+            let _ = child.wait();
         }
     }
 }
 
+impl Drop for K8sNode {
+    fn drop(&mut self) {
+        self.kill_port_forward();
+    }
+}
+
 #[async_trait::async_trait]
 impl Node for K8sNode {
     fn peer_id(&self) -> PeerId {
@@ -152,8 +182,52 @@ impl Node for K8sNode {
         todo!()
     }
 
+    // the config lives in the node's ConfigMap, which the next helm upgrade of the release overwrites
+    async fn update_config(
+        &mut self,
+        update: Box<dyn FnOnce(&mut NodeConfig) + Send>,
+    ) -> Result<()> {
+        let configmaps_api: Api<ConfigMap> =
+            Api::namespaced(create_k8s_client().await, self.namespace());
+        // a validator shares its ConfigMap with its fullnode
+        let configmap_name = format!("aptos-node-{}", self.node_id);
+        let config_file = if self.sts_name.contains("fullnode") {
+            "fullnode.yaml"
+        } else {
+            "validator.yaml"
+        };
+        let mut configmap = configmaps_api.get(&configmap_name).await?;
+        let data = configmap.data.get_or_insert_with(BTreeMap::new);
+        let mut config =
+            NodeConfig::parse(data.get(config_file).ok_or_else(|| {
+                format_err!("No {} in ConfigMap {}", config_file, configmap_name)
+            })?)?;
+        update(&mut config);
+
+        let tmp_dir = TempDir::new()?;
+        let config_path = tmp_dir.path().join(config_file);
+        config.save_config(&config_path)?;
+        data.insert(config_file.to_string(), fs::read_to_string(config_path)?);
+        configmaps_api
+            .replace(&configmap_name, &PostParams::default(), &configmap)
+            .await?;
+        info!("Updated {} in ConfigMap {}", config_file, configmap_name);
+
+        self.restart().await
+    }
+
     async fn start(&mut self) -> Result<()> {
-        scale_stateful_set_replicas(self.sts_name(), 1)?;
+        scale_stateful_set_replicas(self.sts_name(), self.namespace(), 1)?;
+        if self.use_port_forward {
+            // the port-forward attaches to a running pod, so it waits for the new one
+            wait_stateful_sets(
+                &create_k8s_client().await,
+                self.namespace(),
+                &[self.sts_name().to_string()],
+            )
+            .await?;
+            self.spawn_port_forward()?;
+        }
         self.wait_until_healthy(Instant::now() + Duration::from_secs(60))
             .await?;
 
@@ -162,7 +236,7 @@ impl Node for K8sNode {
 
     fn stop(&mut self) -> Result<()> {
         info!("going to stop node {}", self.sts_name());
-        scale_stateful_set_replicas(self.sts_name(), 0)
+        scale_stateful_set_replicas(self.sts_name(), self.namespace(), 0)
     }
 
     fn clear_storage(&mut self) -> Result<()> {
@@ -172,9 +246,9 @@ impl Node for K8sNode {
         } else {
             sts_name
         };
-        let delete_pvc_args = ["delete", "pvc", &pvc_name];
+        let delete_pvc_args = ["-n", self.namespace(), "delete", "pvc", &pvc_name];
         info!("{:?}", delete_pvc_args);
-        let cleanup_output = Command::new("kubectl")
+        let cleanup_output = Command::new(KUBECTL_BIN)
             .stdout(Stdio::inherit())
             .args(&delete_pvc_args)
             .output()
@@ -227,12 +301,14 @@ impl Node for K8sNode {
             port += 1;
         }
         let port_forward_args = [
+            "-n",
+            self.namespace(),
             "port-forward",
             &format!("pod/{}", pod_name),
             &format!("{}:{}", port, NODE_METRIC_PORT),
         ];
         info!("{:?}", port_forward_args);
-        let _ = Command::new("kubectl")
+        let _ = Command::new(KUBECTL_BIN)
             .stdout(Stdio::null())
             .args(&port_forward_args)
             .spawn()
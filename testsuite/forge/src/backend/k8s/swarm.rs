@@ -2,32 +2,38 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    chaos, create_k8s_client,
+    chaos, create_k8s_client, install_new_fullnode, install_new_validator,
     node::{K8sNode, REST_API_HAPROXY_SERVICE_PORT, REST_API_SERVICE_PORT},
     prometheus::{self, query_with_metadata},
-    query_sequence_numbers, set_validator_image_tag, uninstall_testnet_resources, ChainInfo,
-    FullNode, Node, Result, Swarm, SwarmChaos, Validator, Version,
+    query_sequence_numbers, scale_stateful_set_replicas, set_validator_image_tag,
+    uninstall_fullnodes, uninstall_testnet_resources, uninstall_validators, ChainInfo, FullNode,
+    Node, NodeExt, Result, Swarm, SwarmChaos, Validator, Version, MAX_HEALTHY_LEDGER_STALENESS,
+    VALIDATOR_FULL_NODE_IDENTITY_FILE, VALIDATOR_IDENTITY_FILE,
 };
 use ::aptos_logger::*;
 use anyhow::{anyhow, bail, format_err};
-use aptos_config::config::NodeConfig;
+use aptos_config::config::{IdentityBlob, NodeConfig, PersistableConfig};
 use aptos_retrier::ExponentWithLimitDelay;
 use aptos_sdk::{
-    crypto::ed25519::Ed25519PrivateKey,
+    crypto::{ed25519::Ed25519PrivateKey, PrivateKey},
     move_types::account_address::AccountAddress,
-    types::{chain_id::ChainId, AccountKey, LocalAccount, PeerId},
+    types::{
+        account_address::from_identity_public_key, chain_id::ChainId, AccountKey, LocalAccount,
+        PeerId,
+    },
 };
-use k8s_openapi::api::core::v1::Service;
+use k8s_openapi::api::core::v1::{Pod, Secret, Service};
 use kube::{
-    api::{Api, ListParams},
+    api::{Api, ListParams, LogParams, Meta},
     client::Client as K8sClient,
 };
 use prometheus_http_query::{response::PromqlResult, Client as PrometheusClient};
 use std::{
     collections::{BTreeMap, HashMap, HashSet},
     convert::TryFrom,
-    env,
+    env, fs,
     net::TcpListener,
+    path::{Path, PathBuf},
     str,
     sync::Arc,
 };
@@ -48,6 +54,8 @@ pub struct K8sSwarm {
     pub chain_id: ChainId,
     kube_namespace: String,
     keep: bool,
+    use_port_forward: bool,
+    enable_haproxy: bool,
     chaoses: HashSet<SwarmChaos>,
     prom_client: Option<PrometheusClient>,
 }
@@ -55,12 +63,15 @@ pub struct K8sSwarm {
 impl K8sSwarm {
     pub async fn new(
         root_key: &[u8],
+        chain_id: ChainId,
         image_tag: &str,
         base_image_tag: &str,
         kube_namespace: &str,
         validators: HashMap<AccountAddress, K8sNode>,
         fullnodes: HashMap<AccountAddress, K8sNode>,
         keep: bool,
+        use_port_forward: bool,
+        enable_haproxy: bool,
     ) -> Result<Self> {
         let kube_client = create_k8s_client().await;
 
@@ -98,10 +109,12 @@ impl K8sSwarm {
             fullnodes,
             root_account,
             kube_client,
-            chain_id: ChainId::new(4),
+            chain_id,
             versions: Arc::new(versions),
             kube_namespace: kube_namespace.to_string(),
             keep,
+            use_port_forward,
+            enable_haproxy,
             chaoses: HashSet::new(),
             prom_client,
         })
@@ -116,6 +129,16 @@ impl K8sSwarm {
             .to_string()
     }
 
+    /// The pod of each node, each StatefulSet runs a single replica. A fullnode shares its peer id with its
+    /// validator, which takes precedence
+    fn pod_names(&self) -> HashMap<PeerId, String> {
+        self.fullnodes
+            .iter()
+            .chain(self.validators.iter())
+            .map(|(peer_id, node)| (*peer_id, format!("{}-0", node.sts_name())))
+            .collect()
+    }
+
     #[allow(dead_code)]
     fn get_kube_client(&self) -> K8sClient {
         self.kube_client.clone()
@@ -193,20 +216,152 @@ impl Swarm for K8sSwarm {
         self.fullnodes.get_mut(&id).map(|v| v as &mut dyn FullNode)
     }
 
-    fn add_validator(&mut self, _version: &Version, _template: NodeConfig) -> Result<PeerId> {
-        todo!()
+    // the config of the new validator is templated by the aptos-node helm chart, like the others
+    fn add_validator(&mut self, version: &Version, _template: NodeConfig) -> Result<PeerId> {
+        let image_tag = self
+            .versions
+            .get(version)
+            .cloned()
+            .ok_or_else(|| anyhow!("Invalid version: {:?}", version))?;
+        let node_id = self
+            .validators
+            .values()
+            .map(|v| v.node_id() + 1)
+            .max()
+            .unwrap_or(0);
+        let validator = Runtime::new().unwrap().block_on(install_new_validator(
+            &self.kube_client,
+            self.kube_namespace.clone(),
+            node_id,
+            image_tag.clone(),
+            self.use_port_forward,
+            self.enable_haproxy,
+        ))?;
+        let peer_id = validator.peer_id();
+        set_validator_image_tag(
+            validator.sts_name().to_string(),
+            image_tag,
+            self.kube_namespace.clone(),
+        )?;
+        self.validators.insert(peer_id, validator);
+        Ok(peer_id)
     }
 
     fn remove_validator(&mut self, _id: PeerId) -> Result<()> {
         todo!()
     }
 
-    fn add_full_node(&mut self, _version: &Version, _template: NodeConfig) -> Result<PeerId> {
-        todo!()
+    // each validator can have a single fullnode, which is added for the first validator without one
+    fn add_full_node(&mut self, version: &Version, _template: NodeConfig) -> Result<PeerId> {
+        let image_tag = self
+            .versions
+            .get(version)
+            .cloned()
+            .ok_or_else(|| anyhow!("Invalid version: {:?}", version))?;
+        let node_id = self
+            .fullnodes
+            .values()
+            .map(|f| f.node_id() + 1)
+            .max()
+            .unwrap_or(0);
+        if !self.validators.values().any(|v| v.node_id() == node_id) {
+            bail!("No validator {} to add a fullnode for", node_id);
+        }
+        let fullnode = Runtime::new().unwrap().block_on(install_new_fullnode(
+            &self.kube_client,
+            self.kube_namespace.clone(),
+            node_id,
+            image_tag,
+            self.use_port_forward,
+            self.enable_haproxy,
+        ))?;
+        let peer_id = fullnode.peer_id();
+        self.fullnodes.insert(peer_id, fullnode);
+        Ok(peer_id)
     }
 
-    fn remove_full_node(&mut self, _id: PeerId) -> Result<()> {
-        todo!()
+    fn remove_full_node(&mut self, id: PeerId) -> Result<()> {
+        let fullnode = self
+            .fullnodes
+            .remove(&id)
+            .ok_or_else(|| anyhow!("Invalid id: {}", id))?;
+        let num_fullnode_groups = self
+            .fullnodes
+            .values()
+            .map(|f| f.node_id() + 1)
+            .max()
+            .unwrap_or(0);
+        if fullnode.node_id() < num_fullnode_groups {
+            // the release can only drop the last fullnodes, so others are scaled down instead
+            scale_stateful_set_replicas(fullnode.sts_name(), &self.kube_namespace, 0)
+        } else {
+            uninstall_fullnodes(num_fullnode_groups, self.kube_namespace.clone())
+        }
+    }
+
+    /// Grows or shrinks the swarm to `num_validators` validator pods, without touching the on-chain validator
+    /// set. New validators run the latest version, and need to join the validator set on-chain to take part in
+    /// consensus. Validators are removed from the highest index down, along with their fullnodes and storage,
+    /// but stay in the validator set, so the remaining validators have to keep a quorum of it. The cluster
+    /// autoscaler provisions or releases the k8s nodes the validators are scheduled on
+    fn scale_validator_nodes(&mut self, num_validators: usize) -> Result<()> {
+        if num_validators == 0 {
+            bail!("The swarm needs at least one validator");
+        }
+        let current_num_validators = self.validators.len();
+        if num_validators > current_num_validators {
+            let version = self
+                .versions
+                .keys()
+                .max()
+                .cloned()
+                .ok_or_else(|| anyhow!("No versions to add validators with"))?;
+            for _ in current_num_validators..num_validators {
+                self.add_validator(&version, NodeConfig::default())?;
+            }
+            return Ok(());
+        }
+
+        // the fullnodes of the removed validators have to go first, the release ties them to the validator
+        let removed_fullnodes = self
+            .fullnodes
+            .iter()
+            .filter(|(_, f)| f.node_id() >= num_validators)
+            .map(|(peer_id, _)| *peer_id)
+            .collect::<Vec<_>>();
+        if !removed_fullnodes.is_empty() {
+            for peer_id in removed_fullnodes {
+                self.fullnodes.remove(&peer_id);
+            }
+            let num_fullnode_groups = self
+                .fullnodes
+                .values()
+                .map(|f| f.node_id() + 1)
+                .max()
+                .unwrap_or(0);
+            uninstall_fullnodes(num_fullnode_groups, self.kube_namespace.clone())?;
+        }
+
+        let removed_validators = self
+            .validators
+            .iter()
+            .filter(|(_, v)| v.node_id() >= num_validators)
+            .map(|(peer_id, _)| *peer_id)
+            .collect::<Vec<_>>();
+        let mut removed_validators = removed_validators
+            .into_iter()
+            .filter_map(|peer_id| self.validators.remove(&peer_id))
+            .collect::<Vec<_>>();
+        Runtime::new().unwrap().block_on(uninstall_validators(
+            &self.kube_client,
+            self.kube_namespace.clone(),
+            num_validators,
+        ))?;
+        // a new validator with the same index must not start from the removed one's db
+        for validator in removed_validators.iter_mut() {
+            validator.clear_storage()?;
+        }
+        Ok(())
     }
 
     fn versions<'a>(&'a self) -> Box<dyn Iterator<Item = Version> + 'a> {
@@ -218,27 +373,70 @@ impl Swarm for K8sSwarm {
         ChainInfo::new(&mut self.root_account, rest_api_url, self.chain_id)
     }
 
-    // returns a kubectl logs command to retrieve the logs manually
-    // and instructions to check the actual live logs location from fgi
+    // dumps the logs of every node to a local directory, which outlives the namespace
     fn logs_location(&mut self) -> String {
-        "See fgi output for more information.".to_string()
+        let nodes = self
+            .validators
+            .values()
+            .chain(self.fullnodes.values())
+            .collect();
+        match Runtime::new().unwrap().block_on(dump_node_logs(
+            self.kube_client.clone(),
+            &self.kube_namespace,
+            nodes,
+        )) {
+            Ok(logs_dir) => logs_dir.display().to_string(),
+            Err(e) => format!(
+                "Failed to collect logs: {}. See fgi output for more information.",
+                e
+            ),
+        }
+    }
+
+    // dump_node_logs writes each node's logs to a file named after it
+    fn node_logs_locations(&self, logs_location: &str) -> Vec<(String, String)> {
+        self.validators
+            .values()
+            .chain(self.fullnodes.values())
+            .map(|node| {
+                let log_path = Path::new(logs_location).join(format!("{}.log", node.name()));
+                (node.name().to_string(), log_path)
+            })
+            .filter(|(_, log_path)| log_path.exists())
+            .map(|(name, log_path)| (name, log_path.display().to_string()))
+            .collect()
     }
 
     fn inject_chaos(&mut self, chaos: SwarmChaos) -> Result<()> {
-        chaos::inject_swarm_chaos(&self.kube_namespace, &chaos)?;
+        chaos::inject_swarm_chaos(&self.kube_namespace, &chaos, &self.pod_names())?;
         self.chaoses.insert(chaos);
         Ok(())
     }
 
     fn remove_chaos(&mut self, chaos: SwarmChaos) -> Result<()> {
         if self.chaoses.remove(&chaos) {
-            chaos::remove_swarm_chaos(&self.kube_namespace, &chaos)?;
+            chaos::remove_swarm_chaos(&self.kube_namespace, &chaos, &self.pod_names())?;
         } else {
             bail!("Chaos {:?} not found", chaos);
         }
         Ok(())
     }
 
+    // the nodes' state lives in persistent volumes, which forge has no way to copy
+    async fn snapshot_state(&mut self, name: &str) -> Result<()> {
+        bail!(
+            "Snapshotting the chain state as {} is not supported by the k8s swarm",
+            name
+        )
+    }
+
+    async fn restore_state(&mut self, name: &str) -> Result<()> {
+        bail!(
+            "Restoring the chain state from {} is not supported by the k8s swarm",
+            name
+        )
+    }
+
     async fn query_metrics(
         &self,
         query: &str,
@@ -288,7 +486,11 @@ impl TryFrom<Service> for KubeService {
 
 async fn list_services(client: K8sClient, kube_namespace: &str) -> Result<Vec<KubeService>> {
     let node_api: Api<Service> = Api::namespaced(client, kube_namespace);
-    let lp = ListParams::default();
+    // only the services of the aptos-node release installed for this namespace, which may be shared
+    let lp = ListParams::default().labels(&format!(
+        "app.kubernetes.io/part-of=aptos-node,forge-namespace={}",
+        kube_namespace
+    ));
     let services = node_api.list(&lp).await?.items;
     services.into_iter().map(KubeService::try_from).collect()
 }
@@ -306,7 +508,8 @@ pub(crate) async fn get_validators(
     use_port_forward: bool,
     enable_haproxy: bool,
 ) -> Result<HashMap<PeerId, K8sNode>> {
-    let services = list_services(client, kube_namespace).await?;
+    let services = list_services(client.clone(), kube_namespace).await?;
+    let peer_ids = get_peer_ids(client, kube_namespace, VALIDATOR_IDENTITY_FILE).await?;
     let service_suffix = if enable_haproxy {
         VALIDATOR_HAPROXY_SERVICE_SUFFIX
     } else {
@@ -326,14 +529,16 @@ pub(crate) async fn get_validators(
                 port = get_free_port();
                 ip = LOCALHOST.to_string();
             }
-            let node_id = parse_node_id(&s.name).expect("error to parse node id");
+            let node_id = parse_node_id(&s.name)?;
+            let peer_id = *peer_ids
+                .get(&node_id)
+                .ok_or_else(|| format_err!("No identity found for validator {}", node_id))?;
             // the base validator name is the same as that of the StatefulSet, and does not have era
             let validator_name = format!("aptos-node-{}-validator", node_id);
             let node = K8sNode {
                 name: validator_name.clone(),
                 sts_name: validator_name,
-                // TODO: fetch this from running node
-                peer_id: PeerId::random(),
+                peer_id,
                 node_id,
                 ip,
                 port: port as u32,
@@ -342,10 +547,12 @@ pub(crate) async fn get_validators(
                 version: Version::new(0, image_tag.to_string()),
                 namespace: kube_namespace.to_string(),
                 enable_haproxy,
+                use_port_forward,
+                port_forward: None,
             };
-            (node.peer_id(), node)
+            Ok((node.peer_id(), node))
         })
-        .collect::<HashMap<_, _>>();
+        .collect::<Result<HashMap<_, _>>>()?;
 
     Ok(validators)
 }
@@ -353,11 +560,13 @@ pub(crate) async fn get_validators(
 pub(crate) async fn get_fullnodes(
     client: K8sClient,
     image_tag: &str,
+    era: &str,
     kube_namespace: &str,
     use_port_forward: bool,
     enable_haproxy: bool,
 ) -> Result<HashMap<PeerId, K8sNode>> {
-    let services = list_services(client, kube_namespace).await?;
+    let services = list_services(client.clone(), kube_namespace).await?;
+    let peer_ids = get_peer_ids(client, kube_namespace, VALIDATOR_FULL_NODE_IDENTITY_FILE).await?;
     let service_suffix = if enable_haproxy {
         FULLNODE_HAPROXY_SERVICE_SUFFIX
     } else {
@@ -377,15 +586,16 @@ pub(crate) async fn get_fullnodes(
                 port = get_free_port();
                 ip = LOCALHOST.to_string();
             }
-            let node_id = parse_node_id(&s.name).expect("error to parse node id");
-            // the base fullnode name is the same as that of the StatefulSet
-            // TODO: get the era and fullnode group, for now ignore it
-            let fullnode_name = format!("aptos-node-{}-fullnode", node_id);
+            let node_id = parse_node_id(&s.name)?;
+            let peer_id = *peer_ids
+                .get(&node_id)
+                .ok_or_else(|| format_err!("No identity found for fullnode {}", node_id))?;
+            // the StatefulSet name has the fullnode group and era, forge only uses the one group
+            let fullnode_name = format!("aptos-node-{}-{}", node_id, FULLNODE_SERVICE_SUFFIX);
             let node = K8sNode {
                 name: fullnode_name.clone(),
-                sts_name: fullnode_name,
-                // TODO: fetch this from running node
-                peer_id: PeerId::random(),
+                sts_name: format!("{}-e{}", fullnode_name, era),
+                peer_id,
                 node_id,
                 ip,
                 port: port as u32,
@@ -394,16 +604,49 @@ pub(crate) async fn get_fullnodes(
                 version: Version::new(0, image_tag.to_string()),
                 namespace: kube_namespace.to_string(),
                 enable_haproxy,
+                use_port_forward,
+                port_forward: None,
             };
-            (node.peer_id(), node)
+            Ok((node.peer_id(), node))
         })
-        .collect::<HashMap<_, _>>();
+        .collect::<Result<HashMap<_, _>>>()?;
 
     Ok(fullnodes)
 }
 
+/// Reads the peer id of each node from the identities in the genesis secrets, keyed by node index. A fullnode's
+/// identity carries the account address of its validator, which is also its peer id
+async fn get_peer_ids(
+    client: K8sClient,
+    kube_namespace: &str,
+    identity_file: &str,
+) -> Result<HashMap<usize, PeerId>> {
+    let secrets_api: Api<Secret> = Api::namespaced(client, kube_namespace);
+    let secrets = secrets_api.list(&ListParams::default()).await?.items;
+    let mut peer_ids = HashMap::new();
+    for secret in secrets {
+        let secret_name = secret.name();
+        if !secret_name.contains("-genesis-e") {
+            continue;
+        }
+        let node_id = parse_node_id(&secret_name)?;
+        let identity = secret
+            .data
+            .as_ref()
+            .and_then(|data| data.get(identity_file))
+            .ok_or_else(|| format_err!("No {} in secret {}", identity_file, secret_name))?;
+        let identity = IdentityBlob::parse(str::from_utf8(&identity.0)?)?;
+        let peer_id = identity
+            .account_address
+            .unwrap_or_else(|| from_identity_public_key(identity.network_private_key.public_key()));
+        peer_ids.insert(node_id, peer_id);
+    }
+    Ok(peer_ids)
+}
+
 // gets the node index based on its associated LB service name
 // assumes the input is named <RELEASE>-aptos-node-<INDEX>-<validator|fullnode>[-lb]
+// genesis secrets, named aptos-node-<INDEX>-genesis-e<ERA>, are parsed the same way
 fn parse_node_id(s: &str) -> Result<usize> {
     // first get rid of the prefixes
     let v = s.split("aptos-node-").collect::<Vec<&str>>();
@@ -412,7 +655,7 @@ fn parse_node_id(s: &str) -> Result<usize> {
     }
     // then get rid of the service name suffix
     let v = v[1].split('-').collect::<Vec<&str>>();
-    let idx: usize = v[0].parse().unwrap();
+    let idx: usize = v[0].parse()?;
     Ok(idx)
 }
 
@@ -437,6 +680,8 @@ pub async fn nodes_healthcheck(nodes: Vec<&K8sNode>) -> Result<Vec<String>> {
                         // ensure a threshold liveness for each node
                         // we want to guarantee node is making progress without spinning too long
                         if version > 100 {
+                            node.rest_api_health_check(MAX_HEALTHY_LEDGER_STALENESS)
+                                .await?;
                             info!("Node {} healthy @ version {} > 100", node.name(), version);
                             return Ok(());
                         }
@@ -464,6 +709,33 @@ pub async fn nodes_healthcheck(nodes: Vec<&K8sNode>) -> Result<Vec<String>> {
     Ok(unhealthy_nodes)
 }
 
+/// Writes the logs of each node's pod to `<node name>.log` in a new directory, and returns the directory
+async fn dump_node_logs(
+    client: K8sClient,
+    kube_namespace: &str,
+    nodes: Vec<&K8sNode>,
+) -> Result<PathBuf> {
+    let pods_api: Api<Pod> = Api::namespaced(client, kube_namespace);
+    let logs_dir = tempfile::Builder::new()
+        .prefix(&format!("{}-logs-", kube_namespace))
+        .tempdir()?
+        .into_path();
+    for node in nodes {
+        // each StatefulSet runs a single replica
+        let pod_name = format!("{}-0", node.sts_name());
+        let logs = match pods_api.logs(&pod_name, &LogParams::default()).await {
+            Ok(logs) => logs,
+            Err(e) => {
+                warn!("Failed to get logs of pod {}: {}", pod_name, e);
+                continue;
+            }
+        };
+        fs::write(logs_dir.join(format!("{}.log", node.name())), logs)?;
+    }
+    info!("Wrote node logs to {}", logs_dir.display());
+    Ok(logs_dir)
+}
+
 impl Drop for K8sSwarm {
     fn drop(&mut self) {
         let runtime = Runtime::new().unwrap();
@@ -0,0 +1,169 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{Result, SwarmChaos};
+use anyhow::{bail, ensure, format_err};
+use std::{collections::HashSet, mem, process::Command};
+
+const TC_BIN: &str = "tc";
+const LOOPBACK: &str = "lo";
+
+/// Network chaos applied to the validators of a local swarm with tc/netem. The nodes all talk over
+/// the loopback interface, so the traffic between validators is picked out by the ports they listen
+/// on for the validator network, and shaped by a single netem qdisc. This needs CAP_NET_ADMIN.
+///
+/// A node's outgoing connections use ephemeral ports, so the traffic of a single node can't be told
+/// apart from the others: only chaos applying to every validator is supported.
+#[derive(Debug, Default)]
+pub struct LocalChaos {
+    active: HashSet<SwarmChaos>,
+}
+
+impl LocalChaos {
+    pub fn inject(&mut self, chaos: SwarmChaos, ports: &[u16]) -> Result<()> {
+        match &chaos {
+            SwarmChaos::Delay(_) | SwarmChaos::Loss(_) | SwarmChaos::Bandwidth(_) => {}
+            _ => bail!(
+                "Chaos {:?} targets a subset of the nodes, which the local swarm can't tell apart",
+                chaos
+            ),
+        }
+        ensure!(
+            !self
+                .active
+                .iter()
+                .any(|active| mem::discriminant(active) == mem::discriminant(&chaos)),
+            "Chaos of the same kind as {:?} is already injected",
+            chaos
+        );
+        self.active.insert(chaos.clone());
+        if let Err(e) = self.apply(ports) {
+            // leave the interface as it was before
+            self.active.remove(&chaos);
+            let _ = self.apply(ports);
+            return Err(e);
+        }
+        Ok(())
+    }
+
+    pub fn remove(&mut self, chaos: SwarmChaos, ports: &[u16]) -> Result<()> {
+        if self.active.remove(&chaos) {
+            self.apply(ports)?;
+        }
+        Ok(())
+    }
+
+    /// Removes all the chaos, e.g. when the swarm goes away
+    pub fn clear(&mut self) -> Result<()> {
+        if self.active.is_empty() {
+            return Ok(());
+        }
+        self.active.clear();
+        self.apply(&[])
+    }
+
+    /// Rebuilds the qdiscs of the loopback interface for the active chaos. Traffic to and from the
+    /// validator network ports goes to a netem band, everything else keeps the default priomap
+    fn apply(&self, ports: &[u16]) -> Result<()> {
+        // there is nothing to remove if no chaos was applied yet
+        let _ = run_tc(&["qdisc", "del", "dev", LOOPBACK, "root"]);
+        if self.active.is_empty() {
+            return Ok(());
+        }
+
+        run_tc(&[
+            "qdisc", "add", "dev", LOOPBACK, "root", "handle", "1:", "prio", "bands", "4",
+        ])?;
+        let mut netem = vec![
+            "qdisc".to_string(),
+            "add".to_string(),
+            "dev".to_string(),
+            LOOPBACK.to_string(),
+            "parent".to_string(),
+            "1:4".to_string(),
+            "handle".to_string(),
+            "40:".to_string(),
+            "netem".to_string(),
+        ];
+        let mut bandwidth = None;
+        for chaos in &self.active {
+            match chaos {
+                SwarmChaos::Delay(delay) => netem.extend([
+                    "delay".to_string(),
+                    format!("{}ms", delay.latency_ms),
+                    format!("{}ms", delay.jitter_ms),
+                    format!("{}%", delay.correlation_percentage),
+                ]),
+                SwarmChaos::Loss(loss) => netem.extend([
+                    "loss".to_string(),
+                    format!("{}%", loss.loss_percentage),
+                    format!("{}%", loss.correlation_percentage),
+                ]),
+                SwarmChaos::Bandwidth(swarm_bandwidth) => bandwidth = Some(swarm_bandwidth),
+                _ => unreachable!("only swarm wide chaos is injected"),
+            }
+        }
+        run_tc(&netem.iter().map(String::as_str).collect::<Vec<_>>())?;
+        // all the validators share the interface, so they share the bandwidth too
+        if let Some(bandwidth) = bandwidth {
+            run_tc(&[
+                "qdisc",
+                "add",
+                "dev",
+                LOOPBACK,
+                "parent",
+                "40:1",
+                "handle",
+                "41:",
+                "tbf",
+                "rate",
+                &format!("{}mbit", bandwidth.rate),
+                "burst",
+                &bandwidth.buffer.to_string(),
+                "limit",
+                &bandwidth.limit.to_string(),
+            ])?;
+        }
+
+        for port in ports {
+            for direction in ["sport", "dport"] {
+                run_tc(&[
+                    "filter",
+                    "add",
+                    "dev",
+                    LOOPBACK,
+                    "parent",
+                    "1:",
+                    "protocol",
+                    "ip",
+                    "prio",
+                    "1",
+                    "u32",
+                    "match",
+                    "ip",
+                    direction,
+                    &port.to_string(),
+                    "0xffff",
+                    "flowid",
+                    "1:4",
+                ])?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn run_tc(args: &[&str]) -> Result<()> {
+    let output = Command::new(TC_BIN)
+        .args(args)
+        .output()
+        .map_err(|e| format_err!("Failed to run {} {}: {}", TC_BIN, args.join(" "), e))?;
+    ensure!(
+        output.status.success(),
+        "{} {} failed: {}",
+        TC_BIN,
+        args.join(" "),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    Ok(())
+}
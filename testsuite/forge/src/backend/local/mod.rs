@@ -1,7 +1,7 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{Factory, GenesisConfig, Result, Swarm, Version};
+use crate::{ChainConfig, Factory, GenesisConfig, NodeResourcesConfig, Result, Swarm, Version};
 use anyhow::{bail, Context};
 use aptos_genesis::builder::InitConfigFn;
 use rand::rngs::StdRng;
@@ -13,26 +13,48 @@ use std::{
 };
 
 mod cargo;
+mod chaos;
 mod node;
 mod swarm;
 pub use node::LocalNode;
 pub use swarm::{LocalSwarm, LocalSwarmBuilder, SwarmDirectory};
 
+// where the aptos-node binary lives in the validator image
+const DOCKER_APTOS_NODE_BIN: &str = "/opt/aptos/bin/aptos-node";
+
 #[derive(Clone, Debug)]
 pub struct LocalVersion {
     bin: PathBuf,
     version: Version,
+    // the docker image providing the binary, which is run in a container rather than directly
+    image: Option<String>,
 }
 
 impl LocalVersion {
     pub fn new(bin: PathBuf, version: Version) -> Self {
-        Self { bin, version }
+        Self {
+            bin,
+            version,
+            image: None,
+        }
+    }
+
+    pub fn from_docker_image(image: String, version: Version) -> Self {
+        Self {
+            bin: PathBuf::from(DOCKER_APTOS_NODE_BIN),
+            version,
+            image: Some(image),
+        }
     }
 
     pub fn bin(&self) -> &Path {
         &self.bin
     }
 
+    pub fn image(&self) -> Option<&str> {
+        self.image.as_deref()
+    }
+
     pub fn version(&self) -> Version {
         self.version.clone()
     }
@@ -53,7 +75,7 @@ impl LocalFactory {
         let mut versions = HashMap::new();
         let new_version = cargo::get_aptos_node_binary_from_worktree().map(|(revision, bin)| {
             let version = Version::new(usize::max_value(), revision);
-            LocalVersion { bin, version }
+            LocalVersion::new(bin, version)
         })?;
 
         versions.insert(new_version.version.clone(), new_version);
@@ -65,7 +87,7 @@ impl LocalFactory {
         let new_version =
             cargo::get_aptos_node_binary_at_revision(revision).map(|(revision, bin)| {
                 let version = Version::new(usize::max_value(), revision);
-                LocalVersion { bin, version }
+                LocalVersion::new(bin, version)
             })?;
 
         versions.insert(new_version.version.clone(), new_version);
@@ -75,12 +97,12 @@ impl LocalFactory {
     pub fn with_revision_and_workspace(revision: &str) -> Result<Self> {
         let workspace = cargo::get_aptos_node_binary_from_worktree().map(|(revision, bin)| {
             let version = Version::new(usize::max_value(), revision);
-            LocalVersion { bin, version }
+            LocalVersion::new(bin, version)
         })?;
         let revision =
             cargo::get_aptos_node_binary_at_revision(revision).map(|(revision, bin)| {
                 let version = Version::new(usize::min_value(), revision);
-                LocalVersion { bin, version }
+                LocalVersion::new(bin, version)
             })?;
 
         let mut versions = HashMap::new();
@@ -162,7 +184,12 @@ impl Factory for LocalFactory {
         version: &Version,
         _genesis_version: &Version,
         genesis_config: Option<&GenesisConfig>,
+        _node_resources: &NodeResourcesConfig,
+        chain_config: &ChainConfig,
     ) -> Result<Box<dyn Swarm>> {
+        if chain_config.chain_id.is_some() || chain_config.root_key.is_some() {
+            bail!("local forge backend creates its own chain, and does not support a chain id or root key");
+        }
         let genesis_modules = match genesis_config {
             Some(config) => match config {
                 GenesisConfig::Bytes(bytes) => Some(bytes.clone()),
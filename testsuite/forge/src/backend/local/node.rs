@@ -9,7 +9,7 @@ use aptos_sdk::types::{account_address::AccountAddress, PeerId};
 use std::{
     env,
     fs::{self, OpenOptions},
-    path::PathBuf,
+    path::{Path, PathBuf},
     process::{Child, Command},
     str::FromStr,
 };
@@ -35,6 +35,8 @@ impl Drop for Process {
     }
 }
 
+const DOCKER_BIN: &str = "docker";
+
 #[derive(Debug)]
 pub struct LocalNode {
     version: LocalVersion,
@@ -91,11 +93,17 @@ impl LocalNode {
             .open(self.log_path())?;
 
         // Start node process
-        let mut node_command = Command::new(self.version.bin());
-        node_command
-            .current_dir(&self.directory)
-            .arg("-f")
-            .arg(self.config_path());
+        let mut node_command = match self.version.image() {
+            Some(image) => self.docker_command(image),
+            None => {
+                let mut node_command = Command::new(self.version.bin());
+                node_command
+                    .current_dir(&self.directory)
+                    .arg("-f")
+                    .arg(self.config_path());
+                node_command
+            }
+        };
         if env::var("RUST_LOG").is_err() {
             // Only set our RUST_LOG if its not present in environment
             node_command.env("RUST_LOG", "debug");
@@ -115,6 +123,51 @@ impl LocalNode {
 
     pub fn stop(&mut self) {
         self.process = None;
+        // killing the docker client leaves the container running
+        if self.version.image().is_some() {
+            let _ = Command::new(DOCKER_BIN)
+                .args(["rm", "--force", &self.container_name()])
+                .output();
+        }
+    }
+
+    /// Runs the node in a container on the host's network, so the addresses in its config work as they are.
+    /// The node's directory is mounted at the same path, since its config refers to files in it
+    fn docker_command(&self, image: &str) -> Command {
+        let directory = self.directory.display().to_string();
+        let mut node_command = Command::new(DOCKER_BIN);
+        node_command
+            .args([
+                "run",
+                "--rm",
+                "--name",
+                &self.container_name(),
+                "--network",
+                "host",
+                "--volume",
+                &format!("{0}:{0}", directory),
+                "--workdir",
+                &directory,
+                // passed on from the docker client's environment
+                "--env",
+                "RUST_LOG",
+                image,
+            ])
+            .arg(self.version.bin())
+            .arg("-f")
+            .arg(self.config_path());
+        node_command
+    }
+
+    // node directories are unique, unlike node names across swarms
+    fn container_name(&self) -> String {
+        format!(
+            "forge{}",
+            self.directory
+                .display()
+                .to_string()
+                .replace(|c: char| !c.is_ascii_alphanumeric(), "-")
+        )
     }
 
     pub fn port(&self) -> u16 {
@@ -143,6 +196,62 @@ impl LocalNode {
         fs::read_to_string(self.log_path()).map_err(Into::into)
     }
 
+    /// Copies the node's state (its db and secure storage) to `snapshot_dir`. The node has to be stopped so the copy is
+    /// consistent
+    pub fn snapshot_state(&self, snapshot_dir: &Path) -> Result<()> {
+        ensure!(
+            self.process.is_none(),
+            "node {} has to be stopped to snapshot its state",
+            self.name
+        );
+        fs::create_dir_all(snapshot_dir)?;
+        for entry in self.state_entries()? {
+            copy_all(&entry, &snapshot_dir.join(entry.file_name().unwrap()))?;
+        }
+        Ok(())
+    }
+
+    /// Replaces the node's state with the one copied to `snapshot_dir` by `snapshot_state`
+    pub fn restore_state(&mut self, snapshot_dir: &Path) -> Result<()> {
+        ensure!(
+            self.process.is_none(),
+            "node {} has to be stopped to restore its state",
+            self.name
+        );
+        ensure!(
+            snapshot_dir.is_dir(),
+            "no snapshot of node {} at {:?}",
+            self.name,
+            snapshot_dir
+        );
+        for entry in self.state_entries()? {
+            if entry.is_dir() {
+                fs::remove_dir_all(&entry)?;
+            } else {
+                fs::remove_file(&entry)?;
+            }
+        }
+        for entry in fs::read_dir(snapshot_dir)? {
+            let entry = entry?;
+            copy_all(&entry.path(), &self.directory.join(entry.file_name()))?;
+        }
+        Ok(())
+    }
+
+    /// Everything in the node's directory except its config and log, which are kept across snapshots
+    fn state_entries(&self) -> Result<Vec<PathBuf>> {
+        let config_path = self.config_path();
+        let log_path = self.log_path();
+        let mut entries = vec![];
+        for entry in fs::read_dir(&self.directory)? {
+            let path = entry?.path();
+            if path != config_path && path != log_path {
+                entries.push(path);
+            }
+        }
+        Ok(entries)
+    }
+
     pub async fn health_check(&mut self) -> Result<(), HealthCheckError> {
         debug!("Health check on node '{}'", self.name);
 
@@ -181,6 +290,25 @@ impl LocalNode {
     }
 }
 
+fn copy_all(src: &Path, dst: &Path) -> Result<()> {
+    if src.is_dir() {
+        fs::create_dir_all(dst)?;
+        for entry in fs::read_dir(src)? {
+            let entry = entry?;
+            copy_all(&entry.path(), &dst.join(entry.file_name()))?;
+        }
+    } else {
+        fs::copy(src, dst).with_context(|| format!("Failed to copy {:?} to {:?}", src, dst))?;
+    }
+    Ok(())
+}
+
+impl Drop for LocalNode {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
 #[async_trait::async_trait]
 impl Node for LocalNode {
     fn peer_id(&self) -> PeerId {
@@ -213,6 +341,16 @@ impl Node for LocalNode {
         self.config()
     }
 
+    async fn update_config(
+        &mut self,
+        update: Box<dyn FnOnce(&mut NodeConfig) + Send>,
+    ) -> Result<()> {
+        update(&mut self.config);
+        let config_path = self.config_path();
+        self.config.save(config_path)?;
+        self.restart().await
+    }
+
     async fn start(&mut self) -> Result<()> {
         self.start()
     }
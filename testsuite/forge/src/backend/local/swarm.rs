@@ -1,6 +1,7 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
+use super::chaos::LocalChaos;
 use crate::{
     ChainInfo, FullNode, HealthCheckError, LocalNode, LocalVersion, Node, NodeExt, Swarm,
     SwarmChaos, SwarmExt, Validator, Version,
@@ -208,6 +209,7 @@ impl LocalSwarmBuilder {
             root_account,
             chain_id: ChainId::test(),
             root_key,
+            chaos: LocalChaos::default(),
             launched: false,
         })
     }
@@ -225,6 +227,7 @@ pub struct LocalSwarm {
     root_account: LocalAccount,
     chain_id: ChainId,
     root_key: ConfigKey<Ed25519PrivateKey>,
+    chaos: LocalChaos,
 
     launched: bool,
 }
@@ -402,6 +405,15 @@ impl LocalSwarm {
         self.validators.values_mut()
     }
 
+    /// The ports the validators listen on for each other, which chaos is applied to
+    fn validator_network_ports(&self) -> Vec<u16> {
+        self.validators
+            .values()
+            .filter_map(|v| v.config().validator_network.as_ref())
+            .filter_map(|network| network.listen_address.find_port())
+            .collect()
+    }
+
     pub fn fullnode(&self, peer_id: PeerId) -> Option<&LocalNode> {
         self.fullnodes.get(&peer_id)
     }
@@ -413,10 +425,24 @@ impl LocalSwarm {
     pub fn dir(&self) -> &Path {
         self.dir.as_ref()
     }
+
+    fn snapshot_dir(&self, name: &str) -> PathBuf {
+        self.dir.join("snapshots").join(name)
+    }
+
+    fn nodes_mut(&mut self) -> impl Iterator<Item = &mut LocalNode> {
+        self.validators
+            .values_mut()
+            .chain(self.fullnodes.values_mut())
+    }
 }
 
 impl Drop for LocalSwarm {
     fn drop(&mut self) {
+        // chaos is applied to the host's loopback interface, so it would outlive the swarm
+        if let Err(e) = self.chaos.clear() {
+            eprintln!("Failed to remove the swarm's chaos: {}", e);
+        }
         // If panicking, persist logs
         if std::thread::panicking() {
             eprintln!("Logs located at {}", self.logs_location());
@@ -505,6 +531,13 @@ impl Swarm for LocalSwarm {
         Ok(())
     }
 
+    fn scale_validator_nodes(&mut self, num_validators: usize) -> Result<()> {
+        bail!(
+            "Scaling to {} validator nodes is not supported by the local swarm",
+            num_validators
+        )
+    }
+
     fn versions<'a>(&'a self) -> Box<dyn Iterator<Item = Version> + 'a> {
         Box::new(self.versions.keys().cloned())
     }
@@ -526,12 +559,81 @@ impl Swarm for LocalSwarm {
         self.dir.display().to_string()
     }
 
-    fn inject_chaos(&mut self, _chaos: SwarmChaos) -> Result<()> {
-        todo!()
+    // every node logs to a file in its own directory, which logs_location keeps around
+    fn node_logs_locations(&self, _logs_location: &str) -> Vec<(String, String)> {
+        self.validators
+            .values()
+            .chain(self.fullnodes.values())
+            .filter(|node| node.log_path().exists())
+            .map(|node| {
+                (
+                    node.name().to_string(),
+                    node.log_path().display().to_string(),
+                )
+            })
+            .collect()
     }
 
-    fn remove_chaos(&mut self, _chaos: SwarmChaos) -> Result<()> {
-        todo!()
+    fn inject_chaos(&mut self, chaos: SwarmChaos) -> Result<()> {
+        let ports = self.validator_network_ports();
+        self.chaos.inject(chaos, &ports)
+    }
+
+    fn remove_chaos(&mut self, chaos: SwarmChaos) -> Result<()> {
+        let ports = self.validator_network_ports();
+        self.chaos.remove(chaos, &ports)
+    }
+
+    async fn snapshot_state(&mut self, name: &str) -> Result<()> {
+        let snapshot_dir = self.snapshot_dir(name);
+        if snapshot_dir.exists() {
+            fs::remove_dir_all(&snapshot_dir)?;
+        }
+
+        // All the nodes are stopped at once, so their snapshots are of the same ledger version
+        for node in self.nodes_mut() {
+            node.stop();
+        }
+        for node in self.nodes_mut() {
+            node.snapshot_state(&snapshot_dir.join(node.name()))?;
+        }
+        for node in self.nodes_mut() {
+            node.start()?;
+        }
+
+        self.wait_all_alive(Duration::from_secs(60)).await
+    }
+
+    async fn restore_state(&mut self, name: &str) -> Result<()> {
+        let snapshot_dir = self.snapshot_dir(name);
+        if !snapshot_dir.exists() {
+            bail!("No snapshot named {}", name);
+        }
+
+        for node in self.nodes_mut() {
+            node.stop();
+        }
+        for node in self.nodes_mut() {
+            node.restore_state(&snapshot_dir.join(node.name()))?;
+        }
+        for node in self.nodes_mut() {
+            node.start()?;
+        }
+        self.wait_all_alive(Duration::from_secs(60)).await?;
+
+        // The root account's transactions since the snapshot were rolled back with it
+        let root_address = self.root_account.address();
+        let account = self
+            .validators
+            .values()
+            .next()
+            .ok_or_else(|| anyhow!("Swarm has no validators"))?
+            .rest_client()
+            .get_account(root_address)
+            .await?
+            .into_inner();
+        *self.root_account.sequence_number_mut() = account.sequence_number;
+        Ok(())
     }
 
     async fn query_metrics(
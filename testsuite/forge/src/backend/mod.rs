@@ -4,5 +4,8 @@
 mod local;
 pub use local::{LocalNode, *};
 
+mod docker;
+pub use docker::*;
+
 mod k8s;
 pub use k8s::{K8sNode, *};
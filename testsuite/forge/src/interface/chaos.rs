@@ -1,11 +1,17 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
+use aptos_sdk::types::PeerId;
+
 #[derive(Eq, Hash, PartialEq, Debug, Clone)]
 pub enum SwarmChaos {
     Delay(SwarmNetworkDelay),
     Partition(SwarmNetworkPartition),
     Bandwidth(SwarmNetworkBandwidth),
+    Loss(SwarmNetworkLoss),
+    NodePartition(SwarmNetworkNodePartition),
+    NodeLoss(SwarmNetworkNodeLoss),
+    LinkDelay(SwarmNetworkLinkDelay),
 }
 
 #[derive(Eq, Hash, PartialEq, Debug, Clone)]
@@ -32,6 +38,40 @@ pub struct SwarmNetworkBandwidth {
     pub buffer: u64,
 }
 
+#[derive(Eq, Hash, PartialEq, Debug, Clone)]
+pub struct SwarmNetworkLoss {
+    pub loss_percentage: u64,
+    pub correlation_percentage: u64,
+}
+
+/// Cuts the network between two groups of nodes, in both directions
+#[derive(Eq, Hash, PartialEq, Debug, Clone)]
+pub struct SwarmNetworkNodePartition {
+    pub source_nodes: Vec<PeerId>,
+    pub target_nodes: Vec<PeerId>,
+}
+
+/// Drops packets sent by the given nodes only, rather than by every validator
+#[derive(Eq, Hash, PartialEq, Debug, Clone)]
+pub struct SwarmNetworkNodeLoss {
+    pub nodes: Vec<PeerId>,
+    pub loss_percentage: u64,
+    pub correlation_percentage: u64,
+}
+
+/// Delays the traffic sent by one group of nodes to another, and optionally caps its bandwidth,
+/// e.g. to model the link between two regions. The traffic back is only affected by the link in
+/// the other direction, so links can be asymmetric
+#[derive(Eq, Hash, PartialEq, Debug, Clone)]
+pub struct SwarmNetworkLinkDelay {
+    pub source_nodes: Vec<PeerId>,
+    pub target_nodes: Vec<PeerId>,
+    pub latency_ms: u64,
+    pub jitter_ms: u64,
+    pub correlation_percentage: u64,
+    pub bandwidth: Option<SwarmNetworkBandwidth>,
+}
+
 #[derive(Eq, Hash, PartialEq, Debug, Clone)]
 pub struct NodeNetworkDelay {
     pub latency_ms: u64,
@@ -1,7 +1,7 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
-use super::{GenesisConfig, Swarm, Version};
+use super::{ChainConfig, GenesisConfig, NodeResourcesConfig, Swarm, Version};
 use crate::Result;
 use rand::rngs::StdRng;
 use std::num::NonZeroUsize;
@@ -18,5 +18,7 @@ pub trait Factory {
         version: &Version,
         genesis_version: &Version,
         genesis_modules: Option<&GenesisConfig>,
+        node_resources: &NodeResourcesConfig,
+        chain_config: &ChainConfig,
     ) -> Result<Box<dyn Swarm>>;
 }
@@ -0,0 +1,39 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use std::collections::HashMap;
+
+/// A way a faulty Validator misbehaves. Faults are injected through the failpoints of the Validator's
+/// config, so they need a node binary built with the `failpoints` feature. A faulty image can be run
+/// with `Swarm::upgrade_validator` instead
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ValidatorFault {
+    /// Never broadcasts its proposals, so its rounds as leader time out
+    SilentLeader,
+    /// Stays connected but sends no consensus messages at all
+    Silent,
+    /// Arbitrary failpoints, keyed by failpoint name with the action to take, e.g. `return`
+    Failpoints(HashMap<String, String>),
+}
+
+impl ValidatorFault {
+    pub fn failpoints(&self) -> HashMap<String, String> {
+        let failpoints: &[&str] = match self {
+            ValidatorFault::SilentLeader => &["consensus::send::broadcast_proposal"],
+            ValidatorFault::Silent => &[
+                "consensus::send::broadcast_proposal",
+                "consensus::send::broadcast_sync_info",
+                "consensus::send::broadcast_timeout_vote",
+                "consensus::send::broadcast_commit_vote",
+                "consensus::send::vote",
+                "consensus::send::proposal",
+                "consensus::send::commit_proof",
+            ],
+            ValidatorFault::Failpoints(failpoints) => return failpoints.clone(),
+        };
+        failpoints
+            .iter()
+            .map(|failpoint| (failpoint.to_string(), "return".to_string()))
+            .collect()
+    }
+}
@@ -0,0 +1,96 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use std::{fmt, time::Duration};
+
+/// The percentiles reported for every stage of a transaction's latency
+pub const LATENCY_PERCENTILES: [u64; 3] = [50, 90, 99];
+
+/// A stage of a transaction's way from the client to being committed, as measured by the nodes
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LatencyStage {
+    /// From the API receiving a submission to the transaction being in mempool
+    SubmissionToMempool,
+    /// From the transaction entering mempool to consensus pulling it into a block
+    MempoolToConsensus,
+    /// From a block being proposed to it being committed
+    ConsensusToCommit,
+}
+
+impl LatencyStage {
+    pub const ALL: [LatencyStage; 3] = [
+        LatencyStage::SubmissionToMempool,
+        LatencyStage::MempoolToConsensus,
+        LatencyStage::ConsensusToCommit,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            LatencyStage::SubmissionToMempool => "submission_to_mempool",
+            LatencyStage::MempoolToConsensus => "mempool_to_consensus",
+            LatencyStage::ConsensusToCommit => "consensus_to_commit",
+        }
+    }
+
+    /// The histogram of the stage, in seconds
+    fn histogram(&self) -> &'static str {
+        match self {
+            LatencyStage::SubmissionToMempool => {
+                r#"aptos_api_requests_bucket{operation_id=~"submit_.*_transactions",status="202"}"#
+            }
+            LatencyStage::MempoolToConsensus => {
+                r#"aptos_core_mempool_txn_commit_latency_bucket{stage="get_block"}"#
+            }
+            LatencyStage::ConsensusToCommit => {
+                r#"aptos_consensus_block_tracing_bucket{stage="committed"}"#
+            }
+        }
+    }
+
+    /// The PromQL query of the stage's `percentile` over the last `window`, across all nodes
+    pub fn percentile_query(&self, percentile: u64, window: Duration) -> String {
+        format!(
+            "histogram_quantile({}, sum(rate({}[{}s])) by (le))",
+            percentile as f64 / 100.0,
+            self.histogram(),
+            window.as_secs().max(1)
+        )
+    }
+}
+
+impl fmt::Display for LatencyStage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            LatencyStage::SubmissionToMempool => "submission -> mempool",
+            LatencyStage::MempoolToConsensus => "mempool -> consensus",
+            LatencyStage::ConsensusToCommit => "consensus -> commit",
+        })
+    }
+}
+
+/// The latency of a stage at each of `LATENCY_PERCENTILES`, in milliseconds. A percentile is None
+/// if the stage had no samples in the window
+#[derive(Clone, Debug)]
+pub struct StageLatency {
+    pub stage: LatencyStage,
+    pub percentiles: Vec<(u64, Option<f64>)>,
+}
+
+/// Splits the end to end latency which the emitter measures into the stages seen by the nodes
+#[derive(Clone, Debug, Default)]
+pub struct LatencyBreakdown {
+    pub stages: Vec<StageLatency>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_percentile_query() {
+        assert_eq!(
+            LatencyStage::ConsensusToCommit.percentile_query(99, Duration::from_secs(240)),
+            r#"histogram_quantile(0.99, sum(rate(aptos_consensus_block_tracing_bucket{stage="committed"}[240s])) by (le))"#
+        );
+    }
+}
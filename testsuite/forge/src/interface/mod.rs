@@ -15,10 +15,22 @@ mod swarm;
 pub use swarm::*;
 mod chaos;
 pub use chaos::*;
+mod fault;
+pub use fault::*;
 mod node;
 pub use node::*;
 mod chain_info;
 pub use chain_info::*;
+mod latency;
+pub use latency::*;
+
+use crate::Result;
+use anyhow::Context;
+use aptos_sdk::{
+    crypto::{ed25519::Ed25519PrivateKey, ValidCryptoMaterialStringExt},
+    types::chain_id::ChainId,
+};
+use std::{fs, path::PathBuf};
 
 /// A wrapper around a usize in order to represent an opaque version of a Node.
 ///
@@ -48,3 +60,45 @@ pub enum GenesisConfig {
     Bytes(Vec<Vec<u8>>),
     Path(String),
 }
+
+/// The compute and storage to provision for each node, as Kubernetes quantities, e.g. "3.5" cpus or "6Gi" of
+/// memory. Unset fields keep the backend's defaults, and backends which don't provision their nodes ignore them
+#[derive(Clone, Debug, Default)]
+pub struct NodeResourcesConfig {
+    pub cpu_request: Option<String>,
+    pub cpu_limit: Option<String>,
+    pub memory_request: Option<String>,
+    pub memory_limit: Option<String>,
+    pub storage_class: Option<String>,
+    pub storage_size: Option<String>,
+}
+
+/// The chain a swarm runs, e.g. to target a long-lived test network rather than one created for the test. Unset
+/// fields keep the backend's defaults
+#[derive(Clone, Debug, Default)]
+pub struct ChainConfig {
+    pub chain_id: Option<ChainId>,
+    pub root_key: Option<RootKeyConfig>,
+}
+
+/// The private key of the root account, which mints coins
+#[derive(Clone, Debug)]
+pub enum RootKeyConfig {
+    /// A hex encoded key, with or without a 0x prefix
+    Hex(String),
+    /// A BCS encoded key file, like the mint.key of a test network
+    File(PathBuf),
+}
+
+impl RootKeyConfig {
+    pub fn private_key(&self) -> Result<Ed25519PrivateKey> {
+        match self {
+            RootKeyConfig::Hex(key) => Ok(Ed25519PrivateKey::from_encoded_string(key)?),
+            RootKeyConfig::File(path) => {
+                let bytes = fs::read(path)
+                    .with_context(|| format!("Failed to read root key file {:?}", path))?;
+                Ok(bcs::from_bytes(&bytes)?)
+            }
+        }
+    }
+}
@@ -9,10 +9,13 @@ use aptos_sdk::types::PeerId;
 use inspection_service::inspection_client::InspectionClient;
 use std::{
     collections::HashMap,
-    time::{Duration, Instant},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
 };
 use url::Url;
 
+/// How far behind the wall clock a healthy node's ledger timestamp may be
+pub const MAX_HEALTHY_LEDGER_STALENESS: Duration = Duration::from_secs(60);
+
 #[derive(Debug)]
 pub enum HealthCheckError {
     NotRunning(String),
@@ -28,6 +31,50 @@ impl std::fmt::Display for HealthCheckError {
 
 impl std::error::Error for HealthCheckError {}
 
+/// Well-known metrics of a Node, for tests to assert on what happens inside the nodes rather than only on what
+/// clients observe
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum NodeMetric {
+    /// The number of consensus rounds which timed out since the node started
+    ConsensusTimeouts,
+    /// The latest version committed by consensus
+    ConsensusCommittedVersion,
+    /// The number of transactions in mempool
+    MempoolSize,
+    /// The latest version state sync wrote to storage
+    StateSyncSyncedVersion,
+    /// The number of periodic mempool garbage collections since the node started. It grows on
+    /// every node, loaded or not, so it going down means the node restarted
+    MempoolGcEvents,
+}
+
+impl NodeMetric {
+    pub fn name(&self) -> &'static str {
+        match self {
+            NodeMetric::ConsensusTimeouts => "aptos_consensus_timeout_count",
+            NodeMetric::ConsensusCommittedVersion => "aptos_consensus_last_committed_version",
+            NodeMetric::MempoolSize => "aptos_core_mempool_index_size",
+            NodeMetric::StateSyncSyncedVersion => "aptos_state_sync_version",
+            NodeMetric::MempoolGcEvents => "aptos_core_mempool_gc_event_count",
+        }
+    }
+
+    /// The labels selecting the metric's value
+    pub fn fields(&self) -> HashMap<String, String> {
+        let fields: &[(&str, &str)] = match self {
+            NodeMetric::ConsensusTimeouts | NodeMetric::ConsensusCommittedVersion => &[],
+            // every transaction in mempool is in its system ttl index
+            NodeMetric::MempoolSize => &[("index", "system_ttl")],
+            NodeMetric::StateSyncSyncedVersion => &[("type", "synced")],
+            NodeMetric::MempoolGcEvents => &[("type", "system_ttl")],
+        };
+        fields
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect()
+    }
+}
+
 /// Trait used to represent a running Validator or FullNode
 #[async_trait::async_trait]
 pub trait Node: Send + Sync {
@@ -49,6 +96,12 @@ pub trait Node: Send + Sync {
     /// Return a reference to the Config this Node is using
     fn config(&self) -> &NodeConfig;
 
+    /// Applies `update` to the Config this Node is using, and restarts the Node to pick it up
+    async fn update_config(
+        &mut self,
+        update: Box<dyn FnOnce(&mut NodeConfig) + Send>,
+    ) -> Result<()>;
+
     /// Start this Node.
     /// This should be a noop if the Node is already running.
     async fn start(&mut self) -> Result<()>;
@@ -141,6 +194,29 @@ pub trait NodeExt: Node {
         InspectionClient::from_url(self.inspection_service_endpoint())
     }
 
+    /// Checks that the REST API responds with a ledger timestamp at most `max_staleness` behind the
+    /// wall clock, i.e. the node serves requests and keeps up with the chain. Unlike `health_check`,
+    /// this fails for a node that is still catching up, e.g. right after it was started
+    async fn rest_api_health_check(&self, max_staleness: Duration) -> Result<()> {
+        let state = self
+            .rest_client()
+            .get_ledger_information()
+            .await?
+            .into_inner();
+        let ledger_timestamp = Duration::from_micros(state.timestamp_usecs);
+        let now = SystemTime::now().duration_since(UNIX_EPOCH)?;
+        let staleness = now.checked_sub(ledger_timestamp).unwrap_or_default();
+        if staleness > max_staleness {
+            return Err(anyhow!(
+                "Node {} ledger timestamp is {:?} old at version {}",
+                self.name(),
+                staleness,
+                state.version
+            ));
+        }
+        Ok(())
+    }
+
     /// Restarts this Node by calling Node::Stop followed by Node::Start
     async fn restart(&mut self) -> Result<()> {
         self.stop()?;
@@ -152,6 +228,18 @@ pub trait NodeExt: Node {
         self.inspection_client().get_node_metric(metric_name).await
     }
 
+    /// Scrape all Metrics of this Node, keyed by their name and labels, e.g.
+    /// `aptos_core_mempool_index_size{index=system_ttl}`
+    async fn get_metrics(&self) -> Result<HashMap<String, i64>> {
+        self.inspection_client().get_node_metrics().await
+    }
+
+    /// Query one of the well-known Metrics of this Node, None if the Node doesn't report it (yet)
+    async fn get_node_metric(&self, metric: NodeMetric) -> Result<Option<i64>> {
+        self.get_metric_with_fields(metric.name(), metric.fields())
+            .await
+    }
+
     async fn get_metric_with_fields(
         &self,
         metric_name: &str,
@@ -1,12 +1,15 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{ChainInfo, FullNode, NodeExt, Result, SwarmChaos, Validator, Version};
+use crate::{
+    ChainInfo, FullNode, LatencyBreakdown, LatencyStage, Node, NodeExt, Result, StageLatency,
+    SwarmChaos, Validator, ValidatorFault, Version, LATENCY_PERCENTILES,
+};
 use anyhow::{anyhow, bail};
 use aptos_config::config::NodeConfig;
 use aptos_rest_client::Client as RestClient;
 use aptos_sdk::types::PeerId;
-use futures::future::try_join_all;
+use futures::future::{join_all, try_join_all};
 use prometheus_http_query::response::PromqlResult;
 use std::time::{Duration, Instant};
 use tokio::runtime::Runtime;
@@ -57,6 +60,11 @@ pub trait Swarm: Sync {
     /// Removes the FullNode with the provided PeerId
     fn remove_full_node(&mut self, id: PeerId) -> Result<()>;
 
+    /// Grows or shrinks the Swarm to `num_validators` running Validator nodes. This only changes
+    /// which nodes run: the on-chain validator set is left as it is, so added nodes don't take part
+    /// in consensus until they join it, and removed nodes still count towards its quorum
+    fn scale_validator_nodes(&mut self, num_validators: usize) -> Result<()>;
+
     /// Return a list of supported Versions
     fn versions<'a>(&'a self) -> Box<dyn Iterator<Item = Version> + 'a>;
 
@@ -65,10 +73,27 @@ pub trait Swarm: Sync {
 
     fn logs_location(&mut self) -> String;
 
+    /// The log file of each node, by node name, given what `logs_location` returned. Nodes whose
+    /// logs couldn't be kept are left out
+    fn node_logs_locations(&self, logs_location: &str) -> Vec<(String, String)>;
+
     /// Injects all types of chaos
     fn inject_chaos(&mut self, chaos: SwarmChaos) -> Result<()>;
     fn remove_chaos(&mut self, chaos: SwarmChaos) -> Result<()>;
 
+    /// Snapshots the chain state of every node under `name`, so setup shared by several tests can be
+    /// restored with `restore_state` instead of re-running it. Taking a snapshot with an existing
+    /// name replaces it.
+    ///
+    /// Only the local swarm supports snapshots. The k8s swarm keeps node state in persistent volumes,
+    /// which it has no way to copy, so it returns an error: tests relying on snapshots can only run
+    /// locally
+    async fn snapshot_state(&mut self, name: &str) -> Result<()>;
+
+    /// Restores every node to the chain state snapshotted under `name` and waits for the swarm to
+    /// be alive again. Like `snapshot_state`, this is only supported by the local swarm
+    async fn restore_state(&mut self, name: &str) -> Result<()>;
+
     // Get prometheus metrics from the swarm
     async fn query_metrics(
         &self,
@@ -273,4 +298,184 @@ pub trait SwarmExt: Swarm {
         self.wait_for_all_nodes_to_catchup_to_version(latest_version, deadline)
             .await
     }
+
+    /// Waits for every node to be at most `max_version_delta` versions behind the most advanced
+    /// one. Unlike `wait_for_all_nodes_to_catchup` the target moves along with the chain, so this
+    /// can be used while transactions are still being submitted. The lagging nodes are logged
+    /// every 10 seconds
+    async fn wait_for_all_nodes_to_catchup_within(
+        &self,
+        max_version_delta: u64,
+        deadline: Instant,
+    ) -> Result<()> {
+        const PROGRESS_INTERVAL: Duration = Duration::from_secs(10);
+
+        let nodes = self
+            .validators()
+            .map(|node| (node.name().to_string(), node.rest_client()))
+            .chain(
+                self.full_nodes()
+                    .map(|node| (node.name().to_string(), node.rest_client())),
+            )
+            .collect::<Vec<_>>();
+        if nodes.is_empty() {
+            bail!("no nodes available")
+        }
+
+        let mut last_progress = Instant::now();
+        loop {
+            // an unreachable node counts as being at version 0
+            let versions = join_all(
+                nodes
+                    .iter()
+                    .map(|(_, client)| client.get_ledger_information()),
+            )
+            .await
+            .into_iter()
+            .map(|r| r.map(|r| r.into_inner().version).unwrap_or(0))
+            .collect::<Vec<_>>();
+            let max_version = versions.iter().copied().max().unwrap_or(0);
+            let lagging = nodes
+                .iter()
+                .zip(versions.iter())
+                .filter(|(_, version)| max_version - **version > max_version_delta)
+                .map(|((name, _), version)| format!("{} @ {}", name, version))
+                .collect::<Vec<_>>();
+            if lagging.is_empty() {
+                return Ok(());
+            }
+
+            if Instant::now() > deadline {
+                bail!(
+                    "waiting for nodes to catch up within {} versions of {} timed out, lagging: {:?}",
+                    max_version_delta,
+                    max_version,
+                    lagging
+                );
+            }
+            if last_progress.elapsed() > PROGRESS_INTERVAL {
+                println!(
+                    "Waiting for nodes to catch up within {} versions of {}, lagging: {:?}",
+                    max_version_delta, max_version, lagging
+                );
+                last_progress = Instant::now();
+            }
+
+            tokio::time::sleep(Duration::from_millis(500)).await;
+        }
+    }
+
+    /// Queries the percentiles of each stage of the transactions' latency over the last `window`
+    /// from the nodes' metrics. Fails if the swarm's metrics can't be queried
+    async fn latency_breakdown(&self, window: Duration) -> Result<LatencyBreakdown> {
+        let mut breakdown = LatencyBreakdown::default();
+        for stage in LatencyStage::ALL {
+            let mut percentiles = vec![];
+            for percentile in LATENCY_PERCENTILES {
+                let result = self
+                    .query_metrics(&stage.percentile_query(percentile, window), None, None)
+                    .await?;
+                // a stage without samples yields NaN
+                let seconds = result
+                    .data()
+                    .as_vector()
+                    .and_then(|v| v.first())
+                    .map(|v| v.sample().value())
+                    .filter(|v| v.is_finite());
+                percentiles.push((percentile, seconds.map(|s| s * 1000.0)));
+            }
+            breakdown.stages.push(StageLatency { stage, percentiles });
+        }
+
+        Ok(breakdown)
+    }
+
+    /// Upgrades every Validator which isn't running `version` yet, `batch_size` Validators at a
+    /// time. With `wait_for_healthy`, each batch has to be healthy and the swarm live again before
+    /// the next batch is upgraded
+    fn upgrade_all_validators(
+        &mut self,
+        version: &Version,
+        batch_size: usize,
+        wait_for_healthy: bool,
+    ) -> Result<()> {
+        let validators_to_upgrade = self
+            .validators()
+            .filter(|v| &v.version() != version)
+            .map(|v| v.peer_id())
+            .collect::<Vec<_>>();
+        self.upgrade_validators(
+            &validators_to_upgrade,
+            version,
+            batch_size,
+            wait_for_healthy,
+        )
+    }
+
+    /// Upgrades the given Validators to `version`, `batch_size` Validators at a time, like
+    /// `upgrade_all_validators`
+    fn upgrade_validators(
+        &mut self,
+        validators: &[PeerId],
+        version: &Version,
+        batch_size: usize,
+        wait_for_healthy: bool,
+    ) -> Result<()> {
+        if batch_size == 0 {
+            bail!("batch_size has to be at least 1");
+        }
+
+        let runtime = Runtime::new().unwrap();
+        for batch in validators.chunks(batch_size) {
+            for validator in batch {
+                self.upgrade_validator(*validator, version)?;
+            }
+            if !wait_for_healthy {
+                continue;
+            }
+
+            runtime.block_on(self.health_check())?;
+            let deadline = Instant::now() + Duration::from_secs(60);
+            for validator in batch {
+                runtime.block_on(
+                    self.validator_mut(*validator)
+                        .ok_or_else(|| anyhow!("Invalid id: {}", validator))?
+                        .wait_until_healthy(deadline),
+                )?;
+            }
+            runtime.block_on(self.liveness_check(deadline))?;
+        }
+
+        Ok(())
+    }
+
+    /// Makes the given Validators faulty, restarting them with the fault's failpoints. Any
+    /// failpoints set before are replaced
+    fn inject_validator_fault(&mut self, ids: &[PeerId], fault: &ValidatorFault) -> Result<()> {
+        let runtime = Runtime::new().unwrap();
+        for id in ids {
+            let failpoints = fault.failpoints();
+            runtime.block_on(
+                self.validator_mut(*id)
+                    .ok_or_else(|| anyhow!("Invalid id: {}", id))?
+                    .update_config(Box::new(move |config: &mut NodeConfig| {
+                        config.failpoints = Some(failpoints);
+                    })),
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Restarts the given Validators without failpoints
+    fn remove_validator_fault(&mut self, ids: &[PeerId]) -> Result<()> {
+        let runtime = Runtime::new().unwrap();
+        for id in ids {
+            runtime.block_on(
+                self.validator_mut(*id)
+                    .ok_or_else(|| anyhow!("Invalid id: {}", id))?
+                    .update_config(Box::new(|config: &mut NodeConfig| config.failpoints = None)),
+            )?;
+        }
+        Ok(())
+    }
 }
@@ -24,5 +24,6 @@ pub use github::*;
 
 mod slack;
 pub mod success_criteria;
+pub mod test_results;
 
 pub use slack::*;
@@ -1,8 +1,11 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
+use crate::LatencyBreakdown;
+use anyhow::{bail, format_err, Context};
+use reqwest::Url;
 use serde::Serialize;
-use std::{fmt, time::Duration};
+use std::{fmt, fmt::Write, fs, path::Path, time::Duration};
 use transaction_emitter_lib::emitter::stats::TxnStats;
 
 #[derive(Default, Debug, Serialize)]
@@ -64,6 +67,33 @@ impl TestReport {
         ));
     }
 
+    /// Reports each percentile of every stage as `<stage>_p<percentile>_latency`, in ms
+    pub fn report_latency_breakdown(&mut self, test_name: String, breakdown: &LatencyBreakdown) {
+        let mut stages_text = vec![];
+        for stage in &breakdown.stages {
+            let mut percentiles_text = vec![];
+            for (percentile, latency) in &stage.percentiles {
+                match latency {
+                    Some(latency) => {
+                        self.report_metric(
+                            test_name.clone(),
+                            format!("{}_p{}_latency", stage.stage.name(), percentile),
+                            *latency,
+                        );
+                        percentiles_text.push(format!("{:.1} ms p{}", latency, percentile));
+                    }
+                    None => percentiles_text.push(format!("no samples p{}", percentile)),
+                }
+            }
+            stages_text.push(format!("{}: {}", stage.stage, percentiles_text.join(", ")));
+        }
+        self.report_text(format!(
+            "{} latency breakdown : {}",
+            test_name,
+            stages_text.join("; ")
+        ));
+    }
+
     pub fn print_report(&self) {
         println!("Test Statistics: ");
         println!("{}", self);
@@ -74,6 +104,65 @@ impl TestReport {
             json_report
         );
     }
+
+    /// Writes the report, including every reported metric, as a JSON artifact
+    pub fn write_json(&self, path: &Path) -> anyhow::Result<()> {
+        let json_report = serde_json::to_string_pretty(&self)?;
+        fs::write(path, json_report)
+            .with_context(|| format!("Failed to write the JSON report to {:?}", path))
+    }
+
+    /// The metrics in the Prometheus text format, as `forge_<metric>{test="<test name>"}`
+    pub fn to_prometheus_text(&self) -> String {
+        let mut text = String::new();
+        for metric in &self.metrics {
+            writeln!(
+                text,
+                r#"forge_{}{{test="{}"}} {}"#,
+                sanitize_metric_name(&metric.metric),
+                escape_label_value(&metric.test_name),
+                metric.value
+            )
+            .unwrap();
+        }
+        text
+    }
+
+    /// Pushes the metrics to a Prometheus pushgateway, grouped under the `forge` job and the
+    /// `experiment` and `commit` labels, so runs can be compared over time
+    pub fn push_to_pushgateway(
+        &self,
+        pushgateway_url: &Url,
+        experiment: &str,
+        commit: &str,
+    ) -> anyhow::Result<()> {
+        let url = pushgateway_url.join(&format!(
+            "metrics/job/forge/experiment/{}/commit/{}",
+            experiment, commit
+        ))?;
+        let response = reqwest::blocking::Client::new()
+            .put(url)
+            .body(self.to_prometheus_text())
+            .send()
+            .map_err(|e| format_err!("Failed to push metrics to the pushgateway: {:?}", e))?;
+        if !response.status().is_success() {
+            bail!("Pushgateway returned error code: {}", response.status())
+        }
+        Ok(())
+    }
+}
+
+fn sanitize_metric_name(name: &str) -> String {
+    name.chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect()
+}
+
+fn escape_label_value(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
 }
 
 impl fmt::Display for TestReport {
@@ -81,3 +170,20 @@ impl fmt::Display for TestReport {
         write!(f, "{}", self.text)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prometheus_text() {
+        let mut report = TestReport::new();
+        report.report_metric("network::loss-test:loss", "avg_tps", 1200.0);
+        report.report_metric("say \"hi\"", "p99 latency", 1.5);
+        assert_eq!(
+            report.to_prometheus_text(),
+            "forge_avg_tps{test=\"network::loss-test:loss\"} 1200\n\
+             forge_p99_latency{test=\"say \\\"hi\\\"\"} 1.5\n"
+        );
+    }
+}
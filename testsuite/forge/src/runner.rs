@@ -3,16 +3,22 @@
 
 use crate::*;
 use rand::{Rng, SeedableRng};
+use reqwest::Url;
 use std::{
     io::{self, Write},
     num::NonZeroUsize,
+    path::PathBuf,
     process,
+    time::{Duration, Instant},
 };
 use structopt::{clap::arg_enum, StructOpt};
 use termcolor::{Color, ColorChoice, ColorSpec, StandardStream, WriteColor};
 use tokio::runtime::Runtime;
 // TODO going to remove random seed once cluster deployment supports re-run genesis
-use crate::success_criteria::SuccessCriteria;
+use crate::{
+    success_criteria::SuccessCriteria,
+    test_results::{TestOutcome, TestResults},
+};
 use rand::rngs::OsRng;
 
 #[derive(Debug, StructOpt)]
@@ -63,6 +69,24 @@ pub struct Options {
     /// NO-OP: unsupported option, exists for compatibility with the default test harness
     /// Show captured stdout of successful tests
     show_output: bool,
+    #[structopt(long, parse(from_os_str))]
+    /// Write the results of the run as a JUnit XML report to this path
+    junit_xml: Option<PathBuf>,
+    #[structopt(long, parse(from_os_str))]
+    /// Write the results of the run as a JSON report to this path
+    json_report: Option<PathBuf>,
+    #[structopt(long, parse(from_os_str))]
+    /// Write the metrics and text reported by the tests as a JSON report to this path
+    metrics_report: Option<PathBuf>,
+    #[structopt(long)]
+    /// Push the metrics reported by the tests to the Prometheus pushgateway at this URL
+    pushgateway_url: Option<Url>,
+    #[structopt(long, default_value = "forge")]
+    /// The experiment label of the metrics pushed to the pushgateway
+    experiment: String,
+    #[structopt(long, env = "FORGE_COMMIT", default_value = "unknown")]
+    /// The commit label of the metrics pushed to the pushgateway
+    commit: String,
 }
 
 impl Options {
@@ -129,6 +153,12 @@ pub struct ForgeConfig<'cfg> {
 
     /// The initial genesis modules to use when starting a network
     genesis_config: Option<GenesisConfig>,
+
+    /// The compute and storage to provision for each node when the test harness creates a swarm
+    node_resources: NodeResourcesConfig,
+
+    /// The chain id and root key of the swarm
+    chain_config: ChainConfig,
 }
 
 impl<'cfg> ForgeConfig<'cfg> {
@@ -171,6 +201,16 @@ impl<'cfg> ForgeConfig<'cfg> {
         self
     }
 
+    pub fn with_node_resources(mut self, node_resources: NodeResourcesConfig) -> Self {
+        self.node_resources = node_resources;
+        self
+    }
+
+    pub fn with_chain_config(mut self, chain_config: ChainConfig) -> Self {
+        self.chain_config = chain_config;
+        self
+    }
+
     pub fn number_of_tests(&self) -> usize {
         self.admin_tests.len() + self.network_tests.len() + self.aptos_tests.len()
     }
@@ -193,6 +233,8 @@ impl<'cfg> Default for ForgeConfig<'cfg> {
             initial_validator_count: NonZeroUsize::new(1).unwrap(),
             initial_version: InitialVersion::Newest,
             genesis_config: None,
+            node_resources: NodeResourcesConfig::default(),
+            chain_config: ChainConfig::default(),
         }
     }
 }
@@ -280,6 +322,8 @@ impl<'cfg, F: Factory> Forge<'cfg, F> {
                 &initial_version,
                 &genesis_version,
                 self.tests.genesis_config.as_ref(),
+                &self.tests.node_resources,
+                &self.tests.chain_config,
             ))?;
 
             // Run AptosTests
@@ -289,8 +333,8 @@ impl<'cfg, F: Factory> Forge<'cfg, F> {
                     swarm.chain_info().into_aptos_public_info(),
                     &mut report,
                 );
-                let result = run_test(|| runtime.block_on(test.run(&mut aptos_ctx)));
-                summary.handle_result(test.name().to_owned(), result)?;
+                let (result, duration) = run_test(|| runtime.block_on(test.run(&mut aptos_ctx)));
+                summary.handle_result(test.name().to_owned(), result, duration)?;
             }
 
             // Run AdminTests
@@ -300,8 +344,8 @@ impl<'cfg, F: Factory> Forge<'cfg, F> {
                     swarm.chain_info(),
                     &mut report,
                 );
-                let result = run_test(|| test.run(&mut admin_ctx));
-                summary.handle_result(test.name().to_owned(), result)?;
+                let (result, duration) = run_test(|| test.run(&mut admin_ctx));
+                summary.handle_result(test.name().to_owned(), result, duration)?;
             }
 
             for test in self.filter_tests(self.tests.network_tests.iter()) {
@@ -312,8 +356,8 @@ impl<'cfg, F: Factory> Forge<'cfg, F> {
                     self.global_job_request.clone(),
                     self.success_criteria.clone(),
                 );
-                let result = run_test(|| test.run(&mut network_ctx));
-                summary.handle_result(test.name().to_owned(), result)?;
+                let (result, duration) = run_test(|| test.run(&mut network_ctx));
+                summary.handle_result(test.name().to_owned(), result, duration)?;
             }
 
             report.print_report();
@@ -322,12 +366,34 @@ impl<'cfg, F: Factory> Forge<'cfg, F> {
             io::stderr().flush()?;
 
             if !summary.success() {
+                let logs_location = swarm.logs_location();
                 println!();
-                println!("Swarm logs can be found here: {}", swarm.logs_location());
+                println!("Swarm logs can be found here: {}", logs_location);
+                summary
+                    .results
+                    .set_node_logs(swarm.node_logs_locations(&logs_location));
+                summary.results.set_logs_location(logs_location);
             }
         }
 
         summary.write_summary()?;
+        if let Some(path) = &self.options.junit_xml {
+            summary.results.write_junit_xml(path)?;
+        }
+        if let Some(path) = &self.options.json_report {
+            summary.results.write_json(path)?;
+        }
+        if let Some(path) = &self.options.metrics_report {
+            report.write_json(path)?;
+        }
+        if let Some(url) = &self.options.pushgateway_url {
+            // dashboards missing a run shouldn't fail it
+            if let Err(e) =
+                report.push_to_pushgateway(url, &self.options.experiment, &self.options.commit)
+            {
+                println!("Failed to push the metrics to the pushgateway: {:?}", e);
+            }
+        }
 
         if summary.success() {
             Ok(report)
@@ -366,16 +432,24 @@ impl<'cfg, F: Factory> Forge<'cfg, F> {
 
 enum TestResult {
     Ok,
-    Failed,
+    /// The test panicked, the panic message was already printed by the panic hook
+    Failed(Option<String>),
     FailedWithMsg(String),
 }
 
-fn run_test<F: FnOnce() -> Result<()>>(f: F) -> TestResult {
-    match ::std::panic::catch_unwind(::std::panic::AssertUnwindSafe(f)) {
+fn run_test<F: FnOnce() -> Result<()>>(f: F) -> (TestResult, Duration) {
+    let start = Instant::now();
+    let result = match ::std::panic::catch_unwind(::std::panic::AssertUnwindSafe(f)) {
         Ok(Ok(())) => TestResult::Ok,
         Ok(Err(e)) => TestResult::FailedWithMsg(format!("{:?}", e)),
-        Err(_) => TestResult::Failed,
-    }
+        Err(panic) => TestResult::Failed(
+            panic
+                .downcast_ref::<&str>()
+                .map(|msg| msg.to_string())
+                .or_else(|| panic.downcast_ref::<String>().cloned()),
+        ),
+    };
+    (result, start.elapsed())
 }
 
 struct TestSummary {
@@ -384,6 +458,7 @@ struct TestSummary {
     filtered_out: usize,
     passed: usize,
     failed: Vec<String>,
+    results: TestResults,
 }
 
 impl TestSummary {
@@ -394,26 +469,39 @@ impl TestSummary {
             filtered_out,
             passed: 0,
             failed: Vec::new(),
+            results: TestResults::new("forge".to_string()),
         }
     }
 
-    fn handle_result(&mut self, name: String, result: TestResult) -> io::Result<()> {
+    fn handle_result(
+        &mut self,
+        name: String,
+        result: TestResult,
+        duration: Duration,
+    ) -> io::Result<()> {
         write!(self.stdout, "test {} ... ", name)?;
         match result {
             TestResult::Ok => {
                 self.passed += 1;
                 self.write_ok()?;
+                self.results
+                    .record(name, TestOutcome::Passed, duration, None);
             }
-            TestResult::Failed => {
-                self.failed.push(name);
+            TestResult::Failed(panic_msg) => {
+                self.failed.push(name.clone());
                 self.write_failed()?;
+                let output = panic_msg.map(|msg| format!("test panicked: {}", msg));
+                self.results
+                    .record(name, TestOutcome::Failed, duration, output);
             }
             TestResult::FailedWithMsg(msg) => {
-                self.failed.push(name);
+                self.failed.push(name.clone());
                 self.write_failed()?;
                 writeln!(self.stdout)?;
 
                 write!(self.stdout, "Error: {}", msg)?;
+                self.results
+                    .record(name, TestOutcome::Failed, duration, Some(msg));
             }
         }
         writeln!(self.stdout)?;
@@ -0,0 +1,234 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::Context;
+use serde::Serialize;
+use std::{collections::BTreeMap, fmt::Write, fs, path::Path, time::Duration};
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TestOutcome {
+    Passed,
+    Failed,
+}
+
+/// The outcome of a single forge test, as written to the JUnit XML / JSON artifacts
+#[derive(Debug, Serialize)]
+pub struct TestCaseResult {
+    pub name: String,
+    pub outcome: TestOutcome,
+    pub duration_secs: f64,
+    /// The error the test failed with, or the message it panicked with
+    pub output: Option<String>,
+}
+
+/// Results of every test of a forge run, so CI systems can render them as test results
+#[derive(Debug, Default, Serialize)]
+pub struct TestResults {
+    pub suite: String,
+    pub tests: Vec<TestCaseResult>,
+    /// Where the swarm's logs were dumped, if any test failed
+    pub logs_location: Option<String>,
+    /// The log file of each node, by node name, if any test failed
+    pub node_logs: BTreeMap<String, String>,
+}
+
+impl TestResults {
+    pub fn new(suite: String) -> Self {
+        Self {
+            suite,
+            ..Default::default()
+        }
+    }
+
+    pub fn record(
+        &mut self,
+        name: String,
+        outcome: TestOutcome,
+        duration: Duration,
+        output: Option<String>,
+    ) {
+        self.tests.push(TestCaseResult {
+            name,
+            outcome,
+            duration_secs: duration.as_secs_f64(),
+            output,
+        });
+    }
+
+    pub fn set_logs_location(&mut self, logs_location: String) {
+        self.logs_location = Some(logs_location);
+    }
+
+    pub fn set_node_logs(&mut self, node_logs: impl IntoIterator<Item = (String, String)>) {
+        self.node_logs = node_logs.into_iter().collect();
+    }
+
+    /// Where to find the logs of each node, written to the output of failed tests, as the tests
+    /// themselves don't capture the nodes' output
+    fn node_logs_output(&self) -> Option<String> {
+        if self.node_logs.is_empty() {
+            return None;
+        }
+        let mut output = String::new();
+        for (name, path) in &self.node_logs {
+            writeln!(output, "{}: {}", name, path).unwrap();
+        }
+        Some(output)
+    }
+
+    fn failures(&self) -> usize {
+        self.tests
+            .iter()
+            .filter(|test| test.outcome == TestOutcome::Failed)
+            .count()
+    }
+
+    fn duration_secs(&self) -> f64 {
+        self.tests.iter().map(|test| test.duration_secs).sum()
+    }
+
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string_pretty(self)
+    }
+
+    pub fn to_junit_xml(&self) -> String {
+        let mut xml = String::new();
+        writeln!(xml, r#"<?xml version="1.0" encoding="UTF-8"?>"#).unwrap();
+        writeln!(
+            xml,
+            r#"<testsuites tests="{}" failures="{}" time="{:.3}">"#,
+            self.tests.len(),
+            self.failures(),
+            self.duration_secs()
+        )
+        .unwrap();
+        writeln!(
+            xml,
+            r#"  <testsuite name="{}" tests="{}" failures="{}" errors="0" skipped="0" time="{:.3}">"#,
+            escape_xml(&self.suite),
+            self.tests.len(),
+            self.failures(),
+            self.duration_secs()
+        )
+        .unwrap();
+        if let Some(logs_location) = &self.logs_location {
+            writeln!(xml, "    <properties>").unwrap();
+            writeln!(
+                xml,
+                r#"      <property name="logs_location" value="{}"/>"#,
+                escape_xml(logs_location)
+            )
+            .unwrap();
+            writeln!(xml, "    </properties>").unwrap();
+        }
+        for test in &self.tests {
+            write!(
+                xml,
+                r#"    <testcase name="{}" classname="{}" time="{:.3}""#,
+                escape_xml(&test.name),
+                escape_xml(&self.suite),
+                test.duration_secs
+            )
+            .unwrap();
+            match (test.outcome, &test.output) {
+                (TestOutcome::Passed, None) => writeln!(xml, "/>").unwrap(),
+                (TestOutcome::Passed, Some(output)) => {
+                    writeln!(xml, ">").unwrap();
+                    writeln!(xml, "      <system-out>{}</system-out>", escape_xml(output)).unwrap();
+                    writeln!(xml, "    </testcase>").unwrap();
+                }
+                (TestOutcome::Failed, output) => {
+                    let output = output.as_deref().unwrap_or("test failed");
+                    let message = output.lines().next().unwrap_or_default();
+                    writeln!(xml, ">").unwrap();
+                    writeln!(
+                        xml,
+                        r#"      <failure message="{}">{}</failure>"#,
+                        escape_xml(message),
+                        escape_xml(output)
+                    )
+                    .unwrap();
+                    if let Some(node_logs) = self.node_logs_output() {
+                        writeln!(
+                            xml,
+                            "      <system-out>{}</system-out>",
+                            escape_xml(&node_logs)
+                        )
+                        .unwrap();
+                    }
+                    writeln!(xml, "    </testcase>").unwrap();
+                }
+            }
+        }
+        writeln!(xml, "  </testsuite>").unwrap();
+        writeln!(xml, "</testsuites>").unwrap();
+        xml
+    }
+
+    pub fn write_json(&self, path: &Path) -> anyhow::Result<()> {
+        fs::write(path, self.to_json()?)
+            .with_context(|| format!("Failed to write JSON test results to {:?}", path))
+    }
+
+    pub fn write_junit_xml(&self, path: &Path) -> anyhow::Result<()> {
+        fs::write(path, self.to_junit_xml())
+            .with_context(|| format!("Failed to write JUnit test results to {:?}", path))
+    }
+}
+
+fn escape_xml(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            // Control characters other than whitespace aren't allowed in XML 1.0
+            c if c.is_control() && !matches!(c, '\n' | '\r' | '\t') => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_junit_xml() {
+        let mut results = TestResults::new("forge".to_string());
+        results.record(
+            "passing".to_string(),
+            TestOutcome::Passed,
+            Duration::from_millis(1500),
+            None,
+        );
+        results.record(
+            "failing".to_string(),
+            TestOutcome::Failed,
+            Duration::from_secs(2),
+            Some("expected <1> & got \"2\"\nbacktrace".to_string()),
+        );
+        results.set_node_logs(vec![(
+            "validator-0".to_string(),
+            "/tmp/swarm/0/log".to_string(),
+        )]);
+        let xml = results.to_junit_xml();
+        assert!(xml.contains(r#"<testsuite name="forge" tests="2" failures="1""#));
+        assert!(xml.contains(r#"<testcase name="passing" classname="forge" time="1.500"/>"#));
+        assert!(xml.contains(
+            r#"<failure message="expected &lt;1&gt; &amp; got &quot;2&quot;">expected &lt;1&gt; &amp; got &quot;2&quot;
+backtrace</failure>"#
+        ));
+        assert!(xml.contains("<system-out>validator-0: /tmp/swarm/0/log\n</system-out>"));
+
+        let json: serde_json::Value = serde_json::from_str(&results.to_json().unwrap()).unwrap();
+        assert_eq!(json["tests"][1]["outcome"], "failed");
+        assert_eq!(json["tests"][0]["duration_secs"], 1.5);
+        assert_eq!(json["node_logs"]["validator-0"], "/tmp/swarm/0/log");
+    }
+}
@@ -2,7 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use aptos_indexer::{
-    database::{new_db_pool, PgDbPool, PgPoolConnection},
+    database::{new_db_pool, DbPool, DbPoolConnection},
     default_processor::DefaultTransactionProcessor,
     indexer::tailer::Tailer,
     models::transactions::TransactionModel,
@@ -20,7 +20,7 @@ impl Test for Indexer {
     }
 }
 
-pub fn wipe_database(conn: &PgPoolConnection) {
+pub fn wipe_database(conn: &DbPoolConnection) {
     for table in [
         "metadatas",
         "tokens",
@@ -54,7 +54,7 @@ pub fn should_skip() -> bool {
     }
 }
 
-pub fn setup_indexer(ctx: &mut AptosContext) -> anyhow::Result<(PgDbPool, Tailer)> {
+pub fn setup_indexer(ctx: &mut AptosContext) -> anyhow::Result<(DbPool, Tailer)> {
     let database_url = std::env::var("INDEXER_DATABASE_URL")
         .expect("must set 'INDEXER_DATABASE_URL' to run tests!");
 
@@ -1,10 +1,10 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{batch_update, generate_traffic};
+use crate::generate_traffic;
 use anyhow::bail;
 use forge::{NetworkContext, NetworkTest, Result, SwarmExt, Test};
-use tokio::{runtime::Runtime, time::Duration};
+use tokio::time::Duration;
 
 pub struct SimpleValidatorUpgrade;
 
@@ -16,8 +16,6 @@ impl Test for SimpleValidatorUpgrade {
 
 impl NetworkTest for SimpleValidatorUpgrade {
     fn run<'t>(&self, ctx: &mut NetworkContext<'t>) -> Result<()> {
-        let runtime = Runtime::new()?;
-
         // Get the different versions we're testing with
         let (old_version, new_version) = {
             let mut versions = ctx.swarm().versions().collect::<Vec<_>>();
@@ -57,13 +55,8 @@ impl NetworkTest for SimpleValidatorUpgrade {
         println!("{}", msg);
         ctx.report.report_text(msg);
         // Ensure that all validators are running the older version of the software
-        let validators_to_downgrade = ctx
-            .swarm()
-            .validators()
-            .filter(|v| v.version() != old_version)
-            .map(|v| v.peer_id())
-            .collect::<Vec<_>>();
-        runtime.block_on(batch_update(ctx, &validators_to_downgrade, &old_version))?;
+        ctx.swarm()
+            .upgrade_all_validators(&old_version, all_validators.len(), true)?;
 
         // Generate some traffic
         generate_traffic(ctx, &all_validators, duration, 1, None)?;
@@ -75,7 +68,8 @@ impl NetworkTest for SimpleValidatorUpgrade {
         );
         println!("{}", msg);
         ctx.report.report_text(msg);
-        runtime.block_on(batch_update(ctx, &[first_node], &new_version))?;
+        ctx.swarm()
+            .upgrade_validators(&[first_node], &new_version, 1, true)?;
         generate_traffic(ctx, &[first_node], duration, 1, None)?;
 
         // Update the rest of the first batch
@@ -85,7 +79,8 @@ impl NetworkTest for SimpleValidatorUpgrade {
         );
         println!("{}", msg);
         ctx.report.report_text(msg);
-        runtime.block_on(batch_update(ctx, &first_batch, &new_version))?;
+        ctx.swarm()
+            .upgrade_validators(&first_batch, &new_version, first_batch.len(), true)?;
         generate_traffic(ctx, &first_batch, duration, 1, None)?;
 
         ctx.swarm().fork_check()?;
@@ -94,7 +89,8 @@ impl NetworkTest for SimpleValidatorUpgrade {
         let msg = format!("4. upgrading second batch to new version: {}", new_version);
         println!("{}", msg);
         ctx.report.report_text(msg);
-        runtime.block_on(batch_update(ctx, &second_batch, &new_version))?;
+        ctx.swarm()
+            .upgrade_all_validators(&new_version, second_batch.len(), true)?;
         generate_traffic(ctx, &second_batch, duration, 1, None)?;
 
         let msg = "5. check swarm health".to_string();
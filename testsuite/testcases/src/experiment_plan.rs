@@ -0,0 +1,334 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    five_region_test::FiveRegionTest, generate_traffic,
+    network_node_loss_test::NetworkNodeLossTest, network_split_brain_test::NetworkSplitBrainTest,
+    soak_test::SoakTest, state_sync_catch_up_test::StateSyncCatchUpTest,
+    validator_restart_test::ValidatorRestartUnderLoadTest,
+};
+use anyhow::{bail, Context};
+use forge::{
+    NetworkContext, NetworkTest, Result, SwarmChaos, SwarmNetworkBandwidth, SwarmNetworkDelay,
+    SwarmNetworkLoss, SwarmNetworkPartition, Test,
+};
+use serde::Deserialize;
+use std::{fs, path::Path, time::Duration};
+
+/// A sequence of experiments declared in YAML, so long soak suites don't have to be encoded in
+/// Rust, e.g.
+///
+/// ```yaml
+/// repeat: 2
+/// steps:
+///   - duration_secs: 600
+///     experiments:
+///       - type: validator_restart
+///         restart_interval_secs: 120
+///         validators_per_restart: 3
+///         max_recovery_secs: 60
+///   - duration_secs: 600
+///     experiments:
+///       - type: network_delay
+///         latency_ms: 80
+///         jitter_ms: 20
+///         correlation_percentage: 10
+///       - type: network_loss
+///         loss_percentage: 5
+///         correlation_percentage: 10
+/// ```
+///
+/// Steps run one after the other, with the global job lasting for the step's duration. A step
+/// with several experiments runs them in parallel, which only network chaos supports: all of it is
+/// injected at once while load is emitted
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ExperimentPlan {
+    /// How many times the steps are run
+    #[serde(default = "default_repeat")]
+    pub repeat: usize,
+    pub steps: Vec<ExperimentStep>,
+}
+
+fn default_repeat() -> usize {
+    1
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ExperimentStep {
+    pub duration_secs: u64,
+    pub experiments: Vec<Experiment>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum Experiment {
+    /// Only emits load
+    Traffic,
+    NetworkDelay {
+        latency_ms: u64,
+        jitter_ms: u64,
+        correlation_percentage: u64,
+    },
+    NetworkLoss {
+        loss_percentage: u64,
+        correlation_percentage: u64,
+    },
+    NetworkBandwidth {
+        rate: u64,
+        limit: u64,
+        buffer: u64,
+    },
+    NetworkPartition {
+        partition_percentage: u64,
+    },
+    NetworkNodeLoss {
+        loss_percentage: u64,
+        lossy_validators_percentage: u64,
+    },
+    ValidatorRestart {
+        restart_interval_secs: u64,
+        validators_per_restart: usize,
+        max_recovery_secs: u64,
+    },
+    StateSyncCatchUp {
+        wipe_validator: bool,
+    },
+    SplitBrain {
+        minority_percentage: u64,
+        partition_secs: u64,
+    },
+    FiveRegion,
+    Soak {
+        check_interval_secs: u64,
+        target_tps: u64,
+        max_epoch_secs: Option<u64>,
+    },
+}
+
+impl Experiment {
+    /// The swarm wide chaos of the experiment, if it is only made of chaos
+    fn chaos(&self) -> Option<SwarmChaos> {
+        match self {
+            Experiment::NetworkDelay {
+                latency_ms,
+                jitter_ms,
+                correlation_percentage,
+            } => Some(SwarmChaos::Delay(SwarmNetworkDelay {
+                latency_ms: *latency_ms,
+                jitter_ms: *jitter_ms,
+                correlation_percentage: *correlation_percentage,
+            })),
+            Experiment::NetworkLoss {
+                loss_percentage,
+                correlation_percentage,
+            } => Some(SwarmChaos::Loss(SwarmNetworkLoss {
+                loss_percentage: *loss_percentage,
+                correlation_percentage: *correlation_percentage,
+            })),
+            Experiment::NetworkBandwidth {
+                rate,
+                limit,
+                buffer,
+            } => Some(SwarmChaos::Bandwidth(SwarmNetworkBandwidth {
+                rate: *rate,
+                limit: *limit,
+                buffer: *buffer,
+            })),
+            Experiment::NetworkPartition {
+                partition_percentage,
+            } => Some(SwarmChaos::Partition(SwarmNetworkPartition {
+                partition_percentage: *partition_percentage,
+            })),
+            _ => None,
+        }
+    }
+
+    /// Runs an experiment which drives the swarm itself, rather than only injecting chaos
+    fn run(&self, ctx: &mut NetworkContext<'_>) -> Result<()> {
+        match self {
+            Experiment::NetworkNodeLoss {
+                loss_percentage,
+                lossy_validators_percentage,
+            } => NetworkNodeLossTest {
+                loss_percentage: *loss_percentage,
+                lossy_validators_percentage: *lossy_validators_percentage,
+            }
+            .run(ctx),
+            Experiment::ValidatorRestart {
+                restart_interval_secs,
+                validators_per_restart,
+                max_recovery_secs,
+            } => ValidatorRestartUnderLoadTest {
+                restart_interval_secs: *restart_interval_secs,
+                validators_per_restart: *validators_per_restart,
+                max_recovery_secs: *max_recovery_secs,
+            }
+            .run(ctx),
+            Experiment::StateSyncCatchUp { wipe_validator } => StateSyncCatchUpTest {
+                wipe_validator: *wipe_validator,
+            }
+            .run(ctx),
+            Experiment::SplitBrain {
+                minority_percentage,
+                partition_secs,
+            } => NetworkSplitBrainTest {
+                minority_percentage: *minority_percentage,
+                partition_secs: *partition_secs,
+            }
+            .run(ctx),
+            Experiment::FiveRegion => FiveRegionTest.run(ctx),
+            Experiment::Soak {
+                check_interval_secs,
+                target_tps,
+                max_epoch_secs,
+            } => SoakTest {
+                check_interval_secs: *check_interval_secs,
+                target_tps: *target_tps,
+                max_epoch_secs: *max_epoch_secs,
+            }
+            .run(ctx),
+            _ => run_with_chaos(ctx, self.chaos().into_iter().collect()),
+        }
+    }
+}
+
+impl ExperimentPlan {
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let contents = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read the experiment plan {:?}", path))?;
+        Self::parse(&contents)
+    }
+
+    pub fn parse(contents: &str) -> Result<Self> {
+        let plan: Self = serde_yaml::from_str(contents)?;
+        plan.validate()?;
+        Ok(plan)
+    }
+
+    fn validate(&self) -> Result<()> {
+        for (i, step) in self.steps.iter().enumerate() {
+            if step.experiments.is_empty() {
+                bail!("Step {} has no experiment", i);
+            }
+            if step.experiments.len() > 1 && step.experiments.iter().any(|e| e.chaos().is_none()) {
+                bail!(
+                    "Step {} runs several experiments, which is only supported for network chaos",
+                    i
+                );
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Test for ExperimentPlan {
+    fn name(&self) -> &'static str {
+        "experiment-plan"
+    }
+}
+
+impl NetworkTest for ExperimentPlan {
+    fn run<'t>(&self, ctx: &mut NetworkContext<'t>) -> Result<()> {
+        let global_duration = ctx.global_job.duration;
+        for round in 0..self.repeat {
+            for (i, step) in self.steps.iter().enumerate() {
+                let msg = format!(
+                    "Round {}, step {}: {:?} for {}s",
+                    round, i, step.experiments, step.duration_secs
+                );
+                println!("{}", msg);
+                ctx.report.report_text(msg);
+                ctx.global_job.duration = Duration::from_secs(step.duration_secs);
+                let result = match step.experiments.as_slice() {
+                    [experiment] => experiment.run(ctx),
+                    experiments => {
+                        run_with_chaos(ctx, experiments.iter().filter_map(|e| e.chaos()).collect())
+                    }
+                };
+                ctx.global_job.duration = global_duration;
+                result.with_context(|| format!("Round {}, step {} failed", round, i))?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Injects all of `chaos` while emitting to all validators for the global job's duration
+fn run_with_chaos(ctx: &mut NetworkContext<'_>, chaos: Vec<SwarmChaos>) -> Result<()> {
+    let duration = ctx.global_job.duration;
+    let all_validators = ctx
+        .swarm()
+        .validators()
+        .map(|v| v.peer_id())
+        .collect::<Vec<_>>();
+
+    let mut injected = vec![];
+    let mut result = Ok(());
+    for c in chaos {
+        if let Err(e) = ctx.swarm().inject_chaos(c.clone()) {
+            result = Err(e);
+            break;
+        }
+        injected.push(c);
+    }
+    let txn_stat = result.and_then(|_| generate_traffic(ctx, &all_validators, duration, 1, None));
+    for c in injected {
+        ctx.swarm().remove_chaos(c)?;
+    }
+    let txn_stat = txn_stat?;
+    ctx.report
+        .report_txn_stats("experiment-plan".to_string(), &txn_stat, duration);
+
+    // ensure we meet the success criteria
+    ctx.success_criteria()
+        .check_for_success(&txn_stat, &duration)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_plan() {
+        let plan = ExperimentPlan::parse(
+            r#"
+steps:
+  - duration_secs: 60
+    experiments:
+      - type: traffic
+  - duration_secs: 120
+    experiments:
+      - type: network_delay
+        latency_ms: 80
+        jitter_ms: 20
+        correlation_percentage: 10
+      - type: network_loss
+        loss_percentage: 5
+        correlation_percentage: 10
+"#,
+        )
+        .unwrap();
+        assert_eq!(plan.repeat, 1);
+        assert_eq!(plan.steps.len(), 2);
+        assert_eq!(plan.steps[1].experiments.len(), 2);
+
+        // only chaos can run in parallel
+        assert!(ExperimentPlan::parse(
+            r#"
+steps:
+  - duration_secs: 60
+    experiments:
+      - type: network_partition
+        partition_percentage: 30
+      - type: state_sync_catch_up
+        wipe_validator: true
+"#,
+        )
+        .is_err());
+    }
+}
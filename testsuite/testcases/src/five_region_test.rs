@@ -0,0 +1,120 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::generate_traffic;
+use anyhow::ensure;
+use forge::{
+    NetworkContext, NetworkTest, Result, SwarmChaos, SwarmNetworkBandwidth, SwarmNetworkLinkDelay,
+    Test,
+};
+
+/// Spreads the validators over five regions, and delays and caps the traffic between each pair of
+/// regions as their intercontinental links would, while emitting to all validators
+pub struct FiveRegionTest;
+
+pub const REGIONS: [&str; 5] = ["us-west", "us-east", "europe", "asia", "south-america"];
+
+/// One way latency between the regions, in the order of `REGIONS`
+const LATENCY_MS: [[u64; 5]; 5] = [
+    [0, 32, 70, 55, 90],
+    [32, 0, 40, 85, 60],
+    [70, 40, 0, 110, 95],
+    [55, 85, 110, 0, 140],
+    [90, 60, 95, 140, 0],
+];
+
+/// Bandwidth of the link from the region of the row to the region of the column, in mbps. Links
+/// out of the regions with less transit capacity are narrower than the links into them
+const BANDWIDTH_MBPS: [[u64; 5]; 5] = [
+    [0, 1000, 500, 400, 200],
+    [1000, 0, 800, 300, 300],
+    [500, 800, 0, 250, 150],
+    [250, 200, 150, 0, 80],
+    [100, 150, 100, 50, 0],
+];
+
+const JITTER_MS: u64 = 5;
+const CORRELATION_PERCENTAGE: u64 = 10;
+const LIMIT_BYTES: u64 = 20971520;
+const BUFFER_BYTES: u64 = 10000;
+
+impl Test for FiveRegionTest {
+    fn name(&self) -> &'static str {
+        "network::five-region-test"
+    }
+}
+
+impl NetworkTest for FiveRegionTest {
+    fn run<'t>(&self, ctx: &mut NetworkContext<'t>) -> Result<()> {
+        let duration = ctx.global_job.duration;
+        let all_validators = ctx
+            .swarm()
+            .validators()
+            .map(|v| v.peer_id())
+            .collect::<Vec<_>>();
+        ensure!(
+            all_validators.len() >= REGIONS.len(),
+            "The five regions need at least {} validators, got {}",
+            REGIONS.len(),
+            all_validators.len()
+        );
+
+        // assign the validators to the regions round robin
+        let mut regions = vec![vec![]; REGIONS.len()];
+        for (i, peer_id) in all_validators.iter().enumerate() {
+            regions[i % REGIONS.len()].push(*peer_id);
+        }
+        let mut links = vec![];
+        for (from, source_nodes) in regions.iter().enumerate() {
+            for (to, target_nodes) in regions.iter().enumerate() {
+                if from == to {
+                    continue;
+                }
+                links.push(SwarmChaos::LinkDelay(SwarmNetworkLinkDelay {
+                    source_nodes: source_nodes.clone(),
+                    target_nodes: target_nodes.clone(),
+                    latency_ms: LATENCY_MS[from][to],
+                    jitter_ms: JITTER_MS,
+                    correlation_percentage: CORRELATION_PERCENTAGE,
+                    bandwidth: Some(SwarmNetworkBandwidth {
+                        rate: BANDWIDTH_MBPS[from][to],
+                        limit: LIMIT_BYTES,
+                        buffer: BUFFER_BYTES,
+                    }),
+                }));
+            }
+        }
+
+        // INJECT THE LINKS AND EMIT TXNS
+        let mut injected = vec![];
+        let mut result = Ok(());
+        for link in links {
+            if let Err(e) = ctx.swarm().inject_chaos(link.clone()) {
+                result = Err(e);
+                break;
+            }
+            injected.push(link);
+        }
+        let msg = format!(
+            "Spread {} validators over regions {:?}",
+            all_validators.len(),
+            REGIONS
+        );
+        println!("{}", msg);
+        ctx.report.report_text(msg);
+        let txn_stat =
+            result.and_then(|_| generate_traffic(ctx, &all_validators, duration, 1, None));
+        for link in injected {
+            ctx.swarm().remove_chaos(link)?;
+        }
+        let txn_stat = txn_stat?;
+        ctx.report
+            .report_txn_stats(format!("{}:delay", self.name()), &txn_stat, duration);
+
+        // ensure we meet the success criteria
+        ctx.success_criteria()
+            .check_for_success(&txn_stat, &duration)?;
+
+        Ok(())
+    }
+}
@@ -2,48 +2,31 @@
 // SPDX-License-Identifier: Apache-2.0
 
 pub mod compatibility_test;
+pub mod experiment_plan;
+pub mod five_region_test;
 pub mod fixed_tps_test;
 pub mod gas_price_test;
 pub mod network_bandwidth_test;
 pub mod network_latency_test;
+pub mod network_loss_test;
+pub mod network_node_loss_test;
 pub mod network_partition_test;
+pub mod network_split_brain_test;
 pub mod partial_nodes_down_test;
 pub mod performance_test;
 pub mod reconfiguration_test;
+pub mod soak_test;
+pub mod state_sync_catch_up_test;
 pub mod state_sync_performance;
+pub mod validator_restart_test;
 
 use anyhow::ensure;
 use aptos_sdk::{transaction_builder::TransactionFactory, types::PeerId};
-use forge::{NetworkContext, NodeExt, Result, TxnEmitter, TxnStats, Version};
+use forge::{EmitJobRequest, NetworkContext, NodeExt, Result, TxnEmitter, TxnStats};
 use rand::SeedableRng;
-use std::{
-    convert::TryInto,
-    time::{Duration, Instant},
-};
+use std::{convert::TryInto, time::Duration};
 use tokio::runtime::Runtime;
 
-async fn batch_update(
-    ctx: &mut NetworkContext<'_>,
-    validators_to_update: &[PeerId],
-    version: &Version,
-) -> Result<()> {
-    for validator in validators_to_update {
-        ctx.swarm().upgrade_validator(*validator, version)?;
-    }
-
-    ctx.swarm().health_check().await?;
-    let deadline = Instant::now() + Duration::from_secs(60);
-    for validator in validators_to_update {
-        ctx.swarm()
-            .validator_mut(*validator)
-            .unwrap()
-            .wait_until_healthy(deadline)
-            .await?;
-    }
-
-    Ok(())
-}
-
 pub fn generate_traffic<'t>(
     ctx: &mut NetworkContext<'t>,
     validators: &[PeerId],
@@ -51,8 +34,26 @@ pub fn generate_traffic<'t>(
     gas_price: u64,
     fixed_tps: Option<u64>,
 ) -> Result<TxnStats> {
-    ensure!(gas_price > 0, "gas_price is required to be non zero");
     let rt = Runtime::new()?;
+    let (mut emitter, mut emit_job_request) = create_emitter(ctx, validators, gas_price)?;
+    emit_job_request = emit_job_request.duration(duration);
+    if let Some(target_tps) = fixed_tps {
+        emit_job_request = emit_job_request.fixed_tps(target_tps.try_into().unwrap());
+    }
+    let stats = rt.block_on(emitter.emit_txn_for(emit_job_request))?;
+
+    Ok(stats)
+}
+
+/// An emitter funded by the swarm's root account, and the global job sending to `validators`.
+/// The emitter borrows the root account, so tests which change the swarm while emitting start
+/// the job and drop the emitter before doing so
+pub fn create_emitter<'a>(
+    ctx: &'a mut NetworkContext<'_>,
+    validators: &[PeerId],
+    gas_price: u64,
+) -> Result<(TxnEmitter<'a>, EmitJobRequest)> {
+    ensure!(gas_price > 0, "gas_price is required to be non zero");
     let rng = SeedableRng::from_rng(ctx.core().rng())?;
     let validator_clients = ctx
         .swarm()
@@ -60,24 +61,19 @@ pub fn generate_traffic<'t>(
         .filter(|v| validators.contains(&v.peer_id()))
         .map(|n| n.rest_client())
         .collect::<Vec<_>>();
-    let mut emit_job_request = ctx.global_job.clone();
+    let emit_job_request = ctx
+        .global_job
+        .clone()
+        .rest_clients(validator_clients.clone())
+        .gas_price(gas_price);
     let chain_info = ctx.swarm().chain_info();
     let transaction_factory = TransactionFactory::new(chain_info.chain_id).with_gas_unit_price(1);
-    let mut emitter = TxnEmitter::new(
+    let emitter = TxnEmitter::new(
         chain_info.root_account,
         validator_clients[0].clone(),
         transaction_factory,
         rng,
     );
 
-    emit_job_request = emit_job_request
-        .rest_clients(validator_clients)
-        .gas_price(gas_price)
-        .duration(duration);
-    if let Some(target_tps) = fixed_tps {
-        emit_job_request = emit_job_request.fixed_tps(target_tps.try_into().unwrap());
-    }
-    let stats = rt.block_on(emitter.emit_txn_for(emit_job_request))?;
-
-    Ok(stats)
+    Ok((emitter, emit_job_request))
 }
@@ -0,0 +1,53 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::generate_traffic;
+use forge::{NetworkContext, NetworkTest, Result, SwarmChaos, SwarmNetworkLoss, Test};
+
+pub struct NetworkLossTest;
+
+// Loss
+pub const LOSS_PERCENTAGE: u64 = 5;
+pub const CORRELATION_PERCENTAGE: u64 = 10;
+
+impl Test for NetworkLossTest {
+    fn name(&self) -> &'static str {
+        "network::loss-test"
+    }
+}
+
+impl NetworkTest for NetworkLossTest {
+    fn run<'t>(&self, ctx: &mut NetworkContext<'t>) -> Result<()> {
+        let duration = ctx.global_job.duration;
+        let loss = SwarmChaos::Loss(SwarmNetworkLoss {
+            loss_percentage: LOSS_PERCENTAGE,
+            correlation_percentage: CORRELATION_PERCENTAGE,
+        });
+        // emit to all validator
+        let all_validators = ctx
+            .swarm()
+            .validators()
+            .map(|v| v.peer_id())
+            .collect::<Vec<_>>();
+
+        // INJECT LOSS AND EMIT TXNS
+        ctx.swarm().inject_chaos(loss.clone())?;
+        let msg = format!(
+            "Injected {}% packet loss with {}% correlation to namespace",
+            LOSS_PERCENTAGE, CORRELATION_PERCENTAGE
+        );
+        println!("{}", msg);
+        ctx.report.report_text(msg);
+        let txn_stat = generate_traffic(ctx, &all_validators, duration, 1, None);
+        ctx.swarm().remove_chaos(loss)?;
+        let txn_stat = txn_stat?;
+        ctx.report
+            .report_txn_stats(format!("{}:loss", self.name()), &txn_stat, duration);
+
+        // ensure we meet the success criteria
+        ctx.success_criteria()
+            .check_for_success(&txn_stat, &duration)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,107 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::generate_traffic;
+use anyhow::ensure;
+use forge::{NetworkContext, NetworkTest, Result, SwarmChaos, SwarmNetworkNodeLoss, Test};
+
+/// Drops a percentage of the packets sent by a subset of the validators while emitting load, and
+/// reports how much TPS and latency degrade compared to a baseline run without loss
+pub struct NetworkNodeLossTest {
+    /// Between 1 and 10
+    pub loss_percentage: u64,
+    /// The share of validators which lose packets, the others are left untouched
+    pub lossy_validators_percentage: u64,
+}
+
+// Loss
+pub const DEFAULT_LOSS_PERCENTAGE: u64 = 5;
+pub const DEFAULT_LOSSY_VALIDATORS_PERCENTAGE: u64 = 30;
+pub const CORRELATION_PERCENTAGE: u64 = 10;
+
+impl Test for NetworkNodeLossTest {
+    fn name(&self) -> &'static str {
+        "network::node-loss-test"
+    }
+}
+
+fn degradation_percentage(baseline: u64, value: u64) -> f64 {
+    if baseline == 0 {
+        0.0
+    } else {
+        (baseline as f64 - value as f64) * 100.0 / baseline as f64
+    }
+}
+
+impl NetworkTest for NetworkNodeLossTest {
+    fn run<'t>(&self, ctx: &mut NetworkContext<'t>) -> Result<()> {
+        ensure!(
+            (1..=10).contains(&self.loss_percentage),
+            "loss_percentage must be between 1 and 10, got {}",
+            self.loss_percentage
+        );
+        // half of the time is spent on the baseline, the other half with packet loss
+        let duration = ctx.global_job.duration / 2;
+        // emit to all validator
+        let all_validators = ctx
+            .swarm()
+            .validators()
+            .map(|v| v.peer_id())
+            .collect::<Vec<_>>();
+        let num_lossy = ((all_validators.len() * self.lossy_validators_percentage as usize) / 100)
+            .clamp(1, all_validators.len());
+        let lossy_validators = all_validators[..num_lossy].to_vec();
+
+        // BASELINE
+        let baseline_stat = generate_traffic(ctx, &all_validators, duration, 1, None)?;
+        ctx.report.report_txn_stats(
+            format!("{}:baseline", self.name()),
+            &baseline_stat,
+            duration,
+        );
+
+        // INJECT LOSS AND EMIT TXNS
+        let loss = SwarmChaos::NodeLoss(SwarmNetworkNodeLoss {
+            nodes: lossy_validators,
+            loss_percentage: self.loss_percentage,
+            correlation_percentage: CORRELATION_PERCENTAGE,
+        });
+        ctx.swarm().inject_chaos(loss.clone())?;
+        let msg = format!(
+            "Injected {}% packet loss with {}% correlation to {} of {} validators",
+            self.loss_percentage,
+            CORRELATION_PERCENTAGE,
+            num_lossy,
+            all_validators.len()
+        );
+        println!("{}", msg);
+        ctx.report.report_text(msg);
+        let txn_stat = generate_traffic(ctx, &all_validators, duration, 1, None);
+        ctx.swarm().remove_chaos(loss)?;
+        let txn_stat = txn_stat?;
+        ctx.report
+            .report_txn_stats(format!("{}:loss", self.name()), &txn_stat, duration);
+
+        // DEGRADATION
+        let baseline_rate = baseline_stat.rate(duration);
+        let rate = txn_stat.rate(duration);
+        let tps_degradation = degradation_percentage(baseline_rate.committed, rate.committed);
+        let latency_degradation = -degradation_percentage(baseline_rate.latency, rate.latency);
+        ctx.report
+            .report_metric(self.name(), "tps_degradation_percent", tps_degradation);
+        ctx.report
+            .report_metric(self.name(), "latency_increase_percent", latency_degradation);
+        ctx.report.report_text(format!(
+            "{} : TPS dropped by {:.1}%, latency increased by {:.1}% compared to the baseline",
+            self.name(),
+            tps_degradation,
+            latency_degradation
+        ));
+
+        // ensure we meet the success criteria
+        ctx.success_criteria()
+            .check_for_success(&txn_stat, &duration)?;
+
+        Ok(())
+    }
+}
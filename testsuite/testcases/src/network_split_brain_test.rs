@@ -0,0 +1,174 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::generate_traffic;
+use anyhow::{bail, ensure};
+use aptos_rest_client::Client as RestClient;
+use aptos_sdk::types::PeerId;
+use forge::{
+    NetworkContext, NetworkTest, NodeExt, Result, SwarmChaos, SwarmExt, SwarmNetworkNodePartition,
+    Test,
+};
+use std::{
+    thread,
+    time::{Duration, Instant},
+};
+use tokio::runtime::Runtime;
+
+/// Splits the validators into a majority and a minority which can't reach each other for
+/// `partition_secs`, while load is sent to the majority. The minority must not commit anything
+/// while partitioned. Once the partition heals, the time for the chain to commit again and for
+/// every validator to converge is reported
+pub struct NetworkSplitBrainTest {
+    pub minority_percentage: u64,
+    pub partition_secs: u64,
+}
+
+/// Lets in flight blocks commit before the minority's versions are recorded
+const SETTLE_TIME: Duration = Duration::from_secs(5);
+const MAX_HEAL_TIME: Duration = Duration::from_secs(300);
+/// Validators have converged once all of them are this close to the most advanced one
+const CONVERGED_VERSION_DELTA: u64 = 100;
+
+impl Test for NetworkSplitBrainTest {
+    fn name(&self) -> &'static str {
+        "network::split-brain-test"
+    }
+}
+
+impl NetworkTest for NetworkSplitBrainTest {
+    fn run<'t>(&self, ctx: &mut NetworkContext<'t>) -> Result<()> {
+        ensure!(
+            self.minority_percentage > 0 && self.minority_percentage < 50,
+            "minority_percentage must be between 1 and 49, got {}",
+            self.minority_percentage
+        );
+        let runtime = Runtime::new()?;
+        let duration = Duration::from_secs(self.partition_secs);
+        let mut majority = ctx
+            .swarm()
+            .validators()
+            .map(|v| v.peer_id())
+            .collect::<Vec<_>>();
+        let num_minority = ((majority.len() as u64 * self.minority_percentage) / 100).max(1);
+        let minority = majority.drain(..num_minority as usize).collect::<Vec<_>>();
+        let minority_clients = rest_clients(ctx, &minority);
+        let all_clients = minority_clients
+            .iter()
+            .cloned()
+            .chain(rest_clients(ctx, &majority))
+            .collect::<Vec<_>>();
+
+        // PARTITION THE VALIDATORS AND EMIT TXNS TO THE MAJORITY
+        let partition = SwarmChaos::NodePartition(SwarmNetworkNodePartition {
+            source_nodes: minority.clone(),
+            target_nodes: majority.clone(),
+        });
+        ctx.swarm().inject_chaos(partition.clone())?;
+        let msg = format!(
+            "Partitioned {} validators from the other {} for {}s",
+            minority.len(),
+            majority.len(),
+            self.partition_secs
+        );
+        println!("{}", msg);
+        ctx.report.report_text(msg);
+        thread::sleep(SETTLE_TIME);
+        let minority_versions = versions(&runtime, &minority_clients);
+        let txn_stat = generate_traffic(ctx, &majority, duration, 1, None);
+        let partitioned_minority_versions = versions(&runtime, &minority_clients);
+
+        // HEAL THE PARTITION
+        ctx.swarm().remove_chaos(partition)?;
+        let heal_start = Instant::now();
+        let txn_stat = txn_stat?;
+        ctx.report
+            .report_txn_stats(format!("{}:partition", self.name()), &txn_stat, duration);
+        for (before, after) in minority_versions
+            .iter()
+            .zip(partitioned_minority_versions.iter())
+        {
+            if let (Some(before), Some(after)) = (before, after) {
+                ensure!(
+                    after <= before,
+                    "The minority committed from version {} to {} while partitioned",
+                    before,
+                    after
+                );
+            }
+        }
+
+        // the chain commits again once a validator goes past the versions reached while partitioned
+        let partitioned_max = versions(&runtime, &all_clients)
+            .into_iter()
+            .flatten()
+            .max()
+            .unwrap_or(0);
+        loop {
+            let max = versions(&runtime, &all_clients)
+                .into_iter()
+                .flatten()
+                .max()
+                .unwrap_or(0);
+            if max > partitioned_max {
+                break;
+            }
+            if heal_start.elapsed() > MAX_HEAL_TIME {
+                bail!(
+                    "No version was committed after version {} within {}s of healing the partition",
+                    partitioned_max,
+                    MAX_HEAL_TIME.as_secs()
+                );
+            }
+            thread::sleep(Duration::from_secs(1));
+        }
+        let time_to_resume = heal_start.elapsed();
+
+        runtime.block_on(ctx.swarm().wait_for_all_nodes_to_catchup_within(
+            CONVERGED_VERSION_DELTA,
+            heal_start + MAX_HEAL_TIME,
+        ))?;
+        let time_to_converge = heal_start.elapsed();
+
+        let msg = format!(
+            "{} : commits resumed {:.1}s and validators converged {:.1}s after healing",
+            self.name(),
+            time_to_resume.as_secs_f64(),
+            time_to_converge.as_secs_f64()
+        );
+        println!("{}", msg);
+        ctx.report.report_text(msg);
+        ctx.report.report_metric(
+            self.name(),
+            "time_to_resume_secs",
+            time_to_resume.as_secs_f64(),
+        );
+        ctx.report.report_metric(
+            self.name(),
+            "time_to_converge_secs",
+            time_to_converge.as_secs_f64(),
+        );
+
+        Ok(())
+    }
+}
+
+fn rest_clients(ctx: &mut NetworkContext<'_>, validators: &[PeerId]) -> Vec<RestClient> {
+    validators
+        .iter()
+        .map(|peer_id| ctx.swarm().validator(*peer_id).unwrap().rest_client())
+        .collect()
+}
+
+/// The version of each node, or None if it can't be reached
+fn versions(runtime: &Runtime, clients: &[RestClient]) -> Vec<Option<u64>> {
+    clients
+        .iter()
+        .map(|client| {
+            runtime
+                .block_on(client.get_ledger_information())
+                .ok()
+                .map(|r| r.into_inner().version)
+        })
+        .collect()
+}
@@ -2,7 +2,8 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::generate_traffic;
-use forge::{NetworkContext, NetworkTest, Result, Test};
+use forge::{NetworkContext, NetworkTest, Result, SwarmExt, Test};
+use tokio::runtime::Runtime;
 
 pub struct PerformanceBenchmark;
 
@@ -25,6 +26,13 @@ impl NetworkTest for PerformanceBenchmark {
         let txn_stat = generate_traffic(ctx, &all_validators, duration, 1, None)?;
         ctx.report
             .report_txn_stats(self.name().to_string(), &txn_stat, duration);
+        // the breakdown needs the nodes' metrics, which not every backend can query
+        match Runtime::new()?.block_on(ctx.swarm().latency_breakdown(duration)) {
+            Ok(breakdown) => ctx
+                .report
+                .report_latency_breakdown(self.name().to_string(), &breakdown),
+            Err(e) => println!("Skipping the latency breakdown: {}", e),
+        }
         // ensure we meet the success criteria
         ctx.success_criteria()
             .check_for_success(&txn_stat, &duration)?;
@@ -0,0 +1,219 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::create_emitter;
+use anyhow::{bail, ensure};
+use forge::{
+    EmitJob, NetworkContext, NetworkTest, Node, NodeExt, NodeMetric, Result, SwarmExt, Test,
+    TxnStats,
+};
+use std::{
+    collections::HashMap,
+    convert::TryInto,
+    thread,
+    time::{Duration, Instant},
+};
+use tokio::runtime::Runtime;
+
+/// Emits a moderate load to all validators for the whole job, which is meant to last for hours.
+/// Every `check_interval_secs` the stats of the interval are reported and the invariants are
+/// checked: no node restarted, the ledger didn't fork and the epoch kept moving forward
+pub struct SoakTest {
+    pub check_interval_secs: u64,
+    pub target_tps: u64,
+    /// The epoch has to change at least this often, None if the chain's epochs outlast the soak
+    pub max_epoch_secs: Option<u64>,
+}
+
+impl Test for SoakTest {
+    fn name(&self) -> &'static str {
+        "soak"
+    }
+}
+
+impl NetworkTest for SoakTest {
+    fn run<'t>(&self, ctx: &mut NetworkContext<'t>) -> Result<()> {
+        let duration = ctx.global_job.duration;
+        let check_interval = Duration::from_secs(self.check_interval_secs);
+        ensure!(
+            check_interval > Duration::ZERO && check_interval <= duration,
+            "check_interval_secs must be between 1 and the job's {}s",
+            duration.as_secs()
+        );
+        let all_validators = ctx
+            .swarm()
+            .validators()
+            .map(|v| v.peer_id())
+            .collect::<Vec<_>>();
+
+        let runtime = Runtime::new()?;
+        let mut invariants = Invariants::new(ctx, &runtime)?;
+        // the job keeps running once the emitter is dropped, which frees the swarm for the checks
+        let job = {
+            let (mut emitter, emit_job_request) = create_emitter(ctx, &all_validators, 1)?;
+            let emit_job_request = emit_job_request.fixed_tps(self.target_tps.try_into()?);
+            runtime.block_on(emitter.start_job(emit_job_request))?
+        };
+        let start = Instant::now();
+        let deadline = start + duration;
+
+        let result = self.check_periodically(ctx, &runtime, &job, &mut invariants, deadline);
+        let txn_stat = runtime.block_on(job.stop());
+        let duration = start.elapsed();
+        ctx.report
+            .report_txn_stats(self.name().to_string(), &txn_stat, duration);
+        ctx.report.report_metric(
+            self.name(),
+            "epoch_changes",
+            invariants.epoch_changes as f64,
+        );
+        result?;
+
+        // ensure we meet the success criteria
+        ctx.success_criteria()
+            .check_for_success(&txn_stat, &duration)?;
+
+        Ok(())
+    }
+}
+
+impl SoakTest {
+    fn check_periodically(
+        &self,
+        ctx: &mut NetworkContext<'_>,
+        runtime: &Runtime,
+        job: &EmitJob,
+        invariants: &mut Invariants,
+        deadline: Instant,
+    ) -> Result<()> {
+        let check_interval = Duration::from_secs(self.check_interval_secs);
+        let mut prev_stats = TxnStats::default();
+        let mut interval = 0;
+        while let Some(remaining) = deadline.checked_duration_since(Instant::now()) {
+            let interval_start = Instant::now();
+            thread::sleep(check_interval.min(remaining));
+            let stats = job.peek_stats();
+            let interval_stats = &stats - &prev_stats;
+            let msg = format!(
+                "{} : interval {}, {}",
+                self.name(),
+                interval,
+                interval_stats.rate(interval_start.elapsed())
+            );
+            println!("{}", msg);
+            ctx.report.report_text(msg);
+            if interval_stats.committed == 0 {
+                bail!("No transaction was committed in interval {}", interval);
+            }
+
+            invariants
+                .check(ctx, runtime, self.max_epoch_secs.map(Duration::from_secs))
+                .map_err(|e| e.context(format!("Invariant violated in interval {}", interval)))?;
+            prev_stats = stats;
+            interval += 1;
+        }
+
+        Ok(())
+    }
+}
+
+/// What the invariants are checked against, as of the previous check
+struct Invariants {
+    /// The mempool gc count of every node, keyed by the node's name
+    gc_events: HashMap<String, i64>,
+    epoch: u64,
+    epoch_changed_at: Instant,
+    epoch_changes: u64,
+}
+
+impl Invariants {
+    fn new(ctx: &mut NetworkContext<'_>, runtime: &Runtime) -> Result<Self> {
+        Ok(Self {
+            gc_events: gc_events(ctx, runtime)?,
+            epoch: max_epoch(ctx, runtime)?,
+            epoch_changed_at: Instant::now(),
+            epoch_changes: 0,
+        })
+    }
+
+    fn check(
+        &mut self,
+        ctx: &mut NetworkContext<'_>,
+        runtime: &Runtime,
+        max_epoch_duration: Option<Duration>,
+    ) -> Result<()> {
+        // no node restarted
+        let gc_events = gc_events(ctx, runtime)?;
+        for (name, count) in &gc_events {
+            if let Some(prev_count) = self.gc_events.get(name) {
+                ensure!(
+                    count >= prev_count,
+                    "Node {} restarted unexpectedly, its mempool gc count went from {} to {}",
+                    name,
+                    prev_count,
+                    count
+                );
+            }
+        }
+        self.gc_events = gc_events;
+
+        // the ledger didn't fork
+        ctx.swarm().fork_check()?;
+
+        // the epoch changes
+        let epoch = max_epoch(ctx, runtime)?;
+        ensure!(
+            epoch >= self.epoch,
+            "The epoch went back from {} to {}",
+            self.epoch,
+            epoch
+        );
+        if epoch > self.epoch {
+            println!("Epoch changed from {} to {}", self.epoch, epoch);
+            self.epoch_changes += epoch - self.epoch;
+            self.epoch = epoch;
+            self.epoch_changed_at = Instant::now();
+        } else if let Some(max_epoch_duration) = max_epoch_duration {
+            ensure!(
+                self.epoch_changed_at.elapsed() <= max_epoch_duration,
+                "The epoch stayed at {} for more than {}s",
+                epoch,
+                max_epoch_duration.as_secs()
+            );
+        }
+
+        Ok(())
+    }
+}
+
+fn gc_events(ctx: &mut NetworkContext<'_>, runtime: &Runtime) -> Result<HashMap<String, i64>> {
+    let mut gc_events = HashMap::new();
+    for validator in ctx.swarm().validators() {
+        let (name, count) = gc_event_count(runtime, validator)?;
+        gc_events.insert(name, count);
+    }
+    for full_node in ctx.swarm().full_nodes() {
+        let (name, count) = gc_event_count(runtime, full_node)?;
+        gc_events.insert(name, count);
+    }
+    Ok(gc_events)
+}
+
+fn gc_event_count<N: Node + ?Sized>(runtime: &Runtime, node: &N) -> Result<(String, i64)> {
+    let count = runtime
+        .block_on(node.get_node_metric(NodeMetric::MempoolGcEvents))?
+        .unwrap_or(0);
+    Ok((node.name().to_string(), count))
+}
+
+/// The highest epoch any validator reached
+fn max_epoch(ctx: &mut NetworkContext<'_>, runtime: &Runtime) -> Result<u64> {
+    let mut max_epoch = 0;
+    for validator in ctx.swarm().validators() {
+        let state = runtime
+            .block_on(validator.rest_client().get_ledger_information())?
+            .into_inner();
+        max_epoch = max_epoch.max(state.epoch);
+    }
+    Ok(max_epoch)
+}
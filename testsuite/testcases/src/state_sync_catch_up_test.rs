@@ -0,0 +1,162 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::generate_traffic;
+use anyhow::{bail, format_err};
+use aptos_rest_client::Client as RestClient;
+use forge::{NetworkContext, NetworkTest, Node, NodeExt, Result, Test};
+use rand::{
+    rngs::{OsRng, StdRng},
+    seq::IteratorRandom,
+    Rng, SeedableRng,
+};
+use std::{
+    thread,
+    time::{Duration, Instant},
+};
+use tokio::runtime::Runtime;
+
+const MAX_CATCH_UP_TIME: Duration = Duration::from_secs(600);
+
+/// Wipes the storage of a validator (or a fullnode), lets the chain advance under load without
+/// it, then measures how long the node takes to state sync back to the tip
+pub struct StateSyncCatchUpTest {
+    /// Whether a validator or a fullnode is wiped
+    pub wipe_validator: bool,
+}
+
+impl Test for StateSyncCatchUpTest {
+    fn name(&self) -> &'static str {
+        if self.wipe_validator {
+            "state-sync-catch-up::validator"
+        } else {
+            "state-sync-catch-up::fullnode"
+        }
+    }
+}
+
+impl NetworkTest for StateSyncCatchUpTest {
+    fn run<'t>(&self, ctx: &mut NetworkContext<'t>) -> Result<()> {
+        let mut rng = StdRng::from_seed(OsRng.gen());
+        let duration = ctx.global_job.duration;
+        let all_validators = ctx
+            .swarm()
+            .validators()
+            .map(|v| v.peer_id())
+            .collect::<Vec<_>>();
+
+        // 1. pick the node to wipe, and stop it
+        let wiped_id = if self.wipe_validator {
+            *all_validators.iter().choose(&mut rng).unwrap()
+        } else {
+            ctx.swarm()
+                .full_nodes()
+                .map(|v| v.peer_id())
+                .choose(&mut rng)
+                .ok_or_else(|| format_err!("The swarm has no fullnode to wipe"))?
+        };
+        let wiped_name = if self.wipe_validator {
+            stop_and_wipe(ctx.swarm().validator_mut(wiped_id).unwrap())?
+        } else {
+            stop_and_wipe(ctx.swarm().full_node_mut(wiped_id).unwrap())?
+        };
+
+        // 2. emit txns to the other validators, so the chain advances without the wiped node
+        let emit_to = all_validators
+            .iter()
+            .filter(|peer_id| **peer_id != wiped_id)
+            .copied()
+            .collect::<Vec<_>>();
+        let txn_stat = generate_traffic(ctx, &emit_to, duration, 1, None)?;
+        ctx.report
+            .report_txn_stats(format!("{}:load", self.name()), &txn_stat, duration);
+
+        // 3. read the tip from the nodes which kept running
+        let runtime = Runtime::new()?;
+        let clients = ctx
+            .swarm()
+            .validators()
+            .filter(|v| v.peer_id() != wiped_id)
+            .map(|v| v.rest_client())
+            .collect::<Vec<_>>();
+        let tip = clients
+            .iter()
+            .filter_map(|client| {
+                runtime
+                    .block_on(client.get_ledger_information())
+                    .ok()
+                    .map(|r| r.into_inner().version)
+            })
+            .max()
+            .ok_or_else(|| format_err!("No validator could report its version"))?;
+
+        // 4. restart the wiped node, and wait for it to state sync to the tip. Syncing starts as
+        // soon as the node does, so the time it takes to come up counts too
+        let catch_up_start = Instant::now();
+        let client = if self.wipe_validator {
+            start(&runtime, ctx.swarm().validator_mut(wiped_id).unwrap())
+        } else {
+            start(&runtime, ctx.swarm().full_node_mut(wiped_id).unwrap())
+        };
+        println!(
+            "Node {} is starting again. Waiting for it to state sync to version {}",
+            wiped_name, tip
+        );
+        loop {
+            let version = runtime
+                .block_on(client.get_ledger_information())
+                .map(|r| r.into_inner().version)
+                .unwrap_or(0);
+            if version >= tip {
+                break;
+            }
+            if catch_up_start.elapsed() > MAX_CATCH_UP_TIME {
+                bail!(
+                    "Node {} only synced to version {} of {} within {}s",
+                    wiped_name,
+                    version,
+                    tip,
+                    MAX_CATCH_UP_TIME.as_secs()
+                );
+            }
+            thread::sleep(Duration::from_secs(1));
+        }
+
+        // the wiped node synced every version from genesis
+        let catch_up_time = catch_up_start.elapsed();
+        let throughput = tip as f64 / catch_up_time.as_secs_f64();
+        let msg = format!(
+            "{} : caught up to version {} in {:.1}s, {:.0} versions/sec",
+            self.name(),
+            tip,
+            catch_up_time.as_secs_f64(),
+            throughput
+        );
+        println!("{}", msg);
+        ctx.report.report_text(msg);
+        ctx.report
+            .report_metric(self.name(), "catch_up_secs", catch_up_time.as_secs_f64());
+        ctx.report
+            .report_metric(self.name(), "catch_up_versions_per_sec", throughput);
+
+        Ok(())
+    }
+}
+
+/// Returns the name of the wiped node
+fn stop_and_wipe<N: Node + ?Sized>(node: &mut N) -> Result<String> {
+    println!("Node {} is going to be wiped", node.name());
+    node.stop()?;
+    node.clear_storage()?;
+    Ok(node.name().to_string())
+}
+
+/// Returns the REST client of the started node. Starting may wait for the node to be healthy,
+/// which a node still syncing might not be in time, so failing to start isn't fatal: the caller
+/// keeps polling the node until it catches up
+fn start<N: Node + ?Sized>(runtime: &Runtime, node: &mut N) -> RestClient {
+    if let Err(e) = runtime.block_on(node.start()) {
+        println!("Node {} is not healthy yet: {}", node.name(), e);
+    }
+    node.rest_client()
+}
@@ -0,0 +1,179 @@
+// Copyright (c) Aptos
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::create_emitter;
+use anyhow::{bail, ensure};
+use forge::{EmitJob, NetworkContext, NetworkTest, NodeExt, Result, Test};
+use std::{
+    thread,
+    time::{Duration, Instant},
+};
+use tokio::runtime::Runtime;
+
+/// Emits transactions for the whole job while restarting a rotating subset of the validators on
+/// an interval. After each restart commit latency has to come back to the pre-restart level
+/// within `max_recovery_secs`
+pub struct ValidatorRestartUnderLoadTest {
+    pub restart_interval_secs: u64,
+    pub validators_per_restart: usize,
+    pub max_recovery_secs: u64,
+}
+
+/// The load emitted before any restart, which recovered latency is compared against
+const BASELINE: Duration = Duration::from_secs(30);
+/// Latency is measured over windows this long while waiting for it to recover
+const RECOVERY_WINDOW: Duration = Duration::from_secs(5);
+/// Latency has recovered once it is at most this many times the baseline latency
+const LATENCY_RECOVERY_FACTOR: u64 = 2;
+
+impl Test for ValidatorRestartUnderLoadTest {
+    fn name(&self) -> &'static str {
+        "validator-restart-under-load"
+    }
+}
+
+impl NetworkTest for ValidatorRestartUnderLoadTest {
+    fn run<'t>(&self, ctx: &mut NetworkContext<'t>) -> Result<()> {
+        let duration = ctx.global_job.duration;
+        ensure!(
+            duration >= BASELINE + Duration::from_secs(self.restart_interval_secs),
+            "the job has to last for at least {}s to restart any validator",
+            (BASELINE + Duration::from_secs(self.restart_interval_secs)).as_secs()
+        );
+        let all_validators = ctx
+            .swarm()
+            .validators()
+            .map(|v| v.peer_id())
+            .collect::<Vec<_>>();
+        ensure!(
+            self.validators_per_restart > 0 && self.validators_per_restart < all_validators.len(),
+            "validators_per_restart must be between 1 and {}",
+            all_validators.len() - 1
+        );
+
+        // the job keeps running once the emitter is dropped, which frees the swarm for restarts
+        let runtime = Runtime::new()?;
+        let job = {
+            let (mut emitter, emit_job_request) = create_emitter(ctx, &all_validators, 1)?;
+            runtime.block_on(emitter.start_job(emit_job_request))?
+        };
+        let start = Instant::now();
+        let deadline = start + duration;
+
+        thread::sleep(BASELINE);
+        let baseline_stats = job.peek_stats();
+        if baseline_stats.committed == 0 {
+            runtime.block_on(job.stop());
+            bail!("No transaction was committed before restarting any validator");
+        }
+        let baseline = baseline_stats.rate(BASELINE);
+        let msg = format!("Baseline before restarts: {}", baseline);
+        println!("{}", msg);
+        ctx.report.report_text(msg);
+
+        let mut recovery_times = vec![];
+        let mut result = Ok(());
+        let mut round = 0;
+        while Instant::now() + Duration::from_secs(self.restart_interval_secs) <= deadline {
+            let restart_start = Instant::now();
+            let to_restart = (0..self.validators_per_restart)
+                .map(|i| {
+                    all_validators[(round * self.validators_per_restart + i) % all_validators.len()]
+                })
+                .collect::<Vec<_>>();
+            let mut names = vec![];
+            for peer_id in &to_restart {
+                let node = ctx.swarm().validator_mut(*peer_id).unwrap();
+                println!("Node {} is going to restart", node.name());
+                names.push(node.name().to_string());
+                if let Err(e) = runtime.block_on(node.restart()) {
+                    result = Err(e);
+                    break;
+                }
+            }
+            if result.is_err() {
+                break;
+            }
+
+            match wait_for_latency_recovery(
+                &job,
+                baseline.latency,
+                restart_start,
+                Duration::from_secs(self.max_recovery_secs),
+            ) {
+                Ok(recovery_time) => {
+                    let msg = format!(
+                        "Restarted {:?}, latency recovered in {:.1}s",
+                        names,
+                        recovery_time.as_secs_f64()
+                    );
+                    println!("{}", msg);
+                    ctx.report.report_text(msg);
+                    recovery_times.push(recovery_time);
+                }
+                Err(e) => {
+                    result = Err(e.context(format!("Restarted {:?}", names)));
+                    break;
+                }
+            }
+
+            round += 1;
+            let next_restart = restart_start + Duration::from_secs(self.restart_interval_secs);
+            if let Some(wait) = next_restart.checked_duration_since(Instant::now()) {
+                thread::sleep(wait);
+            }
+        }
+        if let Some(remaining) = deadline.checked_duration_since(Instant::now()) {
+            if result.is_ok() {
+                thread::sleep(remaining);
+            }
+        }
+        let txn_stat = runtime.block_on(job.stop());
+        let duration = start.elapsed();
+        ctx.report
+            .report_txn_stats(self.name().to_string(), &txn_stat, duration);
+        result?;
+
+        if let Some(max_recovery) = recovery_times.iter().max() {
+            let avg_recovery =
+                recovery_times.iter().sum::<Duration>() / recovery_times.len() as u32;
+            ctx.report
+                .report_metric(self.name(), "max_recovery_secs", max_recovery.as_secs_f64());
+            ctx.report
+                .report_metric(self.name(), "avg_recovery_secs", avg_recovery.as_secs_f64());
+        }
+
+        // ensure we meet the success criteria
+        ctx.success_criteria()
+            .check_for_success(&txn_stat, &duration)?;
+
+        Ok(())
+    }
+}
+
+/// Waits for the average latency of the transactions committed in a window to be back within
+/// `LATENCY_RECOVERY_FACTOR` times the baseline, and returns how long it took since the restart
+fn wait_for_latency_recovery(
+    job: &EmitJob,
+    baseline_latency: u64,
+    restart_start: Instant,
+    max_recovery_time: Duration,
+) -> Result<Duration> {
+    let max_latency = baseline_latency.max(1) * LATENCY_RECOVERY_FACTOR;
+    loop {
+        let prev_stats = job.peek_stats();
+        thread::sleep(RECOVERY_WINDOW);
+        let rate = (&job.peek_stats() - &prev_stats).rate(RECOVERY_WINDOW);
+        if rate.committed > 0 && rate.latency <= max_latency {
+            return Ok(restart_start.elapsed());
+        }
+        if restart_start.elapsed() > max_recovery_time {
+            bail!(
+                "Latency didn't recover below {}ms within {}s, last window: {}",
+                max_latency,
+                max_recovery_time.as_secs(),
+                rate
+            );
+        }
+    }
+}
@@ -12,6 +12,7 @@ use serde::{Deserialize, Serialize};
 #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub enum OnChainConsensusConfig {
     V1(ConsensusConfigV1),
+    V2(ConsensusConfigV2),
 }
 
 /// The public interface that exposes all values with safe fallback.
@@ -20,6 +21,26 @@ impl OnChainConsensusConfig {
     pub fn leader_reputation_exclude_round(&self) -> u64 {
         match &self {
             OnChainConsensusConfig::V1(config) => config.exclude_round,
+            OnChainConsensusConfig::V2(config) => config.exclude_round,
+        }
+    }
+
+    /// The number of proposers, in addition to the primary one, that can propose in a round.
+    pub fn leader_reputation_num_fallback_proposers(&self) -> usize {
+        match &self {
+            OnChainConsensusConfig::V1(_) => 0,
+            OnChainConsensusConfig::V2(config) => config.num_fallback_proposers,
+        }
+    }
+
+    /// When and for how long proposers that keep failing are excluded from leader reputation election.
+    /// An invalid jail config disables jailing, the same way on every node.
+    pub fn leader_jail_config(&self) -> Option<LeaderJailConfig> {
+        match &self {
+            OnChainConsensusConfig::V1(_) => None,
+            OnChainConsensusConfig::V2(config) => {
+                config.leader_jail_config.filter(LeaderJailConfig::is_valid)
+            }
         }
     }
 
@@ -27,6 +48,7 @@ impl OnChainConsensusConfig {
     pub fn decoupled_execution(&self) -> bool {
         match &self {
             OnChainConsensusConfig::V1(config) => config.decoupled_execution,
+            OnChainConsensusConfig::V2(config) => config.decoupled_execution,
         }
     }
 
@@ -39,6 +61,7 @@ impl OnChainConsensusConfig {
         }
         match &self {
             OnChainConsensusConfig::V1(config) => config.back_pressure_limit,
+            OnChainConsensusConfig::V2(config) => config.back_pressure_limit,
         }
     }
 
@@ -47,6 +70,7 @@ impl OnChainConsensusConfig {
     pub fn max_failed_authors_to_store(&self) -> usize {
         match &self {
             OnChainConsensusConfig::V1(config) => config.max_failed_authors_to_store,
+            OnChainConsensusConfig::V2(config) => config.max_failed_authors_to_store,
         }
     }
 
@@ -54,6 +78,7 @@ impl OnChainConsensusConfig {
     pub fn proposer_election_type(&self) -> &ProposerElectionType {
         match &self {
             OnChainConsensusConfig::V1(config) => &config.proposer_election_type,
+            OnChainConsensusConfig::V2(config) => &config.proposer_election_type,
         }
     }
 }
@@ -117,6 +142,34 @@ impl Default for ConsensusConfigV1 {
     }
 }
 
+/// ConsensusConfigV1 with fallback proposers and leader jailing. Nodes that don't know about V2 fail to read it,
+/// so it can only be set once every validator runs a release that does.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct ConsensusConfigV2 {
+    pub decoupled_execution: bool,
+    pub back_pressure_limit: u64,
+    pub exclude_round: u64,
+    pub proposer_election_type: ProposerElectionType,
+    pub max_failed_authors_to_store: usize,
+    pub leader_jail_config: Option<LeaderJailConfig>,
+    pub num_fallback_proposers: usize,
+}
+
+impl Default for ConsensusConfigV2 {
+    fn default() -> Self {
+        let v1 = ConsensusConfigV1::default();
+        Self {
+            decoupled_execution: v1.decoupled_execution,
+            back_pressure_limit: v1.back_pressure_limit,
+            exclude_round: v1.exclude_round,
+            proposer_election_type: v1.proposer_election_type,
+            max_failed_authors_to_store: v1.max_failed_authors_to_store,
+            leader_jail_config: None,
+            num_fallback_proposers: 0,
+        }
+    }
+}
+
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
 #[serde(rename_all = "snake_case")] // cannot use tag = "type" as nested enums cannot work, and bcs doesn't support it
 pub enum ProposerElectionType {
@@ -142,6 +195,21 @@ pub enum LeaderReputationType {
     // Proposer election based on whether nodes succeeded or failed
     // their proposer election rounds, and whether they voted.
     ProposerAndVoter(ProposerAndVoterConfig),
+    // Proposer election penalizing nodes for their failed proposer rounds,
+    // with the penalty decaying as the failures get older.
+    FailedProposalPenalty(FailedProposalPenaltyConfig),
+    // Proposer election based on how often nodes voted.
+    VoteParticipation(VoteParticipationConfig),
+    // Proposer election combining the weights of several of the above,
+    // each contributing in proportion to its weight.
+    Composite(Vec<WeightedLeaderReputationType>),
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct WeightedLeaderReputationType {
+    // Relative weight of the heuristic, i.e. 70 and 30 combine two heuristics as 70% and 30%
+    pub weight: u64,
+    pub heuristic: LeaderReputationType,
 }
 
 #[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
@@ -170,6 +238,51 @@ pub struct ProposerAndVoterConfig {
     pub voter_window_num_validators_multiplier: usize,
 }
 
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct FailedProposalPenaltyConfig {
+    // Selection weight for validators without failures in the window
+    pub active_weight: u64,
+    // Lower bound on the selection weight, however many failures a validator has
+    pub min_weight: u64,
+    // Penalty added for each recent failure, integer values representing percentages,
+    // i.e. 50 reduces the weight of a validator with a single fresh failure to 2/3.
+    pub failure_penalty_percent: u64,
+    // Number of rounds after which the penalty of a failure is halved
+    pub penalty_half_life_rounds: u64,
+    // Window into history considered for proposer statistics, multiplier
+    // on top of number of validators
+    pub window_num_validators_multiplier: usize,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct VoteParticipationConfig {
+    // Selection weight for validators that voted on every block in the window
+    pub max_weight: u64,
+    // Selection weight for validators that didn't vote on any block in the window
+    pub min_weight: u64,
+    // Window into history considered for vote statistics, multiplier
+    // on top of number of validators
+    pub window_num_validators_multiplier: usize,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct LeaderJailConfig {
+    // Number of consecutive rounds a validator has to fail as the proposer to get jailed,
+    // a successful proposal resets the count
+    pub consecutive_failures: u32,
+    // Number of rounds after the block recording its last failure a jailed validator can't be
+    // elected for. Failures are only seen exclude_round rounds later, so this needs to be larger
+    // than that.
+    pub jail_rounds: u64,
+}
+
+impl LeaderJailConfig {
+    /// A validator has to fail at least once to get jailed.
+    pub fn is_valid(&self) -> bool {
+        self.consecutive_failures > 0
+    }
+}
+
 #[cfg(test)]
 mod test {
     use std::sync::Arc;
@@ -212,6 +325,133 @@ mod test {
         ));
     }
 
+    #[test]
+    fn test_config_serialization_failed_proposal_penalty() {
+        let config = OnChainConsensusConfig::V1(ConsensusConfigV1 {
+            proposer_election_type: ProposerElectionType::LeaderReputation(
+                LeaderReputationType::FailedProposalPenalty(FailedProposalPenaltyConfig {
+                    active_weight: 1000,
+                    min_weight: 1,
+                    failure_penalty_percent: 50,
+                    penalty_half_life_rounds: 100,
+                    window_num_validators_multiplier: 10,
+                }),
+            ),
+            ..ConsensusConfigV1::default()
+        });
+
+        let s = bcs::to_bytes(&config).unwrap();
+        assert_eq!(
+            bcs::from_bytes::<OnChainConsensusConfig>(&s).unwrap(),
+            config
+        );
+    }
+
+    #[test]
+    fn test_config_serialization_composite() {
+        let config = OnChainConsensusConfig::V1(ConsensusConfigV1 {
+            proposer_election_type: ProposerElectionType::LeaderReputation(
+                LeaderReputationType::Composite(vec![
+                    WeightedLeaderReputationType {
+                        weight: 70,
+                        heuristic: LeaderReputationType::ActiveInactive(ActiveInactiveConfig {
+                            active_weight: 1000,
+                            inactive_weight: 10,
+                            window_num_validators_multiplier: 10,
+                        }),
+                    },
+                    WeightedLeaderReputationType {
+                        weight: 30,
+                        heuristic: LeaderReputationType::FailedProposalPenalty(
+                            FailedProposalPenaltyConfig {
+                                active_weight: 1000,
+                                min_weight: 1,
+                                failure_penalty_percent: 50,
+                                penalty_half_life_rounds: 100,
+                                window_num_validators_multiplier: 10,
+                            },
+                        ),
+                    },
+                ]),
+            ),
+            ..ConsensusConfigV1::default()
+        });
+
+        let s = serde_yaml::to_string(&config).unwrap();
+        assert_eq!(
+            serde_yaml::from_str::<OnChainConsensusConfig>(&s).unwrap(),
+            config
+        );
+        let s = bcs::to_bytes(&config).unwrap();
+        assert_eq!(
+            bcs::from_bytes::<OnChainConsensusConfig>(&s).unwrap(),
+            config
+        );
+    }
+
+    #[test]
+    fn test_config_v1_bcs_layout() {
+        // The V1 layout as it is on chain, which has to keep decoding
+        #[derive(Serialize)]
+        enum OnChainV1 {
+            V1 {
+                decoupled_execution: bool,
+                back_pressure_limit: u64,
+                exclude_round: u64,
+                proposer_election_type: ProposerElectionType,
+                max_failed_authors_to_store: usize,
+            },
+        }
+        let config = ConsensusConfigV1::default();
+        let s = bcs::to_bytes(&OnChainV1::V1 {
+            decoupled_execution: config.decoupled_execution,
+            back_pressure_limit: config.back_pressure_limit,
+            exclude_round: config.exclude_round,
+            proposer_election_type: config.proposer_election_type.clone(),
+            max_failed_authors_to_store: config.max_failed_authors_to_store,
+        })
+        .unwrap();
+        assert_eq!(
+            bcs::from_bytes::<OnChainConsensusConfig>(&s).unwrap(),
+            OnChainConsensusConfig::V1(config)
+        );
+    }
+
+    #[test]
+    fn test_leader_jail_config() {
+        let with_jail_config = |consecutive_failures| {
+            OnChainConsensusConfig::V2(ConsensusConfigV2 {
+                leader_jail_config: Some(LeaderJailConfig {
+                    consecutive_failures,
+                    jail_rounds: 100,
+                }),
+                ..ConsensusConfigV2::default()
+            })
+        };
+        assert!(with_jail_config(3).leader_jail_config().is_some());
+        assert!(with_jail_config(0).leader_jail_config().is_none());
+        assert!(OnChainConsensusConfig::default()
+            .leader_jail_config()
+            .is_none());
+    }
+
+    #[test]
+    fn test_config_v2_serialization() {
+        let config = OnChainConsensusConfig::V2(ConsensusConfigV2 {
+            num_fallback_proposers: 2,
+            ..ConsensusConfigV2::default()
+        });
+
+        let s = bcs::to_bytes(&config).unwrap();
+        let result = bcs::from_bytes::<OnChainConsensusConfig>(&s).unwrap();
+        assert_eq!(result, config);
+        assert_eq!(result.leader_reputation_num_fallback_proposers(), 2);
+        assert_eq!(
+            OnChainConsensusConfig::default().leader_reputation_num_fallback_proposers(),
+            0
+        );
+    }
+
     #[test]
     fn test_config_onchain_payload() {
         let consensus_config = OnChainConsensusConfig::V1(ConsensusConfigV1 {
@@ -28,7 +28,8 @@ pub use self::{
         Version, APTOS_MAX_KNOWN_VERSION, APTOS_VERSION_2, APTOS_VERSION_3, APTOS_VERSION_4,
     },
     consensus_config::{
-        ConsensusConfigV1, LeaderReputationType, OnChainConsensusConfig, ProposerElectionType,
+        ConsensusConfigV1, ConsensusConfigV2, LeaderJailConfig, LeaderReputationType,
+        OnChainConsensusConfig, ProposerElectionType,
     },
     registered_currencies::RegisteredCurrencies,
     validator_set::{ConsensusScheme, ValidatorSet},
@@ -1,7 +1,13 @@
 // Copyright (c) Aptos
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{proof::SparseMerkleRangeProof, state_store::state_key::StateKey};
+use crate::{
+    ledger_info::LedgerInfo,
+    proof::{SparseMerkleProof, SparseMerkleRangeProof, TransactionInfoWithProof},
+    state_store::state_key::StateKey,
+    transaction::Version,
+};
+use anyhow::{format_err, Result};
 use aptos_crypto::{
     hash::{CryptoHash, CryptoHasher, SPARSE_MERKLE_PLACEHOLDER_HASH},
     HashValue,
@@ -106,6 +112,51 @@ impl StateValueChunkWithProof {
     }
 }
 
+/// A state value (or its absence) at a specific version, along with the
+/// proofs needed to authenticate it against a `LedgerInfo`.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct StateValueWithProof {
+    /// The version the state value was read at.
+    pub version: Version,
+    /// The state value, `None` if the key does not exist at `version`.
+    pub value: Option<StateValue>,
+    /// The proof from the value to the state checkpoint hash committed in the
+    /// transaction info at `version`.
+    pub proof: SparseMerkleProof,
+    /// The proof from the transaction info at `version` to the ledger root.
+    pub transaction_info_with_proof: TransactionInfoWithProof,
+}
+
+impl StateValueWithProof {
+    pub fn new(
+        version: Version,
+        value: Option<StateValue>,
+        proof: SparseMerkleProof,
+        transaction_info_with_proof: TransactionInfoWithProof,
+    ) -> Self {
+        Self {
+            version,
+            value,
+            proof,
+            transaction_info_with_proof,
+        }
+    }
+
+    /// Verifies that the value of `state_key` at `version` is `value` in the
+    /// ledger represented by `ledger_info`.
+    pub fn verify(&self, ledger_info: &LedgerInfo, state_key: &StateKey) -> Result<()> {
+        self.transaction_info_with_proof
+            .verify(ledger_info, self.version)?;
+        let state_root_hash = self
+            .transaction_info_with_proof
+            .transaction_info()
+            .state_checkpoint_hash()
+            .ok_or_else(|| format_err!("Version {} is not a state checkpoint.", self.version))?;
+        self.proof
+            .verify(state_root_hash, state_key.hash(), self.value.as_ref())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::state_store::state_value::StateValue;